@@ -0,0 +1,71 @@
+//! Benchmarks of parsing and of looking records up by xref, run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gedcom::{store::IndexedGedcomData, GedcomData, GedcomDocument, ParseOptions};
+
+const WASHINGTON: &str = include_str!("../tests/fixtures/washington.ged");
+const ALLGED: &str = include_str!("../tests/fixtures/allged.ged");
+
+fn parse(contents: &str, options: ParseOptions) -> GedcomData {
+    GedcomDocument::with_options(contents.chars(), options)
+        .parse_document()
+        .unwrap()
+}
+
+fn parsing(c: &mut Criterion) {
+    c.bench_function("parse washington", |b| {
+        b.iter(|| parse(black_box(WASHINGTON), ParseOptions::default()))
+    });
+    c.bench_function("parse allged", |b| {
+        b.iter(|| parse(black_box(ALLGED), ParseOptions::default()))
+    });
+    let interned = ParseOptions {
+        intern_strings: true,
+        ..ParseOptions::default()
+    };
+    c.bench_function("parse washington interned", |b| {
+        b.iter(|| parse(black_box(WASHINGTON), interned))
+    });
+}
+
+/// Resolves the spouses and children of every family, the way code walking a tree does
+fn lookups(c: &mut Criterion) {
+    let data = parse(WASHINGTON, ParseOptions::default());
+    let pointers: Vec<String> = data
+        .families
+        .iter()
+        .flat_map(|fam| {
+            fam.individual1
+                .iter()
+                .chain(&fam.individual2)
+                .chain(&fam.children)
+                .cloned()
+        })
+        .collect();
+
+    c.bench_function("find individuals in GedcomData", |b| {
+        b.iter(|| {
+            pointers
+                .iter()
+                .filter(|xref| {
+                    data.individuals
+                        .iter()
+                        .any(|indi| indi.xref.as_ref() == Some(*xref))
+                })
+                .count()
+        })
+    });
+
+    let tree = IndexedGedcomData::from(parse(WASHINGTON, ParseOptions::default()));
+    c.bench_function("find individuals in IndexedGedcomData", |b| {
+        b.iter(|| {
+            pointers
+                .iter()
+                .filter(|xref| tree.find_individual(xref).is_some())
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, parsing, lookups);
+criterion_main!(benches);
@@ -0,0 +1,522 @@
+//! Frequencies of the surnames and given names of a tree, for one-name studies and the like, and
+//! the sourcing of its facts, for research-quality dashboards.
+//!
+//! `name_frequencies` counts each surname and each word of the given names once per individual.
+//! With `NameOptions`, spellings can be counted together when they only differ by case or by
+//! diacritics, e.g. "Ødegård" and "Odegard", and surnames when they are patronymic variants of
+//! the same name, e.g. "Olsen", "Olsson" and "Olsdatter". Each group is then shown under its most
+//! common spelling, with the others listed as variants.
+//!
+//! Tables are sorted by count, most common first, and then alphabetically, ignoring case and
+//! diacritics so that "Ødegård" sorts among the O's.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{analysis::{self, NameOptions}, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME Ole /Olsen/\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Kari Ann /Olsdatter/\n\
+//!     0 @I3@ INDI\n\
+//!     1 NAME Ann /Olsen/\n\
+//!     0 @I4@ INDI\n\
+//!     1 NAME Åse /Berg/\n\
+//!     0 @I5@ INDI\n\
+//!     1 NAME Ase /Berg/\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//!
+//! let frequencies = analysis::name_frequencies(&data);
+//! assert_eq!(frequencies.surnames[0].name, "Berg");
+//! assert_eq!(frequencies.surnames[0].count, 2);
+//! assert_eq!(frequencies.given_names[0].name, "Ann");
+//!
+//! let options = NameOptions {
+//!     strip_diacritics: true,
+//!     group_patronymics: true,
+//!     ..NameOptions::default()
+//! };
+//! let grouped = analysis::name_frequencies_with(&data, options);
+//! let olsen = &grouped.surnames[0];
+//! assert_eq!((olsen.name.as_str(), olsen.count), ("Olsen", 3));
+//! assert_eq!(olsen.variants, vec!["Olsdatter"]);
+//! let ase = &grouped.given_names[1];
+//! assert_eq!((ase.name.as_str(), ase.count), ("Ase", 2));
+//! assert_eq!(ase.variants, vec!["Åse"]);
+//! ```
+//!
+//! `sourcing` assesses how well the births, deaths and marriages of each individual are supported
+//! by the citations of their events. A fact with no citation is `Confidence::Unsourced`, one with
+//! a citation assessed as direct and primary evidence (`QUAY 3`) is `Confidence::Primary`, and
+//! any other cited fact, including one whose citations have no QUAY, is `Confidence::Secondary`.
+//! Facts missing from the tree aren't assessed. The `SourcingReport` lists the facts of each
+//! individual, a marriage being listed for both spouses, along with totals for the tree, where
+//! each marriage is counted once.
+//!
+//! ```
+//! use gedcom::{analysis::{self, Confidence, Fact}, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 BIRT\n\
+//!     2 DATE 1820\n\
+//!     2 SOUR @S1@\n\
+//!     3 QUAY 3\n\
+//!     1 DEAT\n\
+//!     2 DATE 1881\n\
+//!     2 SOUR @S2@\n\
+//!     0 @I2@ INDI\n\
+//!     1 BIRT\n\
+//!     2 DATE 1824\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @I1@\n\
+//!     1 WIFE @I2@\n\
+//!     1 MARR\n\
+//!     2 SOUR @S1@\n\
+//!     3 QUAY 1\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let report = analysis::sourcing(&data);
+//!
+//! let john = report.person("@I1@").unwrap();
+//! let confidences: Vec<_> = john.facts.iter().map(|fact| (fact.fact, fact.confidence)).collect();
+//! assert_eq!(
+//!     confidences,
+//!     vec![
+//!         (Fact::Birth, Confidence::Primary),
+//!         (Fact::Death, Confidence::Secondary),
+//!         (Fact::Marriage, Confidence::Secondary),
+//!     ]
+//! );
+//! assert_eq!(john.facts[2].family.as_deref(), Some("@F1@"));
+//! assert_eq!(john.facts[2].best_quality, Some(1));
+//! assert_eq!(report.person("@I2@").unwrap().weakest(), Some(Confidence::Unsourced));
+//!
+//! assert_eq!(report.births.unsourced, 1);
+//! assert_eq!(report.births.primary, 1);
+//! assert_eq!(report.counts(Fact::Marriage).total(), 1);
+//! ```
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use crate::{
+    types::{Event, EventDetail},
+    GedcomData,
+};
+
+/// `NameOptions` picks which spellings of a name are counted together, see the `analysis` module.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct NameOptions {
+    /// Counts names differing only by case together, e.g. "OLSEN" and "Olsen"
+    pub fold_case: bool,
+    /// Counts names differing only by diacritics together, e.g. "Ødegård" and "Odegard"
+    pub strip_diacritics: bool,
+    /// Counts surnames built from the same name with a patronymic suffix together, e.g.
+    /// "Olsen", "Olson", "Olsson" and "Olsdatter". Case and diacritics are ignored to compare
+    /// them.
+    pub group_patronymics: bool,
+}
+
+/// `NameCount` is a row of a frequency table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameCount {
+    /// The most common spelling of the name
+    pub name: String,
+    /// The number of individuals bearing the name, in any spelling
+    pub count: usize,
+    /// The other spellings counted along with it, most common first
+    pub variants: Vec<String>,
+}
+
+/// `NameFrequencies` holds the surname and given name frequency tables of a tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NameFrequencies {
+    /// The surnames, most common first
+    pub surnames: Vec<NameCount>,
+    /// The words of the given names, most common first
+    pub given_names: Vec<NameCount>,
+}
+
+/// Counts the surnames and given names of the individuals of the tree, spelled exactly as they
+/// are, see the `analysis` module
+#[must_use]
+pub fn name_frequencies(data: &GedcomData) -> NameFrequencies {
+    name_frequencies_with(data, NameOptions::default())
+}
+
+/// Counts the surnames and given names of the individuals of the tree, the spellings `options`
+/// picks counted together
+#[must_use]
+pub fn name_frequencies_with(data: &GedcomData, options: NameOptions) -> NameFrequencies {
+    let mut surnames = Tally::default();
+    let mut given_names = Tally::default();
+    for name in data
+        .individuals
+        .iter()
+        .filter_map(|indi| indi.name.as_ref())
+    {
+        if let Some(surname) = name.family_name() {
+            surnames.add(normalize(surname, options, true), surname);
+        }
+        let words: HashSet<&str> = name
+            .given_name()
+            .into_iter()
+            .flat_map(str::split_whitespace)
+            .collect();
+        for word in words {
+            given_names.add(normalize(word, options, false), word);
+        }
+    }
+    NameFrequencies {
+        surnames: surnames.into_table(),
+        given_names: given_names.into_table(),
+    }
+}
+
+/// `Fact` is a fact of an individual whose sourcing `sourcing` assesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Fact {
+    /// The BIRT events of the individual
+    Birth,
+    /// The DEAT events of the individual
+    Death,
+    /// The MARR events of the families the individual is a spouse in
+    Marriage,
+}
+
+impl Fact {
+    /// The event the fact is read from
+    fn matches(self, event: &Event) -> bool {
+        matches!(
+            (self, event),
+            (Fact::Birth, Event::Birth)
+                | (Fact::Death, Event::Death)
+                | (Fact::Marriage, Event::Marriage)
+        )
+    }
+}
+
+/// `Confidence` summarizes the citations of a fact, from least to most supported, see the
+/// `analysis` module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    /// No citation supports the fact
+    Unsourced,
+    /// The fact is cited, but not by direct and primary evidence
+    Secondary,
+    /// A citation of the fact is assessed as direct and primary evidence, `QUAY 3`
+    Primary,
+}
+
+/// `FactSourcing` is the sourcing of a single event of an individual.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FactSourcing {
+    /// The fact the event records
+    pub fact: Fact,
+    /// The xref of the family, for a marriage
+    pub family: Option<String>,
+    /// The number of citations of the event
+    pub citations: usize,
+    /// The best QUAY among the citations, from 0 to 3, or `None` when none has one
+    pub best_quality: Option<u8>,
+    /// The summary of the citations
+    pub confidence: Confidence,
+}
+
+impl FactSourcing {
+    fn new(fact: Fact, family: Option<&str>, event: &EventDetail) -> FactSourcing {
+        let best_quality = event
+            .citations
+            .iter()
+            .filter_map(|citation| citation.certainty_assessment.as_ref()?.get_int())
+            .max();
+        let confidence = match (event.citations.is_empty(), best_quality) {
+            (true, _) => Confidence::Unsourced,
+            (false, Some(3)) => Confidence::Primary,
+            (false, _) => Confidence::Secondary,
+        };
+        FactSourcing {
+            fact,
+            family: family.map(str::to_string),
+            citations: event.citations.len(),
+            best_quality,
+            confidence,
+        }
+    }
+}
+
+/// `PersonSourcing` is the sourcing of the facts of an individual.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PersonSourcing {
+    /// The xref of the individual
+    pub xref: String,
+    /// The births, deaths and marriages of the individual, in that order
+    pub facts: Vec<FactSourcing>,
+}
+
+impl PersonSourcing {
+    /// The least confidence among the facts of the individual, or `None` when it has none
+    #[must_use]
+    pub fn weakest(&self) -> Option<Confidence> {
+        self.facts.iter().map(|fact| fact.confidence).min()
+    }
+}
+
+/// `ConfidenceCounts` counts the facts of a tree by `Confidence`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfidenceCounts {
+    /// The facts without citations
+    pub unsourced: usize,
+    /// The facts cited, but not by direct and primary evidence
+    pub secondary: usize,
+    /// The facts cited by direct and primary evidence
+    pub primary: usize,
+}
+
+impl ConfidenceCounts {
+    /// The number of facts counted
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.unsourced + self.secondary + self.primary
+    }
+
+    fn add(&mut self, confidence: Confidence) {
+        *match confidence {
+            Confidence::Unsourced => &mut self.unsourced,
+            Confidence::Secondary => &mut self.secondary,
+            Confidence::Primary => &mut self.primary,
+        } += 1;
+    }
+}
+
+/// `SourcingReport` holds the sourcing of the facts of each individual of a tree, and the totals
+/// for the tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourcingReport {
+    /// The individuals with an xref, in file order
+    pub individuals: Vec<PersonSourcing>,
+    /// The births of the tree
+    pub births: ConfidenceCounts,
+    /// The deaths of the tree
+    pub deaths: ConfidenceCounts,
+    /// The marriages of the tree, each counted once
+    pub marriages: ConfidenceCounts,
+}
+
+impl SourcingReport {
+    /// The sourcing of the individual with the xref
+    #[must_use]
+    pub fn person(&self, xref: &str) -> Option<&PersonSourcing> {
+        self.individuals.iter().find(|person| person.xref == xref)
+    }
+
+    /// The totals for the tree of a kind of fact
+    #[must_use]
+    pub fn counts(&self, fact: Fact) -> &ConfidenceCounts {
+        match fact {
+            Fact::Birth => &self.births,
+            Fact::Death => &self.deaths,
+            Fact::Marriage => &self.marriages,
+        }
+    }
+
+    fn counts_mut(&mut self, fact: Fact) -> &mut ConfidenceCounts {
+        match fact {
+            Fact::Birth => &mut self.births,
+            Fact::Death => &mut self.deaths,
+            Fact::Marriage => &mut self.marriages,
+        }
+    }
+}
+
+/// Assesses how well the births, deaths and marriages of the individuals of the tree are sourced,
+/// see the `analysis` module
+#[must_use]
+pub fn sourcing(data: &GedcomData) -> SourcingReport {
+    let mut report = SourcingReport::default();
+    let mut marriages: HashMap<&str, Vec<FactSourcing>> = HashMap::new();
+    for fam in &data.families {
+        let events = fam.events.iter().chain(&fam.family_event);
+        let facts: Vec<FactSourcing> = events
+            .filter(|event| Fact::Marriage.matches(&event.event))
+            .map(|event| FactSourcing::new(Fact::Marriage, fam.xref.as_deref(), event))
+            .collect();
+        for fact in &facts {
+            report.marriages.add(fact.confidence);
+        }
+        for spouse in fam.individual1.iter().chain(&fam.individual2) {
+            marriages
+                .entry(spouse)
+                .or_default()
+                .extend(facts.iter().cloned());
+        }
+    }
+
+    for indi in &data.individuals {
+        let mut facts = Vec::new();
+        for fact in [Fact::Birth, Fact::Death] {
+            let events = indi
+                .events
+                .iter()
+                .filter(|event| fact.matches(&event.event));
+            for event in events {
+                let sourcing = FactSourcing::new(fact, None, event);
+                report.counts_mut(fact).add(sourcing.confidence);
+                facts.push(sourcing);
+            }
+        }
+        let Some(xref) = indi.xref.as_deref() else {
+            continue;
+        };
+        facts.extend(marriages.remove(xref).unwrap_or_default());
+        report.individuals.push(PersonSourcing {
+            xref: xref.to_string(),
+            facts,
+        });
+    }
+    report
+}
+
+/// The spellings seen for each normalized name, with their counts
+#[derive(Default)]
+struct Tally<'a> {
+    groups: HashMap<String, BTreeMap<&'a str, usize>>,
+}
+
+impl<'a> Tally<'a> {
+    fn add(&mut self, key: String, spelling: &'a str) {
+        *self
+            .groups
+            .entry(key)
+            .or_default()
+            .entry(spelling)
+            .or_insert(0) += 1;
+    }
+
+    fn into_table(self) -> Vec<NameCount> {
+        let mut table: Vec<NameCount> = self
+            .groups
+            .into_values()
+            .map(|spellings| {
+                let count = spellings.values().sum();
+                let mut spellings: Vec<(&str, usize)> = spellings.into_iter().collect();
+                // the BTreeMap order is kept among spellings seen as often
+                spellings.sort_by_key(|(_, count)| Reverse(*count));
+                let mut spellings = spellings
+                    .into_iter()
+                    .map(|(spelling, _)| spelling.to_string());
+                NameCount {
+                    name: spellings.next().unwrap_or_default(),
+                    count,
+                    variants: spellings.collect(),
+                }
+            })
+            .collect();
+        table.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| collate(&a.name, &b.name))
+        });
+        table
+    }
+}
+
+/// Orders names alphabetically, ignoring case and diacritics, and then by their exact spelling
+fn collate(a: &str, b: &str) -> Ordering {
+    let key = |name: &str| strip_diacritics(name).to_lowercase();
+    key(a).cmp(&key(b)).then_with(|| a.cmp(b))
+}
+
+/// The key names counted together share
+fn normalize(name: &str, options: NameOptions, surname: bool) -> String {
+    if surname && options.group_patronymics {
+        let name = strip_diacritics(name).to_lowercase();
+        return patronymic_root(&name).to_string();
+    }
+    let mut name = if options.strip_diacritics {
+        strip_diacritics(name)
+    } else {
+        name.to_string()
+    };
+    if options.fold_case {
+        name = name.to_lowercase();
+    }
+    name
+}
+
+/// Patronymic suffixes, longest first, in lowercase and without diacritics
+const PATRONYMIC_SUFFIXES: [&str; 9] = [
+    "sdotter", "sdatter", "sdottir", "dotter", "datter", "dottir", "sson", "sen", "son",
+];
+
+/// The name a patronymic was formed from, without its suffix, e.g. "ol" for "olsson"
+fn patronymic_root(name: &str) -> &str {
+    PATRONYMIC_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        // "son" alone, or "sen", isn't a patronymic
+        .filter(|root| root.chars().count() >= 2)
+        .unwrap_or(name)
+}
+
+/// The name with the diacritics of Latin letters taken off, e.g. "Odegard" for "Ødegård".
+/// Letters without an accented form, such as "ß", are spelled out in plain letters.
+fn strip_diacritics(name: &str) -> String {
+    let mut plain = String::with_capacity(name.len());
+    for c in name.chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        match plain_letters(lower) {
+            Some(letters) if c.is_uppercase() => {
+                let mut letters = letters.chars();
+                plain.extend(letters.next().map(|first| first.to_ascii_uppercase()));
+                plain.extend(letters);
+            }
+            Some(letters) => plain.push_str(letters),
+            None => plain.push(c),
+        }
+    }
+    plain
+}
+
+/// The plain letters for a lowercase Latin letter with a diacritic
+fn plain_letters(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
@@ -4,7 +4,7 @@ use std::env;
 use std::fs;
 use std::path::PathBuf;
 
-use gedcom::{GedcomData, GedcomDocument};
+use gedcom::{input, GedcomData, GedcomDocument};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -24,11 +24,14 @@ fn main() {
 
     if let Ok(contents) = read_relative(filename) {
         let mut doc = GedcomDocument::new(contents.chars());
-        data = doc.parse_document();
+        match doc.parse_document() {
+            Ok(parsed) => data = parsed,
+            Err(err) => return exit_with_error(&err.to_string()),
+        }
 
         println!("Parsing complete!");
         // println!("\n\n{:#?}", data);
-        data.stats();
+        println!("{}", data.stats());
     } else {
         exit_with_error(&format!("File '{}' not found.", filename));
     }
@@ -37,7 +40,7 @@ fn main() {
 fn read_relative(path: &str) -> Result<String, std::io::Error> {
     let path_buf: PathBuf = PathBuf::from(path);
     let absolute_path: PathBuf = fs::canonicalize(path_buf)?;
-    fs::read_to_string(absolute_path)
+    input::read_file(absolute_path)
 }
 
 fn usage(msg: &str) {
@@ -0,0 +1,155 @@
+//! Command line tool for inspecting and converting GEDCOM files, built with the `cli` feature.
+//!
+//! Files are parsed in lenient mode, with the problems skipped printed to stderr, so that files
+//! from applications bending the specification can still be inspected.
+
+use std::{env, process};
+
+use gedcom::{
+    input, inspect, lint,
+    search::SearchMode,
+    validate::{self, Issue},
+    GedcomData, GedcomDocument, ParseOptions,
+};
+
+const USAGE: &str = "\
+Usage: gedcom <command> <file> [argument]
+
+Commands:
+  stats <file>           Counts the records, events and surnames of the file
+  validate <file>        Lists broken and mistyped pointers, and duplicate xrefs
+  lint <file>            Lists the lines breaking the style and conformance rules
+  query <file> <xref>    Prints the record with the xref, as GEDCOM
+  grep <file> <name>     Lists the individuals with a name sounding like <name>
+  unused <file>          Lists the sources, notes, media and submitters nothing points to
+  tags <file>            Counts the lines of the file by tag path, e.g. INDI.BIRT.DATE";
+
+/// The commands only built with the json feature
+#[cfg(feature = "json")]
+const JSON_USAGE: &str = "
+  to-json <file>         Prints the parsed tree as JSON";
+#[cfg(not(feature = "json"))]
+const JSON_USAGE: &str = "";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{USAGE}{JSON_USAGE}");
+        return;
+    }
+    let (command, path, argument) = match args.as_slice() {
+        [command, path] => (command.as_str(), path, None),
+        [command, path, argument] => (command.as_str(), path, Some(argument.as_str())),
+        _ => usage("Expected a command and a file."),
+    };
+    let contents = input::read_file(path)
+        .unwrap_or_else(|err| exit_with_error(&format!("Can't read '{path}': {err}")));
+
+    match (command, argument) {
+        ("stats", None) => println!("{}", parse(&contents).stats()),
+        ("validate", None) => validate(&contents),
+        ("lint", None) => lint(&contents),
+        #[cfg(feature = "json")]
+        ("to-json", None) => to_json(&parse(&contents)),
+        ("query", Some(xref)) => query(&parse(&contents), xref),
+        ("grep", Some(name)) => grep(&parse(&contents), name),
+        ("unused", None) => {
+            for xref in parse(&contents).usage_counts().unused() {
+                println!("{xref}");
+            }
+        }
+        ("tags", None) => match inspect::tag_profile(contents.as_bytes()) {
+            Ok(profile) => print!("{profile}"),
+            Err(err) => exit_with_error(&err.to_string()),
+        },
+        ("stats" | "validate" | "lint" | "unused" | "tags", Some(_)) => {
+            usage("Found more args than expected.")
+        }
+        #[cfg(feature = "json")]
+        ("to-json", Some(_)) => usage("Found more args than expected."),
+        ("query" | "grep", None) => usage(&format!("Missing the argument of {command}.")),
+        _ => usage(&format!("Unknown command '{command}'.")),
+    }
+}
+
+fn parse(contents: &str) -> GedcomData {
+    let mut doc = GedcomDocument::with_options(contents.chars(), ParseOptions::lenient());
+    let data = doc
+        .parse_document()
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    for warning in doc.warnings() {
+        eprintln!("warning: {warning}");
+    }
+    data
+}
+
+/// Exits with status 1 when issues are found
+fn validate(contents: &str) {
+    let issues: Vec<Issue> = validate::validate_stream(contents, ParseOptions::lenient())
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    for issue in &issues {
+        println!("{issue}");
+    }
+    if !issues.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// Exits with status 1 when lints are found
+fn lint(contents: &str) {
+    let lints = lint::lint(contents);
+    for lint in &lints {
+        println!("{lint}");
+    }
+    if !lints.is_empty() {
+        process::exit(1);
+    }
+}
+
+#[cfg(feature = "json")]
+fn to_json(data: &GedcomData) {
+    match serde_json::to_string_pretty(data) {
+        Ok(json) => println!("{json}"),
+        Err(err) => exit_with_error(&err.to_string()),
+    }
+}
+
+/// Prints the lines of the top-level record defining `xref`
+fn query(data: &GedcomData, xref: &str) {
+    let text = data.to_gedcom_string();
+    let mut record = text
+        .lines()
+        .skip_while(|line| line.split(' ').nth(1) != Some(xref))
+        .enumerate()
+        .take_while(|(index, line)| *index == 0 || !line.starts_with("0 "))
+        .map(|(_, line)| line)
+        .peekable();
+    if record.peek().is_none() {
+        exit_with_error(&format!("No record has the xref {xref}."));
+    }
+    for line in record {
+        println!("{line}");
+    }
+}
+
+fn grep(data: &GedcomData, name: &str) {
+    for result in data.search_individuals(name, SearchMode::Soundex) {
+        let name = data
+            .individuals
+            .iter()
+            .find(|indi| indi.xref.as_deref() == Some(result.xref.as_str()))
+            .and_then(|indi| indi.name.as_ref()?.value.as_deref())
+            .unwrap_or_default();
+        println!("{}\t{:.2}\t{name}", result.xref, result.score);
+    }
+}
+
+fn usage(msg: &str) -> ! {
+    eprintln!("{msg}\n{USAGE}{JSON_USAGE}");
+    process::exit(2);
+}
+
+fn exit_with_error(msg: &str) -> ! {
+    eprintln!("Error! {msg}");
+    process::exit(1);
+}
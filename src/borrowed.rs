@@ -0,0 +1,955 @@
+//! A read-only GEDCOM model borrowing all of its strings from the input buffer.
+//!
+//! `GedcomRef` trades the completeness and mutability of `GedcomData` for speed: nothing is
+//! copied out of the file contents, so workloads which parse, compute and drop (statistics,
+//! searches, exports) avoid allocating a `String` for every value in the file. Text spread over
+//! CONT/CONC lines is the only exception, as it has to be joined into a new string.
+//!
+//! `GedcomRef::to_owned` promotes a borrowed parse to `GedcomData` once editing is needed, and
+//! `GedcomData::as_ref_model` goes the other way. Both only carry over the values the borrowed
+//! model represents.
+//!
+//! As in `GedcomData`, a tag which is present without a payload is read as `Some("")`. Values
+//! are borrowed as they are written, so an at-sign escaped as `@@` reads as two, until the
+//! values are copied out with `to_owned`.
+
+use std::borrow::Cow;
+
+use crate::{
+    intern::SharedStr,
+    types::{
+        AttributeDetail, ContactInfo, Date, Encoding, Event, EventDetail, Family, FamilyLink,
+        FamilyLinkType, GedcomMeta, Gender, GenderType, HeadSour, Header, Individual,
+        MultimediaFileRefn, MultimediaFormat, MultimediaRecord, Name, RepoCitation, Repository,
+        Source, SourceCitation, Submitter,
+    },
+    util::{split_lines, unescape_at_signs},
+    GedcomData,
+};
+
+/// `GedcomRef` is the borrowed counterpart of `GedcomData`, see the module documentation.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomRef;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     2 GIVN John\n\
+///     2 SURN Doe\n\
+///     1 SEX M\n\
+///     1 BIRT\n\
+///     2 DATE 1 JAN 1900\n\
+///     2 PLAC Springfield\n\
+///     1 FAMS @F1@\n\
+///     0 @F1@ FAM\n\
+///     1 HUSB @I1@\n\
+///     0 @S1@ SOUR\n\
+///     1 TITL Parish regis\n\
+///     2 CONC ter\n\
+///     0 TRLR";
+///
+/// let data = GedcomRef::parse(sample);
+///
+/// assert_eq!(data.header.as_ref().unwrap().version, Some("5.5"));
+///
+/// let indi = &data.individuals[0];
+/// assert_eq!(indi.xref, Some("@I1@"));
+/// assert_eq!(indi.name.as_ref().unwrap().surname, Some("Doe"));
+/// assert_eq!(indi.sex, Some("M"));
+/// assert_eq!(indi.events[0].tag, "BIRT");
+/// assert_eq!(indi.events[0].date, Some("1 JAN 1900"));
+/// assert_eq!(indi.families[0].xref, "@F1@");
+///
+/// assert_eq!(data.families[0].individual1, Some("@I1@"));
+/// assert_eq!(data.sources[0].title.as_deref(), Some("Parish register"));
+/// ```
+#[derive(Debug, Default)]
+pub struct GedcomRef<'a> {
+    /// Header containing file metadata
+    pub header: Option<HeaderRef<'a>>,
+    /// List of submitters of the facts
+    pub submitters: Vec<SubmitterRef<'a>>,
+    /// Individuals within the family tree
+    pub individuals: Vec<IndividualRef<'a>>,
+    /// The family units of the tree, representing relationships between individuals
+    pub families: Vec<FamilyRef<'a>>,
+    /// A data repository where `sources` are held
+    pub repositories: Vec<RepositoryRef<'a>>,
+    /// Sources of facts. _ie._ book, document, census, etc.
+    pub sources: Vec<SourceRef<'a>>,
+    /// A multimedia asset linked to a fact
+    pub multimedia: Vec<MultimediaRef<'a>>,
+}
+
+impl<'a> GedcomRef<'a> {
+    /// Parses the GEDCOM file contents. Parsing is lenient: lines which can't be read are skipped,
+    /// as are structures the borrowed model doesn't represent.
+    #[must_use]
+    pub fn parse(input: &'a str) -> GedcomRef<'a> {
+        let mut data = GedcomRef::default();
+
+        for record in Node::parse_records(input) {
+            let xref = record.line.xref;
+            match record.line.tag {
+                "HEAD" => data.header = Some(HeaderRef::from_node(&record)),
+                "INDI" => data
+                    .individuals
+                    .push(IndividualRef::from_node(xref, &record)),
+                "FAM" => data.families.push(FamilyRef::from_node(xref, &record)),
+                "SOUR" => data.sources.push(SourceRef::from_node(xref, &record)),
+                "REPO" => data.repositories.push(RepositoryRef {
+                    xref,
+                    name: record.child_value("NAME"),
+                }),
+                "SUBM" => data.submitters.push(SubmitterRef {
+                    xref,
+                    name: record.child_value("NAME"),
+                }),
+                "OBJE" => data.multimedia.push(MultimediaRef::from_node(xref, &record)),
+                _ => (),
+            }
+        }
+
+        data
+    }
+}
+
+/// Borrowed counterpart of `Header`, the HEAD record.
+#[derive(Debug, Default)]
+pub struct HeaderRef<'a> {
+    /// tag: GEDC.VERS
+    pub version: Option<&'a str>,
+    /// tag: CHAR
+    pub encoding: Option<&'a str>,
+    /// tag: SOUR
+    pub source: Option<&'a str>,
+    /// tag: DATE
+    pub date: Option<&'a str>,
+    /// tag: SUBM
+    pub submitter_tag: Option<&'a str>,
+    /// tag: LANG
+    pub language: Option<&'a str>,
+    /// tag: FILE
+    pub filename: Option<&'a str>,
+}
+
+impl<'a> HeaderRef<'a> {
+    fn from_node(node: &Node<'a>) -> HeaderRef<'a> {
+        HeaderRef {
+            version: node.child("GEDC").and_then(|gedc| gedc.child_value("VERS")),
+            encoding: node.child_value("CHAR"),
+            source: node.child_value("SOUR"),
+            date: node.child_value("DATE"),
+            submitter_tag: node.child_value("SUBM"),
+            language: node.child_value("LANG"),
+            filename: node.child_value("FILE"),
+        }
+    }
+}
+
+/// Borrowed counterpart of `Individual`, the INDI record.
+#[derive(Debug, Default)]
+pub struct IndividualRef<'a> {
+    /// Optional cross-reference identifier of the record
+    pub xref: Option<&'a str>,
+    /// tag: NAME, the first name of the individual
+    pub name: Option<NameRef<'a>>,
+    /// tag: SEX, the raw payload, e.g. "M"
+    pub sex: Option<&'a str>,
+    /// tag: FAMC/FAMS
+    pub families: Vec<FamilyLinkRef<'a>>,
+    /// Individual events, e.g. BIRT
+    pub events: Vec<EventRef<'a>>,
+    /// Individual attributes, e.g. OCCU
+    pub attributes: Vec<EventRef<'a>>,
+    /// xrefs of the sources cited by the record
+    pub sources: Vec<&'a str>,
+}
+
+impl<'a> IndividualRef<'a> {
+    fn from_node(xref: Option<&'a str>, node: &Node<'a>) -> IndividualRef<'a> {
+        let mut indi = IndividualRef {
+            xref,
+            ..IndividualRef::default()
+        };
+
+        for child in &node.children {
+            let tag = child.line.tag;
+            match tag {
+                "NAME" if indi.name.is_none() => indi.name = Some(NameRef::from_node(child)),
+                "SEX" => indi.sex = Some(child.line.value.unwrap_or("")),
+                "FAMC" | "FAMS" => {
+                    if let Some(xref) = child.line.value {
+                        indi.families.push(FamilyLinkRef { xref, tag });
+                    }
+                }
+                "SOUR" => indi.sources.extend(child.line.value),
+                _ if INDIVIDUAL_EVENT_TAGS.contains(&tag) => {
+                    indi.events.push(EventRef::from_node(child));
+                }
+                _ if INDIVIDUAL_ATTRIBUTE_TAGS.contains(&tag) => {
+                    indi.attributes.push(EventRef::from_node(child));
+                }
+                _ => (),
+            }
+        }
+        indi
+    }
+}
+
+/// Borrowed counterpart of `Name`.
+#[derive(Debug, Default)]
+pub struct NameRef<'a> {
+    /// The line payload
+    pub value: Option<&'a str>,
+    /// tag: GIVN
+    pub given: Option<&'a str>,
+    /// tag: SURN
+    pub surname: Option<&'a str>,
+    /// tag: NPFX
+    pub prefix: Option<&'a str>,
+    /// tag: SPFX
+    pub surname_prefix: Option<&'a str>,
+    /// tag: NSFX
+    pub suffix: Option<&'a str>,
+}
+
+impl<'a> NameRef<'a> {
+    fn from_node(node: &Node<'a>) -> NameRef<'a> {
+        NameRef {
+            value: node.line.value,
+            given: node.child_value("GIVN"),
+            surname: node.child_value("SURN"),
+            prefix: node.child_value("NPFX"),
+            surname_prefix: node.child_value("SPFX"),
+            suffix: node.child_value("NSFX"),
+        }
+    }
+}
+
+/// Borrowed counterpart of `FamilyLink`.
+#[derive(Debug)]
+pub struct FamilyLinkRef<'a> {
+    /// Reference to the `FamilyRef`
+    pub xref: &'a str,
+    /// FAMC for a family where the individual is a child, FAMS where they are a spouse
+    pub tag: &'a str,
+}
+
+/// Borrowed counterpart of `EventDetail` and `AttributeDetail`.
+#[derive(Debug)]
+pub struct EventRef<'a> {
+    /// The event or attribute tag, e.g. BIRT
+    pub tag: &'a str,
+    /// The line payload
+    pub value: Option<&'a str>,
+    /// tag: DATE
+    pub date: Option<&'a str>,
+    /// tag: PLAC
+    pub place: Option<&'a str>,
+    /// tag: TYPE
+    pub event_type: Option<&'a str>,
+}
+
+impl<'a> EventRef<'a> {
+    fn from_node(node: &Node<'a>) -> EventRef<'a> {
+        EventRef {
+            tag: node.line.tag,
+            value: node.line.value,
+            date: node.child_value("DATE"),
+            place: node.child_value("PLAC"),
+            event_type: node.child_value("TYPE"),
+        }
+    }
+}
+
+/// Borrowed counterpart of `Family`, the FAM record.
+#[derive(Debug, Default)]
+pub struct FamilyRef<'a> {
+    /// Optional cross-reference identifier of the record
+    pub xref: Option<&'a str>,
+    /// mapped from HUSB
+    pub individual1: Option<&'a str>,
+    /// mapped from WIFE
+    pub individual2: Option<&'a str>,
+    /// tag: CHIL
+    pub children: Vec<&'a str>,
+    /// Family events, e.g. MARR
+    pub events: Vec<EventRef<'a>>,
+}
+
+impl<'a> FamilyRef<'a> {
+    fn from_node(xref: Option<&'a str>, node: &Node<'a>) -> FamilyRef<'a> {
+        let mut fam = FamilyRef {
+            xref,
+            ..FamilyRef::default()
+        };
+
+        for child in &node.children {
+            match child.line.tag {
+                "HUSB" => fam.individual1 = child.line.value,
+                "WIFE" => fam.individual2 = child.line.value,
+                "CHIL" => fam.children.extend(child.line.value),
+                tag if FAMILY_EVENT_TAGS.contains(&tag) => {
+                    fam.events.push(EventRef::from_node(child));
+                }
+                _ => (),
+            }
+        }
+        fam
+    }
+}
+
+/// Borrowed counterpart of `Source`, the SOUR record.
+#[derive(Debug, Default)]
+pub struct SourceRef<'a> {
+    /// Optional cross-reference identifier of the record
+    pub xref: Option<&'a str>,
+    /// tag: ABBR
+    pub abbreviation: Option<Cow<'a, str>>,
+    /// tag: TITL
+    pub title: Option<Cow<'a, str>>,
+    /// tag: AUTH
+    pub author: Option<Cow<'a, str>>,
+    /// tag: PUBL
+    pub publication_facts: Option<Cow<'a, str>>,
+    /// xrefs of the repositories holding the source
+    pub repositories: Vec<&'a str>,
+}
+
+impl<'a> SourceRef<'a> {
+    fn from_node(xref: Option<&'a str>, node: &Node<'a>) -> SourceRef<'a> {
+        let mut sour = SourceRef {
+            xref,
+            ..SourceRef::default()
+        };
+
+        for child in &node.children {
+            match child.line.tag {
+                "ABBR" => sour.abbreviation = Some(child.continued_text()),
+                "TITL" => sour.title = Some(child.continued_text()),
+                "AUTH" => sour.author = Some(child.continued_text()),
+                "PUBL" => sour.publication_facts = Some(child.continued_text()),
+                "REPO" => sour.repositories.extend(child.line.value),
+                _ => (),
+            }
+        }
+        sour
+    }
+}
+
+/// Borrowed counterpart of `Repository`, the REPO record.
+#[derive(Debug, Default)]
+pub struct RepositoryRef<'a> {
+    /// Optional cross-reference identifier of the record
+    pub xref: Option<&'a str>,
+    /// tag: NAME
+    pub name: Option<&'a str>,
+}
+
+/// Borrowed counterpart of `Submitter`, the SUBM record.
+#[derive(Debug, Default)]
+pub struct SubmitterRef<'a> {
+    /// Optional cross-reference identifier of the record
+    pub xref: Option<&'a str>,
+    /// tag: NAME
+    pub name: Option<&'a str>,
+}
+
+/// Borrowed counterpart of `MultimediaRecord`, the OBJE record.
+#[derive(Debug, Default)]
+pub struct MultimediaRef<'a> {
+    /// Optional cross-reference identifier of the record
+    pub xref: Option<&'a str>,
+    /// tag: FILE
+    pub file: Option<&'a str>,
+    /// tag: FORM, found either under FILE or as a sibling of it
+    pub form: Option<&'a str>,
+    /// tag: TITL, found either under FILE or as a sibling of it
+    pub title: Option<&'a str>,
+}
+
+impl<'a> MultimediaRef<'a> {
+    fn from_node(xref: Option<&'a str>, node: &Node<'a>) -> MultimediaRef<'a> {
+        let file = node.child("FILE");
+        MultimediaRef {
+            xref,
+            file: file.and_then(|f| f.line.value),
+            form: node
+                .child_value("FORM")
+                .or_else(|| file.and_then(|f| f.child_value("FORM"))),
+            title: node
+                .child_value("TITL")
+                .or_else(|| file.and_then(|f| f.child_value("TITL"))),
+        }
+    }
+}
+
+const INDIVIDUAL_EVENT_TAGS: [&str; 25] = [
+    "ADOP", "BIRT", "BAPM", "BARM", "BASM", "BLES", "BURI", "CENS", "CHR", "CHRA", "CONF", "CREM",
+    "DEAT", "EMIG", "FCOM", "GRAD", "IMMI", "NATU", "ORDN", "RETI", "RESI", "PROB", "WILL",
+    "EVEN", "MARR",
+];
+
+const INDIVIDUAL_ATTRIBUTE_TAGS: [&str; 13] = [
+    "CAST", "DSCR", "EDUC", "IDNO", "NATI", "NCHI", "NMR", "OCCU", "PROP", "RELI", "SSN", "TITL",
+    "FACT",
+];
+
+const FAMILY_EVENT_TAGS: [&str; 12] = [
+    "MARR", "ANUL", "CENS", "DIV", "DIVF", "ENGA", "MARB", "MARC", "MARL", "MARS", "RESI", "EVEN",
+];
+
+/// A single `gedcom_line`, with every part borrowed from the input.
+#[derive(Debug)]
+struct Line<'a> {
+    level: u8,
+    xref: Option<&'a str>,
+    tag: &'a str,
+    value: Option<&'a str>,
+}
+
+impl<'a> Line<'a> {
+    fn parse(text: &'a str) -> Option<Line<'a>> {
+        let text = text
+            .trim_start_matches(|c: char| c.is_whitespace() || c == '\u{FEFF}')
+            .trim_end_matches(['\r', '\n']);
+
+        let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+        let level = text[..digits].parse().ok()?;
+        let rest = text[digits..].trim_start();
+
+        let (xref, rest) = if rest.starts_with('@') {
+            let end = rest.find(' ')?;
+            (Some(&rest[..end]), rest[end..].trim_start())
+        } else {
+            (None, rest)
+        };
+
+        let (tag, value) = match rest.find(' ') {
+            Some(end) => (&rest[..end], Some(&rest[end + 1..])),
+            None => (rest, None),
+        };
+        if tag.is_empty() {
+            return None;
+        }
+
+        Some(Line {
+            level,
+            xref,
+            tag,
+            value: value.filter(|v| !v.is_empty()),
+        })
+    }
+}
+
+/// A line along with the lines nested beneath it.
+#[derive(Debug)]
+struct Node<'a> {
+    line: Line<'a>,
+    children: Vec<Node<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// Yields each level 0 record of the input as a tree of nodes.
+    fn parse_records(input: &'a str) -> impl Iterator<Item = Node<'a>> {
+        let mut lines = split_lines(input).filter_map(Line::parse).peekable();
+        std::iter::from_fn(move || {
+            let line = lines.next()?;
+            Some(Node::build(line, &mut lines))
+        })
+    }
+
+    fn build<I>(line: Line<'a>, lines: &mut std::iter::Peekable<I>) -> Node<'a>
+    where
+        I: Iterator<Item = Line<'a>>,
+    {
+        let mut node = Node {
+            line,
+            children: Vec::new(),
+        };
+        while let Some(next) = lines.peek() {
+            if next.level <= node.line.level {
+                break;
+            }
+            if let Some(child) = lines.next() {
+                node.children.push(Node::build(child, lines));
+            }
+        }
+        node
+    }
+
+    fn child(&self, tag: &str) -> Option<&Node<'a>> {
+        self.children.iter().find(|c| c.line.tag == tag)
+    }
+
+    fn child_value(&self, tag: &str) -> Option<&'a str> {
+        self.child(tag).map(|c| c.line.value.unwrap_or(""))
+    }
+
+    /// The value of the line joined with any CONT/CONC lines beneath it, only allocating when
+    /// there are continuation lines.
+    fn continued_text(&self) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(self.line.value.unwrap_or(""));
+        for child in &self.children {
+            match child.line.tag {
+                "CONT" => {
+                    let text = text.to_mut();
+                    text.push('\n');
+                    text.push_str(child.line.value.unwrap_or(""));
+                }
+                "CONC" => text.to_mut().push_str(child.line.value.unwrap_or("")),
+                _ => (),
+            }
+        }
+        text
+    }
+}
+
+impl GedcomRef<'_> {
+    /// Copies the borrowed model into an owned, mutable `GedcomData`. Only the values the
+    /// borrowed model captures are carried over, see `GedcomData::as_ref_model` for the reverse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomRef;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Doe/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 1 JAN 1900\n\
+    ///     0 TRLR";
+    ///
+    /// let mut data = GedcomRef::parse(sample).to_owned();
+    /// data.individuals[0].xref = Some("@I2@".to_string());
+    ///
+    /// let indi = &data.individuals[0];
+    /// assert_eq!(indi.name.as_ref().unwrap().value.as_ref().unwrap(), "John /Doe/");
+    /// assert_eq!(indi.events[0].date.as_ref().unwrap().value.as_ref().unwrap(), "1 JAN 1900");
+    ///
+    /// let borrowed = data.as_ref_model();
+    /// assert_eq!(borrowed.individuals[0].xref, Some("@I2@"));
+    /// ```
+    #[must_use]
+    pub fn to_owned(&self) -> GedcomData {
+        GedcomData {
+            header: self.header.as_ref().map(HeaderRef::to_owned),
+            submitters: self.submitters.iter().map(SubmitterRef::to_owned).collect(),
+            individuals: self.individuals.iter().map(IndividualRef::to_owned).collect(),
+            families: self.families.iter().map(FamilyRef::to_owned).collect(),
+            repositories: self.repositories.iter().map(RepositoryRef::to_owned).collect(),
+            sources: self.sources.iter().map(SourceRef::to_owned).collect(),
+            multimedia: self.multimedia.iter().map(MultimediaRef::to_owned).collect(),
+            ..GedcomData::default()
+        }
+    }
+}
+
+impl GedcomData {
+    /// Borrows the data as a `GedcomRef`, e.g. to share analysis code between both models.
+    /// Structures the borrowed model doesn't represent are left out.
+    #[must_use]
+    pub fn as_ref_model(&self) -> GedcomRef<'_> {
+        GedcomRef {
+            header: self.header.as_ref().map(HeaderRef::from),
+            submitters: self.submitters.iter().map(SubmitterRef::from).collect(),
+            individuals: self.individuals.iter().map(IndividualRef::from).collect(),
+            families: self.families.iter().map(FamilyRef::from).collect(),
+            repositories: self.repositories.iter().map(RepositoryRef::from).collect(),
+            sources: self.sources.iter().map(SourceRef::from).collect(),
+            multimedia: self.multimedia.iter().map(MultimediaRef::from).collect(),
+        }
+    }
+}
+
+impl HeaderRef<'_> {
+    /// Copies the header into an owned `Header`.
+    #[must_use]
+    pub fn to_owned(&self) -> Header {
+        Header {
+            gedcom: self.version.map(|version| GedcomMeta {
+                version: Some(version.to_string()),
+                form: None,
+                custom_data: Vec::new(),
+            }),
+            encoding: self.encoding.map(|encoding| Encoding {
+                value: Some(encoding.to_string()),
+                version: None,
+                custom_data: Vec::new(),
+            }),
+            source: self.source.map(|source| HeadSour {
+                value: Some(source.to_string()),
+                ..HeadSour::default()
+            }),
+            date: self.date.map(owned_date),
+            submitter_tag: owned(self.submitter_tag),
+            language: owned(self.language),
+            filename: owned(self.filename),
+            ..Header::default()
+        }
+    }
+}
+
+impl<'a> From<&'a Header> for HeaderRef<'a> {
+    fn from(header: &'a Header) -> HeaderRef<'a> {
+        HeaderRef {
+            version: header.gedcom_version(),
+            encoding: header.character_set(),
+            source: header.source_system(),
+            date: header.date.as_ref().and_then(|d| d.value.as_deref()),
+            submitter_tag: header.submitter_tag.as_deref(),
+            language: header.language.as_deref(),
+            filename: header.filename.as_deref(),
+        }
+    }
+}
+
+impl IndividualRef<'_> {
+    /// Copies the individual into an owned `Individual`. A SEX payload other than M, F, X or U
+    /// is dropped.
+    #[must_use]
+    pub fn to_owned(&self) -> Individual {
+        let sex = self.sex.and_then(GenderType::from_tag).map(|value| Gender {
+            value,
+            fact: None,
+            sources: Vec::new(),
+            notes: Vec::new(),
+            custom_data: Vec::new(),
+        });
+
+        Individual {
+            xref: owned(self.xref),
+            name: self.name.as_ref().map(NameRef::to_owned),
+            sex,
+            families: self.families.iter().map(FamilyLinkRef::to_owned).collect(),
+            attributes: self
+                .attributes
+                .iter()
+                .map(|attr| AttributeDetail {
+                    attribute: AttributeDetail::from_tag(attr.tag),
+                    value: owned(attr.value),
+                    place: attr.place.map(SharedStr::from),
+                    date: attr.date.map(owned_date),
+                    sources: Vec::new(),
+                    notes: Vec::new(),
+                    attribute_type: owned(attr.event_type),
+                    custom_data: Vec::new(),
+                })
+                .collect(),
+            source: self.sources.iter().map(|xref| owned_citation(xref)).collect(),
+            events: self.events.iter().map(EventRef::to_owned).collect(),
+            ..Individual::default()
+        }
+    }
+}
+
+impl<'a> From<&'a Individual> for IndividualRef<'a> {
+    fn from(indi: &'a Individual) -> IndividualRef<'a> {
+        IndividualRef {
+            xref: indi.xref.as_deref(),
+            name: indi.name.as_ref().map(NameRef::from),
+            sex: indi.sex.as_ref().map(|sex| sex.value.tag()),
+            families: indi.families.iter().map(FamilyLinkRef::from).collect(),
+            events: indi.events.iter().map(EventRef::from).collect(),
+            attributes: indi
+                .attributes
+                .iter()
+                .map(|attr| EventRef {
+                    tag: attr.attribute.tag(),
+                    value: attr.value.as_deref(),
+                    date: attr.date.as_ref().and_then(|d| d.value.as_deref()),
+                    place: attr.place.as_deref(),
+                    event_type: attr.attribute_type.as_deref(),
+                })
+                .collect(),
+            sources: indi.source.iter().map(|c| c.xref.as_str()).collect(),
+        }
+    }
+}
+
+impl NameRef<'_> {
+    /// Copies the name into an owned `Name`.
+    #[must_use]
+    pub fn to_owned(&self) -> Name {
+        Name {
+            value: owned(self.value),
+            given: owned(self.given),
+            surname: self.surname.map(SharedStr::from),
+            prefix: owned(self.prefix),
+            surname_prefix: owned(self.surname_prefix),
+            notes: Vec::new(),
+            suffix: owned(self.suffix),
+            source: Vec::new(),
+            name_type: None,
+            custom_data: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<&'a Name> for NameRef<'a> {
+    fn from(name: &'a Name) -> NameRef<'a> {
+        NameRef {
+            value: name.value.as_deref(),
+            given: name.given.as_deref(),
+            surname: name.surname.as_deref(),
+            prefix: name.prefix.as_deref(),
+            surname_prefix: name.surname_prefix.as_deref(),
+            suffix: name.suffix.as_deref(),
+        }
+    }
+}
+
+impl FamilyLinkRef<'_> {
+    /// Copies the link into an owned `FamilyLink`.
+    #[must_use]
+    pub fn to_owned(&self) -> FamilyLink {
+        FamilyLink {
+            xref: self.xref.to_string(),
+            family_link_type: if self.tag == "FAMS" {
+                FamilyLinkType::Spouse
+            } else {
+                FamilyLinkType::Child
+            },
+            pedigree_linkage_type: None,
+            child_linkage_status: None,
+            adopted_by: None,
+            father_relationship: None,
+            mother_relationship: None,
+            notes: Vec::new(),
+            custom_data: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<&'a FamilyLink> for FamilyLinkRef<'a> {
+    fn from(link: &'a FamilyLink) -> FamilyLinkRef<'a> {
+        FamilyLinkRef {
+            xref: &link.xref,
+            tag: link.family_link_type.tag(),
+        }
+    }
+}
+
+impl EventRef<'_> {
+    /// Copies the event into an owned `EventDetail`.
+    #[must_use]
+    pub fn to_owned(&self) -> EventDetail {
+        EventDetail {
+            event: Event::from_tag(self.tag),
+            value: owned(self.value),
+            date: self.date.map(owned_date),
+            place: self.place.map(SharedStr::from),
+            coordinates: None,
+            contact: ContactInfo::default(),
+            agency: None,
+            religion: None,
+            cause: None,
+            restriction: None,
+            age: None,
+            notes: Vec::new(),
+            family_link: None,
+            family_event_details: Vec::new(),
+            event_type: owned(self.event_type),
+            citations: Vec::new(),
+            multimedia: Vec::new(),
+            associations: Vec::new(),
+            custom_data: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<&'a EventDetail> for EventRef<'a> {
+    fn from(event: &'a EventDetail) -> EventRef<'a> {
+        EventRef {
+            tag: event.event.tag(),
+            value: event.value.as_deref(),
+            date: event.date.as_ref().and_then(|d| d.value.as_deref()),
+            place: event.place.as_deref(),
+            event_type: event.event_type.as_deref(),
+        }
+    }
+}
+
+impl FamilyRef<'_> {
+    /// Copies the family into an owned `Family`.
+    #[must_use]
+    pub fn to_owned(&self) -> Family {
+        Family {
+            xref: owned(self.xref),
+            individual1: owned(self.individual1),
+            individual2: owned(self.individual2),
+            children: self.children.iter().map(ToString::to_string).collect(),
+            events: self.events.iter().map(EventRef::to_owned).collect(),
+            ..Family::default()
+        }
+    }
+}
+
+impl<'a> From<&'a Family> for FamilyRef<'a> {
+    fn from(fam: &'a Family) -> FamilyRef<'a> {
+        FamilyRef {
+            xref: fam.xref.as_deref(),
+            individual1: fam.individual1.as_deref(),
+            individual2: fam.individual2.as_deref(),
+            children: fam.children.iter().map(String::as_str).collect(),
+            events: fam.events.iter().map(EventRef::from).collect(),
+        }
+    }
+}
+
+impl SourceRef<'_> {
+    /// Copies the source into an owned `Source`.
+    #[must_use]
+    pub fn to_owned(&self) -> Source {
+        let text = |text: &Option<Cow<str>>| owned(text.as_deref());
+        Source {
+            xref: owned(self.xref),
+            abbreviation: text(&self.abbreviation),
+            title: text(&self.title),
+            author: text(&self.author),
+            publication_facts: text(&self.publication_facts),
+            repo_citations: self
+                .repositories
+                .iter()
+                .map(|xref| RepoCitation {
+                    xref: (*xref).to_string(),
+                    call_number: None,
+                    notes: Vec::new(),
+                    custom_data: Vec::new(),
+                })
+                .collect(),
+            ..Source::default()
+        }
+    }
+}
+
+impl<'a> From<&'a Source> for SourceRef<'a> {
+    fn from(sour: &'a Source) -> SourceRef<'a> {
+        let text = |text: &'a Option<String>| text.as_deref().map(Cow::Borrowed);
+        SourceRef {
+            xref: sour.xref.as_deref(),
+            abbreviation: text(&sour.abbreviation),
+            title: text(&sour.title),
+            author: text(&sour.author),
+            publication_facts: text(&sour.publication_facts),
+            repositories: sour.repo_citations.iter().map(|c| c.xref.as_str()).collect(),
+        }
+    }
+}
+
+impl RepositoryRef<'_> {
+    /// Copies the repository into an owned `Repository`.
+    #[must_use]
+    pub fn to_owned(&self) -> Repository {
+        Repository {
+            xref: owned(self.xref),
+            name: owned(self.name),
+            ..Repository::default()
+        }
+    }
+}
+
+impl<'a> From<&'a Repository> for RepositoryRef<'a> {
+    fn from(repo: &'a Repository) -> RepositoryRef<'a> {
+        RepositoryRef {
+            xref: repo.xref.as_deref(),
+            name: repo.name.as_deref(),
+        }
+    }
+}
+
+impl SubmitterRef<'_> {
+    /// Copies the submitter into an owned `Submitter`.
+    #[must_use]
+    pub fn to_owned(&self) -> Submitter {
+        Submitter {
+            xref: owned(self.xref),
+            name: owned(self.name),
+            ..Submitter::default()
+        }
+    }
+}
+
+impl<'a> From<&'a Submitter> for SubmitterRef<'a> {
+    fn from(subm: &'a Submitter) -> SubmitterRef<'a> {
+        SubmitterRef {
+            xref: subm.xref.as_deref(),
+            name: subm.name.as_deref(),
+        }
+    }
+}
+
+impl MultimediaRef<'_> {
+    /// Copies the multimedia record into an owned `MultimediaRecord`.
+    #[must_use]
+    pub fn to_owned(&self) -> MultimediaRecord {
+        MultimediaRecord {
+            xref: owned(self.xref),
+            file: self.file.map(|file| MultimediaFileRefn {
+                value: Some(file.to_string()),
+                ..MultimediaFileRefn::default()
+            }),
+            form: self.form.map(|form| MultimediaFormat {
+                value: Some(form.to_string()),
+                source_media_type: None,
+                custom_data: Vec::new(),
+            }),
+            title: owned(self.title),
+            ..MultimediaRecord::default()
+        }
+    }
+}
+
+impl<'a> From<&'a MultimediaRecord> for MultimediaRef<'a> {
+    fn from(obje: &'a MultimediaRecord) -> MultimediaRef<'a> {
+        let file = obje.file.as_ref();
+        MultimediaRef {
+            xref: obje.xref.as_deref(),
+            file: file.and_then(|f| f.value.as_deref()),
+            form: obje
+                .form
+                .as_ref()
+                .or_else(|| file.and_then(|f| f.form.as_ref()))
+                .and_then(|f| f.value.as_deref()),
+            title: obje
+                .title
+                .as_deref()
+                .or_else(|| file.and_then(|f| f.title.as_deref())),
+        }
+    }
+}
+
+fn owned(value: Option<&str>) -> Option<String> {
+    value.map(|value| unescape_at_signs(value).into_owned())
+}
+
+fn owned_date(value: &str) -> Date {
+    Date {
+        value: Some(value.to_string()),
+        time: None,
+        custom_data: Vec::new(),
+    }
+}
+
+fn owned_citation(xref: &str) -> SourceCitation {
+    SourceCitation {
+        xref: xref.to_string(),
+        page: None,
+        data: None,
+        notes: Vec::new(),
+        certainty_assessment: None,
+        submitter_registered_rfn: None,
+        multimedia: Vec::new(),
+        custom_data: Vec::new(),
+        duplicates: 0,
+    }
+}
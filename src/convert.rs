@@ -0,0 +1,674 @@
+//! Conversion of the text written for a tree between versions of GEDCOM.
+//!
+//! `GedcomData::upgrade_to_v7` writes a tree read from a GEDCOM 5.5 or 5.5.1 file as a GEDCOM 7
+//! document, and `GedcomData::downgrade_to_v551` writes a tree read from a GEDCOM 7 file as a
+//! GEDCOM 5.5.1 document, for the many programs which only import 5.5.1.
+//! `WriterOptions::target_version` does the same for any `GedcomWriter`. The records are written
+//! as usual, and the output is then converted.
+//!
+//! Upgrading to GEDCOM 7:
+//!
+//! - CONC lines, which GEDCOM 7 removed, are joined back to the line they continue.
+//! - The header declares version 7.0, without the `GEDC.FORM`, `CHAR` and `FILE` structures
+//!   GEDCOM 7 dropped: the output is always UTF-8, whatever encoding the file was read in.
+//!   Submission records and the header pointer to them are left out too.
+//! - Inline notes repeated word for word in several places become a single `SNOTE` record the
+//!   places point to. `NOTE` records become `SNOTE` records, and the pointers to them `SNOTE`
+//!   pointers.
+//! - The `RFN`, `AFN` and `RIN` identifiers become `EXID` structures typed with their GEDCOM 7
+//!   URI, e.g. `https://gedcom.io/terms/v7/RFN`, and the `_UID` vendor tag becomes `UID`.
+//! - Only an at-sign starting a value is written `@@`, and dates name their calendar with a
+//!   keyword rather than an escape, e.g. `JULIAN 1 JAN 1700` for `@#DJULIAN@ 1 JAN 1700`.
+//!
+//! Downgrading to GEDCOM 5.5.1:
+//!
+//! - Values making a line longer than 255 characters are split over CONC lines.
+//! - The header declares version 5.5.1, in the `LINEAGE-LINKED` form and the UTF-8 encoding. The
+//!   `SCHMA` structure is dropped.
+//! - Each pointer to an `SNOTE` record is replaced by the text of the note, and the `SNOTE`
+//!   records are left out.
+//! - The structures GEDCOM 5.5.1 doesn't have are written as the custom tags of
+//!   `CUSTOM_TAGS`, e.g. `_EXID` for `EXID`, and `ASSO.ROLE` as `ASSO.RELA`. Pointers to
+//!   `@VOID@` are dropped.
+//! - The calendar keywords of dates are written as escapes, e.g. `@#DJULIAN@` for `JULIAN`.
+//!
+//! The structures written with a custom tag or dropped are listed as `LossyConversion`s, since
+//! other programs are likely to ignore them. The parser doesn't keep `SNOTE` records nor
+//! pointers to them, so a GEDCOM 7 file to downgrade should be parsed with
+//! `ParseOptions::lenient` and `ParseOptions::preserve_lines`, which keep the text of what the
+//! parser skipped.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{GedcomDocument, ParseOptions};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5.1\n\
+//!     2 FORM LINEAGE-LINKED\n\
+//!     1 CHAR ANSEL\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 NOTE Emigrated with his bro\n\
+//!     2 CONC ther\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Tom /Smith/\n\
+//!     1 NOTE Emigrated with his brother\n\
+//!     0 @R1@ REPO\n\
+//!     1 NAME Leeds Library\n\
+//!     1 RIN 12\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! assert_eq!(
+//!     data.upgrade_to_v7(),
+//!     "\
+//! 0 HEAD
+//! 1 GEDC
+//! 2 VERS 7.0
+//! 0 @I1@ INDI
+//! 1 NAME John /Smith/
+//! 1 SNOTE @N1@
+//! 0 @I2@ INDI
+//! 1 NAME Tom /Smith/
+//! 1 SNOTE @N1@
+//! 0 @R1@ REPO
+//! 1 NAME Leeds Library
+//! 1 EXID 12
+//! 2 TYPE https://gedcom.io/terms/v7/RIN
+//! 0 @N1@ SNOTE Emigrated with his brother
+//! 0 TRLR
+//! "
+//! );
+//!
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 7.0\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 SNOTE @N1@\n\
+//!     1 EXID 1234\n\
+//!     2 TYPE https://example.org/ids\n\
+//!     0 @N1@ SNOTE Emigrated with his brother\n\
+//!     0 TRLR\n";
+//!
+//! let options = ParseOptions {
+//!     lenient: true,
+//!     preserve_lines: true,
+//!     ..ParseOptions::default()
+//! };
+//! let data = GedcomDocument::with_options(sample.chars(), options)
+//!     .parse_document()
+//!     .unwrap();
+//! let downgrade = data.downgrade_to_v551();
+//! assert_eq!(
+//!     downgrade.text,
+//!     "\
+//! 0 HEAD
+//! 1 GEDC
+//! 2 VERS 5.5.1
+//! 2 FORM LINEAGE-LINKED
+//! 1 CHAR UTF-8
+//! 0 @I1@ INDI
+//! 1 NAME John /Smith/
+//! 1 NOTE Emigrated with his brother
+//! 1 _EXID 1234
+//! 2 TYPE https://example.org/ids
+//! 0 TRLR
+//! "
+//! );
+//! assert_eq!(
+//!     downgrade.lossy[0].to_string(),
+//!     "EXID of @I1@ was written as _EXID"
+//! );
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    types::{Calendar, Pointer},
+    util::{is_pointer, split_lines, unescape_at_signs, LineFields},
+    writer::{split_payload, GedcomWriter, TargetVersion, WriterOptions},
+    GedcomData,
+};
+
+/// The calendars GEDCOM 7 names with a keyword
+const CALENDARS: [Calendar; 4] = [
+    Calendar::Gregorian,
+    Calendar::Julian,
+    Calendar::Hebrew,
+    Calendar::French,
+];
+
+/// The GEDCOM 7 structures written with a custom tag when downgrading to GEDCOM 5.5.1, and their
+/// custom tag
+pub const CUSTOM_TAGS: [(&str, &str); 10] = [
+    ("CREA", "_CREA"),
+    ("CROP", "_CROP"),
+    ("EXID", "_EXID"),
+    ("INIL", "_INIL"),
+    ("MIME", "_MIME"),
+    ("NO", "_NO"),
+    ("PHRASE", "_PHRASE"),
+    ("SDATE", "_SDATE"),
+    ("TRAN", "_TRAN"),
+    ("UID", "_UID"),
+];
+
+/// The longest line GEDCOM 5.5.1 allows, in characters
+const MAX_LINE_LENGTH: usize = 255;
+
+/// `Downgrade` is a tree written as a GEDCOM 5.5.1 document, see `GedcomData::downgrade_to_v551`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Downgrade {
+    /// The GEDCOM 5.5.1 text
+    pub text: String,
+    /// The structures written with a custom tag or dropped, in file order
+    pub lossy: Vec<LossyConversion>,
+}
+
+/// `LossyConversion` is a structure which GEDCOM 5.5.1 can't hold as it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LossyConversion {
+    /// The xref of the record holding the structure, or its tag when it has none, e.g. HEAD
+    pub record: String,
+    /// The tag of the structure, e.g. EXID
+    pub tag: String,
+    /// What became of it, e.g. "was written as _EXID"
+    pub change: String,
+}
+
+impl fmt::Display for LossyConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} {}", self.tag, self.record, self.change)
+    }
+}
+
+impl GedcomData {
+    /// Writes the tree as a GEDCOM 7 document, see the `convert` module
+    #[must_use]
+    pub fn upgrade_to_v7(&self) -> String {
+        let options = WriterOptions {
+            target_version: Some(TargetVersion::V7),
+            ..WriterOptions::default()
+        };
+        let mut output = Vec::new();
+        // writing to a Vec can't fail
+        GedcomWriter::with_options(&mut output, options)
+            .write(self)
+            .ok();
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    /// Writes the tree as a GEDCOM 5.5.1 document, listing what couldn't be converted without
+    /// loss, see the `convert` module
+    #[must_use]
+    pub fn downgrade_to_v551(&self) -> Downgrade {
+        downgrade(&self.to_gedcom_string())
+    }
+}
+
+/// A line of GEDCOM text
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Line {
+    level: u8,
+    xref: Option<String>,
+    tag: String,
+    value: Option<String>,
+}
+
+impl Line {
+    fn new(level: u8, tag: &str, value: Option<&str>) -> Line {
+        Line {
+            level,
+            xref: None,
+            tag: tag.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+}
+
+/// Converts GEDCOM 5.5.1 text as written by `GedcomWriter` to GEDCOM 7, see the `convert` module.
+/// Lines end with `\n`.
+pub(crate) fn upgrade(text: &str) -> String {
+    let lines = join_conc(read_lines(text));
+    let lines = upgrade_header(&lines);
+    let lines = translate_tags(lines);
+    let lines = share_notes(&lines);
+    let lines = upgrade_escapes(lines);
+    write_lines(&lines)
+}
+
+fn read_lines(text: &str) -> Vec<Line> {
+    split_lines(text)
+        .filter_map(|line| {
+            let fields = LineFields::split(line.trim_start_matches(['\u{FEFF}', ' ', '\t']));
+            Some(Line {
+                level: fields.level.parse().ok()?,
+                xref: fields.xref.map(str::to_string),
+                tag: fields.tag?.to_string(),
+                value: fields.value.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+fn write_lines(lines: &[Line]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(&line.level.to_string());
+        let fields = line.xref.iter().chain(Some(&line.tag)).chain(&line.value);
+        for field in fields.filter(|field| !field.is_empty()) {
+            text.push(' ');
+            text.push_str(field);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// The index just past the structure starting at `start`, i.e. past its substructures
+fn structure_end(lines: &[Line], start: usize) -> usize {
+    let level = lines[start].level;
+    lines[start + 1..]
+        .iter()
+        .position(|line| line.level <= level)
+        .map_or(lines.len(), |position| start + 1 + position)
+}
+
+/// Appends the value of each CONC line to the line it continues
+fn join_conc(lines: Vec<Line>) -> Vec<Line> {
+    let mut joined: Vec<Line> = Vec::with_capacity(lines.len());
+    for line in lines {
+        match joined.last_mut() {
+            Some(last) if line.tag == "CONC" && line.level > 0 => {
+                let value = last.value.get_or_insert_with(String::new);
+                value.push_str(line.value.as_deref().unwrap_or_default());
+            }
+            _ => joined.push(line),
+        }
+    }
+    joined
+}
+
+/// Declares version 7.0 in the header, dropping the header structures and records GEDCOM 7
+/// removed
+fn upgrade_header(lines: &[Line]) -> Vec<Line> {
+    let head_end = match lines.first() {
+        Some(line) if line.level == 0 && line.tag == "HEAD" => structure_end(lines, 0),
+        _ => 0,
+    };
+    let mut upgraded = vec![
+        Line::new(0, "HEAD", None),
+        Line::new(1, "GEDC", None),
+        Line::new(2, "VERS", Some("7.0")),
+    ];
+    let mut index = head_end.min(1);
+    while index < head_end {
+        let end = structure_end(lines, index);
+        if !matches!(lines[index].tag.as_str(), "GEDC" | "CHAR" | "FILE" | "SUBN") {
+            upgraded.extend_from_slice(&lines[index..end]);
+        }
+        index = end;
+    }
+    let mut index = head_end;
+    while index < lines.len() {
+        let end = structure_end(lines, index);
+        if lines[index].tag != "SUBN" {
+            upgraded.extend_from_slice(&lines[index..end]);
+        }
+        index = end;
+    }
+    upgraded
+}
+
+/// Renames the tags GEDCOM 7 replaced
+fn translate_tags(lines: Vec<Line>) -> Vec<Line> {
+    let mut translated = Vec::with_capacity(lines.len());
+    for mut line in lines {
+        match line.tag.as_str() {
+            "RFN" | "AFN" | "RIN" if line.level == 1 => {
+                let uri = format!("https://gedcom.io/terms/v7/{}", line.tag);
+                line.tag = "EXID".to_string();
+                translated.push(line);
+                translated.push(Line::new(2, "TYPE", Some(&uri)));
+                continue;
+            }
+            "_UID" if line.level == 1 => line.tag = "UID".to_string(),
+            "NOTE" if line.value.as_deref().is_some_and(is_pointer) || line.level == 0 => {
+                line.tag = "SNOTE".to_string();
+            }
+            _ => {}
+        }
+        translated.push(line);
+    }
+    translated
+}
+
+/// Writes the escapes of the values the GEDCOM 7 way: only an at-sign starting a value is doubled,
+/// and dates name their calendar with a keyword
+fn upgrade_escapes(mut lines: Vec<Line>) -> Vec<Line> {
+    for line in &mut lines {
+        let Some(value) = line.value.as_mut().filter(|value| !is_pointer(value)) else {
+            continue;
+        };
+        if line.tag == "DATE" {
+            for calendar in CALENDARS {
+                if let Some(keyword) = calendar.keyword() {
+                    *value = value.replace(calendar.escape(), keyword);
+                }
+            }
+        }
+        let text = unescape_at_signs(value);
+        *value = if text.starts_with('@') {
+            format!("@{text}")
+        } else {
+            text.into_owned()
+        };
+    }
+    lines
+}
+
+/// Turns the inline notes found in several places into SNOTE records
+fn share_notes(lines: &[Line]) -> Vec<Line> {
+    // the inline notes made of a single line and its CONT lines: their start, end and text
+    let mut notes = Vec::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (start, line) in lines.iter().enumerate() {
+        if line.tag != "NOTE" || line.level == 0 {
+            continue;
+        }
+        let end = structure_end(lines, start);
+        let continued = lines[start + 1..end]
+            .iter()
+            .all(|cont| cont.tag == "CONT" && cont.level == line.level + 1);
+        if !continued {
+            continue;
+        }
+        let text: Vec<&str> = lines[start..end]
+            .iter()
+            .map(|line| line.value.as_deref().unwrap_or_default())
+            .collect();
+        let text = text.join("\n");
+        if !text.is_empty() {
+            *counts.entry(text.clone()).or_insert(0) += 1;
+            notes.push((start, end, text));
+        }
+    }
+
+    let taken: HashSet<&str> = lines
+        .iter()
+        .filter(|line| line.level == 0)
+        .filter_map(|line| line.xref.as_deref())
+        .collect();
+    let mut next_number = 0;
+    let mut xrefs: HashMap<&str, String> = HashMap::new();
+    let mut records = Vec::new();
+    for (_, _, text) in &notes {
+        if counts[text] < 2 || xrefs.contains_key(text.as_str()) {
+            continue;
+        }
+        let xref = loop {
+            next_number += 1;
+            let xref = format!("@N{next_number}@");
+            if !taken.contains(xref.as_str()) {
+                break xref;
+            }
+        };
+        for (index, part) in text.split('\n').enumerate() {
+            let line = if index == 0 {
+                Line {
+                    xref: Some(xref.clone()),
+                    ..Line::new(0, "SNOTE", Some(part))
+                }
+            } else {
+                Line::new(1, "CONT", Some(part))
+            };
+            records.push(line);
+        }
+        xrefs.insert(text, xref);
+    }
+
+    let mut shared = Vec::with_capacity(lines.len());
+    let mut notes = notes.iter().peekable();
+    let mut index = 0;
+    while index < lines.len() {
+        let note = notes.next_if(|(start, ..)| *start == index);
+        if let Some(xref) = note.and_then(|(_, _, text)| xrefs.get(text.as_str())) {
+            shared.push(Line::new(lines[index].level, "SNOTE", Some(xref)));
+            index = note.map_or(index + 1, |(_, end, _)| *end);
+            continue;
+        }
+        if lines[index].level == 0 && lines[index].tag == "TRLR" {
+            shared.append(&mut records);
+        }
+        shared.push(lines[index].clone());
+        index += 1;
+    }
+    shared.append(&mut records);
+    shared
+}
+
+/// Converts GEDCOM 7 text as written by `GedcomWriter` to GEDCOM 5.5.1, see the `convert` module.
+/// Lines end with `\n`.
+pub(crate) fn downgrade(text: &str) -> Downgrade {
+    let lines = read_lines(text);
+    let mut lossy = Vec::new();
+    let lines = downgrade_header(&lines, &mut lossy);
+    let lines = embed_notes(&lines, &mut lossy);
+    let lines = map_structures(&lines, &mut lossy);
+    let lines = downgrade_calendars(lines);
+    let lines = split_long_lines(lines);
+    Downgrade {
+        text: write_lines(&lines),
+        lossy,
+    }
+}
+
+/// Writes the calendar keywords of dates as escapes, e.g. `@#DJULIAN@` for `JULIAN`
+fn downgrade_calendars(mut lines: Vec<Line>) -> Vec<Line> {
+    for line in lines.iter_mut().filter(|line| line.tag == "DATE") {
+        let Some(value) = line.value.as_mut() else {
+            continue;
+        };
+        // the phrase of a date is left as it is
+        let (date, phrase) = value.split_at(value.find('(').unwrap_or(value.len()));
+        let words: Vec<&str> = date
+            .split(' ')
+            .map(|word| match Calendar::parse(word) {
+                Some(calendar) if !word.starts_with('@') => calendar.escape(),
+                _ => word,
+            })
+            .collect();
+        *value = words.join(" ") + phrase;
+    }
+    lines
+}
+
+/// Declares version 5.5.1 in the header, dropping the header structures GEDCOM 5.5.1 doesn't have
+fn downgrade_header(lines: &[Line], lossy: &mut Vec<LossyConversion>) -> Vec<Line> {
+    let head_end = match lines.first() {
+        Some(line) if line.level == 0 && line.tag == "HEAD" => structure_end(lines, 0),
+        _ => 0,
+    };
+    let mut downgraded = vec![
+        Line::new(0, "HEAD", None),
+        Line::new(1, "GEDC", None),
+        Line::new(2, "VERS", Some("5.5.1")),
+        Line::new(2, "FORM", Some("LINEAGE-LINKED")),
+        Line::new(1, "CHAR", Some("UTF-8")),
+    ];
+    let mut index = head_end.min(1);
+    while index < head_end {
+        let end = structure_end(lines, index);
+        match lines[index].tag.as_str() {
+            "GEDC" | "CHAR" => {}
+            "SCHMA" => lossy.push(LossyConversion {
+                record: "HEAD".to_string(),
+                tag: "SCHMA".to_string(),
+                change: "was dropped".to_string(),
+            }),
+            _ => downgraded.extend_from_slice(&lines[index..end]),
+        }
+        index = end;
+    }
+    downgraded.extend_from_slice(&lines[head_end..]);
+    downgraded
+}
+
+/// Replaces the pointers to SNOTE records by the text of the notes, leaving the records out
+fn embed_notes(lines: &[Line], lossy: &mut Vec<LossyConversion>) -> Vec<Line> {
+    let mut notes: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (start, line) in lines.iter().enumerate() {
+        let (0, "SNOTE", Some(xref)) = (line.level, line.tag.as_str(), &line.xref) else {
+            continue;
+        };
+        let end = structure_end(lines, start);
+        let mut text = vec![line.value.as_deref().unwrap_or_default()];
+        for sub in &lines[start + 1..end] {
+            match (sub.level, sub.tag.as_str()) {
+                (1, "CONT") => text.push(sub.value.as_deref().unwrap_or_default()),
+                (1, "CONC") => {}
+                (1, tag) => lossy.push(LossyConversion {
+                    record: xref.clone(),
+                    tag: tag.to_string(),
+                    change: "was dropped along with the SNOTE record".to_string(),
+                }),
+                _ => {}
+            }
+        }
+        notes.insert(xref, text);
+    }
+
+    let mut embedded = Vec::with_capacity(lines.len());
+    let mut index = 0;
+    while index < lines.len() {
+        let line = &lines[index];
+        if line.level == 0 && line.tag == "SNOTE" {
+            index = structure_end(lines, index);
+            continue;
+        }
+        let note = line.value.as_deref().and_then(|xref| notes.get(xref));
+        match note {
+            Some(text) if line.tag == "SNOTE" => {
+                for (number, part) in text.iter().enumerate() {
+                    embedded.push(match number {
+                        0 => Line::new(line.level, "NOTE", Some(part)),
+                        _ => Line::new(line.level + 1, "CONT", Some(part)),
+                    });
+                }
+            }
+            _ if line.tag == "SNOTE" => embedded.push(Line {
+                tag: "NOTE".to_string(),
+                ..line.clone()
+            }),
+            _ => embedded.push(line.clone()),
+        }
+        index += 1;
+    }
+    embedded
+}
+
+/// Writes the structures GEDCOM 5.5.1 doesn't have with custom tags, see `CUSTOM_TAGS`
+fn map_structures(lines: &[Line], lossy: &mut Vec<LossyConversion>) -> Vec<Line> {
+    let mut mapped = Vec::with_capacity(lines.len());
+    let mut record = String::new();
+    // the tags of the structures enclosing the line, by level
+    let mut path: Vec<&str> = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = &lines[index];
+        if line.level == 0 {
+            record = line.xref.clone().unwrap_or_else(|| line.tag.clone());
+        }
+        path.truncate(usize::from(line.level));
+        let custom = path.iter().any(|tag| tag.starts_with('_'));
+        let parent = path.last().copied();
+        path.push(&line.tag);
+        let mut report = |change: String| {
+            lossy.push(LossyConversion {
+                record: record.clone(),
+                tag: line.tag.clone(),
+                change,
+            });
+        };
+
+        let tag = CUSTOM_TAGS
+            .iter()
+            .find(|(tag, _)| *tag == line.tag)
+            .map(|(_, custom)| *custom);
+        match tag {
+            _ if custom || line.level == 0 => {}
+            _ if line.value.as_deref() == Some(Pointer::VOID) => {
+                report(format!(
+                    "was dropped, GEDCOM 5.5.1 has no {} pointers",
+                    Pointer::VOID
+                ));
+                index = structure_end(lines, index);
+                continue;
+            }
+            Some(tag) => {
+                report(format!("was written as {tag}"));
+                mapped.push(Line {
+                    tag: tag.to_string(),
+                    ..line.clone()
+                });
+                index += 1;
+                continue;
+            }
+            None if line.tag == "ROLE" && parent == Some("ASSO") => {
+                mapped.push(Line {
+                    tag: "RELA".to_string(),
+                    ..line.clone()
+                });
+                index += 1;
+                continue;
+            }
+            None => {}
+        }
+        mapped.push(line.clone());
+        index += 1;
+    }
+    mapped
+}
+
+/// Splits the values making a line longer than GEDCOM 5.5.1 allows over CONC lines
+fn split_long_lines(lines: Vec<Line>) -> Vec<Line> {
+    let mut split = Vec::with_capacity(lines.len());
+    for line in lines {
+        let Some(value) = line.value.as_deref() else {
+            split.push(line);
+            continue;
+        };
+        let prefix = write_lines(&[Line {
+            value: None,
+            ..line.clone()
+        }]);
+        // continuing CONT and CONC lines keeps to their level
+        let conc_level = match line.tag.as_str() {
+            "CONT" | "CONC" => line.level,
+            _ => line.level + 1,
+        };
+        let conc_prefix = format!("{conc_level} CONC \n");
+        if prefix.chars().count() + value.chars().count() <= MAX_LINE_LENGTH {
+            split.push(line);
+            continue;
+        }
+        // the prefixes end with a line feed, standing for the space before the value
+        let max_length = MAX_LINE_LENGTH - prefix.len().max(conc_prefix.len());
+        let mut parts = split_payload(value, Some(max_length));
+        split.push(Line {
+            value: parts.next().map(str::to_string),
+            ..line.clone()
+        });
+        for part in parts {
+            split.push(Line::new(conc_level, "CONC", Some(part)));
+        }
+    }
+    split
+}
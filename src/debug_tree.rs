@@ -0,0 +1,221 @@
+//! An indented view of the ancestors and descendants of an individual, for inspecting parse
+//! results.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::{
+    duplicates::{date_part, event_date},
+    kinship::Kinship,
+    types::{Event, Family, FamilyLinkType, Individual, Name},
+    GedcomData,
+};
+
+impl GedcomData {
+    /// Prints an indented tree of the ancestors and descendants of an individual to `out`, up to
+    /// `depth` generations each way, with their names, lifespans and xrefs. Ancestors are
+    /// followed through birth families only, see `Kinship::parents`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by `out`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Smith/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 12 MAR 1850\n\
+    ///     1 DEAT\n\
+    ///     2 DATE 1921\n\
+    ///     1 FAMS @F1@\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 NAME Mary /Brown/\n\
+    ///     1 FAMS @F1@\n\
+    ///     0 @I3@ INDI\n\
+    ///     1 NAME Thomas /Smith/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE ABT 1875\n\
+    ///     1 FAMC @F1@\n\
+    ///     0 @F1@ FAM\n\
+    ///     1 HUSB @I1@\n\
+    ///     1 WIFE @I2@\n\
+    ///     1 CHIL @I3@\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// data.debug_tree(&mut output, "@I1@", 2).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     "\
+    /// John Smith (1850-1921) @I1@
+    ///   descendants:
+    ///     spouse: Mary Brown @I2@ in @F1@
+    ///       child: Thomas Smith (1875-) @I3@
+    /// "
+    /// );
+    /// ```
+    pub fn debug_tree(&self, out: &mut impl Write, xref: &str, depth: usize) -> io::Result<()> {
+        let tree = DebugTree::new(self);
+        writeln!(out, "{}", tree.describe(xref))?;
+        if depth == 0 {
+            return Ok(());
+        }
+        let (father, mother) = tree.kinship.parents(xref);
+        if father.is_some() || mother.is_some() {
+            writeln!(out, "  ancestors:")?;
+            tree.ancestors(out, xref, depth, &mut vec![xref])?;
+        }
+        if tree.has_families(xref) {
+            writeln!(out, "  descendants:")?;
+            tree.descendants(out, xref, depth, &mut vec![xref])?;
+        }
+        Ok(())
+    }
+}
+
+struct DebugTree<'a> {
+    kinship: Kinship<'a>,
+    individuals: HashMap<&'a str, &'a Individual>,
+    families: HashMap<&'a str, &'a Family>,
+}
+
+impl<'a> DebugTree<'a> {
+    fn new(data: &'a GedcomData) -> DebugTree<'a> {
+        DebugTree {
+            kinship: Kinship::new(data),
+            individuals: data
+                .individuals
+                .iter()
+                .filter_map(|indi| Some((indi.xref.as_deref()?, indi)))
+                .collect(),
+            families: data
+                .families
+                .iter()
+                .filter_map(|fam| Some((fam.xref.as_deref()?, fam)))
+                .collect(),
+        }
+    }
+
+    /// `path` holds the individuals printed above, so that looping ancestry stops
+    fn ancestors(
+        &self,
+        out: &mut impl Write,
+        xref: &str,
+        depth: usize,
+        path: &mut Vec<&'a str>,
+    ) -> io::Result<()> {
+        let (father, mother) = self.kinship.parents(xref);
+        let parents = father.map(|f| ("father", f)).into_iter();
+        for (role, parent) in parents.chain(mother.map(|m| ("mother", m))) {
+            let indent = "  ".repeat(path.len() + 1);
+            writeln!(out, "{indent}{role}: {}", self.describe(parent))?;
+            if path.len() < depth && !path.contains(&parent) {
+                path.push(parent);
+                self.ancestors(out, parent, depth, path)?;
+                path.pop();
+            }
+        }
+        Ok(())
+    }
+
+    fn descendants(
+        &self,
+        out: &mut impl Write,
+        xref: &str,
+        depth: usize,
+        path: &mut Vec<&'a str>,
+    ) -> io::Result<()> {
+        let indent = "    ".repeat(path.len());
+        for family in self.spouse_families(xref) {
+            let family_xref = family.xref.as_deref().unwrap_or_default();
+            let spouse = family
+                .individual1
+                .iter()
+                .chain(&family.individual2)
+                .find(|spouse| spouse.as_str() != xref);
+            match spouse {
+                Some(spouse) => writeln!(
+                    out,
+                    "{indent}spouse: {} in {family_xref}",
+                    self.describe(spouse)
+                )?,
+                None => writeln!(out, "{indent}family: {family_xref}")?,
+            }
+            for child in &family.children {
+                writeln!(out, "{indent}  child: {}", self.describe(child))?;
+                if path.len() < depth && !path.contains(&child.as_str()) {
+                    let Some((child, _)) = self.individuals.get_key_value(child.as_str()) else {
+                        continue;
+                    };
+                    path.push(child);
+                    self.descendants(out, child, depth, path)?;
+                    path.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn has_families(&self, xref: &str) -> bool {
+        self.spouse_families(xref).next().is_some()
+    }
+
+    /// The families an individual is a spouse in, through their FAMS links
+    fn spouse_families(&self, xref: &str) -> impl Iterator<Item = &'a Family> + '_ {
+        self.individuals
+            .get(xref)
+            .into_iter()
+            .flat_map(|indi| &indi.families)
+            .filter(|link| matches!(link.family_link_type, FamilyLinkType::Spouse))
+            .filter_map(move |link| self.families.get(link.xref.as_str()).copied())
+    }
+
+    fn describe(&self, xref: &str) -> String {
+        describe(self.individuals.get(xref).copied(), xref)
+    }
+}
+
+/// The name and lifespan of an individual, followed by their xref, e.g.
+/// "John Smith (1850-1921) @I1@"
+pub(crate) fn describe(indi: Option<&Individual>, xref: &str) -> String {
+    let Some(indi) = indi else {
+        return format!("{xref} (missing)");
+    };
+    let name = indi
+        .name
+        .as_ref()
+        .and_then(Name::full_name)
+        .unwrap_or_else(|| "(unnamed)".to_string());
+
+    match lifespan_years(indi) {
+        Some(years) => format!("{name} ({years}) {xref}"),
+        None => format!("{name} {xref}"),
+    }
+}
+
+/// The years of birth and death of an individual, e.g. "1850-1921" or "1875-", when either is
+/// dated
+pub(crate) fn lifespan_years(indi: &Individual) -> Option<String> {
+    let year = |event| {
+        let date = event_date(indi, &event)?;
+        Some(date_part(&date)?.year)
+    };
+    match (year(Event::Birth), year(Event::Death)) {
+        (None, None) => None,
+        (birth, death) => {
+            let year = |year: Option<i32>| year.map(|year| year.to_string()).unwrap_or_default();
+            Some(format!("{}-{}", year(birth), year(death)))
+        }
+    }
+}
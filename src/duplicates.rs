@@ -0,0 +1,324 @@
+//! Detection of individuals which are likely entered more than once in a tree, e.g. after merging
+//! files from several relatives.
+//!
+//! Only individuals whose surnames share a [Soundex](https://en.wikipedia.org/wiki/Soundex) code,
+//! e.g. "Smith" and "Smyth", are compared, so large trees don't need a comparison of every pair.
+//! Individuals without a surname are grouped by their first given name instead. Individuals of
+//! different known sex are never paired.
+//!
+//! Each pair is scored by the `Matcher`s of `DuplicateOptions`. A matcher only takes part when
+//! both individuals have the data it looks at, e.g. a birth date, and the score of the pair is the
+//! weighted mean of the matchers which took part.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{duplicates::DuplicateOptions, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME Johann /Schmidt/\n\
+//!     1 BIRT\n\
+//!     2 DATE 12 MAR 1850\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Johan /Schmitt/\n\
+//!     1 BIRT\n\
+//!     2 DATE ABT 1850\n\
+//!     0 @I3@ INDI\n\
+//!     1 NAME Anna /Schmidt/\n\
+//!     1 BIRT\n\
+//!     2 DATE 1880\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let duplicates = data.find_duplicates(&DuplicateOptions::default());
+//!
+//! assert_eq!(duplicates.len(), 1);
+//! assert_eq!(duplicates[0].first, "@I1@");
+//! assert_eq!(duplicates[0].second, "@I2@");
+//! assert!(duplicates[0].score > 0.85);
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    kinship::Kinship,
+    search::{as_f64, name_words, similarity, soundex},
+    stats::surname,
+    types::{Date, DatePart, DateValue, Event, GenderType, Individual},
+    GedcomData,
+};
+
+/// `Matcher` compares one aspect of two individuals, scoring from 0.0 to 1.0 for a perfect match.
+/// Its weight sets how much it counts towards the score of a pair.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Matcher {
+    /// Spelling similarity of the words of the names, ignoring case
+    Name {
+        /// Weight of the matcher
+        weight: f64,
+    },
+    /// Years between the birth dates, scoring 0 past `max_years`
+    BirthDate {
+        /// Largest difference in years which still scores
+        max_years: u32,
+        /// Weight of the matcher
+        weight: f64,
+    },
+    /// Years between the death dates, scoring 0 past `max_years`
+    DeathDate {
+        /// Largest difference in years which still scores
+        max_years: u32,
+        /// Weight of the matcher
+        weight: f64,
+    },
+    /// The share of known parents which are the same individual, from the birth families (FAMC)
+    SameParents {
+        /// Weight of the matcher
+        weight: f64,
+    },
+}
+
+impl Matcher {
+    /// The weight of the matcher
+    #[must_use]
+    pub fn weight(&self) -> f64 {
+        match self {
+            Matcher::Name { weight }
+            | Matcher::BirthDate { weight, .. }
+            | Matcher::DeathDate { weight, .. }
+            | Matcher::SameParents { weight } => *weight,
+        }
+    }
+}
+
+/// `DuplicateOptions` configures `GedcomData::find_duplicates`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateOptions {
+    /// The matchers scoring each pair. By default names count double, and birth and death dates
+    /// may be two years apart.
+    pub matchers: Vec<Matcher>,
+    /// The lowest score of the pairs reported, 0.8 by default
+    pub min_score: f64,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> DuplicateOptions {
+        DuplicateOptions {
+            matchers: vec![
+                Matcher::Name { weight: 2.0 },
+                Matcher::BirthDate {
+                    max_years: 2,
+                    weight: 1.0,
+                },
+                Matcher::DeathDate {
+                    max_years: 2,
+                    weight: 1.0,
+                },
+                Matcher::SameParents { weight: 1.0 },
+            ],
+            min_score: 0.8,
+        }
+    }
+}
+
+/// `DuplicateCandidate` is a pair of individuals which may be the same person.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateCandidate {
+    /// xref of the individual coming first in the file
+    pub first: String,
+    /// xref of the other individual
+    pub second: String,
+    /// The weighted mean of the matcher scores, from 0.0 to 1.0
+    pub score: f64,
+    /// The score of each matcher which took part
+    pub matches: Vec<(Matcher, f64)>,
+}
+
+impl GedcomData {
+    /// Finds pairs of individuals which may be the same person, best matches first. See the
+    /// `duplicates` module for how candidates are picked and scored.
+    #[must_use]
+    pub fn find_duplicates(&self, options: &DuplicateOptions) -> Vec<DuplicateCandidate> {
+        let kinship = Kinship::new(self);
+        let individuals: Vec<(&str, &Individual)> = self
+            .individuals
+            .iter()
+            .filter_map(|indi| Some((indi.xref.as_deref()?, indi)))
+            .collect();
+
+        let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, (_, indi)) in individuals.iter().enumerate() {
+            if let Some(key) = group_key(indi) {
+                groups.entry(key).or_default().push(index);
+            }
+        }
+        let mut pairs = BTreeSet::new();
+        for group in groups.values() {
+            for (i, first) in group.iter().enumerate() {
+                for second in &group[i + 1..] {
+                    pairs.insert((*first, *second));
+                }
+            }
+        }
+
+        let mut candidates: Vec<DuplicateCandidate> = pairs
+            .into_iter()
+            .filter_map(|(first, second)| {
+                let (first, first_indi) = individuals[first];
+                let (second, second_indi) = individuals[second];
+                if different_sex(first_indi, second_indi) {
+                    return None;
+                }
+
+                let matches: Vec<(Matcher, f64)> = options
+                    .matchers
+                    .iter()
+                    .filter_map(|matcher| {
+                        let score = match matcher {
+                            Matcher::Name { .. } => name_score(first_indi, second_indi),
+                            Matcher::BirthDate { max_years, .. } => date_score(
+                                &event_date(first_indi, &Event::Birth)?,
+                                &event_date(second_indi, &Event::Birth)?,
+                                *max_years,
+                            ),
+                            Matcher::DeathDate { max_years, .. } => date_score(
+                                &event_date(first_indi, &Event::Death)?,
+                                &event_date(second_indi, &Event::Death)?,
+                                *max_years,
+                            ),
+                            Matcher::SameParents { .. } => parents_score(&kinship, first, second),
+                        }?;
+                        Some((*matcher, score))
+                    })
+                    .collect();
+
+                let weights: f64 = matches.iter().map(|(matcher, _)| matcher.weight()).sum();
+                if weights <= 0.0 {
+                    return None;
+                }
+                let score = matches
+                    .iter()
+                    .map(|(matcher, score)| matcher.weight() * score)
+                    .sum::<f64>()
+                    / weights;
+                if score < options.min_score {
+                    return None;
+                }
+                Some(DuplicateCandidate {
+                    first: first.to_string(),
+                    second: second.to_string(),
+                    score,
+                    matches,
+                })
+            })
+            .collect();
+
+        // sort_by is stable, keeping file order among equal scores
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates
+    }
+}
+
+/// The Soundex code grouping an individual with the others they are compared with
+fn group_key(indi: &Individual) -> Option<String> {
+    match surname(indi) {
+        Some(surname) => soundex(&surname),
+        None => soundex(indi.name.as_ref()?.given.as_deref()?),
+    }
+}
+
+fn different_sex(first: &Individual, second: &Individual) -> bool {
+    let known = |indi: &Individual| match indi.sex.as_ref().map(|sex| &sex.value) {
+        Some(GenderType::Male) => Some(true),
+        Some(GenderType::Female) => Some(false),
+        _ => None,
+    };
+    matches!((known(first), known(second)), (Some(a), Some(b)) if a != b)
+}
+
+/// Matches each word of the shorter name with its most similar word of the other name
+fn name_score(first: &Individual, second: &Individual) -> Option<f64> {
+    let lowercase = |indi| -> Vec<String> {
+        name_words(indi)
+            .into_iter()
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (mut shorter, mut longer) = (lowercase(first), lowercase(second));
+    if shorter.len() > longer.len() {
+        std::mem::swap(&mut shorter, &mut longer);
+    }
+    if shorter.is_empty() {
+        return None;
+    }
+    let total: f64 = shorter
+        .iter()
+        .map(|word| {
+            longer
+                .iter()
+                .map(|other| similarity(word, other))
+                .fold(0.0, f64::max)
+        })
+        .sum();
+    Some(total / as_f64(shorter.len()))
+}
+
+pub(crate) fn event_date(indi: &Individual, event: &Event) -> Option<DateValue> {
+    indi.events
+        .iter()
+        .filter(|detail| detail.event == *event)
+        .find_map(|detail| detail.date.as_ref().and_then(Date::value_structured))
+}
+
+fn date_score(first: &DateValue, second: &DateValue, max_years: u32) -> Option<f64> {
+    let (first, second) = (date_part(first)?, date_part(second)?);
+    if first.day.is_some() && first.month.is_some() && first == second {
+        return Some(1.0);
+    }
+    let years = first.year.abs_diff(second.year);
+    if years > max_years {
+        return Some(0.0);
+    }
+    Some(1.0 - f64::from(years) / (f64::from(max_years) + 1.0))
+}
+
+/// The date an event is taken to have happened, the first one of a range
+pub(crate) fn date_part(date: &DateValue) -> Option<&DatePart> {
+    match date {
+        DateValue::Exact(part)
+        | DateValue::About(part)
+        | DateValue::Calculated(part)
+        | DateValue::Estimated(part)
+        | DateValue::Before(part)
+        | DateValue::After(part)
+        | DateValue::From(part)
+        | DateValue::To(part)
+        | DateValue::Interpreted(part, _)
+        | DateValue::Between(part, _)
+        | DateValue::FromTo(part, _) => Some(part),
+        DateValue::Phrase(_) => None,
+    }
+}
+
+fn parents_score(kinship: &Kinship, first: &str, second: &str) -> Option<f64> {
+    let (first_father, first_mother) = kinship.parents(first);
+    let (second_father, second_mother) = kinship.parents(second);
+    let mut compared = 0;
+    let mut same = 0;
+    for pair in &[(first_father, second_father), (first_mother, second_mother)] {
+        if let (Some(a), Some(b)) = pair {
+            compared += 1;
+            if a == b {
+                same += 1;
+            }
+        }
+    }
+    if compared == 0 {
+        None
+    } else {
+        Some(f64::from(same) / f64::from(compared))
+    }
+}
@@ -0,0 +1,910 @@
+//! Bulk transformations over a parsed `GedcomData` tree, e.g. to make a file consistent before
+//! sharing it, and edits of single records which keep the links between records intact.
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    duplicates::{date_part, event_date},
+    intern::SharedStr,
+    store::Record,
+    types::{
+        AttributeDetail, ChangeDate, Date, DateValue, Event, EventDetail, Family, FamilyLink,
+        FamilyLinkType, Gender, Individual, Name, SourceCitation,
+    },
+    GedcomData,
+};
+
+/// `DateNormalization` reports the outcome of `normalize_dates`.
+#[derive(Debug, Default)]
+pub struct DateNormalization {
+    /// Number of date payloads rewritten to canonical form
+    pub normalized: usize,
+    /// Date payloads that could not be interpreted and were left untouched
+    pub unparsed: Vec<UnparsedDate>,
+}
+
+/// `UnparsedDate` is a date payload `normalize_dates` could not interpret.
+#[derive(Debug)]
+pub struct UnparsedDate {
+    /// xref of the record containing the date, if the record has one
+    pub xref: Option<String>,
+    /// The original date payload
+    pub value: String,
+}
+
+/// Rewrites every parseable date payload in the tree to canonical GEDCOM form (e.g. "Feb 2, 1832"
+/// becomes "2 FEB 1832"), see `DateValue`. Payloads which can't be interpreted are left as-is and
+/// listed in the returned report.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{edit, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 BIRT\n\
+///     2 DATE abt. February 2, 1832\n\
+///     1 DEAT\n\
+///     2 DATE 1901-12-24\n\
+///     1 BURI\n\
+///     2 DATE the week after\n\
+///     0 TRLR";
+///
+/// let mut doc = GedcomDocument::new(sample.chars());
+/// let mut data = doc.parse_document().unwrap();
+///
+/// let report = edit::normalize_dates(&mut data);
+/// assert_eq!(report.normalized, 2);
+/// assert_eq!(report.unparsed.len(), 1);
+/// assert_eq!(report.unparsed[0].xref.as_ref().unwrap(), "@I1@");
+/// assert_eq!(report.unparsed[0].value, "the week after");
+///
+/// let events = &data.individuals[0].events;
+/// assert_eq!(events[0].date.as_ref().unwrap().value.as_ref().unwrap(), "ABT 2 FEB 1832");
+/// assert_eq!(events[1].date.as_ref().unwrap().value.as_ref().unwrap(), "24 DEC 1901");
+/// assert_eq!(events[2].date.as_ref().unwrap().value.as_ref().unwrap(), "the week after");
+/// ```
+pub fn normalize_dates(data: &mut GedcomData) -> DateNormalization {
+    let mut report = DateNormalization::default();
+
+    visit_dates(data, &mut |xref, date| {
+        let value = match &date.value {
+            Some(value) if !value.trim().is_empty() => value,
+            _ => return,
+        };
+
+        if let Some(parsed) = DateValue::parse(value) {
+            let canonical = parsed.to_string();
+            if &canonical != value {
+                date.value = Some(canonical);
+                report.normalized += 1;
+            }
+        } else {
+            report.unparsed.push(UnparsedDate {
+                xref: xref.map(ToString::to_string),
+                value: value.clone(),
+            });
+        }
+    });
+
+    report
+}
+
+impl GedcomData {
+    /// Puts the tree in a canonical form, so that the exports of a tree from different
+    /// applications can be compared line by line:
+    ///
+    /// - the records of each kind are sorted by xref, comparing the numbers in xrefs by value, e.g.
+    ///   `@I2@` comes before `@I10@`
+    /// - the children of each family are ordered by birth date, while children with no known
+    ///   birth date keep their place
+    /// - the whitespace around names, dates, places, the payloads of events and attributes, and
+    ///   the titles of sources is trimmed, and runs of it are collapsed into a single space,
+    ///   keeping line breaks
+    ///
+    /// `GedcomWriter` already writes tags in the order of the specification. To write a
+    /// normalized copy of a tree instead, see `WriterOptions::normalize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @F1@ FAM\n\
+    ///     1 CHIL @I10@\n\
+    ///     1 CHIL @I2@\n\
+    ///     0 @I10@ INDI\n\
+    ///     1 NAME  Mary   /Doe/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 3 MAR 1852\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 NAME John /Doe/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 1850\n\
+    ///     2 PLAC London,  England\n\
+    ///     0 TRLR";
+    ///
+    /// let mut data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// data.normalize();
+    ///
+    /// let xrefs: Vec<_> = data.individuals.iter().map(|indi| indi.xref.as_deref()).collect();
+    /// assert_eq!(xrefs, vec![Some("@I2@"), Some("@I10@")]);
+    /// assert_eq!(data.families[0].children, vec!["@I2@", "@I10@"]);
+    ///
+    /// let mary = data.individuals[1].name.as_ref().unwrap();
+    /// assert_eq!(mary.value.as_deref(), Some("Mary /Doe/"));
+    /// let birth = &data.individuals[0].events[0];
+    /// assert_eq!(birth.place.as_deref(), Some("London, England"));
+    /// ```
+    pub fn normalize(&mut self) {
+        sort_by_xref(&mut self.individuals);
+        sort_by_xref(&mut self.families);
+        sort_by_xref(&mut self.sources);
+        sort_by_xref(&mut self.repositories);
+        sort_by_xref(&mut self.multimedia);
+        sort_by_xref(&mut self.notes);
+        sort_by_xref(&mut self.submitters);
+        sort_by_xref(&mut self.submissions);
+        self.order_children();
+        normalize_whitespace(self);
+    }
+
+    /// Orders the children of each family by birth date, leaving those without one in place
+    fn order_children(&mut self) {
+        let births: HashMap<&str, (i32, u8, u8)> = self
+            .individuals
+            .iter()
+            .filter_map(|indi| Some((indi.xref.as_deref()?, birth(indi)?)))
+            .collect();
+        for fam in &mut self.families {
+            let dated: Vec<usize> = (0..fam.children.len())
+                .filter(|i| births.contains_key(fam.children[*i].as_str()))
+                .collect();
+            let mut children: Vec<String> =
+                dated.iter().map(|i| fam.children[*i].clone()).collect();
+            children.sort_by_key(|child| births[child.as_str()]);
+            for (i, child) in dated.into_iter().zip(children) {
+                fam.children[i] = child;
+            }
+        }
+    }
+}
+
+/// `PrivacyOptions` configures `GedcomData::privatize`.
+#[derive(Clone, Debug)]
+pub struct PrivacyOptions {
+    /// The current year, which births are measured from
+    pub current_year: i32,
+    /// Individuals born fewer years than this before the current year, and with no death,
+    /// burial or cremation, are taken to be living. Defaults to 100.
+    pub years: u32,
+    /// Whether individuals with neither a birth date nor a death, burial or cremation are taken
+    /// to be living. Defaults to false.
+    pub undated_are_living: bool,
+    /// What becomes of the living individuals. Defaults to `PrivacyAction::Living`.
+    pub action: PrivacyAction,
+}
+
+impl PrivacyOptions {
+    /// The default options, measuring births from `current_year`
+    #[must_use]
+    pub fn new(current_year: i32) -> PrivacyOptions {
+        PrivacyOptions {
+            current_year,
+            years: 100,
+            undated_are_living: false,
+            action: PrivacyAction::Living,
+        }
+    }
+}
+
+/// `PrivacyAction` is what `GedcomData::privatize` does with living individuals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrivacyAction {
+    /// Removes them from the tree, along with the families left with no members
+    Remove,
+    /// Keeps their name, sex and family links only
+    NameOnly,
+    /// Keeps their sex and family links only, with "Living" as their name
+    Living,
+}
+
+impl GedcomData {
+    /// Hides the details of the individuals who are probably living, before publishing a tree,
+    /// returning their xrefs. An individual is taken to be living when they have no death,
+    /// burial or cremation, and were born, or else christened or baptised, fewer than
+    /// `PrivacyOptions::years` years ago. `PrivacyOptions::action` picks whether they are
+    /// removed, reduced to their name, or named "Living".
+    ///
+    /// The families with a living spouse lose their events, facts, notes, citations, multimedia
+    /// and user-defined structures, e.g. the date of a marriage, while keeping their members. When
+    /// the tree was parsed with `ParseOptions::preserve_lines`, the lines of these records which
+    /// the parser didn't keep are dropped too. Living individuals without an xref are hidden as
+    /// well, though they have no xref to return.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{
+    ///     edit::{PrivacyAction, PrivacyOptions},
+    ///     GedcomDocument,
+    /// };
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Smith/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 1890\n\
+    ///     1 DEAT\n\
+    ///     2 DATE 1961\n\
+    ///     1 FAMC @F1@\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 NAME Mary /Smith/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 12 MAY 1958\n\
+    ///     2 PLAC Leeds\n\
+    ///     1 FAMS @F1@\n\
+    ///     0 @F1@ FAM\n\
+    ///     1 WIFE @I2@\n\
+    ///     1 CHIL @I1@\n\
+    ///     1 MARR\n\
+    ///     2 DATE 1980\n\
+    ///     0 TRLR";
+    ///
+    /// let parse = || GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    ///
+    /// let mut living = parse();
+    /// assert_eq!(living.privatize(&PrivacyOptions::new(2026)), vec!["@I2@"]);
+    /// let mary = &living.individuals[1];
+    /// assert_eq!(mary.name.as_ref().unwrap().value.as_deref(), Some("Living"));
+    /// assert!(mary.events.is_empty());
+    /// assert_eq!(mary.families.len(), 1);
+    /// assert!(living.families[0].events.is_empty());
+    ///
+    /// let mut removed = parse();
+    /// let options = PrivacyOptions {
+    ///     action: PrivacyAction::Remove,
+    ///     ..PrivacyOptions::new(2026)
+    /// };
+    /// removed.privatize(&options);
+    /// assert_eq!(removed.individuals.len(), 1);
+    /// assert_eq!(removed.families[0].individual2, None);
+    /// ```
+    pub fn privatize(&mut self, options: &PrivacyOptions) -> Vec<String> {
+        let living: Vec<usize> = self
+            .individuals
+            .iter()
+            .enumerate()
+            .filter(|(_, indi)| is_probably_living(indi, options))
+            .map(|(i, _)| i)
+            .collect();
+        let xrefs: Vec<String> = living
+            .iter()
+            .filter_map(|&i| self.individuals[i].xref.clone())
+            .collect();
+        let living_xrefs: HashSet<&str> = xrefs.iter().map(String::as_str).collect();
+        let is_living = |xref: &Option<String>| {
+            xref.as_deref()
+                .is_some_and(|xref| living_xrefs.contains(xref))
+        };
+
+        let mut redacted_families = Vec::new();
+        for fam in &mut self.families {
+            if is_living(&fam.individual1) || is_living(&fam.individual2) {
+                fam.events.clear();
+                fam.family_event.clear();
+                fam.attributes.clear();
+                fam.notes.clear();
+                fam.sources.clear();
+                fam.multimedia.clear();
+                fam.custom_data.clear();
+                redacted_families.push(fam.xref.clone());
+            }
+        }
+        // the lines the parser didn't keep would otherwise be copied back into the output
+        if let Some(source_map) = &mut self.source_map {
+            for &i in &living {
+                source_map.redact("INDI", self.individuals[i].xref.as_deref());
+            }
+            for xref in &redacted_families {
+                source_map.redact("FAM", xref.as_deref());
+            }
+        }
+
+        match options.action {
+            PrivacyAction::Remove => {
+                // those without an xref have no links to remove along with them
+                for &i in living.iter().rev() {
+                    if self.individuals[i].xref.is_none() {
+                        self.individuals.remove(i);
+                    }
+                }
+                for xref in &xrefs {
+                    self.remove_individual(xref);
+                }
+                let empty: Vec<String> = self
+                    .families
+                    .iter()
+                    .filter(|fam| {
+                        fam.individual1.is_none()
+                            && fam.individual2.is_none()
+                            && fam.children.is_empty()
+                    })
+                    .filter_map(|fam| fam.xref.clone())
+                    .collect();
+                for xref in &empty {
+                    self.remove_family(xref);
+                }
+            }
+            PrivacyAction::NameOnly | PrivacyAction::Living => {
+                for &i in &living {
+                    let indi = &mut self.individuals[i];
+                    *indi = private_individual(indi, options.action);
+                }
+            }
+        }
+        xrefs
+    }
+}
+
+/// Whether the individual is taken to be living, see `GedcomData::privatize`
+fn is_probably_living(indi: &Individual, options: &PrivacyOptions) -> bool {
+    let died = indi
+        .events
+        .iter()
+        .any(|event| matches!(event.event, Event::Death | Event::Burial | Event::Cremation));
+    if died {
+        return false;
+    }
+    let born = [Event::Birth, Event::Christening, Event::Baptism]
+        .iter()
+        .filter_map(|event| event_date(indi, event))
+        .find_map(|date| date_part(&date).map(|part| part.year));
+    match born {
+        Some(year) => i64::from(options.current_year) - i64::from(year) < i64::from(options.years),
+        None => options.undated_are_living,
+    }
+}
+
+/// The individual reduced to what `action` keeps of it
+fn private_individual(indi: &Individual, action: PrivacyAction) -> Individual {
+    let name = match action {
+        PrivacyAction::Living => Some(Name {
+            value: Some("Living".to_string()),
+            given: None,
+            surname: None,
+            prefix: None,
+            surname_prefix: None,
+            notes: Vec::new(),
+            suffix: None,
+            source: Vec::new(),
+            name_type: None,
+            custom_data: Vec::new(),
+        }),
+        _ => indi.name.as_ref().map(|name| Name {
+            notes: Vec::new(),
+            source: Vec::new(),
+            ..name.clone()
+        }),
+    };
+    let sex = indi.sex.as_ref().map(|sex| Gender {
+        value: sex.value.clone(),
+        fact: None,
+        sources: Vec::new(),
+        notes: Vec::new(),
+        custom_data: Vec::new(),
+    });
+    let families = indi
+        .families
+        .iter()
+        .map(|link| FamilyLink {
+            notes: Vec::new(),
+            custom_data: Vec::new(),
+            ..link.clone()
+        })
+        .collect();
+    Individual {
+        xref: indi.xref.clone(),
+        name,
+        sex,
+        families,
+        ..Individual::default()
+    }
+}
+
+impl GedcomData {
+    /// Replaces the individual with the same xref as `individual`, returning the record replaced.
+    /// Nothing changes, and `None` is returned, when the tree has no such individual.
+    ///
+    /// The families are brought in line with the FAMC and FAMS links of the new record: they
+    /// gain the individual as a child or spouse where it links to them, and lose it where it no
+    /// longer does. Links to families missing from the tree are dropped, as are FAMS links to
+    /// families which already have two spouses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Smith/\n\
+    ///     1 FAMS @F1@\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 NAME Mary /Brown/\n\
+    ///     1 FAMC @F1@\n\
+    ///     0 @F1@ FAM\n\
+    ///     1 HUSB @I1@\n\
+    ///     1 CHIL @I2@\n\
+    ///     0 @F2@ FAM\n\
+    ///     0 TRLR";
+    ///
+    /// let mut data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// // Mary turns out to be John's wife rather than his daughter
+    /// let mut mary = data.individuals[1].clone();
+    /// mary.families[0].family_link_type = gedcom::types::FamilyLinkType::Spouse;
+    /// let old = data.replace_individual(mary).unwrap();
+    ///
+    /// assert_eq!(old.name.unwrap().value.as_deref(), Some("Mary /Brown/"));
+    /// assert!(data.families[0].children.is_empty());
+    /// assert_eq!(data.families[0].individual2.as_deref(), Some("@I2@"));
+    /// ```
+    pub fn replace_individual(&mut self, individual: Individual) -> Option<Individual> {
+        let index = self
+            .individuals
+            .iter()
+            .position(|indi| indi.xref.is_some() && indi.xref == individual.xref)?;
+        let old = std::mem::replace(&mut self.individuals[index], individual);
+        self.sync_family_links(index);
+        Some(old)
+    }
+
+    /// Removes the individual with the xref, returning it. It is taken out of the families it
+    /// was a spouse or child in.
+    pub fn remove_individual(&mut self, xref: &str) -> Option<Individual> {
+        let index = self
+            .individuals
+            .iter()
+            .position(|indi| indi.xref.as_deref() == Some(xref))?;
+        for fam in &mut self.families {
+            for spouse in [&mut fam.individual1, &mut fam.individual2] {
+                if spouse.as_deref() == Some(xref) {
+                    *spouse = None;
+                }
+            }
+            fam.children.retain(|child| child != xref);
+        }
+        Some(self.individuals.remove(index))
+    }
+
+    /// Removes the family with the xref, returning it. The FAMC and FAMS links of its members
+    /// are removed along with it, as are the links of events to it, e.g. of an adoption.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 FAMS @F1@\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 FAMC @F1@\n\
+    ///     0 @F1@ FAM\n\
+    ///     1 HUSB @I1@\n\
+    ///     1 CHIL @I2@\n\
+    ///     0 TRLR";
+    ///
+    /// let mut data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// assert!(data.remove_family("@F1@").is_some());
+    /// assert!(data.families.is_empty());
+    /// assert!(data.individuals.iter().all(|indi| indi.families.is_empty()));
+    /// assert!(data.remove_family("@F1@").is_none());
+    /// ```
+    pub fn remove_family(&mut self, xref: &str) -> Option<Family> {
+        let index = self
+            .families
+            .iter()
+            .position(|fam| fam.xref.as_deref() == Some(xref))?;
+        for indi in &mut self.individuals {
+            indi.families.retain(|link| link.xref != xref);
+            for event in &mut indi.events {
+                if event
+                    .family_link
+                    .as_ref()
+                    .is_some_and(|link| link.xref == xref)
+                {
+                    event.family_link = None;
+                }
+            }
+        }
+        Some(self.families.remove(index))
+    }
+
+    /// Adds the individual to the children of the family, with both the CHIL line of the family
+    /// and the FAMC link of the individual. Returns false, changing nothing, when either record is
+    /// missing from the tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     0 @F1@ FAM\n\
+    ///     0 TRLR";
+    ///
+    /// let mut data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// assert!(data.add_child_to_family("@F1@", "@I1@"));
+    /// assert_eq!(data.families[0].children, vec!["@I1@"]);
+    /// assert_eq!(data.individuals[0].families[0].xref, "@F1@");
+    /// assert!(!data.add_child_to_family("@F2@", "@I1@"));
+    /// ```
+    pub fn add_child_to_family(&mut self, family: &str, child: &str) -> bool {
+        let fam = self
+            .families
+            .iter_mut()
+            .find(|fam| fam.xref.as_deref() == Some(family));
+        let indi = self
+            .individuals
+            .iter_mut()
+            .find(|indi| indi.xref.as_deref() == Some(child));
+        let (Some(fam), Some(indi)) = (fam, indi) else {
+            return false;
+        };
+        if !fam.children.iter().any(|xref| xref == child) {
+            fam.add_child(child.to_string());
+        }
+        if !indi
+            .families
+            .iter()
+            .any(|link| is_link(link, family, false))
+        {
+            indi.families
+                .push(family_link(family, FamilyLinkType::Child));
+        }
+        true
+    }
+
+    /// Makes the families agree with the FAMC and FAMS links of an individual, see
+    /// `replace_individual`
+    fn sync_family_links(&mut self, index: usize) {
+        let indi = &mut self.individuals[index];
+        let families = &mut self.families;
+        let Some(xref) = indi.xref.clone() else {
+            return;
+        };
+        let female = indi.sex.as_ref().is_some_and(|sex| sex.value.tag() == "F");
+        indi.families.retain(|link| {
+            families
+                .iter()
+                .any(|fam| fam.xref.as_deref() == Some(link.xref.as_str()))
+        });
+
+        for fam in families.iter_mut() {
+            let Some(family) = fam.xref.clone() else {
+                continue;
+            };
+            let spouse = indi
+                .families
+                .iter()
+                .any(|link| is_link(link, &family, true));
+            let child = indi
+                .families
+                .iter()
+                .any(|link| is_link(link, &family, false));
+
+            let slots = if female {
+                [&mut fam.individual2, &mut fam.individual1]
+            } else {
+                [&mut fam.individual1, &mut fam.individual2]
+            };
+            let [first, second] = slots;
+            let is_spouse = first.as_deref() == Some(&xref) || second.as_deref() == Some(&xref);
+            if spouse && !is_spouse {
+                match (first.is_none(), second.is_none()) {
+                    (true, _) => *first = Some(xref.clone()),
+                    (false, true) => *second = Some(xref.clone()),
+                    (false, false) => indi.families.retain(|link| !is_link(link, &family, true)),
+                }
+            } else if !spouse && is_spouse {
+                for slot in [first, second] {
+                    if slot.as_deref() == Some(&xref) {
+                        *slot = None;
+                    }
+                }
+            }
+
+            let is_child = fam.children.contains(&xref);
+            if child && !is_child {
+                fam.add_child(xref.clone());
+            } else if !child && is_child {
+                fam.children.retain(|member| *member != xref);
+            }
+        }
+    }
+}
+
+/// Whether the link points to the family, as a FAMS link if `spouse` or else as a FAMC one
+pub(crate) fn is_link(link: &FamilyLink, family: &str, spouse: bool) -> bool {
+    link.xref == family && matches!(link.family_link_type, FamilyLinkType::Spouse) == spouse
+}
+
+pub(crate) fn family_link(family: &str, family_link_type: FamilyLinkType) -> FamilyLink {
+    FamilyLink {
+        xref: family.to_string(),
+        family_link_type,
+        pedigree_linkage_type: None,
+        child_linkage_status: None,
+        adopted_by: None,
+        father_relationship: None,
+        mother_relationship: None,
+        notes: Vec::new(),
+        custom_data: Vec::new(),
+    }
+}
+
+/// The birth date of an individual, as year, month and day, for sorting
+fn birth(indi: &Individual) -> Option<(i32, u8, u8)> {
+    let date = event_date(indi, &Event::Birth)?;
+    let part = date_part(&date)?;
+    Some((
+        part.year,
+        part.month.unwrap_or_default(),
+        part.day.unwrap_or_default(),
+    ))
+}
+
+/// Sorts records by xref, see `compare_xrefs`. Records without an xref come last.
+fn sort_by_xref<T: Record>(records: &mut [T]) {
+    records.sort_by(|a, b| match (a.xref(), b.xref()) {
+        (Some(a), Some(b)) => compare_xrefs(a, b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+}
+
+/// Compares xrefs as text, except for runs of digits, which are compared by value
+fn compare_xrefs(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let (x, rest_a) = split_digits(a);
+            let (y, rest_b) = split_digits(b);
+            let ordering = x
+                .trim_start_matches('0')
+                .len()
+                .cmp(&y.trim_start_matches('0').len())
+                .then_with(|| x.trim_start_matches('0').cmp(y.trim_start_matches('0')))
+                .then_with(|| x.len().cmp(&y.len()));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            a = rest_a;
+            b = rest_b;
+        } else if x != y {
+            return x.cmp(&y);
+        } else {
+            a = &a[x.len_utf8()..];
+            b = &b[y.len_utf8()..];
+        }
+    }
+}
+
+fn split_digits(text: &str) -> (&str, &str) {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    text.split_at(end)
+}
+
+/// Trims the whitespace around values, and collapses runs of it, see `GedcomData::normalize`
+fn normalize_whitespace(data: &mut GedcomData) {
+    // line breaks from CONT lines are kept
+    let collapsed = |value: &str| {
+        value
+            .split('\n')
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let collapse = |value: &mut Option<String>| {
+        if let Some(value) = value {
+            *value = collapsed(value);
+        }
+    };
+    // values already collapsed keep sharing the string pool
+    let collapse_shared = |value: &mut Option<SharedStr>| {
+        if let Some(value) = value {
+            let normal = collapsed(value);
+            if *value != normal {
+                *value = normal.into();
+            }
+        }
+    };
+    let event = |event: &mut EventDetail| {
+        collapse(&mut event.value);
+        collapse_shared(&mut event.place);
+        collapse(&mut event.event_type);
+        collapse(&mut event.cause);
+        collapse(&mut event.age);
+    };
+    let attribute = |attr: &mut AttributeDetail| {
+        collapse(&mut attr.value);
+        collapse_shared(&mut attr.place);
+        collapse(&mut attr.attribute_type);
+    };
+    let name = |name: &mut Name| {
+        collapse(&mut name.value);
+        collapse(&mut name.given);
+        collapse_shared(&mut name.surname);
+        collapse(&mut name.prefix);
+        collapse(&mut name.surname_prefix);
+        collapse(&mut name.suffix);
+    };
+
+    visit_dates(data, &mut |_, date| collapse(&mut date.value));
+    for indi in &mut data.individuals {
+        indi.name
+            .iter_mut()
+            .chain(&mut indi.alternate_names)
+            .for_each(name);
+        indi.events.iter_mut().for_each(event);
+        indi.attributes.iter_mut().for_each(attribute);
+    }
+    for fam in &mut data.families {
+        fam.events.iter_mut().for_each(event);
+        fam.family_event.iter_mut().for_each(event);
+        fam.attributes.iter_mut().for_each(attribute);
+    }
+    for sour in &mut data.sources {
+        collapse(&mut sour.title);
+        collapse(&mut sour.abbreviation);
+        collapse(&mut sour.author);
+        for recorded in &mut sour.data.events {
+            collapse_shared(&mut recorded.place);
+        }
+    }
+    for repo in &mut data.repositories {
+        collapse(&mut repo.name);
+    }
+    for subm in &mut data.submitters {
+        collapse(&mut subm.name);
+    }
+}
+
+/// Calls `f` with every `Date` in the tree, along with the xref of the record containing it.
+fn visit_dates(data: &mut GedcomData, f: &mut dyn FnMut(Option<&str>, &mut Date)) {
+    if let Some(header) = &mut data.header {
+        if let Some(date) = &mut header.date {
+            f(None, date);
+        }
+        if let Some(date) = header
+            .source
+            .as_mut()
+            .and_then(|sour| sour.data.as_mut())
+            .and_then(|data| data.date.as_mut())
+        {
+            f(None, date);
+        }
+    }
+
+    for indi in &mut data.individuals {
+        let xref = indi.xref.as_deref();
+        for event in &mut indi.events {
+            event_dates(xref, event, f);
+        }
+        for attr in &mut indi.attributes {
+            if let Some(date) = &mut attr.date {
+                f(xref, date);
+            }
+            citation_dates(xref, &mut attr.sources, f);
+        }
+        for name in indi.name.iter_mut().chain(&mut indi.alternate_names) {
+            citation_dates(xref, &mut name.source, f);
+        }
+        if let Some(sex) = &mut indi.sex {
+            citation_dates(xref, &mut sex.sources, f);
+        }
+        citation_dates(xref, &mut indi.source, f);
+        change_dates(xref, indi.change_date.as_mut(), f);
+        change_dates(xref, indi.created.as_mut(), f);
+    }
+
+    for fam in &mut data.families {
+        let xref = fam.xref.as_deref();
+        for event in fam.events.iter_mut().chain(fam.family_event.iter_mut()) {
+            event_dates(xref, event, f);
+        }
+        for attr in &mut fam.attributes {
+            if let Some(date) = &mut attr.date {
+                f(xref, date);
+            }
+            citation_dates(xref, &mut attr.sources, f);
+        }
+        citation_dates(xref, &mut fam.sources, f);
+        change_dates(xref, fam.change_date.as_mut(), f);
+        change_dates(xref, fam.created.as_mut(), f);
+    }
+
+    for sour in &mut data.sources {
+        let xref = sour.xref.as_deref();
+        for date in sour
+            .data
+            .events
+            .iter_mut()
+            .filter_map(|recorded| recorded.date.as_mut())
+        {
+            f(xref, date);
+        }
+        change_dates(xref, sour.change_date.as_deref_mut(), f);
+        change_dates(xref, sour.created.as_deref_mut(), f);
+    }
+
+    for subm in &mut data.submitters {
+        change_dates(subm.xref.as_deref(), subm.change_date.as_mut(), f);
+        change_dates(subm.xref.as_deref(), subm.created.as_mut(), f);
+    }
+
+    for subn in &mut data.submissions {
+        change_dates(subn.xref.as_deref(), subn.change_date.as_mut(), f);
+    }
+
+    for obje in &mut data.multimedia {
+        change_dates(obje.xref.as_deref(), obje.change_date.as_mut(), f);
+        change_dates(obje.xref.as_deref(), obje.created.as_mut(), f);
+    }
+}
+
+fn event_dates(
+    xref: Option<&str>,
+    event: &mut EventDetail,
+    f: &mut dyn FnMut(Option<&str>, &mut Date),
+) {
+    if let Some(date) = &mut event.date {
+        f(xref, date);
+    }
+    citation_dates(xref, &mut event.citations, f);
+}
+
+fn citation_dates(
+    xref: Option<&str>,
+    citations: &mut [SourceCitation],
+    f: &mut dyn FnMut(Option<&str>, &mut Date),
+) {
+    for citation in citations {
+        if let Some(date) = citation.data.as_mut().and_then(|data| data.date.as_mut()) {
+            f(xref, date);
+        }
+    }
+}
+
+fn change_dates(
+    xref: Option<&str>,
+    change_date: Option<&mut ChangeDate>,
+    f: &mut dyn FnMut(Option<&str>, &mut Date),
+) {
+    if let Some(date) = change_date.and_then(|chan| chan.date.as_mut()) {
+        f(xref, date);
+    }
+}
@@ -0,0 +1,208 @@
+//! Errors and diagnostics raised while parsing a GEDCOM file.
+
+use std::{error::Error, fmt, io};
+
+/// `Span` locates a piece of text within the GEDCOM file, e.g. a token or the offending part of a
+/// line, so tools can point at it.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{GedcomDocument, Span};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 SEX Z\n\
+///     0 TRLR";
+///
+/// let err = GedcomDocument::new(sample.chars()).parse_document().unwrap_err();
+/// let span = err.span();
+/// assert_eq!(span, Span { line: 5, column: 7, offset: 43, len: 1 });
+/// assert_eq!(&sample[span.offset..span.offset + span.len], "Z");
+/// assert_eq!(err.to_string(), "line 5, column 7: Invalid SEX value: Z");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// Line number, starting at 1
+    pub line: u32,
+    /// Column within the line in characters, starting at 1
+    pub column: u32,
+    /// Byte offset from the start of the file
+    pub offset: usize,
+    /// Length in bytes
+    pub len: usize,
+}
+
+/// `GedcomError` describes a problem found while parsing a GEDCOM file. In the default strict mode
+/// the first problem fails the parse; in lenient mode (see `ParseOptions`) problems within a record
+/// are collected as warnings and the offending structure is skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GedcomError {
+    /// The file contents don't follow the `gedcom_line` grammar, e.g. a line without a level, or
+    /// the file ends before the TRLR record
+    ParseError {
+        /// Where the problem was found
+        span: Span,
+        /// Description of the problem
+        message: String,
+    },
+    /// A standard tag appeared where the parser doesn't expect it
+    UnhandledTag {
+        /// Location of the tag
+        span: Span,
+        /// The structure being parsed, e.g. "Individual"
+        structure: &'static str,
+        /// The unexpected tag
+        tag: String,
+    },
+    /// A tag has a payload outside of its set of allowed values, e.g. `1 SEX Z`
+    InvalidValue {
+        /// Location of the value
+        span: Span,
+        /// The tag carrying the value
+        tag: &'static str,
+        /// The invalid payload
+        value: String,
+    },
+    /// A substructure was dropped because the model has nowhere to keep it, e.g. a SOUR under a
+    /// `1 SEX M` line. Only ever reported as a warning.
+    DroppedSubstructure {
+        /// Location of the dropped tag
+        span: Span,
+        /// The tag of the line the substructure belongs to
+        parent: String,
+        /// The dropped tag, whose own substructures are dropped along with it
+        tag: String,
+    },
+    /// A standard tag wasn't in uppercase, e.g. `1 birt`, and was read as its uppercase form, see
+    /// `ParseOptions::case_insensitive_tags`. Only ever reported as a warning.
+    MiscasedTag {
+        /// Location of the tag
+        span: Span,
+        /// The tag as written in the file
+        tag: String,
+        /// The standard tag it was read as
+        normalized: &'static str,
+    },
+    /// The parse was cancelled through a `CancellationToken`, before the record starting at
+    /// `span`. The records parsed until then are kept, see `GedcomDocument::take_partial_data`.
+    Cancelled {
+        /// Location of the first record left unparsed
+        span: Span,
+    },
+}
+
+impl GedcomError {
+    /// Where the problem was found
+    #[must_use]
+    pub fn span(&self) -> Span {
+        match self {
+            GedcomError::ParseError { span, .. }
+            | GedcomError::UnhandledTag { span, .. }
+            | GedcomError::InvalidValue { span, .. }
+            | GedcomError::DroppedSubstructure { span, .. }
+            | GedcomError::MiscasedTag { span, .. }
+            | GedcomError::Cancelled { span } => *span,
+        }
+    }
+
+    /// Line number the problem was found on
+    #[must_use]
+    pub fn line(&self) -> u32 {
+        self.span().line
+    }
+}
+
+impl fmt::Display for GedcomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Span { line, column, .. } = self.span();
+        write!(f, "line {line}, column {column}: ")?;
+        match self {
+            GedcomError::ParseError { message, .. } => write!(f, "{message}"),
+            GedcomError::UnhandledTag { structure, tag, .. } => {
+                write!(f, "Unhandled {structure} tag: {tag}")
+            }
+            GedcomError::InvalidValue { tag, value, .. } => {
+                write!(f, "Invalid {tag} value: {value}")
+            }
+            GedcomError::DroppedSubstructure { parent, tag, .. } => {
+                write!(f, "Dropped {tag} substructure of {parent}")
+            }
+            GedcomError::MiscasedTag {
+                tag, normalized, ..
+            } => write!(f, "Tag {tag} read as {normalized}"),
+            GedcomError::Cancelled { .. } => write!(f, "Parse cancelled"),
+        }
+    }
+}
+
+impl Error for GedcomError {}
+
+/// `RecordError` is a top-level record which failed to parse and was skipped, with
+/// `ParseOptions::skip_broken_records`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordError {
+    /// The xref of the record, if it has one
+    pub xref: Option<String>,
+    /// The line the record starts on
+    pub line: u32,
+    /// The problem which failed the record
+    pub cause: GedcomError,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.xref {
+            Some(xref) => write!(f, "skipped record {xref} at line {}: ", self.line)?,
+            None => write!(f, "skipped record at line {}: ", self.line)?,
+        }
+        self.cause.fmt(f)
+    }
+}
+
+impl Error for RecordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+/// `ParseFileError` is returned by `parse_file`, when a file can't be read or can't be parsed.
+#[derive(Debug)]
+pub enum ParseFileError {
+    /// The file couldn't be read or decompressed
+    Io(io::Error),
+    /// The contents of the file couldn't be parsed
+    Parse(GedcomError),
+}
+
+impl fmt::Display for ParseFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseFileError::Io(err) => write!(f, "failed to read file: {err}"),
+            ParseFileError::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ParseFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseFileError::Io(err) => Some(err),
+            ParseFileError::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for ParseFileError {
+    fn from(err: io::Error) -> ParseFileError {
+        ParseFileError::Io(err)
+    }
+}
+
+impl From<GedcomError> for ParseFileError {
+    fn from(err: GedcomError) -> ParseFileError {
+        ParseFileError::Parse(err)
+    }
+}
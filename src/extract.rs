@@ -0,0 +1,512 @@
+//! Extraction of part of a tree into a standalone `GedcomData`, e.g. for a one-place study of
+//! everyone who lived in a village, or a one-name study of everyone bearing a surname.
+//!
+//! The extracted tree is consistent on its own:
+//! - individuals are copied whole, with their links to families left out removed,
+//! - families are kept when one of their spouses or children is, with links to individuals left
+//!   out removed,
+//! - associations, e.g. witnesses, with individuals left out lose their pointer but keep the
+//!   name of the person,
+//! - the sources, repositories, multimedia records and shared notes pointed to are kept, along
+//!   with the header, submitters and submissions.
+//!
+//! `GedcomData::extract` takes a branch of the tree instead: an individual along with some
+//! generations of their ancestors and descendants, e.g. to share with a cousin.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{extract, search::SearchMode, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 BIRT\n\
+//!     2 PLAC Lavenham, Suffolk, England\n\
+//!     2 SOUR @S1@\n\
+//!     1 FAMS @F1@\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Mary /Brown/\n\
+//!     1 FAMS @F1@\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @I1@\n\
+//!     1 WIFE @I2@\n\
+//!     0 @S1@ SOUR\n\
+//!     1 TITL Lavenham parish registers\n\
+//!     0 @S2@ SOUR\n\
+//!     1 TITL Unrelated source\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//!
+//! let study = extract::one_place(&data, "lavenham");
+//! assert_eq!(study.individuals.len(), 1);
+//! assert_eq!(study.families[0].individual2, None);
+//! assert_eq!(study.sources.len(), 1);
+//!
+//! let study = extract::one_name(&data, "Smyth", SearchMode::Soundex);
+//! assert_eq!(study.individuals[0].xref.as_deref(), Some("@I1@"));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    intern::SharedStr,
+    search::{word_score, IndexedWord, SearchMode},
+    stats::surname,
+    types::{EventDetail, Family, FamilyLink, FamilyLinkType, MultimediaRecord},
+    util::{is_record_pointer, LineFields},
+    GedcomData,
+};
+
+/// Extracts the individuals with an event or attribute at the given place, along with the spouses
+/// of families with an event there and the people associated with those events, e.g. witnesses.
+///
+/// Places are compared by their comma-separated jurisdictions, ignoring case: "Lavenham" and
+/// "Lavenham, Suffolk" both match "Lavenham, Suffolk, England", but "Lav" doesn't.
+#[must_use]
+pub fn one_place(data: &GedcomData, place: &str) -> GedcomData {
+    let query = jurisdictions(place);
+    let at_place = |place: &Option<SharedStr>| {
+        let place = jurisdictions(place.as_deref().unwrap_or_default());
+        !query.is_empty()
+            && place
+                .windows(query.len())
+                .any(|window| window == &query[..])
+    };
+
+    let mut selected: Vec<&str> = Vec::new();
+    for indi in &data.individuals {
+        let Some(xref) = indi.xref.as_deref() else {
+            continue;
+        };
+        let events: Vec<&EventDetail> = indi
+            .events
+            .iter()
+            .filter(|event| at_place(&event.place))
+            .collect();
+        if !events.is_empty() || indi.attributes.iter().any(|attr| at_place(&attr.place)) {
+            selected.push(xref);
+        }
+        selected.extend(events.into_iter().flat_map(associated));
+    }
+    for fam in &data.families {
+        let events: Vec<&EventDetail> = fam
+            .events
+            .iter()
+            .chain(&fam.family_event)
+            .filter(|event| at_place(&event.place))
+            .collect();
+        if !events.is_empty() || fam.attributes.iter().any(|attr| at_place(&attr.place)) {
+            selected.extend(fam.individual1.as_deref());
+            selected.extend(fam.individual2.as_deref());
+        }
+        selected.extend(events.into_iter().flat_map(associated));
+    }
+    individuals(data, selected)
+}
+
+/// Extracts the individuals whose surname matches `surname`. The search mode sets which variants
+/// are included, e.g. `SearchMode::Soundex` gathers "Smith", "Smyth" and "Schmidt".
+#[must_use]
+pub fn one_name(data: &GedcomData, surname: &str, mode: SearchMode) -> GedcomData {
+    let query = IndexedWord::new(surname);
+    let selected = data.individuals.iter().filter_map(|indi| {
+        let candidate = IndexedWord::new(&self::surname(indi)?);
+        word_score(&query, &candidate, mode)?;
+        indi.xref.as_deref()
+    });
+    individuals(data, selected)
+}
+
+/// `ExtractOptions` picks the relatives `GedcomData::extract` takes along with an individual.
+#[derive(Clone, Copy, Debug)]
+pub struct ExtractOptions {
+    /// The number of generations of ancestors taken, through all the families the individuals
+    /// are a child in. Defaults to 0.
+    pub ancestors: usize,
+    /// The number of generations of descendants taken, through all the families the individuals
+    /// are a spouse in. Defaults to 0.
+    pub descendants: usize,
+    /// Whether the spouses of the individuals taken are taken too, e.g. the wives of sons.
+    /// Defaults to true.
+    pub include_spouses: bool,
+    /// Whether the sources cited are kept, along with their repositories. Without them, the
+    /// citations are removed too. Defaults to true.
+    pub include_sources: bool,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions {
+            ancestors: 0,
+            descendants: 0,
+            include_spouses: true,
+            include_sources: true,
+        }
+    }
+}
+
+impl GedcomData {
+    /// Extracts the branch of the tree around the individual `root`: their ancestors and
+    /// descendants up to the number of generations in `options`, along with the spouses of all
+    /// those taken. The extracted tree is consistent on its own, see the `extract` module. It
+    /// holds no individuals when there is no individual `root`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{extract::ExtractOptions, GedcomDocument};
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Smith/\n\
+    ///     1 FAMS @F1@\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 NAME Mary /Brown/\n\
+    ///     1 FAMS @F1@\n\
+    ///     0 @I3@ INDI\n\
+    ///     1 NAME Tom /Smith/\n\
+    ///     1 FAMC @F1@\n\
+    ///     1 BIRT\n\
+    ///     2 SOUR @S1@\n\
+    ///     0 @F1@ FAM\n\
+    ///     1 HUSB @I1@\n\
+    ///     1 WIFE @I2@\n\
+    ///     1 CHIL @I3@\n\
+    ///     0 @S1@ SOUR\n\
+    ///     1 TITL Parish registers\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    ///
+    /// let options = ExtractOptions {
+    ///     descendants: 1,
+    ///     include_spouses: false,
+    ///     ..ExtractOptions::default()
+    /// };
+    /// let branch = data.extract("@I1@", options);
+    /// assert_eq!(branch.individuals.len(), 2);
+    /// assert_eq!(branch.families[0].individual2, None);
+    /// assert_eq!(branch.sources.len(), 1);
+    ///
+    /// let options = ExtractOptions {
+    ///     ancestors: 1,
+    ///     include_sources: false,
+    ///     ..ExtractOptions::default()
+    /// };
+    /// let branch = data.extract("@I3@", options);
+    /// assert_eq!(branch.individuals.len(), 3);
+    /// assert!(branch.sources.is_empty());
+    /// assert!(branch.individuals[2].events[0].citations.is_empty());
+    /// ```
+    #[must_use]
+    pub fn extract(&self, root: &str, options: ExtractOptions) -> GedcomData {
+        let families: HashMap<&str, &Family> = self
+            .families
+            .iter()
+            .filter_map(|fam| Some((fam.xref.as_deref()?, fam)))
+            .collect();
+        let links: HashMap<&str, &[FamilyLink]> = self
+            .individuals
+            .iter()
+            .filter_map(|indi| Some((indi.xref.as_deref()?, indi.families.as_slice())))
+            .collect();
+        // the families the individual is a child or spouse in
+        let linked = |xref: &str, spouse: bool| -> Vec<&Family> {
+            links
+                .get(xref)
+                .copied()
+                .unwrap_or_default()
+                .iter()
+                .filter(|link| matches!(link.family_link_type, FamilyLinkType::Spouse) == spouse)
+                .filter_map(|link| families.get(link.xref.as_str()).copied())
+                .collect()
+        };
+
+        let Some(root) = links.get_key_value(root).map(|(xref, _)| *xref) else {
+            return individuals_with(self, [], options.include_sources);
+        };
+        let mut selected = vec![root];
+        let mut generation = vec![root];
+        for _ in 0..options.ancestors {
+            generation = generation
+                .into_iter()
+                .flat_map(|xref| linked(xref, false))
+                .flat_map(|fam| fam.individual1.iter().chain(&fam.individual2))
+                .map(String::as_str)
+                .filter(|xref| !selected.contains(xref))
+                .collect();
+            selected.extend(&generation);
+        }
+        let mut generation = vec![root];
+        for _ in 0..options.descendants {
+            generation = generation
+                .into_iter()
+                .flat_map(|xref| linked(xref, true))
+                .flat_map(|fam| &fam.children)
+                .map(String::as_str)
+                .filter(|xref| !selected.contains(xref))
+                .collect();
+            selected.extend(&generation);
+        }
+        if options.include_spouses {
+            let spouses: Vec<&str> = selected
+                .iter()
+                .flat_map(|xref| linked(xref, true))
+                .flat_map(|fam| fam.individual1.iter().chain(&fam.individual2))
+                .map(String::as_str)
+                .collect();
+            selected.extend(spouses);
+        }
+        individuals_with(self, selected, options.include_sources)
+    }
+}
+
+/// Extracts the individuals with the given xrefs, see the module documentation for what else is
+/// kept.
+#[must_use]
+pub fn individuals<'a>(data: &GedcomData, xrefs: impl IntoIterator<Item = &'a str>) -> GedcomData {
+    individuals_with(data, xrefs, true)
+}
+
+/// Extracts the individuals with the given xrefs, removing the citations of sources unless
+/// `include_sources` is set
+fn individuals_with<'a>(
+    data: &GedcomData,
+    xrefs: impl IntoIterator<Item = &'a str>,
+    include_sources: bool,
+) -> GedcomData {
+    let selected: HashSet<&str> = xrefs.into_iter().collect();
+    let names: HashMap<&str, &str> = data
+        .individuals
+        .iter()
+        .filter_map(|indi| {
+            let name = indi.name.as_ref()?.value.as_deref()?;
+            Some((indi.xref.as_deref()?, name))
+        })
+        .collect();
+
+    let families: Vec<Family> = data
+        .families
+        .iter()
+        .filter(|fam| {
+            fam.individual1
+                .iter()
+                .chain(&fam.individual2)
+                .chain(&fam.children)
+                .any(|xref| selected.contains(xref.as_str()))
+        })
+        .cloned()
+        .collect();
+    let kept_families: HashSet<String> =
+        families.iter().filter_map(|fam| fam.xref.clone()).collect();
+
+    let mut extracted = GedcomData {
+        header: data.header.clone(),
+        submitters: data.submitters.clone(),
+        submissions: data.submissions.clone(),
+        individuals: data
+            .individuals
+            .iter()
+            .filter(|indi| {
+                indi.xref
+                    .as_deref()
+                    .is_some_and(|xref| selected.contains(xref))
+            })
+            .cloned()
+            .collect(),
+        families,
+        ..GedcomData::default()
+    };
+
+    for indi in &mut extracted.individuals {
+        indi.families
+            .retain(|link| kept_families.contains(&link.xref));
+        for event in &mut indi.events {
+            if event
+                .family_link
+                .as_ref()
+                .is_some_and(|link| !kept_families.contains(&link.xref))
+            {
+                event.family_link = None;
+            }
+        }
+        prune_associations(&mut indi.events, &selected, &names);
+    }
+    for fam in &mut extracted.families {
+        let kept =
+            |xref: &Option<String>| xref.as_deref().is_some_and(|xref| selected.contains(xref));
+        if !kept(&fam.individual1) {
+            fam.individual1 = None;
+        }
+        if !kept(&fam.individual2) {
+            fam.individual2 = None;
+        }
+        fam.children.retain(|xref| selected.contains(xref.as_str()));
+        prune_associations(&mut fam.events, &selected, &names);
+        prune_associations(&mut fam.family_event, &selected, &names);
+    }
+    if !include_sources {
+        remove_citations(&mut extracted);
+    }
+    add_pointed_records(data, &mut extracted, include_sources);
+    extracted
+}
+
+/// Adds the sources, repositories, multimedia records and shared notes of `data` pointed to from
+/// `extracted`, leaving out sources and repositories unless `include_sources` is set
+fn add_pointed_records(data: &GedcomData, extracted: &mut GedcomData, include_sources: bool) {
+    let count = |data: &GedcomData| {
+        data.sources.len() + data.repositories.len() + data.multimedia.len() + data.notes.len()
+    };
+    // sources point to repositories, multimedia and notes, so keep going until nothing is added
+    loop {
+        let pointers = pointers(extracted);
+        let pointed =
+            |xref: &Option<String>| xref.as_ref().is_some_and(|xref| pointers.contains(xref));
+        let before = count(extracted);
+        extracted.sources = data
+            .sources
+            .iter()
+            .filter(|sour| include_sources && pointed(&sour.xref))
+            .cloned()
+            .collect();
+        extracted.repositories = data
+            .repositories
+            .iter()
+            .filter(|repo| pointed(&repo.xref))
+            .cloned()
+            .collect();
+        extracted.multimedia = data
+            .multimedia
+            .iter()
+            .filter(|obje| pointed(&obje.xref))
+            .cloned()
+            .map(|mut obje| {
+                if !include_sources {
+                    obje.source_citation = None;
+                }
+                obje
+            })
+            .collect();
+        extracted.notes = data
+            .notes
+            .iter()
+            .filter(|note| pointed(&note.xref))
+            .cloned()
+            .map(|mut note| {
+                if !include_sources {
+                    note.sources.clear();
+                }
+                note
+            })
+            .collect();
+        if count(extracted) == before {
+            break;
+        }
+    }
+}
+
+/// The jurisdictions of a place, e.g. "lavenham", "suffolk" and "england"
+fn jurisdictions(place: &str) -> Vec<String> {
+    place
+        .split(',')
+        .map(|part| part.trim().to_lowercase())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// The xrefs of the people associated with an event
+fn associated(event: &EventDetail) -> impl Iterator<Item = &str> {
+    event
+        .associations
+        .iter()
+        .filter_map(|asso| asso.value.as_deref())
+        .filter(|value| is_record_pointer(value))
+}
+
+/// Drops the pointers of associations with individuals left out, keeping their name instead.
+fn prune_associations(
+    events: &mut [EventDetail],
+    selected: &HashSet<&str>,
+    names: &HashMap<&str, &str>,
+) {
+    for asso in events.iter_mut().flat_map(|event| &mut event.associations) {
+        let Some(value) = asso.value.as_deref() else {
+            continue;
+        };
+        if is_record_pointer(value) && !selected.contains(value) {
+            if asso.name.is_none() {
+                asso.name = names.get(value).map(|name| (*name).to_string());
+            }
+            asso.value = None;
+        }
+    }
+}
+
+/// Removes the citations of sources from the individuals and families of the tree
+fn remove_citations(data: &mut GedcomData) {
+    fn events(events: &mut [EventDetail]) {
+        for event in events {
+            event.citations.clear();
+            multimedia(&mut event.multimedia);
+            for asso in &mut event.associations {
+                asso.citations.clear();
+            }
+        }
+    }
+    fn multimedia(multimedia: &mut [MultimediaRecord]) {
+        for obje in multimedia {
+            obje.source_citation = None;
+        }
+    }
+
+    for indi in &mut data.individuals {
+        indi.source.clear();
+        for name in indi.name.iter_mut().chain(&mut indi.alternate_names) {
+            name.source.clear();
+        }
+        if let Some(sex) = &mut indi.sex {
+            sex.sources.clear();
+        }
+        events(&mut indi.events);
+        for attr in &mut indi.attributes {
+            attr.sources.clear();
+        }
+        multimedia(&mut indi.multimedia);
+        #[cfg(feature = "lds")]
+        for ordinance in &mut indi.lds_ordinances {
+            ordinance.sources.clear();
+        }
+    }
+    for fam in &mut data.families {
+        fam.sources.clear();
+        events(&mut fam.events);
+        events(&mut fam.family_event);
+        for attr in &mut fam.attributes {
+            attr.sources.clear();
+        }
+        multimedia(&mut fam.multimedia);
+        #[cfg(feature = "lds")]
+        for ordinance in &mut fam.lds_ordinances {
+            ordinance.sources.clear();
+        }
+    }
+}
+
+/// The pointers found in the tree as written out by `GedcomWriter`
+fn pointers(data: &GedcomData) -> HashSet<String> {
+    data.to_gedcom_string()
+        .lines()
+        .map(LineFields::split)
+        // continued text can look like a pointer
+        .filter(|line| !matches!(line.tag, Some("CONT" | "CONC")))
+        .filter_map(|line| line.value.filter(|value| is_record_pointer(value)))
+        .map(str::to_string)
+        .collect()
+}
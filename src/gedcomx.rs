@@ -0,0 +1,504 @@
+//! Export to the [GEDCOM X](http://www.gedcomx.org) conceptual model, enabled with the
+//! `"gedcomx"` feature.
+//!
+//! Unlike the `"json"` feature, which serializes `GedcomData` as it is, the export reshapes the
+//! tree into GEDCOM X persons, relationships and source descriptions, serialized with the GEDCOM X
+//! JSON property names so the output can be sent to FamilySearch-compatible APIs.
+//!
+//! The mapping is opinionated and lossy:
+//! * Ids are the xrefs without their `@` delimiters, and references are local, e.g. `#I1`.
+//! * Each family becomes a `Couple` relationship between its spouses, plus a `ParentChild`
+//!   relationship between each spouse and each child. Family events become facts of the couple.
+//! * Events and attributes become facts, using the GEDCOM X fact type where there is one and a
+//!   `data:` URI of the GEDCOM tag otherwise, or of the TYPE of a generic EVEN or FACT. Dates and
+//!   places are kept as their original text.
+//! * A SEX of `X` has no GEDCOM X equivalent and is exported as `Unknown`.
+//! * Notes, multimedia, repositories and custom tags aren't exported.
+
+use serde::Serialize;
+
+use crate::{
+    types::{
+        AttributeDetail, Date, Event, EventDetail, Family, GenderType, Individual,
+        IndividualAttribute, Name, Pointer, Source, SourceCitation,
+    },
+    GedcomData,
+};
+
+const GEDCOMX: &str = "http://gedcomx.org/";
+
+/// `Gedcomx` is the root of a GEDCOM X document.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     1 SEX M\n\
+///     1 BIRT\n\
+///     2 DATE 1 JAN 1900\n\
+///     0 @I2@ INDI\n\
+///     1 NAME Jane /Doe/\n\
+///     0 @F1@ FAM\n\
+///     1 HUSB @I1@\n\
+///     1 CHIL @I2@\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let gedcomx = data.to_gedcomx();
+///
+/// assert_eq!(gedcomx.persons[0].id, "I1");
+/// assert_eq!(gedcomx.persons[0].facts[0].fact_type, "http://gedcomx.org/Birth");
+/// assert_eq!(gedcomx.relationships[0].relationship_type, "http://gedcomx.org/ParentChild");
+/// assert_eq!(gedcomx.relationships[0].person2.resource, "#I2");
+///
+/// let json = gedcomx.to_json().unwrap();
+/// assert!(json.contains(r#""fullText":"John Doe""#));
+/// ```
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gedcomx {
+    /// One person per individual
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub persons: Vec<Person>,
+    /// Couple and parent-child relationships derived from the families
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub relationships: Vec<Relationship>,
+    /// One source description per source record
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub source_descriptions: Vec<SourceDescription>,
+}
+
+impl Gedcomx {
+    /// Serializes the document as GEDCOM X JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by `serde_json`.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Serializes the document as indented GEDCOM X JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by `serde_json`.
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl From<&GedcomData> for Gedcomx {
+    fn from(data: &GedcomData) -> Gedcomx {
+        Gedcomx {
+            persons: data.individuals.iter().map(Person::from).collect(),
+            relationships: data.families.iter().flat_map(relationships).collect(),
+            source_descriptions: data.sources.iter().map(SourceDescription::from).collect(),
+        }
+    }
+}
+
+impl GedcomData {
+    /// Maps the tree to the GEDCOM X model, see the `gedcomx` module.
+    #[must_use]
+    pub fn to_gedcomx(&self) -> Gedcomx {
+        Gedcomx::from(self)
+    }
+}
+
+/// `Person` is a GEDCOM X person, mapped from an individual.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Person {
+    /// The xref of the individual without its `@` delimiters
+    pub id: String,
+    /// tag: SEX
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gender: Option<Gender>,
+    /// tag: NAME
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<PersonName>,
+    /// The events and attributes of the individual
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub facts: Vec<Fact>,
+    /// tag: SOUR
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SourceReference>,
+}
+
+impl From<&Individual> for Person {
+    fn from(indi: &Individual) -> Person {
+        let events = indi.events.iter().map(Fact::from);
+        let attributes = indi.attributes.iter().map(Fact::from);
+
+        Person {
+            id: id(indi.xref.as_deref()),
+            gender: indi.sex.as_ref().map(|sex| Gender {
+                gender_type: gender_type(&sex.value),
+            }),
+            names: indi.names().map(PersonName::from).collect(),
+            facts: events.chain(attributes).collect(),
+            sources: source_references(&indi.source),
+        }
+    }
+}
+
+/// `Gender` is the gender of a person.
+#[derive(Clone, Debug, Serialize)]
+pub struct Gender {
+    /// A GEDCOM X gender type URI, e.g. `http://gedcomx.org/Female`
+    #[serde(rename = "type")]
+    pub gender_type: String,
+}
+
+/// `PersonName` is a name of a person, with a single name form.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonName {
+    /// The name, in the GEDCOM X model a name may have several forms, e.g. in different scripts
+    pub name_forms: Vec<NameForm>,
+}
+
+/// `NameForm` is a name written out in full, and split into its parts.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameForm {
+    /// The name without the slashes around the surname, e.g. "John Doe"
+    pub full_text: String,
+    /// tags: NPFX, GIVN, SPFX, SURN, NSFX
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<NamePart>,
+}
+
+/// `NamePart` is a part of a name, e.g. the surname.
+#[derive(Clone, Debug, Serialize)]
+pub struct NamePart {
+    /// A GEDCOM X name part type URI, e.g. `http://gedcomx.org/Surname`
+    #[serde(rename = "type")]
+    pub part_type: String,
+    /// The text of the part
+    pub value: String,
+}
+
+impl From<&Name> for PersonName {
+    fn from(name: &Name) -> PersonName {
+        let full_text = name.full_name().unwrap_or_default();
+
+        // prefer the explicit name pieces, falling back to the parts of the NAME payload
+        let parts = [
+            ("Prefix", name.prefix.as_deref()),
+            ("Given", name.given_name()),
+            ("Surname", name.family_name()),
+            ("Suffix", name.suffix.as_deref()),
+        ]
+        .iter()
+        .filter_map(|(part_type, value)| {
+            Some(NamePart {
+                part_type: format!("{GEDCOMX}{part_type}"),
+                value: (*value)?.to_string(),
+            })
+        })
+        .collect();
+
+        PersonName {
+            name_forms: vec![NameForm { full_text, parts }],
+        }
+    }
+}
+
+/// `Fact` is an event or attribute of a person or relationship.
+#[derive(Clone, Debug, Serialize)]
+pub struct Fact {
+    /// A GEDCOM X fact type URI, e.g. `http://gedcomx.org/Birth`
+    #[serde(rename = "type")]
+    pub fact_type: String,
+    /// tag: DATE
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<Original>,
+    /// tag: PLAC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place: Option<Original>,
+    /// The payload of the event or attribute, e.g. the occupation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl From<&EventDetail> for Fact {
+    fn from(event: &EventDetail) -> Fact {
+        Fact {
+            fact_type: match (&event.event, &event.event_type) {
+                (Event::Event, Some(kind)) => data_uri(kind),
+                _ => event_type(&event.event),
+            },
+            date: event.date.as_ref().and_then(original_date),
+            place: event.place.as_deref().map(Original::from),
+            value: event.value.clone().filter(|value| !value.is_empty()),
+        }
+    }
+}
+
+impl From<&AttributeDetail> for Fact {
+    fn from(attribute: &AttributeDetail) -> Fact {
+        Fact {
+            fact_type: match (&attribute.attribute, &attribute.attribute_type) {
+                (IndividualAttribute::Fact, Some(kind)) => data_uri(kind),
+                _ => attribute_type(&attribute.attribute),
+            },
+            date: attribute.date.as_ref().and_then(original_date),
+            place: attribute.place.as_deref().map(Original::from),
+            value: attribute.value.clone().filter(|value| !value.is_empty()),
+        }
+    }
+}
+
+/// `Original` is a date or place kept as the text it was recorded as.
+#[derive(Clone, Debug, Serialize)]
+pub struct Original {
+    /// The recorded text, e.g. "ABT 1900"
+    pub original: String,
+}
+
+impl From<&str> for Original {
+    fn from(original: &str) -> Original {
+        Original {
+            original: original.to_string(),
+        }
+    }
+}
+
+/// `Relationship` is a couple or parent-child relationship between two persons.
+#[derive(Clone, Debug, Serialize)]
+pub struct Relationship {
+    /// `http://gedcomx.org/Couple` or `http://gedcomx.org/ParentChild`
+    #[serde(rename = "type")]
+    pub relationship_type: String,
+    /// The first spouse, or the parent
+    pub person1: ResourceReference,
+    /// The second spouse, or the child
+    pub person2: ResourceReference,
+    /// The family events, only on couple relationships
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub facts: Vec<Fact>,
+    /// The citations of the family
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SourceReference>,
+}
+
+/// `ResourceReference` refers to another resource of the document, e.g. `#I1`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ResourceReference {
+    /// The local reference
+    pub resource: String,
+}
+
+impl ResourceReference {
+    fn new(xref: &str) -> ResourceReference {
+        ResourceReference {
+            resource: format!("#{}", id(Some(xref))),
+        }
+    }
+}
+
+fn relationships(family: &Family) -> Vec<Relationship> {
+    let parents: Vec<&str> = [&family.individual1, &family.individual2]
+        .iter()
+        .filter_map(|parent| parent.as_deref())
+        .filter(|parent| *parent != Pointer::VOID)
+        .collect();
+    let mut relationships = Vec::new();
+
+    if let [husband, wife] = parents[..] {
+        relationships.push(Relationship {
+            relationship_type: format!("{GEDCOMX}Couple"),
+            person1: ResourceReference::new(husband),
+            person2: ResourceReference::new(wife),
+            facts: family
+                .events
+                .iter()
+                .map(Fact::from)
+                .chain(family.attributes.iter().map(Fact::from))
+                .collect(),
+            sources: source_references(&family.sources),
+        });
+    }
+    for parent in &parents {
+        for child in family
+            .children
+            .iter()
+            .filter(|child| *child != Pointer::VOID)
+        {
+            relationships.push(Relationship {
+                relationship_type: format!("{GEDCOMX}ParentChild"),
+                person1: ResourceReference::new(parent),
+                person2: ResourceReference::new(child),
+                facts: Vec::new(),
+                sources: Vec::new(),
+            });
+        }
+    }
+    relationships
+}
+
+/// `SourceDescription` describes a source, mapped from a source record.
+#[derive(Clone, Debug, Serialize)]
+pub struct SourceDescription {
+    /// The xref of the source without its `@` delimiters
+    pub id: String,
+    /// The author, title and publication facts of the source as a single citation
+    pub citations: Vec<Citation>,
+    /// tag: TITL
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub titles: Vec<TextValue>,
+}
+
+/// `Citation` is a bibliographic citation of a source.
+#[derive(Clone, Debug, Serialize)]
+pub struct Citation {
+    /// The citation text
+    pub value: String,
+}
+
+/// `TextValue` is a piece of text, e.g. a title.
+#[derive(Clone, Debug, Serialize)]
+pub struct TextValue {
+    /// The text
+    pub value: String,
+}
+
+impl From<&Source> for SourceDescription {
+    fn from(source: &Source) -> SourceDescription {
+        let citation = [&source.author, &source.title, &source.publication_facts]
+            .iter()
+            .filter_map(|part| part.as_deref())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        SourceDescription {
+            id: id(source.xref.as_deref()),
+            citations: vec![Citation { value: citation }],
+            titles: source
+                .title
+                .iter()
+                .map(|title| TextValue {
+                    value: title.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `SourceReference` refers to the description of a cited source.
+#[derive(Clone, Debug, Serialize)]
+pub struct SourceReference {
+    /// The local reference to the source description, e.g. `#S1`
+    pub description: String,
+}
+
+fn source_references(citations: &[SourceCitation]) -> Vec<SourceReference> {
+    citations
+        .iter()
+        .map(|citation| SourceReference {
+            description: ResourceReference::new(&citation.xref).resource,
+        })
+        .collect()
+}
+
+/// The id of a record, its xref without the `@` delimiters
+fn id(xref: Option<&str>) -> String {
+    xref.unwrap_or("").trim_matches('@').to_string()
+}
+
+fn original_date(date: &Date) -> Option<Original> {
+    date.value.as_deref().map(Original::from)
+}
+
+fn gender_type(gender: &GenderType) -> String {
+    let name = match gender {
+        GenderType::Male => "Male",
+        GenderType::Female => "Female",
+        GenderType::Nonbinary | GenderType::Unknown => "Unknown",
+    };
+    format!("{GEDCOMX}{name}")
+}
+
+fn event_type(event: &Event) -> String {
+    let name = match event {
+        Event::Adoption => "Adoption",
+        Event::AdultChristening => "AdultChristening",
+        Event::Annulment => "Annulment",
+        Event::Baptism => "Baptism",
+        Event::BarMitzvah => "BarMitzvah",
+        Event::BasMitzvah => "BatMitzvah",
+        Event::Birth => "Birth",
+        Event::Blessing => "Blessing",
+        Event::Burial => "Burial",
+        Event::Census => "Census",
+        Event::Christening => "Christening",
+        Event::Confirmation => "Confirmation",
+        Event::Cremation => "Cremation",
+        Event::Death => "Death",
+        Event::Divorce => "Divorce",
+        Event::DivorceFiled => "DivorceFiling",
+        Event::Emigration => "Emigration",
+        Event::Engagement => "Engagement",
+        Event::FirstCommunion => "FirstCommunion",
+        Event::Graduation => "Graduation",
+        Event::Immigration => "Immigration",
+        Event::Marriage => "Marriage",
+        Event::MarriageBann => "MarriageBanns",
+        Event::MarriageContract => "MarriageContract",
+        Event::MarriageLicense => "MarriageLicense",
+        Event::MarriageSettlement => "MarriageSettlement",
+        Event::Naturalization => "Naturalization",
+        Event::Ordination => "Ordination",
+        Event::Probate => "Probate",
+        Event::Residence => "Residence",
+        Event::Retired => "Retirement",
+        Event::Will => "Will",
+        Event::Event | Event::Custom(_) | Event::Registered(_) => return data_uri(event.tag()),
+    };
+    format!("{GEDCOMX}{name}")
+}
+
+fn attribute_type(attribute: &IndividualAttribute) -> String {
+    let name = match attribute {
+        IndividualAttribute::CastName => "Caste",
+        IndividualAttribute::PhysicalDescription => "PhysicalDescription",
+        IndividualAttribute::ScholasticAchievement => "Education",
+        IndividualAttribute::NationalIDNumber => "NationalId",
+        IndividualAttribute::NationalOrTribalOrigin => "Nationality",
+        IndividualAttribute::CountOfChildren => "NumberOfChildren",
+        IndividualAttribute::CountOfMarriages => "NumberOfMarriages",
+        IndividualAttribute::Occupation => "Occupation",
+        IndividualAttribute::Possessions => "Property",
+        IndividualAttribute::ReligiousAffiliation => "Religion",
+        IndividualAttribute::ResidesAt => "Residence",
+        IndividualAttribute::NobilityTypeTitle => "NobilityTitle",
+        IndividualAttribute::SocialSecurityNumber | IndividualAttribute::Fact => {
+            return data_uri(attribute.tag())
+        }
+    };
+    format!("{GEDCOMX}{name}")
+}
+
+/// A fact type for a GEDCOM tag without a GEDCOM X equivalent, or for the TYPE of a generic event
+/// or fact
+fn data_uri(name: &str) -> String {
+    let encoded: String = name
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect();
+    format!("data:,{encoded}")
+}
@@ -0,0 +1,260 @@
+//! Reading and writing GEDZIP packages (`.gdz`), enabled by the `gedzip` feature.
+//!
+//! GEDCOM 7 defines a GEDZIP package as a zip archive holding the document as `gedcom.ged` at its
+//! root, along with the media files it refers to. A FILE path which is a relative URL, e.g.
+//! `photos/John%20Smith.jpg`, refers to the file of the archive at that path once
+//! percent-decoded. See <https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#gedzip>.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::gedzip::{self, Gedzip};
+//! use std::io::Cursor;
+//!
+//! let mut package = Gedzip::open("./tests/fixtures/gedzip/sample.gdz").unwrap();
+//! let data = package.parse().unwrap();
+//! assert_eq!(package.referenced_media(&data), vec!["photos/John Smith.jpg"]);
+//!
+//! // package the tree again, with the media of a directory
+//! let mut output = Cursor::new(Vec::new());
+//! gedzip::write(&mut output, &data, "./tests/fixtures/gedzip/media").unwrap();
+//!
+//! let mut package = Gedzip::new(output).unwrap();
+//! assert_eq!(package.parse().unwrap().individuals.len(), 1);
+//! assert!(!package.read_media("photos/John Smith.jpg").unwrap().is_empty());
+//! ```
+
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, Read, Seek, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use zip::{result::ZipError, write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    input::decode,
+    media::{file_references, percent_decode, BrokenMedia},
+    parse_ged,
+    types::MultimediaFileRefn,
+    util::LineFields,
+    GedcomData, ParseFileError,
+};
+
+/// The name of the document within a package
+const DOCUMENT_NAME: &str = "gedcom.ged";
+
+/// `Gedzip` is an open GEDZIP package.
+pub struct Gedzip<R: Read + Seek> {
+    archive: ZipArchive<R>,
+}
+
+impl Gedzip<File> {
+    /// Opens the package at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error raised while opening the file, or an `io::ErrorKind::InvalidData` error
+    /// when it isn't a zip archive.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Gedzip<File>> {
+        Gedzip::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> Gedzip<R> {
+    /// Reads the package from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::ErrorKind::InvalidData` error when the contents aren't a zip archive.
+    pub fn new(reader: R) -> io::Result<Gedzip<R>> {
+        Ok(Gedzip {
+            archive: ZipArchive::new(reader).map_err(zip_error)?,
+        })
+    }
+
+    /// Parses the document of the package, see `parse_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseFileError::Io` when the package has no `gedcom.ged` or it can't be read, or
+    /// `ParseFileError::Parse` when its contents can't be parsed.
+    pub fn parse(&mut self) -> Result<GedcomData, ParseFileError> {
+        let mut bytes = Vec::new();
+        self.archive
+            .by_name(DOCUMENT_NAME)
+            .map_err(zip_error)?
+            .read_to_end(&mut bytes)?;
+        Ok(parse_ged(decode(&bytes).chars())?)
+    }
+
+    /// The paths of the files of the package other than the document, e.g. media
+    pub fn media_files(&self) -> impl Iterator<Item = &str> {
+        self.archive
+            .file_names()
+            .filter(|name| *name != DOCUMENT_NAME && !name.ends_with('/'))
+    }
+
+    /// The media files referenced by the FILE paths of `data` which are in the package, in order
+    /// of first reference.
+    #[must_use]
+    pub fn referenced_media(&self, data: &GedcomData) -> Vec<String> {
+        let files: HashSet<&str> = self.media_files().collect();
+        media_references(data)
+            .into_iter()
+            .filter_map(|reference| archive_path(&reference))
+            .filter(|path| files.contains(path.as_str()))
+            .collect()
+    }
+
+    /// Lists the FILE paths of `data` which lead to no file of the package, e.g. absolute paths
+    /// or files left out of it, see `GedcomData::check_media`. Remote URLs aren't checked.
+    #[must_use]
+    pub fn check_media(&self, data: &GedcomData) -> Vec<BrokenMedia> {
+        let files: HashSet<&str> = self.media_files().collect();
+        file_references(data)
+            .into_iter()
+            .filter_map(|(record, file)| {
+                let reference = MultimediaFileRefn {
+                    value: Some(file.clone()),
+                    ..MultimediaFileRefn::default()
+                };
+                let path = reference.local_path()?;
+                let packaged =
+                    archive_path(&path).is_some_and(|path| files.contains(path.as_str()));
+                if packaged {
+                    return None;
+                }
+                Some(BrokenMedia {
+                    record,
+                    file,
+                    resolved: PathBuf::from(path),
+                })
+            })
+            .collect()
+    }
+
+    /// Reads a media file of the package.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::ErrorKind::NotFound` error when the package has no such file, or the error
+    /// raised while reading it.
+    pub fn read_media(&mut self, path: &str) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.archive
+            .by_name(path)
+            .map_err(zip_error)?
+            .read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Extracts the media files referenced by `data` into `dir`, keeping their paths within the
+    /// package, and returns the paths of the extracted files.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error raised while reading a file from the package or writing it out.
+    pub fn extract_media(
+        &mut self,
+        data: &GedcomData,
+        dir: impl AsRef<Path>,
+    ) -> io::Result<Vec<PathBuf>> {
+        let mut extracted = Vec::new();
+        for path in self.referenced_media(data) {
+            let target = dir.as_ref().join(&path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&target, self.read_media(&path)?)?;
+            extracted.push(target);
+        }
+        Ok(extracted)
+    }
+}
+
+/// Packages `data` as a GEDZIP, along with the media files it references from `media_dir`.
+/// References which aren't relative paths, e.g. URLs or absolute paths, are left out.
+///
+/// # Errors
+///
+/// Returns an `io::ErrorKind::NotFound` error when a referenced file isn't in `media_dir`, or the
+/// error raised while reading a file or writing the package.
+pub fn write<W: Write + Seek>(
+    writer: W,
+    data: &GedcomData,
+    media_dir: impl AsRef<Path>,
+) -> io::Result<()> {
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(writer);
+    zip.start_file(DOCUMENT_NAME, options).map_err(zip_error)?;
+    zip.write_all(data.to_gedcom_string().as_bytes())?;
+
+    let mut written = HashSet::new();
+    for path in media_references(data)
+        .iter()
+        .filter_map(|r| archive_path(r))
+    {
+        if !written.insert(path.clone()) {
+            continue;
+        }
+        let bytes = fs::read(media_dir.as_ref().join(&path))
+            .map_err(|err| io::Error::new(err.kind(), format!("media file {path}: {err}")))?;
+        zip.start_file(path.as_str(), options).map_err(zip_error)?;
+        zip.write_all(&bytes)?;
+    }
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+/// Lists the FILE paths of the multimedia of `data`, in order of first reference.
+#[must_use]
+pub fn media_references(data: &GedcomData) -> Vec<String> {
+    let mut references: Vec<String> = Vec::new();
+    let mut record = "";
+    for line in data.to_gedcom_string().lines() {
+        let line = LineFields::split(line);
+        if line.level == "0" {
+            record = line.tag.unwrap_or_default();
+        }
+        // HEAD.FILE is the name of the document itself
+        if record == "HEAD" || line.tag != Some("FILE") {
+            continue;
+        }
+        if let Some(value) = line.value {
+            if !references.iter().any(|reference| reference == value) {
+                references.push(value.to_string());
+            }
+        }
+    }
+    references
+}
+
+/// The path within a package a FILE reference points to, or `None` when it isn't a relative path
+/// staying within the package
+fn archive_path(reference: &str) -> Option<String> {
+    let has_scheme = reference
+        .split_once(':')
+        .is_some_and(|(scheme, _)| !scheme.is_empty() && !scheme.contains('/'));
+    if has_scheme || reference.starts_with('/') || reference.starts_with('\\') {
+        return None;
+    }
+    let path = percent_decode(reference)?;
+    let relative = Path::new(&path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if relative {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn zip_error(err: ZipError) -> io::Error {
+    match err {
+        ZipError::Io(err) => err,
+        ZipError::FileNotFound => io::Error::new(io::ErrorKind::NotFound, err),
+        _ => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
@@ -0,0 +1,217 @@
+//! Distances between event places and grouping of events by location, from the coordinates given
+//! in PLAC.MAP.
+//!
+//! Distances are great-circle distances computed with the
+//! [haversine formula](https://en.wikipedia.org/wiki/Haversine_formula), which is accurate to
+//! within about 0.5% for the scale of a one-place study.
+//!
+//! # Example
+//!
+//! Everyone born within 50 km of Boston:
+//!
+//! ```
+//! use gedcom::{geo::{self, LatLon}, types::Event, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5.1\n\
+//!     0 @I1@ INDI\n\
+//!     1 BIRT\n\
+//!     2 PLAC Cambridge, Massachusetts\n\
+//!     3 MAP\n\
+//!     4 LATI N42.3736\n\
+//!     4 LONG W71.1097\n\
+//!     0 @I2@ INDI\n\
+//!     1 BIRT\n\
+//!     2 PLAC New York, New York\n\
+//!     3 MAP\n\
+//!     4 LATI N40.7128\n\
+//!     4 LONG W74.0060\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let boston = LatLon::new(42.3601, -71.0589);
+//!
+//! let born_nearby: Vec<&str> = geo::events_within(&data, boston, 50.0)
+//!     .iter()
+//!     .filter(|located| located.event.event == Event::Birth)
+//!     .filter_map(|located| located.xref)
+//!     .collect();
+//! assert_eq!(born_nearby, vec!["@I1@"]);
+//! ```
+
+use crate::{
+    search::{RecordKind, RecordLocation},
+    types::EventDetail,
+    GedcomData,
+};
+
+/// Mean radius of the Earth in kilometres
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// `LatLon` is a position on the globe in decimal degrees, north and east being positive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LatLon {
+    /// Degrees north of the equator, -90 to 90
+    pub latitude: f64,
+    /// Degrees east of the prime meridian, -180 to 180
+    pub longitude: f64,
+}
+
+impl LatLon {
+    /// Creates a position from decimal degrees
+    #[must_use]
+    pub fn new(latitude: f64, longitude: f64) -> LatLon {
+        LatLon {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// The position of an event, when its place has coordinates
+    #[must_use]
+    pub fn of_event(event: &EventDetail) -> Option<LatLon> {
+        let (latitude, longitude) = event.coordinates.as_ref()?.degrees()?;
+        Some(LatLon::new(latitude, longitude))
+    }
+
+    /// Great-circle distance to `other` in kilometres
+    ///
+    /// ```
+    /// use gedcom::geo::LatLon;
+    /// let paris = LatLon::new(48.8566, 2.3522);
+    /// let london = LatLon::new(51.5074, -0.1278);
+    /// assert_eq!(paris.distance_km(&london).round(), 344.0);
+    /// ```
+    #[must_use]
+    pub fn distance_km(&self, other: &LatLon) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// `LocatedEvent` is an individual or family event whose place has coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct LocatedEvent<'a> {
+    /// The individual or family the event belongs to
+    pub record: RecordLocation,
+    /// The xref of the individual or family
+    pub xref: Option<&'a str>,
+    /// The event itself
+    pub event: &'a EventDetail,
+    /// Where the event took place
+    pub position: LatLon,
+}
+
+/// Lists the individual and family events whose place has coordinates, individuals first.
+#[must_use]
+pub fn located_events(data: &GedcomData) -> Vec<LocatedEvent<'_>> {
+    let individuals = data.individuals.iter().enumerate().map(|(index, indi)| {
+        let record = RecordLocation {
+            kind: RecordKind::Individual,
+            index,
+        };
+        (record, indi.xref.as_deref(), &indi.events)
+    });
+    let families = data.families.iter().enumerate().map(|(index, fam)| {
+        let record = RecordLocation {
+            kind: RecordKind::Family,
+            index,
+        };
+        (record, fam.xref.as_deref(), &fam.events)
+    });
+
+    individuals
+        .chain(families)
+        .flat_map(|(record, xref, events)| {
+            events.iter().filter_map(move |event| {
+                Some(LocatedEvent {
+                    record,
+                    xref,
+                    event,
+                    position: LatLon::of_event(event)?,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Lists the events which took place within `radius_km` of `center`, nearest first.
+#[must_use]
+pub fn events_within(data: &GedcomData, center: LatLon, radius_km: f64) -> Vec<LocatedEvent<'_>> {
+    let mut nearby: Vec<(f64, LocatedEvent)> = located_events(data)
+        .into_iter()
+        .map(|located| (center.distance_km(&located.position), located))
+        .filter(|(distance, _)| *distance <= radius_km)
+        .collect();
+    nearby.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    nearby.into_iter().map(|(_, located)| located).collect()
+}
+
+/// `Cluster` is a group of events which took place close to each other.
+#[derive(Clone, Debug)]
+pub struct Cluster<'a> {
+    /// The mean position of the events
+    pub center: LatLon,
+    /// The events of the cluster
+    pub events: Vec<LocatedEvent<'a>>,
+}
+
+/// Groups events by location. Each event joins the first cluster whose center is within
+/// `radius_km`, which then moves to the mean position of its events, or else starts a new cluster.
+/// The result depends on the order of `events`, but is quick and good enough to find the main
+/// places a family lived in.
+///
+/// ```
+/// use gedcom::geo::{cluster, LatLon, LocatedEvent};
+/// # use gedcom::{search::{RecordKind, RecordLocation}, GedcomDocument};
+/// # let sample = "0 HEAD\n1 GEDC\n2 VERS 5.5.1\n0 @I1@ INDI\n1 BIRT\n0 TRLR";
+/// # let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// # let located = |latitude, longitude| LocatedEvent {
+/// #     record: RecordLocation { kind: RecordKind::Individual, index: 0 },
+/// #     xref: Some("@I1@"),
+/// #     event: &data.individuals[0].events[0],
+/// #     position: LatLon::new(latitude, longitude),
+/// # };
+/// let events = vec![
+///     located(42.3601, -71.0589), // Boston
+///     located(40.7128, -74.0060), // New York
+///     located(42.3736, -71.1097), // Cambridge
+/// ];
+///
+/// let clusters = cluster(&events, 25.0);
+/// assert_eq!(clusters.len(), 2);
+/// assert_eq!(clusters[0].events.len(), 2);
+/// assert_eq!(clusters[1].events.len(), 1);
+/// ```
+#[must_use]
+pub fn cluster<'a>(events: &[LocatedEvent<'a>], radius_km: f64) -> Vec<Cluster<'a>> {
+    let mut clusters: Vec<Cluster<'a>> = Vec::new();
+    for located in events {
+        let nearby = clusters
+            .iter_mut()
+            .find(|cluster| cluster.center.distance_km(&located.position) <= radius_km);
+        match nearby {
+            Some(cluster) => {
+                cluster.events.push(*located);
+                cluster.center = mean_position(&cluster.events);
+            }
+            None => clusters.push(Cluster {
+                center: located.position,
+                events: vec![*located],
+            }),
+        }
+    }
+    clusters
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mean_position(events: &[LocatedEvent]) -> LatLon {
+    let count = events.len() as f64;
+    let latitude = events.iter().map(|e| e.position.latitude).sum::<f64>() / count;
+    let longitude = events.iter().map(|e| e.position.longitude).sum::<f64>() / count;
+    LatLon::new(latitude, longitude)
+}
@@ -0,0 +1,348 @@
+//! Import of spreadsheets listing one person per row into a tree, enabled with the `"import"`
+//! feature.
+//!
+//! `from_csv` reads a CSV file with a header row, and `from_json` an array of objects. Both hold
+//! the columns of a `PersonRow`, e.g. `id,given_names,surname,sex,birth_date,father,mother`.
+//! Columns may come in any order, missing columns and empty cells are left out, and columns
+//! which aren't recognised are ignored.
+//!
+//! Each row becomes an individual, numbered in row order, e.g. `@I1@` for the first row. The
+//! `father` and `mother` columns hold the `id` of another row, and children with the same
+//! parents are gathered into a family, numbered in the order of its first child, e.g. `@F1@`.
+//! The father of a family is its HUSB and the mother its WIFE. Rows without an `id` are
+//! imported but can't be a parent. The tree gets a GEDCOM 5.5.1 header, and its links are
+//! recorded on both sides, so that `GedcomData::validate` finds no issues.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::import;
+//! let csv = "\
+//! id,given_names,surname,sex,birth_date,birth_place,father,mother
+//! 1,John,Smith,M,12 MAR 1850,Leeds,,
+//! 2,Mary,Brown,F,,,,
+//! 3,Thomas,Smith,male,ABT 1875,,1,2
+//! 4,Ann,Smith,female,1877,,1,2
+//! ";
+//!
+//! let data = import::from_csv(csv.as_bytes()).unwrap();
+//! assert!(data.validate().is_empty());
+//!
+//! let thomas = &data.individuals[2];
+//! assert_eq!(thomas.xref.as_deref(), Some("@I3@"));
+//! assert_eq!(thomas.name.as_ref().unwrap().value.as_deref(), Some("Thomas /Smith/"));
+//! let birth = thomas.events[0].date.as_ref().unwrap();
+//! assert_eq!(birth.value.as_deref(), Some("ABT 1875"));
+//!
+//! let family = &data.families[0];
+//! assert_eq!(family.individual1.as_deref(), Some("@I1@"));
+//! assert_eq!(family.individual2.as_deref(), Some("@I2@"));
+//! assert_eq!(family.children, vec!["@I3@", "@I4@"]);
+//!
+//! let json = r#"[{"id": "a", "given_names": "Jane"}, {"given_names": "Tom", "mother": "a"}]"#;
+//! let data = import::from_json(json.as_bytes()).unwrap();
+//! assert_eq!(data.families[0].individual2.as_deref(), Some("@I1@"));
+//! ```
+
+use std::{collections::HashMap, error::Error, fmt, io};
+
+use serde::Deserialize;
+
+use crate::{
+    borrowed::{EventRef, FamilyLinkRef, FamilyRef, GedcomRef, HeaderRef, IndividualRef, NameRef},
+    GedcomData,
+};
+
+/// `PersonRow` is a row of a spreadsheet to import, see the `import` module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PersonRow {
+    /// The id parents are referred to by in the `father` and `mother` columns of other rows
+    pub id: Option<String>,
+    /// The given names, e.g. "John Henry"
+    pub given_names: Option<String>,
+    /// The surname, e.g. "Smith"
+    pub surname: Option<String>,
+    /// The SEX payload, e.g. "M", or one of "male", "female" and "unknown"
+    pub sex: Option<String>,
+    /// The date of birth as a GEDCOM date, e.g. "12 MAR 1850"
+    pub birth_date: Option<String>,
+    /// The place of birth, e.g. "Leeds, Yorkshire, England"
+    pub birth_place: Option<String>,
+    /// The date of death as a GEDCOM date
+    pub death_date: Option<String>,
+    /// The place of death
+    pub death_place: Option<String>,
+    /// The id of the row of the father
+    pub father: Option<String>,
+    /// The id of the row of the mother
+    pub mother: Option<String>,
+}
+
+/// `ImportError` is returned when a spreadsheet can't be read or its rows don't fit together.
+#[derive(Debug)]
+pub enum ImportError {
+    /// The CSV couldn't be read
+    Csv(csv::Error),
+    /// The JSON couldn't be read
+    Json(serde_json::Error),
+    /// More than one row has the same id
+    DuplicateId {
+        /// The second row with the id, numbered from 1 leaving out the CSV header
+        row: usize,
+        /// The id
+        id: String,
+    },
+    /// A `father` or `mother` column holds an id which no row has
+    UnknownParent {
+        /// The row of the child, numbered from 1 leaving out the CSV header
+        row: usize,
+        /// The id of the parent
+        parent: String,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Csv(err) => write!(f, "failed to read CSV: {err}"),
+            ImportError::Json(err) => write!(f, "failed to read JSON: {err}"),
+            ImportError::DuplicateId { row, id } => {
+                write!(f, "row {row}: id {id} is already used by another row")
+            }
+            ImportError::UnknownParent { row, parent } => {
+                write!(f, "row {row}: no row has the id {parent} of its parent")
+            }
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImportError::Csv(err) => Some(err),
+            ImportError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the rows of a CSV file with a header row into a tree, see the `import` module.
+///
+/// # Errors
+///
+/// Returns an `ImportError` when the CSV can't be read, or when its rows don't fit together.
+pub fn from_csv(reader: impl io::Read) -> Result<GedcomData, ImportError> {
+    let rows = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader)
+        .deserialize()
+        .collect::<Result<Vec<PersonRow>, _>>()
+        .map_err(ImportError::Csv)?;
+    from_rows(&rows)
+}
+
+/// Reads a JSON array of rows into a tree, see the `import` module.
+///
+/// # Errors
+///
+/// Returns an `ImportError` when the JSON can't be read, or when its rows don't fit together.
+pub fn from_json(reader: impl io::Read) -> Result<GedcomData, ImportError> {
+    let rows: Vec<PersonRow> = serde_json::from_reader(reader).map_err(ImportError::Json)?;
+    from_rows(&rows)
+}
+
+/// Builds a tree from rows, see the `import` module.
+///
+/// # Errors
+///
+/// Returns an `ImportError` when two rows have the same id, or when a row refers to a parent
+/// which no row has the id of.
+pub fn from_rows(rows: &[PersonRow]) -> Result<GedcomData, ImportError> {
+    let families = Families::new(&row_parents(rows)?);
+    let xrefs: Vec<String> = (1..=rows.len()).map(|n| format!("@I{n}@")).collect();
+    let family_xrefs: Vec<String> = (1..=families.couples.len())
+        .map(|n| format!("@F{n}@"))
+        .collect();
+
+    let mut data = GedcomRef {
+        header: Some(HeaderRef {
+            version: Some("5.5.1"),
+            encoding: Some("UTF-8"),
+            ..HeaderRef::default()
+        }),
+        ..GedcomRef::default()
+    };
+    let names: Vec<Option<String>> = rows.iter().map(name_value).collect();
+    for (index, row) in rows.iter().enumerate() {
+        let child_link = families.child_of[index].map(|family| (family, "FAMC"));
+        let parent_links = (families.parent_of[index].iter()).map(|family| (*family, "FAMS"));
+        let links = child_link
+            .into_iter()
+            .chain(parent_links)
+            .map(|(family, tag)| FamilyLinkRef {
+                xref: &family_xrefs[family],
+                tag,
+            })
+            .collect();
+        let events = [
+            (
+                "BIRT",
+                row.birth_date.as_deref(),
+                row.birth_place.as_deref(),
+            ),
+            (
+                "DEAT",
+                row.death_date.as_deref(),
+                row.death_place.as_deref(),
+            ),
+        ];
+        data.individuals.push(IndividualRef {
+            xref: Some(&xrefs[index]),
+            name: names[index].as_deref().map(|value| NameRef {
+                value: Some(value),
+                given: cell(row.given_names.as_deref()),
+                surname: cell(row.surname.as_deref()),
+                ..NameRef::default()
+            }),
+            sex: cell(row.sex.as_deref()).map(sex_tag),
+            families: links,
+            events: events
+                .iter()
+                .filter(|(_, date, place)| cell(*date).is_some() || cell(*place).is_some())
+                .map(|(tag, date, place)| EventRef {
+                    tag,
+                    value: None,
+                    date: cell(*date),
+                    place: cell(*place),
+                    event_type: None,
+                })
+                .collect(),
+            ..IndividualRef::default()
+        });
+    }
+    for (family, (father, mother)) in families.couples.iter().enumerate() {
+        data.families.push(FamilyRef {
+            xref: Some(&family_xrefs[family]),
+            individual1: father.map(|father| xrefs[father].as_str()),
+            individual2: mother.map(|mother| xrefs[mother].as_str()),
+            children: families.children[family]
+                .iter()
+                .map(|child| xrefs[*child].as_str())
+                .collect(),
+            events: Vec::new(),
+        });
+    }
+
+    let mut data = data.to_owned();
+    if let Some(gedcom) = data.header.as_mut().and_then(|head| head.gedcom.as_mut()) {
+        gedcom.form = Some("LINEAGE-LINKED".to_string());
+    }
+    Ok(data)
+}
+
+/// The rows of the father and mother of each row
+fn row_parents(rows: &[PersonRow]) -> Result<Vec<Couple>, ImportError> {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    for (index, row) in rows.iter().enumerate() {
+        if let Some(id) = cell(row.id.as_deref()) {
+            if ids.insert(id, index).is_some() {
+                return Err(ImportError::DuplicateId {
+                    row: index + 1,
+                    id: id.to_string(),
+                });
+            }
+        }
+    }
+    let mut parents = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        let parent = |column: Option<&str>| match cell(column) {
+            Some(parent) => match ids.get(parent) {
+                Some(parent) => Ok(Some(*parent)),
+                None => Err(ImportError::UnknownParent {
+                    row: index + 1,
+                    parent: parent.to_string(),
+                }),
+            },
+            None => Ok(None),
+        };
+        parents.push((
+            parent(row.father.as_deref())?,
+            parent(row.mother.as_deref())?,
+        ));
+    }
+    Ok(parents)
+}
+
+/// The rows of a father and a mother, either of which may be unknown
+type Couple = (Option<usize>, Option<usize>);
+
+/// The families inferred from the parents of the rows, numbered in order of their first child
+struct Families {
+    couples: Vec<Couple>,
+    /// The rows of the children of each family
+    children: Vec<Vec<usize>>,
+    /// The family each row is a child in
+    child_of: Vec<Option<usize>>,
+    /// The families each row is a parent in
+    parent_of: Vec<Vec<usize>>,
+}
+
+impl Families {
+    fn new(parents: &[Couple]) -> Families {
+        let mut families = Families {
+            couples: Vec::new(),
+            children: Vec::new(),
+            child_of: vec![None; parents.len()],
+            parent_of: vec![Vec::new(); parents.len()],
+        };
+        let mut numbers: HashMap<Couple, usize> = HashMap::new();
+        for (child, couple) in parents.iter().enumerate() {
+            if *couple == (None, None) {
+                continue;
+            }
+            let family = *numbers.entry(*couple).or_insert_with(|| {
+                let family = families.couples.len();
+                families.couples.push(*couple);
+                families.children.push(Vec::new());
+                let (father, mother) = couple;
+                let mother = mother.filter(|mother| *father != Some(*mother));
+                for parent in father.iter().chain(&mother) {
+                    families.parent_of[*parent].push(family);
+                }
+                family
+            });
+            families.children[family].push(child);
+            families.child_of[child] = Some(family);
+        }
+        families
+    }
+}
+
+/// The trimmed text of a cell, unless it's empty
+fn cell(column: Option<&str>) -> Option<&str> {
+    column.map(str::trim).filter(|text| !text.is_empty())
+}
+
+/// The NAME payload of a row, e.g. "John /Smith/"
+fn name_value(row: &PersonRow) -> Option<String> {
+    match (
+        cell(row.given_names.as_deref()),
+        cell(row.surname.as_deref()),
+    ) {
+        (None, None) => None,
+        (Some(given), None) => Some(given.to_string()),
+        (None, Some(surname)) => Some(format!("/{surname}/")),
+        (Some(given), Some(surname)) => Some(format!("{given} /{surname}/")),
+    }
+}
+
+/// The SEX payload of a cell, which may spell the sex out
+fn sex_tag(sex: &str) -> &str {
+    match sex.to_ascii_lowercase().as_str() {
+        "male" => "M",
+        "female" => "F",
+        "unknown" => "U",
+        _ => sex,
+    }
+}
@@ -0,0 +1,182 @@
+//! Handles decoding the raw bytes of a GEDCOM file into text for the `Tokenizer`.
+//!
+//! GEDCOM 5.5.1 permits files in UNICODE (UTF-16) as well as UTF-8 and ASCII-compatible
+//! encodings, see Gedcom 5.5.1 specification, p. 44.
+//!
+//! Trees are often distributed compressed, as a `.ged.gz` or as a `.zip` holding the `.ged`.
+//! With the `compression` feature `read_file` decompresses these transparently.
+
+use std::{fs, io, path::Path};
+
+#[cfg(feature = "compression")]
+use std::io::Read;
+
+/// `InputEncoding` is the byte encoding detected for a GEDCOM file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEncoding {
+    /// UTF-8, or any ASCII-compatible encoding
+    Utf8,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+}
+
+/// Detects the encoding of GEDCOM file contents, from the byte order mark if one is present.
+/// Otherwise the file is recognized from the layout of its first line: every GEDCOM file starts
+/// with `0 HEAD`, and the ASCII characters of a UTF-16 file (e.g. one declaring `1 CHAR UNICODE`)
+/// are each paired with a zero byte.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::input::{detect_encoding, InputEncoding};
+///
+/// assert_eq!(detect_encoding(b"0 HEAD\n"), InputEncoding::Utf8);
+/// assert_eq!(detect_encoding(&[0xFF, 0xFE, b'0', 0]), InputEncoding::Utf16Le);
+/// assert_eq!(detect_encoding(&[0, b'0', 0, b' ']), InputEncoding::Utf16Be);
+/// ```
+#[must_use]
+pub fn detect_encoding(bytes: &[u8]) -> InputEncoding {
+    match bytes {
+        [0xFF, 0xFE, ..] => InputEncoding::Utf16Le,
+        [0xFE, 0xFF, ..] => InputEncoding::Utf16Be,
+        [first, 0, ..] if *first != 0 => InputEncoding::Utf16Le,
+        [0, second, ..] if *second != 0 => InputEncoding::Utf16Be,
+        _ => InputEncoding::Utf8,
+    }
+}
+
+/// Decodes GEDCOM file contents into a String, transparently handling UTF-16 (little or big
+/// endian) and UTF-8 files, with or without a byte order mark. Invalid sequences are replaced
+/// with U+FFFD rather than failing the whole file.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::input::decode;
+///
+/// let utf16: Vec<u8> = "0 HEAD\n1 CHAR UNICODE\n0 TRLR"
+///     .encode_utf16()
+///     .flat_map(|unit| unit.to_le_bytes().to_vec())
+///     .collect();
+///
+/// assert_eq!(decode(&utf16), "0 HEAD\n1 CHAR UNICODE\n0 TRLR");
+/// ```
+#[must_use]
+pub fn decode(bytes: &[u8]) -> String {
+    match detect_encoding(bytes) {
+        InputEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+        InputEncoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes),
+        InputEncoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => from_bytes([*a, *b]),
+            _ => 0xFFFD,
+        })
+        .collect();
+    let text = String::from_utf16_lossy(&units);
+    match text.strip_prefix('\u{FEFF}') {
+        Some(text) => text.to_string(),
+        None => text,
+    }
+}
+
+/// `Compression` is the container format detected for a file, from its leading magic bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain GEDCOM text
+    None,
+    /// A gzip stream, e.g. a `.ged.gz`
+    Gzip,
+    /// A zip archive holding the `.ged`
+    Zip,
+}
+
+/// Detects whether file contents are compressed.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::input::{detect_compression, Compression};
+///
+/// assert_eq!(detect_compression(b"0 HEAD\n"), Compression::None);
+/// assert_eq!(detect_compression(&[0x1F, 0x8B, 8, 0]), Compression::Gzip);
+/// assert_eq!(detect_compression(b"PK\x03\x04"), Compression::Zip);
+/// ```
+#[must_use]
+pub fn detect_compression(bytes: &[u8]) -> Compression {
+    match bytes {
+        [0x1F, 0x8B, ..] => Compression::Gzip,
+        [b'P', b'K', 3, 4, ..] => Compression::Zip,
+        _ => Compression::None,
+    }
+}
+
+/// Reads a GEDCOM file into a String, see `decode`. A gzip stream, or a zip archive containing a
+/// single `.ged` file, is decompressed first when the `compression` feature is enabled.
+///
+/// # Errors
+///
+/// Returns the error raised while reading the file. Compressed contents which can't be
+/// decompressed, including any compressed file without the `compression` feature, and archives
+/// without exactly one `.ged` file give an `io::ErrorKind::InvalidData` error.
+pub fn read_file(path: impl AsRef<Path>) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(decode(&decompress(bytes)?))
+}
+
+#[cfg(feature = "compression")]
+fn decompress(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    match detect_compression(&bytes) {
+        Compression::None => Ok(bytes),
+        Compression::Gzip => {
+            let mut contents = Vec::new();
+            flate2::read::MultiGzDecoder::new(&bytes[..]).read_to_end(&mut contents)?;
+            Ok(contents)
+        }
+        Compression::Zip => {
+            let mut archive = zip::ZipArchive::new(io::Cursor::new(bytes))?;
+            let geds: Vec<String> = archive
+                .file_names()
+                .filter(|name| {
+                    Path::new(name)
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("ged"))
+                })
+                .map(str::to_string)
+                .collect();
+            let [name] = &geds[..] else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "expected one .ged file in the archive, found {}",
+                        geds.len()
+                    ),
+                ));
+            };
+            let mut contents = Vec::new();
+            archive.by_name(name)?.read_to_end(&mut contents)?;
+            Ok(contents)
+        }
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    match detect_compression(&bytes) {
+        Compression::None => Ok(bytes),
+        Compression::Gzip | Compression::Zip => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "reading compressed files requires the `compression` feature",
+        )),
+    }
+}
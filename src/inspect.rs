@@ -0,0 +1,90 @@
+//! Reports on the structure of a file as written, before parsing it, e.g. to see which vendor
+//! tags a file uses before choosing `ParseOptions` and custom tag handlers.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::inspect;
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 BIRT\n\
+//!     2 DATE 1 JAN 1900\n\
+//!     1 _UID 4D2A\n\
+//!     0 @I2@ INDI\n\
+//!     1 BIRT\n\
+//!     2 DATE 2 FEB 1902\n\
+//!     0 TRLR";
+//!
+//! let profile = inspect::tag_profile(sample.as_bytes()).unwrap();
+//! assert_eq!(profile.paths["INDI.BIRT.DATE"], 2);
+//! assert_eq!(profile.paths["HEAD.GEDC.VERS"], 1);
+//! assert_eq!(profile.custom().collect::<Vec<_>>(), vec![("INDI._UID", 1)]);
+//! assert!(profile.to_string().contains("INDI.BIRT.DATE: 2\n"));
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read},
+};
+
+use crate::{
+    input,
+    util::{split_lines, LineFields},
+};
+
+/// `TagProfile` counts the tag paths of a file, see `tag_profile`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagProfile {
+    /// How many lines have each path, the tags of a line and its enclosing lines joined with
+    /// dots, e.g. `INDI.BIRT.DATE`, in order of path
+    pub paths: BTreeMap<String, usize>,
+}
+
+impl TagProfile {
+    /// The paths going through a user-defined tag, one starting with an underscore, with their
+    /// counts
+    pub fn custom(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.paths
+            .iter()
+            .filter(|(path, _)| path.split('.').any(|tag| tag.starts_with('_')))
+            .map(|(path, count)| (path.as_str(), *count))
+    }
+}
+
+impl fmt::Display for TagProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, count) in &self.paths {
+            writeln!(f, "{path}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts every tag path found in GEDCOM text, see `TagProfile`. The text is decoded as by
+/// `input::decode`, and lines without a numeric level are skipped.
+///
+/// # Errors
+///
+/// Returns any error raised while reading from `reader`.
+pub fn tag_profile(mut reader: impl Read) -> io::Result<TagProfile> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let text = input::decode(&bytes);
+
+    let mut profile = TagProfile::default();
+    let mut path: Vec<&str> = Vec::new();
+    for line in split_lines(&text) {
+        let line = LineFields::split(line.trim_start());
+        let (Ok(level), Some(tag)) = (line.level.parse::<usize>(), line.tag) else {
+            continue;
+        };
+        path.truncate(level);
+        path.push(tag);
+        *profile.paths.entry(path.join(".")).or_insert(0) += 1;
+    }
+    Ok(profile)
+}
@@ -0,0 +1,190 @@
+//! Sharing of strings repeated throughout a tree, such as place names and surnames.
+//!
+//! Large files repeat the same places ("New York, New York, USA") tens of thousands of times.
+//! Parsed with `ParseOptions::intern_strings`, the places and surnames of a tree are looked up in
+//! a `StringPool` held by `GedcomData::strings`, and each distinct value is allocated once and
+//! shared by every structure using it. The fields holding them are `SharedStr`s, which read like
+//! a `&str` whether or not the strings were interned.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{GedcomDocument, ParseOptions};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME Ole /Olsen/\n\
+//!     2 SURN Olsen\n\
+//!     1 BIRT\n\
+//!     2 PLAC Bergen, Norway\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Kari /Olsen/\n\
+//!     2 SURN Olsen\n\
+//!     1 BIRT\n\
+//!     2 PLAC Bergen, Norway\n\
+//!     0 TRLR";
+//!
+//! let options = ParseOptions {
+//!     intern_strings: true,
+//!     ..ParseOptions::default()
+//! };
+//! let mut doc = GedcomDocument::with_options(sample.chars(), options);
+//! let data = doc.parse_document().unwrap();
+//!
+//! let (ole, kari) = (&data.individuals[0], &data.individuals[1]);
+//! let place = ole.events[0].place.as_ref().unwrap();
+//! assert_eq!(place, "Bergen, Norway");
+//! assert!(place.ptr_eq(kari.events[0].place.as_ref().unwrap()));
+//! assert_eq!(data.strings.len(), 2);
+//! ```
+
+use std::{borrow::Borrow, collections::HashSet, fmt, ops::Deref, sync::Arc};
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// `SharedStr` is an immutable string which is cheap to clone, see the `intern` module. It
+/// dereferences to `str`.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedStr(Arc<str>);
+
+impl SharedStr {
+    /// The string as a `&str`
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether both strings are the same allocation, as they are when taken from the same
+    /// `StringPool`
+    #[must_use]
+    pub fn ptr_eq(&self, other: &SharedStr) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for SharedStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for SharedStr {
+    fn from(value: &str) -> SharedStr {
+        SharedStr(Arc::from(value))
+    }
+}
+
+impl From<String> for SharedStr {
+    fn from(value: String) -> SharedStr {
+        SharedStr(Arc::from(value))
+    }
+}
+
+impl From<SharedStr> for String {
+    fn from(value: SharedStr) -> String {
+        value.0.to_string()
+    }
+}
+
+impl PartialEq<str> for SharedStr {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for SharedStr {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for SharedStr {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for SharedStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de> Deserialize<'de> for SharedStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SharedStr, D::Error> {
+        String::deserialize(deserializer).map(SharedStr::from)
+    }
+}
+
+/// `StringPool` holds one allocation of each distinct string interned, see the `intern` module.
+#[derive(Clone, Debug, Default)]
+pub struct StringPool {
+    strings: HashSet<SharedStr>,
+}
+
+impl StringPool {
+    /// Creates an empty pool
+    #[must_use]
+    pub fn new() -> StringPool {
+        StringPool::default()
+    }
+
+    /// The pooled copy of `value`, added to the pool when it isn't there yet
+    pub fn intern(&mut self, value: &str) -> SharedStr {
+        if let Some(shared) = self.strings.get(value) {
+            return shared.clone();
+        }
+        let shared = SharedStr::from(value);
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    /// The number of distinct strings in the pool
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Whether nothing has been interned
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Drops the strings no structure uses anymore, e.g. after records were removed
+    pub fn shrink(&mut self) {
+        self.strings
+            .retain(|shared| Arc::strong_count(&shared.0) > 1);
+    }
+}
@@ -0,0 +1,273 @@
+//! Detection of pedigree collapse and consanguinity, by walking the FAMC links of individuals to
+//! their ancestors.
+//!
+//! Only genetic parentage is followed: FAMC links with an adopted, foster or sealing pedigree
+//! (PEDI) are skipped. Coefficients follow
+//! [Wright's path method](https://en.wikipedia.org/wiki/Coefficient_of_relationship): each pair of
+//! paths from two individuals up to a common ancestor contributes `(1/2)^(n1 + n2)`, weighted by
+//! `1 + F` for an ancestor with inbreeding coefficient `F`, as long as the paths only meet at that
+//! ancestor.
+//!
+//! # Example
+//!
+//! A child of first cousins:
+//!
+//! ```
+//! use gedcom::{kinship::Kinship, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @GRANDFATHER@\n\
+//!     1 WIFE @GRANDMOTHER@\n\
+//!     1 CHIL @SON@\n\
+//!     1 CHIL @DAUGHTER@\n\
+//!     0 @SON@ INDI\n\
+//!     1 FAMC @F1@\n\
+//!     0 @DAUGHTER@ INDI\n\
+//!     1 FAMC @F1@\n\
+//!     0 @COUSIN1@ INDI\n\
+//!     1 FAMC @F2@\n\
+//!     0 @COUSIN2@ INDI\n\
+//!     1 FAMC @F3@\n\
+//!     0 @CHILD@ INDI\n\
+//!     1 FAMC @F4@\n\
+//!     0 @F2@ FAM\n\
+//!     1 HUSB @SON@\n\
+//!     0 @F3@ FAM\n\
+//!     1 WIFE @DAUGHTER@\n\
+//!     0 @F4@ FAM\n\
+//!     1 HUSB @COUSIN1@\n\
+//!     1 WIFE @COUSIN2@\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let kinship = Kinship::new(&data);
+//!
+//! let collapse = kinship.pedigree_collapse("@CHILD@");
+//! assert_eq!(collapse.len(), 2);
+//! assert_eq!(collapse[0].xref, "@GRANDFATHER@");
+//! assert_eq!(collapse[0].path_lengths, vec![3, 3]);
+//!
+//! assert_eq!(kinship.relationship("@COUSIN1@", "@COUSIN2@").coefficient, 0.125);
+//! assert_eq!(kinship.inbreeding_coefficient("@CHILD@"), 0.0625);
+//! ```
+
+use std::{collections::HashMap, convert::TryFrom};
+
+use crate::{
+    types::{Family, FamilyLinkType, Individual, Pedigree, Pointer},
+    GedcomData,
+};
+
+/// `Kinship` looks up the genetic ancestry of the individuals of a tree.
+#[derive(Clone, Debug)]
+pub struct Kinship<'a> {
+    individuals: HashMap<&'a str, &'a Individual>,
+    families: HashMap<&'a str, &'a Family>,
+    /// How many generations up to look for ancestors, 12 by default. The number of paths can
+    /// double with every generation in a collapsed pedigree, so this bounds the work done.
+    pub max_generations: usize,
+}
+
+/// `SharedAncestor` is an ancestor reachable through more than one line of descent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedAncestor<'a> {
+    /// The xref of the ancestor
+    pub xref: &'a str,
+    /// The number of generations along each path to the ancestor, shortest first
+    pub path_lengths: Vec<usize>,
+}
+
+/// `CommonAncestor` is an ancestor shared by two individuals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommonAncestor<'a> {
+    /// The xref of the ancestor, which may be one of the two individuals
+    pub xref: &'a str,
+    /// For each pair of independent paths, the number of generations from the first and from the
+    /// second individual to the ancestor
+    pub path_lengths: Vec<(usize, usize)>,
+}
+
+/// `Relationship` is how closely two individuals are related by descent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relationship<'a> {
+    /// The coefficient of relationship, e.g. 0.5 for siblings or for a parent and child, 0.125 for
+    /// first cousins, or 0 for unrelated individuals
+    pub coefficient: f64,
+    /// The common ancestors through which the two are related, closest first
+    pub common_ancestors: Vec<CommonAncestor<'a>>,
+}
+
+impl<'a> Kinship<'a> {
+    /// Indexes the individuals and families of `data` by xref
+    #[must_use]
+    pub fn new(data: &'a GedcomData) -> Kinship<'a> {
+        let individuals = data
+            .individuals
+            .iter()
+            .filter_map(|indi| Some((indi.xref.as_deref()?, indi)))
+            .collect();
+        let families = data
+            .families
+            .iter()
+            .filter_map(|fam| Some((fam.xref.as_deref()?, fam)))
+            .collect();
+        Kinship {
+            individuals,
+            families,
+            max_generations: 12,
+        }
+    }
+
+    /// The xrefs of the father and mother of an individual, from the first FAMC link which isn't
+    /// an adoption, fostering or sealing.
+    #[must_use]
+    pub fn parents(&self, xref: &str) -> (Option<&'a str>, Option<&'a str>) {
+        let link = self.individuals.get(xref).and_then(|indi| {
+            indi.families.iter().find(|link| {
+                matches!(link.family_link_type, FamilyLinkType::Child)
+                    && matches!(link.pedigree_linkage_type, None | Some(Pedigree::Birth))
+            })
+        });
+        let parent = |parent: &'a Option<String>| {
+            parent.as_deref().filter(|parent| *parent != Pointer::VOID)
+        };
+        match link.and_then(|link| self.families.get(link.xref.as_str())) {
+            Some(family) => (parent(&family.individual1), parent(&family.individual2)),
+            None => (None, None),
+        }
+    }
+
+    /// Finds the ancestors of an individual which are reachable through more than one line of
+    /// descent, closest first.
+    #[must_use]
+    pub fn pedigree_collapse(&self, xref: &str) -> Vec<SharedAncestor<'a>> {
+        let mut lengths: HashMap<&'a str, Vec<usize>> = HashMap::new();
+        for path in self.ancestor_paths(xref) {
+            if let [_, .., ancestor] = path[..] {
+                lengths.entry(ancestor).or_default().push(path.len() - 1);
+            }
+        }
+
+        let mut shared: Vec<SharedAncestor> = lengths
+            .into_iter()
+            .filter(|(_, path_lengths)| path_lengths.len() > 1)
+            .map(|(xref, mut path_lengths)| {
+                path_lengths.sort_unstable();
+                SharedAncestor { xref, path_lengths }
+            })
+            .collect();
+        shared.sort_by(|a, b| (a.path_lengths[0], a.xref).cmp(&(b.path_lengths[0], b.xref)));
+        shared
+    }
+
+    /// Works out how closely two individuals are related through their common ancestors.
+    #[must_use]
+    pub fn relationship(&self, first: &str, second: &str) -> Relationship<'a> {
+        self.relationship_with(first, second, &mut HashMap::new())
+    }
+
+    /// The inbreeding coefficient of an individual, the probability that both copies of a gene
+    /// come from the same ancestor. It is half the coefficient of relationship of the parents,
+    /// see `relationship` for the common ancestors involved.
+    #[must_use]
+    pub fn inbreeding_coefficient(&self, xref: &str) -> f64 {
+        self.inbreeding_with(xref, &mut HashMap::new())
+    }
+
+    /// `inbreeding` memoizes the coefficients of ancestors, which are reused across paths. An
+    /// ancestor still being worked out, which only happens with looping ancestry, counts as 0.
+    fn inbreeding_with(&self, xref: &str, inbreeding: &mut HashMap<String, f64>) -> f64 {
+        if let Some(coefficient) = inbreeding.get(xref) {
+            return *coefficient;
+        }
+        inbreeding.insert(xref.to_string(), 0.0);
+        let coefficient = match self.parents(xref) {
+            (Some(father), Some(mother)) => {
+                self.relationship_with(father, mother, inbreeding)
+                    .coefficient
+                    / 2.0
+            }
+            _ => 0.0,
+        };
+        inbreeding.insert(xref.to_string(), coefficient);
+        coefficient
+    }
+
+    fn relationship_with(
+        &self,
+        first: &str,
+        second: &str,
+        inbreeding: &mut HashMap<String, f64>,
+    ) -> Relationship<'a> {
+        let mut second_paths: HashMap<&'a str, Vec<Vec<&'a str>>> = HashMap::new();
+        for path in self.ancestor_paths(second) {
+            second_paths
+                .entry(path[path.len() - 1])
+                .or_default()
+                .push(path);
+        }
+
+        let mut lengths: HashMap<&'a str, Vec<(usize, usize)>> = HashMap::new();
+        for path in self.ancestor_paths(first) {
+            let ancestor = path[path.len() - 1];
+            for other in second_paths.get(ancestor).into_iter().flatten() {
+                let independent = path[..path.len() - 1]
+                    .iter()
+                    .all(|xref| !other.contains(xref));
+                if independent {
+                    lengths
+                        .entry(ancestor)
+                        .or_default()
+                        .push((path.len() - 1, other.len() - 1));
+                }
+            }
+        }
+
+        let mut coefficient = 0.0;
+        let mut common_ancestors: Vec<CommonAncestor> = Vec::new();
+        for (xref, mut path_lengths) in lengths {
+            let weight = 1.0 + self.inbreeding_with(xref, inbreeding);
+            for (n1, n2) in &path_lengths {
+                coefficient += weight * 0.5_f64.powi(i32::try_from(n1 + n2).unwrap_or(i32::MAX));
+            }
+            path_lengths.sort_unstable_by_key(|(n1, n2)| (n1 + n2, *n1));
+            common_ancestors.push(CommonAncestor { xref, path_lengths });
+        }
+        common_ancestors.sort_by_key(|ancestor| {
+            let (n1, n2) = ancestor.path_lengths[0];
+            (n1 + n2, ancestor.xref)
+        });
+
+        Relationship {
+            coefficient,
+            common_ancestors,
+        }
+    }
+
+    /// Lists every path from an individual up through their ancestors, starting with the path
+    /// holding only the individual. A path which would loop back on itself is cut short.
+    fn ancestor_paths(&self, xref: &str) -> Vec<Vec<&'a str>> {
+        let Some((xref, _)) = self.individuals.get_key_value(xref) else {
+            return Vec::new();
+        };
+        let mut paths = Vec::new();
+        let mut pending = vec![vec![*xref]];
+        while let Some(path) = pending.pop() {
+            if path.len() <= self.max_generations {
+                let (father, mother) = self.parents(path[path.len() - 1]);
+                for parent in father.into_iter().chain(mother) {
+                    if !path.contains(&parent) {
+                        let mut longer = path.clone();
+                        longer.push(parent);
+                        pending.push(longer);
+                    }
+                }
+            }
+            paths.push(path);
+        }
+        paths
+    }
+}
@@ -6,19 +6,88 @@ use gedcom::GedcomDocument;
 let gedcom_source = std::fs::read_to_string("./tests/fixtures/sample.ged").unwrap();
 
 let mut doc = GedcomDocument::new(gedcom_source.chars());
-let gedcom_data = doc.parse_document();
+let gedcom_data = doc.parse_document().unwrap();
 
 // output some stats on the gedcom contents
-gedcom_data.stats();
+println!("{}", gedcom_data.stats());
 ```
 
 This crate contains an optional `"json"` feature that implements serialization & deserialization to json with [`serde`](https://serde.rs).
+
+The optional `"parallel"` feature adds `parallel::parse_parallel`, which parses large files on
+multiple threads with [`rayon`](https://docs.rs/rayon).
+
+The optional `"compression"` feature lets `parse_file` read `.ged.gz` files and zip archives
+holding a single `.ged` directly.
+
+The optional `"gedzip"` feature adds the `gedzip` module, which reads and writes GEDCOM 7
+GEDZIP packages (`.gdz`) bundling a document with its media files.
+
+The optional `"import"` feature adds the `import` module, which builds a tree from a CSV or JSON
+spreadsheet listing one person per row, with the families inferred from the parent columns.
+
+The optional `"chrono"` feature adds `Date::to_naive_datetime`, which converts an exact date
+and its `TIME` to a [`chrono`](https://docs.rs/chrono) timestamp.
+
+The optional `"gedcomx"` feature adds the `gedcomx` module, which exports a tree as
+[GEDCOM X](http://www.gedcomx.org) persons, relationships and source descriptions.
+
+For read-only workloads, `GedcomRef::parse` builds a lighter model which borrows its strings
+from the file contents instead of copying them, and `GedcomDocument::records` streams the records
+of a file one at a time instead of building a whole `GedcomData`. The `intern` module shares the
+places and surnames repeated throughout large files.
+
+Individuals can be looked up by name, including phonetically and with typos, through the
+`search` module, and records can be filtered on their events, names and relations with
+`GedcomData::query`. Events whose places have coordinates can be found by distance, or grouped by
+location, through the `geo` module. The `kinship` module detects pedigree collapse and works out
+coefficients of relationship and inbreeding, and the `numbering` module assigns Ahnentafel numbers
+to the ancestors of an individual and d'Aboville or Henry numbers to their descendants, for reports.
+`GedcomData::find_duplicates` lists individuals which may have been entered twice.
+`GedcomData::repair` fixes common structural problems, such as missing reciprocal FAMC and FAMS
+links, see the `repair` module. The `extract` module pulls the individuals of a one-place or
+one-name study, or a branch of the tree, out into a tree of their own, and
+`analysis::name_frequencies` counts its surnames and given names. `analysis::sourcing` reports how
+well the births, deaths and marriages of each individual are supported by citations.
+`GedcomData::timeline` lists the dated events of a tree in chronological order, for chronologies and
+"on this day" views.
+
+`GedcomData::debug_tree` prints the ancestors and descendants of an individual as an indented tree,
+which is easier to read than a `{:#?}` dump when inspecting parse results, and the `render` module
+draws ancestry and descendancy charts as Graphviz DOT or mermaid flowcharts. The `report` module
+writes individual summaries, family group sheets and ancestor outlines as text or Markdown. Before
+parsing, `inspect::tag_profile` counts the tag paths a file uses, including vendor tags. The `lint`
+module checks the lines of a file against the style and conformance rules of the specification.
+`GedcomData::check_media` lists the multimedia FILE paths which lead to no file, see the `media`
+module. Fields the typed model doesn't cover yet can be read by tag path, e.g.
+`data.select_values("INDI[@I12@]/NAME/SURN")` or `individual.get_path("BIRT/DATE")`, see the `path`
+module.
+
+`writer::GedcomWriter` writes a `GedcomData` back out as GEDCOM text. Parsed with
+`ParseOptions::preserve_lines`, untouched records are written back exactly as they were read, see
+the `roundtrip` module. `GedcomData::upgrade_to_v7` writes a tree read from a GEDCOM 5.5.1 file as
+a GEDCOM 7 document, see the `convert` module.
+
+The optional `"wasm"` feature adds the `wasm` module, with `wasm-bindgen` exports for parsing
+and browsing a tree from JavaScript.
+
+The optional `"test-util"` feature adds the `testing` module, with helpers for applications to
+load fixture files and compare trees in their own tests, ignoring how records are numbered.
+
+The writer and the `search` indexes read records through the traits of the `store` module, so
+they also work over trees kept in other storage backends.
 */
 
 #![deny(clippy::pedantic)]
 #![warn(missing_docs)]
 
-use std::str::Chars;
+use std::{
+    str::Chars,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
@@ -27,12 +96,70 @@ use serde::{Deserialize, Serialize};
 mod util;
 
 pub mod tokenizer;
-use tokenizer::{Token, Tokenizer};
+use tokenizer::{TagAlias, Token, Tokenizer};
+use util::split_lines_inclusive;
+
+mod debug_tree;
+
+mod error;
+pub use error::{GedcomError, ParseFileError, RecordError, Span};
+
+pub mod analysis;
+pub mod borrowed;
+pub use borrowed::GedcomRef;
+
+pub mod convert;
+pub mod duplicates;
+pub mod edit;
+pub mod extract;
+pub mod geo;
+pub mod input;
+pub mod inspect;
+pub mod intern;
+pub mod kinship;
+pub mod lint;
+pub mod media;
+pub mod numbering;
+pub mod path;
+pub mod query;
+pub mod render;
+pub mod repair;
+pub mod report;
+pub mod roundtrip;
+pub mod search;
+pub mod spans;
+pub mod stats;
+pub mod store;
+pub mod timeline;
+pub mod validate;
+pub mod writer;
+
+#[cfg(feature = "parallel")]
+pub mod parallel;
+
+#[cfg(feature = "gedcomx")]
+pub mod gedcomx;
+
+#[cfg(feature = "gedzip")]
+pub mod gedzip;
+
+#[cfg(feature = "import")]
+pub mod import;
+
+#[cfg(feature = "test-util")]
+pub mod testing;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use intern::StringPool;
+use roundtrip::SourceMap;
+use spans::SpanMap;
 
 pub mod types;
 use types::{
-    Family, Header, Individual, MultimediaRecord, Repository, Source, Submission, Submitter,
-    UserDefinedDataset,
+    CustomEventType, CustomTagContext, EventCategory, Family, Header, Individual, MultimediaRecord,
+    NoteRecord, ProvenanceInfo, Repository, Source, Submission, Submitter, UserDefinedDataset,
 };
 
 /// The GedcomDocument can convert the token list into a data structure. The order of the Dataset
@@ -50,53 +177,584 @@ use types::{
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let head = data.header.unwrap();
 /// let gedc = head.gedcom.unwrap();
 /// assert_eq!(gedc.version.unwrap(), "5.5");
 /// ```
+///
+/// # Recovering from errors
+///
+/// By default the first problem found fails the parse. With `ParseOptions::lenient` problems
+/// within a record are collected as warnings instead, and the offending structure is skipped.
+///
+/// ```rust
+/// use gedcom::{GedcomDocument, ParseOptions};
+/// let sample = "\
+///    0 HEAD\n\
+///    1 GEDC\n\
+///    2 VERS 5.5\n\
+///    0 @I1@ INDI\n\
+///    1 SEX Z\n\
+///    1 NAME John /Doe/\n\
+///    0 TRLR";
+///
+/// assert!(GedcomDocument::new(sample.chars()).parse_document().is_err());
+///
+/// let mut doc = GedcomDocument::with_options(sample.chars(), ParseOptions::lenient());
+/// let data = doc.parse_document().unwrap();
+/// assert_eq!(doc.warnings().len(), 1);
+/// assert_eq!(doc.warnings()[0].line(), 5);
+/// assert!(data.individuals[0].sex.is_none());
+/// assert!(data.individuals[0].name.is_some());
+/// ```
 pub struct GedcomDocument<'a> {
     tokenizer: Tokenizer<'a>,
+    /// The file contents, kept for `ParseOptions::preserve_lines` and `ParseOptions::record_spans`
+    input: &'a str,
+    /// The records parsed before the parse was cancelled, see `GedcomDocument::set_cancellation`
+    partial_data: Option<GedcomData>,
 }
 
 impl<'a> GedcomDocument<'a> {
     /// Creates a parser state machine for parsing a gedcom file as a chars iterator
     #[must_use]
-    pub fn new(chars: Chars<'a>) -> GedcomDocument {
+    pub fn new(chars: Chars<'a>) -> GedcomDocument<'a> {
+        GedcomDocument::with_options(chars, ParseOptions::default())
+    }
+
+    /// Creates a parser state machine which handles problems in the file according to `options`
+    #[must_use]
+    pub fn with_options(chars: Chars<'a>, options: ParseOptions) -> GedcomDocument<'a> {
+        let input = chars.as_str();
         let mut tokenizer = Tokenizer::new(chars);
-        tokenizer.next_token();
-        GedcomDocument { tokenizer }
+        tokenizer.options = options;
+        GedcomDocument {
+            tokenizer,
+            input,
+            partial_data: None,
+        }
     }
 
     /// Does the actual parsing of the record.
-    pub fn parse_document(&mut self) -> GedcomData {
-        GedcomData::new(&mut self.tokenizer, 0)
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` describing the first problem found in the file. In lenient mode,
+    /// only problems the parser can't skip past are returned.
+    pub fn parse_document(&mut self) -> Result<GedcomData, GedcomError> {
+        self.tokenizer.next_token()?;
+        let mut data = GedcomData::default();
+        if let Err(err) = data.parse(&mut self.tokenizer, 0) {
+            if let GedcomError::Cancelled { .. } = err {
+                data.strings = std::mem::take(&mut self.tokenizer.strings);
+                self.partial_data = Some(data);
+            }
+            return Err(err);
+        }
+        data.strings = std::mem::take(&mut self.tokenizer.strings);
+        if self.tokenizer.options.preserve_lines {
+            data.source_map = Some(SourceMap::new(self.input, &data));
+        }
+        if self.tokenizer.options.record_spans {
+            data.spans = Some(SpanMap::new(self.input));
+        }
+        Ok(data)
+    }
+
+    /// Parses a stream of documents concatenated one after the other, e.g. by a pipeline merging
+    /// exports, into a `GedcomData` each. A document ends at its TRLR, or where the HEAD of the
+    /// next one starts when its TRLR is missing. With `ParseOptions::preserve_lines`, the source
+    /// map of each document holds its own records, numbered by their lines in the whole stream.
+    ///
+    /// ```rust
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///    0 HEAD\n\
+    ///    1 GEDC\n\
+    ///    2 VERS 5.5\n\
+    ///    0 @I1@ INDI\n\
+    ///    1 NAME John /Doe/\n\
+    ///    0 TRLR\n\
+    ///    0 HEAD\n\
+    ///    1 GEDC\n\
+    ///    2 VERS 5.5.1\n\
+    ///    0 @I1@ INDI\n\
+    ///    1 NAME Jane /Roe/\n\
+    ///    0 TRLR\n";
+    ///
+    /// let documents = GedcomDocument::new(sample.chars())
+    ///     .parse_all_documents()
+    ///     .unwrap();
+    /// assert_eq!(documents.len(), 2);
+    /// let name = |i: usize| documents[i].individuals[0].name.as_ref()?.value.clone();
+    /// assert_eq!(name(0).as_deref(), Some("John /Doe/"));
+    /// assert_eq!(name(1).as_deref(), Some("Jane /Roe/"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// See `parse_document`.
+    pub fn parse_all_documents(&mut self) -> Result<Vec<GedcomData>, GedcomError> {
+        let mut documents = Vec::new();
+        self.tokenizer.next_token()?;
+        let mut data = GedcomData::default();
+        let mut start = (self.tokenizer.line_offset(), self.tokenizer.line);
+        loop {
+            let record_start = (self.tokenizer.line_offset(), self.tokenizer.line);
+            match next_record(&mut self.tokenizer, 0)? {
+                // the previous document has no TRLR
+                Some(Record::Header(head)) if data.header.is_some() => {
+                    documents.push(self.finish_document(data, start, record_start.0));
+                    data = GedcomData::default();
+                    data.header = Some(head);
+                    start = record_start;
+                }
+                Some(record) => data.add_record(record),
+                None => {
+                    // the rest of the stream after the TRLR line, or a truncated document
+                    let trlr = self.tokenizer.line_offset();
+                    let rest = &self.input[trlr..];
+                    let rest = split_lines_inclusive(rest)
+                        .next()
+                        .map_or("", |line| &rest[line.len()..]);
+                    if self.tokenizer.done() || rest.trim().is_empty() {
+                        documents.push(self.finish_document(data, start, self.input.len()));
+                        return Ok(documents);
+                    }
+                    self.tokenizer.skip_subtree(0)?;
+                    let end = self.tokenizer.line_offset();
+                    documents.push(self.finish_document(data, start, end));
+                    data = GedcomData::default();
+                    start = (end, self.tokenizer.line);
+                }
+            }
+        }
+    }
+
+    /// Builds the source map and span map of a document parsed from `self.input[start.0..end]`,
+    /// starting on line `start.1`, when they are asked for, and hands it the strings interned since
+    /// the previous document
+    fn finish_document(
+        &mut self,
+        mut data: GedcomData,
+        start: (usize, u32),
+        end: usize,
+    ) -> GedcomData {
+        data.strings = std::mem::take(&mut self.tokenizer.strings);
+        if self.tokenizer.options.preserve_lines {
+            let (offset, line) = start;
+            let map = SourceMap::new(&self.input[offset..end], &data);
+            data.source_map = Some(map.starting_at_line(line.max(1)));
+        }
+        if self.tokenizer.options.record_spans {
+            let (offset, line) = start;
+            let spans = SpanMap::new(&self.input[offset..end]);
+            data.spans = Some(spans.starting_at(offset, line.max(1)));
+        }
+        data
+    }
+
+    /// Registers a handler called with each occurrence of the user-defined tag `tag`, e.g. a
+    /// vendor extension, once its subtree is parsed. The tag is still kept in the `custom_data` of
+    /// the structure it belongs to. An error returned by the handler fails the parse, or in
+    /// lenient mode is recorded as a warning.
+    ///
+    /// ```rust
+    /// use gedcom::GedcomDocument;
+    /// use std::collections::HashMap;
+    /// let sample = "\
+    ///    0 HEAD\n\
+    ///    1 GEDC\n\
+    ///    2 VERS 5.5\n\
+    ///    0 @I1@ INDI\n\
+    ///    1 NAME John /Doe/\n\
+    ///    1 _MILT Army\n\
+    ///    2 DATE 1917\n\
+    ///    0 TRLR";
+    ///
+    /// let mut service = HashMap::new();
+    /// let mut doc = GedcomDocument::new(sample.chars());
+    /// doc.on_custom_tag("_MILT", |milt| {
+    ///     let date = milt.dataset.child("DATE").and_then(|date| date.value.clone());
+    ///     service.insert(milt.record.unwrap().to_string(), date);
+    ///     Ok(())
+    /// });
+    /// doc.parse_document().unwrap();
+    /// drop(doc);
+    ///
+    /// assert_eq!(service["@I1@"].as_deref(), Some("1917"));
+    /// ```
+    pub fn on_custom_tag<F>(&mut self, tag: &str, handler: F) -> &mut GedcomDocument<'a>
+    where
+        F: FnMut(&CustomTagContext) -> Result<(), GedcomError> + 'a,
+    {
+        self.tokenizer.on_custom_tag(tag, handler);
+        self
+    }
+
+    /// Registers the user-defined tag `tag`, e.g. a vendor's `_MILT`, as a kind of event. Within
+    /// individuals and families the tag is then parsed as an `EventDetail` of
+    /// `Event::Registered`, with its date, place, citations and so on, rather than kept as custom
+    /// data. Elsewhere it stays custom data.
+    ///
+    /// ```rust
+    /// use gedcom::{types::{Event, EventCategory}, GedcomDocument};
+    /// let sample = "\
+    ///    0 HEAD\n\
+    ///    1 GEDC\n\
+    ///    2 VERS 5.5\n\
+    ///    0 @I1@ INDI\n\
+    ///    1 NAME John /Doe/\n\
+    ///    1 _MILT Private, 107th Infantry\n\
+    ///    2 DATE 1917\n\
+    ///    2 PLAC Rochester, New York, USA\n\
+    ///    0 TRLR";
+    ///
+    /// let mut doc = GedcomDocument::new(sample.chars());
+    /// doc.register_event_type("_MILT", "Military Service", EventCategory::Military);
+    /// let data = doc.parse_document().unwrap();
+    ///
+    /// let service = &data.individuals[0].events[0];
+    /// assert_eq!(service.event.to_string(), "Military Service");
+    /// assert_eq!(service.event.tag(), "_MILT");
+    /// assert!(matches!(
+    ///     &service.event,
+    ///     Event::Registered(event_type) if event_type.category == EventCategory::Military
+    /// ));
+    /// assert_eq!(service.place.as_deref(), Some("Rochester, New York, USA"));
+    /// assert!(data.individuals[0].custom_data.is_empty());
+    /// ```
+    pub fn register_event_type(
+        &mut self,
+        tag: &str,
+        name: &str,
+        category: EventCategory,
+    ) -> &mut GedcomDocument<'a> {
+        self.tokenizer.register_event_type(CustomEventType {
+            tag: tag.to_string(),
+            name: name.to_string(),
+            category,
+        });
+        self
+    }
+
+    /// Reads the user-defined tag of `alias` as its standard tag, e.g. `_MARNM` as a NAME of TYPE
+    /// married, so that the data of quirky exporters lands in the typed model rather than custom
+    /// data. Aliased structures are written back with the standard tag. It replaces any alias
+    /// registered for the tag before. See `tokenizer::TagAlias` for an example.
+    pub fn register_tag_alias(&mut self, alias: TagAlias) -> &mut GedcomDocument<'a> {
+        self.tokenizer.register_tag_alias(alias);
+        self
+    }
+
+    /// Lets another thread stop the parse through `token`, e.g. when the user aborts a long
+    /// import. The parser checks the token before each top-level record, and once it is cancelled
+    /// fails with `GedcomError::Cancelled`, in lenient mode too. The records parsed by
+    /// `parse_document` until then are kept, see `GedcomDocument::take_partial_data`.
+    ///
+    /// ```rust
+    /// use gedcom::{CancellationToken, GedcomDocument, GedcomError};
+    /// let sample = "\
+    ///    0 HEAD\n\
+    ///    1 GEDC\n\
+    ///    2 VERS 5.5\n\
+    ///    0 @I1@ INDI\n\
+    ///    1 NAME John /Doe/\n\
+    ///    1 _MILT Army\n\
+    ///    0 @I2@ INDI\n\
+    ///    1 NAME Jane /Doe/\n\
+    ///    0 TRLR";
+    ///
+    /// let token = CancellationToken::new();
+    /// let mut doc = GedcomDocument::new(sample.chars());
+    /// doc.set_cancellation(token.clone());
+    /// // cancelled from within the parse here, usually from another thread
+    /// doc.on_custom_tag("_MILT", move |_| {
+    ///     token.cancel();
+    ///     Ok(())
+    /// });
+    ///
+    /// let err = doc.parse_document().unwrap_err();
+    /// assert!(matches!(err, GedcomError::Cancelled { .. }));
+    /// assert_eq!(err.to_string(), "line 7, column 1: Parse cancelled");
+    ///
+    /// let partial = doc.take_partial_data().unwrap();
+    /// assert_eq!(partial.individuals.len(), 1);
+    /// ```
+    pub fn set_cancellation(
+        &mut self,
+        token: impl Into<CancellationToken>,
+    ) -> &mut GedcomDocument<'a> {
+        self.tokenizer.cancellation = Some(token.into());
+        self
+    }
+
+    /// The records parsed before `parse_document` failed with `GedcomError::Cancelled`, see
+    /// `GedcomDocument::set_cancellation`. They are handed over once, and `None` when the last
+    /// parse wasn't cancelled.
+    pub fn take_partial_data(&mut self) -> Option<GedcomData> {
+        self.partial_data.take()
+    }
+
+    /// Parses the records of the document one at a time, so that large files can be streamed
+    /// without building the whole `GedcomData`. Records the parser doesn't understand are skipped,
+    /// and the iterator ends at the TRLR or after the first error.
+    ///
+    /// ```rust
+    /// use gedcom::{GedcomDocument, Record};
+    /// let sample = "\
+    ///    0 HEAD\n\
+    ///    1 GEDC\n\
+    ///    2 VERS 5.5\n\
+    ///    0 @I1@ INDI\n\
+    ///    1 NAME John /Doe/\n\
+    ///    0 @F1@ FAM\n\
+    ///    1 HUSB @I1@\n\
+    ///    0 @I2@ INDI\n\
+    ///    1 NAME Jane /Doe/\n\
+    ///    0 TRLR";
+    ///
+    /// let mut doc = GedcomDocument::new(sample.chars());
+    /// let names: Vec<String> = doc
+    ///     .records()
+    ///     .filter_map(|record| match record.unwrap() {
+    ///         Record::Individual(indi) => indi.name?.value,
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(names, vec!["John /Doe/", "Jane /Doe/"]);
+    /// ```
+    pub fn records(&mut self) -> RecordIter<'_, 'a> {
+        RecordIter {
+            tokenizer: &mut self.tokenizer,
+            done: false,
+        }
+    }
+
+    /// The size of the input in bytes, which the bytes read passed to `ParseOptions::progress`
+    /// reach at the end of the parse
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{GedcomDocument, ParseOptions};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static BYTES_READ: AtomicUsize = AtomicUsize::new(0);
+    /// static RECORDS_PARSED: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Doe/\n\
+    ///     0 TRLR\n";
+    ///
+    /// let options = ParseOptions {
+    ///     progress: Some(|bytes_read, records_parsed| {
+    ///         BYTES_READ.store(bytes_read, Ordering::Relaxed);
+    ///         RECORDS_PARSED.store(records_parsed, Ordering::Relaxed);
+    ///     }),
+    ///     ..ParseOptions::default()
+    /// };
+    /// let mut doc = GedcomDocument::with_options(sample.chars(), options);
+    /// assert_eq!(doc.input_len(), 62);
+    /// doc.parse_document().unwrap();
+    ///
+    /// assert_eq!(BYTES_READ.load(Ordering::Relaxed), 62);
+    /// assert_eq!(RECORDS_PARSED.load(Ordering::Relaxed), 2);
+    /// ```
+    #[must_use]
+    pub fn input_len(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Problems found and recovered from while parsing the document
+    #[must_use]
+    pub fn warnings(&self) -> &[GedcomError] {
+        self.tokenizer.warnings()
+    }
+
+    /// The records skipped with `ParseOptions::skip_broken_records`, in file order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{GedcomDocument, ParseOptions};
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Doe/\n\
+    ///     1 SEX Z\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 NAME Jane /Doe/\n\
+    ///     0 TRLR";
+    ///
+    /// let options = ParseOptions {
+    ///     skip_broken_records: true,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let mut doc = GedcomDocument::with_options(sample.chars(), options);
+    /// let data = doc.parse_document().unwrap();
+    ///
+    /// assert_eq!(data.individuals.len(), 1);
+    /// assert_eq!(data.individuals[0].xref.as_deref(), Some("@I2@"));
+    /// let skipped = &doc.record_errors()[0];
+    /// assert_eq!(skipped.xref.as_deref(), Some("@I1@"));
+    /// assert_eq!(skipped.line, 4);
+    /// assert_eq!(skipped.cause.line(), 6);
+    /// ```
+    #[must_use]
+    pub fn record_errors(&self) -> &[RecordError] {
+        &self.tokenizer.record_errors
+    }
+}
+
+/// `ParseOptions` controls how the parser handles problems in a file.
+// each option is an independent switch
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    /// When set, problems within a record are collected as warnings and the offending structure
+    /// is skipped, rather than failing the whole parse. Off by default.
+    pub lenient: bool,
+    /// When set, the original text of each top-level record is kept in `GedcomData::source_map`,
+    /// so that records left untouched are written back byte for byte, see the `roundtrip` module.
+    /// Off by default.
+    pub preserve_lines: bool,
+    /// When set, a citation identical to one already cited by the same structure is dropped, and
+    /// counted in the `SourceCitation::duplicates` of the one kept. This saves memory on exports
+    /// repeating the same citation many times per record. Off by default.
+    pub deduplicate_citations: bool,
+    /// When set, standard tags written in lower or mixed case by sloppy exporters, e.g. `0 head`
+    /// or `1 Birt`, are read as their uppercase form, with a `GedcomError::MiscasedTag` warning
+    /// for each. Otherwise they are unknown tags. Off by default.
+    pub case_insensitive_tags: bool,
+    /// When set, each distinct place and surname is allocated once and shared by all the
+    /// structures using it, see the `intern` module. This saves memory on large files. Off by
+    /// default.
+    pub intern_strings: bool,
+    /// When set, the lines and byte offsets each structure was read from are kept in
+    /// `GedcomData::spans`, for editor tooling, see the `spans` module. Off by default.
+    pub record_spans: bool,
+    /// When set, a top-level record which fails to parse is left out of the tree, and parsing
+    /// resumes at the next record. Each record skipped is reported as a `RecordError` by
+    /// `GedcomDocument::record_errors`, so that a bad record doesn't lose the rest of the file,
+    /// e.g. when ingesting files in bulk. Off by default.
+    pub skip_broken_records: bool,
+    /// Called as the parse goes with the bytes of the input read so far and the number of
+    /// top-level records parsed, for progress bars over long parses. It is called each time
+    /// another 64 KiB of the input is read, and once at the end of each document, when the bytes
+    /// read reach `GedcomDocument::input_len` for the last one. `None` by default.
+    pub progress: Option<fn(usize, usize)>,
+}
+
+impl ParseOptions {
+    /// Options for a lenient parse, suited to messy real-world files
+    #[must_use]
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            lenient: true,
+            ..ParseOptions::default()
+        }
+    }
+}
+
+/// `CancellationToken` stops a parse from another thread, see `GedcomDocument::set_cancellation`.
+/// Clones share the same state, so a clone kept by the caller cancels the parse it was handed to.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token which isn't cancelled yet
+    #[must_use]
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancels the parses using the token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token was cancelled
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    /// Uses a flag of the caller's, which cancels the parse once set to `true`
+    fn from(flag: Arc<AtomicBool>) -> CancellationToken {
+        CancellationToken(flag)
     }
 }
 
 /// The Parser trait converts a subset of a token list into a type's data structure.
 pub trait Parser {
     /// parse does the actual parsing of a subset of a token list
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8);
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError` when the subset can't be parsed.
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError>;
 }
 
-#[must_use]
 /// Helper function for converting GEDCOM file content stream to parsed data.
-pub fn parse_ged(content: std::str::Chars) -> GedcomData {
+///
+/// # Errors
+///
+/// See `GedcomDocument::parse_document`.
+pub fn parse_ged(content: std::str::Chars) -> Result<GedcomData, GedcomError> {
     let mut p = GedcomDocument::new(content);
     p.parse_document()
 }
 
+/// Helper function for converting the raw bytes of a GEDCOM file to parsed data. The encoding
+/// (UTF-8 or UTF-16) is detected and decoded transparently, see `input::decode`.
+///
+/// # Errors
+///
+/// See `GedcomDocument::parse_document`.
+pub fn parse_bytes(bytes: &[u8]) -> Result<GedcomData, GedcomError> {
+    let content = input::decode(bytes);
+    parse_ged(content.chars())
+}
+
+/// Helper function for reading and parsing a GEDCOM file, see `input::read_file`. With the
+/// `compression` feature, a `.ged.gz` or a zip archive containing a single `.ged` is read as well.
+///
+/// # Errors
+///
+/// Returns `ParseFileError::Io` when the file can't be read, or `ParseFileError::Parse` when its
+/// contents can't be parsed, see `GedcomDocument::parse_document`.
+pub fn parse_file(path: impl AsRef<std::path::Path>) -> Result<GedcomData, ParseFileError> {
+    let content = input::read_file(path)?;
+    Ok(parse_ged(content.chars())?)
+}
+
 /// parse_subset is a helper function that handles some boilerplate code involved in implementing
-/// the Parser trait. It returns a Vector of any UserDefinedData.
+/// the Parser trait. It returns a Vector of any UserDefinedData. An error returned by
+/// `tag_handler` fails the parse, or in lenient mode skips the structure of the tag. Substructures
+/// `tag_handler` leaves behind, e.g. after only taking the value of the line, are dropped with a
+/// `GedcomError::DroppedSubstructure` warning, see `Tokenizer::drop_substructures`.
+///
+/// # Errors
+///
+/// Returns the errors raised by `tag_handler`, or while tokenizing the subset.
 pub fn parse_subset<F>(
     tokenizer: &mut Tokenizer,
     level: u8,
     mut tag_handler: F,
-) -> Vec<Box<UserDefinedDataset>>
+) -> Result<Vec<Box<UserDefinedDataset>>, GedcomError>
 where
-    F: FnMut(&str, &mut Tokenizer),
+    F: FnMut(&str, &mut Tokenizer) -> Result<(), GedcomError>,
 {
     let mut non_standard_dataset = Vec::new();
     loop {
@@ -106,29 +764,40 @@ where
             }
         }
 
-        match &tokenizer.current_token {
+        match tokenizer.current_token {
             Token::Tag(tag) => {
-                let tag_clone = tag.clone();
-                tag_handler(tag_clone.as_str(), tokenizer);
+                if let Err(err) = tag_handler(tag, tokenizer) {
+                    tokenizer.recover(err, level + 1)?;
+                }
+                tokenizer.drop_substructures(tag, level + 1)?;
             }
             Token::CustomTag(tag) => {
-                let tag_clone = tag.clone();
-                non_standard_dataset.push(Box::new(UserDefinedDataset::new(
-                    tokenizer,
-                    level + 1,
-                    &tag_clone,
-                )));
-                // custom_data.push(parse_custom_tag(tokenizer, tag_clone));
+                // registered event types are offered to the structures holding events, e.g. INDI,
+                // and stay custom data elsewhere
+                if tokenizer.event_type(tag).is_some() {
+                    let line = tokenizer.line;
+                    match tag_handler(tag, tokenizer) {
+                        Ok(()) => continue,
+                        Err(_) if tokenizer.line == line => {}
+                        Err(err) => {
+                            tokenizer.recover(err, level + 1)?;
+                            continue;
+                        }
+                    }
+                }
+                non_standard_dataset.push(Box::new(tokenizer.take_custom_tag(level + 1, tag)?));
+            }
+            Token::Level(_) => tokenizer.next_token()?,
+            // a truncated file, reported once the whole document is parsed
+            Token::EOF => break,
+            _ => {
+                let err = tokenizer
+                    .parse_error(format!("Unhandled Token: {:?}", tokenizer.current_token));
+                tokenizer.recover(err, level + 1)?;
             }
-            Token::Level(_) => tokenizer.next_token(),
-            _ => panic!(
-                "{}, Unhandled Token: {:?}",
-                tokenizer.debug(),
-                tokenizer.current_token
-            ),
         }
     }
-    non_standard_dataset
+    Ok(non_standard_dataset)
 }
 
 /// GedcomData is the data structure representing all the data within a gedcom file
@@ -151,7 +820,7 @@ where
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// assert_eq!(data.submitters.len(), 1);
 /// assert_eq!(data.submitters[0].xref.as_ref().unwrap(), "@SUBMITTER@");
@@ -187,21 +856,37 @@ pub struct GedcomData {
     pub sources: Vec<Source>,
     /// A multimedia asset linked to a fact
     pub multimedia: Vec<MultimediaRecord>,
+    /// Shared notes, which other structures point to rather than repeating their text
+    pub notes: Vec<NoteRecord>,
     /// Applications requiring the use of nonstandard tags should define them with a leading underscore
     /// so that they will not conflict with future GEDCOM standard tags. Systems that read
     /// user-defined tags must consider that they have meaning only with respect to a system
     /// contained in the HEAD.SOUR context.
     pub custom_data: Vec<Box<UserDefinedDataset>>,
+    /// The original text of the records, when parsed with `ParseOptions::preserve_lines`
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub source_map: Option<SourceMap>,
+    /// The places and surnames shared by the records, when parsed with
+    /// `ParseOptions::intern_strings`
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub strings: StringPool,
+    /// The location of each structure in the file, when parsed with `ParseOptions::record_spans`
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub spans: Option<SpanMap>,
 }
 
 // should maybe store these by xref if available?
 impl GedcomData {
     /// contructor for GedcomData
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> GedcomData {
+    ///
+    /// # Errors
+    ///
+    /// See `GedcomDocument::parse_document`.
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<GedcomData, GedcomError> {
         let mut data = GedcomData::default();
-        data.parse(tokenizer, level);
-        data
+        data.parse(tokenizer, level)?;
+        data.strings = std::mem::take(&mut tokenizer.strings);
+        Ok(data)
     }
 
     /// Adds a `Family` (a relationship between individuals) to the tree
@@ -239,83 +924,353 @@ impl GedcomData {
         self.multimedia.push(multimedia);
     }
 
+    /// Adds a shared `NoteRecord` to the tree
+    pub fn add_note(&mut self, note: NoteRecord) {
+        self.notes.push(note);
+    }
+
     /// Adds a `UserDefinedData` to the tree
     pub fn add_custom_data(&mut self, non_standard_data: UserDefinedDataset) {
         self.custom_data.push(Box::new(non_standard_data));
     }
 
-    /// Outputs a summary of data contained in the tree to stdout
-    pub fn stats(&self) {
-        println!("----------------------");
-        println!("| Gedcom Data Stats: |");
-        println!("----------------------");
-        println!("  submissions: {}", self.submissions.len());
-        println!("  submitters: {}", self.submitters.len());
-        println!("  individuals: {}", self.individuals.len());
-        println!("  families: {}", self.families.len());
-        println!("  repositories: {}", self.repositories.len());
-        println!("  sources: {}", self.sources.len());
-        println!("  multimedia: {}", self.multimedia.len());
-        println!("----------------------");
+    /// Adds a `Record`, e.g. one yielded by `GedcomDocument::records`, to the tree
+    pub fn add_record(&mut self, record: Record) {
+        match record {
+            Record::Header(head) => self.header = Some(head),
+            Record::Submitter(subm) => self.add_submitter(subm),
+            Record::Submission(subn) => self.add_submission(subn),
+            Record::Individual(indi) => self.add_individual(indi),
+            Record::Family(fam) => self.add_family(fam),
+            Record::Repository(repo) => self.add_repository(repo),
+            Record::Source(sour) => self.add_source(sour),
+            Record::Multimedia(obje) => self.add_multimedia(obje),
+            Record::Note(note) => self.add_note(note),
+            Record::Custom(custom) => self.custom_data.push(custom),
+        }
+    }
+
+    /// Where the document came from, from the metadata of its header, see `ProvenanceInfo`
+    #[must_use]
+    pub fn provenance(&self) -> ProvenanceInfo<'_> {
+        self.header
+            .as_ref()
+            .map(Header::provenance)
+            .unwrap_or_default()
+    }
+
+    /// The top-level records of the tree, whatever their kind, in the order they are written
+    /// out. The header, which isn't a record with an xref, is left out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{GedcomDocument, RecordRef};
+    /// let sample = "\
+    ///    0 HEAD\n\
+    ///    1 GEDC\n\
+    ///    2 VERS 5.5\n\
+    ///    0 @F1@ FAM\n\
+    ///    1 HUSB @I1@\n\
+    ///    0 @I1@ INDI\n\
+    ///    1 NAME John /Doe/\n\
+    ///    0 @S1@ SOUR\n\
+    ///    1 TITL Parish register\n\
+    ///    0 @N1@ NOTE Moved to Leeds in 1850\n\
+    ///    0 @P1@ _PLAC Leeds\n\
+    ///    0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let records: Vec<String> = data
+    ///     .records()
+    ///     .map(|record| format!("{} {}", record.xref().unwrap(), record.tag()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     records,
+    ///     vec!["@I1@ INDI", "@F1@ FAM", "@S1@ SOUR", "@N1@ NOTE", "@P1@ _PLAC"]
+    /// );
+    ///
+    /// match data.get_record("@S1@") {
+    ///     Some(RecordRef::Source(source)) => {
+    ///         assert_eq!(source.title.as_deref(), Some("Parish register"));
+    ///     }
+    ///     _ => panic!("expected a source"),
+    /// }
+    /// assert!(matches!(data.get_record("@N1@"), Some(RecordRef::Note(_))));
+    /// assert!(data.get_record("@S2@").is_none());
+    /// ```
+    pub fn records(&self) -> impl Iterator<Item = RecordRef<'_>> {
+        let submitters = self.submitters.iter().map(RecordRef::Submitter);
+        let submissions = self.submissions.iter().map(RecordRef::Submission);
+        let individuals = self.individuals.iter().map(RecordRef::Individual);
+        let families = self.families.iter().map(RecordRef::Family);
+        let sources = self.sources.iter().map(RecordRef::Source);
+        let repositories = self.repositories.iter().map(RecordRef::Repository);
+        let multimedia = self.multimedia.iter().map(RecordRef::Multimedia);
+        let notes = self.notes.iter().map(RecordRef::Note);
+        let custom = self
+            .custom_data
+            .iter()
+            .map(|custom| RecordRef::Custom(custom));
+        submitters
+            .chain(submissions)
+            .chain(individuals)
+            .chain(families)
+            .chain(sources)
+            .chain(repositories)
+            .chain(multimedia)
+            .chain(notes)
+            .chain(custom)
+    }
+
+    /// The top-level record with the xref, whatever its kind, see `GedcomData::records`. The
+    /// records are scanned in turn, see `store::IndexedGedcomData` for constant time lookups.
+    #[must_use]
+    pub fn get_record(&self, xref: &str) -> Option<RecordRef<'_>> {
+        self.records().find(|record| record.xref() == Some(xref))
     }
 }
 
 impl Parser for GedcomData {
     /// Does the actual parsing of the record.
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        loop {
-            let current_level = match tokenizer.current_token {
-                Token::Level(n) => n,
-                _ => panic!(
-                    "{} Expected Level, found {:?}",
-                    tokenizer.debug(),
-                    tokenizer.current_token
-                ),
-            };
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        while let Some(record) = next_record(tokenizer, level)? {
+            self.add_record(record);
+        }
+        Ok(())
+    }
+}
+
+/// `Record` is a top-level record of a GEDCOM file, as yielded by `GedcomDocument::records`.
+// records are yielded one at a time and moved into the tree right away, so boxing them would only
+// add an allocation each
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Record {
+    /// The HEAD record
+    Header(Header),
+    /// A SUBM record
+    Submitter(Submitter),
+    /// A SUBN record
+    Submission(Submission),
+    /// An INDI record
+    Individual(Individual),
+    /// A FAM record
+    Family(Family),
+    /// A REPO record
+    Repository(Repository),
+    /// A SOUR record
+    Source(Source),
+    /// An OBJE record
+    Multimedia(MultimediaRecord),
+    /// A NOTE record
+    Note(NoteRecord),
+    /// A top-level user-defined tag
+    Custom(Box<UserDefinedDataset>),
+}
+
+/// `RecordRef` borrows a top-level record of a `GedcomData`, whatever its kind, as yielded by
+/// `GedcomData::records`.
+#[derive(Clone, Copy, Debug)]
+pub enum RecordRef<'a> {
+    /// A SUBM record
+    Submitter(&'a Submitter),
+    /// A SUBN record
+    Submission(&'a Submission),
+    /// An INDI record
+    Individual(&'a Individual),
+    /// A FAM record
+    Family(&'a Family),
+    /// A SOUR record
+    Source(&'a Source),
+    /// A REPO record
+    Repository(&'a Repository),
+    /// An OBJE record
+    Multimedia(&'a MultimediaRecord),
+    /// A NOTE record
+    Note(&'a NoteRecord),
+    /// A top-level user-defined tag
+    Custom(&'a UserDefinedDataset),
+}
+
+impl<'a> RecordRef<'a> {
+    /// The xref of the record, e.g. `@I1@`
+    #[must_use]
+    pub fn xref(&self) -> Option<&'a str> {
+        match self {
+            RecordRef::Submitter(subm) => subm.xref.as_deref(),
+            RecordRef::Submission(subn) => subn.xref.as_deref(),
+            RecordRef::Individual(indi) => indi.xref.as_deref(),
+            RecordRef::Family(fam) => fam.xref.as_deref(),
+            RecordRef::Source(sour) => sour.xref.as_deref(),
+            RecordRef::Repository(repo) => repo.xref.as_deref(),
+            RecordRef::Multimedia(obje) => obje.xref.as_deref(),
+            RecordRef::Note(note) => note.xref.as_deref(),
+            RecordRef::Custom(custom) => custom.xref.as_deref(),
+        }
+    }
+
+    /// The tag of the record, e.g. INDI, or the tag of a user-defined record, e.g. `_PLAC`
+    #[must_use]
+    pub fn tag(&self) -> &'a str {
+        match self {
+            RecordRef::Submitter(_) => "SUBM",
+            RecordRef::Submission(_) => "SUBN",
+            RecordRef::Individual(_) => "INDI",
+            RecordRef::Family(_) => "FAM",
+            RecordRef::Source(_) => "SOUR",
+            RecordRef::Repository(_) => "REPO",
+            RecordRef::Multimedia(_) => "OBJE",
+            RecordRef::Note(_) => "NOTE",
+            RecordRef::Custom(custom) => &custom.tag,
+        }
+    }
+
+    /// Copies the record into an owned `Record`, e.g. to add it to another tree with
+    /// `GedcomData::add_record`
+    #[must_use]
+    pub fn to_owned(&self) -> Record {
+        match *self {
+            RecordRef::Submitter(subm) => Record::Submitter(subm.clone()),
+            RecordRef::Submission(subn) => Record::Submission(subn.clone()),
+            RecordRef::Individual(indi) => Record::Individual(indi.clone()),
+            RecordRef::Family(fam) => Record::Family(fam.clone()),
+            RecordRef::Source(sour) => Record::Source(sour.clone()),
+            RecordRef::Repository(repo) => Record::Repository(repo.clone()),
+            RecordRef::Multimedia(obje) => Record::Multimedia(obje.clone()),
+            RecordRef::Note(note) => Record::Note(note.clone()),
+            RecordRef::Custom(custom) => Record::Custom(Box::new(custom.clone())),
+        }
+    }
+}
+
+/// `RecordIter` parses the records of a document one at a time, see `GedcomDocument::records`.
+pub struct RecordIter<'d, 'a> {
+    tokenizer: &'d mut Tokenizer<'a>,
+    done: bool,
+}
+
+impl Iterator for RecordIter<'_, '_> {
+    type Item = Result<Record, GedcomError>;
 
-            tokenizer.next_token();
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = if self.tokenizer.current_token == Token::None {
+            self.tokenizer
+                .next_token()
+                .and_then(|()| next_record(self.tokenizer, 0))
+        } else {
+            next_record(self.tokenizer, 0)
+        };
+        // the parse can't carry on after an error, nor past the TRLR
+        if !matches!(record, Ok(Some(_))) {
+            self.done = true;
+        }
+        record.transpose()
+    }
+}
 
-            let mut pointer: Option<String> = None;
-            if let Token::Pointer(xref) = &tokenizer.current_token {
-                pointer = Some(xref.to_string());
-                tokenizer.next_token();
+/// Parses the next record at `level`, skipping the records which aren't understood. Returns
+/// `None` once the TRLR is reached, or at the end of a truncated file in lenient mode.
+fn next_record(tokenizer: &mut Tokenizer, level: u8) -> Result<Option<Record>, GedcomError> {
+    loop {
+        tokenizer.check_cancelled()?;
+        let line = tokenizer.line;
+        let current_level = match tokenizer.current_token {
+            Token::Level(n) => n,
+            Token::EOF => {
+                let err = tokenizer.parse_error("Unexpected end of file, expected TRLR");
+                tokenizer.recover(err, level)?;
+                tokenizer.document_parsed();
+                return Ok(None);
             }
+            _ => {
+                let err = tokenizer.parse_error(format!(
+                    "Expected Level, found {:?}",
+                    tokenizer.current_token
+                ));
+                tokenizer.recover(err, level)?;
+                continue;
+            }
+        };
 
-            if let Token::Tag(tag) = &tokenizer.current_token {
-                match tag.as_str() {
-                    "HEAD" => self.header = Some(Header::new(tokenizer, level)),
-                    "FAM" => self.add_family(Family::new(tokenizer, level, pointer)),
-                    "INDI" => {
-                        self.add_individual(Individual::new(tokenizer, current_level, pointer))
-                    }
-                    "REPO" => {
-                        self.add_repository(Repository::new(tokenizer, current_level, pointer))
-                    }
-                    "SOUR" => self.add_source(Source::new(tokenizer, current_level, pointer)),
-                    "SUBN" => self.add_submission(Submission::new(tokenizer, level, pointer)),
-                    "SUBM" => self.add_submitter(Submitter::new(tokenizer, level, pointer)),
-                    "OBJE" => self.add_multimedia(MultimediaRecord::new(tokenizer, level, pointer)),
-                    "TRLR" => break,
-                    _ => {
-                        println!("{} Unhandled tag {}", tokenizer.debug(), tag);
-                        tokenizer.next_token();
-                    }
-                };
-            } else if let Token::CustomTag(tag) = &tokenizer.current_token {
-                let tag_clone = tag.clone();
-                self.add_custom_data(UserDefinedDataset::new(tokenizer, level + 1, &tag_clone));
-                // self.add_custom_data(parse_custom_tag(tokenizer, tag_clone));
-                while tokenizer.current_token != Token::Level(level) {
-                    tokenizer.next_token();
+        tokenizer.next_token()?;
+
+        let mut pointer: Option<String> = None;
+        if let Token::Pointer(xref) = tokenizer.current_token {
+            pointer = Some(xref.to_string());
+            tokenizer.next_token()?;
+        }
+        tokenizer.record.clone_from(&pointer);
+        #[cfg(feature = "log")]
+        if let Token::Tag(tag) | Token::CustomTag(tag) = tokenizer.current_token {
+            let xref = pointer.as_deref().unwrap_or("without xref");
+            log::debug!(target: "gedcom", "line {}: {tag} record {xref}", tokenizer.line);
+        }
+
+        let result = match tokenizer.current_token {
+            Token::Tag(tag) => match tag {
+                "HEAD" => Header::new(tokenizer, level).map(|head| Some(Record::Header(head))),
+                "FAM" => {
+                    Family::new(tokenizer, level, pointer).map(|fam| Some(Record::Family(fam)))
                 }
-            } else {
-                println!(
-                    "{} Unhandled token {:?}",
-                    tokenizer.debug(),
-                    tokenizer.current_token
-                );
-                tokenizer.next_token();
-            };
+                "INDI" => Individual::new(tokenizer, current_level, pointer)
+                    .map(|indi| Some(Record::Individual(indi))),
+                "REPO" => Repository::new(tokenizer, current_level, pointer)
+                    .map(|repo| Some(Record::Repository(repo))),
+                "SOUR" => Source::new(tokenizer, current_level, pointer)
+                    .map(|sour| Some(Record::Source(sour))),
+                "SUBN" => Submission::new(tokenizer, level, pointer)
+                    .map(|subn| Some(Record::Submission(subn))),
+                "SUBM" => Submitter::new(tokenizer, level, pointer)
+                    .map(|subm| Some(Record::Submitter(subm))),
+                "OBJE" => MultimediaRecord::new(tokenizer, level, pointer)
+                    .map(|obje| Some(Record::Multimedia(obje))),
+                "NOTE" => {
+                    NoteRecord::new(tokenizer, level, pointer).map(|note| Some(Record::Note(note)))
+                }
+                "TRLR" => {
+                    tokenizer.document_parsed();
+                    return Ok(None);
+                }
+                _ => {
+                    let warning = tokenizer.unhandled_tag("record", tag);
+                    tokenizer.warn(warning);
+                    tokenizer.next_token()?;
+                    tokenizer.skip_subtree(level).map(|()| None)
+                }
+            },
+            Token::CustomTag(tag) => {
+                tokenizer
+                    .take_custom_tag(level, tag)
+                    .and_then(|mut custom| {
+                        custom.xref = pointer;
+                        tokenizer.skip_subtree(level)?;
+                        Ok(Some(Record::Custom(Box::new(custom))))
+                    })
+            }
+            _ => {
+                let warning =
+                    tokenizer.parse_error(format!("Unhandled token {:?}", tokenizer.current_token));
+                tokenizer.warn(warning);
+                tokenizer.next_token()?;
+                tokenizer.skip_subtree(level).map(|()| None)
+            }
+        };
+        tokenizer.record_parsed();
+        match result {
+            Ok(Some(record)) => return Ok(Some(record)),
+            Ok(None) => (),
+            Err(cause) if tokenizer.options.skip_broken_records => {
+                let xref = tokenizer.record.clone();
+                tokenizer
+                    .record_errors
+                    .push(RecordError { xref, line, cause });
+                tokenizer.skip_to_level(level);
+            }
+            Err(err) => tokenizer.recover(err, level)?,
         }
     }
 }
@@ -0,0 +1,298 @@
+//! Style and conformance checks over the lines of a file, as opposed to the checks of the tree
+//! made by the `validate` module.
+//!
+//! `lint` reads the text line by line, without parsing it, and reports each line breaking a rule
+//! of the specification: lines over the length limit, xrefs with characters other than letters,
+//! digits and underscores, CONC lines without a value, levels more than one deeper than the line
+//! before, xrefs defined twice, files without a TRLR, and files mixing CRLF, LF and CR line
+//! terminators. Every `Lint` carries the `Rule` it
+//! breaks, whose id, e.g. `level-jump`, tools can use to let users turn rules off, see
+//! `LintOptions::allow`.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::lint::{self, LintOptions, Rule};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5.1\n\
+//!     0 @I#1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     3 DATE 1850\n\
+//!     1 NOTE Some\n\
+//!     2 CONC\n";
+//!
+//! let lints = lint::lint(sample);
+//! let found: Vec<(&str, u32)> = lints.iter().map(|lint| (lint.rule.id(), lint.line)).collect();
+//! assert_eq!(
+//!     found,
+//!     vec![
+//!         ("xref-characters", 4),
+//!         ("level-jump", 6),
+//!         ("empty-conc", 8),
+//!         ("missing-trlr", 8),
+//!     ]
+//! );
+//! assert_eq!(
+//!     lints[1].to_string(),
+//!     "line 6: level 3 follows level 1 [level-jump]"
+//! );
+//!
+//! let options = LintOptions {
+//!     allow: vec!["missing-trlr".parse::<Rule>().unwrap()],
+//!     ..LintOptions::default()
+//! };
+//! assert_eq!(lint::lint_with(sample, &options).len(), 3);
+//! ```
+
+use std::{collections::HashMap, convert::TryFrom, fmt, str::FromStr};
+
+use crate::util::{split_lines, split_lines_inclusive, LineFields};
+
+/// `Rule` is a check made by `lint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// A line is longer than `LintOptions::max_line_length` characters
+    LineTooLong,
+    /// An xref has characters other than letters, digits and underscores between its @ signs
+    XrefCharacters,
+    /// A CONC line has no value to add
+    EmptyConc,
+    /// A line is more than one level deeper than the line before it
+    LevelJump,
+    /// More than one top-level record has the same xref
+    DuplicateXref,
+    /// The file doesn't end with a TRLR record
+    MissingTrlr,
+    /// A line ends with another terminator than the first line, e.g. LF among CRLF lines
+    MixedLineEndings,
+}
+
+impl Rule {
+    /// Every rule, in the order of the `Rule` variants
+    pub const ALL: [Rule; 7] = [
+        Rule::LineTooLong,
+        Rule::XrefCharacters,
+        Rule::EmptyConc,
+        Rule::LevelJump,
+        Rule::DuplicateXref,
+        Rule::MissingTrlr,
+        Rule::MixedLineEndings,
+    ];
+
+    /// The id of the rule, e.g. `level-jump`, which `Rule::from_str` reads back
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::LineTooLong => "line-too-long",
+            Rule::XrefCharacters => "xref-characters",
+            Rule::EmptyConc => "empty-conc",
+            Rule::LevelJump => "level-jump",
+            Rule::DuplicateXref => "duplicate-xref",
+            Rule::MissingTrlr => "missing-trlr",
+            Rule::MixedLineEndings => "mixed-line-endings",
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// Reads a rule id, e.g. `level-jump`
+    fn from_str(id: &str) -> Result<Rule, String> {
+        Rule::ALL
+            .iter()
+            .copied()
+            .find(|rule| rule.id() == id)
+            .ok_or_else(|| format!("unknown lint rule '{id}'"))
+    }
+}
+
+/// `Lint` is a line breaking a rule, see the `lint` module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// The rule broken
+    pub rule: Rule,
+    /// The line breaking it, starting at 1. A missing TRLR is reported on the last line.
+    pub line: u32,
+    /// What is wrong with the line
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} [{}]", self.line, self.message, self.rule)
+    }
+}
+
+/// `LintOptions` configures the checks made by `lint_with`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintOptions {
+    /// The rules not to check
+    pub allow: Vec<Rule>,
+    /// The longest a line may be, in characters and without its terminator. GEDCOM 5.5.1 allows
+    /// 255; GEDCOM 7 has no limit, which `usize::MAX` stands for.
+    pub max_line_length: usize,
+}
+
+impl Default for LintOptions {
+    /// Every rule, with the line length limit of GEDCOM 5.5.1
+    fn default() -> LintOptions {
+        LintOptions {
+            allow: Vec::new(),
+            max_line_length: 255,
+        }
+    }
+}
+
+/// Checks the lines of the file `contents` against every rule, see the `lint` module
+#[must_use]
+pub fn lint(contents: &str) -> Vec<Lint> {
+    lint_with(contents, &LintOptions::default())
+}
+
+/// Checks the lines of the file `contents` against the rules `options` doesn't allow, in line
+/// order
+#[must_use]
+pub fn lint_with(contents: &str, options: &LintOptions) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut report = |rule: Rule, line: u32, message: String| {
+        if !options.allow.contains(&rule) {
+            lints.push(Lint {
+                rule,
+                line,
+                message,
+            });
+        }
+    };
+
+    // the line defining each xref
+    let mut definitions: HashMap<&str, u32> = HashMap::new();
+    let mut previous_level: Option<u8> = None;
+    let mut last_record = None;
+    let mut last_line = 0;
+    for (number, line) in split_lines(contents).enumerate() {
+        let number = u32::try_from(number + 1).unwrap_or(u32::MAX);
+        last_line = number;
+        let length = line.chars().count();
+        if length > options.max_line_length {
+            report(
+                Rule::LineTooLong,
+                number,
+                format!(
+                    "line is {length} characters long, over the limit of {}",
+                    options.max_line_length
+                ),
+            );
+        }
+
+        let fields = LineFields::split(
+            line.trim_start_matches(|c: char| c.is_whitespace() || c == '\u{FEFF}'),
+        );
+        let Ok(level) = fields.level.parse::<u8>() else {
+            continue;
+        };
+        if let Some(previous) =
+            previous_level.filter(|previous| u16::from(level) > u16::from(*previous) + 1)
+        {
+            report(
+                Rule::LevelJump,
+                number,
+                format!("level {level} follows level {previous}"),
+            );
+        }
+        previous_level = Some(level);
+
+        let tag = fields.tag.unwrap_or_default();
+        if level == 0 {
+            last_record = Some(tag);
+        }
+        if tag == "CONC" && fields.value.is_none_or(str::is_empty) {
+            report(
+                Rule::EmptyConc,
+                number,
+                "CONC line has no value".to_string(),
+            );
+        }
+        let Some(xref) = fields.xref else {
+            continue;
+        };
+        if !is_valid_xref(xref) {
+            report(
+                Rule::XrefCharacters,
+                number,
+                format!("xref {xref} has characters other than letters, digits and underscores"),
+            );
+        }
+        if level == 0 {
+            if let Some(first) = definitions.get(xref) {
+                report(
+                    Rule::DuplicateXref,
+                    number,
+                    format!("xref {xref} is already defined on line {first}"),
+                );
+            } else {
+                definitions.insert(xref, number);
+            }
+        }
+    }
+    if last_record != Some("TRLR") {
+        report(
+            Rule::MissingTrlr,
+            last_line.max(1),
+            "file doesn't end with a TRLR record".to_string(),
+        );
+    }
+    if let Some((number, message)) = mixed_line_endings(contents) {
+        report(Rule::MixedLineEndings, number, message);
+    }
+    // sort_by_key is stable, keeping the order of the lints of a line
+    lints.sort_by_key(|lint| lint.line);
+    lints
+}
+
+/// The first line ending with another terminator than the first line, if any, along with the
+/// message reporting it
+fn mixed_line_endings(contents: &str) -> Option<(u32, String)> {
+    let mut terminators = split_lines_inclusive(contents)
+        .map(|line| &line[line.trim_end_matches(['\r', '\n']).len()..])
+        .enumerate();
+    let (_, first) = terminators.next()?;
+    let (number, other) =
+        terminators.find(|(_, terminator)| !terminator.is_empty() && *terminator != first)?;
+    let number = u32::try_from(number + 1).unwrap_or(u32::MAX);
+    Some((
+        number,
+        format!(
+            "line ends with {}, while line 1 ends with {}",
+            terminator_name(other),
+            terminator_name(first)
+        ),
+    ))
+}
+
+/// The name of a line terminator, e.g. CRLF
+fn terminator_name(terminator: &str) -> &'static str {
+    match terminator {
+        "\r\n" => "CRLF",
+        "\r" => "CR",
+        _ => "LF",
+    }
+}
+
+/// Whether the xref is a letter, digit or underscore followed by letters, digits and underscores,
+/// between @ signs
+fn is_valid_xref(xref: &str) -> bool {
+    xref.strip_prefix('@')
+        .and_then(|xref| xref.strip_suffix('@'))
+        .is_some_and(|id| {
+            !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
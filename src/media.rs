@@ -0,0 +1,212 @@
+//! Resolution of the FILE paths of multimedia to local files, and checks for broken media links.
+//!
+//! FILE paths are written in many ways: relative to the GEDCOM file, absolute, with Windows
+//! backslashes, or as `file://` URLs. `MultimediaFileRefn::local_path` brings them to a single
+//! form, with forward slashes and percent escapes decoded, and `MultimediaFileRefn::resolve`
+//! places relative paths within a base directory, usually the directory of the GEDCOM file.
+//! Remote URLs, e.g. `https://` links, aren't local files and resolve to `None`.
+//!
+//! `GedcomData::check_media` lists the FILE paths of the tree which don't lead to a file. The
+//! media of a GEDZIP package are checked with `Gedzip::check_media`, with the `gedzip` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{types::MultimediaFileRefn, GedcomDocument};
+//! use std::path::Path;
+//!
+//! let file = MultimediaFileRefn {
+//!     value: Some("photos\\John%20Smith.jpg".to_string()),
+//!     ..MultimediaFileRefn::default()
+//! };
+//! assert_eq!(file.local_path().as_deref(), Some("photos/John Smith.jpg"));
+//! assert_eq!(
+//!     file.resolve("/home/user/tree"),
+//!     Some(Path::new("/home/user/tree/photos/John Smith.jpg").to_path_buf())
+//! );
+//!
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @M1@ OBJE\n\
+//!     1 FILE gedzip/media/photos/John Smith.jpg\n\
+//!     0 @M2@ OBJE\n\
+//!     1 FILE gedzip/media/photos/Mary Smith.jpg\n\
+//!     0 @M3@ OBJE\n\
+//!     1 FILE https://example.org/photos/Thomas%20Smith.jpg\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let broken = data.check_media("./tests/fixtures");
+//! assert_eq!(broken.len(), 1);
+//! assert_eq!(
+//!     broken[0].to_string(),
+//!     "FILE gedzip/media/photos/Mary Smith.jpg of @M2@ leads to no file"
+//! );
+//! ```
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{types::MultimediaFileRefn, util::LineFields, GedcomData};
+
+/// `BrokenMedia` is a FILE path leading to no file, see `GedcomData::check_media`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrokenMedia {
+    /// The xref of the record holding the FILE, or its tag when it has none
+    pub record: String,
+    /// The FILE path as written in the tree
+    pub file: String,
+    /// Where the file was looked for
+    pub resolved: PathBuf,
+}
+
+impl fmt::Display for BrokenMedia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FILE {} of {} leads to no file", self.file, self.record)
+    }
+}
+
+impl MultimediaFileRefn {
+    /// The FILE path as a local path with forward slashes, decoded from a `file://` URL or from
+    /// percent escapes, or `None` for a remote URL or an empty path
+    #[must_use]
+    pub fn local_path(&self) -> Option<String> {
+        let value = self.value.as_deref()?.trim();
+        let path = match url_scheme(value) {
+            Some("file") => {
+                let path = &value["file://".len().min(value.len())..];
+                // file:///C:/photo.jpg is a Windows path
+                match path.strip_prefix('/') {
+                    Some(rest) if drive_letter(rest) => rest,
+                    _ => path,
+                }
+            }
+            Some(_) => return None,
+            None => value,
+        };
+        let path = path.replace('\\', "/");
+        let path = percent_decode(&path).unwrap_or(path);
+        if path.is_empty() {
+            None
+        } else {
+            Some(path)
+        }
+    }
+
+    /// The local file the FILE path leads to, relative paths being taken from `base_dir`, or
+    /// `None` for a remote URL. Windows paths with a drive letter, e.g. `C:/photo.jpg`, are kept
+    /// as they are.
+    #[must_use]
+    pub fn resolve(&self, base_dir: impl AsRef<Path>) -> Option<PathBuf> {
+        let path = self.local_path()?;
+        if path.starts_with('/') || drive_letter(&path) {
+            Some(PathBuf::from(path))
+        } else {
+            Some(base_dir.as_ref().join(path))
+        }
+    }
+
+    /// Whether the FILE path leads to an existing file, relative paths being taken from
+    /// `base_dir`. Remote URLs aren't checked and count as existing.
+    #[must_use]
+    pub fn exists(&self, base_dir: impl AsRef<Path>) -> bool {
+        match url_scheme(self.value.as_deref().unwrap_or_default().trim()) {
+            Some(scheme) if scheme != "file" => true,
+            _ => self.resolve(base_dir).is_some_and(|path| path.is_file()),
+        }
+    }
+}
+
+impl GedcomData {
+    /// Lists the FILE paths of the multimedia of the tree which lead to no file, relative paths
+    /// being taken from `base_dir`, in file order. Remote URLs aren't checked, see the `media`
+    /// module.
+    #[must_use]
+    pub fn check_media(&self, base_dir: impl AsRef<Path>) -> Vec<BrokenMedia> {
+        file_references(self)
+            .into_iter()
+            .filter_map(|(record, file)| {
+                let reference = MultimediaFileRefn {
+                    value: Some(file.clone()),
+                    ..MultimediaFileRefn::default()
+                };
+                if reference.exists(&base_dir) {
+                    return None;
+                }
+                let resolved = reference.resolve(&base_dir)?;
+                Some(BrokenMedia {
+                    record,
+                    file,
+                    resolved,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The FILE paths of the multimedia of `data`, along with the xref of the record holding them or
+/// its tag, in file order
+pub(crate) fn file_references(data: &GedcomData) -> Vec<(String, String)> {
+    let mut references = Vec::new();
+    let mut record = String::new();
+    for line in data.to_gedcom_string().lines() {
+        let line = LineFields::split(line);
+        if line.level == "0" {
+            record = line.xref.or(line.tag).unwrap_or_default().to_string();
+        }
+        // HEAD.FILE is the name of the document itself
+        if record == "HEAD" || line.tag != Some("FILE") {
+            continue;
+        }
+        if let Some(value) = line.value {
+            references.push((record.clone(), value.to_string()));
+        }
+    }
+    references
+}
+
+/// The scheme of a URL, e.g. "https", leaving out the drive letters of Windows paths
+pub(crate) fn url_scheme(reference: &str) -> Option<&str> {
+    let (scheme, _) = reference.split_once(':')?;
+    let is_scheme = scheme.len() > 1
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if is_scheme {
+        Some(scheme)
+    } else {
+        None
+    }
+}
+
+/// Whether the path starts with a Windows drive letter, e.g. `C:/`
+fn drive_letter(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+/// Decodes the `%XX` escapes of a URL path, or `None` when they don't decode to UTF-8
+pub(crate) fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = match tail {
+            [high, low, ..] if byte == b'%' => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        if let Some(decoded) = escaped {
+            bytes.push(decoded);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
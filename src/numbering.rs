@@ -0,0 +1,189 @@
+//! Genealogical numbering systems, for reports listing the ancestors or descendants of an
+//! individual.
+//!
+//! `ahnentafel` numbers the ancestors of a root individual: the root is 1, and the father and
+//! mother of the individual numbered `n` are `2n` and `2n + 1`. As in the `kinship` module, only
+//! genetic parentage is followed. An ancestor reached through more than one line of descent keeps
+//! the lowest of its numbers.
+//!
+//! `daboville` and `henry` number the descendants of a root individual, the children of each
+//! individual being numbered in the order of their families and of the children within them. In
+//! the d'Aboville system the numbers are separated by dots, e.g. `1.2.10`, while the Henry system
+//! appends a digit per generation, e.g. `12(10)`, with children past the ninth written between
+//! parentheses, as in the modified Henry system. A descendant reached through more than one line,
+//! e.g. the child of cousins, keeps the number it was reached by first.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{numbering, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @ROOT@ INDI\n\
+//!     1 FAMC @F1@\n\
+//!     1 FAMS @F2@\n\
+//!     0 @FATHER@ INDI\n\
+//!     1 FAMC @F3@\n\
+//!     0 @MOTHER@ INDI\n\
+//!     0 @GRANDMOTHER@ INDI\n\
+//!     0 @SON@ INDI\n\
+//!     1 FAMS @F4@\n\
+//!     0 @DAUGHTER@ INDI\n\
+//!     0 @GRANDSON@ INDI\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @FATHER@\n\
+//!     1 WIFE @MOTHER@\n\
+//!     1 CHIL @ROOT@\n\
+//!     0 @F3@ FAM\n\
+//!     1 WIFE @GRANDMOTHER@\n\
+//!     1 CHIL @FATHER@\n\
+//!     0 @F2@ FAM\n\
+//!     1 HUSB @ROOT@\n\
+//!     1 CHIL @SON@\n\
+//!     1 CHIL @DAUGHTER@\n\
+//!     0 @F4@ FAM\n\
+//!     1 HUSB @SON@\n\
+//!     1 CHIL @GRANDSON@\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//!
+//! let ancestors = numbering::ahnentafel(&data, "@ROOT@");
+//! assert_eq!(ancestors["@ROOT@"], 1);
+//! assert_eq!(ancestors["@FATHER@"], 2);
+//! assert_eq!(ancestors["@MOTHER@"], 3);
+//! assert_eq!(ancestors["@GRANDMOTHER@"], 5);
+//!
+//! let descendants = numbering::daboville(&data, "@ROOT@");
+//! assert_eq!(descendants["@ROOT@"], "1");
+//! assert_eq!(descendants["@DAUGHTER@"], "1.2");
+//! assert_eq!(descendants["@GRANDSON@"], "1.1.1");
+//!
+//! let descendants = numbering::henry(&data, "@ROOT@");
+//! assert_eq!(descendants["@GRANDSON@"], "111");
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{kinship::Kinship, types::FamilyLinkType, GedcomData};
+
+/// The most generations `ahnentafel` numbers, past which the numbers no longer fit in a `u64`
+pub(crate) const MAX_GENERATIONS: u32 = 63;
+
+/// Numbers the ancestors of the individual `root` with their Ahnentafel number, the root being 1,
+/// up to 63 generations back. Returns an empty map when there is no individual `root`. See the
+/// `numbering` module.
+#[must_use]
+pub fn ahnentafel<'a>(data: &'a GedcomData, root: &str) -> HashMap<&'a str, u64> {
+    let mut numbers = HashMap::new();
+    let Some(root) = individual_xref(data, root) else {
+        return numbers;
+    };
+    let kinship = Kinship::new(data);
+    // breadth first, so that each ancestor is reached first by its lowest number
+    let mut queue = VecDeque::from(vec![(root, 1_u64, 0)]);
+    while let Some((xref, number, generation)) = queue.pop_front() {
+        if numbers.contains_key(xref) {
+            continue;
+        }
+        numbers.insert(xref, number);
+        if generation == MAX_GENERATIONS {
+            continue;
+        }
+        let (father, mother) = kinship.parents(xref);
+        if let Some(father) = father {
+            queue.push_back((father, 2 * number, generation + 1));
+        }
+        if let Some(mother) = mother {
+            queue.push_back((mother, 2 * number + 1, generation + 1));
+        }
+    }
+    numbers
+}
+
+/// Numbers the descendants of the individual `root` in the d'Aboville system, e.g. `1.2.10` for
+/// the tenth child of the second child of the root. Returns an empty map when there is no
+/// individual `root`. See the `numbering` module.
+#[must_use]
+pub fn daboville<'a>(data: &'a GedcomData, root: &str) -> HashMap<&'a str, String> {
+    descendant_numbers(data, root, |parent, child| format!("{parent}.{child}"))
+}
+
+/// Numbers the descendants of the individual `root` in the modified Henry system, e.g. `12(10)`
+/// for the tenth child of the second child of the root. Returns an empty map when there is no
+/// individual `root`. See the `numbering` module.
+#[must_use]
+pub fn henry<'a>(data: &'a GedcomData, root: &str) -> HashMap<&'a str, String> {
+    descendant_numbers(data, root, |parent, child| {
+        if child < 10 {
+            format!("{parent}{child}")
+        } else {
+            format!("{parent}({child})")
+        }
+    })
+}
+
+/// Numbers the descendants of `root` depth first, `number` giving the number of the nth child,
+/// starting at 1, of the individual with the number given
+fn descendant_numbers<'a>(
+    data: &'a GedcomData,
+    root: &str,
+    number: impl Fn(&str, usize) -> String,
+) -> HashMap<&'a str, String> {
+    let mut numbers = HashMap::new();
+    let Some(root) = individual_xref(data, root) else {
+        return numbers;
+    };
+    let individuals: HashMap<&str, _> = data
+        .individuals
+        .iter()
+        .filter_map(|indi| Some((indi.xref.as_deref()?, indi)))
+        .collect();
+    let families: HashMap<&str, _> = data
+        .families
+        .iter()
+        .filter_map(|fam| Some((fam.xref.as_deref()?, fam)))
+        .collect();
+    let children = |xref: &str| -> Vec<&'a str> {
+        let mut children: Vec<&'a str> = Vec::new();
+        let links = individuals
+            .get(xref)
+            .into_iter()
+            .flat_map(|indi| &indi.families);
+        for link in links.filter(|link| matches!(link.family_link_type, FamilyLinkType::Spouse)) {
+            let family_children = families.get(link.xref.as_str()).map(|fam| &fam.children);
+            for child in family_children.into_iter().flatten() {
+                if !children.contains(&child.as_str()) {
+                    children.push(child);
+                }
+            }
+        }
+        children
+    };
+
+    let mut seen = HashSet::new();
+    seen.insert(root);
+    let mut stack = vec![(root, "1".to_string())];
+    while let Some((xref, parent_number)) = stack.pop() {
+        let next: Vec<(&str, String)> = children(xref)
+            .into_iter()
+            .filter(|child| seen.insert(*child))
+            .enumerate()
+            .map(|(index, child)| (child, number(&parent_number, index + 1)))
+            .collect();
+        numbers.insert(xref, parent_number);
+        // pushed in reverse, so that the first child is numbered first
+        stack.extend(next.into_iter().rev());
+    }
+    numbers
+}
+
+/// The xref of the individual `xref`, borrowed from the tree
+fn individual_xref<'a>(data: &'a GedcomData, xref: &str) -> Option<&'a str> {
+    data.individuals
+        .iter()
+        .filter_map(|indi| indi.xref.as_deref())
+        .find(|candidate| *candidate == xref)
+}
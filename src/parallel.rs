@@ -0,0 +1,230 @@
+//! Multi-threaded parsing for large GEDCOM files, enabled with the `"parallel"` feature.
+//!
+//! Parsing happens in two phases: the file contents are first split into batches of top-level
+//! records at level 0 boundaries, then each batch is parsed on the [`rayon`](https://docs.rs/rayon)
+//! thread pool and the results are merged, in file order, into a single `GedcomData`.
+//!
+//! Lookup indexes over a parsed tree can also be built in the background, see
+//! `GedcomData::build_indexes_async`.
+
+use rayon::prelude::*;
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+};
+
+use crate::{
+    search::{Indexes, NameIndex, PlaceIndex, UidIndex, XrefIndex},
+    tokenizer::Tokenizer,
+    GedcomData, GedcomError,
+};
+
+/// Number of batches created per worker thread, so that threads finishing early can pick up
+/// remaining work.
+const BATCHES_PER_THREAD: usize = 4;
+
+/// Parses the GEDCOM file contents using all threads of the current rayon thread pool. The
+/// result is the same as `parse_ged`, only faster for large files.
+///
+/// # Errors
+///
+/// Returns the first `GedcomError` found, by position in the file.
+///
+/// # Example
+///
+/// ```
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     0 @I2@ INDI\n\
+///     1 NAME Jane /Doe/\n\
+///     0 @F1@ FAM\n\
+///     1 HUSB @I1@\n\
+///     1 WIFE @I2@\n\
+///     0 TRLR";
+///
+/// let data = gedcom::parallel::parse_parallel(sample).unwrap();
+///
+/// assert!(data.header.is_some());
+/// assert_eq!(data.individuals.len(), 2);
+/// assert_eq!(data.individuals[1].xref.as_ref().unwrap(), "@I2@");
+/// assert_eq!(data.families.len(), 1);
+/// ```
+pub fn parse_parallel(content: &str) -> Result<GedcomData, GedcomError> {
+    let target_size = content.len() / (rayon::current_num_threads() * BATCHES_PER_THREAD) + 1;
+
+    let parsed: Vec<GedcomData> = split_batches(content, target_size)
+        .into_par_iter()
+        .map(|batch| parse_batch(&batch))
+        .collect::<Result<_, _>>()?;
+
+    let mut data = GedcomData::default();
+    for batch in parsed {
+        merge(&mut data, batch);
+    }
+    Ok(data)
+}
+
+/// A run of consecutive top-level records, along with the line number it starts at.
+struct Batch<'a> {
+    text: &'a str,
+    line: usize,
+    offset: usize,
+}
+
+/// Splits the content into batches of roughly `target_size` bytes, only ever splitting right
+/// before a level 0 line.
+fn split_batches(content: &str, target_size: usize) -> Vec<Batch<'_>> {
+    let mut batches = Vec::new();
+    let mut batch_start = 0;
+    let mut batch_line = 0;
+    let mut offset = 0;
+
+    for (line, text) in content.split_inclusive('\n').enumerate() {
+        let is_record_start = text
+            .trim_start_matches(|c: char| c.is_whitespace() || c == '\u{FEFF}')
+            .strip_prefix('0')
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace));
+
+        if is_record_start && offset - batch_start >= target_size {
+            batches.push(Batch {
+                text: &content[batch_start..offset],
+                line: batch_line,
+                offset: batch_start,
+            });
+            batch_start = offset;
+            batch_line = line;
+        }
+
+        offset += text.len();
+    }
+
+    if batch_start < content.len() {
+        batches.push(Batch {
+            text: &content[batch_start..],
+            line: batch_line,
+            offset: batch_start,
+        });
+    }
+    batches
+}
+
+/// Parses one batch of records as a document of its own, keeping locations in diagnostics
+/// relative to the whole file.
+fn parse_batch(batch: &Batch) -> Result<GedcomData, GedcomError> {
+    let mut text = batch.text.to_string();
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str("0 TRLR");
+
+    let mut tokenizer = Tokenizer::new(text.chars());
+    tokenizer.line = u32::try_from(batch.line).unwrap_or(u32::MAX);
+    tokenizer.base_offset = batch.offset;
+    tokenizer.next_token()?;
+    GedcomData::new(&mut tokenizer, 0)
+}
+
+fn merge(data: &mut GedcomData, batch: GedcomData) {
+    if batch.header.is_some() {
+        data.header = batch.header;
+    }
+    data.submitters.extend(batch.submitters);
+    data.submissions.extend(batch.submissions);
+    data.individuals.extend(batch.individuals);
+    data.families.extend(batch.families);
+    data.repositories.extend(batch.repositories);
+    data.sources.extend(batch.sources);
+    data.multimedia.extend(batch.multimedia);
+    data.notes.extend(batch.notes);
+    data.custom_data.extend(batch.custom_data);
+}
+
+impl GedcomData {
+    /// Builds the xref, name and place indexes of the tree on the threads of `pool`, returning
+    /// immediately. The indexes are published all at once through the returned `IndexHandle`
+    /// when they are complete, so the tree can be used while they build.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{search::SearchMode, GedcomDocument};
+    /// use std::sync::Arc;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME Hans /Schmidt/\n\
+    ///     1 BIRT\n\
+    ///     2 PLAC Berlin\n\
+    ///     0 TRLR";
+    ///
+    /// let data = Arc::new(GedcomDocument::new(sample.chars()).parse_document().unwrap());
+    /// let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    ///
+    /// let handle = data.build_indexes_async(&pool);
+    /// assert_eq!(data.individuals.len(), 1);
+    ///
+    /// let indexes = handle.wait();
+    /// assert!(handle.get().is_some());
+    /// assert_eq!(indexes.xrefs.get("@I1@").unwrap().index, 0);
+    /// assert_eq!(indexes.names.search("Smith", SearchMode::Soundex)[0].xref, "@I1@");
+    /// assert_eq!(indexes.places.get("Berlin"), ["@I1@"]);
+    /// ```
+    #[must_use]
+    pub fn build_indexes_async(self: &Arc<Self>, pool: &rayon::ThreadPool) -> IndexHandle {
+        let handle = IndexHandle::default();
+        let data = Arc::clone(self);
+        let slot = Arc::clone(&handle.slot);
+
+        pool.spawn(move || {
+            let ((xrefs, uids), (names, places)) = rayon::join(
+                || rayon::join(|| XrefIndex::new(&*data), || UidIndex::new(&*data)),
+                || rayon::join(|| NameIndex::new(&*data), || PlaceIndex::new(&*data)),
+            );
+            let indexes = Arc::new(Indexes {
+                xrefs,
+                uids,
+                names,
+                places,
+            });
+
+            let (ready, built) = &*slot;
+            *ready.lock().unwrap_or_else(PoisonError::into_inner) = Some(indexes);
+            built.notify_all();
+        });
+        handle
+    }
+}
+
+/// `IndexHandle` receives the `Indexes` built by `GedcomData::build_indexes_async`.
+#[derive(Clone, Debug, Default)]
+pub struct IndexHandle {
+    slot: Arc<(Mutex<Option<Arc<Indexes>>>, Condvar)>,
+}
+
+impl IndexHandle {
+    /// The indexes, or `None` while they are still being built
+    #[must_use]
+    pub fn get(&self) -> Option<Arc<Indexes>> {
+        let (ready, _) = &*self.slot;
+        ready.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Blocks until the indexes are built, then returns them
+    #[must_use]
+    pub fn wait(&self) -> Arc<Indexes> {
+        let (ready, built) = &*self.slot;
+        let mut indexes = ready.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(indexes) = &*indexes {
+                return Arc::clone(indexes);
+            }
+            indexes = built.wait(indexes).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
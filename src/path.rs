@@ -0,0 +1,298 @@
+//! Generic access to the structures of a tree by tag path, e.g. `BIRT/DATE`, for quick scripts and
+//! for fields the typed model doesn't cover yet.
+//!
+//! A path is a list of tags separated by `/`, each step selecting the substructures with that tag
+//! among those selected by the step before. `*` selects substructures of any tag. A step may be
+//! narrowed down by a predicate between brackets: a number selects the nth structure matched by
+//! the step, starting at 1, and any other text selects the structures with that xref or value,
+//! e.g. `INDI[@I12@]/NAME/SURN` or `INDI/FAMS[@F1@]`. Paths which can't match, e.g. with an empty
+//! step, select nothing.
+//!
+//! Paths are resolved over `Node`s, the structures of the tree as they are written out, with
+//! their CONC and CONT lines joined into their values. `GedcomData::select` resolves a path from
+//! the top-level records, including those the parser skips, e.g. SNOTE records, when parsed with
+//! `ParseOptions::preserve_lines`. The typed records and user-defined structures implement
+//! `TagPath`, which resolves a path from their own substructures.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{path::TagPath, GedcomDocument, ParseOptions};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     2 SURN Smith\n\
+//!     1 BIRT\n\
+//!     2 DATE 2 OCT 1822\n\
+//!     1 RESI\n\
+//!     2 PLAC Leeds\n\
+//!     1 RESI\n\
+//!     2 PLAC York\n\
+//!     0 @I12@ INDI\n\
+//!     1 NAME Mary /Brown/\n\
+//!     2 SURN Brown\n\
+//!     0 @N1@ SNOTE A note the parser\n\
+//!     1 CONT doesn't keep\n\
+//!     0 TRLR";
+//!
+//! let options = ParseOptions {
+//!     preserve_lines: true,
+//!     ..ParseOptions::default()
+//! };
+//! let data = GedcomDocument::with_options(sample.chars(), options)
+//!     .parse_document()
+//!     .unwrap();
+//!
+//! let john = &data.individuals[0];
+//! assert_eq!(john.get_path("BIRT/DATE").as_deref(), Some("2 OCT 1822"));
+//! assert_eq!(john.get_path("RESI[2]/PLAC").as_deref(), Some("York"));
+//! assert_eq!(john.get_path("DEAT/DATE"), None);
+//!
+//! assert_eq!(data.select_values("INDI[@I12@]/NAME/SURN"), vec!["Brown"]);
+//! assert_eq!(data.select_values("INDI/NAME/SURN"), vec!["Smith", "Brown"]);
+//! assert_eq!(
+//!     data.select_values("SNOTE[@N1@]"),
+//!     vec!["A note the parser\ndoesn't keep"]
+//! );
+//!
+//! let residences = data.select("INDI/RESI");
+//! assert_eq!(residences.len(), 2);
+//! assert_eq!(residences[1].children[0].tag, "PLAC");
+//! ```
+
+use crate::{
+    types::{
+        Family, Header, Individual, MultimediaRecord, Repository, Source, Submission, Submitter,
+        UserDefinedDataset,
+    },
+    util::LineFields,
+    GedcomData,
+};
+
+/// `Node` is a structure of the tree as written out: a line along with its substructures.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Node {
+    /// The xref of the structure, for top-level records
+    pub xref: Option<String>,
+    /// The tag of the structure, e.g. BIRT
+    pub tag: String,
+    /// The value of the structure, with its CONC and CONT lines joined
+    pub value: Option<String>,
+    /// The substructures, in file order, leaving out CONC and CONT lines
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /// The substructures matching `path`, starting from the substructures of this node, in file
+    /// order. See the `path` module for the syntax of paths.
+    #[must_use]
+    pub fn select(&self, path: &str) -> Vec<&Node> {
+        let mut selected = vec![self];
+        for step in path.split('/') {
+            let Some(step) = Step::parse(step) else {
+                return Vec::new();
+            };
+            selected = selected
+                .into_iter()
+                .flat_map(|node| step.select(&node.children))
+                .collect();
+        }
+        selected
+    }
+
+    /// The first value of the substructures matching `path`, see `Node::select`
+    #[must_use]
+    pub fn get_path(&self, path: &str) -> Option<&str> {
+        self.select(path)
+            .into_iter()
+            .find_map(|node| node.value.as_deref())
+    }
+
+    /// The values of the substructures matching `path`, leaving out those without one, see
+    /// `Node::select`
+    #[must_use]
+    pub fn values(&self, path: &str) -> Vec<&str> {
+        self.select(path)
+            .into_iter()
+            .filter_map(|node| node.value.as_deref())
+            .collect()
+    }
+}
+
+/// `TagPath` gives access to the substructures of a record by tag path, see the `path` module.
+pub trait TagPath {
+    /// The record as a `Node`, as it is written out
+    fn to_node(&self) -> Node;
+
+    /// The substructures of the record matching `path`, see `Node::select`
+    fn select(&self, path: &str) -> Vec<Node> {
+        self.to_node().select(path).into_iter().cloned().collect()
+    }
+
+    /// The first value of the substructures of the record matching `path`, e.g. `BIRT/DATE`, see
+    /// `Node::get_path`
+    fn get_path(&self, path: &str) -> Option<String> {
+        self.to_node().get_path(path).map(str::to_string)
+    }
+}
+
+macro_rules! impl_tag_path {
+    ($($record:ty => $field:ident: $wrap:expr),* $(,)?) => {
+        $(impl TagPath for $record {
+            fn to_node(&self) -> Node {
+                let data = GedcomData {
+                    $field: $wrap(self.clone()),
+                    ..GedcomData::default()
+                };
+                data.nodes().into_iter().next().unwrap_or_default()
+            }
+        })*
+    };
+}
+
+impl_tag_path!(
+    Header => header: Some,
+    Submitter => submitters: |subm| vec![subm],
+    Submission => submissions: |subn| vec![subn],
+    Individual => individuals: |indi| vec![indi],
+    Family => families: |fam| vec![fam],
+    Repository => repositories: |repo| vec![repo],
+    Source => sources: |sour| vec![sour],
+    MultimediaRecord => multimedia: |obje| vec![obje],
+    UserDefinedDataset => custom_data: |custom| vec![Box::new(custom)],
+);
+
+impl GedcomData {
+    /// The top-level records of the tree as `Node`s, in the order they are written out, leaving
+    /// out the TRLR
+    #[must_use]
+    pub fn nodes(&self) -> Vec<Node> {
+        let mut nodes = read_nodes(&self.to_gedcom_string());
+        nodes.retain(|node| node.tag != "TRLR");
+        nodes
+    }
+
+    /// The structures matching `path`, starting from the top-level records, e.g.
+    /// `INDI[@I12@]/NAME`. See the `path` module for the syntax of paths.
+    #[must_use]
+    pub fn select(&self, path: &str) -> Vec<Node> {
+        let root = Node {
+            children: self.nodes(),
+            ..Node::default()
+        };
+        root.select(path).into_iter().cloned().collect()
+    }
+
+    /// The values of the structures matching `path`, e.g. `INDI/NAME/SURN`, leaving out those
+    /// without one, see `GedcomData::select`
+    #[must_use]
+    pub fn select_values(&self, path: &str) -> Vec<String> {
+        self.select(path)
+            .into_iter()
+            .filter_map(|node| node.value)
+            .collect()
+    }
+}
+
+/// A step of a path: a tag or `*`, along with an optional predicate
+struct Step<'a> {
+    tag: &'a str,
+    predicate: Option<Predicate<'a>>,
+}
+
+enum Predicate<'a> {
+    /// The nth match, starting at 1
+    Position(usize),
+    /// The matches with this xref or value
+    Equals(&'a str),
+}
+
+impl<'a> Step<'a> {
+    fn parse(step: &'a str) -> Option<Step<'a>> {
+        let (tag, predicate) = match step.split_once('[') {
+            Some((tag, rest)) => {
+                let predicate = rest.strip_suffix(']')?;
+                let predicate = match predicate.parse::<usize>() {
+                    Ok(position) => Predicate::Position(position),
+                    Err(_) => Predicate::Equals(predicate),
+                };
+                (tag, Some(predicate))
+            }
+            None => (step, None),
+        };
+        if tag.is_empty() {
+            return None;
+        }
+        Some(Step { tag, predicate })
+    }
+
+    fn select<'n>(&self, nodes: &'n [Node]) -> Vec<&'n Node> {
+        let matches = nodes
+            .iter()
+            .filter(|node| self.tag == "*" || node.tag == self.tag);
+        match self.predicate {
+            None => matches.collect(),
+            Some(Predicate::Position(position)) => matches
+                .skip(position.saturating_sub(1))
+                .take(usize::from(position > 0))
+                .collect(),
+            Some(Predicate::Equals(text)) => matches
+                .filter(|node| {
+                    node.xref.as_deref() == Some(text) || node.value.as_deref() == Some(text)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Reads GEDCOM text into a tree of `Node`s, joining CONC and CONT lines into the values they
+/// continue
+pub(crate) fn read_nodes(text: &str) -> Vec<Node> {
+    let mut lines: Vec<(u8, Node)> = Vec::new();
+    for line in text.lines() {
+        let fields = LineFields::split(line.trim_start_matches(['\u{FEFF}', ' ', '\t']));
+        let (Ok(level), Some(tag)) = (fields.level.parse::<u8>(), fields.tag) else {
+            continue;
+        };
+        let value = fields.value.unwrap_or_default();
+        match lines.last_mut() {
+            Some((parent, node)) if level > 0 && *parent == level - 1 && tag == "CONC" => {
+                node.value.get_or_insert_with(String::new).push_str(value);
+            }
+            Some((parent, node)) if level > 0 && *parent == level - 1 && tag == "CONT" => {
+                let text = node.value.get_or_insert_with(String::new);
+                text.push('\n');
+                text.push_str(value);
+            }
+            _ => lines.push((
+                level,
+                Node {
+                    xref: fields.xref.map(str::to_string),
+                    tag: tag.to_string(),
+                    value: fields.value.map(str::to_string),
+                    children: Vec::new(),
+                },
+            )),
+        }
+    }
+    let mut lines = lines.into_iter().peekable();
+    children(&mut lines, None)
+}
+
+/// The nodes deeper than `parent`, each with its own substructures
+fn children(
+    lines: &mut std::iter::Peekable<impl Iterator<Item = (u8, Node)>>,
+    parent: Option<u8>,
+) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    while let Some((level, mut node)) =
+        lines.next_if(|(level, _)| parent.is_none_or(|parent| *level > parent))
+    {
+        node.children = children(lines, Some(level));
+        nodes.push(node);
+    }
+    nodes
+}
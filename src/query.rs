@@ -0,0 +1,352 @@
+//! Filtered iterators over the records of a tree, built by chaining predicates on events, names
+//! and relations. A record is selected when it matches every predicate of the query.
+//!
+//! Predicates on relations look the related records up once, when they are added, so that a
+//! query runs in a single pass over the records.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::GedcomDocument;
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME Ole /Olsen/\n\
+//!     1 BIRT\n\
+//!     2 DATE 1802\n\
+//!     1 FAMS @F1@\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Kari /Olsen/\n\
+//!     1 BIRT\n\
+//!     2 DATE ABT 1831\n\
+//!     2 PLAC Bergen, Norway\n\
+//!     1 FAMC @F1@\n\
+//!     0 @I3@ INDI\n\
+//!     1 NAME Nils /Berg/\n\
+//!     1 BIRT\n\
+//!     2 DATE 1835\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @I1@\n\
+//!     1 CHIL @I2@\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let xrefs = |query: gedcom::query::IndividualQuery| -> Vec<String> {
+//!     query.iter().filter_map(|indi| indi.xref.clone()).collect()
+//! };
+//!
+//! let olsens = data.query().individuals().born_between(1800, 1850).surname("Olsen");
+//! assert_eq!(xrefs(olsens), vec!["@I1@", "@I2@"]);
+//!
+//! let children = data.query().individuals().child_of("@I1@");
+//! assert_eq!(xrefs(children), vec!["@I2@"]);
+//!
+//! let norwegians = data
+//!     .query()
+//!     .individuals()
+//!     .place("norway")
+//!     .filter(|indi| indi.families.len() == 1);
+//! assert_eq!(xrefs(norwegians), vec!["@I2@"]);
+//! ```
+
+use std::collections::HashSet;
+
+use crate::{
+    duplicates::{date_part, event_date},
+    types::{Event, EventDetail, Family, FamilyLinkType, GenderType, Individual, Pointer},
+    GedcomData,
+};
+
+impl GedcomData {
+    /// Starts a query over the records of the tree, see the `query` module
+    #[must_use]
+    pub fn query(&self) -> Query<'_> {
+        Query { data: self }
+    }
+}
+
+/// `Query` picks the kind of records to select, see the `query` module.
+#[derive(Clone, Copy)]
+pub struct Query<'a> {
+    data: &'a GedcomData,
+}
+
+impl<'a> Query<'a> {
+    /// Selects individuals, all of them until predicates are added
+    #[must_use]
+    pub fn individuals(self) -> IndividualQuery<'a> {
+        IndividualQuery {
+            data: self.data,
+            predicates: Vec::new(),
+        }
+    }
+
+    /// Selects families, all of them until predicates are added
+    #[must_use]
+    pub fn families(self) -> FamilyQuery<'a> {
+        FamilyQuery {
+            data: self.data,
+            predicates: Vec::new(),
+        }
+    }
+}
+
+type Predicate<'a, T> = Box<dyn Fn(&T) -> bool + 'a>;
+
+/// `IndividualQuery` selects the individuals of a tree matching all of its predicates.
+#[allow(clippy::module_name_repetitions)]
+pub struct IndividualQuery<'a> {
+    data: &'a GedcomData,
+    predicates: Vec<Predicate<'a, Individual>>,
+}
+
+impl<'a> IndividualQuery<'a> {
+    /// Keeps the individuals for which `predicate` holds
+    #[must_use]
+    pub fn filter(mut self, predicate: impl Fn(&Individual) -> bool + 'a) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Keeps the individuals born in the years `from` to `to`, both included. The first year of
+    /// a date range or period is the one compared, and undated births don't match.
+    #[must_use]
+    pub fn born_between(self, from: i32, to: i32) -> Self {
+        self.event_between(Event::Birth, from, to)
+    }
+
+    /// Keeps the individuals who died in the years `from` to `to`, both included
+    #[must_use]
+    pub fn died_between(self, from: i32, to: i32) -> Self {
+        self.event_between(Event::Death, from, to)
+    }
+
+    /// Keeps the individuals whose first dated event of the kind happened in the years `from` to
+    /// `to`, both included
+    #[must_use]
+    pub fn event_between(self, event: Event, from: i32, to: i32) -> Self {
+        self.filter(move |indi| {
+            event_date(indi, &event)
+                .as_ref()
+                .and_then(date_part)
+                .is_some_and(|part| (from..=to).contains(&part.year))
+        })
+    }
+
+    /// Keeps the individuals with an event of the kind, dated or not
+    #[must_use]
+    pub fn has_event(self, event: Event) -> Self {
+        self.filter(move |indi| indi.events.iter().any(|detail| detail.event == event))
+    }
+
+    /// Keeps the individuals with an event whose place contains `place`, ignoring case
+    #[must_use]
+    pub fn place(self, place: &str) -> Self {
+        let place = place.to_lowercase();
+        self.filter(move |indi| indi.events.iter().any(|event| in_place(event, &place)))
+    }
+
+    /// Keeps the individuals with the surname, ignoring case, see `Name::family_name`
+    #[must_use]
+    pub fn surname(self, surname: &str) -> Self {
+        let surname = surname.to_lowercase();
+        self.filter(move |indi| {
+            indi.name
+                .as_ref()
+                .and_then(|name| name.family_name())
+                .is_some_and(|name| name.to_lowercase() == surname)
+        })
+    }
+
+    /// Keeps the individuals with `given` among their given names, ignoring case
+    #[must_use]
+    pub fn given_name(self, given: &str) -> Self {
+        let given = given.to_lowercase();
+        self.filter(move |indi| {
+            indi.name
+                .as_ref()
+                .and_then(|name| name.given_name())
+                .is_some_and(|name| {
+                    name.split_whitespace()
+                        .any(|word| word.to_lowercase() == given)
+                })
+        })
+    }
+
+    /// Keeps the individuals of the sex
+    #[must_use]
+    pub fn sex(self, sex: &GenderType) -> Self {
+        let tag = sex.tag();
+        self.filter(move |indi| indi.sex.as_ref().is_some_and(|sex| sex.value.tag() == tag))
+    }
+
+    /// Keeps the children of the individual with the xref, in any of the families they are a
+    /// spouse of
+    #[must_use]
+    pub fn child_of(self, xref: &str) -> Self {
+        let children: HashSet<&'a str> = self
+            .data
+            .families
+            .iter()
+            .filter(|fam| spouses(fam).any(|spouse| spouse == xref))
+            .flat_map(|fam| fam.children.iter().map(String::as_str))
+            .collect();
+        self.related_to(children)
+    }
+
+    /// Keeps the parents of the individual with the xref, from all of the families they are a
+    /// child of, including adoptive ones
+    #[must_use]
+    pub fn parent_of(self, xref: &str) -> Self {
+        let parents: HashSet<&'a str> = self
+            .data
+            .families
+            .iter()
+            .filter(|fam| fam.children.iter().any(|child| child == xref))
+            .flat_map(spouses)
+            .collect();
+        self.related_to(parents)
+    }
+
+    /// Keeps the spouses of the individual with the xref
+    #[must_use]
+    pub fn spouse_of(self, xref: &str) -> Self {
+        let spouses: HashSet<&'a str> = self
+            .data
+            .families
+            .iter()
+            .filter(|fam| spouses(fam).any(|spouse| spouse == xref))
+            .flat_map(spouses)
+            .filter(|spouse| *spouse != xref)
+            .collect();
+        self.related_to(spouses)
+    }
+
+    /// Keeps the individuals who are a spouse in at least one family
+    #[must_use]
+    pub fn married(self) -> Self {
+        self.filter(|indi| {
+            indi.families
+                .iter()
+                .any(|link| matches!(link.family_link_type, FamilyLinkType::Spouse))
+        })
+    }
+
+    fn related_to(self, xrefs: HashSet<&'a str>) -> Self {
+        self.filter(move |indi| {
+            indi.xref
+                .as_deref()
+                .is_some_and(|xref| xrefs.contains(xref))
+        })
+    }
+
+    /// The individuals matching the query, in the order of the tree
+    pub fn iter(&self) -> impl Iterator<Item = &'a Individual> + '_ {
+        let predicates = &self.predicates;
+        self.data
+            .individuals
+            .iter()
+            .filter(move |indi| predicates.iter().all(|predicate| predicate(indi)))
+    }
+}
+
+/// `FamilyQuery` selects the families of a tree matching all of its predicates.
+#[allow(clippy::module_name_repetitions)]
+pub struct FamilyQuery<'a> {
+    data: &'a GedcomData,
+    predicates: Vec<Predicate<'a, Family>>,
+}
+
+impl<'a> FamilyQuery<'a> {
+    /// Keeps the families for which `predicate` holds
+    #[must_use]
+    pub fn filter(mut self, predicate: impl Fn(&Family) -> bool + 'a) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Keeps the families married in the years `from` to `to`, both included
+    #[must_use]
+    pub fn married_between(self, from: i32, to: i32) -> Self {
+        self.event_between(Event::Marriage, from, to)
+    }
+
+    /// Keeps the families whose first dated event of the kind happened in the years `from` to
+    /// `to`, both included
+    #[must_use]
+    pub fn event_between(self, event: Event, from: i32, to: i32) -> Self {
+        self.filter(move |fam| {
+            family_events(fam)
+                .filter(|detail| detail.event == event)
+                .find_map(|detail| detail.date.as_ref()?.value_structured())
+                .as_ref()
+                .and_then(date_part)
+                .is_some_and(|part| (from..=to).contains(&part.year))
+        })
+    }
+
+    /// Keeps the families with an event of the kind, dated or not
+    #[must_use]
+    pub fn has_event(self, event: Event) -> Self {
+        self.filter(move |fam| family_events(fam).any(|detail| detail.event == event))
+    }
+
+    /// Keeps the families with an event whose place contains `place`, ignoring case
+    #[must_use]
+    pub fn place(self, place: &str) -> Self {
+        let place = place.to_lowercase();
+        self.filter(move |fam| family_events(fam).any(|event| in_place(event, &place)))
+    }
+
+    /// Keeps the families with the individual as a spouse
+    #[must_use]
+    pub fn spouse(self, xref: &str) -> Self {
+        let xref = xref.to_string();
+        self.filter(move |fam| spouses(fam).any(|spouse| spouse == xref))
+    }
+
+    /// Keeps the families with the individual as a child
+    #[must_use]
+    pub fn child(self, xref: &str) -> Self {
+        let xref = xref.to_string();
+        self.filter(move |fam| fam.children.contains(&xref))
+    }
+
+    /// Keeps the families with at least `count` children
+    #[must_use]
+    pub fn min_children(self, count: usize) -> Self {
+        self.filter(move |fam| fam.children.len() >= count)
+    }
+
+    /// The families matching the query, in the order of the tree
+    pub fn iter(&self) -> impl Iterator<Item = &'a Family> + '_ {
+        let predicates = &self.predicates;
+        self.data
+            .families
+            .iter()
+            .filter(move |fam| predicates.iter().all(|predicate| predicate(fam)))
+    }
+}
+
+/// The individuals heading a family, leaving out `@VOID@`
+fn spouses(fam: &Family) -> impl Iterator<Item = &str> {
+    fam.individual1
+        .iter()
+        .chain(&fam.individual2)
+        .map(String::as_str)
+        .filter(|xref| *xref != Pointer::VOID)
+}
+
+fn family_events(fam: &Family) -> impl Iterator<Item = &EventDetail> {
+    fam.events.iter().chain(&fam.family_event)
+}
+
+/// Whether the place of the event contains `place`, already in lowercase
+fn in_place(event: &EventDetail, place: &str) -> bool {
+    event
+        .place
+        .as_ref()
+        .is_some_and(|name| name.to_lowercase().contains(place))
+}
@@ -0,0 +1,286 @@
+//! Charts of the ancestry or descendancy of an individual, as [Graphviz](https://graphviz.org)
+//! DOT or [mermaid](https://mermaid.js.org) flowcharts.
+//!
+//! `Chart::ancestors` and `Chart::descendants` collect the individuals up to some number of
+//! generations away, with an edge from each parent to their child. The chart is then written out
+//! with `Chart::to_dot` or `Chart::to_mermaid`, the boxes labelled as `Labels` says. Ancestors are
+//! followed through birth families only, see `Kinship::parents`, and an ancestor reached through
+//! several lines of descent is drawn once.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{render::{Chart, Labels}, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 BIRT\n\
+//!     2 DATE 12 MAR 1850\n\
+//!     1 DEAT\n\
+//!     2 DATE 1921\n\
+//!     1 FAMS @F1@\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Mary /Brown/\n\
+//!     1 FAMS @F1@\n\
+//!     0 @I3@ INDI\n\
+//!     1 NAME Thomas /Smith/\n\
+//!     1 FAMC @F1@\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @I1@\n\
+//!     1 WIFE @I2@\n\
+//!     1 CHIL @I3@\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let chart = Chart::ancestors(&data, "@I3@", 4);
+//!
+//! assert_eq!(
+//!     chart.to_dot(Labels::default()),
+//!     "\
+//! digraph ancestors {
+//!   node [shape=box];
+//!   \"@I3@\" [label=\"Thomas Smith\"];
+//!   \"@I1@\" [label=\"John Smith\\n1850-1921\"];
+//!   \"@I2@\" [label=\"Mary Brown\"];
+//!   \"@I1@\" -> \"@I3@\";
+//!   \"@I2@\" -> \"@I3@\";
+//! }
+//! "
+//! );
+//!
+//! let labels = Labels {
+//!     years: false,
+//!     xref: true,
+//!     ..Labels::default()
+//! };
+//! assert_eq!(
+//!     Chart::descendants(&data, "@I2@", 1).to_mermaid(labels),
+//!     "\
+//! flowchart TD
+//!   I2[\"Mary Brown<br/>@I2@\"]
+//!   I3[\"Thomas Smith<br/>@I3@\"]
+//!   I2 --> I3
+//! "
+//! );
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    debug_tree::lifespan_years,
+    kinship::Kinship,
+    types::{FamilyLinkType, Individual, Name},
+    GedcomData,
+};
+
+/// `Labels` picks what the box of each individual shows, one item per line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Labels {
+    /// The full name, see `Name::full_name`
+    pub name: bool,
+    /// The years of birth and death, e.g. "1850-1921"
+    pub years: bool,
+    /// The xref of the individual
+    pub xref: bool,
+}
+
+impl Default for Labels {
+    /// Names and years
+    fn default() -> Labels {
+        Labels {
+            name: true,
+            years: true,
+            xref: false,
+        }
+    }
+}
+
+/// `Chart` is the ancestry or descendancy of an individual, see the `render` module.
+pub struct Chart<'a> {
+    /// "ancestors" or "descendants", naming the DOT graph
+    kind: &'static str,
+    /// The individuals charted, by generation
+    individuals: Vec<&'a str>,
+    /// Parent and child pairs
+    edges: Vec<(&'a str, &'a str)>,
+    records: HashMap<&'a str, &'a Individual>,
+}
+
+impl<'a> Chart<'a> {
+    /// The ancestors of the individual with the xref, up to `generations` generations back
+    #[must_use]
+    pub fn ancestors(data: &'a GedcomData, xref: &'a str, generations: usize) -> Chart<'a> {
+        let kinship = Kinship::new(data);
+        let mut chart = Chart::new(data, "ancestors", xref);
+        chart.walk(xref, generations, |xref| {
+            let (father, mother) = kinship.parents(xref);
+            father
+                .into_iter()
+                .chain(mother)
+                .map(|parent| (parent, xref))
+                .collect()
+        });
+        chart
+    }
+
+    /// The descendants of the individual with the xref, up to `generations` generations down,
+    /// through all of the families they are a spouse in
+    #[must_use]
+    pub fn descendants(data: &'a GedcomData, xref: &'a str, generations: usize) -> Chart<'a> {
+        let families: HashMap<&str, _> = data
+            .families
+            .iter()
+            .filter_map(|fam| Some((fam.xref.as_deref()?, fam)))
+            .collect();
+        let mut chart = Chart::new(data, "descendants", xref);
+        let records = chart.records.clone();
+        chart.walk(xref, generations, |xref| {
+            records
+                .get(xref)
+                .into_iter()
+                .flat_map(|indi| &indi.families)
+                .filter(|link| matches!(link.family_link_type, FamilyLinkType::Spouse))
+                .filter_map(|link| families.get(link.xref.as_str()))
+                .flat_map(|fam| &fam.children)
+                .map(|child| (xref, child.as_str()))
+                .collect()
+        });
+        chart
+    }
+
+    fn new(data: &'a GedcomData, kind: &'static str, xref: &'a str) -> Chart<'a> {
+        Chart {
+            kind,
+            individuals: vec![xref],
+            edges: Vec::new(),
+            records: data
+                .individuals
+                .iter()
+                .filter_map(|indi| Some((indi.xref.as_deref()?, indi)))
+                .collect(),
+        }
+    }
+
+    /// Adds the individuals reached from `start` in breadth-first order, `next` giving the
+    /// parent and child edges leading one generation further from an individual
+    fn walk(
+        &mut self,
+        start: &'a str,
+        generations: usize,
+        next: impl Fn(&'a str) -> Vec<(&'a str, &'a str)>,
+    ) {
+        let mut seen: HashSet<&str> = HashSet::new();
+        seen.insert(start);
+        let mut queue = VecDeque::from(vec![(start, 0)]);
+        while let Some((xref, generation)) = queue.pop_front() {
+            if generation == generations {
+                continue;
+            }
+            for (parent, child) in next(xref) {
+                self.edges.push((parent, child));
+                let reached = if parent == xref { child } else { parent };
+                if seen.insert(reached) {
+                    self.individuals.push(reached);
+                    queue.push_back((reached, generation + 1));
+                }
+            }
+        }
+    }
+
+    /// The xrefs of the individuals charted, the root first and then by generation
+    #[must_use]
+    pub fn individuals(&self) -> &[&'a str] {
+        &self.individuals
+    }
+
+    /// The parent and child xrefs linked in the chart
+    #[must_use]
+    pub fn edges(&self) -> &[(&'a str, &'a str)] {
+        &self.edges
+    }
+
+    /// The chart as a Graphviz DOT digraph, edges leading from parents to children
+    #[must_use]
+    pub fn to_dot(&self, labels: Labels) -> String {
+        let mut lines = vec![
+            format!("digraph {} {{", self.kind),
+            "  node [shape=box];".into(),
+        ];
+        for xref in &self.individuals {
+            let label: Vec<String> = self
+                .label(xref, labels)
+                .iter()
+                .map(|line| escape(line))
+                .collect();
+            let label = label.join("\\n");
+            lines.push(format!("  {} [label=\"{label}\"];", dot_id(xref)));
+        }
+        for (parent, child) in &self.edges {
+            lines.push(format!("  {} -> {};", dot_id(parent), dot_id(child)));
+        }
+        lines.push("}".into());
+        lines.join("\n") + "\n"
+    }
+
+    /// The chart as a mermaid flowchart, top down from parents to children
+    #[must_use]
+    pub fn to_mermaid(&self, labels: Labels) -> String {
+        let mut lines = vec!["flowchart TD".to_string()];
+        for xref in &self.individuals {
+            let label: Vec<String> = self
+                .label(xref, labels)
+                .iter()
+                .map(|line| line.replace('"', "#quot;"))
+                .collect();
+            let label = label.join("<br/>");
+            lines.push(format!("  {}[\"{label}\"]", mermaid_id(xref)));
+        }
+        for (parent, child) in &self.edges {
+            lines.push(format!(
+                "  {} --> {}",
+                mermaid_id(parent),
+                mermaid_id(child)
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// The lines of the box of an individual
+    fn label(&self, xref: &str, labels: Labels) -> Vec<String> {
+        let indi = self.records.get(xref);
+        let mut lines = Vec::new();
+        if labels.name {
+            let name = indi
+                .and_then(|indi| indi.name.as_ref())
+                .and_then(Name::full_name);
+            lines.push(name.unwrap_or_else(|| "(unnamed)".to_string()));
+        }
+        if labels.years {
+            lines.extend(indi.and_then(|indi| lifespan_years(indi)));
+        }
+        if labels.xref || lines.is_empty() {
+            lines.push(xref.to_string());
+        }
+        lines
+    }
+}
+
+/// Escapes the quotes and backslashes of a DOT string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn dot_id(xref: &str) -> String {
+    format!("\"{}\"", escape(xref))
+}
+
+/// Mermaid node ids are kept to letters, digits and underscores, e.g. I1 for @I1@
+fn mermaid_id(xref: &str) -> String {
+    xref.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect()
+}
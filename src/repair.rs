@@ -0,0 +1,574 @@
+//! Repairs of the structural problems commonly found in exported files, for "clean my file"
+//! features.
+//!
+//! `GedcomData::repair` applies the repairs picked by `RepairOptions`, returning a `Repair` for
+//! each change made, in this order:
+//!
+//! - records without an xref are given the first free one with the default prefix of their kind
+//!   in `XrefFormat`, e.g. `@I3@` for an individual
+//! - empty structures are removed from individuals and families: notes without text, events with
+//!   no details, user-defined structures with neither a value nor substructures, and pointers
+//!   with no xref. Top-level user-defined records with nothing in them are removed as well.
+//! - CHIL lines repeated within a family are removed, keeping the first
+//! - links between individuals and families are made to go both ways: an individual listed in a
+//!   family gains the FAMC or FAMS link to it, and an individual linking to a family is added to
+//!   its children or spouses. A spouse is added as WIFE when female and as HUSB otherwise,
+//!   falling back to the other slot. When the family already has two spouses, the link is left
+//!   as it is, with a `Repair` of kind `RepairKind::UnrepairedLink` reporting it.
+//!
+//! Pointers to records missing from the tree are left alone, see `GedcomData::validate`.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{repair::{RepairKind, RepairOptions}, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 NOTE\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Mary /Smith/\n\
+//!     1 FAMC @F1@\n\
+//!     0 INDI\n\
+//!     1 NAME Ann /Smith/\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @I1@\n\
+//!     1 CHIL @I2@\n\
+//!     1 CHIL @I2@\n\
+//!     0 TRLR";
+//!
+//! let mut data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let repairs = data.repair(RepairOptions::default());
+//!
+//! let changes: Vec<String> = repairs.iter().map(ToString::to_string).collect();
+//! assert_eq!(
+//!     changes,
+//!     vec![
+//!         "INDI record given the xref @I3@",
+//!         "empty NOTE removed from @I1@",
+//!         "duplicate CHIL @I2@ removed from @F1@",
+//!         "FAMS @F1@ added to @I1@",
+//!     ]
+//! );
+//! assert_eq!(repairs[0].kind, RepairKind::AssignedXref);
+//!
+//! let john = &data.individuals[0];
+//! assert!(john.notes.is_empty());
+//! assert_eq!(john.families[0].xref, "@F1@");
+//! assert_eq!(data.families[0].children, vec!["@I2@"]);
+//! assert_eq!(data.individuals[2].xref.as_deref(), Some("@I3@"));
+//!
+//! // a repaired tree has nothing left to repair
+//! assert!(data.repair(RepairOptions::default()).is_empty());
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    edit::{family_link, is_link},
+    types::{EventDetail, FamilyLinkType, Note, UserDefinedDataset},
+    writer::XrefFormat,
+    GedcomData,
+};
+
+/// `RepairOptions` picks the repairs `GedcomData::repair` applies, all of them by default.
+#[derive(Clone, Copy, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RepairOptions {
+    /// Gives an xref to the records without one
+    pub assign_xrefs: bool,
+    /// Removes the structures with nothing in them, see the `repair` module
+    pub strip_empty: bool,
+    /// Removes the CHIL lines repeated within a family
+    pub remove_duplicate_children: bool,
+    /// Adds the missing half of the links between individuals and families
+    pub link_families: bool,
+}
+
+impl Default for RepairOptions {
+    fn default() -> RepairOptions {
+        RepairOptions {
+            assign_xrefs: true,
+            strip_empty: true,
+            remove_duplicate_children: true,
+            link_families: true,
+        }
+    }
+}
+
+/// `RepairKind` is the kind of change a `Repair` made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RepairKind {
+    /// A record was given an xref
+    AssignedXref,
+    /// An empty structure was removed
+    RemovedEmpty,
+    /// A repeated structure was removed
+    RemovedDuplicate,
+    /// A link was added to a record, to match the link pointing back at it
+    AddedLink,
+    /// A FAMS link was left without a match, as its family already has two spouses. Nothing was
+    /// changed.
+    UnrepairedLink,
+}
+
+/// `Repair` is a change made by `GedcomData::repair`, or a problem it left in place.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repair {
+    /// What was done
+    pub kind: RepairKind,
+    /// The xref of the record changed, or its tag when it is a user-defined record without one.
+    /// For an unrepaired link, the individual holding it.
+    pub record: String,
+    /// The tag of the structure added or removed, e.g. FAMS, or of the record given an xref
+    pub tag: String,
+    /// The value of the structure added or removed, e.g. the xref of a CHIL line
+    pub value: Option<String>,
+}
+
+impl Repair {
+    fn new(kind: RepairKind, record: &str, tag: &str, value: Option<&str>) -> Repair {
+        Repair {
+            kind,
+            record: record.to_string(),
+            tag: tag.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let structure = match &self.value {
+            Some(value) => format!("{} {value}", self.tag),
+            None => self.tag.clone(),
+        };
+        match self.kind {
+            RepairKind::AssignedXref => {
+                write!(f, "{} record given the xref {}", self.tag, self.record)
+            }
+            RepairKind::RemovedEmpty => write!(f, "empty {structure} removed from {}", self.record),
+            RepairKind::RemovedDuplicate => {
+                write!(f, "duplicate {structure} removed from {}", self.record)
+            }
+            RepairKind::AddedLink => write!(f, "{structure} added to {}", self.record),
+            RepairKind::UnrepairedLink => write!(
+                f,
+                "{structure} of {} left unmatched, the family has two spouses",
+                self.record
+            ),
+        }
+    }
+}
+
+impl GedcomData {
+    /// Repairs the common structural problems picked by `options`, returning the changes made
+    /// and the links which couldn't be repaired. See the `repair` module.
+    pub fn repair(&mut self, options: RepairOptions) -> Vec<Repair> {
+        let mut repairs = Vec::new();
+        if options.assign_xrefs {
+            self.assign_xrefs(&mut repairs);
+        }
+        if options.strip_empty {
+            self.strip_empty(&mut repairs);
+        }
+        if options.remove_duplicate_children {
+            for fam in &mut self.families {
+                let family = fam.xref.as_deref().unwrap_or("FAM");
+                let mut seen = HashSet::new();
+                fam.children.retain(|child| {
+                    let first = seen.insert(child.clone());
+                    if !first {
+                        let repair =
+                            Repair::new(RepairKind::RemovedDuplicate, family, "CHIL", Some(child));
+                        repairs.push(repair);
+                    }
+                    first
+                });
+            }
+        }
+        if options.link_families {
+            self.link_families(&mut repairs);
+        }
+        repairs
+    }
+
+    /// Gives the first free xref to each record without one
+    fn assign_xrefs(&mut self, repairs: &mut Vec<Repair>) {
+        let mut used: HashSet<String> = self
+            .records()
+            .filter_map(|record| record.xref())
+            .map(str::to_string)
+            .collect();
+        let format = XrefFormat::default();
+        let mut assign = |tag: &str, xrefs: Vec<&mut Option<String>>| {
+            let style = format.style(tag);
+            let mut number = 0;
+            for xref in xrefs.into_iter().filter(|xref| xref.is_none()) {
+                let fresh = loop {
+                    number += 1;
+                    let candidate = style.xref(number);
+                    if !used.contains(&candidate) {
+                        break candidate;
+                    }
+                };
+                repairs.push(Repair::new(RepairKind::AssignedXref, &fresh, tag, None));
+                used.insert(fresh.clone());
+                *xref = Some(fresh);
+            }
+        };
+        assign(
+            "SUBM",
+            self.submitters
+                .iter_mut()
+                .map(|subm| &mut subm.xref)
+                .collect(),
+        );
+        assign(
+            "SUBN",
+            self.submissions
+                .iter_mut()
+                .map(|subn| &mut subn.xref)
+                .collect(),
+        );
+        assign(
+            "INDI",
+            self.individuals
+                .iter_mut()
+                .map(|indi| &mut indi.xref)
+                .collect(),
+        );
+        assign(
+            "FAM",
+            self.families.iter_mut().map(|fam| &mut fam.xref).collect(),
+        );
+        assign(
+            "SOUR",
+            self.sources.iter_mut().map(|sour| &mut sour.xref).collect(),
+        );
+        assign(
+            "REPO",
+            self.repositories
+                .iter_mut()
+                .map(|repo| &mut repo.xref)
+                .collect(),
+        );
+        assign(
+            "OBJE",
+            self.multimedia
+                .iter_mut()
+                .map(|obje| &mut obje.xref)
+                .collect(),
+        );
+        assign(
+            "NOTE",
+            self.notes.iter_mut().map(|note| &mut note.xref).collect(),
+        );
+    }
+
+    /// Removes the empty structures of individuals and families, and the empty top-level
+    /// user-defined records
+    fn strip_empty(&mut self, repairs: &mut Vec<Repair>) {
+        for indi in &mut self.individuals {
+            let mut removed = Removed::new(indi.xref.as_deref().unwrap_or("INDI"), repairs);
+            indi.families.retain(|link| {
+                let empty = link.xref.trim().is_empty();
+                removed.record_if(empty, link.family_link_type.tag());
+                !empty
+            });
+            removed.notes(&mut indi.notes);
+            removed.events(&mut indi.events);
+            removed.custom_data(&mut indi.custom_data);
+        }
+        for fam in &mut self.families {
+            let mut removed = Removed::new(fam.xref.as_deref().unwrap_or("FAM"), repairs);
+            for (spouse, tag) in [
+                (&mut fam.individual1, "HUSB"),
+                (&mut fam.individual2, "WIFE"),
+            ] {
+                if spouse.as_ref().is_some_and(|xref| xref.trim().is_empty()) {
+                    *spouse = None;
+                    removed.record_if(true, tag);
+                }
+            }
+            fam.children.retain(|child| {
+                let empty = child.trim().is_empty();
+                removed.record_if(empty, "CHIL");
+                !empty
+            });
+            removed.notes(&mut fam.notes);
+            removed.events(&mut fam.events);
+            removed.events(&mut fam.family_event);
+            removed.custom_data(&mut fam.custom_data);
+        }
+        for custom in &mut self.custom_data {
+            let record = custom.xref.clone().unwrap_or_else(|| custom.tag.clone());
+            Removed::new(&record, repairs).custom_data(&mut custom.children);
+        }
+        self.custom_data.retain(|custom| {
+            let empty = is_empty_custom(custom);
+            if empty {
+                repairs.push(Repair::new(
+                    RepairKind::RemovedEmpty,
+                    &custom.tag,
+                    &custom.tag,
+                    None,
+                ));
+            }
+            !empty
+        });
+    }
+
+    /// Adds the FAMC and FAMS links missing from the members of families, and the members
+    /// missing from the families individuals link to
+    fn link_families(&mut self, repairs: &mut Vec<Repair>) {
+        let missing_members = self.missing_members();
+        let missing_links = self.missing_links();
+        for (index, family, link_type) in missing_links {
+            let indi = &mut self.individuals[index];
+            let record = indi.xref.as_deref().unwrap_or_default();
+            let tag = link_type.tag();
+            repairs.push(Repair::new(
+                RepairKind::AddedLink,
+                record,
+                tag,
+                Some(&family),
+            ));
+            indi.families.push(family_link(&family, link_type));
+        }
+        for (family, index, spouse) in missing_members {
+            let indi = &self.individuals[index];
+            let fam = &mut self.families[family];
+            let (Some(family), Some(xref)) = (fam.xref.clone(), indi.xref.clone()) else {
+                continue;
+            };
+            if !spouse {
+                repairs.push(Repair::new(
+                    RepairKind::AddedLink,
+                    &family,
+                    "CHIL",
+                    Some(&xref),
+                ));
+                fam.add_child(xref);
+                continue;
+            }
+            let female = indi.sex.as_ref().is_some_and(|sex| sex.value.tag() == "F");
+            let slots = if female {
+                [
+                    (&mut fam.individual2, "WIFE"),
+                    (&mut fam.individual1, "HUSB"),
+                ]
+            } else {
+                [
+                    (&mut fam.individual1, "HUSB"),
+                    (&mut fam.individual2, "WIFE"),
+                ]
+            };
+            let [(first, first_tag), (second, second_tag)] = slots;
+            let (slot, tag) = match (first.is_none(), second.is_none()) {
+                (true, _) => (first, first_tag),
+                (false, true) => (second, second_tag),
+                (false, false) => {
+                    repairs.push(Repair::new(
+                        RepairKind::UnrepairedLink,
+                        &xref,
+                        "FAMS",
+                        Some(&family),
+                    ));
+                    continue;
+                }
+            };
+            repairs.push(Repair::new(
+                RepairKind::AddedLink,
+                &family,
+                tag,
+                Some(&xref),
+            ));
+            *slot = Some(xref);
+        }
+    }
+
+    /// The links missing from individuals to the families listing them, as the index of the
+    /// individual, the family and the kind of link, in file order
+    fn missing_links(&self) -> Vec<(usize, String, FamilyLinkType)> {
+        let individuals: HashMap<&str, usize> = self
+            .individuals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, indi)| Some((indi.xref.as_deref()?, index)))
+            .collect();
+        let mut links: HashSet<(&str, &str, bool)> = HashSet::new();
+        for indi in &self.individuals {
+            let Some(xref) = indi.xref.as_deref() else {
+                continue;
+            };
+            for link in &indi.families {
+                links.insert((xref, &link.xref, is_link(link, &link.xref, true)));
+            }
+        }
+
+        let mut missing = Vec::new();
+        for fam in &self.families {
+            let Some(family) = fam.xref.as_deref() else {
+                continue;
+            };
+            let spouses = fam.individual1.iter().chain(&fam.individual2);
+            let members = spouses
+                .map(|spouse| (spouse, true))
+                .chain(fam.children.iter().map(|child| (child, false)));
+            for (member, spouse) in members {
+                let Some(&index) = individuals.get(member.as_str()) else {
+                    continue;
+                };
+                if links.insert((member, family, spouse)) {
+                    let link_type = if spouse {
+                        FamilyLinkType::Spouse
+                    } else {
+                        FamilyLinkType::Child
+                    };
+                    missing.push((index, family.to_string(), link_type));
+                }
+            }
+        }
+        missing
+    }
+
+    /// The members missing from the families individuals link to, as the index of the family,
+    /// the index of the individual and whether it is a spouse, in file order
+    fn missing_members(&self) -> Vec<(usize, usize, bool)> {
+        let families: HashMap<&str, usize> = self
+            .families
+            .iter()
+            .enumerate()
+            .filter_map(|(index, fam)| Some((fam.xref.as_deref()?, index)))
+            .collect();
+        let mut members: HashSet<(&str, &str, bool)> = HashSet::new();
+        for fam in &self.families {
+            let Some(family) = fam.xref.as_deref() else {
+                continue;
+            };
+            for spouse in fam.individual1.iter().chain(&fam.individual2) {
+                members.insert((family, spouse, true));
+            }
+            for child in &fam.children {
+                members.insert((family, child, false));
+            }
+        }
+
+        let mut missing = Vec::new();
+        for (index, indi) in self.individuals.iter().enumerate() {
+            let Some(xref) = indi.xref.as_deref() else {
+                continue;
+            };
+            for link in &indi.families {
+                let spouse = is_link(link, &link.xref, true);
+                let Some(&family) = families.get(link.xref.as_str()) else {
+                    continue;
+                };
+                if members.insert((&link.xref, xref, spouse)) {
+                    missing.push((family, index, spouse));
+                }
+            }
+        }
+        missing
+    }
+}
+
+/// Removes the empty substructures of a record, recording a `Repair` for each
+struct Removed<'a> {
+    record: &'a str,
+    repairs: &'a mut Vec<Repair>,
+}
+
+impl<'a> Removed<'a> {
+    fn new(record: &'a str, repairs: &'a mut Vec<Repair>) -> Removed<'a> {
+        Removed { record, repairs }
+    }
+
+    fn record_if(&mut self, empty: bool, tag: &str) {
+        if empty {
+            self.repairs.push(Repair::new(
+                RepairKind::RemovedEmpty,
+                self.record,
+                tag,
+                None,
+            ));
+        }
+    }
+
+    fn notes(&mut self, notes: &mut Vec<Note>) {
+        notes.retain(|note| {
+            let empty = is_empty_note(note);
+            self.record_if(empty, "NOTE");
+            !empty
+        });
+    }
+
+    fn events(&mut self, events: &mut Vec<EventDetail>) {
+        for event in events.iter_mut() {
+            self.notes(&mut event.notes);
+            self.custom_data(&mut event.custom_data);
+        }
+        events.retain(|event| {
+            let empty = is_empty_event(event);
+            self.record_if(empty, event.event.tag());
+            !empty
+        });
+    }
+
+    #[allow(clippy::vec_box)]
+    fn custom_data(&mut self, custom_data: &mut Vec<Box<UserDefinedDataset>>) {
+        for custom in custom_data.iter_mut() {
+            self.custom_data(&mut custom.children);
+        }
+        custom_data.retain(|custom| {
+            let empty = is_empty_custom(custom);
+            self.record_if(empty, &custom.tag);
+            !empty
+        });
+    }
+}
+
+fn is_blank(value: Option<&str>) -> bool {
+    value.is_none_or(|value| value.trim().is_empty())
+}
+
+fn is_empty_note(note: &Note) -> bool {
+    is_blank(note.value.as_deref())
+        && note.mime.is_none()
+        && note.translation.is_none()
+        && note.citation.is_none()
+        && note.language.is_none()
+        && note.custom_data.is_empty()
+}
+
+fn is_empty_event(event: &EventDetail) -> bool {
+    is_blank(event.value.as_deref())
+        && event.date.is_none()
+        && is_blank(event.place.as_deref())
+        && event.coordinates.is_none()
+        && event.contact.is_empty()
+        && event.agency.is_none()
+        && event.religion.is_none()
+        && event.cause.is_none()
+        && event.restriction.is_none()
+        && event.age.is_none()
+        && event.notes.is_empty()
+        && event.family_link.is_none()
+        && event.family_event_details.is_empty()
+        && event.event_type.is_none()
+        && event.citations.is_empty()
+        && event.multimedia.is_empty()
+        && event.associations.is_empty()
+        && event.custom_data.is_empty()
+}
+
+fn is_empty_custom(custom: &UserDefinedDataset) -> bool {
+    custom.xref.is_none() && is_blank(custom.value.as_deref()) && custom.children.is_empty()
+}
@@ -0,0 +1,426 @@
+//! Human-readable reports on the individuals and families of a tree, as plain text or Markdown.
+//!
+//! `Report::individual` writes the summary sheet of an individual: their facts, their parents,
+//! and each family they are a spouse in, with the other spouse, the family events and the
+//! children. `Report::family` writes the group sheet of a family: the spouses and children with
+//! their births, christenings, deaths and burials, along with the family events.
+//! `Report::ancestors` outlines the ancestors of an individual up to some number of generations
+//! back, numbered as in `numbering::ahnentafel`. Ancestors are followed through birth families
+//! only, see `Kinship::parents`.
+//!
+//! Individuals are described by their name, their years of birth and death and their xref, and
+//! dates and places are shown as they are written in the file.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{report::{Report, ReportFormat}, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 SEX M\n\
+//!     1 BIRT\n\
+//!     2 DATE 12 MAR 1850\n\
+//!     2 PLAC Leeds\n\
+//!     1 OCCU Weaver\n\
+//!     1 DEAT\n\
+//!     2 DATE 1921\n\
+//!     1 FAMS @F1@\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Mary /Brown/\n\
+//!     1 FAMS @F1@\n\
+//!     0 @I3@ INDI\n\
+//!     1 NAME Thomas /Smith/\n\
+//!     1 BIRT\n\
+//!     2 DATE ABT 1875\n\
+//!     1 FAMC @F1@\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @I1@\n\
+//!     1 WIFE @I2@\n\
+//!     1 MARR\n\
+//!     2 DATE 3 JUN 1874\n\
+//!     1 CHIL @I3@\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//!
+//! let report = Report::new(&data, ReportFormat::Text);
+//! assert_eq!(
+//!     report.individual("@I1@").unwrap(),
+//!     "\
+//! John Smith (1850-1921) @I1@
+//!
+//! Facts:
+//!   Sex: Male
+//!   Birth: 12 MAR 1850 in Leeds
+//!   Death: 1921
+//!   Occupation: Weaver
+//!
+//! Families:
+//!   Spouse: Mary Brown @I2@ in @F1@
+//!     Marriage: 3 JUN 1874
+//!     Child: Thomas Smith (1875-) @I3@
+//! "
+//! );
+//!
+//! let report = Report::new(&data, ReportFormat::Markdown);
+//! assert_eq!(
+//!     report.ancestors("@I3@", 4).unwrap(),
+//!     "\
+//! ## Ancestors of Thomas Smith
+//!
+//! - 1\\. Thomas Smith (1875-) @I3@
+//!   - 2\\. John Smith (1850-1921) @I1@
+//!   - 3\\. Mary Brown @I2@
+//! "
+//! );
+//! assert!(report.family("@F2@").is_none());
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    debug_tree::describe,
+    kinship::Kinship,
+    numbering::MAX_GENERATIONS,
+    types::{
+        AttributeDetail, Event, EventDetail, Family, FamilyLinkType, Individual, Name, Pointer,
+    },
+    GedcomData,
+};
+
+/// `ReportFormat` is the markup a `Report` is written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Plain text, each heading ending with a colon and followed by its indented entries
+    #[default]
+    Text,
+    /// Markdown, each heading followed by a bulleted list of its entries
+    Markdown,
+}
+
+/// `Report` writes reports on the individuals and families of a tree, see the `report` module.
+pub struct Report<'a> {
+    format: ReportFormat,
+    kinship: Kinship<'a>,
+    individuals: HashMap<&'a str, &'a Individual>,
+    families: HashMap<&'a str, &'a Family>,
+}
+
+impl<'a> Report<'a> {
+    /// Starts writing reports on the tree in the given format
+    #[must_use]
+    pub fn new(data: &'a GedcomData, format: ReportFormat) -> Report<'a> {
+        Report {
+            format,
+            kinship: Kinship::new(data),
+            individuals: data
+                .individuals
+                .iter()
+                .filter_map(|indi| Some((indi.xref.as_deref()?, indi)))
+                .collect(),
+            families: data
+                .families
+                .iter()
+                .filter_map(|fam| Some((fam.xref.as_deref()?, fam)))
+                .collect(),
+        }
+    }
+
+    /// The summary sheet of the individual with the xref: their facts, their parents, and each
+    /// family they are a spouse in with the family events and the children. `None` when there is
+    /// no such individual.
+    #[must_use]
+    pub fn individual(&self, xref: &str) -> Option<String> {
+        let indi = *self.individuals.get(xref)?;
+
+        let mut facts = Vec::new();
+        if let Some(sex) = &indi.sex {
+            facts.push((0, format!("Sex: {}", sex.value.to_string())));
+        }
+        facts.extend(indi.events.iter().map(|event| (0, event_line(event))));
+        facts.extend(indi.attributes.iter().map(|attr| (0, attribute_line(attr))));
+
+        let (father, mother) = self.kinship.parents(xref);
+        let parents = [("Father", father), ("Mother", mother)]
+            .iter()
+            .filter_map(|&(role, parent)| {
+                let parent = self.describe(parent?);
+                Some((0, format!("{role}: {parent}")))
+            })
+            .collect();
+
+        let mut families = Vec::new();
+        let links = indi
+            .families
+            .iter()
+            .filter(|link| matches!(link.family_link_type, FamilyLinkType::Spouse));
+        for family in links.filter_map(|link| self.families.get(link.xref.as_str())) {
+            let family_xref = family.xref.as_deref().unwrap_or_default();
+            let spouse = spouses(family).find(|spouse| *spouse != xref);
+            families.push(match spouse {
+                Some(spouse) => (
+                    0,
+                    format!("Spouse: {} in {family_xref}", self.describe(spouse)),
+                ),
+                None => (0, format!("Family: {family_xref}")),
+            });
+            families.extend(family_facts(family).map(|line| (1, line)));
+            families.extend(
+                (family.children.iter())
+                    .map(|child| (1, format!("Child: {}", self.describe(child)))),
+            );
+        }
+
+        let sheet = Sheet {
+            title: self.describe(xref),
+            sections: vec![
+                Section::new("Facts", facts),
+                Section::new("Parents", parents),
+                Section::new("Families", families),
+            ],
+        };
+        Some(sheet.render(self.format))
+    }
+
+    /// The group sheet of the family with the xref: the spouses and children with their births,
+    /// christenings, deaths and burials, along with the family events. `None` when there is no
+    /// such family.
+    #[must_use]
+    pub fn family(&self, xref: &str) -> Option<String> {
+        let family = *self.families.get(xref)?;
+
+        let names: Vec<String> = spouses(family).map(|spouse| self.name(spouse)).collect();
+        let title = if names.is_empty() {
+            format!("Family {xref}")
+        } else {
+            format!("Family of {} {xref}", names.join(" and "))
+        };
+
+        let roles = [
+            ("Husband", &family.individual1),
+            ("Wife", &family.individual2),
+        ];
+        let mut sections: Vec<Section> = roles
+            .iter()
+            .map(|(role, spouse)| {
+                let spouse = spouse.as_deref().filter(|spouse| *spouse != Pointer::VOID);
+                let lines = spouse.map(|spouse| self.vital_lines(spouse));
+                Section::new(role, lines.unwrap_or_default())
+            })
+            .collect();
+        sections.push(Section::new(
+            "Events",
+            family_facts(family).map(|line| (0, line)).collect(),
+        ));
+        let children = family
+            .children
+            .iter()
+            .flat_map(|child| self.vital_lines(child))
+            .collect();
+        sections.push(Section::new("Children", children));
+
+        Some(Sheet { title, sections }.render(self.format))
+    }
+
+    /// An outline of the ancestors of the individual with the xref, up to `generations`
+    /// generations back and at most 63, each numbered with their Ahnentafel number. An ancestor
+    /// reached through several lines of descent is listed under each. `None` when there is no
+    /// such individual.
+    #[must_use]
+    pub fn ancestors(&self, xref: &str, generations: usize) -> Option<String> {
+        self.individuals.get(xref)?;
+        let mut lines = Vec::new();
+        let generations = generations.min(MAX_GENERATIONS as usize);
+        self.outline(&mut lines, xref, 1, 0, generations);
+        let sheet = Sheet {
+            title: format!("Ancestors of {}", self.name(xref)),
+            sections: vec![Section {
+                heading: None,
+                lines,
+            }],
+        };
+        Some(sheet.render(self.format))
+    }
+
+    /// Adds the individual numbered `number` and their ancestors to the outline, depth first
+    fn outline(
+        &self,
+        lines: &mut Vec<(usize, String)>,
+        xref: &str,
+        number: u64,
+        generation: usize,
+        generations: usize,
+    ) {
+        lines.push((generation, format!("{number}. {}", self.describe(xref))));
+        if generation == generations {
+            return;
+        }
+        let (father, mother) = self.kinship.parents(xref);
+        if let Some(father) = father {
+            self.outline(lines, father, 2 * number, generation + 1, generations);
+        }
+        if let Some(mother) = mother {
+            self.outline(lines, mother, 2 * number + 1, generation + 1, generations);
+        }
+    }
+
+    /// The individual, followed by the events of their birth and death, indented below them
+    fn vital_lines(&self, xref: &str) -> Vec<(usize, String)> {
+        let mut lines = vec![(0, self.describe(xref))];
+        let events = self.individuals.get(xref).into_iter().flat_map(|indi| {
+            indi.events.iter().filter(|event| {
+                matches!(
+                    event.event,
+                    Event::Birth | Event::Christening | Event::Death | Event::Burial
+                )
+            })
+        });
+        lines.extend(events.map(|event| (1, event_line(event))));
+        lines
+    }
+
+    fn describe(&self, xref: &str) -> String {
+        describe(self.individuals.get(xref).copied(), xref)
+    }
+
+    /// The full name of an individual, or their xref when they have none
+    fn name(&self, xref: &str) -> String {
+        self.individuals
+            .get(xref)
+            .and_then(|indi| indi.name.as_ref())
+            .and_then(Name::full_name)
+            .unwrap_or_else(|| xref.to_string())
+    }
+}
+
+/// The xrefs of the spouses of a family, leaving out VOID pointers
+fn spouses(family: &Family) -> impl Iterator<Item = &str> {
+    (family.individual1.iter())
+        .chain(&family.individual2)
+        .map(String::as_str)
+        .filter(|spouse| *spouse != Pointer::VOID)
+}
+
+/// The events and facts of a family
+fn family_facts(family: &Family) -> impl Iterator<Item = String> + '_ {
+    let events = family.events.iter().chain(&family.family_event);
+    events
+        .map(event_line)
+        .chain(family.attributes.iter().map(attribute_line))
+}
+
+/// An event, e.g. "Birth: 12 MAR 1850 in Leeds", leaving out the `Y` of events only known to
+/// have happened
+fn event_line(event: &EventDetail) -> String {
+    let value = event.value.as_deref().filter(|value| *value != "Y");
+    let date = event.date.as_ref().and_then(|date| date.value.as_deref());
+    let place = event.place.as_ref().map(ToString::to_string);
+    fact_line(event.kind(), &[value, date], place.as_deref())
+}
+
+/// A fact, e.g. "Occupation: Weaver, 1881 in Leeds"
+fn attribute_line(attribute: &AttributeDetail) -> String {
+    let date = attribute
+        .date
+        .as_ref()
+        .and_then(|date| date.value.as_deref());
+    let place = attribute.place.as_ref().map(ToString::to_string);
+    fact_line(
+        attribute.kind(),
+        &[attribute.value.as_deref(), date],
+        place.as_deref(),
+    )
+}
+
+fn fact_line(kind: String, details: &[Option<&str>], place: Option<&str>) -> String {
+    let details: Vec<&str> = details
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|detail| !detail.trim().is_empty())
+        .collect();
+    let mut line = kind;
+    if !details.is_empty() {
+        line = format!("{line}: {}", details.join(", "));
+    }
+    if let Some(place) = place.filter(|place| !place.trim().is_empty()) {
+        line = format!("{line} in {place}");
+    }
+    line
+}
+
+/// A report: a title followed by sections of indented lines
+struct Sheet {
+    title: String,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    heading: Option<&'static str>,
+    /// The lines of the section along with their depth
+    lines: Vec<(usize, String)>,
+}
+
+impl Section {
+    fn new(heading: &'static str, lines: Vec<(usize, String)>) -> Section {
+        Section {
+            heading: Some(heading),
+            lines,
+        }
+    }
+}
+
+impl Sheet {
+    /// The report, leaving out the sections without lines
+    fn render(&self, format: ReportFormat) -> String {
+        let mut lines = vec![match format {
+            ReportFormat::Text => self.title.clone(),
+            ReportFormat::Markdown => format!("# {}", escape_markdown(&self.title)),
+        }];
+        for section in self
+            .sections
+            .iter()
+            .filter(|section| !section.lines.is_empty())
+        {
+            lines.push(String::new());
+            match (format, section.heading) {
+                (ReportFormat::Text, Some(heading)) => lines.push(format!("{heading}:")),
+                (ReportFormat::Markdown, Some(heading)) => {
+                    lines.push(format!("## {heading}"));
+                    lines.push(String::new());
+                }
+                (_, None) => {}
+            }
+            // entries are indented below the heading of their section in plain text
+            let indent = usize::from(format == ReportFormat::Text && section.heading.is_some());
+            lines.extend(section.lines.iter().map(|(depth, line)| {
+                let margin = "  ".repeat(depth + indent);
+                match format {
+                    ReportFormat::Text => format!("{margin}{line}"),
+                    ReportFormat::Markdown => format!("{margin}- {}", escape_markdown(line)),
+                }
+            }));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Escapes the characters of the text which Markdown would read as markup, including the dot
+/// of a leading number which would start a numbered list
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let number = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    for (index, c) in text.char_indices() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#')
+            || (c == '.' && number > 0 && index == number)
+        {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
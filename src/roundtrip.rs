@@ -0,0 +1,456 @@
+//! Line-preserving round trips, for tools making surgical edits to files kept under version
+//! control.
+//!
+//! Parsing with `ParseOptions::preserve_lines` keeps the original text of each top-level record in
+//! a `SourceMap`, stored as `GedcomData::source_map`. When the tree is written back, a record
+//! which writes out the same as it did right after the parse is copied from the original text byte
+//! for byte, keeping its line endings, tag order and CONC splits. Only records which were modified
+//! are regenerated. Records keep their original order, with new records written just before the
+//! TRLR, and top-level records the parser skipped, e.g. SNOTE records, are kept as they were.
+//!
+//! Substructures the parser doesn't keep, e.g. tags it doesn't know skipped in lenient mode, are
+//! kept too: when their record is regenerated they are copied back verbatim, after the sibling
+//! they followed in the file. They are dropped along with their parent structure, should it be
+//! removed from the tree, and from the records whose details `GedcomData::privatize` hides. See
+//! `RecordSource::unrecognized`.
+//!
+//! Setting `GedcomData::source_map` to `None` writes the whole tree afresh instead.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{GedcomDocument, ParseOptions};
+//! let sample = "\
+//!     0 HEAD\r\n\
+//!     1 GEDC\r\n\
+//!     2 VERS 5.5\r\n\
+//!     0 @I1@ INDI\r\n\
+//!     1 NAME John /Doe/\r\n\
+//!     1 SEX M\r\n\
+//!     0 @I2@ INDI\r\n\
+//!     1 NAME Jane /Doe/\r\n\
+//!     0 TRLR\r\n";
+//!
+//! let options = ParseOptions {
+//!     preserve_lines: true,
+//!     ..ParseOptions::default()
+//! };
+//! let mut data = GedcomDocument::with_options(sample.chars(), options)
+//!     .parse_document()
+//!     .unwrap();
+//! assert_eq!(data.to_gedcom_string(), sample);
+//!
+//! let record = data.source_map.as_ref().unwrap().record("@I2@").unwrap();
+//! assert_eq!(record.lines, 7..9);
+//!
+//! // only the modified record is regenerated, with the writer's line endings
+//! data.individuals[1].name.as_mut().unwrap().value = Some("Jane /Smith/".to_string());
+//! let output = data.to_gedcom_string();
+//! assert!(output.starts_with("0 HEAD\r\n1 GEDC\r\n2 VERS 5.5\r\n0 @I1@ INDI\r\n"));
+//! assert!(output.ends_with("0 @I2@ INDI\n1 NAME Jane /Smith/\n0 TRLR\r\n"));
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, HashMap},
+    convert::TryFrom,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    ops::Range,
+};
+
+use crate::{
+    tokenizer::standard_tag,
+    util::{split_lines_inclusive, LineFields},
+    GedcomData,
+};
+
+/// `SourceMap` holds the original text of the top-level records of a parsed file, in file order.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    records: Vec<RecordSource>,
+}
+
+/// `RecordSource` is the original text of a top-level record, from its level 0 line up to the
+/// next one.
+#[derive(Clone, Debug)]
+pub struct RecordSource {
+    /// The tag of the record, e.g. INDI
+    pub tag: String,
+    /// The xref of the record, if it has one
+    pub xref: Option<String>,
+    /// The lines of the record in the file, starting at 1
+    pub lines: Range<u32>,
+    /// The text of the record, including its line terminators
+    pub raw: String,
+    /// Tells records with the same tag and xref apart, in file order
+    occurrence: usize,
+    /// Hash of the record as written right after the parse, or `None` when the parser skipped it
+    written: Option<u64>,
+    /// The substructures of the record the parser didn't keep
+    unrecognized: Vec<Passthrough>,
+    /// Whether the record is regenerated even when unmodified, see `SourceMap::redact`
+    redacted: bool,
+}
+
+/// A substructure of a record which the parser didn't keep, copied back when the record is
+/// regenerated
+#[derive(Clone, Debug)]
+struct Passthrough {
+    /// The tag and occurrence of each structure leading to it from the record line
+    parent: Vec<LineKey>,
+    /// The siblings it followed in the file which the parser kept, nearest first
+    after: Vec<LineKey>,
+    /// The text of the substructure, including its line terminators
+    raw: String,
+}
+
+/// Identifies a line among its siblings by its tag, and how many siblings with the same tag come
+/// before it
+type LineKey = (String, usize);
+
+/// Identifies a top-level record across the original text and the writer output
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct RecordKey<'a> {
+    tag: &'a str,
+    xref: Option<&'a str>,
+    occurrence: usize,
+}
+
+impl SourceMap {
+    /// Splits `input`, the text `data` was parsed from, into its top-level records.
+    #[must_use]
+    pub fn new(input: &str, data: &GedcomData) -> SourceMap {
+        let written_text = data.to_gedcom_string();
+        let written: HashMap<RecordKey, &str> = split_records(&written_text).into_iter().collect();
+
+        let mut line = 1;
+        let records = split_records(input)
+            .into_iter()
+            .map(|(key, raw)| {
+                let count = split_lines_inclusive(raw).count().max(1);
+                let start = line;
+                line += u32::try_from(count).unwrap_or(u32::MAX);
+                let written = written.get(&key);
+                RecordSource {
+                    tag: key.tag.to_string(),
+                    xref: key.xref.map(str::to_string),
+                    lines: start..line,
+                    raw: raw.to_string(),
+                    occurrence: key.occurrence,
+                    written: written.map(|text| fingerprint(text)),
+                    unrecognized: written
+                        .map(|text| unrecognized(raw, text))
+                        .unwrap_or_default(),
+                    redacted: false,
+                }
+            })
+            .collect();
+        SourceMap { records }
+    }
+
+    /// Shifts the line numbers of the records, for text starting on line `first_line` of a file
+    pub(crate) fn starting_at_line(mut self, first_line: u32) -> SourceMap {
+        for record in &mut self.records {
+            let start = record.lines.start + first_line - 1;
+            let end = record.lines.end + first_line - 1;
+            record.lines = start..end;
+        }
+        self
+    }
+
+    /// The records of the file, in file order
+    #[must_use]
+    pub fn records(&self) -> &[RecordSource] {
+        &self.records
+    }
+
+    /// The first record of the file with the given xref
+    #[must_use]
+    pub fn record(&self, xref: &str) -> Option<&RecordSource> {
+        self.records
+            .iter()
+            .find(|record| record.xref.as_deref() == Some(xref))
+    }
+
+    /// Forgets the original text of the records with the tag and xref beyond what the tree holds:
+    /// they are regenerated, without the substructures the parser didn't keep, e.g. once their
+    /// details were hidden by `GedcomData::privatize`. Without an xref, which can't tell records
+    /// apart, every record with the tag and no xref is redacted.
+    pub(crate) fn redact(&mut self, tag: &str, xref: Option<&str>) {
+        for record in &mut self.records {
+            if record.tag == tag && record.xref.as_deref() == xref {
+                record.unrecognized.clear();
+                record.redacted = true;
+            }
+        }
+    }
+
+    /// Writes the records of the file, taking the modified and new ones from `regenerated`.
+    /// `compared` is the same tree written with the default `WriterOptions`, which tells whether a
+    /// record was modified.
+    pub(crate) fn write(
+        &self,
+        out: &mut impl Write,
+        compared: &str,
+        regenerated: &str,
+    ) -> io::Result<()> {
+        // both come from the same tree, so their records line up
+        let mut pending: Vec<Option<(RecordKey, u64, &str)>> = split_records(compared)
+            .into_iter()
+            .zip(split_records(regenerated))
+            .map(|((key, compared), (_, text))| Some((key, fingerprint(compared), text)))
+            .collect();
+        let index: HashMap<RecordKey, usize> = pending
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| Some((entry.as_ref()?.0, i)))
+            .collect();
+
+        for record in &self.records {
+            let Some(written) = record.written else {
+                out.write_all(record.raw.as_bytes())?;
+                continue;
+            };
+            if record.tag == "TRLR" {
+                for entry in &mut pending {
+                    if entry.is_some_and(|(key, ..)| key.tag != "TRLR") {
+                        if let Some((_, _, text)) = entry.take() {
+                            out.write_all(text.as_bytes())?;
+                        }
+                    }
+                }
+            }
+            // a record no longer in the tree was deleted
+            let key = record.key();
+            let Some((_, hash, text)) = index.get(&key).and_then(|i| pending[*i].take()) else {
+                continue;
+            };
+            let text = if hash == written && !record.redacted {
+                Cow::Borrowed(record.raw.as_str())
+            } else {
+                record.restore_unrecognized(text)
+            };
+            out.write_all(text.as_bytes())?;
+        }
+        for (_, _, text) in pending.into_iter().flatten() {
+            out.write_all(text.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl RecordSource {
+    /// The text of the substructures of the record which the parser didn't keep, e.g. tags it
+    /// doesn't know, in file order
+    pub fn unrecognized(&self) -> impl Iterator<Item = &str> {
+        self.unrecognized
+            .iter()
+            .map(|passthrough| passthrough.raw.as_str())
+    }
+
+    /// Inserts the unrecognized substructures of the record into `text`, the record as
+    /// regenerated by the writer
+    fn restore_unrecognized<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        if self.unrecognized.is_empty() {
+            return Cow::Borrowed(text);
+        }
+        let lines = tree_lines(text);
+        let Some(root) = lines.iter().position(|line| line.level == 0) else {
+            return Cow::Borrowed(text);
+        };
+
+        // the index of the line each substructure goes before
+        let mut inserts: Vec<(usize, &str)> = Vec::new();
+        for passthrough in &self.unrecognized {
+            let mut parent = Some(root);
+            for key in &passthrough.parent {
+                parent = parent.and_then(|index| find_child(&lines, index, key));
+            }
+            // the structure holding it was removed
+            let Some(parent) = parent else {
+                continue;
+            };
+            let position = passthrough
+                .after
+                .iter()
+                .find_map(|key| find_child(&lines, parent, key))
+                .map_or(parent + 1, |sibling| subtree_end(&lines, sibling));
+            inserts.push((position, &passthrough.raw));
+        }
+        // sort_by_key is stable, keeping file order at the same position
+        inserts.sort_by_key(|(position, _)| *position);
+
+        let mut restored = String::with_capacity(text.len());
+        let mut inserts = inserts.into_iter().peekable();
+        for (index, line) in lines.iter().enumerate() {
+            while let Some((_, raw)) = inserts.next_if(|(position, _)| *position == index) {
+                restored.push_str(raw);
+            }
+            restored.push_str(line.text);
+        }
+        for (_, raw) in inserts {
+            restored.push_str(raw);
+        }
+        Cow::Owned(restored)
+    }
+
+    fn key(&self) -> RecordKey<'_> {
+        RecordKey {
+            tag: &self.tag,
+            xref: self.xref.as_deref(),
+            occurrence: self.occurrence,
+        }
+    }
+}
+
+/// Splits GEDCOM text into its top-level records. Text before the first level 0 line is kept with
+/// the first record.
+fn split_records(text: &str) -> Vec<(RecordKey<'_>, &str)> {
+    let mut starts: Vec<(usize, LineFields)> = Vec::new();
+    let mut offset = 0;
+    for line in split_lines_inclusive(text) {
+        let parsed = LineFields::split(line.trim_start());
+        if parsed.level == "0" {
+            starts.push((offset, parsed));
+        }
+        offset += line.len();
+    }
+    if let Some((first, _)) = starts.first_mut() {
+        *first = 0;
+    }
+
+    let mut occurrences: HashMap<(&str, Option<&str>), usize> = HashMap::new();
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain(std::iter::once(text.len()));
+    starts
+        .iter()
+        .zip(ends)
+        .map(|((start, line), end)| {
+            let tag = line.tag.unwrap_or_default();
+            // tags the parser read case-insensitively are written back in uppercase
+            let tag = standard_tag(tag).unwrap_or(tag);
+            let occurrence = occurrences.entry((tag, line.xref)).or_default();
+            let key = RecordKey {
+                tag,
+                xref: line.xref,
+                occurrence: *occurrence,
+            };
+            *occurrence += 1;
+            (key, &text[*start..end])
+        })
+        .collect()
+}
+
+/// A line of GEDCOM text, for walking its tree of structures
+struct TreeLine<'a> {
+    level: usize,
+    tag: &'a str,
+    /// The text of the line, including its terminator
+    text: &'a str,
+}
+
+fn tree_lines(text: &str) -> Vec<TreeLine<'_>> {
+    let mut lines: Vec<TreeLine> = Vec::new();
+    for text in split_lines_inclusive(text) {
+        let parsed = LineFields::split(text.trim_start_matches('\u{feff}').trim_start());
+        // a line without a level belongs to the structure above it
+        let level = parsed
+            .level
+            .parse()
+            .unwrap_or_else(|_| lines.last().map_or(0, |line| line.level.saturating_add(1)));
+        lines.push(TreeLine {
+            level,
+            tag: parsed.tag.unwrap_or_default(),
+            text,
+        });
+    }
+    lines
+}
+
+/// The index just past the last line of the structure starting at `index`
+fn subtree_end(lines: &[TreeLine], index: usize) -> usize {
+    lines[index + 1..]
+        .iter()
+        .position(|line| line.level <= lines[index].level)
+        .map_or(lines.len(), |end| index + 1 + end)
+}
+
+/// The direct substructures of the structure starting at `index`, with their keys
+fn children(lines: &[TreeLine], index: usize) -> Vec<(usize, LineKey)> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut children = Vec::new();
+    let mut child = index + 1;
+    let end = subtree_end(lines, index);
+    while child < end {
+        // tags the parser read case-insensitively are written back in uppercase
+        let tag = lines[child].tag.to_ascii_uppercase();
+        let occurrence = occurrences.entry(tag.clone()).or_default();
+        children.push((child, (tag, *occurrence)));
+        *occurrence += 1;
+        child = subtree_end(lines, child);
+    }
+    children
+}
+
+fn find_child(lines: &[TreeLine], index: usize, key: &LineKey) -> Option<usize> {
+    children(lines, index)
+        .into_iter()
+        .find(|(_, child)| child == key)
+        .map(|(child, _)| child)
+}
+
+/// Finds the substructures of `raw`, the text of a record, which are missing from `written`, the
+/// same record as written right after the parse. Substructures are matched by their tag and
+/// occurrence, so that changes of formatting, e.g. CONC splits, don't count.
+fn unrecognized(raw: &str, written: &str) -> Vec<Passthrough> {
+    fn walk(
+        raw: &[TreeLine],
+        raw_index: usize,
+        written: &[TreeLine],
+        written_index: usize,
+        path: &[LineKey],
+        found: &mut Vec<Passthrough>,
+    ) {
+        let written_children = children(written, written_index);
+        let mut after: Vec<LineKey> = Vec::new();
+        for (child, key) in children(raw, raw_index) {
+            if matches!(key.0.as_str(), "CONT" | "CONC") {
+                continue;
+            }
+            let counterpart = written_children
+                .iter()
+                .find(|(_, written_key)| *written_key == key);
+            if let Some((written_child, _)) = counterpart {
+                let mut child_path = path.to_vec();
+                child_path.push(key.clone());
+                walk(raw, child, written, *written_child, &child_path, found);
+                after.insert(0, key);
+            } else {
+                let end = subtree_end(raw, child);
+                found.push(Passthrough {
+                    parent: path.to_vec(),
+                    after: after.clone(),
+                    raw: raw[child..end].iter().map(|line| line.text).collect(),
+                });
+            }
+        }
+    }
+
+    let (raw, written) = (tree_lines(raw), tree_lines(written));
+    let root = |lines: &[TreeLine]| lines.iter().position(|line| line.level == 0);
+    let mut found = Vec::new();
+    if let (Some(raw_root), Some(written_root)) = (root(&raw), root(&written)) {
+        walk(&raw, raw_root, &written, written_root, &[], &mut found);
+    }
+    found
+}
+
+fn fingerprint(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,616 @@
+//! Indexes for looking up the records of a `GedcomData` tree by xref, UID, name or place.
+//!
+//! Names are spelled inconsistently across records, so besides exact matches a `NameIndex` can be
+//! queried phonetically with [Soundex](https://en.wikipedia.org/wiki/Soundex) codes, or by
+//! [Levenshtein distance](https://en.wikipedia.org/wiki/Levenshtein_distance) to allow for typos.
+
+use std::collections::HashMap;
+
+use crate::{
+    store::{Record, RecordStore, Records},
+    types::{EventDetail, Individual},
+    GedcomData,
+};
+
+/// `Indexes` bundles the lookup indexes of a tree. With the `"parallel"` feature they can also be
+/// built in the background, see `GedcomData::build_indexes_async`.
+#[derive(Clone, Debug, Default)]
+pub struct Indexes {
+    /// Records by xref
+    pub xrefs: XrefIndex,
+    /// Records by UID
+    pub uids: UidIndex,
+    /// Individuals by name
+    pub names: NameIndex,
+    /// Individuals and families by the places of their events
+    pub places: PlaceIndex,
+}
+
+impl Indexes {
+    /// Builds all indexes over `data`
+    #[must_use]
+    pub fn new<R: Records + ?Sized>(data: &R) -> Indexes {
+        Indexes {
+            xrefs: XrefIndex::new(data),
+            uids: UidIndex::new(data),
+            names: NameIndex::new(data),
+            places: PlaceIndex::new(data),
+        }
+    }
+}
+
+/// `RecordKind` is the type of a top-level record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordKind {
+    /// An individual, `GedcomData::individuals`
+    Individual,
+    /// A family, `GedcomData::families`
+    Family,
+    /// A source, `GedcomData::sources`
+    Source,
+    /// A repository, `GedcomData::repositories`
+    Repository,
+    /// A submitter, `GedcomData::submitters`
+    Submitter,
+    /// A submission, `GedcomData::submissions`
+    Submission,
+    /// A multimedia record, `GedcomData::multimedia`
+    Multimedia,
+}
+
+/// `RecordLocation` is the position of a record within `GedcomData`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordLocation {
+    /// The list the record is in
+    pub kind: RecordKind,
+    /// Index of the record in its list
+    pub index: usize,
+}
+
+/// `XrefIndex` locates records by their xref.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{search::{RecordKind, XrefIndex}, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     0 @I2@ INDI\n\
+///     0 @F1@ FAM\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let index = XrefIndex::new(&data);
+///
+/// let location = index.get("@I2@").unwrap();
+/// assert_eq!(location.kind, RecordKind::Individual);
+/// assert_eq!(location.index, 1);
+/// assert_eq!(index.get("@F1@").unwrap().kind, RecordKind::Family);
+/// assert!(index.get("@S1@").is_none());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct XrefIndex {
+    locations: HashMap<String, RecordLocation>,
+}
+
+impl XrefIndex {
+    /// Builds an index over every record in `data` with an xref. If an xref is used by more than
+    /// one record, the first one is kept.
+    #[must_use]
+    pub fn new<R: Records + ?Sized>(data: &R) -> XrefIndex {
+        let mut index = XrefIndex::default();
+        index.insert(RecordKind::Individual, data.individuals());
+        index.insert(RecordKind::Family, data.families());
+        index.insert(RecordKind::Source, data.sources());
+        index.insert(RecordKind::Repository, data.repositories());
+        index.insert(RecordKind::Submitter, data.submitters());
+        index.insert(RecordKind::Submission, data.submissions());
+        index.insert(RecordKind::Multimedia, data.multimedia());
+        index
+    }
+
+    fn insert<T: Record>(&mut self, kind: RecordKind, store: &dyn RecordStore<T>) {
+        for (index, record) in store.iter_records().enumerate() {
+            if let Some(xref) = record.xref() {
+                self.locations
+                    .entry(xref.to_string())
+                    .or_insert(RecordLocation { kind, index });
+            }
+        }
+    }
+
+    /// Finds the record with the given xref
+    #[must_use]
+    pub fn get(&self, xref: &str) -> Option<RecordLocation> {
+        self.locations.get(xref).copied()
+    }
+
+    /// Number of xrefs in the index
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Whether the index holds no xrefs
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+}
+
+/// `UidIndex` locates records by the UIDs of GEDCOM 7, which unlike xrefs stay the same when a
+/// tree is exported again. UIDs are matched ignoring case, as they are usually UUIDs.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{search::{RecordKind, UidIndex}, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 7.0\n\
+///     0 @I1@ INDI\n\
+///     1 UID bbcc0025-34cb-4542-8cfb-45ba201c9c2c\n\
+///     0 @S1@ SOUR\n\
+///     1 UID 9441c3f3-74df-42b4-bb1d-d7d5c4a4c3fe\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let index = UidIndex::new(&data);
+///
+/// let location = index.get("BBCC0025-34CB-4542-8CFB-45BA201C9C2C").unwrap();
+/// assert_eq!(location.kind, RecordKind::Individual);
+/// assert_eq!(index.get("9441c3f3-74df-42b4-bb1d-d7d5c4a4c3fe").unwrap().kind, RecordKind::Source);
+/// assert_eq!(index.len(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct UidIndex {
+    locations: HashMap<String, RecordLocation>,
+}
+
+impl UidIndex {
+    /// Builds an index over the UIDs of every record in `data`. If a UID is used by more than one
+    /// record, the first one is kept.
+    #[must_use]
+    pub fn new<R: Records + ?Sized>(data: &R) -> UidIndex {
+        let mut index = UidIndex::default();
+        index.insert(RecordKind::Individual, data.individuals());
+        index.insert(RecordKind::Family, data.families());
+        index.insert(RecordKind::Source, data.sources());
+        index.insert(RecordKind::Repository, data.repositories());
+        index.insert(RecordKind::Submitter, data.submitters());
+        index.insert(RecordKind::Multimedia, data.multimedia());
+        index
+    }
+
+    fn insert<T: Record>(&mut self, kind: RecordKind, store: &dyn RecordStore<T>) {
+        for (index, record) in store.iter_records().enumerate() {
+            for uid in record.uids() {
+                self.locations
+                    .entry(uid.to_lowercase())
+                    .or_insert(RecordLocation { kind, index });
+            }
+        }
+    }
+
+    /// Finds the record with the given UID
+    #[must_use]
+    pub fn get(&self, uid: &str) -> Option<RecordLocation> {
+        self.locations.get(&uid.to_lowercase()).copied()
+    }
+
+    /// Number of UIDs in the index
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Whether the index holds no UIDs
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+}
+
+/// `PlaceIndex` lists the individuals and families with events or attributes at each place.
+/// Places are matched on their full payload, e.g. "Boston, Suffolk, Massachusetts, USA".
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{search::PlaceIndex, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 BIRT\n\
+///     2 PLAC Boston\n\
+///     1 DEAT\n\
+///     2 PLAC Boston\n\
+///     0 @F1@ FAM\n\
+///     1 MARR\n\
+///     2 PLAC Boston\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let index = PlaceIndex::new(&data);
+///
+/// assert_eq!(index.get("Boston"), ["@I1@", "@F1@"]);
+/// assert!(index.get("Salem").is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PlaceIndex {
+    xrefs: HashMap<String, Vec<String>>,
+}
+
+impl PlaceIndex {
+    /// Builds an index over the places of the events and attributes of every individual and
+    /// family in `data` with an xref
+    #[must_use]
+    pub fn new<R: Records + ?Sized>(data: &R) -> PlaceIndex {
+        let mut index = PlaceIndex::default();
+        for indi in data.individuals().iter_records() {
+            let places = event_places(&indi.events).chain(
+                indi.attributes
+                    .iter()
+                    .filter_map(|attr| attr.place.as_deref()),
+            );
+            index.insert(indi.xref.as_deref(), places);
+        }
+        for fam in data.families().iter_records() {
+            index.insert(fam.xref.as_deref(), event_places(&fam.events));
+        }
+        index
+    }
+
+    fn insert<'a>(&mut self, xref: Option<&str>, places: impl Iterator<Item = &'a str>) {
+        let Some(xref) = xref else { return };
+        for place in places {
+            let xrefs = self.xrefs.entry(place.to_string()).or_default();
+            if !xrefs.iter().any(|known| known == xref) {
+                xrefs.push(xref.to_string());
+            }
+        }
+    }
+
+    /// xrefs of the records with an event or attribute at `place`, in file order
+    #[must_use]
+    pub fn get(&self, place: &str) -> &[String] {
+        self.xrefs.get(place).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every place in the index, in no particular order
+    pub fn places(&self) -> impl Iterator<Item = &str> {
+        self.xrefs.keys().map(String::as_str)
+    }
+}
+
+fn event_places(events: &[EventDetail]) -> impl Iterator<Item = &str> {
+    events.iter().filter_map(|event| event.place.as_deref())
+}
+
+/// `SearchMode` selects how query words are compared against the words of a name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Words must be identical
+    Exact,
+    /// Words must be identical, ignoring case
+    CaseInsensitive,
+    /// Words must have the same Soundex code, e.g. "Schmidt" and "Smith"
+    Soundex,
+    /// Words may differ by at most this many single-character edits, ignoring case
+    Levenshtein(usize),
+}
+
+/// `SearchResult` is an individual whose name matched a query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    /// xref of the matching individual
+    pub xref: String,
+    /// How closely the name matched, from 0.0 to 1.0 for an exact match. Words which match
+    /// phonetically or within the edit distance score by their spelling similarity.
+    pub score: f64,
+}
+
+/// `NameIndex` holds the names of the individuals in a tree, prepared for repeated searches.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{search::{NameIndex, SearchMode}, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME Hans /Schmidt/\n\
+///     0 @I2@ INDI\n\
+///     1 NAME Anna /Smith/\n\
+///     0 @I3@ INDI\n\
+///     1 NAME Hans /Muller/\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let index = NameIndex::new(&data);
+///
+/// let results = index.search("Schmidt", SearchMode::Soundex);
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[0].xref, "@I1@");
+/// assert!((results[0].score - 1.0).abs() < f64::EPSILON);
+/// assert_eq!(results[1].xref, "@I2@");
+/// assert!(results[1].score < 1.0);
+///
+/// let results = index.search("hans mueller", SearchMode::Levenshtein(1));
+/// assert_eq!(results.len(), 1);
+/// assert_eq!(results[0].xref, "@I3@");
+///
+/// assert!(index.search("hans", SearchMode::Exact).is_empty());
+/// assert_eq!(index.search("hans", SearchMode::CaseInsensitive).len(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NameIndex {
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct IndexEntry {
+    xref: String,
+    words: Vec<IndexedWord>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct IndexedWord {
+    text: String,
+    lowercase: String,
+    soundex: Option<String>,
+}
+
+impl IndexedWord {
+    pub(crate) fn new(text: &str) -> IndexedWord {
+        IndexedWord {
+            text: text.to_string(),
+            lowercase: text.to_lowercase(),
+            soundex: soundex(text),
+        }
+    }
+}
+
+impl NameIndex {
+    /// Builds an index over the names of every individual in `data` with an xref
+    #[must_use]
+    pub fn new<R: Records + ?Sized>(data: &R) -> NameIndex {
+        let entries = data
+            .individuals()
+            .iter_records()
+            .filter_map(|indi| {
+                let xref = indi.xref.clone()?;
+                let words = name_words(&indi)
+                    .into_iter()
+                    .map(IndexedWord::new)
+                    .collect();
+                Some(IndexEntry { xref, words })
+            })
+            .collect();
+        NameIndex { entries }
+    }
+
+    /// Number of individuals in the index
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the index holds no individuals
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Finds the individuals whose name matches every word of `query`, best matches first.
+    /// Individuals with equal scores are listed in file order.
+    #[must_use]
+    pub fn search(&self, query: &str, mode: SearchMode) -> Vec<SearchResult> {
+        let query: Vec<IndexedWord> = split_words(query).map(IndexedWord::new).collect();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut results: Vec<SearchResult> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let mut total = 0.0;
+                for word in &query {
+                    total += entry
+                        .words
+                        .iter()
+                        .filter_map(|candidate| word_score(word, candidate, mode))
+                        .fold(None, |best: Option<f64>, score| {
+                            Some(best.map_or(score, |best| best.max(score)))
+                        })?;
+                }
+                Some(SearchResult {
+                    xref: entry.xref.clone(),
+                    score: total / as_f64(query.len()),
+                })
+            })
+            .collect();
+
+        // sort_by is stable, keeping file order among equal scores
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results
+    }
+}
+
+impl GedcomData {
+    /// Finds the individuals whose name matches `query`, see `NameIndex::search`. Build a
+    /// `NameIndex` instead when searching the same tree repeatedly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{search::SearchMode, GedcomDocument};
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME Hans /Schmidt/\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let results = data.search_individuals("Schmitt", SearchMode::Soundex);
+    /// assert_eq!(results[0].xref, "@I1@");
+    /// ```
+    #[must_use]
+    pub fn search_individuals(&self, query: &str, mode: SearchMode) -> Vec<SearchResult> {
+        NameIndex::new(self).search(query, mode)
+    }
+}
+
+/// Scores how well `candidate` matches the query `word`, or `None` if it doesn't match at all.
+pub(crate) fn word_score(word: &IndexedWord, candidate: &IndexedWord, mode: SearchMode) -> Option<f64> {
+    let matches = match mode {
+        SearchMode::Exact => word.text == candidate.text,
+        SearchMode::CaseInsensitive => word.lowercase == candidate.lowercase,
+        SearchMode::Soundex => word.soundex.is_some() && word.soundex == candidate.soundex,
+        SearchMode::Levenshtein(max_distance) => {
+            levenshtein(&word.lowercase, &candidate.lowercase) <= max_distance
+        }
+    };
+    if !matches {
+        return None;
+    }
+    Some(similarity(&word.lowercase, &candidate.lowercase))
+}
+
+/// Spelling similarity of two words from 0.0 to 1.0, based on their edit distance.
+pub(crate) fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - as_f64(levenshtein(a, b)) / as_f64(longest)
+}
+
+/// Converts a count to `f64`; names are far too short to lose precision.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn as_f64(count: usize) -> f64 {
+    count as f64
+}
+
+/// The distinct words of an individual's name, from the name value and its name pieces.
+pub(crate) fn name_words(indi: &Individual) -> Vec<&str> {
+    let mut words: Vec<&str> = Vec::new();
+    if let Some(name) = &indi.name {
+        let pieces = [
+            name.value.as_deref(),
+            name.given.as_deref(),
+            name.surname.as_deref(),
+            name.surname_prefix.as_deref(),
+        ];
+        for piece in pieces.iter().flatten() {
+            for word in split_words(piece) {
+                if !words.contains(&word) {
+                    words.push(word);
+                }
+            }
+        }
+    }
+    words
+}
+
+/// Splits a name on whitespace and the slashes delimiting the surname, e.g. "John /Doe/".
+fn split_words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|c: char| c.is_whitespace() || c == '/')
+        .filter(|word| !word.is_empty())
+}
+
+/// Computes the American Soundex code of a name, e.g. "R163" for both "Robert" and "Rupert".
+/// Characters other than ASCII letters are ignored; returns `None` if there are no letters.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::search::soundex;
+/// assert_eq!(soundex("Washington").unwrap(), "W252");
+/// assert_eq!(soundex("Tymczak").unwrap(), "T522");
+/// assert_eq!(soundex("Ashcraft").unwrap(), "A261");
+/// assert_eq!(soundex("Lee").unwrap(), "L000");
+/// assert_eq!(soundex("1850"), None);
+/// ```
+#[must_use]
+pub fn soundex(name: &str) -> Option<String> {
+    let mut letters = name
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase());
+
+    let first = letters.next()?;
+    let mut code = String::with_capacity(4);
+    code.push(first);
+
+    let mut previous = soundex_digit(first);
+    for letter in letters {
+        let digit = soundex_digit(letter);
+        if digit.is_some() && digit != previous {
+            code.extend(digit);
+            if code.len() == 4 {
+                break;
+            }
+        }
+        // H and W don't separate letters with the same code, vowels do
+        if letter != 'H' && letter != 'W' {
+            previous = digit;
+        }
+    }
+
+    while code.len() < 4 {
+        code.push('0');
+    }
+    Some(code)
+}
+
+fn soundex_digit(letter: char) -> Option<char> {
+    match letter {
+        'B' | 'F' | 'P' | 'V' => Some('1'),
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => Some('2'),
+        'D' | 'T' => Some('3'),
+        'L' => Some('4'),
+        'M' | 'N' => Some('5'),
+        'R' => Some('6'),
+        _ => None,
+    }
+}
+
+/// Counts the single-character insertions, deletions and substitutions needed to turn `a` into
+/// `b`.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::search::levenshtein;
+/// assert_eq!(levenshtein("kitten", "sitting"), 3);
+/// assert_eq!(levenshtein("Müller", "Mueller"), 2);
+/// assert_eq!(levenshtein("", "Doe"), 3);
+/// ```
+#[must_use]
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,240 @@
+//! Locations of the structures of a parsed file, for editor tooling such as language servers.
+//!
+//! Parsing with `ParseOptions::record_spans` keeps a `SpanMap` in `GedcomData::spans`. It is a
+//! side table giving the lines and byte offsets each structure was read from, keyed by the xref of
+//! its record and its tag path within the record, e.g. `BIRT.DATE`. Repeated tags are numbered
+//! from 0 in brackets, e.g. `RESI[1].ADDR` for the address of the second residence, and the
+//! first occurrence can be written without its number. The record itself has the empty path.
+//!
+//! Diagnostics found in the tree are mapped back to the file with `SpanMap::span`, and a position
+//! in the file to the structure under it with `SpanMap::at_offset`, e.g. for go-to-definition on
+//! a pointer.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{GedcomDocument, ParseOptions};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Doe/\n\
+//!     1 RESI\n\
+//!     2 PLAC Leeds\n\
+//!     1 RESI\n\
+//!     2 PLAC York\n\
+//!     0 TRLR";
+//!
+//! let options = ParseOptions {
+//!     record_spans: true,
+//!     ..ParseOptions::default()
+//! };
+//! let data = GedcomDocument::with_options(sample.chars(), options)
+//!     .parse_document()
+//!     .unwrap();
+//! let spans = data.spans.as_ref().unwrap();
+//!
+//! let record = spans.span("@I1@", "").unwrap();
+//! assert_eq!(record.lines, 4..10);
+//! let place = spans.span("@I1@", "RESI[1].PLAC").unwrap();
+//! assert_eq!(place.lines, 9..10);
+//! assert_eq!(&sample[place.bytes.clone()], "2 PLAC York\n");
+//!
+//! let offset = sample.find("Leeds").unwrap();
+//! let (record, structure) = spans.at_offset(offset).unwrap();
+//! assert_eq!(record.xref.as_deref(), Some("@I1@"));
+//! assert_eq!(structure.path, "RESI.PLAC");
+//! ```
+
+use std::{collections::HashMap, convert::TryFrom, ops::Range};
+
+use crate::util::{split_lines_inclusive, LineFields};
+
+/// `SpanMap` holds the location of every structure of a parsed file, see the `spans` module.
+#[derive(Clone, Debug, Default)]
+pub struct SpanMap {
+    records: Vec<RecordSpans>,
+    /// The position of the first record with each xref
+    positions: HashMap<String, usize>,
+}
+
+/// `RecordSpans` is the location of a top-level record and of the structures within it.
+#[derive(Clone, Debug)]
+pub struct RecordSpans {
+    /// The tag of the record, e.g. INDI
+    pub tag: String,
+    /// The xref of the record, if it has one
+    pub xref: Option<String>,
+    /// The record and its structures in file order, the record first
+    pub structures: Vec<StructureSpan>,
+}
+
+/// `StructureSpan` is the location of a structure, from its first line up to the next line which
+/// isn't part of it. CONT and CONC lines belong to the structure they continue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructureSpan {
+    /// The tag path of the structure within its record, e.g. `RESI[1].PLAC`
+    pub path: String,
+    /// The lines of the structure, starting at 1
+    pub lines: Range<u32>,
+    /// The bytes of the structure within the file, including its line terminators
+    pub bytes: Range<usize>,
+}
+
+impl RecordSpans {
+    /// The span of the record itself
+    #[must_use]
+    pub fn span(&self) -> &StructureSpan {
+        &self.structures[0]
+    }
+
+    /// The structure of the record at the tag path, see the `spans` module
+    #[must_use]
+    pub fn structure(&self, path: &str) -> Option<&StructureSpan> {
+        let path = normalize_path(path);
+        self.structures
+            .iter()
+            .find(|structure| normalize_path(&structure.path) == path)
+    }
+}
+
+impl SpanMap {
+    /// Finds the structures of the file contents `input`
+    pub(crate) fn new(input: &str) -> SpanMap {
+        let mut map = SpanMap::default();
+        // the structures enclosing the current line: their level, position in the record, and
+        // the number of times each tag was seen among their children
+        let mut open: Vec<(u8, usize, HashMap<String, usize>)> = Vec::new();
+        let mut offset = 0;
+        for (number, line) in split_lines_inclusive(input).enumerate() {
+            let number = u32::try_from(number + 1).unwrap_or(u32::MAX);
+            let start = offset;
+            offset += line.len();
+
+            let fields = LineFields::split(
+                line.trim_start_matches(|c: char| c.is_whitespace() || c == '\u{FEFF}'),
+            );
+            let (Ok(level), Some(tag)) = (fields.level.parse::<u8>(), fields.tag) else {
+                continue;
+            };
+            if matches!(tag, "CONT" | "CONC") && level > 0 {
+                map.extend_open(&open, number, offset);
+                continue;
+            }
+            while open
+                .last()
+                .is_some_and(|(open_level, ..)| *open_level >= level)
+            {
+                open.pop();
+            }
+
+            let span = |path: String| StructureSpan {
+                path,
+                lines: number..number + 1,
+                bytes: start..offset,
+            };
+            let child = open.last_mut().map(|(_, parent, seen)| {
+                let count = seen.entry(tag.to_string()).or_insert(0);
+                let segment = match *count {
+                    0 => tag.to_string(),
+                    n => format!("{tag}[{n}]"),
+                };
+                *count += 1;
+                (*parent, segment)
+            });
+            match (child, map.records.last_mut()) {
+                (Some((parent, segment)), Some(record)) => {
+                    let path = match record.structures[parent].path.as_str() {
+                        "" => segment,
+                        parent => format!("{parent}.{segment}"),
+                    };
+                    record.structures.push(span(path));
+                    open.push((level, record.structures.len() - 1, HashMap::new()));
+                }
+                _ if level == 0 => {
+                    if let Some(xref) = fields.xref {
+                        map.positions
+                            .entry(xref.to_string())
+                            .or_insert(map.records.len());
+                    }
+                    map.records.push(RecordSpans {
+                        tag: tag.to_string(),
+                        xref: fields.xref.map(str::to_string),
+                        structures: vec![span(String::new())],
+                    });
+                    open.push((level, 0, HashMap::new()));
+                }
+                // a line below level 0 before the first record
+                _ => continue,
+            }
+            map.extend_open(&open, number, offset);
+        }
+        map
+    }
+
+    /// Extends the structures enclosing a line to its end
+    fn extend_open(&mut self, open: &[(u8, usize, HashMap<String, usize>)], line: u32, end: usize) {
+        if let Some(record) = self.records.last_mut() {
+            for (_, position, _) in open {
+                let structure = &mut record.structures[*position];
+                structure.lines.end = line + 1;
+                structure.bytes.end = end;
+            }
+        }
+    }
+
+    /// Shifts the locations of the structures, for text starting at byte `first_byte` and on line
+    /// `first_line` of a file
+    pub(crate) fn starting_at(mut self, first_byte: usize, first_line: u32) -> SpanMap {
+        for structure in self
+            .records
+            .iter_mut()
+            .flat_map(|record| &mut record.structures)
+        {
+            structure.lines =
+                structure.lines.start + first_line - 1..structure.lines.end + first_line - 1;
+            structure.bytes = structure.bytes.start + first_byte..structure.bytes.end + first_byte;
+        }
+        self
+    }
+
+    /// The records of the file, in file order
+    #[must_use]
+    pub fn records(&self) -> &[RecordSpans] {
+        &self.records
+    }
+
+    /// The first record of the file with the given xref
+    #[must_use]
+    pub fn record(&self, xref: &str) -> Option<&RecordSpans> {
+        self.records.get(*self.positions.get(xref)?)
+    }
+
+    /// The structure at the tag path within the record with the given xref, the empty path being
+    /// the record itself
+    #[must_use]
+    pub fn span(&self, xref: &str, path: &str) -> Option<&StructureSpan> {
+        self.record(xref)?.structure(path)
+    }
+
+    /// The innermost structure containing the byte at `offset`, along with its record
+    #[must_use]
+    pub fn at_offset(&self, offset: usize) -> Option<(&RecordSpans, &StructureSpan)> {
+        let position = self
+            .records
+            .partition_point(|record| record.span().bytes.start <= offset);
+        let record = self.records.get(position.checked_sub(1)?)?;
+        let structure = record
+            .structures
+            .iter()
+            .rev()
+            .find(|structure| structure.bytes.contains(&offset))?;
+        Some((record, structure))
+    }
+}
+
+/// The path with the `[0]` of first occurrences left out
+fn normalize_path(path: &str) -> String {
+    path.replace("[0]", "")
+}
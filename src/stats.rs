@@ -0,0 +1,360 @@
+//! Summary statistics of a tree, as printed by the `parse_gedcom` binary, and how often its
+//! shared records are used.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    store::Records,
+    types::{Date, DatePart, DateValue, EventDetail, Individual},
+    util::{is_record_pointer, LineFields},
+    validate::expected_record,
+    writer::GedcomWriter,
+    GedcomData,
+};
+
+/// `Stats` summarizes the contents of a tree: the number of records of each kind, the events by
+/// type, the range of dates covered, how common each surname is, and how many pointers lead
+/// nowhere.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     1 BIRT\n\
+///     2 DATE ABT 1850\n\
+///     1 FAMS @F1@\n\
+///     0 @I2@ INDI\n\
+///     1 NAME Jane /Doe/\n\
+///     1 BIRT\n\
+///     2 DATE 2 FEB 1852\n\
+///     0 @F1@ FAM\n\
+///     1 HUSB @I1@\n\
+///     1 WIFE @I3@\n\
+///     1 MARR\n\
+///     2 DATE BET 1870 AND 1875\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let stats = data.stats();
+///
+/// assert_eq!(stats.individuals, 2);
+/// assert_eq!(stats.families, 1);
+/// assert_eq!(stats.events["BIRT"], 2);
+/// assert_eq!(stats.events["MARR"], 1);
+/// let (earliest, latest) = stats.date_range.unwrap();
+/// assert_eq!(earliest.to_string(), "1850");
+/// assert_eq!(latest.to_string(), "1875");
+/// assert_eq!(stats.surnames, vec![("Doe".to_string(), 2)]);
+/// // @I3@ has no record
+/// assert_eq!(stats.unresolved_xrefs, 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of submitter records
+    pub submitters: usize,
+    /// Number of submission records
+    pub submissions: usize,
+    /// Number of individuals
+    pub individuals: usize,
+    /// Number of families
+    pub families: usize,
+    /// Number of repositories
+    pub repositories: usize,
+    /// Number of sources
+    pub sources: usize,
+    /// Number of multimedia records
+    pub multimedia: usize,
+    /// Number of individual and family events by tag, e.g. "BIRT"
+    pub events: BTreeMap<String, usize>,
+    /// The earliest and latest dates of the events which have a date
+    pub date_range: Option<(DatePart, DatePart)>,
+    /// Surnames with the number of individuals bearing them, most common first
+    pub surnames: Vec<(String, usize)>,
+    /// Number of distinct xrefs pointed to which have no record
+    pub unresolved_xrefs: usize,
+}
+
+impl Stats {
+    /// Gathers the statistics of `data`
+    #[must_use]
+    pub fn new<R: Records + ?Sized>(data: &R) -> Stats {
+        let mut stats = Stats {
+            submitters: data.submitters().len(),
+            submissions: data.submissions().len(),
+            individuals: data.individuals().len(),
+            families: data.families().len(),
+            repositories: data.repositories().len(),
+            sources: data.sources().len(),
+            multimedia: data.multimedia().len(),
+            ..Stats::default()
+        };
+
+        let mut surnames: HashMap<String, usize> = HashMap::new();
+        for individual in data.individuals().iter_records() {
+            stats.add_events(&individual.events);
+            if let Some(surname) = surname(&individual) {
+                *surnames.entry(surname).or_insert(0) += 1;
+            }
+        }
+        for family in data.families().iter_records() {
+            stats.add_events(&family.events);
+        }
+
+        stats.surnames = surnames.into_iter().collect();
+        stats
+            .surnames
+            .sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        stats.unresolved_xrefs = unresolved_xrefs(data);
+        stats
+    }
+
+    fn add_events(&mut self, events: &[EventDetail]) {
+        for event in events {
+            *self
+                .events
+                .entry(event.event.tag().to_string())
+                .or_insert(0) += 1;
+
+            let date = event.date.as_ref().and_then(Date::value_structured);
+            for part in date.as_ref().map(date_parts).unwrap_or_default() {
+                let (earliest, latest) = self
+                    .date_range
+                    .get_or_insert_with(|| (part.clone(), part.clone()));
+                if sort_key(part) < sort_key(earliest) {
+                    *earliest = part.clone();
+                }
+                if sort_key(part) > sort_key(latest) {
+                    *latest = part.clone();
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "----------------------")?;
+        writeln!(f, "| Gedcom Data Stats: |")?;
+        writeln!(f, "----------------------")?;
+        writeln!(f, "  submissions: {}", self.submissions)?;
+        writeln!(f, "  submitters: {}", self.submitters)?;
+        writeln!(f, "  individuals: {}", self.individuals)?;
+        writeln!(f, "  families: {}", self.families)?;
+        writeln!(f, "  repositories: {}", self.repositories)?;
+        writeln!(f, "  sources: {}", self.sources)?;
+        writeln!(f, "  multimedia: {}", self.multimedia)?;
+        writeln!(f, "  events: {}", self.events.values().sum::<usize>())?;
+        for (tag, count) in &self.events {
+            writeln!(f, "    {tag}: {count}")?;
+        }
+        if let Some((earliest, latest)) = &self.date_range {
+            writeln!(f, "  dates: {earliest} to {latest}")?;
+        }
+        writeln!(f, "  surnames: {}", self.surnames.len())?;
+        for (surname, count) in self.surnames.iter().take(10) {
+            writeln!(f, "    {surname}: {count}")?;
+        }
+        writeln!(f, "  unresolved xrefs: {}", self.unresolved_xrefs)?;
+        write!(f, "----------------------")
+    }
+}
+
+impl GedcomData {
+    /// Gathers summary statistics of the tree, see `Stats`.
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        Stats::new(self)
+    }
+}
+
+/// The surname of an individual, from the SURN of their name or else the part of the name between
+/// slashes
+pub(crate) fn surname(individual: &Individual) -> Option<String> {
+    let name = individual.name.as_ref()?;
+    name.family_name().map(ToString::to_string)
+}
+
+fn date_parts(date: &DateValue) -> Vec<&DatePart> {
+    match date {
+        DateValue::Exact(part)
+        | DateValue::About(part)
+        | DateValue::Calculated(part)
+        | DateValue::Estimated(part)
+        | DateValue::Before(part)
+        | DateValue::After(part)
+        | DateValue::From(part)
+        | DateValue::To(part)
+        | DateValue::Interpreted(part, _) => vec![part],
+        DateValue::Between(start, end) | DateValue::FromTo(start, end) => vec![start, end],
+        DateValue::Phrase(_) => Vec::new(),
+    }
+}
+
+fn sort_key(part: &DatePart) -> (i32, u8, u8) {
+    (part.year, part.month.unwrap_or(0), part.day.unwrap_or(0))
+}
+
+/// Counts the distinct pointers of the tree which don't lead to a record
+fn unresolved_xrefs<R: Records + ?Sized>(data: &R) -> usize {
+    let pointers = Pointers::new(data);
+    let pointed: HashSet<&str> = pointers.pointers.iter().map(|(_, p)| p.as_str()).collect();
+    pointed
+        .into_iter()
+        .filter(|pointer| !pointers.defined.contains_key(*pointer))
+        .count()
+}
+
+/// The xrefs of the records of a tree along with the pointers to them. Pointers are read from the
+/// tree as written out by `GedcomWriter`, so every structure holding one is covered.
+struct Pointers {
+    /// The tag of the record defining each xref
+    defined: HashMap<String, String>,
+    /// Each pointer along with the tag of its line, e.g. FAMC
+    pointers: Vec<(String, String)>,
+}
+
+impl Pointers {
+    fn new<R: Records + ?Sized>(data: &R) -> Pointers {
+        let mut found = Pointers {
+            defined: HashMap::new(),
+            pointers: Vec::new(),
+        };
+        let mut output = Vec::new();
+        if GedcomWriter::new(&mut output).write(data).is_err() {
+            return found;
+        }
+        let text = String::from_utf8_lossy(&output);
+
+        for line in text.lines() {
+            let line = LineFields::split(line);
+            let tag = line.tag.unwrap_or_default();
+            if let (Some(xref), "0") = (line.xref, line.level) {
+                found.defined.insert(xref.to_string(), tag.to_string());
+            }
+            // continued text can look like a pointer
+            if matches!(tag, "CONT" | "CONC") {
+                continue;
+            }
+            if let Some(value) = line.value.filter(|value| is_record_pointer(value)) {
+                found.pointers.push((tag.to_string(), value.to_string()));
+            }
+        }
+        found
+    }
+}
+
+/// `UsageCounts` is how many times each shared record of a tree is pointed to, e.g. to find the
+/// sources nothing cites any more. Each map holds every record of its kind, along with the xrefs
+/// pointed to as that kind which have no record.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     1 SUBM @U1@\n\
+///     0 @U1@ SUBM\n\
+///     1 NAME Jane Doe\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     1 SOUR @S1@\n\
+///     1 BIRT\n\
+///     2 SOUR @S1@\n\
+///     1 NOTE @N1@\n\
+///     0 @S1@ SOUR\n\
+///     1 TITL Parish register\n\
+///     0 @S2@ SOUR\n\
+///     1 TITL Census\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let usage = data.usage_counts();
+///
+/// assert_eq!(usage.sources["@S1@"], 2);
+/// assert_eq!(usage.sources["@S2@"], 0);
+/// assert_eq!(usage.notes["@N1@"], 1);
+/// assert_eq!(usage.submitters["@U1@"], 1);
+/// assert_eq!(usage.unused(), vec!["@S2@"]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UsageCounts {
+    /// Pointers to each source record
+    pub sources: BTreeMap<String, usize>,
+    /// Pointers to each note record
+    pub notes: BTreeMap<String, usize>,
+    /// Pointers to each multimedia record
+    pub multimedia: BTreeMap<String, usize>,
+    /// Pointers to each submitter record, including those of the header
+    pub submitters: BTreeMap<String, usize>,
+}
+
+impl UsageCounts {
+    /// Counts the pointers to the shared records of `data`
+    #[must_use]
+    pub fn new<R: Records + ?Sized>(data: &R) -> UsageCounts {
+        let pointers = Pointers::new(data);
+        let mut usage = UsageCounts::default();
+        for (xref, tag) in &pointers.defined {
+            if let Some(counts) = usage.counts_mut(tag) {
+                counts.entry(xref.clone()).or_insert(0);
+            }
+        }
+        for (tag, pointer) in &pointers.pointers {
+            let kind = match pointers.defined.get(pointer) {
+                Some(tag) => Some(tag.as_str()),
+                None => expected_record(tag),
+            };
+            if let Some(counts) = kind.and_then(|kind| usage.counts_mut(kind)) {
+                *counts.entry(pointer.clone()).or_insert(0) += 1;
+            }
+        }
+        usage
+    }
+
+    /// The xrefs of the records nothing points to, in order of kind and then xref
+    #[must_use]
+    pub fn unused(&self) -> Vec<&str> {
+        [
+            &self.sources,
+            &self.notes,
+            &self.multimedia,
+            &self.submitters,
+        ]
+        .iter()
+        .flat_map(|counts| counts.iter())
+        .filter(|(_, count)| **count == 0)
+        .map(|(xref, _)| xref.as_str())
+        .collect()
+    }
+
+    fn counts_mut(&mut self, record_tag: &str) -> Option<&mut BTreeMap<String, usize>> {
+        match record_tag {
+            "SOUR" => Some(&mut self.sources),
+            "NOTE" => Some(&mut self.notes),
+            "OBJE" => Some(&mut self.multimedia),
+            "SUBM" => Some(&mut self.submitters),
+            _ => None,
+        }
+    }
+}
+
+impl GedcomData {
+    /// Counts how many times each source, note, multimedia and submitter record is pointed to,
+    /// see `UsageCounts`.
+    #[must_use]
+    pub fn usage_counts(&self) -> UsageCounts {
+        UsageCounts::new(self)
+    }
+}
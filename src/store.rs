@@ -0,0 +1,788 @@
+//! Storage for the top-level records of a tree.
+//!
+//! `GedcomData` keeps its records in plain `Vec`s, but code which only reads a tree, such as the
+//! `writer` and the `search` indexes, goes through the `Records` and `RecordStore` traits instead.
+//! An application can implement them over another backend, e.g. records kept on disk or
+//! compressed in memory, and reuse that code unchanged.
+//!
+//! `RecordStore` hands out records as `Cow`s, so a backend can return records it decoded on the
+//! fly as well as references to records it holds.
+//!
+//! Finding a record of a `GedcomData` by xref scans its list, which makes code resolving many
+//! pointers quadratic on large trees. `IndexedGedcomData` keeps each kind of record in a
+//! `MemoryStore` instead, finding records by xref in constant time while still iterating over
+//! them in file order.
+//!
+//! `GedcomData` is `Send` and `Sync`, so a parsed tree can be shared between threads in an `Arc`.
+//! Handlers which keep hold of single records, e.g. in a web service, can turn the tree into an
+//! `ArcGedcomData` instead, whose records are each held in an `Arc` and are cloned in constant
+//! time.
+
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+use crate::{
+    intern::StringPool,
+    roundtrip::SourceMap,
+    types::{
+        Family, Header, Individual, MultimediaRecord, NoteRecord, Repository, Source, Submission,
+        Submitter, UserDefinedDataset,
+    },
+    GedcomData,
+};
+
+/// `Record` is a top-level record which can be referred to by its xref.
+pub trait Record: Clone {
+    /// The cross-reference identifier of the record, e.g. `@I1@`
+    fn xref(&self) -> Option<&str>;
+
+    /// The UIDs of the record, which unlike its xref stay the same across files. None by default.
+    fn uids(&self) -> &[String] {
+        &[]
+    }
+}
+
+macro_rules! impl_record {
+    ($($record:ty),*) => {
+        $(impl Record for $record {
+            fn xref(&self) -> Option<&str> {
+                self.xref.as_deref()
+            }
+
+            fn uids(&self) -> &[String] {
+                &self.uids
+            }
+        })*
+    };
+}
+
+impl_record!(
+    Individual,
+    Family,
+    Source,
+    Repository,
+    Submitter,
+    MultimediaRecord,
+    NoteRecord
+);
+
+impl Record for Submission {
+    fn xref(&self) -> Option<&str> {
+        self.xref.as_deref()
+    }
+}
+
+/// `RecordStore` holds the records of one kind, in file order.
+pub trait RecordStore<T: Record> {
+    /// Number of records in the store
+    fn len(&self) -> usize;
+
+    /// Whether the store holds no records
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Finds the first record with the given xref
+    fn lookup(&self, xref: &str) -> Option<Cow<'_, T>>;
+
+    /// Iterates over the records, in file order
+    fn iter_records(&self) -> Box<dyn Iterator<Item = Cow<'_, T>> + '_>;
+
+    /// Adds a record after the existing ones
+    fn add(&mut self, record: T);
+}
+
+impl<T: Record> RecordStore<T> for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    /// Finds the record by scanning the whole list, see `MemoryStore` for faster lookups
+    fn lookup(&self, xref: &str) -> Option<Cow<'_, T>> {
+        self.iter()
+            .find(|record| record.xref() == Some(xref))
+            .map(Cow::Borrowed)
+    }
+
+    fn iter_records(&self) -> Box<dyn Iterator<Item = Cow<'_, T>> + '_> {
+        Box::new(self.iter().map(Cow::Borrowed))
+    }
+
+    fn add(&mut self, record: T) {
+        self.push(record);
+    }
+}
+
+/// `MemoryStore` keeps records in memory along with an index of their xrefs, for constant time
+/// lookups.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{store::{MemoryStore, RecordStore}, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     0 @I2@ INDI\n\
+///     1 NAME Jane /Doe/\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let store = MemoryStore::from(data.individuals);
+///
+/// assert_eq!(store.len(), 2);
+/// let jane = store.lookup("@I2@").unwrap();
+/// assert_eq!(jane.name.as_ref().unwrap().value.as_deref(), Some("Jane /Doe/"));
+/// assert!(store.lookup("@I3@").is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct MemoryStore<T> {
+    records: Vec<T>,
+    positions: HashMap<String, usize>,
+}
+
+impl<T: Record> MemoryStore<T> {
+    /// Creates an empty store
+    #[must_use]
+    pub fn new() -> MemoryStore<T> {
+        MemoryStore {
+            records: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// The record with the given xref
+    #[must_use]
+    pub fn get(&self, xref: &str) -> Option<&T> {
+        let position = *self.positions.get(xref)?;
+        self.records.get(position)
+    }
+
+    /// The records of the store, in file order
+    #[must_use]
+    pub fn records(&self) -> &[T] {
+        &self.records
+    }
+
+    /// Unwraps the records of the store, in file order
+    #[must_use]
+    pub fn into_vec(self) -> Vec<T> {
+        self.records
+    }
+}
+
+impl<T: Record> Default for MemoryStore<T> {
+    fn default() -> MemoryStore<T> {
+        MemoryStore::new()
+    }
+}
+
+impl<T: Record> From<Vec<T>> for MemoryStore<T> {
+    fn from(records: Vec<T>) -> MemoryStore<T> {
+        let mut store = MemoryStore::new();
+        for record in records {
+            store.add(record);
+        }
+        store
+    }
+}
+
+impl<T: Record> RecordStore<T> for MemoryStore<T> {
+    fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    fn lookup(&self, xref: &str) -> Option<Cow<'_, T>> {
+        let position = *self.positions.get(xref)?;
+        self.records.get(position).map(Cow::Borrowed)
+    }
+
+    fn iter_records(&self) -> Box<dyn Iterator<Item = Cow<'_, T>> + '_> {
+        Box::new(self.records.iter().map(Cow::Borrowed))
+    }
+
+    /// Adds a record. If its xref is already used, lookups keep finding the first record.
+    fn add(&mut self, record: T) {
+        if let Some(xref) = record.xref() {
+            self.positions
+                .entry(xref.to_string())
+                .or_insert(self.records.len());
+        }
+        self.records.push(record);
+    }
+}
+
+/// `Records` gives access to all the records of a tree, each kind in its own `RecordStore`.
+///
+/// # Example
+///
+/// A tree moving its individuals to a `MemoryStore`, which is written out the same way as the
+/// `GedcomData` it came from:
+///
+/// ```
+/// use gedcom::{
+///     store::{MemoryStore, RecordStore, Records},
+///     types::{
+///         Family, Header, Individual, MultimediaRecord, Repository, Source, Submission, Submitter,
+///         UserDefinedDataset,
+///     },
+///     GedcomData, GedcomDocument,
+/// };
+///
+/// struct Tree {
+///     rest: GedcomData,
+///     individuals: MemoryStore<Individual>,
+/// }
+///
+/// impl Records for Tree {
+///     fn header(&self) -> Option<&Header> {
+///         self.rest.header()
+///     }
+///     fn submitters(&self) -> &dyn RecordStore<Submitter> {
+///         self.rest.submitters()
+///     }
+///     fn submissions(&self) -> &dyn RecordStore<Submission> {
+///         self.rest.submissions()
+///     }
+///     fn individuals(&self) -> &dyn RecordStore<Individual> {
+///         &self.individuals
+///     }
+///     fn families(&self) -> &dyn RecordStore<Family> {
+///         self.rest.families()
+///     }
+///     fn repositories(&self) -> &dyn RecordStore<Repository> {
+///         self.rest.repositories()
+///     }
+///     fn sources(&self) -> &dyn RecordStore<Source> {
+///         self.rest.sources()
+///     }
+///     fn multimedia(&self) -> &dyn RecordStore<MultimediaRecord> {
+///         self.rest.multimedia()
+///     }
+///     fn custom_data(&self) -> &[Box<UserDefinedDataset>] {
+///         self.rest.custom_data()
+///     }
+/// }
+///
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     0 @F1@ FAM\n\
+///     1 HUSB @I1@\n\
+///     0 TRLR\n";
+///
+/// let mut rest = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let individuals = MemoryStore::from(std::mem::take(&mut rest.individuals));
+/// let tree = Tree { rest, individuals };
+///
+/// assert!(tree.individuals().lookup("@I1@").is_some());
+/// let mut output = Vec::new();
+/// gedcom::writer::GedcomWriter::new(&mut output).write(&tree).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), sample);
+/// ```
+pub trait Records {
+    /// Header containing file metadata
+    fn header(&self) -> Option<&Header>;
+    /// Submitters of the facts
+    fn submitters(&self) -> &dyn RecordStore<Submitter>;
+    /// Submission records
+    fn submissions(&self) -> &dyn RecordStore<Submission>;
+    /// Individuals within the family tree
+    fn individuals(&self) -> &dyn RecordStore<Individual>;
+    /// The family units of the tree
+    fn families(&self) -> &dyn RecordStore<Family>;
+    /// Repositories where sources are held
+    fn repositories(&self) -> &dyn RecordStore<Repository>;
+    /// Sources of facts
+    fn sources(&self) -> &dyn RecordStore<Source>;
+    /// Multimedia records
+    fn multimedia(&self) -> &dyn RecordStore<MultimediaRecord>;
+    /// Shared notes. None by default.
+    fn notes(&self) -> &dyn RecordStore<NoteRecord> {
+        static NONE: Vec<NoteRecord> = Vec::new();
+        &NONE
+    }
+    /// Top-level user-defined tags
+    fn custom_data(&self) -> &[Box<UserDefinedDataset>];
+    /// The original text of the records, for writing untouched records back as they were read,
+    /// see `roundtrip::SourceMap`. None by default.
+    fn source_map(&self) -> Option<&SourceMap> {
+        None
+    }
+}
+
+impl Records for GedcomData {
+    fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    fn submitters(&self) -> &dyn RecordStore<Submitter> {
+        &self.submitters
+    }
+
+    fn submissions(&self) -> &dyn RecordStore<Submission> {
+        &self.submissions
+    }
+
+    fn individuals(&self) -> &dyn RecordStore<Individual> {
+        &self.individuals
+    }
+
+    fn families(&self) -> &dyn RecordStore<Family> {
+        &self.families
+    }
+
+    fn repositories(&self) -> &dyn RecordStore<Repository> {
+        &self.repositories
+    }
+
+    fn sources(&self) -> &dyn RecordStore<Source> {
+        &self.sources
+    }
+
+    fn multimedia(&self) -> &dyn RecordStore<MultimediaRecord> {
+        &self.multimedia
+    }
+
+    fn notes(&self) -> &dyn RecordStore<NoteRecord> {
+        &self.notes
+    }
+
+    fn custom_data(&self) -> &[Box<UserDefinedDataset>] {
+        &self.custom_data
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_ref()
+    }
+}
+
+/// `IndexedGedcomData` is a tree whose records are kept in `MemoryStore`s, to find them by xref in
+/// constant time, see the `store` module. It is built from a parsed `GedcomData`, and can be
+/// written and searched like one through `Records`.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{store::IndexedGedcomData, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     1 FAMS @F1@\n\
+///     0 @I2@ INDI\n\
+///     1 NAME Jane /Doe/\n\
+///     1 FAMC @F1@\n\
+///     0 @F1@ FAM\n\
+///     1 HUSB @I1@\n\
+///     1 CHIL @I2@\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let tree = IndexedGedcomData::from(data);
+///
+/// let family = tree.find_family("@F1@").unwrap();
+/// let child = tree.find_individual(&family.children[0]).unwrap();
+/// assert_eq!(child.name.as_ref().unwrap().value.as_deref(), Some("Jane /Doe/"));
+///
+/// let names: Vec<_> = tree
+///     .individuals
+///     .records()
+///     .iter()
+///     .filter_map(|indi| indi.name.as_ref()?.value.as_deref())
+///     .collect();
+/// assert_eq!(names, vec!["John /Doe/", "Jane /Doe/"]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IndexedGedcomData {
+    /// Header containing file metadata
+    pub header: Option<Header>,
+    /// Submitters of the facts
+    pub submitters: MemoryStore<Submitter>,
+    /// Submission records
+    pub submissions: MemoryStore<Submission>,
+    /// Individuals within the family tree
+    pub individuals: MemoryStore<Individual>,
+    /// The family units of the tree
+    pub families: MemoryStore<Family>,
+    /// Repositories where sources are held
+    pub repositories: MemoryStore<Repository>,
+    /// Sources of facts
+    pub sources: MemoryStore<Source>,
+    /// Multimedia records
+    pub multimedia: MemoryStore<MultimediaRecord>,
+    /// Shared notes
+    pub notes: MemoryStore<NoteRecord>,
+    /// Top-level user-defined tags
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+    /// The original text of the records, see `GedcomData::source_map`
+    pub source_map: Option<SourceMap>,
+    /// The places and surnames shared by the records, see `GedcomData::strings`
+    pub strings: StringPool,
+}
+
+impl From<GedcomData> for IndexedGedcomData {
+    fn from(data: GedcomData) -> IndexedGedcomData {
+        IndexedGedcomData {
+            header: data.header,
+            submitters: MemoryStore::from(data.submitters),
+            submissions: MemoryStore::from(data.submissions),
+            individuals: MemoryStore::from(data.individuals),
+            families: MemoryStore::from(data.families),
+            repositories: MemoryStore::from(data.repositories),
+            sources: MemoryStore::from(data.sources),
+            multimedia: MemoryStore::from(data.multimedia),
+            notes: MemoryStore::from(data.notes),
+            custom_data: data.custom_data,
+            source_map: data.source_map,
+            strings: data.strings,
+        }
+    }
+}
+
+impl IndexedGedcomData {
+    /// The individual with the given xref
+    #[must_use]
+    pub fn find_individual(&self, xref: &str) -> Option<&Individual> {
+        self.individuals.get(xref)
+    }
+
+    /// The family with the given xref
+    #[must_use]
+    pub fn find_family(&self, xref: &str) -> Option<&Family> {
+        self.families.get(xref)
+    }
+
+    /// The source with the given xref
+    #[must_use]
+    pub fn find_source(&self, xref: &str) -> Option<&Source> {
+        self.sources.get(xref)
+    }
+
+    /// The repository with the given xref
+    #[must_use]
+    pub fn find_repository(&self, xref: &str) -> Option<&Repository> {
+        self.repositories.get(xref)
+    }
+
+    /// The submitter with the given xref
+    #[must_use]
+    pub fn find_submitter(&self, xref: &str) -> Option<&Submitter> {
+        self.submitters.get(xref)
+    }
+
+    /// The multimedia record with the given xref
+    #[must_use]
+    pub fn find_multimedia(&self, xref: &str) -> Option<&MultimediaRecord> {
+        self.multimedia.get(xref)
+    }
+
+    /// The shared note with the given xref
+    #[must_use]
+    pub fn find_note(&self, xref: &str) -> Option<&NoteRecord> {
+        self.notes.get(xref)
+    }
+
+    /// Unwraps the records into a `GedcomData`, e.g. to edit them
+    #[must_use]
+    pub fn into_gedcom_data(self) -> GedcomData {
+        GedcomData {
+            header: self.header,
+            submitters: self.submitters.into_vec(),
+            submissions: self.submissions.into_vec(),
+            individuals: self.individuals.into_vec(),
+            families: self.families.into_vec(),
+            repositories: self.repositories.into_vec(),
+            sources: self.sources.into_vec(),
+            multimedia: self.multimedia.into_vec(),
+            notes: self.notes.into_vec(),
+            custom_data: self.custom_data,
+            source_map: self.source_map,
+            strings: self.strings,
+            ..GedcomData::default()
+        }
+    }
+}
+
+impl Records for IndexedGedcomData {
+    fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    fn submitters(&self) -> &dyn RecordStore<Submitter> {
+        &self.submitters
+    }
+
+    fn submissions(&self) -> &dyn RecordStore<Submission> {
+        &self.submissions
+    }
+
+    fn individuals(&self) -> &dyn RecordStore<Individual> {
+        &self.individuals
+    }
+
+    fn families(&self) -> &dyn RecordStore<Family> {
+        &self.families
+    }
+
+    fn repositories(&self) -> &dyn RecordStore<Repository> {
+        &self.repositories
+    }
+
+    fn sources(&self) -> &dyn RecordStore<Source> {
+        &self.sources
+    }
+
+    fn multimedia(&self) -> &dyn RecordStore<MultimediaRecord> {
+        &self.multimedia
+    }
+
+    fn notes(&self) -> &dyn RecordStore<NoteRecord> {
+        &self.notes
+    }
+
+    fn custom_data(&self) -> &[Box<UserDefinedDataset>] {
+        &self.custom_data
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_ref()
+    }
+}
+
+/// `SharedStore` holds each record in an `Arc`, along with an index of their xrefs, so that
+/// records can be handed out and kept past the lifetime of a borrow of the store.
+#[derive(Clone, Debug)]
+pub struct SharedStore<T> {
+    records: Vec<Arc<T>>,
+    positions: HashMap<String, usize>,
+}
+
+impl<T: Record> SharedStore<T> {
+    /// Creates an empty store
+    #[must_use]
+    pub fn new() -> SharedStore<T> {
+        SharedStore {
+            records: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// The record with the given xref, shared with the store
+    #[must_use]
+    pub fn get(&self, xref: &str) -> Option<Arc<T>> {
+        let position = *self.positions.get(xref)?;
+        self.records.get(position).cloned()
+    }
+
+    /// The records of the store, in file order
+    #[must_use]
+    pub fn records(&self) -> &[Arc<T>] {
+        &self.records
+    }
+
+    /// Adds a record held in an `Arc` already. If its xref is already used, lookups keep finding
+    /// the first record.
+    pub fn add_shared(&mut self, record: Arc<T>) {
+        if let Some(xref) = record.xref() {
+            self.positions
+                .entry(xref.to_string())
+                .or_insert(self.records.len());
+        }
+        self.records.push(record);
+    }
+}
+
+impl<T: Record> Default for SharedStore<T> {
+    fn default() -> SharedStore<T> {
+        SharedStore::new()
+    }
+}
+
+impl<T: Record> From<Vec<T>> for SharedStore<T> {
+    fn from(records: Vec<T>) -> SharedStore<T> {
+        let mut store = SharedStore::new();
+        for record in records {
+            store.add(record);
+        }
+        store
+    }
+}
+
+impl<T: Record> RecordStore<T> for SharedStore<T> {
+    fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    fn lookup(&self, xref: &str) -> Option<Cow<'_, T>> {
+        let position = *self.positions.get(xref)?;
+        self.records
+            .get(position)
+            .map(|record| Cow::Borrowed(&**record))
+    }
+
+    fn iter_records(&self) -> Box<dyn Iterator<Item = Cow<'_, T>> + '_> {
+        Box::new(self.records.iter().map(|record| Cow::Borrowed(&**record)))
+    }
+
+    fn add(&mut self, record: T) {
+        self.add_shared(Arc::new(record));
+    }
+}
+
+/// `ArcGedcomData` is a read-only tree whose records are each held in an `Arc`, for sharing
+/// between threads, e.g. the request handlers of a web service. It is built from a parsed
+/// `GedcomData`, and can be written and searched like one through `Records`.
+///
+/// # Example
+///
+/// ```
+/// use std::{sync::Arc, thread};
+/// use gedcom::{store::ArcGedcomData, writer::GedcomWriter, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     0 TRLR\n";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let tree = Arc::new(ArcGedcomData::from(data));
+///
+/// let handler = {
+///     let tree = Arc::clone(&tree);
+///     thread::spawn(move || tree.individuals.get("@I1@"))
+/// };
+/// let john = handler.join().unwrap().unwrap();
+/// assert_eq!(john.name.as_ref().unwrap().value.as_deref(), Some("John /Doe/"));
+/// assert!(Arc::ptr_eq(&john, &tree.individuals.records()[0]));
+///
+/// let mut output = Vec::new();
+/// GedcomWriter::new(&mut output).write(&*tree).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), sample);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ArcGedcomData {
+    /// Header containing file metadata
+    pub header: Option<Arc<Header>>,
+    /// Submitters of the facts
+    pub submitters: SharedStore<Submitter>,
+    /// Submission records
+    pub submissions: SharedStore<Submission>,
+    /// Individuals within the family tree
+    pub individuals: SharedStore<Individual>,
+    /// The family units of the tree
+    pub families: SharedStore<Family>,
+    /// Repositories where sources are held
+    pub repositories: SharedStore<Repository>,
+    /// Sources of facts
+    pub sources: SharedStore<Source>,
+    /// Multimedia records
+    pub multimedia: SharedStore<MultimediaRecord>,
+    /// Shared notes
+    pub notes: SharedStore<NoteRecord>,
+    /// Top-level user-defined tags
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+    /// The original text of the records, see `GedcomData::source_map`
+    pub source_map: Option<SourceMap>,
+}
+
+impl From<GedcomData> for ArcGedcomData {
+    fn from(data: GedcomData) -> ArcGedcomData {
+        ArcGedcomData {
+            header: data.header.map(Arc::new),
+            submitters: SharedStore::from(data.submitters),
+            submissions: SharedStore::from(data.submissions),
+            individuals: SharedStore::from(data.individuals),
+            families: SharedStore::from(data.families),
+            repositories: SharedStore::from(data.repositories),
+            sources: SharedStore::from(data.sources),
+            multimedia: SharedStore::from(data.multimedia),
+            notes: SharedStore::from(data.notes),
+            custom_data: data.custom_data,
+            source_map: data.source_map,
+        }
+    }
+}
+
+impl ArcGedcomData {
+    /// Copies the records into a `GedcomData`, e.g. to edit them
+    #[must_use]
+    pub fn to_gedcom_data(&self) -> GedcomData {
+        fn copied<T: Clone>(store: &SharedStore<T>) -> Vec<T> {
+            store
+                .records
+                .iter()
+                .map(|record| T::clone(record))
+                .collect()
+        }
+        GedcomData {
+            header: self.header.as_deref().cloned(),
+            submitters: copied(&self.submitters),
+            submissions: copied(&self.submissions),
+            individuals: copied(&self.individuals),
+            families: copied(&self.families),
+            repositories: copied(&self.repositories),
+            sources: copied(&self.sources),
+            multimedia: copied(&self.multimedia),
+            notes: copied(&self.notes),
+            custom_data: self.custom_data.clone(),
+            source_map: self.source_map.clone(),
+            ..GedcomData::default()
+        }
+    }
+}
+
+impl Records for ArcGedcomData {
+    fn header(&self) -> Option<&Header> {
+        self.header.as_deref()
+    }
+
+    fn submitters(&self) -> &dyn RecordStore<Submitter> {
+        &self.submitters
+    }
+
+    fn submissions(&self) -> &dyn RecordStore<Submission> {
+        &self.submissions
+    }
+
+    fn individuals(&self) -> &dyn RecordStore<Individual> {
+        &self.individuals
+    }
+
+    fn families(&self) -> &dyn RecordStore<Family> {
+        &self.families
+    }
+
+    fn repositories(&self) -> &dyn RecordStore<Repository> {
+        &self.repositories
+    }
+
+    fn sources(&self) -> &dyn RecordStore<Source> {
+        &self.sources
+    }
+
+    fn multimedia(&self) -> &dyn RecordStore<MultimediaRecord> {
+        &self.multimedia
+    }
+
+    fn notes(&self) -> &dyn RecordStore<NoteRecord> {
+        &self.notes
+    }
+
+    fn custom_data(&self) -> &[Box<UserDefinedDataset>] {
+        &self.custom_data
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_ref()
+    }
+}
@@ -0,0 +1,163 @@
+//! Helpers for testing applications built on this crate, enabled by the `test-util` feature.
+//!
+//! Trees are compared through their normalized text: the tree written out as GEDCOM by
+//! `writer::GedcomWriter`, with every xref renumbered in order of first appearance. Records keep
+//! a prefix for their kind, e.g. `@I1@` for the first individual and `@F1@` for the first family,
+//! so two trees which only differ in how their records are numbered, e.g. after a round trip
+//! through another application, normalize to the same text.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{testing, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @FATHER@ INDI\n\
+//!     1 NAME John /Doe/\n\
+//!     1 FAMS @FAMILY@\n\
+//!     0 @FAMILY@ FAM\n\
+//!     1 HUSB @FATHER@\n\
+//!     0 TRLR";
+//! let renumbered = sample.replace("@FATHER@", "@I4@").replace("@FAMILY@", "@F9@");
+//!
+//! let left = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! let right = GedcomDocument::new(renumbered.chars()).parse_document().unwrap();
+//! testing::assert_trees_eq(&left, &right);
+//!
+//! assert_eq!(
+//!     testing::snapshot_record(&right, "@F9@").unwrap(),
+//!     "0 @F1@ FAM\n1 HUSB @I1@\n"
+//! );
+//! ```
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    parse_file,
+    store::Records,
+    util::{remap_xrefs, LineFields},
+    writer::{GedcomWriter, XrefFormat},
+    GedcomData,
+};
+
+/// Reads and parses a fixture file, see `parse_file`.
+///
+/// # Panics
+///
+/// Panics when the file can't be read or parsed, naming the file and the problem.
+#[must_use]
+#[track_caller]
+pub fn load_fixture(path: impl AsRef<Path>) -> GedcomData {
+    let path = path.as_ref();
+    match parse_file(path) {
+        Ok(data) => data,
+        Err(err) => panic!("failed to load fixture {}: {err}", path.display()),
+    }
+}
+
+/// Writes the tree as GEDCOM text with its xrefs renumbered, see the module documentation.
+#[must_use]
+pub fn normalize<R: Records + ?Sized>(data: &R) -> String {
+    let text = write(data);
+    renumber_xrefs(&text, &record_prefixes(&text))
+}
+
+/// Writes the record with the given xref as GEDCOM text, with the xrefs in the record renumbered
+/// starting from the record itself. Returns `None` when the tree has no such record.
+#[must_use]
+pub fn snapshot_record<R: Records + ?Sized>(data: &R, xref: &str) -> Option<String> {
+    let text = write(data);
+    let mut record = String::new();
+    let mut found = false;
+    for line in text.split_inclusive('\n') {
+        let fields = LineFields::split(line);
+        if fields.level == "0" {
+            if found {
+                break;
+            }
+            found = fields.xref == Some(xref);
+        }
+        if found {
+            record.push_str(line);
+        }
+    }
+    if found {
+        Some(renumber_xrefs(&record, &record_prefixes(&text)))
+    } else {
+        None
+    }
+}
+
+/// Asserts that two trees are equal once normalized, see the module documentation.
+///
+/// # Panics
+///
+/// Panics when the trees differ, showing the first line that differs.
+#[track_caller]
+pub fn assert_trees_eq<L, R>(left: &L, right: &R)
+where
+    L: Records + ?Sized,
+    R: Records + ?Sized,
+{
+    let left = normalize(left);
+    let right = normalize(right);
+    if left == right {
+        return;
+    }
+    let mut left_lines = left.lines();
+    let mut right_lines = right.lines();
+    let mut line = 1;
+    loop {
+        match (left_lines.next(), right_lines.next()) {
+            (Some(l), Some(r)) if l == r => line += 1,
+            (l, r) => panic!(
+                "trees differ at line {line} of the normalized text\n  left: {}\n right: {}",
+                l.unwrap_or("<end of tree>"),
+                r.unwrap_or("<end of tree>"),
+            ),
+        }
+    }
+}
+
+fn write<R: Records + ?Sized>(data: &R) -> String {
+    let mut output = Vec::new();
+    GedcomWriter::new(&mut output)
+        .write(data)
+        .expect("writing to memory doesn't fail");
+    String::from_utf8(output).expect("the writer produces UTF-8")
+}
+
+/// The prefix of renumbered xrefs for each record defined in GEDCOM text
+fn record_prefixes(text: &str) -> HashMap<&str, &'static str> {
+    let mut prefixes = HashMap::new();
+    for line in text.lines() {
+        let fields = LineFields::split(line);
+        if let (Some(xref), Some(tag)) = (fields.xref, fields.tag) {
+            prefixes.insert(xref, record_prefix(tag));
+        }
+    }
+    prefixes
+}
+
+/// Renumbers the xrefs of GEDCOM text, both where records are defined and where they are
+/// pointed to, in order of first appearance.
+fn renumber_xrefs(text: &str, prefixes: &HashMap<&str, &'static str>) -> String {
+    let mut xrefs: HashMap<String, String> = HashMap::new();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    remap_xrefs(text, |xref| {
+        let renumbered = xrefs.entry(xref.to_string()).or_insert_with(|| {
+            let prefix = prefixes.get(xref).copied().unwrap_or("X");
+            let count = counts.entry(prefix).or_insert(0);
+            *count += 1;
+            format!("@{prefix}{count}@")
+        });
+        Some(renumbered.clone())
+    })
+}
+
+/// The prefix of renumbered xrefs for records with the given tag, the default of `XrefFormat`
+fn record_prefix(tag: &str) -> &'static str {
+    XrefFormat::default().style(tag).prefix
+}
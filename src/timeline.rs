@@ -0,0 +1,266 @@
+//! A chronology of the dated events of a tree, for timelines and "on this day" views.
+//!
+//! `GedcomData::timeline` collects the events of individuals and families which have a date that
+//! can be interpreted, see `DateValue::parse`, sorted by date. Events dated with a range or period
+//! are placed at its first date, and events with only a year sort before those of the same year
+//! with a month or a day. Filters narrow the timeline down to a date range, a day of the year, or
+//! the family of an individual.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{types::Event, GedcomDocument};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 BIRT\n\
+//!     2 DATE 12 MAR 1850\n\
+//!     2 PLAC Leeds\n\
+//!     1 DEAT\n\
+//!     2 DATE 1921\n\
+//!     1 FAMS @F1@\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Mary /Brown/\n\
+//!     1 BIRT\n\
+//!     2 DATE ABT 1852\n\
+//!     1 FAMS @F1@\n\
+//!     0 @F1@ FAM\n\
+//!     1 HUSB @I1@\n\
+//!     1 WIFE @I2@\n\
+//!     1 MARR\n\
+//!     2 DATE 12 MAR 1875\n\
+//!     0 TRLR";
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//!
+//! let descriptions: Vec<String> = data
+//!     .timeline()
+//!     .entries()
+//!     .into_iter()
+//!     .map(|entry| entry.description)
+//!     .collect();
+//! assert_eq!(
+//!     descriptions,
+//!     vec![
+//!         "Birth of John Smith in Leeds",
+//!         "Birth of Mary Brown",
+//!         "Marriage of John Smith and Mary Brown",
+//!         "Death of John Smith",
+//!     ]
+//! );
+//!
+//! let anniversaries = data.timeline().on_day(3, 12).entries();
+//! assert_eq!(anniversaries.len(), 2);
+//! assert_eq!(anniversaries[1].event.event, Event::Marriage);
+//! assert_eq!(anniversaries[1].xref, Some("@F1@"));
+//!
+//! assert_eq!(data.timeline().between(1900, 1950).entries()[0].event_kind, "Death");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    duplicates::date_part,
+    render::Chart,
+    search::{RecordKind, RecordLocation},
+    types::{DatePart, DateValue, EventDetail, Family, Individual, Name, Pointer},
+    GedcomData,
+};
+
+impl GedcomData {
+    /// Starts a timeline of the dated events of the tree, see the `timeline` module
+    #[must_use]
+    pub fn timeline(&self) -> Timeline<'_> {
+        Timeline {
+            data: self,
+            years: None,
+            day: None,
+            individuals: None,
+        }
+    }
+}
+
+/// `TimelineEntry` is a dated event of an individual or family.
+#[derive(Clone, Debug)]
+pub struct TimelineEntry<'a> {
+    /// When the event happened
+    pub date: DateValue,
+    /// The individual or family the event belongs to
+    pub record: RecordLocation,
+    /// The xref of the individual or family
+    pub xref: Option<&'a str>,
+    /// What the event is, see `EventDetail::kind`, e.g. "Birth"
+    pub event_kind: String,
+    /// A sentence describing the event, e.g. "Birth of John Smith in Leeds"
+    pub description: String,
+    /// The event itself
+    pub event: &'a EventDetail,
+}
+
+/// `Timeline` picks the events of a tree to list, see the `timeline` module.
+#[derive(Clone, Debug)]
+pub struct Timeline<'a> {
+    data: &'a GedcomData,
+    years: Option<(i32, i32)>,
+    day: Option<(u8, u8)>,
+    /// The individuals whose events are listed, along with those of the families they head
+    individuals: Option<HashSet<&'a str>>,
+}
+
+impl<'a> Timeline<'a> {
+    /// Keeps the events of the years `from` to `to`, both included
+    #[must_use]
+    pub fn between(mut self, from: i32, to: i32) -> Self {
+        self.years = Some((from, to));
+        self
+    }
+
+    /// Keeps the events on the day of the year, whatever the year, e.g. for anniversaries.
+    /// Events without a day or month don't match.
+    #[must_use]
+    pub fn on_day(mut self, month: u8, day: u8) -> Self {
+        self.day = Some((month, day));
+        self
+    }
+
+    /// Keeps the events of the individual with the xref and of their descendants, along with the
+    /// events of the families they head, e.g. their marriages
+    #[must_use]
+    pub fn descendants_of(self, xref: &'a str) -> Self {
+        let chart = Chart::descendants(self.data, xref, usize::MAX);
+        self.of_individuals(chart.individuals())
+    }
+
+    /// Keeps the events of the individual with the xref and of their ancestors, along with the
+    /// events of the families they head
+    #[must_use]
+    pub fn ancestors_of(self, xref: &'a str) -> Self {
+        let chart = Chart::ancestors(self.data, xref, usize::MAX);
+        self.of_individuals(chart.individuals())
+    }
+
+    fn of_individuals(mut self, xrefs: &[&'a str]) -> Self {
+        let xrefs = xrefs.iter().copied();
+        self.individuals = Some(match self.individuals.take() {
+            Some(kept) => xrefs.filter(|xref| kept.contains(xref)).collect(),
+            None => xrefs.collect(),
+        });
+        self
+    }
+
+    /// The events picked, sorted by date. Events on the same date are listed in the order of the
+    /// tree, individuals before families.
+    #[must_use]
+    pub fn entries(&self) -> Vec<TimelineEntry<'a>> {
+        let names: HashMap<&str, &Individual> = self
+            .data
+            .individuals
+            .iter()
+            .filter_map(|indi| Some((indi.xref.as_deref()?, indi)))
+            .collect();
+
+        let individuals = self
+            .data
+            .individuals
+            .iter()
+            .enumerate()
+            .filter(|(_, indi)| self.includes(indi.xref.as_deref()))
+            .map(|(index, indi)| {
+                let record = RecordLocation {
+                    kind: RecordKind::Individual,
+                    index,
+                };
+                let name = full_name(Some(indi));
+                (
+                    record,
+                    indi.xref.as_deref(),
+                    name,
+                    indi.events.iter().collect(),
+                )
+            });
+        let families = self
+            .data
+            .families
+            .iter()
+            .enumerate()
+            .filter(|(_, fam)| spouses(fam).any(|spouse| self.includes(Some(spouse))))
+            .map(|(index, fam)| {
+                let record = RecordLocation {
+                    kind: RecordKind::Family,
+                    index,
+                };
+                let spouses: Vec<String> = spouses(fam)
+                    .map(|xref| full_name(names.get(xref).copied()))
+                    .collect();
+                let events: Vec<&EventDetail> =
+                    fam.events.iter().chain(&fam.family_event).collect();
+                (record, fam.xref.as_deref(), spouses.join(" and "), events)
+            });
+
+        let mut entries: Vec<TimelineEntry<'a>> = individuals
+            .chain(families)
+            .flat_map(|(record, xref, names, events)| {
+                events.into_iter().filter_map(move |event| {
+                    let date = event.date.as_ref()?.value_structured()?;
+                    let kind = event.kind();
+                    let mut description = format!("{kind} of {names}");
+                    if let Some(place) = &event.place {
+                        description = format!("{description} in {place}");
+                    }
+                    Some(TimelineEntry {
+                        date,
+                        record,
+                        xref,
+                        event_kind: kind,
+                        description,
+                        event,
+                    })
+                })
+            })
+            .filter(|entry| date_part(&entry.date).is_some_and(|part| self.matches(part)))
+            .collect();
+        // sort_by_key is stable, keeping the order of the tree among equal dates
+        entries.sort_by_key(|entry| date_part(&entry.date).map(sort_key));
+        entries
+    }
+
+    fn includes(&self, xref: Option<&str>) -> bool {
+        match &self.individuals {
+            Some(xrefs) => xref.is_some_and(|xref| xrefs.contains(xref)),
+            None => true,
+        }
+    }
+
+    fn matches(&self, part: &DatePart) -> bool {
+        let in_years = self
+            .years
+            .is_none_or(|(from, to)| (from..=to).contains(&part.year));
+        let on_day = self
+            .day
+            .is_none_or(|day| (part.month, part.day) == (Some(day.0), Some(day.1)));
+        in_years && on_day
+    }
+}
+
+/// Dates order by year, then month and day, a missing month or day coming first
+fn sort_key(part: &DatePart) -> (i32, u8, u8) {
+    (part.year, part.month.unwrap_or(0), part.day.unwrap_or(0))
+}
+
+fn full_name(indi: Option<&Individual>) -> String {
+    indi.and_then(|indi| indi.name.as_ref())
+        .and_then(Name::full_name)
+        .unwrap_or_else(|| "(unnamed)".to_string())
+}
+
+/// The individuals heading a family, leaving out `@VOID@`
+fn spouses(fam: &Family) -> impl Iterator<Item = &str> {
+    fam.individual1
+        .iter()
+        .chain(&fam.individual2)
+        .map(String::as_str)
+        .filter(|xref| *xref != Pointer::VOID)
+}
@@ -1,22 +1,45 @@
 //! Handles the tokenization of a GEDCOM file
-use std::str::Chars;
+//!
+//! Tokens borrow their text from the file contents, so tokenizing doesn't allocate. Parsers copy
+//! a value only once they decide to keep it, e.g. through `Tokenizer::take_line_value`.
+//!
+//! Spaces and tabs before the level number are skipped, so files pretty-printed with each line
+//! indented by its level read the same as flat ones.
+//!
+//! Tools working on the lines of a file rather than its records can read them as `GedcomLine`s
+//! through `Tokenizer::lines`, without the parser.
+use std::{collections::HashMap, convert::TryFrom, fmt, str::Chars, str::FromStr};
+
+use crate::{
+    intern::{SharedStr, StringPool},
+    types::{CustomEventType, CustomTagContext, UserDefinedDataset},
+    util::{is_xref, split_lines_inclusive, unescape_at_signs},
+    CancellationToken, GedcomError, ParseOptions, RecordError, Span,
+};
+
+/// A handler registered with `Tokenizer::on_custom_tag`
+type CustomTagHandler<'a> = Box<dyn FnMut(&CustomTagContext) -> Result<(), GedcomError> + 'a>;
 
 /// The base enum of Token types making use of
 /// [GEDCOM Standard Release 5.5.1](https://edge.fscdn.org/assets/img/documents/ged551-5bac5e57fe88dd37df0e153d9c515335.pdf),
 /// p.11 `gedcom_line: level + delim + [optional_xref_ID] + tag + [optional_line_value] +
 /// terminator`
-#[derive(Clone, Debug, PartialEq)]
-pub enum Token {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token<'a> {
     /// The `level`, denoting the depth within the tree
     Level(u8),
     /// The `tag`, a four character code that distinguishes datatypes
-    Tag(String),
+    Tag(&'a str),
     /// The value of the data: `optional_line_value`
-    LineValue(String),
+    LineValue(&'a str),
     /// The `optional_xref_ID` used throughout the file to refer to a particular face
-    Pointer(String),
+    Pointer(&'a str),
     /// A user-defined tag, always begins with an underscore
-    CustomTag(String),
+    CustomTag(&'a str),
+    /// A line which doesn't follow the `gedcom_line` grammar, e.g. a blank line, one without a
+    /// level or a tag or one with a malformed xref, as its whole text. See
+    /// `Tokenizer::next_raw_token`.
+    Invalid(&'a str),
     /// End-of-file indicator
     EOF,
     /// The initial token value, indicating nothing
@@ -26,24 +49,271 @@ pub enum Token {
 /// The tokenizer that turns the gedcom characters into a list of tokens
 pub struct Tokenizer<'a> {
     /// The active token type
-    pub current_token: Token,
+    pub current_token: Token<'a>,
     /// Current character tokenizer is parsing
     current_char: char,
+    /// The Gedcom file contents tokens borrow from
+    input: &'a str,
     /// An iterator of charaters of the Gedcom file contents
     chars: Chars<'a>,
     /// The current line number of the file we are parsing
     pub line: u32,
+    /// Byte offset of the input within the file, when tokenizing part of a file
+    pub base_offset: usize,
+    /// Byte offset of the start of the current line within the input
+    line_start: usize,
+    /// Location of the current token
+    span: Span,
+    /// Options controlling how problems in the file are handled
+    pub options: ParseOptions,
+    /// Problems recovered from so far, see `ParseOptions::lenient`
+    warnings: Vec<GedcomError>,
+    /// Records skipped so far, see `ParseOptions::skip_broken_records`
+    pub(crate) record_errors: Vec<RecordError>,
+    /// Handlers of user-defined tags, by tag
+    custom_tag_handlers: HashMap<String, CustomTagHandler<'a>>,
+    /// User-defined tags read as events, by tag
+    event_types: HashMap<String, CustomEventType>,
+    /// User-defined tags read as standard ones, by user-defined tag
+    tag_aliases: HashMap<String, TagAlias>,
+    /// xref of the record being parsed
+    pub(crate) record: Option<String>,
+    /// Values shared with `ParseOptions::intern_strings`
+    pub(crate) strings: StringPool,
+    /// Top-level records parsed so far, for `ParseOptions::progress`
+    records_parsed: usize,
+    /// Bytes of input read when `ParseOptions::progress` was last called
+    progress_reported: usize,
+    /// Stops the parse between two records once cancelled, see `GedcomDocument::set_cancellation`
+    pub(crate) cancellation: Option<CancellationToken>,
+    /// The TYPE given by the alias the tag of the current line was read through, see `TagAlias`
+    alias_type: Option<String>,
+}
+
+/// Bytes of input read between two calls of `ParseOptions::progress`
+const PROGRESS_INTERVAL: usize = 64 * 1024;
+
+/// The tags of GEDCOM 5.5.1 and 7, for reading tags written in lower or mixed case
+const STANDARD_TAGS: &[&str] = &[
+    "ABBR", "ADDR", "ADOP", "ADR1", "ADR2", "ADR3", "AFN", "AGE", "AGNC", "ALIA", "ANCE", "ANCI",
+    "ANUL", "ASSO", "AUTH", "BAPL", "BAPM", "BARM", "BASM", "BIRT", "BLES", "BLOB", "BURI", "CALN",
+    "CAST", "CAUS", "CENS", "CHAN", "CHAR", "CHIL", "CHR", "CHRA", "CITY", "CONC", "CONF", "CONL",
+    "CONT", "COPR", "CORP", "CREA", "CREM", "CROP", "CTRY", "DATA", "DATE", "DEAT", "DESC", "DESI",
+    "DEST", "DIV", "DIVF", "DSCR", "EDUC", "EMAIL", "EMIG", "ENDL", "ENGA", "EVEN", "EXID", "FACT",
+    "FAM", "FAMC", "FAMF", "FAMS", "FAX", "FCOM", "FILE", "FONE", "FORM", "GEDC", "GIVN", "GRAD",
+    "HEAD", "HEIGHT", "HUSB", "IDNO", "IMMI", "INDI", "INIL", "LANG", "LATI", "LEFT", "LONG",
+    "MAP", "MARB", "MARC", "MARL", "MARR", "MARS", "MEDI", "MIME", "NAME", "NATI", "NATU", "NCHI",
+    "NICK", "NMR", "NO", "NOTE", "NPFX", "NSFX", "OBJE", "OCCU", "ORDI", "ORDN", "PAGE", "PEDI",
+    "PHON", "PHRASE", "PLAC", "POST", "PROB", "PROP", "PUBL", "QUAY", "REFN", "RELA", "RELI",
+    "REPO", "RESI", "RESN", "RETI", "RFN", "RIN", "ROLE", "ROMN", "SCHMA", "SDATE", "SEX", "SLGC",
+    "SLGS", "SNOTE", "SOUR", "SPFX", "SSN", "STAE", "STAT", "SUBM", "SUBN", "SURN", "TAG", "TEMP",
+    "TEXT", "TIME", "TITL", "TOP", "TRAN", "TRLR", "TYPE", "UID", "VERS", "WIDTH", "WIFE", "WILL",
+    "WWW",
+];
+
+/// Whether an invalid line starts with a level number, i.e. it lacks a tag rather than a level
+fn starts_with_level(line: &str) -> bool {
+    line.trim_start_matches(|c: char| c.is_whitespace() || c == '\u{feff}')
+        .split(|c: char| c.is_whitespace())
+        .next()
+        .is_some_and(|word| word.parse::<u8>().is_ok())
+}
+
+/// The level and the xref of an invalid line whose xref is malformed, e.g. `0 @I1 INDI`
+fn malformed_xref(line: &str) -> Option<(u8, &str)> {
+    let mut words = line
+        .split(|c: char| c.is_whitespace() || c == '\u{feff}')
+        .filter(|word| !word.is_empty());
+    let level = words.next()?.parse::<u8>().ok()?;
+    let xref = words.next().filter(|word| word.starts_with('@'))?;
+    // an xref without a tag after it makes a line without a tag instead
+    words.next()?;
+    Some((level, xref))
+}
+
+/// The standard tag `tag` is written as, ignoring case
+pub(crate) fn standard_tag(tag: &str) -> Option<&'static str> {
+    STANDARD_TAGS
+        .iter()
+        .find(|standard| standard.eq_ignore_ascii_case(tag))
+        .copied()
+}
+
+/// `TagAlias` reads a user-defined tag as a standard one, so that the data of exporters using
+/// their own tag for a standard structure lands in the typed model instead of custom data, see
+/// `GedcomDocument::register_tag_alias`. An alias may also give the TYPE of the structure, e.g. the
+/// `_MARNM` married name of some exporters is a NAME of TYPE married. A TYPE written under the
+/// tag itself takes precedence.
+///
+/// Aliases are read from text as `_MARNM -> NAME(TYPE=married)`.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{tokenizer::TagAlias, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5.1\n\
+///     0 @I1@ INDI\n\
+///     1 NAME Mary /Smith/\n\
+///     1 _MARNM Mary /Jones/\n\
+///     1 BIRT\n\
+///     2 _DATE2 ABT 1850\n\
+///     0 TRLR";
+///
+/// let mut doc = GedcomDocument::new(sample.chars());
+/// doc.register_tag_alias("_MARNM -> NAME(TYPE=married)".parse().unwrap())
+///     .register_tag_alias(TagAlias::new("_DATE2", "DATE").unwrap());
+/// let data = doc.parse_document().unwrap();
+///
+/// let mary = &data.individuals[0];
+/// let birth_name = mary.name.as_ref().unwrap();
+/// assert_eq!(birth_name.value.as_deref(), Some("Mary /Smith/"));
+/// assert_eq!(birth_name.name_type, None);
+/// let married = &mary.alternate_names[0];
+/// assert_eq!(married.value.as_deref(), Some("Mary /Jones/"));
+/// assert_eq!(married.name_type.as_deref(), Some("married"));
+/// let birth = mary.events[0].date.as_ref().unwrap();
+/// assert_eq!(birth.value.as_deref(), Some("ABT 1850"));
+/// assert!(mary.custom_data.is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagAlias {
+    custom_tag: String,
+    tag: &'static str,
+    type_value: Option<String>,
+}
+
+impl TagAlias {
+    /// Reads the user-defined tag `custom_tag` as the standard tag `tag`, e.g. `_DATE2` as DATE
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem when `custom_tag` doesn't start with an underscore, or
+    /// `tag` isn't a standard tag.
+    pub fn new(custom_tag: &str, tag: &str) -> Result<TagAlias, String> {
+        if !custom_tag.starts_with('_') {
+            return Err(format!(
+                "'{custom_tag}' is not a user-defined tag starting with an underscore"
+            ));
+        }
+        let tag = standard_tag(tag).ok_or_else(|| format!("'{tag}' is not a standard tag"))?;
+        Ok(TagAlias {
+            custom_tag: custom_tag.to_string(),
+            tag,
+            type_value: None,
+        })
+    }
+
+    /// Gives the TYPE `type_value` to the structures read through the alias
+    #[must_use]
+    pub fn with_type(mut self, type_value: &str) -> TagAlias {
+        self.type_value = Some(type_value.to_string());
+        self
+    }
+
+    /// The user-defined tag, e.g. `_MARNM`
+    #[must_use]
+    pub fn custom_tag(&self) -> &str {
+        &self.custom_tag
+    }
+
+    /// The standard tag it is read as, e.g. NAME
+    #[must_use]
+    pub fn tag(&self) -> &'static str {
+        self.tag
+    }
+
+    /// The TYPE given to the structures read through the alias, e.g. married
+    #[must_use]
+    pub fn type_value(&self) -> Option<&str> {
+        self.type_value.as_deref()
+    }
+}
+
+impl fmt::Display for TagAlias {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.custom_tag, self.tag)?;
+        match &self.type_value {
+            Some(type_value) => write!(f, "(TYPE={type_value})"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for TagAlias {
+    type Err = String;
+
+    /// Reads an alias written as `_MARNM -> NAME(TYPE=married)` or `_DATE2 -> DATE`
+    fn from_str(text: &str) -> Result<TagAlias, String> {
+        let (custom_tag, target) = text
+            .split_once("->")
+            .ok_or_else(|| format!("expected '_TAG -> TAG' in '{text}'"))?;
+        let target = target.trim();
+        let (tag, type_value) = match target.strip_suffix(')') {
+            Some(target) => {
+                let (tag, qualifier) = target
+                    .split_once('(')
+                    .ok_or_else(|| format!("unbalanced parenthesis in '{text}'"))?;
+                let type_value = qualifier
+                    .trim()
+                    .strip_prefix("TYPE")
+                    .and_then(|rest| rest.trim_start().strip_prefix('='))
+                    .ok_or_else(|| format!("expected '(TYPE=...)' in '{text}'"))?;
+                (tag.trim(), Some(type_value.trim()))
+            }
+            None => (target, None),
+        };
+        let alias = TagAlias::new(custom_tag.trim(), tag)?;
+        Ok(match type_value {
+            Some(type_value) => alias.with_type(type_value),
+            None => alias,
+        })
+    }
 }
 
 impl<'a> Tokenizer<'a> {
     /// Creates a new tokenizer for a char interator of gedcom file contents
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::tokenizer::{Token, Tokenizer};
+    /// let sample = "0 @I1@ INDI\n1 NAME John /Doe/";
+    ///
+    /// let mut tokenizer = Tokenizer::new(sample.chars());
+    /// tokenizer.next_token().unwrap();
+    /// assert_eq!(tokenizer.take_token().unwrap(), Token::Level(0));
+    /// assert_eq!(tokenizer.take_token().unwrap(), Token::Pointer("@I1@"));
+    /// assert_eq!(tokenizer.take_token().unwrap(), Token::Tag("INDI"));
+    /// assert_eq!(tokenizer.take_token().unwrap(), Token::Level(1));
+    /// assert_eq!(tokenizer.take_token().unwrap(), Token::Tag("NAME"));
+    /// assert_eq!(tokenizer.take_token().unwrap(), Token::LineValue("John /Doe/"));
+    /// ```
     #[must_use]
-    pub fn new(chars: Chars<'a>) -> Tokenizer {
+    pub fn new(chars: Chars<'a>) -> Tokenizer<'a> {
         Tokenizer {
             current_char: '\n',
             current_token: Token::None,
+            input: chars.as_str(),
             chars,
             line: 0,
+            base_offset: 0,
+            line_start: 0,
+            span: Span::default(),
+            options: ParseOptions::default(),
+            warnings: Vec::new(),
+            record_errors: Vec::new(),
+            custom_tag_handlers: HashMap::new(),
+            event_types: HashMap::new(),
+            tag_aliases: HashMap::new(),
+            record: None,
+            strings: StringPool::new(),
+            records_parsed: 0,
+            progress_reported: 0,
+            cancellation: None,
+            alias_type: None,
         }
     }
 
@@ -54,30 +324,140 @@ impl<'a> Tokenizer<'a> {
     }
 
     /// Loads the next token into state
-    pub fn next_token(&mut self) {
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::ParseError` when the input doesn't follow the `gedcom_line`
+    /// grammar. In lenient mode a line without a valid level or tag is skipped with a warning
+    /// instead, and so is a line with a malformed xref, along with the lines under it.
+    pub fn next_token(&mut self) -> Result<(), GedcomError> {
+        self.next_raw_token()?;
+        self.skip_invalid_lines()
+    }
+
+    /// Skips past the `Token::Invalid` lines from the current token on, with a warning for each
+    /// in lenient mode, or else fails on the first
+    fn skip_invalid_lines(&mut self) -> Result<(), GedcomError> {
+        while let Token::Invalid(line) = self.current_token {
+            let err = self.invalid_line_error(line);
+            if !self.options.lenient {
+                return Err(err);
+            }
+            self.warn(err);
+            self.next_raw_token()?;
+            // the lines under a line with a malformed xref are skipped along with it, instead of
+            // being read as part of the record before
+            if let Some((level, _)) = malformed_xref(line) {
+                while !matches!(self.current_token, Token::Level(n) if n <= level) && !self.done() {
+                    self.next_raw_token()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The error reported for a `Token::Invalid` line
+    fn invalid_line_error(&self, line: &str) -> GedcomError {
+        if let Some((_, xref)) = malformed_xref(line) {
+            self.parse_error(format!("Malformed xref {xref}"))
+        } else if starts_with_level(line) {
+            self.parse_error("Expected a tag after the level")
+        } else {
+            self.parse_error("Expected a level number at the start of the line")
+        }
+    }
+
+    /// Loads the next token into state like `next_token`, but yields the lines which don't follow
+    /// the `gedcom_line` grammar as `Token::Invalid`, whatever the options. Tools reporting on
+    /// malformed files can read them this way, while the parsers skip them through `next_token`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::ParseError` when a token appears out of place.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::tokenizer::{Token, Tokenizer};
+    /// let sample = "0 HEAD\n\n1\nNAME John\n0 TRLR";
+    ///
+    /// let mut tokenizer = Tokenizer::new(sample.chars());
+    /// let mut tokens = Vec::new();
+    /// while !tokenizer.done() {
+    ///     tokenizer.next_raw_token().unwrap();
+    ///     tokens.push(tokenizer.current_token);
+    /// }
+    /// assert_eq!(
+    ///     tokens,
+    ///     vec![
+    ///         Token::Level(0),
+    ///         Token::Tag("HEAD"),
+    ///         Token::Invalid(""),
+    ///         Token::Invalid("1"),
+    ///         Token::Invalid("NAME John"),
+    ///         Token::Level(0),
+    ///         Token::Tag("TRLR"),
+    ///         Token::EOF,
+    ///     ]
+    /// );
+    /// ```
+    pub fn next_raw_token(&mut self) -> Result<(), GedcomError> {
+        let line_end = self.offset();
         if self.current_char == '\0' {
             self.current_token = Token::EOF;
-            return;
+            self.span = self.span_of(line_end, line_end);
+            return Ok(());
         }
 
-        // level number is at the start of each line.
-        if self.current_char == '\r' {
-            self.next_char();
-        }
-        if self.current_char == '\n' {
-            self.next_char();
+        // level number is at the start of each line, after a CRLF, LF or lone CR terminator
+        if matches!(self.current_char, '\r' | '\n') {
+            self.skip_line_terminator();
+            if self.current_char == '\0' {
+                self.current_token = Token::EOF;
+                self.span = self.span_of(line_end, line_end);
+                return Ok(());
+            }
 
-            self.current_token = Token::Level(self.extract_number());
             self.line += 1;
-            return;
+            self.line_start = self.offset();
+            self.alias_type = None;
+            if let Some(level) = self.extract_number() {
+                if self.line_has_tag() {
+                    self.current_token = Token::Level(level);
+                    return Ok(());
+                }
+            }
+
+            let rest = &self.input[self.line_start..];
+            let line_len = rest.find(['\r', '\n']).unwrap_or(rest.len());
+            self.span = self.span_of(self.line_start, self.line_start + line_len);
+            self.current_token = Token::Invalid(&rest[..line_len]);
+            while !matches!(self.current_char, '\r' | '\n' | '\0') {
+                self.next_char();
+            }
+            return Ok(());
         }
 
+        let delimiter = self.offset();
         self.skip_whitespace();
 
+        // the payload of a continuation starts right after the delimiter, keeping its leading
+        // spaces, which are part of the text split across the lines
+        if let Token::Tag("CONC" | "CONT") = self.current_token {
+            let start = delimiter
+                + self.input[delimiter..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+            if start < self.offset() || !matches!(self.current_char, '\r' | '\n' | '\0') {
+                self.current_token = Token::LineValue(self.extract_value(start));
+                return Ok(());
+            }
+        }
+
         // handle tag with trailing whitespace
-        if self.current_char == '\n' {
-            self.next_token();
-            return;
+        if matches!(self.current_char, '\r' | '\n') {
+            return self.next_raw_token();
         }
 
         self.current_token = match self.current_token {
@@ -85,60 +465,220 @@ impl<'a> Tokenizer<'a> {
                 if self.current_char == '@' {
                     Token::Pointer(self.extract_word())
                 } else if self.current_char == '_' {
-                    Token::CustomTag(self.extract_word())
+                    self.extract_custom_tag()
                 } else {
-                    Token::Tag(self.extract_word())
+                    self.extract_tag()
                 }
             }
-            Token::Pointer(_) => Token::Tag(self.extract_word()),
-            Token::Tag(_) | Token::CustomTag(_) => Token::LineValue(self.extract_value()),
-            _ => panic!(
-                "line {}: Tokenization error! {:?}",
-                self.line, self.current_token
-            ),
+            Token::Pointer(_) if self.current_char == '_' => self.extract_custom_tag(),
+            Token::Pointer(_) => self.extract_tag(),
+            Token::Tag(_) | Token::CustomTag(_) => {
+                Token::LineValue(self.extract_value(self.offset()))
+            }
+            _ => {
+                return Err(
+                    self.parse_error(format!("Tokenization error! {:?}", self.current_token))
+                )
+            }
         };
+        Ok(())
     }
 
     /// Like `next_token`, but returns a clone of the token you are popping.
-    pub fn take_token(&mut self) -> Token {
-        let current_token = self.current_token.clone();
-        self.next_token();
-        return current_token;
+    ///
+    /// # Errors
+    ///
+    /// See `next_token`.
+    pub fn take_token(&mut self) -> Result<Token<'a>, GedcomError> {
+        let current_token = self.current_token;
+        self.next_token()?;
+        Ok(current_token)
+    }
+
+    /// Location of the current token. For a line without a valid level, it covers the whole line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{tokenizer::{Token, Tokenizer}, Span};
+    /// let sample = "0 HEAD\n1 NOTE Søren Kierkegaard";
+    ///
+    /// let mut tokenizer = Tokenizer::new(sample.chars());
+    /// while tokenizer.current_token != Token::Tag("NOTE") {
+    ///     tokenizer.next_token().unwrap();
+    /// }
+    /// tokenizer.next_token().unwrap();
+    /// assert_eq!(tokenizer.current_token, Token::LineValue("Søren Kierkegaard"));
+    /// assert_eq!(tokenizer.span(), Span { line: 2, column: 8, offset: 14, len: 18 });
+    /// ```
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Builds the span of `input[start..end]`, which lies on the current line
+    fn span_of(&self, start: usize, end: usize) -> Span {
+        let column = self.input[self.line_start.min(start)..start]
+            .chars()
+            .count()
+            + 1;
+        Span {
+            line: self.line,
+            column: u32::try_from(column).unwrap_or(u32::MAX),
+            offset: self.base_offset + start,
+            len: end - start,
+        }
+    }
+
+    /// Byte offset of the start of the current line within the input
+    pub(crate) fn line_offset(&self) -> usize {
+        self.line_start
+    }
+
+    /// Counts a top-level record as parsed, calling `ParseOptions::progress` once enough of the
+    /// input was read since the last call
+    pub(crate) fn record_parsed(&mut self) {
+        self.records_parsed += 1;
+        if self.line_start >= self.progress_reported + PROGRESS_INTERVAL {
+            self.report_progress(self.line_start);
+        }
+    }
+
+    /// Calls `ParseOptions::progress` at the end of a document, i.e. at the end of the current
+    /// line
+    pub(crate) fn document_parsed(&mut self) {
+        let end = split_lines_inclusive(&self.input[self.line_start..])
+            .next()
+            .map_or(self.input.len(), |line| self.line_start + line.len());
+        self.report_progress(end);
+    }
+
+    /// Fails with `GedcomError::Cancelled` at the current line once the parse was cancelled, see
+    /// `GedcomDocument::set_cancellation`
+    pub(crate) fn check_cancelled(&self) -> Result<(), GedcomError> {
+        match &self.cancellation {
+            Some(token) if token.is_cancelled() => Err(GedcomError::Cancelled { span: self.span }),
+            _ => Ok(()),
+        }
+    }
+
+    fn report_progress(&mut self, bytes_read: usize) {
+        if let Some(progress) = self.options.progress {
+            progress(bytes_read, self.records_parsed);
+        }
+        self.progress_reported = bytes_read;
     }
 
     fn next_char(&mut self) {
         self.current_char = self.chars.next().unwrap_or('\0');
     }
 
-    fn extract_number(&mut self) -> u8 {
+    /// Moves past the line terminator at `current_char`: a CRLF pair, or a single LF or CR
+    fn skip_line_terminator(&mut self) {
+        let carriage_return = self.current_char == '\r';
+        self.next_char();
+        if carriage_return && self.current_char == '\n' {
+            self.next_char();
+        }
+    }
+
+    /// Byte offset of `current_char` within the input. Before the first line, `current_char` is a
+    /// newline which isn't part of the input, so the offset is 0.
+    fn offset(&self) -> usize {
+        let remaining = self.chars.as_str().len();
+        if self.current_char == '\0' && remaining == 0 {
+            self.input.len()
+        } else {
+            self.input
+                .len()
+                .saturating_sub(remaining + self.current_char.len_utf8())
+        }
+    }
+
+    fn extract_number(&mut self) -> Option<u8> {
         self.skip_whitespace();
-        let mut digits: Vec<char> = Vec::new();
-        while self.current_char.is_digit(10) {
-            digits.push(self.current_char);
+        let start = self.offset();
+        while self.current_char.is_ascii_digit() {
             self.next_char();
         }
 
-        digits.iter().collect::<String>().parse::<u8>().unwrap()
+        self.span = self.span_of(start, self.offset());
+        self.input[start..self.offset()].parse::<u8>().ok()
+    }
+
+    /// Whether the rest of the current line holds a tag, after an optional xref
+    fn line_has_tag(&self) -> bool {
+        let rest = &self.input[self.offset()..];
+        let rest = &rest[..rest.find(['\r', '\n']).unwrap_or(rest.len())];
+        let mut words = rest
+            .split(|c: char| c.is_whitespace() || c == '\u{feff}')
+            .filter(|word| !word.is_empty());
+        match words.next() {
+            Some(word) if word.starts_with('@') => is_xref(word) && words.next().is_some(),
+            Some(_) => true,
+            None => false,
+        }
     }
 
-    fn extract_word(&mut self) -> String {
-        let mut letters: Vec<char> = Vec::new();
+    fn extract_word(&mut self) -> &'a str {
+        let start = self.offset();
         while !self.current_char.is_whitespace() && self.current_char != '\0' {
-            letters.push(self.current_char);
             self.next_char();
         }
 
-        letters.iter().collect::<String>()
+        self.span = self.span_of(start, self.offset());
+        &self.input[start..self.offset()]
+    }
+
+    /// Reads a standard tag, normalizing its case when `ParseOptions::case_insensitive_tags` is
+    /// set
+    fn extract_tag(&mut self) -> Token<'a> {
+        let tag = self.extract_word();
+        if !self.options.case_insensitive_tags || !tag.bytes().any(|b| b.is_ascii_lowercase()) {
+            return Token::Tag(tag);
+        }
+        match standard_tag(tag) {
+            Some(normalized) => {
+                self.warn(GedcomError::MiscasedTag {
+                    span: self.span,
+                    tag: tag.to_string(),
+                    normalized,
+                });
+                Token::Tag(normalized)
+            }
+            None => Token::Tag(tag),
+        }
     }
 
-    fn extract_value(&mut self) -> String {
-        let mut letters: Vec<char> = Vec::new();
-        while self.current_char != '\n' && self.current_char != '\r' {
-            letters.push(self.current_char);
+    /// Reads a user-defined tag, as the standard tag of its alias if one is registered, see
+    /// `GedcomDocument::register_tag_alias`
+    fn extract_custom_tag(&mut self) -> Token<'a> {
+        let tag = self.extract_word();
+        match self.tag_aliases.get(tag) {
+            Some(alias) => {
+                self.alias_type.clone_from(&alias.type_value);
+                Token::Tag(alias.tag)
+            }
+            None => Token::CustomTag(tag),
+        }
+    }
+
+    /// The TYPE given by the alias the tag of the current line was read through, see `TagAlias`.
+    /// Structures with a TYPE take it before reading the rest of their line.
+    pub(crate) fn take_alias_type(&mut self) -> Option<String> {
+        self.alias_type.take()
+    }
+
+    /// The rest of the line from the byte offset `start`, leaving out the line terminator
+    fn extract_value(&mut self, start: usize) -> &'a str {
+        while self.current_char != '\n' && self.current_char != '\r' && self.current_char != '\0'
+        {
             self.next_char();
         }
 
-        letters.iter().collect::<String>()
+        let value = self.input[start..self.offset()].trim_end_matches('\r');
+        self.span = self.span_of(start, start + value.len());
+        value
     }
 
     fn skip_whitespace(&mut self) {
@@ -149,40 +689,280 @@ impl<'a> Tokenizer<'a> {
 
     fn is_nonnewline_whitespace(&self) -> bool {
         let is_zero_width_space = self.current_char as u32 == 65279_u32;
-        let not_a_newline = self.current_char != '\n';
+        let not_a_newline = !matches!(self.current_char, '\r' | '\n');
         (self.current_char.is_whitespace() || is_zero_width_space) && not_a_newline
     }
 
     /// Debug function displaying GEDCOM line number of error message.
+    #[must_use]
     pub fn debug(&self) -> String {
         format!("line {}:", self.line)
     }
 
-    /// Grabs and returns to the end of the current line as a String
-    pub fn take_line_value(&mut self) -> String {
+    /// Builds a `GedcomError::ParseError` at the current token
+    #[must_use]
+    pub fn parse_error(&self, message: impl Into<String>) -> GedcomError {
+        GedcomError::ParseError {
+            span: self.span,
+            message: message.into(),
+        }
+    }
+
+    /// Builds a `GedcomError::UnhandledTag` for the current token
+    #[must_use]
+    pub fn unhandled_tag(&self, structure: &'static str, tag: &str) -> GedcomError {
+        GedcomError::UnhandledTag {
+            span: self.span,
+            structure,
+            tag: tag.to_string(),
+        }
+    }
+
+    /// Builds a `GedcomError::InvalidValue` for the current token
+    #[must_use]
+    pub fn invalid_value(&self, tag: &'static str, value: &str) -> GedcomError {
+        GedcomError::InvalidValue {
+            span: self.span,
+            tag,
+            value: value.to_string(),
+        }
+    }
+
+    /// Records a problem which doesn't prevent parsing from continuing. With the `log` feature it
+    /// is also logged as a warning, along with the xref of the record being parsed.
+    pub fn warn(&mut self, warning: GedcomError) {
+        #[cfg(feature = "log")]
+        match &self.record {
+            Some(xref) => log::warn!(target: "gedcom", "{warning} (in record {xref})"),
+            None => log::warn!(target: "gedcom", "{warning}"),
+        }
+        self.warnings.push(warning);
+    }
+
+    /// Problems recovered from so far
+    #[must_use]
+    pub fn warnings(&self) -> &[GedcomError] {
+        &self.warnings
+    }
+
+    /// Handles an error raised while parsing a structure at `level`. In lenient mode the error is
+    /// recorded as a warning and the rest of the structure is skipped, otherwise it is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `err` in strict mode, or any error raised while skipping the structure.
+    pub fn recover(&mut self, err: GedcomError, level: u8) -> Result<(), GedcomError> {
+        if !self.options.lenient {
+            return Err(err);
+        }
+        self.warn(err);
+        self.skip_subtree(level)
+    }
+
+    /// Skips tokens until the next line at `level` or above, or the end of the file.
+    ///
+    /// # Errors
+    ///
+    /// See `next_token`.
+    pub fn skip_subtree(&mut self, level: u8) -> Result<(), GedcomError> {
+        loop {
+            match self.current_token {
+                Token::Level(current) if current <= level => return Ok(()),
+                Token::EOF => return Ok(()),
+                _ => self.next_token()?,
+            }
+        }
+    }
+
+    /// Skips to the next line at `level` or above, or the end of the file, like `skip_subtree`,
+    /// but passing over the lines which can't be tokenized, so that parsing can resume at the next
+    /// record whatever went wrong within the one skipped.
+    pub(crate) fn skip_to_level(&mut self, level: u8) {
+        loop {
+            match self.current_token {
+                Token::Level(current) if current <= level => return,
+                Token::EOF => return,
+                _ => {
+                    if self.next_raw_token().is_err() {
+                        // carry on from the start of the next line
+                        while !matches!(self.current_char, '\r' | '\n' | '\0') {
+                            self.next_char();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Registers a user-defined tag to be read as an event, see
+    /// `GedcomDocument::register_event_type`. It replaces any type registered for the tag before.
+    pub fn register_event_type(&mut self, event_type: CustomEventType) {
+        self.event_types.insert(event_type.tag.clone(), event_type);
+    }
+
+    /// Registers an alias reading a user-defined tag as a standard one, see
+    /// `GedcomDocument::register_tag_alias`. It replaces any alias registered for the tag before.
+    pub fn register_tag_alias(&mut self, alias: TagAlias) {
+        self.tag_aliases.insert(alias.custom_tag.clone(), alias);
+    }
+
+    /// The event type registered for the user-defined tag `tag`, if any
+    #[must_use]
+    pub fn event_type(&self, tag: &str) -> Option<&CustomEventType> {
+        self.event_types.get(tag)
+    }
+
+    /// Registers a handler called with each occurrence of the user-defined tag `tag`, once its
+    /// subtree is parsed, see `GedcomDocument::on_custom_tag`. It replaces any handler registered
+    /// for the tag before.
+    pub fn on_custom_tag<F>(&mut self, tag: &str, handler: F)
+    where
+        F: FnMut(&CustomTagContext) -> Result<(), GedcomError> + 'a,
+    {
+        self.custom_tag_handlers
+            .insert(tag.to_string(), Box::new(handler));
+    }
+
+    /// Parses the user-defined tag of the current line, at `level`, and passes it to the handler
+    /// registered for it, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors raised while parsing the subtree of the tag, or by its handler. In
+    /// lenient mode an error raised by the handler is recorded as a warning instead.
+    pub fn take_custom_tag(
+        &mut self,
+        level: u8,
+        tag: &str,
+    ) -> Result<UserDefinedDataset, GedcomError> {
+        let span = self.span;
+        let dataset = UserDefinedDataset::new(self, level, tag)?;
+        if let Some(handler) = self.custom_tag_handlers.get_mut(tag) {
+            let context = CustomTagContext {
+                dataset: &dataset,
+                record: self.record.as_deref(),
+                level,
+                span,
+            };
+            if let Err(err) = handler(&context) {
+                if !self.options.lenient {
+                    return Err(err);
+                }
+                self.warn(err);
+            }
+        }
+        Ok(dataset)
+    }
+
+    /// Skips the substructures left below a line at `level` once its value has been taken,
+    /// warning with a `GedcomError::DroppedSubstructure` for each child of `parent`, the tag of the
+    /// line.
+    ///
+    /// # Errors
+    ///
+    /// See `next_token`.
+    pub fn drop_substructures(&mut self, parent: &str, level: u8) -> Result<(), GedcomError> {
+        while let Token::Level(current) = self.current_token {
+            if current <= level {
+                break;
+            }
+            self.next_token()?;
+            if let Token::Tag(tag) | Token::CustomTag(tag) = self.current_token {
+                if current == level + 1 {
+                    self.warn(GedcomError::DroppedSubstructure {
+                        span: self.span,
+                        parent: parent.to_string(),
+                        tag: tag.to_string(),
+                    });
+                }
+            }
+            self.skip_subtree(level + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Grabs and returns to the end of the current line as a String, reading the `@@` escape as a
+    /// single at-sign
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::ParseError` if the current line has no value to take.
+    pub fn take_line_value(&mut self) -> Result<String, GedcomError> {
+        Ok(self.take_line_value_with_span()?.0)
+    }
+
+    /// Like `take_line_value`, for values repeated throughout files such as places, which are
+    /// taken from the string pool with `ParseOptions::intern_strings`, see the `intern` module.
+    ///
+    /// # Errors
+    ///
+    /// See `take_line_value`.
+    pub fn take_shared_value(&mut self) -> Result<SharedStr, GedcomError> {
+        let value = self.take_line_value()?;
+        Ok(if self.options.intern_strings {
+            self.strings.intern(&value)
+        } else {
+            SharedStr::from(value)
+        })
+    }
+
+    /// Like `take_line_value`, but also returns the location of the value, or of the tag when the
+    /// line has no value.
+    ///
+    /// # Errors
+    ///
+    /// See `take_line_value`.
+    pub fn take_line_value_with_span(&mut self) -> Result<(String, Span), GedcomError> {
         let mut value = String::from("");
-        self.next_token();
+        let mut span = self.span;
+        self.next_token()?;
 
-        match &self.current_token {
+        match self.current_token {
             Token::LineValue(val) => {
-                value = val.to_string();
-                self.next_token();
+                value = unescape_at_signs(val).into_owned();
+                span = self.span;
+                self.next_token()?;
             }
             // gracefully handle an attempt to take a value from a valueless line
-            Token::Level(_) => (),
-            _ => panic!(
-                "{} Expected LineValue, found {:?}",
-                self.debug(),
-                self.current_token
-            ),
+            Token::Level(_) | Token::EOF => (),
+            _ => {
+                return Err(self.parse_error(format!(
+                    "Expected LineValue, found {:?}",
+                    self.current_token
+                )))
+            }
         }
-        value
+        Ok((value, span))
+    }
+
+    /// Takes the value of the current line and converts it with `from_code`, for payloads drawn
+    /// from a set of enumerated values.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::InvalidValue` when `from_code` doesn't recognize the value.
+    pub fn take_enumerated<T>(
+        &mut self,
+        tag: &'static str,
+        from_code: impl FnOnce(&str) -> Option<T>,
+    ) -> Result<T, GedcomError> {
+        let (value, span) = self.take_line_value_with_span()?;
+        from_code(&value).ok_or(GedcomError::InvalidValue { span, tag, value })
     }
 
     /// Takes the value of the current line including handling
     /// multi-line values from CONT & CONC tags.
-    pub fn take_continued_text(&mut self, level: u8) -> String {
-        let mut value = self.take_line_value();
+    ///
+    /// A CONT line starts a new line of the text, while a CONC line is joined to the previous one
+    /// as it is, without inserting a space. The leading spaces of continuation lines are kept, so
+    /// a text split in the middle of a run of spaces reads back the same.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GedcomError::UnhandledTag` if the text has substructures other than CONT or
+    /// CONC.
+    pub fn take_continued_text(&mut self, level: u8) -> Result<String, GedcomError> {
+        let mut value = self.take_line_value()?;
 
         loop {
             if let Token::Level(cur_level) = self.current_token {
@@ -190,26 +970,141 @@ impl<'a> Tokenizer<'a> {
                     break;
                 }
             }
-            match &self.current_token {
-                Token::Tag(tag) => match tag.as_str() {
+            match self.current_token {
+                Token::Tag(tag) => match tag {
                     "CONT" => {
                         value.push('\n');
-                        value.push_str(&self.take_line_value())
-                    }
-                    "CONC" => {
-                        // value.push(' ');
-                        value.push_str(&self.take_line_value())
+                        value.push_str(&self.take_line_value()?)
                     }
-                    _ => panic!("{} Unhandled Continuation Tag: {}", self.debug(), tag),
+                    "CONC" => value.push_str(&self.take_line_value()?),
+                    _ => return Err(self.unhandled_tag("Continuation", tag)),
                 },
-                Token::Level(_) => self.next_token(),
-                _ => panic!(
-                    "{} Unhandled Continuation Token: {:?}",
-                    self.debug(),
-                    self.current_token
-                ),
+                Token::Level(_) => self.next_token()?,
+                Token::EOF => break,
+                _ => {
+                    return Err(self.parse_error(format!(
+                        "Unhandled Continuation Token: {:?}",
+                        self.current_token
+                    )))
+                }
             }
         }
-        value
+        Ok(value)
+    }
+}
+
+/// `GedcomLine` is a single `gedcom_line` of a file, see `Tokenizer::lines`. Its parts borrow from
+/// the file contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GedcomLine<'a> {
+    /// The depth of the line within its record, 0 for a record
+    pub level: u8,
+    /// The `optional_xref_ID` defined by the line, e.g. `@I1@`
+    pub xref: Option<&'a str>,
+    /// The tag, standard or user-defined
+    pub tag: &'a str,
+    /// The `optional_line_value`, which may be a pointer
+    pub value: Option<&'a str>,
+    /// The line number within the file, counting from 1
+    pub line: u32,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Reads the rest of the input line by line, for tools which work on the raw line structure,
+    /// e.g. linters, formatters and converters, without building the tree. Continuation lines are
+    /// returned as they are, and the lines aren't checked against the GEDCOM grammar beyond their
+    /// `gedcom_line` syntax.
+    ///
+    /// The iteration stops at the first error. In lenient mode, lines without a valid level or tag
+    /// are skipped instead and recorded in `warnings`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::tokenizer::{GedcomLine, Tokenizer};
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John /Doe/\n\
+    ///     1 _MILT Army\n\
+    ///     0 TRLR";
+    ///
+    /// let mut tokenizer = Tokenizer::new(sample.chars());
+    /// let lines: Vec<GedcomLine> = tokenizer.lines().collect::<Result<_, _>>().unwrap();
+    /// assert_eq!(lines.len(), 7);
+    /// assert_eq!(
+    ///     lines[3],
+    ///     GedcomLine { level: 0, xref: Some("@I1@"), tag: "INDI", value: None, line: 4 }
+    /// );
+    /// assert_eq!((lines[5].tag, lines[5].value), ("_MILT", Some("Army")));
+    /// ```
+    pub fn lines(&mut self) -> Lines<'_, 'a> {
+        Lines {
+            tokenizer: self,
+            done: false,
+        }
+    }
+
+    /// Reads the next line, or `None` at the end of the input
+    fn next_line(&mut self) -> Result<Option<GedcomLine<'a>>, GedcomError> {
+        if self.current_token == Token::None {
+            self.next_raw_token()?;
+        }
+        self.skip_invalid_lines()?;
+        let level = match self.current_token {
+            Token::Level(level) => level,
+            Token::EOF => return Ok(None),
+            token => return Err(self.parse_error(format!("Expected a level, found {token:?}"))),
+        };
+        let line = self.line;
+        self.next_raw_token()?;
+        let xref = match self.current_token {
+            Token::Pointer(xref) => {
+                self.next_raw_token()?;
+                Some(xref)
+            }
+            _ => None,
+        };
+        let (Token::Tag(tag) | Token::CustomTag(tag)) = self.current_token else {
+            return Err(self.parse_error("Expected a tag after the level"));
+        };
+        self.next_raw_token()?;
+        let value = match self.current_token {
+            Token::LineValue(value) => {
+                self.next_raw_token()?;
+                Some(value)
+            }
+            _ => None,
+        };
+        Ok(Some(GedcomLine {
+            level,
+            xref,
+            tag,
+            value,
+            line,
+        }))
+    }
+}
+
+/// `Lines` reads the lines of a file one at a time, see `Tokenizer::lines`.
+pub struct Lines<'t, 'a> {
+    tokenizer: &'t mut Tokenizer<'a>,
+    done: bool,
+}
+
+impl<'a> Iterator for Lines<'_, 'a> {
+    type Item = Result<GedcomLine<'a>, GedcomError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let line = self.tokenizer.next_line();
+        if !matches!(line, Ok(Some(_))) {
+            self.done = true;
+        }
+        line.transpose()
     }
 }
@@ -5,12 +5,13 @@ use std::fmt;
 use crate::{
     parse_subset,
     tokenizer::{Token, Tokenizer},
-    types::UserDefinedDataset,
-    Parser,
+    types::{Note, UserDefinedDataset},
+    util::unescape_at_signs,
+    GedcomError, Parser,
 };
 
 /// Physical address at which a fact occurs
-#[derive(Default)]
+#[derive(Clone, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Address {
     pub value: Option<String>,
@@ -21,51 +22,107 @@ pub struct Address {
     pub state: Option<String>,
     pub post: Option<String>,
     pub country: Option<String>,
+    /// tag: NOTE, not allowed by the specification but written by some applications
+    pub notes: Vec<Note>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Address {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Address {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Address, GedcomError> {
         let mut addr = Address::default();
-        addr.parse(tokenizer, level);
-        addr
+        addr.parse(tokenizer, level)?;
+        Ok(addr)
     }
 }
 
 impl Parser for Address {
     /// parse handles ADDR tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip ADDR tag
-        tokenizer.next_token();
+        tokenizer.next_token()?;
 
         let mut value = String::new();
 
         // handle value on ADDR line
-        if let Token::LineValue(addr) = &tokenizer.current_token {
-            value.push_str(&addr);
-            tokenizer.next_token();
+        if let Token::LineValue(addr) = tokenizer.current_token {
+            value.push_str(&unescape_at_signs(addr));
+            tokenizer.next_token()?;
         }
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "CONT" | "CONC" => {
-                value.push('\n');
-                value.push_str(&tokenizer.take_line_value());
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "CONT" => {
+                    value.push('\n');
+                    value.push_str(&tokenizer.take_line_value()?);
+                }
+                "CONC" => value.push_str(&tokenizer.take_line_value()?),
+                "ADR1" => self.adr1 = Some(tokenizer.take_line_value()?),
+                "ADR2" => self.adr2 = Some(tokenizer.take_line_value()?),
+                "ADR3" => self.adr3 = Some(tokenizer.take_line_value()?),
+                "CITY" => self.city = Some(tokenizer.take_line_value()?),
+                "STAE" => self.state = Some(tokenizer.take_line_value()?),
+                "POST" => self.post = Some(tokenizer.take_line_value()?),
+                "CTRY" => self.country = Some(tokenizer.take_line_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Address", tag)),
             }
-            "ADR1" => self.adr1 = Some(tokenizer.take_line_value()),
-            "ADR2" => self.adr2 = Some(tokenizer.take_line_value()),
-            "ADR3" => self.adr3 = Some(tokenizer.take_line_value()),
-            "CITY" => self.city = Some(tokenizer.take_line_value()),
-            "STAE" => self.state = Some(tokenizer.take_line_value()),
-            "POST" => self.post = Some(tokenizer.take_line_value()),
-            "CTRY" => self.country = Some(tokenizer.take_line_value()),
-            _ => panic!("{} Unhandled Address Tag: {}", tokenizer.debug(), tag),
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
 
         if &value != "" {
             self.value = Some(value);
         }
+        Ok(())
+    }
+}
+
+/// `ContactInfo` gathers the address of a structure with the phone numbers, emails, faxes and
+/// websites given alongside it. These tags are siblings of ADDR in the specification, and each
+/// may appear several times, up to three times in GEDCOM 5.5.1. All of the values are kept, in
+/// the order of the file.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct ContactInfo {
+    /// tag: ADDR
+    pub address: Option<Address>,
+    /// tag: PHON
+    pub phones: Vec<String>,
+    /// tag: EMAIL
+    pub emails: Vec<String>,
+    /// tag: FAX
+    pub faxes: Vec<String>,
+    /// tag: WWW
+    pub websites: Vec<String>,
+}
+
+impl ContactInfo {
+    /// Whether the structure gave no contact details at all
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.address.is_none()
+            && self.phones.is_empty()
+            && self.emails.is_empty()
+            && self.faxes.is_empty()
+            && self.websites.is_empty()
+    }
+
+    /// Parses one of the ADDR, PHON, EMAIL, FAX and WWW tags found at `level`
+    pub(crate) fn parse_tag(
+        &mut self,
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        tag: &str,
+    ) -> Result<(), GedcomError> {
+        match tag {
+            "ADDR" => self.address = Some(Address::new(tokenizer, level)?),
+            "PHON" => self.phones.push(tokenizer.take_line_value()?),
+            "EMAIL" => self.emails.push(tokenizer.take_line_value()?),
+            "FAX" => self.faxes.push(tokenizer.take_line_value()?),
+            "WWW" => self.websites.push(tokenizer.take_line_value()?),
+            _ => return Err(tokenizer.unhandled_tag("ContactInfo", tag)),
+        }
+        Ok(())
     }
 }
 
@@ -81,6 +138,9 @@ impl fmt::Debug for Address {
         fmt_optional_value!(debug, "state", &self.state);
         fmt_optional_value!(debug, "post", &self.post);
         fmt_optional_value!(debug, "country", &self.country);
+        if !self.notes.is_empty() {
+            debug.field("notes", &self.notes);
+        }
 
         debug.finish()
     }
@@ -0,0 +1,169 @@
+use std::{convert::TryFrom, fmt};
+
+use crate::{
+    duplicates::date_part,
+    types::{date::days_in_month, DateValue},
+};
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// `AgeBound` tells that an `Age` is a bound rather than the age itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum AgeBound {
+    /// `<`, younger than the age
+    Less,
+    /// `>`, older than the age
+    Greater,
+}
+
+/// `Age` is the age of an individual, read from an AGE payload such as ">30y 6m" or computed
+/// from dates by `Age::between` and `Individual::age_at`. The parts missing from the payload, or
+/// beyond the precision of the dates, are `None`. See GEDCOM 5.5.1 specification, p. 42.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::types::{Age, AgeBound, DateValue};
+///
+/// let age = Age::parse("> 30y 6m").unwrap();
+/// assert_eq!(age.bound, Some(AgeBound::Greater));
+/// assert_eq!((age.years, age.months, age.days), (Some(30), Some(6), None));
+/// assert_eq!(age.to_string(), ">30y 6m");
+///
+/// let birth = DateValue::parse("2 FEB 1832").unwrap();
+/// let marriage = DateValue::parse("ABT 1 JAN 1855").unwrap();
+/// let age = Age::between(&birth, &marriage).unwrap();
+/// assert_eq!(age.to_string(), "22y 10m 30d");
+/// assert!(age.approximate);
+///
+/// // only the years are known from a year
+/// let census = DateValue::parse("1861").unwrap();
+/// assert_eq!(Age::between(&birth, &census).unwrap().to_string(), "29y");
+/// // an event before the birth has no age
+/// assert_eq!(Age::between(&marriage, &birth), None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Age {
+    pub bound: Option<AgeBound>,
+    pub years: Option<u32>,
+    pub months: Option<u32>,
+    /// Days, including the weeks of a GEDCOM 7 payload such as "2w"
+    pub days: Option<u32>,
+    /// Whether the age was computed from approximate dates, e.g. ABT or BET dates, or a baptism
+    /// standing in for a birth
+    pub approximate: bool,
+}
+
+impl Age {
+    /// Reads an AGE payload, e.g. "<8y" or "3m 12d", along with the keywords CHILD (younger than
+    /// 8 years), INFANT (younger than a year) and STILLBORN. GEDCOM 7 puts a space after the
+    /// bound, which is accepted too. Returns `None` when the value doesn't follow the AGE
+    /// grammar.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Age> {
+        let value = value.trim();
+        let under = |years| Age {
+            bound: Some(AgeBound::Less),
+            years: Some(years),
+            ..Age::default()
+        };
+        match value.to_uppercase().as_str() {
+            "CHILD" => return Some(under(8)),
+            "INFANT" => return Some(under(1)),
+            "STILLBORN" => {
+                return Some(Age {
+                    years: Some(0),
+                    ..Age::default()
+                })
+            }
+            _ => {}
+        }
+
+        let (bound, value) = match value.chars().next()? {
+            '<' => (Some(AgeBound::Less), &value[1..]),
+            '>' => (Some(AgeBound::Greater), &value[1..]),
+            _ => (None, value),
+        };
+        let mut age = Age {
+            bound,
+            ..Age::default()
+        };
+        let mut units = value.split_whitespace().peekable();
+        units.peek()?;
+        for unit in units {
+            let suffix = unit.chars().last()?;
+            let number: u32 = unit[..unit.len() - suffix.len_utf8()].parse().ok()?;
+            let (field, number) = match suffix.to_ascii_lowercase() {
+                'y' => (&mut age.years, number),
+                'm' => (&mut age.months, number),
+                'w' => (&mut age.days, number * 7),
+                'd' => (&mut age.days, number),
+                _ => return None,
+            };
+            *field = Some(field.unwrap_or_default() + number);
+        }
+        Some(age)
+    }
+
+    /// The age at `to` of someone born at `from`, down to the days when both dates have them.
+    /// Ages computed from dates without a day or month have no days or months, and may be one
+    /// more than the true age. Returns `None` when either date has no calendar date, or `to` is
+    /// before `from`.
+    #[must_use]
+    pub fn between(from: &DateValue, to: &DateValue) -> Option<Age> {
+        let (start, end) = (date_part(from)?, date_part(to)?);
+        let mut years = i64::from(end.year) - i64::from(start.year);
+        let mut months = None;
+        let mut days = None;
+        if let (Some(start_month), Some(end_month)) = (start.month, end.month) {
+            let mut month_count = i64::from(end_month) - i64::from(start_month);
+            if let (Some(start_day), Some(end_day)) = (start.day, end.day) {
+                let mut day_count = i64::from(end_day) - i64::from(start_day);
+                if day_count < 0 {
+                    month_count -= 1;
+                    let (month, year) = match end_month {
+                        1 => (12, end.year - 1),
+                        month => (month - 1, end.year),
+                    };
+                    day_count += i64::from(days_in_month(month, year));
+                }
+                days = Some(day_count);
+            }
+            if month_count < 0 {
+                years -= 1;
+                month_count += 12;
+            }
+            months = Some(month_count);
+        }
+        if years < 0 {
+            return None;
+        }
+        let exact = |date: &DateValue| matches!(date, DateValue::Exact(_));
+        Some(Age {
+            bound: None,
+            years: u32::try_from(years).ok(),
+            months: months.and_then(|months| u32::try_from(months).ok()),
+            days: days.and_then(|days| u32::try_from(days).ok()),
+            approximate: !exact(from) || !exact(to),
+        })
+    }
+}
+
+impl fmt::Display for Age {
+    /// Formats the age as a GEDCOM 5.5.1 AGE payload, e.g. ">30y 6m"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bound {
+            Some(AgeBound::Less) => f.write_str("<")?,
+            Some(AgeBound::Greater) => f.write_str(">")?,
+            None => {}
+        }
+        let parts: Vec<String> = [(self.years, 'y'), (self.months, 'm'), (self.days, 'd')]
+            .iter()
+            .filter_map(|(number, unit)| Some(format!("{}{unit}", (*number)?)))
+            .collect();
+        f.write_str(&parts.join(" "))
+    }
+}
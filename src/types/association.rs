@@ -0,0 +1,177 @@
+use crate::{
+    parse_subset,
+    tokenizer::{Token, Tokenizer},
+    types::{Note, Pointer, SourceCitation, UserDefinedDataset},
+    util::unescape_at_signs,
+    GedcomError, Parser,
+};
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Association (tag: ASSO) links an event to another person who took part in it, e.g. a witness
+/// or godparent, along with their role. See GEDCOM 5.5.1 specification, p. 33.
+///
+/// Vendors record the same thing under their own tags: `WITN` and `_WITN` for witnesses, and
+/// `_SHAR` for a person sharing the event (The Master Genealogist, Legacy). These are read into
+/// an `Association` as well, keeping the tag they came from. Associations from custom tags are
+/// listed after those from standard tags.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 BAPM\n\
+///     2 ASSO @I2@\n\
+///     3 RELA Godfather\n\
+///     2 WITN\n\
+///     3 NAME Mary Smith\n\
+///     2 _WITN @I3@\n\
+///     0 TRLR";
+///
+/// let mut doc = GedcomDocument::new(sample.chars());
+/// let data = doc.parse_document().unwrap();
+///
+/// let associations = &data.individuals[0].events[0].associations;
+/// assert_eq!(associations.len(), 3);
+/// assert_eq!(associations[0].xref(), Some("@I2@"));
+/// assert_eq!(associations[0].role.as_deref(), Some("Godfather"));
+/// assert!(!associations[0].is_witness());
+///
+/// assert_eq!(associations[1].name.as_deref(), Some("Mary Smith"));
+/// assert_eq!(associations[1].xref(), None);
+///
+/// assert_eq!(associations[2].tag, "_WITN");
+/// assert_eq!(associations[2].xref(), Some("@I3@"));
+/// assert!(associations[2].is_witness());
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Association {
+    /// The tag the association was read from: ASSO, WITN, `_WITN`, `_SHAR` or `_ASSO`
+    pub tag: String,
+    /// The payload of the tag, usually a pointer to the associated individual
+    pub value: Option<String>,
+    /// tag: NAME, the name of an associated person who has no record of their own
+    pub name: Option<String>,
+    /// tag: RELA, or ROLE as used by vendors, e.g. "Witness" or "Godfather"
+    pub role: Option<String>,
+    pub notes: Vec<Note>,
+    pub citations: Vec<SourceCitation>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl Association {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        tag: &str,
+    ) -> Result<Association, GedcomError> {
+        let mut asso = Association::with_tag(tag);
+        asso.parse(tokenizer, level)?;
+        Ok(asso)
+    }
+
+    /// Reads a vendor association, e.g. `_SHAR`, from the custom tag it was parsed as.
+    #[must_use]
+    pub fn from_custom(custom: &UserDefinedDataset) -> Association {
+        let mut asso = Association::with_tag(&custom.tag);
+        asso.value.clone_from(&custom.value);
+        for child in &custom.children {
+            match child.tag.as_str() {
+                "NAME" => asso.name = Some(child.value.clone().unwrap_or_default()),
+                "RELA" | "ROLE" => asso.role = Some(child.value.clone().unwrap_or_default()),
+                "NOTE" => {
+                    asso.notes.push(Note {
+                        value: Some(continued_text(child)),
+                        ..Note::default()
+                    });
+                }
+                _ => asso.custom_data.push(child.clone()),
+            }
+        }
+        asso
+    }
+
+    /// Whether `tag` is a vendor tag read as an association
+    #[must_use]
+    pub fn is_custom_tag(tag: &str) -> bool {
+        matches!(tag, "_WITN" | "_SHAR" | "_ASSO")
+    }
+
+    /// The xref of the associated individual, if the payload is a pointer other than `@VOID@`
+    #[must_use]
+    pub fn xref(&self) -> Option<&str> {
+        self.value
+            .as_deref()
+            .filter(|value| value.starts_with('@') && value.ends_with('@'))
+            .filter(|value| *value != Pointer::VOID)
+    }
+
+    /// Whether the associated person witnessed the event, either because of the tag used or
+    /// their role.
+    #[must_use]
+    pub fn is_witness(&self) -> bool {
+        matches!(self.tag.as_str(), "WITN" | "_WITN")
+            || self
+                .role
+                .as_deref()
+                .is_some_and(|role| role.eq_ignore_ascii_case("witness"))
+    }
+
+    fn with_tag(tag: &str) -> Association {
+        Association {
+            tag: tag.to_string(),
+            value: None,
+            name: None,
+            role: None,
+            notes: Vec::new(),
+            citations: Vec::new(),
+            custom_data: Vec::new(),
+        }
+    }
+}
+
+impl Parser for Association {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
+        if let Token::LineValue(val) = tokenizer.current_token {
+            self.value = Some(unescape_at_signs(val).into_owned());
+            tokenizer.next_token()?;
+        }
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "NAME" => self.name = Some(tokenizer.take_line_value()?),
+                "RELA" | "ROLE" => self.role = Some(tokenizer.take_line_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                "SOUR" => SourceCitation::parse_into(&mut self.citations, tokenizer, level + 1)?,
+                _ => return Err(tokenizer.unhandled_tag("Association", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
+
+/// The value of a custom tag joined with its CONT and CONC children
+fn continued_text(custom: &UserDefinedDataset) -> String {
+    let mut text = custom.value.clone().unwrap_or_default();
+    for child in &custom.children {
+        match child.tag.as_str() {
+            "CONT" => {
+                text.push('\n');
+                text.push_str(child.value.as_deref().unwrap_or(""));
+            }
+            "CONC" => text.push_str(child.value.as_deref().unwrap_or("")),
+            _ => (),
+        }
+    }
+    text
+}
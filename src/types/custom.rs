@@ -1,8 +1,13 @@
 use crate::{
+    parse_subset,
     tokenizer::{Token, Tokenizer},
-    Parser,
+    util::unescape_at_signs,
+    GedcomError, Parser, Span,
 };
 
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
 /// UserDefinedData handles User Defined Data. See Gedcom 5.5 spec, p.56
 ///
 /// ```
@@ -23,7 +28,7 @@ use crate::{
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let custom = &data.individuals[0].custom_data;
 /// assert_eq!(custom.len(), 1);
@@ -45,72 +50,233 @@ use crate::{
 /// assert_eq!(cs_sour_page.tag, "PAGE");
 /// assert_eq!(cs_sour_page.value.as_ref().unwrap(), "New York State Archives; Albany, New York; Collection: New York, New York National Guard Service Cards, 1917-1954; Series: Xxxxx; Film Number: Xx");
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct UserDefinedDataset {
+    /// The xref of a user-defined record, e.g. `0 @P1@ _PLAC`, or of a substructure defining one
+    pub xref: Option<String>,
     pub tag: String,
     pub value: Option<String>,
+    /// The substructures, in file order, whatever their tag, including CONT and CONC lines
     pub children: Vec<Box<UserDefinedDataset>>,
 }
 
 impl UserDefinedDataset {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, tag: &str) -> UserDefinedDataset {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        tag: &str,
+    ) -> Result<UserDefinedDataset, GedcomError> {
         let mut udd = UserDefinedDataset {
+            xref: None,
             tag: tag.to_string(),
             value: None,
             children: Vec::new(),
         };
-        udd.parse(tokenizer, level);
-        udd
+        udd.parse(tokenizer, level)?;
+        Ok(udd)
     }
 
     pub fn add_child(&mut self, child: UserDefinedDataset) {
         self.children.push(Box::new(child));
     }
+
+    /// The first substructure with the given tag
+    #[must_use]
+    pub fn child(&self, tag: &str) -> Option<&UserDefinedDataset> {
+        self.children
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|child| child.tag == tag)
+    }
+}
+
+/// `CustomTagContext` is a user-defined tag found while parsing, as passed to the handlers
+/// registered with `GedcomDocument::on_custom_tag`.
+#[derive(Clone, Copy, Debug)]
+pub struct CustomTagContext<'c> {
+    /// The tag along with its value and substructures
+    pub dataset: &'c UserDefinedDataset,
+    /// The xref of the record the tag belongs to, e.g. `@I1@`, or `None` for the header and
+    /// records without one
+    pub record: Option<&'c str>,
+    /// The level of the tag line, 0 for a top-level record
+    pub level: u8,
+    /// Location of the tag
+    pub span: Span,
 }
 
 impl Parser for UserDefinedDataset {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip ahead of initial tag
-        tokenizer.next_token();
+        tokenizer.next_token()?;
+        if let Token::LineValue(val) = tokenizer.current_token {
+            self.value = Some(unescape_at_signs(val).into_owned());
+            tokenizer.next_token()?;
+        }
 
-        let mut has_child = false;
-        loop {
-            if let Token::Level(current) = tokenizer.current_token {
-                if current <= level {
-                    break;
+        // the whole subtree is kept, each substructure being parsed at the level it is written at
+        while let Token::Level(current) = tokenizer.current_token {
+            if current <= level {
+                break;
+            }
+            tokenizer.next_token()?;
+            let xref = match tokenizer.current_token {
+                Token::Pointer(xref) => {
+                    let xref = xref.to_string();
+                    tokenizer.next_token()?;
+                    Some(xref)
                 }
-                if current > level {
-                    has_child = true;
+                _ => None,
+            };
+            let (Token::Tag(tag) | Token::CustomTag(tag)) = tokenizer.current_token else {
+                return Err(tokenizer.parse_error(format!(
+                    "Unhandled Token in UserDefinedDataset: {:?}",
+                    tokenizer.current_token
+                )));
+            };
+            let mut child = UserDefinedDataset::new(tokenizer, current, tag)?;
+            child.xref = xref;
+            self.add_child(child);
+        }
+        Ok(())
+    }
+}
+
+/// `ExternalIdKind` is the service or application which gave an `ExternalId`, by the tag recording
+/// it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum ExternalIdKind {
+    /// tag: `_FSFTID`, the person id of the `FamilySearch` Family Tree, e.g. "KWCB-6VN"
+    FamilySearch,
+    /// tag: `_APID`, an Ancestry record id, e.g. `1,7602::2771226`
+    Ancestry,
+    /// tag: `_UID`, a unique id assigned by the application, e.g. by Legacy or `RootsMagic`
+    Uid,
+    /// tag: `_GUID`, a globally unique id assigned by the application
+    Guid,
+    /// tag: EXID, an id given by the authority named in `ExternalId::id_type`, from GEDCOM 7
+    Exid,
+}
+
+impl ExternalIdKind {
+    /// The kind recorded by the tag, if it is one of the vendor id tags. EXID structures are read
+    /// by the records themselves.
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<ExternalIdKind> {
+        match tag {
+            "_FSFTID" => Some(ExternalIdKind::FamilySearch),
+            "_APID" => Some(ExternalIdKind::Ancestry),
+            "_UID" => Some(ExternalIdKind::Uid),
+            "_GUID" => Some(ExternalIdKind::Guid),
+            _ => None,
+        }
+    }
+
+    /// The vendor tag recording ids of the kind
+    #[must_use]
+    pub fn tag(&self) -> &'static str {
+        match self {
+            ExternalIdKind::FamilySearch => "_FSFTID",
+            ExternalIdKind::Ancestry => "_APID",
+            ExternalIdKind::Uid => "_UID",
+            ExternalIdKind::Guid => "_GUID",
+            ExternalIdKind::Exid => "EXID",
+        }
+    }
+}
+
+/// `ExternalId` is the id of a record at another service or application, read from a GEDCOM 7
+/// EXID structure or a vendor tag such as `_FSFTID`. Matching on them lets sync tools pair up the
+/// persons of trees exported from different services.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{types::ExternalIdKind, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME George /Washington/\n\
+///     1 _FSFTID KNDX-MKG\n\
+///     1 _UID 2B7D51F7A6C14BFE9F6C2B0B3A6C4E7D1A2B\n\
+///     1 _MILT Army\n\
+///     1 EXID 123\n\
+///     2 TYPE https://example.com/person/\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let george = &data.individuals[0];
+/// assert_eq!(george.external_ids.len(), 3);
+/// assert_eq!(george.external_id(ExternalIdKind::FamilySearch), Some("KNDX-MKG"));
+/// let exid = &george.external_ids[0];
+/// assert_eq!(exid.value, "123");
+/// assert_eq!(exid.id_type.as_deref(), Some("https://example.com/person/"));
+/// assert_eq!(george.external_id(ExternalIdKind::Ancestry), None);
+/// assert_eq!(george.custom_data.len(), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct ExternalId {
+    pub kind: ExternalIdKind,
+    pub value: String,
+    /// tag: TYPE, the URI of the authority which gave an EXID
+    pub id_type: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl ExternalId {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<ExternalId, GedcomError> {
+        let mut exid = ExternalId {
+            kind: ExternalIdKind::Exid,
+            value: String::new(),
+            id_type: None,
+            custom_data: Vec::new(),
+        };
+        exid.parse(tokenizer, level)?;
+        Ok(exid)
+    }
+
+    /// Moves the vendor ids out of the custom data of a record. Tags with an empty payload or
+    /// substructures of their own stay custom data, so that they are written back as they were.
+    #[allow(clippy::vec_box)]
+    pub(crate) fn take_from(custom_data: &mut Vec<Box<UserDefinedDataset>>) -> Vec<ExternalId> {
+        let mut ids = Vec::new();
+        custom_data.retain(|custom| {
+            let kind = ExternalIdKind::from_tag(&custom.tag);
+            match (kind, &custom.value) {
+                (Some(kind), Some(value)) if !value.is_empty() && custom.children.is_empty() => {
+                    ids.push(ExternalId {
+                        kind,
+                        value: value.clone(),
+                        id_type: None,
+                        custom_data: Vec::new(),
+                    });
+                    false
                 }
+                _ => true,
             }
+        });
+        ids
+    }
+}
 
-            match &tokenizer.current_token {
-                Token::Tag(tag) => {
-                    if has_child {
-                        let tag_clone = tag.clone();
-                        self.add_child(UserDefinedDataset::new(tokenizer, level + 1, &tag_clone))
-                    }
-                }
-                Token::CustomTag(tag) => {
-                    if has_child {
-                        let tag_clone = tag.clone();
-                        self.add_child(UserDefinedDataset::new(tokenizer, level + 1, &tag_clone))
-                    }
-                }
-                Token::LineValue(val) => {
-                    self.value = Some(val.to_string());
-                    tokenizer.next_token();
-                }
-                Token::Level(_) => tokenizer.next_token(),
-                Token::EOF => break,
-                _ => panic!(
-                    "{}, Unhandled Token in UserDefinedDataset: {:?}",
-                    tokenizer.debug(),
-                    tokenizer.current_token
-                ),
+impl Parser for ExternalId {
+    /// parse handles the EXID tag
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = tokenizer.take_line_value()?;
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "TYPE" => self.id_type = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("ExternalId", tag)),
             }
-        }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
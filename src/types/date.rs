@@ -1,12 +1,13 @@
 use crate::{
     parse_subset,
     tokenizer::Tokenizer,
-    types::Note,
-    Parser,
+    types::{Note, UserDefinedDataset},
+    GedcomError, Parser,
 };
 
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, convert::TryFrom, fmt};
 
 /// Date encompasses a number of date formats, e.g. approximated, period, phrase and range.
 ///
@@ -30,7 +31,7 @@ use serde::{Deserialize, Serialize};
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let head_date = data.header.unwrap().date.unwrap();
 /// assert_eq!(head_date.value.unwrap(), "2 Oct 2019");
@@ -46,14 +47,79 @@ use serde::{Deserialize, Serialize};
 pub struct Date {
     pub value: Option<String>,
     pub time: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Date {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Date {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Date, GedcomError> {
         let mut date = Date::default();
-        date.parse(tokenizer, level);
-        date
+        date.parse(tokenizer, level)?;
+        Ok(date)
+    }
+
+    /// Interprets the date payload, see `DateValue`.
+    #[must_use]
+    pub fn value_structured(&self) -> Option<DateValue> {
+        DateValue::parse(self.value.as_ref()?)
+    }
+
+    /// Interprets the TIME substructure, see `Time`.
+    #[must_use]
+    pub fn time_structured(&self) -> Option<Time> {
+        Time::parse(self.time.as_ref()?)
+    }
+
+    /// The date and time as a `chrono::NaiveDateTime`, e.g. to order files by the timestamp of
+    /// their header, enabled with the `"chrono"` feature. Only exact Gregorian dates with a day
+    /// and a month convert. A date without a TIME is taken at midnight. The time is naive: check
+    /// `Time::utc` to tell UTC times from local ones.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     1 DATE 2 OCT 2019\n\
+    ///     2 TIME 14:05:09.25\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let date = data.header.unwrap().date.unwrap();
+    /// assert_eq!(
+    ///     date.to_naive_datetime().unwrap().to_string(),
+    ///     "2019-10-02 14:05:09.250"
+    /// );
+    /// ```
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn to_naive_datetime(&self) -> Option<chrono::NaiveDateTime> {
+        let DateValue::Exact(part) = self.value_structured()? else {
+            return None;
+        };
+        if part.calendar != Calendar::Gregorian {
+            return None;
+        }
+        let date = chrono::NaiveDate::from_ymd_opt(
+            part.year,
+            u32::from(part.month?),
+            u32::from(part.day?),
+        )?;
+        let time = match &self.time {
+            Some(time) => {
+                let time = Time::parse(time)?;
+                chrono::NaiveTime::from_hms_nano_opt(
+                    u32::from(time.hour),
+                    u32::from(time.minute),
+                    u32::from(time.second.unwrap_or_default()),
+                    time.nanosecond,
+                )?
+            }
+            None => chrono::NaiveTime::MIN,
+        };
+        Some(date.and_time(time))
     }
 
     /// datetime returns Date and Date.time in a single string.
@@ -73,20 +139,35 @@ impl Date {
 
 impl Parser for Date {
     /// parse handles the DATE tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "TIME" => self.time = Some(tokenizer.take_line_value()),
-            _ => panic!("{} unhandled DATE tag: {}", tokenizer.debug(), tag),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "TIME" => {
+                    let (time, span) = tokenizer.take_line_value_with_span()?;
+                    if Time::parse(&time).is_none() {
+                        tokenizer.warn(GedcomError::InvalidValue {
+                            span,
+                            tag: "TIME",
+                            value: time.clone(),
+                        });
+                    }
+                    self.time = Some(time);
+                }
+                _ => return Err(tokenizer.unhandled_tag("DATE", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
 /// ChangeDate is intended to only record the last change to a record. Some systems may want to
 /// manage the change process with more detail, but it is sufficient for GEDCOM purposes to
-/// indicate the last time that a record was modified.
+/// indicate the last time that a record was modified. The creation date of a record (tag: CREA),
+/// from GEDCOM 7, has the same structure without the note.
 ///
 /// # Example
 /// ```
@@ -105,7 +186,7 @@ impl Parser for Date {
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 /// assert_eq!(data.multimedia.len(), 1);
 ///
 /// let obje = &data.multimedia[0];
@@ -115,34 +196,717 @@ impl Parser for Date {
 /// assert_eq!(date.value.as_ref().unwrap(), "1 APR 1998");
 /// assert_eq!(date.time.as_ref().unwrap(), "12:34:56.789");
 ///
-/// let chan_note = chan.note.as_ref().unwrap();
+/// let chan_note = &chan.notes[0];
 /// assert_eq!(chan_note.value.as_ref().unwrap(), "A note");
 /// ```
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct ChangeDate {
     pub date: Option<Date>,
-    pub note: Option<Note>,
+    pub notes: Vec<Note>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl ChangeDate {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> ChangeDate {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<ChangeDate, GedcomError> {
         let mut date = ChangeDate::default();
-        date.parse(tokenizer, level);
-        date
+        date.parse(tokenizer, level)?;
+        Ok(date)
     }
 }
 
 impl Parser for ChangeDate {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "DATE" => self.date = Some(Date::new(tokenizer, level + 1)),
-            "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-            _ => panic!("{} unhandled ChangeDate tag: {}", tokenizer.debug(), tag),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("ChangeDate", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
+
+/// `Time` is the time of day of a date, read from its TIME substructure, e.g. "12:34:56.789".
+/// The seconds and their fraction are optional, and GEDCOM 7 marks times in UTC with a trailing
+/// `Z`. See Gedcom 5.5.1 specification, p. 59.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::types::Time;
+///
+/// let time = Time::parse("9:05:30.25").unwrap();
+/// assert_eq!((time.hour, time.minute, time.second), (9, 5, Some(30)));
+/// assert_eq!(time.nanosecond, 250_000_000);
+/// assert_eq!(time.to_string(), "09:05:30.25");
+///
+/// assert!(Time::parse("12:00Z").unwrap().utc);
+/// assert_eq!(Time::parse("24:00"), None);
+/// assert_eq!(Time::parse("12:60:00"), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Time {
+    /// hour of the day, 0-23
+    pub hour: u8,
+    /// minute of the hour, 0-59
+    pub minute: u8,
+    /// second of the minute, 0-59
+    pub second: Option<u8>,
+    /// fraction of the second, in nanoseconds
+    pub nanosecond: u32,
+    /// whether the time is in UTC, rather than in a local time zone the file doesn't give
+    pub utc: bool,
+}
+
+impl Time {
+    /// parse reads a TIME payload, e.g. "12:34:56.789" or "12:34Z", returning `None` when it
+    /// doesn't follow the grammar or a field is out of range. The hour may be written with a
+    /// single digit, as some applications do.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Time> {
+        let value = value.trim();
+        let (value, utc) = match value.strip_suffix(['Z', 'z']) {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        let mut fields = value.split(':');
+        let hour = number(fields.next()?, 1..=2, 23)?;
+        let minute = number(fields.next()?, 2..=2, 59)?;
+        let (second, nanosecond) = match fields.next() {
+            Some(second) => {
+                let (second, fraction) = match second.split_once('.') {
+                    Some((second, fraction)) => (second, Some(fraction)),
+                    None => (second, None),
+                };
+                let nanosecond = match fraction {
+                    Some(fraction) => {
+                        if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                            return None;
+                        }
+                        // nanoseconds are the first 9 digits, padded with zeros
+                        let digits: String =
+                            fraction.chars().chain("00000000".chars()).take(9).collect();
+                        digits.parse().ok()?
+                    }
+                    None => 0,
+                };
+                (Some(number(second, 2..=2, 59)?), nanosecond)
+            }
+            None => (None, 0),
+        };
+        if fields.next().is_some() {
+            return None;
+        }
+        Some(Time {
+            hour,
+            minute,
+            second,
+            nanosecond,
+            utc,
+        })
+    }
+}
+
+/// Reads a field of a time, with a number of digits in `digits` and a value up to `max`
+fn number(field: &str, digits: std::ops::RangeInclusive<usize>, max: u8) -> Option<u8> {
+    if !digits.contains(&field.len()) || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    field.parse().ok().filter(|number| *number <= max)
+}
+
+impl fmt::Display for Time {
+    /// Formats the time with two digits per field, e.g. "09:05:30.25", leaving out the trailing
+    /// zeros of the fraction
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)?;
+        if let Some(second) = self.second {
+            write!(f, ":{second:02}")?;
+            if self.nanosecond > 0 {
+                let fraction = format!("{:09}", self.nanosecond);
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+        if self.utc {
+            write!(f, "Z")?;
+        }
+        Ok(())
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+const MONTH_NAMES: [&str; 12] = [
+    "JANUARY",
+    "FEBRUARY",
+    "MARCH",
+    "APRIL",
+    "MAY",
+    "JUNE",
+    "JULY",
+    "AUGUST",
+    "SEPTEMBER",
+    "OCTOBER",
+    "NOVEMBER",
+    "DECEMBER",
+];
+
+const HEBREW_MONTHS: [&str; 13] = [
+    "TSH", "CSH", "KSL", "TVT", "SHV", "ADR", "ADS", "NSN", "IYR", "SVN", "TMZ", "AAV", "ELL",
+];
+
+const FRENCH_MONTHS: [&str; 13] = [
+    "VEND", "BRUM", "FRIM", "NIVO", "PLUV", "VENT", "GERM", "FLOR", "PRAI", "MESS", "THER", "FRUC",
+    "COMP",
+];
+
+/// `Calendar` is the calendar a date is written in, given by a calendar escape before the date,
+/// e.g. "@#DJULIAN@ 1 JAN 1700", or by the GEDCOM 7 calendar keyword, e.g. "JULIAN 1 JAN 1700".
+/// Dates without one are Gregorian. See Gedcom 5.5.1 specification, p. 45.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum Calendar {
+    /// @#DGREGORIAN@, the calendar of dates without an escape
+    #[default]
+    Gregorian,
+    /// @#DJULIAN@
+    Julian,
+    /// @#DHEBREW@, with the months TSH, CSH, KSL, TVT, SHV, ADR, ADS, NSN, IYR, SVN, TMZ, AAV and
+    /// ELL
+    Hebrew,
+    /// @#DFRENCH R@, the French Republican calendar, with the months VEND, BRUM, FRIM, NIVO, PLUV,
+    /// VENT, GERM, FLOR, PRAI, MESS, THER, FRUC and COMP
+    French,
+    /// @#DROMAN@, reserved by the specification for future use
+    Roman,
+    /// @#DUNKNOWN@, a calendar which is not known
+    Unknown,
+}
+
+impl Calendar {
+    /// The calendar escape written before dates in this calendar, e.g. `@#DJULIAN@`
+    #[must_use]
+    pub fn escape(self) -> &'static str {
+        match self {
+            Calendar::Gregorian => "@#DGREGORIAN@",
+            Calendar::Julian => "@#DJULIAN@",
+            Calendar::Hebrew => "@#DHEBREW@",
+            Calendar::French => "@#DFRENCH R@",
+            Calendar::Roman => "@#DROMAN@",
+            Calendar::Unknown => "@#DUNKNOWN@",
+        }
+    }
+
+    /// The GEDCOM 7 keyword naming the calendar, e.g. `JULIAN`, for the calendars GEDCOM 7 has
+    #[must_use]
+    pub fn keyword(self) -> Option<&'static str> {
+        match self {
+            Calendar::Gregorian => Some("GREGORIAN"),
+            Calendar::Julian => Some("JULIAN"),
+            Calendar::Hebrew => Some("HEBREW"),
+            Calendar::French => Some("FRENCH_R"),
+            Calendar::Roman | Calendar::Unknown => None,
+        }
+    }
+
+    /// Reads a calendar escape, e.g. `@#DJULIAN@`, or a GEDCOM 7 calendar keyword, e.g.
+    /// `JULIAN` or `FRENCH_R`, case-insensitively
+    #[must_use]
+    pub fn parse(word: &str) -> Option<Calendar> {
+        let word = word.to_uppercase();
+        let name = match word.strip_prefix("@#D") {
+            Some(escape) => escape.strip_suffix('@')?,
+            None => &word,
+        };
+        match name {
+            "GREGORIAN" => Some(Calendar::Gregorian),
+            "JULIAN" => Some(Calendar::Julian),
+            "HEBREW" => Some(Calendar::Hebrew),
+            "FRENCH R" | "FRENCH_R" => Some(Calendar::French),
+            "ROMAN" => Some(Calendar::Roman),
+            "UNKNOWN" => Some(Calendar::Unknown),
+            _ => None,
+        }
+    }
+
+    /// The abbreviated names of the months of the calendar
+    fn months(self) -> &'static [&'static str] {
+        match self {
+            Calendar::Hebrew => &HEBREW_MONTHS,
+            Calendar::French => &FRENCH_MONTHS,
+            _ => &MONTHS,
+        }
+    }
+
+    /// The number of days in the month of the year, for a day to be valid
+    fn days_in_month(self, month: u8, year: i32) -> u8 {
+        match self {
+            Calendar::Julian if month == 2 && year % 4 == 0 => 29,
+            Calendar::French if month == 13 => 6,
+            Calendar::Hebrew | Calendar::French => 30,
+            _ => days_in_month(month, year),
+        }
+    }
+}
+
+/// `DatePart` is a single calendar date within a date payload, e.g. the "2 FEB 1832" in
+/// "BEF 2 FEB 1832". The day and month are optional, as GEDCOM allows dates such as "1832" or
+/// "FEB 1832". See Gedcom 5.5.1 specification, p. 45.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct DatePart {
+    /// day of the month, 1-31
+    pub day: Option<u8>,
+    /// month of the year, starting at 1, e.g. 1-13 in the Hebrew calendar
+    pub month: Option<u8>,
+    /// year in the calendar, e.g. 1832 or 5600 in the Hebrew calendar
+    pub year: i32,
+    /// the calendar the date is written in
+    pub calendar: Calendar,
+}
+
+impl DatePart {
+    /// parse reads a date from its whitespace-separated words, accepting the canonical GEDCOM
+    /// order ("2 FEB 1832"), month-first ("Feb 2, 1832"), full month names, and ISO 8601
+    /// ("1832-02-02"). The date may start with a calendar escape or keyword, see `Calendar`.
+    #[must_use]
+    pub fn parse(words: &[&str]) -> Option<DatePart> {
+        let (calendar, words) = match words
+            .split_first()
+            .and_then(|(first, rest)| Some((Calendar::parse(first)?, rest)))
+        {
+            Some(calendar) => calendar,
+            None => (Calendar::Gregorian, words),
+        };
+        let part = match words {
+            [single] => DatePart {
+                calendar,
+                ..Self::parse_single(single)?
+            },
+            [month, year] => DatePart {
+                day: None,
+                month: Some(month_number(month, calendar)?),
+                year: parse_year(year)?,
+                calendar,
+            },
+            [first, second, year] => {
+                let (day, month) = match (first.parse::<u8>(), second.parse::<u8>()) {
+                    (Ok(day), Err(_)) => (day, month_number(second, calendar)?),
+                    (Err(_), Ok(day)) => (day, month_number(first, calendar)?),
+                    _ => return None,
+                };
+                DatePart {
+                    day: Some(day),
+                    month: Some(month),
+                    year: parse_year(year)?,
+                    calendar,
+                }
+            }
+            _ => return None,
+        };
+
+        if part.is_valid() {
+            Some(part)
+        } else {
+            None
+        }
+    }
+
+    fn parse_single(word: &str) -> Option<DatePart> {
+        if let Some(year) = parse_year(word) {
+            return Some(DatePart {
+                day: None,
+                month: None,
+                year,
+                calendar: Calendar::Gregorian,
+            });
+        }
+
+        // ISO 8601: YYYY-MM or YYYY-MM-DD
+        let pieces: Vec<&str> = word.split('-').collect();
+        match pieces.as_slice() {
+            [year, month] if year.len() == 4 => Some(DatePart {
+                day: None,
+                month: Some(month.parse().ok()?),
+                year: year.parse().ok()?,
+                calendar: Calendar::Gregorian,
+            }),
+            [year, month, day] if year.len() == 4 => Some(DatePart {
+                day: Some(day.parse().ok()?),
+                month: Some(month.parse().ok()?),
+                year: year.parse().ok()?,
+                calendar: Calendar::Gregorian,
+            }),
+            _ => None,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let months = 1..=self.calendar.months().len();
+        match (self.day, self.month) {
+            (None, None) => true,
+            (None, Some(month)) => months.contains(&usize::from(month)),
+            (Some(day), Some(month)) => {
+                months.contains(&usize::from(month))
+                    && day >= 1
+                    && day <= self.calendar.days_in_month(month, self.year)
+            }
+            (Some(_), None) => false,
+        }
+    }
+}
+
+impl fmt::Display for DatePart {
+    /// Formats the date in canonical GEDCOM form, e.g. "2 FEB 1832", or "@#DJULIAN@ 2 FEB 1832"
+    /// in a calendar other than the Gregorian calendar. A month outside the calendar is written
+    /// as its number.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.calendar != Calendar::Gregorian {
+            write!(f, "{} ", self.calendar.escape())?;
+        }
+        if let Some(day) = self.day {
+            write!(f, "{day} ")?;
+        }
+        if let Some(month) = self.month {
+            let months = self.calendar.months();
+            match month
+                .checked_sub(1)
+                .and_then(|i| months.get(usize::from(i)))
+            {
+                Some(name) => write!(f, "{name} ")?,
+                None => write!(f, "{month} ")?,
+            }
+        }
+        write!(f, "{}", self.year)
+    }
+}
+
+/// `DateValue` is the structured interpretation of a DATE payload, covering the approximated,
+/// range, period and phrase forms of the GEDCOM date grammar. See Gedcom 5.5.1 specification,
+/// p. 45.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::types::{Calendar, DatePart, DateValue};
+///
+/// let date = DateValue::parse("bet. Feb 2, 1832 and 1840").unwrap();
+/// assert_eq!(
+///     date,
+///     DateValue::Between(
+///         DatePart { day: Some(2), month: Some(2), year: 1832, calendar: Calendar::Gregorian },
+///         DatePart { day: None, month: None, year: 1840, calendar: Calendar::Gregorian },
+///     )
+/// );
+/// assert_eq!(date.to_string(), "BET 2 FEB 1832 AND 1840");
+///
+/// let date = DateValue::parse("ABT @#DHEBREW@ 15 NSN 5600").unwrap();
+/// assert_eq!(
+///     date,
+///     DateValue::About(DatePart {
+///         day: Some(15),
+///         month: Some(8),
+///         year: 5600,
+///         calendar: Calendar::Hebrew,
+///     })
+/// );
+/// assert_eq!(date.to_string(), "ABT @#DHEBREW@ 15 NSN 5600");
+///
+/// assert!(DateValue::parse("sometime in spring").is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum DateValue {
+    /// A single known date
+    Exact(DatePart),
+    /// ABT; about, meaning the date is not exact
+    About(DatePart),
+    /// CAL; calculated mathematically, for example, from an event date and age
+    Calculated(DatePart),
+    /// EST; estimated based on an algorithm using some other event date
+    Estimated(DatePart),
+    /// BEF; event happened before the given date
+    Before(DatePart),
+    /// AFT; event happened after the given date
+    After(DatePart),
+    /// BET date AND date; event happened some time between the two dates
+    Between(DatePart, DatePart),
+    /// FROM date; the beginning of a period
+    From(DatePart),
+    /// TO date; the end of a period
+    To(DatePart),
+    /// FROM date TO date; a period spanning both dates
+    FromTo(DatePart, DatePart),
+    /// INT date (phrase); a date interpreted from the given phrase
+    Interpreted(DatePart, String),
+    /// (phrase); free text which could not be interpreted as a date
+    Phrase(String),
+}
+
+impl DateValue {
+    /// parse interprets a DATE payload, returning `None` when the value doesn't match any of the
+    /// date forms. Keywords and month names are case-insensitive and may be abbreviated or
+    /// spelled out, so "abt. February 1832" is read the same as "ABT FEB 1832".
+    #[must_use]
+    pub fn parse(value: &str) -> Option<DateValue> {
+        let value = value.trim();
+        if value.starts_with('(') && value.ends_with(')') {
+            return Some(DateValue::Phrase(value[1..value.len() - 1].to_string()));
+        }
+
+        let (value, phrase) = match value.find('(') {
+            Some(i) if value.ends_with(')') => (
+                value[..i].trim(),
+                Some(value[i + 1..value.len() - 1].to_string()),
+            ),
+            _ => (value, None),
+        };
+
+        // the escape of the French Republican calendar is the only one holding a space
+        let value = value.to_uppercase().replace("@#DFRENCH R@", "@#DFRENCH_R@");
+        let words: Vec<String> = value
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| !w.is_empty())
+            .map(|w| w.trim_end_matches('.').to_uppercase())
+            .collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        let (keyword, rest) = match words.split_first() {
+            Some((first, rest)) => (*first, rest),
+            None => return None,
+        };
+
+        let date = match keyword {
+            "ABT" | "ABOUT" | "CIRCA" | "CA" | "C" | "APPROX" => {
+                DateValue::About(DatePart::parse(rest)?)
+            }
+            "CAL" | "CALCULATED" => DateValue::Calculated(DatePart::parse(rest)?),
+            "EST" | "ESTIMATED" => DateValue::Estimated(DatePart::parse(rest)?),
+            "BEF" | "BEFORE" => DateValue::Before(DatePart::parse(rest)?),
+            "AFT" | "AFTER" => DateValue::After(DatePart::parse(rest)?),
+            "BET" | "BETWEEN" => {
+                let (start, end) = split_at_keyword(rest, &["AND", "-"])?;
+                DateValue::Between(DatePart::parse(start)?, DatePart::parse(end)?)
+            }
+            "FROM" => match split_at_keyword(rest, &["TO"]) {
+                Some((start, end)) => {
+                    DateValue::FromTo(DatePart::parse(start)?, DatePart::parse(end)?)
+                }
+                None => DateValue::From(DatePart::parse(rest)?),
+            },
+            "TO" => DateValue::To(DatePart::parse(rest)?),
+            "INT" => DateValue::Interpreted(DatePart::parse(rest)?, phrase.clone()?),
+            _ => DateValue::Exact(DatePart::parse(&words)?),
+        };
+
+        match (&date, phrase) {
+            (DateValue::Interpreted(..), _) | (_, None) => Some(date),
+            _ => None,
+        }
+    }
+}
+
+impl DateValue {
+    /// A key ordering dates chronologically, for sorting events and individuals. The order is
+    /// total and deterministic:
+    ///
+    /// - dates compare by the earliest day they may stand for, the first date of a `BET` range or
+    ///   `FROM` period, with missing components taken from the start of the period: "1832" sorts
+    ///   before "FEB 1832", before "2 FEB 1832"
+    /// - `AFT` dates and open-ended `FROM` periods start after the period they name, with missing
+    ///   components taken from its end: "AFT 1832" sorts after "31 DEC 1832", before "1833"
+    /// - among dates starting on the same day, `BEF` and `TO` come first, then exact,
+    ///   interpreted, calculated, estimated and approximate (`ABT`) dates, then ranges and
+    ///   periods, ending earliest first, then open-ended `FROM` periods, and `AFT` dates last
+    /// - phrases which could not be interpreted as dates sort after all others, by their text
+    /// - dates are not converted between calendars, so a Hebrew or French Republican date compares
+    ///   by the year, month and day written
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::types::DateValue;
+    ///
+    /// let mut dates: Vec<DateValue> = [
+    ///     "(unknown)",
+    ///     "AFT 1832",
+    ///     "2 FEB 1832",
+    ///     "ABT 1832",
+    ///     "BET 1830 AND 1835",
+    ///     "1832",
+    ///     "BEF 1832",
+    ///     "FEB 1832",
+    /// ]
+    /// .iter()
+    /// .map(|date| DateValue::parse(date).unwrap())
+    /// .collect();
+    /// dates.sort_by_key(DateValue::sort_key);
+    ///
+    /// let sorted: Vec<String> = dates.iter().map(ToString::to_string).collect();
+    /// assert_eq!(
+    ///     sorted,
+    ///     vec![
+    ///         "BET 1830 AND 1835",
+    ///         "BEF 1832",
+    ///         "1832",
+    ///         "ABT 1832",
+    ///         "FEB 1832",
+    ///         "2 FEB 1832",
+    ///         "AFT 1832",
+    ///         "(unknown)",
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn sort_key(&self) -> DateSortKey {
+        let (start, end) = match self {
+            DateValue::Between(start, end) | DateValue::FromTo(start, end) => {
+                (start.start_key(), end.end_key())
+            }
+            // open-ended after the period they name
+            DateValue::After(part) | DateValue::From(part) => (part.end_key(), part.end_key()),
+            DateValue::Exact(part)
+            | DateValue::About(part)
+            | DateValue::Calculated(part)
+            | DateValue::Estimated(part)
+            | DateValue::Before(part)
+            | DateValue::To(part)
+            | DateValue::Interpreted(part, _) => (part.start_key(), part.end_key()),
+            DateValue::Phrase(phrase) => {
+                return DateSortKey {
+                    undated: true,
+                    start: (0, 0, 0),
+                    qualifier: 0,
+                    end: (0, 0, 0),
+                    phrase: phrase.clone(),
+                }
+            }
+        };
+        let qualifier = match self {
+            DateValue::Before(_) => 0,
+            DateValue::To(_) => 1,
+            DateValue::Exact(_) => 2,
+            DateValue::Interpreted(..) => 3,
+            DateValue::Calculated(_) => 4,
+            DateValue::Estimated(_) => 5,
+            DateValue::About(_) => 6,
+            DateValue::Between(..) => 7,
+            DateValue::FromTo(..) => 8,
+            DateValue::From(_) => 9,
+            DateValue::After(_) => 10,
+            DateValue::Phrase(_) => unreachable!(),
+        };
+        let phrase = match self {
+            DateValue::Interpreted(_, phrase) => phrase.clone(),
+            _ => String::new(),
+        };
+        DateSortKey {
+            undated: false,
+            start,
+            qualifier,
+            end,
+            phrase,
+        }
+    }
+
+    /// Compares two dates chronologically, see `DateValue::sort_key`
+    #[must_use]
+    pub fn cmp_chronologically(&self, other: &DateValue) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+/// `DateSortKey` orders `DateValue`s chronologically, see `DateValue::sort_key`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateSortKey {
+    /// phrases sort after dates
+    undated: bool,
+    /// the year, month and day the date starts at, see `DatePart::start_key`
+    start: (i32, u8, u8),
+    /// the rank of the kind of date among those starting on the same day
+    qualifier: u8,
+    /// the year, month and day the date ends at, see `DatePart::end_key`
+    end: (i32, u8, u8),
+    phrase: String,
+}
+
+impl DatePart {
+    /// The start of the period the date names, missing components coming before any other
+    fn start_key(&self) -> (i32, u8, u8) {
+        (self.year, self.month.unwrap_or(0), self.day.unwrap_or(0))
+    }
+
+    /// The end of the period the date names, missing components coming after any other
+    fn end_key(&self) -> (i32, u8, u8) {
+        (
+            self.year,
+            self.month.unwrap_or(u8::MAX),
+            self.day.unwrap_or(u8::MAX),
+        )
+    }
+}
+
+impl fmt::Display for DateValue {
+    /// Formats the date in canonical GEDCOM form, e.g. "ABT 2 FEB 1832"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateValue::Exact(d) => write!(f, "{d}"),
+            DateValue::About(d) => write!(f, "ABT {d}"),
+            DateValue::Calculated(d) => write!(f, "CAL {d}"),
+            DateValue::Estimated(d) => write!(f, "EST {d}"),
+            DateValue::Before(d) => write!(f, "BEF {d}"),
+            DateValue::After(d) => write!(f, "AFT {d}"),
+            DateValue::Between(start, end) => write!(f, "BET {start} AND {end}"),
+            DateValue::From(d) => write!(f, "FROM {d}"),
+            DateValue::To(d) => write!(f, "TO {d}"),
+            DateValue::FromTo(start, end) => write!(f, "FROM {start} TO {end}"),
+            DateValue::Interpreted(d, phrase) => write!(f, "INT {d} ({phrase})"),
+            DateValue::Phrase(phrase) => write!(f, "({phrase})"),
+        }
+    }
+}
+
+fn split_at_keyword<'a, 'b>(
+    words: &'a [&'b str],
+    keywords: &[&str],
+) -> Option<(&'a [&'b str], &'a [&'b str])> {
+    let i = words.iter().position(|w| keywords.contains(w))?;
+    Some((&words[..i], &words[i + 1..]))
+}
+
+fn month_number(word: &str, calendar: Calendar) -> Option<u8> {
+    let word = word.to_uppercase();
+    let index = match calendar {
+        Calendar::Hebrew | Calendar::French => calendar.months().iter().position(|m| *m == word)?,
+        _ => MONTHS
+            .iter()
+            .position(|m| *m == word)
+            .or_else(|| MONTH_NAMES.iter().position(|m| *m == word))?,
+    };
+    u8::try_from(index + 1).ok()
+}
+
+fn parse_year(word: &str) -> Option<i32> {
+    if word.is_empty() || word.len() > 4 || !word.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    word.parse().ok()
+}
+
+pub(crate) fn days_in_month(month: u8, year: i32) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
     }
 }
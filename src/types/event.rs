@@ -1,14 +1,22 @@
 use crate::{
+    intern::SharedStr,
     parse_subset,
     tokenizer::{Token, Tokenizer},
-    types::{Date, FamilyLink, MultimediaRecord, Note, SourceCitation},
-    Parser,
+    types::{
+        Age, Association, ContactInfo, Date, DateSortKey, DateValue, FamilyLink, MapCoordinates,
+        MultimediaRecord, Note, Place, SourceCitation, UserDefinedDataset,
+    },
+    util::unescape_at_signs,
+    GedcomError, Parser,
 };
 
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
 use std::{fmt, string::ToString};
 
+/// `Event` is the kind of an event, one variant for each of the standard individual and family
+/// events. Events with other tags keep their tag in `Custom`, or in `Registered` when the tag was
+/// registered with `GedcomDocument::register_event_type`.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
@@ -46,17 +54,188 @@ pub enum Event {
     Residence,
     Retired,
     Will,
-    // "Other" is used to construct an event without requiring an explicit event type
-    Other,
-    SourceData(String),
+    /// An event with a tag that isn't a standard event, e.g. a vendor's `_DNA`, holding the tag
+    Custom(String),
+    /// A user-defined event tag registered with `GedcomDocument::register_event_type`
+    Registered(CustomEventType),
+}
+
+impl Event {
+    /// The kind of event with the GEDCOM tag `tag`, `Custom` when it isn't a standard event.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::types::Event;
+    /// assert_eq!(Event::from_tag("MARR"), Event::Marriage);
+    /// assert!(Event::from_tag("MARR").is_family_event());
+    ///
+    /// let dna = Event::from_tag("_DNA");
+    /// assert_eq!(dna, Event::Custom("_DNA".to_string()));
+    /// assert_eq!(dna.tag(), "_DNA");
+    /// assert!(!dna.is_vital());
+    /// ```
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Event {
+        match tag {
+            "ADOP" => Event::Adoption,
+            "ANUL" => Event::Annulment,
+            "BAPM" => Event::Baptism,
+            "BARM" => Event::BarMitzvah,
+            "BASM" => Event::BasMitzvah,
+            "BIRT" => Event::Birth,
+            "BLES" => Event::Blessing,
+            "BURI" => Event::Burial,
+            "CENS" => Event::Census,
+            "CHR" => Event::Christening,
+            "CHRA" => Event::AdultChristening,
+            "CONF" => Event::Confirmation,
+            "CREM" => Event::Cremation,
+            "DEAT" => Event::Death,
+            "DIV" => Event::Divorce,
+            "DIVF" => Event::DivorceFiled,
+            "EMIG" => Event::Emigration,
+            "ENGA" => Event::Engagement,
+            "EVEN" => Event::Event,
+            "FCOM" => Event::FirstCommunion,
+            "GRAD" => Event::Graduation,
+            "IMMI" => Event::Immigration,
+            "MARB" => Event::MarriageBann,
+            "MARC" => Event::MarriageContract,
+            "MARL" => Event::MarriageLicense,
+            "MARR" => Event::Marriage,
+            "MARS" => Event::MarriageSettlement,
+            "NATU" => Event::Naturalization,
+            "ORDN" => Event::Ordination,
+            "PROB" => Event::Probate,
+            "RESI" => Event::Residence,
+            "RETI" => Event::Retired,
+            "WILL" => Event::Will,
+            _ => Event::Custom(tag.to_string()),
+        }
+    }
+
+    /// Whether the event is one of the vital events kept by civil registration: births, deaths,
+    /// marriages, divorces and annulments. Registered events are vital when their category is
+    /// `EventCategory::Vital`.
+    #[must_use]
+    pub fn is_vital(&self) -> bool {
+        match self {
+            Event::Birth | Event::Death | Event::Marriage | Event::Divorce | Event::Annulment => {
+                true
+            }
+            Event::Registered(event_type) => event_type.category == EventCategory::Vital,
+            _ => false,
+        }
+    }
+
+    /// Whether the event is one of those recorded for families only, e.g. a marriage. Censuses,
+    /// residences and generic EVEN events, which both individuals and families have, aren't.
+    #[must_use]
+    pub fn is_family_event(&self) -> bool {
+        matches!(
+            self,
+            Event::Annulment
+                | Event::Divorce
+                | Event::DivorceFiled
+                | Event::Engagement
+                | Event::Marriage
+                | Event::MarriageBann
+                | Event::MarriageContract
+                | Event::MarriageLicense
+                | Event::MarriageSettlement
+        )
+    }
+
+    /// The GEDCOM tag of the event
+    #[must_use]
+    pub fn tag(&self) -> &str {
+        match self {
+            Event::Adoption => "ADOP",
+            Event::AdultChristening => "CHRA",
+            Event::Annulment => "ANUL",
+            Event::Baptism => "BAPM",
+            Event::BarMitzvah => "BARM",
+            Event::BasMitzvah => "BASM",
+            Event::Birth => "BIRT",
+            Event::Blessing => "BLES",
+            Event::Burial => "BURI",
+            Event::Census => "CENS",
+            Event::Christening => "CHR",
+            Event::Confirmation => "CONF",
+            Event::Cremation => "CREM",
+            Event::Death => "DEAT",
+            Event::Divorce => "DIV",
+            Event::DivorceFiled => "DIVF",
+            Event::Emigration => "EMIG",
+            Event::Engagement => "ENGA",
+            Event::FirstCommunion => "FCOM",
+            Event::Graduation => "GRAD",
+            Event::Immigration => "IMMI",
+            Event::Marriage => "MARR",
+            Event::MarriageBann => "MARB",
+            Event::MarriageContract => "MARC",
+            Event::MarriageLicense => "MARL",
+            Event::MarriageSettlement => "MARS",
+            Event::Naturalization => "NATU",
+            Event::Ordination => "ORDN",
+            Event::Probate => "PROB",
+            Event::Residence => "RESI",
+            Event::Retired => "RETI",
+            Event::Will => "WILL",
+            Event::Event => "EVEN",
+            Event::Custom(tag) => tag,
+            Event::Registered(event_type) => &event_type.tag,
+        }
+    }
 }
 
 impl ToString for Event {
     fn to_string(&self) -> String {
-        format!("{:?}", self)
+        match self {
+            Event::Custom(tag) => tag.clone(),
+            Event::Registered(event_type) => event_type.name.clone(),
+            _ => format!("{:?}", self),
+        }
     }
 }
 
+/// `CustomEventType` describes a user-defined event tag, e.g. `_MILT`, so that the parser reads
+/// it as an event of the individuals and families it appears in rather than as custom data. See
+/// `GedcomDocument::register_event_type`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct CustomEventType {
+    /// The tag of the event, e.g. `_MILT`
+    pub tag: String,
+    /// The name of the event to display, e.g. "Military Service"
+    pub name: String,
+    /// The kind of event, for grouping events in a user interface
+    pub category: EventCategory,
+}
+
+/// `EventCategory` groups events by the part of life they belong to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum EventCategory {
+    /// Birth, marriage, death and the like
+    Vital,
+    /// Baptisms, confirmations and other religious rites
+    Religious,
+    /// Wills, probates, court cases and other legal proceedings
+    Legal,
+    /// Emigration, immigration and residence
+    Migration,
+    /// Military service, enlistments and decorations
+    Military,
+    /// Schooling, graduations and apprenticeships
+    Education,
+    /// Occupations and employment
+    Occupation,
+    /// Events fitting no other category
+    Other,
+}
+
 /// EventDetail is a thing that happens on a specific date. Use the date form 'BET date AND date'
 /// to indicate that an event took place at some time between two dates. Resist the temptation to
 /// use a 'FROM date TO date' form in an event structure. If the subject of your recording occurred
@@ -94,7 +273,7 @@ impl ToString for Event {
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let event = data.individuals[0].events[0].event.to_string();
 /// assert_eq!(event, "Census");
@@ -105,8 +284,23 @@ pub struct EventDetail {
     pub event: Event,
     pub value: Option<String>,
     pub date: Option<Date>,
-    pub place: Option<String>,
-    pub note: Option<Note>,
+    pub place: Option<SharedStr>,
+    /// tag: MAP, the coordinates of the place, given under PLAC
+    pub coordinates: Option<MapCoordinates>,
+    /// tags: ADDR, PHON, EMAIL, FAX and WWW, where the event took place
+    pub contact: ContactInfo,
+    /// tag: AGNC, the institution or individual responsible for the event
+    pub agency: Option<String>,
+    /// tag: RELI, a religious denomination associated with the event
+    pub religion: Option<String>,
+    /// tag: CAUS, used in particular for the cause of death
+    pub cause: Option<String>,
+    /// tag: RESN, a privacy restriction, e.g. "confidential"
+    pub restriction: Option<String>,
+    /// tag: AGE, the age of the individual at the time of the event. Ages of spouses in family
+    /// events are in `family_event_details`.
+    pub age: Option<String>,
+    pub notes: Vec<Note>,
     pub family_link: Option<FamilyLink>,
     pub family_event_details: Vec<FamilyEventDetail>,
     /// event_type handles the TYPE tag, a descriptive word or phrase used to further classify the
@@ -115,70 +309,93 @@ pub struct EventDetail {
     pub event_type: Option<String>,
     pub citations: Vec<SourceCitation>,
     pub multimedia: Vec<MultimediaRecord>,
+    /// tag: ASSO, people who took part in the event other than its principals, e.g. witnesses.
+    /// Also read from the vendor tags WITN, `_WITN`, `_SHAR` and `_ASSO`.
+    pub associations: Vec<Association>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl EventDetail {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, tag: &str) -> EventDetail {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        tag: &str,
+    ) -> Result<EventDetail, GedcomError> {
+        let event = match tokenizer.event_type(tag) {
+            Some(event_type) => Event::Registered(event_type.clone()),
+            None => Event::from_tag(tag),
+        };
         let mut event = EventDetail {
-            event: Self::from_tag(tag),
+            event_type: tokenizer.take_alias_type(),
+            ..EventDetail::from_event(event)
+        };
+        event.parse(tokenizer, level)?;
+        Ok(event)
+    }
+
+    /// An event of the kind given, with no details
+    #[must_use]
+    pub fn from_event(event: Event) -> EventDetail {
+        EventDetail {
+            event,
             value: None,
             date: None,
             place: None,
-            note: None,
+            coordinates: None,
+            contact: ContactInfo::default(),
+            agency: None,
+            religion: None,
+            cause: None,
+            restriction: None,
+            age: None,
+            notes: Vec::new(),
             family_link: None,
             family_event_details: Vec::new(),
             event_type: None,
             citations: Vec::new(),
             multimedia: Vec::new(),
-        };
-        event.parse(tokenizer, level);
-        event
+            associations: Vec::new(),
+            custom_data: Vec::new(),
+        }
     }
 
-    /** converts an event to be of type `SourceData` with `value` as the data */
-    pub fn with_source_data(&mut self, value: String) {
-        self.event = Event::SourceData(value);
+    /// What the event is: the TYPE of a generic EVEN event, e.g. "DNA Match", or else the name of
+    /// the event, e.g. "Birth".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{types::Event, GedcomDocument};
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5.1\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 EVEN 27 cM across 2 segments\n\
+    ///     2 TYPE DNA Match\n\
+    ///     2 DATE 2019\n\
+    ///     1 BIRT\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let events = &data.individuals[0].events;
+    /// assert_eq!(events[0].event, Event::Event);
+    /// assert_eq!(events[0].value.as_deref(), Some("27 cM across 2 segments"));
+    /// assert_eq!(events[0].kind(), "DNA Match");
+    /// assert_eq!(events[1].kind(), "Birth");
+    /// ```
+    #[must_use]
+    pub fn kind(&self) -> String {
+        match (&self.event, &self.event_type) {
+            (Event::Event, Some(event_type)) => event_type.clone(),
+            _ => self.event.to_string(),
+        }
     }
 
+    /// The kind of event with the GEDCOM tag `tag`, see `Event::from_tag`
+    #[must_use]
     pub fn from_tag(tag: &str) -> Event {
-        match tag {
-            "ADOP" => Event::Adoption,
-            "ANUL" => Event::Annulment,
-            "BAPM" => Event::Baptism,
-            "BARM" => Event::BarMitzvah,
-            "BASM" => Event::BasMitzvah,
-            "BIRT" => Event::Birth,
-            "BLES" => Event::Blessing,
-            "BURI" => Event::Burial,
-            "CENS" => Event::Census,
-            "CHR" => Event::Christening,
-            "CHRA" => Event::AdultChristening,
-            "CONF" => Event::Confirmation,
-            "CREM" => Event::Cremation,
-            "DEAT" => Event::Death,
-            "DIV" => Event::Divorce,
-            "DIVF" => Event::DivorceFiled,
-            "EMIG" => Event::Emigration,
-            "ENGA" => Event::Engagement,
-            "EVEN" => Event::Event,
-            "FCOM" => Event::FirstCommunion,
-            "GRAD" => Event::Graduation,
-            "IMMI" => Event::Immigration,
-            "MARB" => Event::MarriageBann,
-            "MARC" => Event::MarriageContract,
-            "MARL" => Event::MarriageLicense,
-            "MARR" => Event::Marriage,
-            "MARS" => Event::MarriageSettlement,
-            "NATU" => Event::Naturalization,
-            "ORDN" => Event::Ordination,
-            "OTHER" => Event::Other,
-            "PROB" => Event::Probate,
-            "RESI" => Event::Residence,
-            "RETI" => Event::Retired,
-            "WILL" => Event::Will,
-            _ => panic!("Unrecognized EventType tag: {}", tag),
-        }
+        Event::from_tag(tag)
     }
 
     pub fn add_citation(&mut self, citation: SourceCitation) {
@@ -189,6 +406,16 @@ impl EventDetail {
         self.family_event_details.push(detail);
     }
 
+    /// The age of a spouse at the time of a family event, read from the AGE of its HUSB or WIFE
+    /// substructure. Ages which can't be read are `None`, but are kept in `family_event_details`.
+    #[must_use]
+    pub fn spouse_age(&self, spouse: Spouse) -> Option<Age> {
+        self.family_event_details
+            .iter()
+            .find(|detail| detail.member == spouse)
+            .and_then(|detail| Age::parse(detail.age.as_deref()?))
+    }
+
     pub fn add_multimedia_record(&mut self, m: MultimediaRecord) {
         self.multimedia.push(m);
     }
@@ -228,74 +455,200 @@ pub trait HasEvents {
         let mut places: Vec<String> = Vec::new();
         for event in self.events() {
             if let Some(p) = &event.place {
-                places.push(p.clone());
+                places.push(p.to_string());
             }
         }
         places
     }
+
+    /// The events of the kind given, e.g. `Event::Residence`, in file order
+    fn events_of_kind(&self, kind: &Event) -> Vec<EventDetail> {
+        self.events()
+            .into_iter()
+            .filter(|event| event.event == *kind)
+            .collect()
+    }
+
+    /// The first birth event
+    fn birth(&self) -> Option<EventDetail> {
+        self.events_of_kind(&Event::Birth).into_iter().next()
+    }
+
+    /// The first death event
+    fn death(&self) -> Option<EventDetail> {
+        self.events_of_kind(&Event::Death).into_iter().next()
+    }
+
+    /// The events with a date which can be interpreted, see `DateValue`, in chronological order,
+    /// see `DateValue::sort_key`. Events on the same date keep their order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{
+    ///     types::{event::HasEvents, Event},
+    ///     GedcomDocument,
+    /// };
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 DEAT\n\
+    ///     2 DATE 14 DEC 1799\n\
+    ///     1 RESI\n\
+    ///     2 DATE ABT 1760\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 22 FEB 1732\n\
+    ///     1 RESI\n\
+    ///     2 DATE (while young)\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let george = &data.individuals[0];
+    ///
+    /// let birth = george.birth().unwrap();
+    /// assert_eq!(birth.date.unwrap().value.as_deref(), Some("22 FEB 1732"));
+    /// assert_eq!(george.events_of_kind(&Event::Residence).len(), 2);
+    ///
+    /// let sorted: Vec<Event> = george
+    ///     .dated_events_sorted()
+    ///     .into_iter()
+    ///     .map(|event| event.event)
+    ///     .collect();
+    /// assert_eq!(sorted, vec![Event::Birth, Event::Residence, Event::Death]);
+    /// ```
+    fn dated_events_sorted(&self) -> Vec<EventDetail> {
+        let mut events: Vec<(DateSortKey, EventDetail)> = self
+            .events()
+            .into_iter()
+            .filter_map(|event| {
+                let date = event.date.as_ref()?.value_structured()?;
+                if matches!(date, DateValue::Phrase(_)) {
+                    return None;
+                }
+                Some((date.sort_key(), event))
+            })
+            .collect();
+        // sort_by is stable, keeping the order of the events on the same date
+        events.sort_by(|(first, _), (second, _)| first.cmp(second));
+        events.into_iter().map(|(_, event)| event).collect()
+    }
 }
 
 impl Parser for EventDetail {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
 
         // handle value on event line
         let mut value = String::new();
 
-        if let Token::LineValue(val) = &tokenizer.current_token {
-            value.push_str(&val);
-            tokenizer.next_token();
+        if let Token::LineValue(val) = tokenizer.current_token {
+            value.push_str(&unescape_at_signs(val));
+            tokenizer.next_token()?;
         }
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| {
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
             let mut pointer: Option<String> = None;
-            if let Token::Pointer(xref) = &tokenizer.current_token {
+            if let Token::Pointer(xref) = tokenizer.current_token {
                 pointer = Some(xref.to_string());
-                tokenizer.next_token();
+                tokenizer.next_token()?;
             }
             match tag {
-                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)),
-                "PLAC" => self.place = Some(tokenizer.take_line_value()),
-                "SOUR" => self.add_citation(SourceCitation::new(tokenizer, level + 1)),
-                "FAMC" => self.family_link = Some(FamilyLink::new(tokenizer, level + 1, tag)),
-                "HUSB" | "WIFE" => {
-                    self.add_family_event_detail(FamilyEventDetail::new(tokenizer, level + 1, tag));
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "PLAC" => {
+                    let place = Place::new(tokenizer, level + 1)?;
+                    self.place = place.value;
+                    self.coordinates = place.map;
                 }
-                "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-                "TYPE" => self.event_type = Some(tokenizer.take_line_value()),
-                "OBJE" => {
-                    self.add_multimedia_record(MultimediaRecord::new(tokenizer, level + 1, pointer))
+                "ADDR" | "PHON" | "EMAIL" | "FAX" | "WWW" => {
+                    self.contact.parse_tag(tokenizer, level + 1, tag)?;
                 }
-                _ => panic!("{} Unhandled Event Tag: {}", tokenizer.debug(), tag),
+                "AGNC" => self.agency = Some(tokenizer.take_line_value()?),
+                "RELI" => self.religion = Some(tokenizer.take_line_value()?),
+                "CAUS" => self.cause = Some(tokenizer.take_line_value()?),
+                "RESN" => self.restriction = Some(tokenizer.take_line_value()?),
+                "AGE" => self.age = Some(tokenizer.take_line_value()?),
+                "SOUR" => SourceCitation::parse_into(&mut self.citations, tokenizer, level + 1)?,
+                "FAMC" => self.family_link = Some(FamilyLink::new(tokenizer, level + 1, tag)?),
+                "HUSB" => self.add_family_event_detail(FamilyEventDetail::new(
+                    tokenizer,
+                    level + 1,
+                    Spouse::Spouse1,
+                )?),
+                "WIFE" => self.add_family_event_detail(FamilyEventDetail::new(
+                    tokenizer,
+                    level + 1,
+                    Spouse::Spouse2,
+                )?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                "TYPE" => self.event_type = Some(tokenizer.take_line_value()?),
+                "OBJE" => self.add_multimedia_record(MultimediaRecord::new(
+                    tokenizer,
+                    level + 1,
+                    pointer,
+                )?),
+                "ASSO" | "WITN" => {
+                    self.associations
+                        .push(Association::new(tokenizer, level + 1, tag)?);
+                }
+                _ => return Err(tokenizer.unhandled_tag("Event", tag)),
             }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        let (associations, custom_data): (Vec<_>, Vec<_>) =
+            parse_subset(tokenizer, level, handle_subset)?
+                .into_iter()
+                .partition(|custom| Association::is_custom_tag(&custom.tag));
+        self.associations.extend(
+            associations
+                .iter()
+                .map(|custom| Association::from_custom(custom)),
+        );
+        self.custom_data = custom_data;
 
         if &value != "" {
             self.value = Some(value);
         }
+        Ok(())
     }
 }
 
 /// Spouse in a family that experiences an event.
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum Spouse {
+    /// tag: HUSB
     Spouse1,
+    /// tag: WIFE
     Spouse2,
 }
 
+impl Spouse {
+    /// The tag of the spouse's substructure of a family event
+    #[must_use]
+    pub fn tag(self) -> &'static str {
+        match self {
+            Spouse::Spouse1 => "HUSB",
+            Spouse::Spouse2 => "WIFE",
+        }
+    }
+}
+
 impl ToString for Spouse {
     fn to_string(&self) -> String {
         format!("{:?}", self)
     }
 }
 
-/// FamilyEventDetail defines an additional dataset found in certain events.
+/// FamilyEventDetail holds the details of a family event specific to one of the spouses, read
+/// from its HUSB or WIFE substructure, i.e. their age at the time of the event. Some applications
+/// also write the xref of the spouse on the HUSB or WIFE line, which is kept as its value.
 ///
 /// # Example
 ///
 /// ```rust
-/// use gedcom::GedcomDocument;
+/// use gedcom::{types::event::Spouse, GedcomDocument};
 /// let sample = "\
 ///    0 HEAD\n\
 ///    1 GEDC\n\
@@ -325,50 +678,60 @@ impl ToString for Spouse {
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let anul = &data.families[0].events;
 /// assert_eq!(anul.len(), 1);
 ///
+/// let wife = anul[0].spouse_age(Spouse::Spouse2).unwrap();
+/// assert_eq!((wife.years, wife.months), (Some(42), Some(6)));
+/// assert_eq!(anul[0].family_event_details[0].age.as_deref(), Some("42y"));
 /// ```
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct FamilyEventDetail {
+    /// The spouse the details are about
     pub member: Spouse,
+    /// The payload of the HUSB or WIFE line, if any, e.g. "@I1@"
+    pub value: Option<String>,
+    /// tag: AGE, the age of the spouse at the time of the event, e.g. "25y", see `Age::parse`
     pub age: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl FamilyEventDetail {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, tag: &str) -> FamilyEventDetail {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        member: Spouse,
+    ) -> Result<FamilyEventDetail, GedcomError> {
         let mut fe = FamilyEventDetail {
-            member: Self::from_tag(tag),
+            member,
+            value: None,
             age: None,
+            custom_data: Vec::new(),
         };
-        fe.parse(tokenizer, level);
-        fe
-    }
-
-    pub fn from_tag(tag: &str) -> Spouse {
-        match tag {
-            "HUSB" => Spouse::Spouse1,
-            "WIFE" => Spouse::Spouse2,
-            _ => panic!("{:?}, Unrecognized FamilyEventMember", tag),
-        }
+        fe.parse(tokenizer, level)?;
+        Ok(fe)
     }
 }
 
 impl Parser for FamilyEventDetail {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "AGE" => self.age = Some(tokenizer.take_line_value()),
-            _ => panic!(
-                "{}, Unrecognized FamilyEventDetail tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
+        if let Token::LineValue(value) = tokenizer.current_token {
+            self.value = Some(unescape_at_signs(value).into_owned());
+            tokenizer.next_token()?;
+        }
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "AGE" => self.age = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("FamilyEventDetail", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
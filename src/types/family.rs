@@ -1,13 +1,18 @@
 use crate::{
+    duplicates::event_date,
     parse_subset,
     tokenizer::{Token, Tokenizer},
     types::{
-        event::HasEvents, ChangeDate, EventDetail, MultimediaRecord, Note, SourceCitation,
-        UserDefinedDataset, Xref,
+        event::HasEvents, AttributeDetail, ChangeDate, DateSortKey, DateValue, Event, EventDetail,
+        ExternalId, ExternalIdKind, MultimediaRecord, Note, SourceCitation, UserDefinedDataset,
+        Xref,
     },
-    Parser,
+    GedcomData, GedcomError, Parser,
 };
 
+#[cfg(feature = "lds")]
+use crate::types::{LdsOrdinance, OrdinanceType};
+
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
 
@@ -15,7 +20,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// This data representation understands that HUSB & WIFE are just poorly-named
 /// pointers to individuals. no gender "validating" is done on parse.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Family {
     pub xref: Option<Xref>,
@@ -25,16 +30,31 @@ pub struct Family {
     pub children: Vec<Xref>,
     pub num_children: Option<String>,
     pub change_date: Option<ChangeDate>,
+    /// tag: CREA, when the record was created, from GEDCOM 7
+    pub created: Option<ChangeDate>,
     pub events: Vec<EventDetail>,
+    /// tag: FACT, facts of the family classified by their TYPE, from GEDCOM 7
+    pub attributes: Vec<AttributeDetail>,
     pub sources: Vec<SourceCitation>,
     pub multimedia: Vec<MultimediaRecord>,
     pub notes: Vec<Note>,
+    /// tag: SLGS
+    #[cfg(feature = "lds")]
+    pub lds_ordinances: Vec<LdsOrdinance>,
+    /// tag: UID, ids of the record which stay the same across files, from GEDCOM 7
+    pub uids: Vec<String>,
+    /// tags: EXID, `_FSFTID`, `_APID`, `_UID` and `_GUID`, the ids of the family at other
+    /// services
+    pub external_ids: Vec<ExternalId>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Family {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<Xref>) -> Family {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<Xref>,
+    ) -> Result<Family, GedcomError> {
         let mut fam = Family::default();
         fam.xref = xref;
         fam.children = Vec::new();
@@ -43,8 +63,8 @@ impl Family {
         fam.multimedia = Vec::new();
         fam.notes = Vec::new();
         fam.custom_data = Vec::new();
-        fam.parse(tokenizer, level);
-        fam
+        fam.parse(tokenizer, level)?;
+        Ok(fam)
     }
 
     pub fn set_individual1(&mut self, xref: Xref) {
@@ -80,39 +100,151 @@ impl Family {
     pub fn add_note(&mut self, note: Note) {
         self.notes.push(note);
     }
+
+    /// The first id of the kind given to the family, see `ExternalId`
+    #[must_use]
+    pub fn external_id(&self, kind: ExternalIdKind) -> Option<&str> {
+        self.external_ids
+            .iter()
+            .find(|id| id.kind == kind)
+            .map(|id| id.value.as_str())
+    }
+
+    /// The xrefs of the children of the family, sorted by their dates of birth, or else of
+    /// christening or baptism, see `DateValue::sort_key`. Children without such a date, those
+    /// dated by a phrase only, and those missing from `data` come last. Children born on the same date, e.g. twins, keep their order
+    /// in the family.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 BIRT\n\
+    ///     2 DATE ABT 1835\n\
+    ///     0 @I2@ INDI\n\
+    ///     0 @I3@ INDI\n\
+    ///     1 CHR\n\
+    ///     2 DATE 4 MAY 1831\n\
+    ///     0 @F1@ FAM\n\
+    ///     1 CHIL @I1@\n\
+    ///     1 CHIL @I2@\n\
+    ///     1 CHIL @I3@\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let family = &data.families[0];
+    /// assert_eq!(
+    ///     family.children_sorted_by_birth(&data),
+    ///     vec!["@I3@", "@I1@", "@I2@"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn children_sorted_by_birth<'a>(&'a self, data: &GedcomData) -> Vec<&'a str> {
+        let mut children: Vec<(&str, Option<DateSortKey>)> = self
+            .children
+            .iter()
+            .map(|xref| {
+                let birth = data
+                    .individuals
+                    .iter()
+                    .find(|indi| indi.xref.as_deref() == Some(xref.as_str()))
+                    .and_then(|indi| {
+                        [Event::Birth, Event::Christening, Event::Baptism]
+                            .iter()
+                            .filter_map(|event| event_date(indi, event))
+                            .find(|date| !matches!(date, DateValue::Phrase(_)))
+                    });
+                (xref.as_str(), birth.as_ref().map(DateValue::sort_key))
+            })
+            .collect();
+        // sort_by is stable, keeping the order of the family among equal dates
+        children.sort_by(|(_, first), (_, second)| {
+            (first.is_none(), first).cmp(&(second.is_none(), second))
+        });
+        children.into_iter().map(|(xref, _)| xref).collect()
+    }
 }
 
 impl Parser for Family {
     /// parse handles FAM top-level tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip over FAM tag name
-        tokenizer.next_token();
+        tokenizer.next_token()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| {
+        // LDS ordinances, kept as custom data without the lds feature
+        #[cfg(not(feature = "lds"))]
+        let mut ordinances = Vec::new();
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
             let mut pointer: Option<String> = None;
-            if let Token::Pointer(xref) = &tokenizer.current_token {
+            if let Token::Pointer(xref) = tokenizer.current_token {
                 pointer = Some(xref.to_string());
-                tokenizer.next_token();
+                tokenizer.next_token()?;
             }
 
             match tag {
                 "MARR" | "ANUL" | "CENS" | "DIV" | "DIVF" | "ENGA" | "MARB" | "MARC" | "MARL"
                 | "MARS" | "RESI" | "EVEN" => {
-                    self.add_event(EventDetail::new(tokenizer, level + 1, tag));
+                    self.add_event(EventDetail::new(tokenizer, level + 1, tag)?);
                 }
-                "HUSB" => self.set_individual1(tokenizer.take_line_value()),
-                "WIFE" => self.set_individual2(tokenizer.take_line_value()),
-                "CHIL" => self.add_child(tokenizer.take_line_value()),
-                "NCHI" => self.num_children = Some(tokenizer.take_line_value()),
-                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)),
-                "SOUR" => self.add_source(SourceCitation::new(tokenizer, level + 1)),
-                "NOTE" => self.add_note(Note::new(tokenizer, level + 1)),
-                "OBJE" => self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, pointer)),
-                _ => panic!("{} Unhandled Family Tag: {}", tokenizer.debug(), tag),
+                "HUSB" if self.individual1.is_some() => {
+                    return Err(tokenizer.parse_error("Family has more than one HUSB"))
+                }
+                "WIFE" if self.individual2.is_some() => {
+                    return Err(tokenizer.parse_error("Family has more than one WIFE"))
+                }
+                "HUSB" => self.set_individual1(tokenizer.take_line_value()?),
+                "WIFE" => self.set_individual2(tokenizer.take_line_value()?),
+                "CHIL" => self.add_child(tokenizer.take_line_value()?),
+                "NCHI" => self.num_children = Some(tokenizer.take_line_value()?),
+                "FACT" => {
+                    self.attributes
+                        .push(AttributeDetail::new(tokenizer, level + 1, tag)?);
+                }
+                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "CREA" => self.created = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "UID" => self.uids.push(tokenizer.take_line_value()?),
+                "EXID" => self
+                    .external_ids
+                    .push(ExternalId::new(tokenizer, level + 1)?),
+                "SOUR" => SourceCitation::parse_into(&mut self.sources, tokenizer, level + 1)?,
+                "NOTE" => self.add_note(Note::new(tokenizer, level + 1)?),
+                "OBJE" => {
+                    self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, pointer)?);
+                }
+                #[cfg(feature = "lds")]
+                "SLGS" => self.lds_ordinances.push(LdsOrdinance::new(
+                    tokenizer,
+                    level + 1,
+                    OrdinanceType::SpouseSealing,
+                )?),
+                #[cfg(not(feature = "lds"))]
+                "SLGS" => {
+                    ordinances.push(Box::new(UserDefinedDataset::new(
+                        tokenizer,
+                        level + 1,
+                        tag,
+                    )?));
+                }
+                _ if tokenizer.event_type(tag).is_some() => {
+                    self.add_event(EventDetail::new(tokenizer, level + 1, tag)?);
+                }
+                _ => return Err(tokenizer.unhandled_tag("Family", tag)),
             }
+            Ok(())
         };
 
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        let mut vendor_ids = ExternalId::take_from(&mut self.custom_data);
+        self.external_ids.append(&mut vendor_ids);
+        #[cfg(not(feature = "lds"))]
+        self.custom_data.append(&mut ordinances);
+
+        Ok(())
     }
 }
 
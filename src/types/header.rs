@@ -2,7 +2,7 @@ use crate::{
     parse_subset,
     tokenizer::Tokenizer,
     types::{Corporation, Date, Note},
-    Parser,
+    GedcomError, Parser,
 };
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
@@ -28,7 +28,7 @@ use super::UserDefinedDataset;
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 /// let header = data.header.unwrap();
 ///
 /// let dest = header.destination.unwrap();
@@ -46,7 +46,38 @@ use super::UserDefinedDataset;
 /// let file = header.filename.unwrap();
 /// assert_eq!(file, "ALLGED.GED");
 /// ```
-#[derive(Debug, Default)]
+///
+/// Nested values are also available through accessors, and GEDCOM 7 substructures such as SCHMA
+/// and repeated NOTEs are kept in full:
+///
+/// ```rust
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 7.0\n\
+///     1 SCHMA\n\
+///     2 TAG _SKYPEID http://xmlns.com/foaf/0.1/skypeID\n\
+///     1 SOUR GRAMPS\n\
+///     1 CHAR UTF-8\n\
+///     1 PLAC\n\
+///     2 FORM City, Country\n\
+///     1 NOTE First note\n\
+///     1 NOTE Second note\n\
+///     0 TRLR";
+///
+/// let mut doc = GedcomDocument::new(sample.chars());
+/// let header = doc.parse_document().unwrap().header.unwrap();
+///
+/// assert_eq!(header.gedcom_version(), Some("7.0"));
+/// assert_eq!(header.source_system(), Some("GRAMPS"));
+/// assert_eq!(header.character_set(), Some("UTF-8"));
+/// assert_eq!(header.place_form(), ["City", "Country"]);
+/// assert_eq!(header.extension_uri("_SKYPEID"), Some("http://xmlns.com/foaf/0.1/skypeID"));
+/// assert_eq!(header.notes.len(), 2);
+/// assert_eq!(header.notes[1].value.as_ref().unwrap(), "Second note");
+/// ```
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Header {
     /// tag: GEDC
@@ -74,45 +105,204 @@ pub struct Header {
     /// tag: FILE, the name of the GEDCOM transmission file. If the file name includes a file
     /// extension it must be shown in the form (filename.ext). See Gedcom 5.5.1 specification, p. 50.
     pub filename: Option<String>,
-    /// tag: NOTE
-    pub note: Option<Note>,
+    /// tag: NOTE, any number of notes describing the contents of the document
+    pub notes: Vec<Note>,
     /// tag: PLAC
     pub place: Option<HeadPlac>,
+    /// tag: SCHMA
+    pub schema: Option<HeadSchema>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Header {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Header {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Header, GedcomError> {
         let mut header = Header::default();
-        header.parse(tokenizer, level);
-        header
+        header.parse(tokenizer, level)?;
+        Ok(header)
+    }
+
+    /// Adds a NOTE describing the contents of the document, after those it has already
+    pub fn add_note(&mut self, note: Note) {
+        self.notes.push(note);
+    }
+
+    /// The GEDCOM version the document declares (tag: GEDC.VERS)
+    #[must_use]
+    pub fn gedcom_version(&self) -> Option<&str> {
+        self.gedcom.as_ref().and_then(|gedc| gedc.version.as_deref())
+    }
+
+    /// The GEDCOM form the document declares (tag: GEDC.FORM)
+    #[must_use]
+    pub fn gedcom_form(&self) -> Option<&str> {
+        self.gedcom.as_ref().and_then(|gedc| gedc.form.as_deref())
+    }
+
+    /// The character set the document declares (tag: CHAR)
+    #[must_use]
+    pub fn character_set(&self) -> Option<&str> {
+        self.encoding.as_ref().and_then(|chars| chars.value.as_deref())
+    }
+
+    /// The identifier of the system which produced the document (tag: SOUR)
+    #[must_use]
+    pub fn source_system(&self) -> Option<&str> {
+        self.source.as_ref().and_then(HeadSour::product)
+    }
+
+    /// The default jurisdictional titles of places in the document (tag: PLAC.FORM), empty when
+    /// the header doesn't define any
+    #[must_use]
+    pub fn place_form(&self) -> &[String] {
+        self.place
+            .as_ref()
+            .map_or(&[], |plac| plac.jurisdictional_titles())
+    }
+
+    /// The URI the document's schema defines for an extension tag (tag: SCHMA.TAG)
+    #[must_use]
+    pub fn extension_uri(&self, tag: &str) -> Option<&str> {
+        self.schema.as_ref().and_then(|schma| schma.uri(tag))
+    }
+
+    /// The first custom structure of the header with the given tag, e.g. "_TREE"
+    #[must_use]
+    pub fn custom(&self, tag: &str) -> Option<&UserDefinedDataset> {
+        self.custom_data
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|custom| custom.tag == tag)
+    }
+
+    /// The name of the online tree the document was exported from (tag: `_TREE`), as written by
+    /// Ancestry
+    #[must_use]
+    pub fn tree_name(&self) -> Option<&str> {
+        self.custom("_TREE")?.value.as_deref()
+    }
+
+    /// The identifier of the online tree the document was exported from (tag: `_TREE.RIN`), as
+    /// written by Ancestry
+    #[must_use]
+    pub fn tree_id(&self) -> Option<&str> {
+        self.custom("_TREE")?.child("RIN")?.value.as_deref()
+    }
+
+    /// The build number of the product which produced the document: the last part of a four-part
+    /// version such as "24.0.0.1230" (tag: SOUR.VERS), as written by Family Tree Maker and PAF
+    /// among others
+    #[must_use]
+    pub fn product_build(&self) -> Option<&str> {
+        let version = self.source.as_ref()?.version()?;
+        let parts: Vec<&str> = version.trim().split('.').collect();
+        match parts[..] {
+            [_, _, _, build] if parts.iter().all(|part| part.parse::<u32>().is_ok()) => Some(build),
+            _ => None,
+        }
+    }
+
+    /// Where the document came from, see `ProvenanceInfo`
+    #[must_use]
+    pub fn provenance(&self) -> ProvenanceInfo<'_> {
+        let source = self.source.as_ref();
+        let custom_value = |tag| self.custom(tag).and_then(|custom| custom.value.as_deref());
+        ProvenanceInfo {
+            product: source.and_then(HeadSour::product),
+            product_name: source.and_then(HeadSour::name),
+            product_version: source.and_then(HeadSour::version),
+            product_build: self.product_build(),
+            corporation: source.and_then(HeadSour::corporation_name),
+            exported: self.date.as_ref().and_then(|date| date.value.as_deref()),
+            tree_name: self.tree_name(),
+            tree_id: self.tree_id(),
+            project_guid: custom_value("_PROJECT_GUID"),
+            site_id: custom_value("_EXPORTED_FROM_SITE_ID"),
+        }
     }
 }
 
+/// `ProvenanceInfo` gathers the metadata of a header describing where a document came from: the
+/// product which produced it (HEAD.SOUR) and the vendor extensions identifying the tree it was
+/// exported from.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 SOUR Ancestry.com Family Trees\n\
+///     2 VERS (2010.3)\n\
+///     2 NAME Ancestry.com Member Trees\n\
+///     2 CORP Ancestry.com\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     1 _TREE Smith Family Tree\n\
+///     2 RIN 123456789\n\
+///     2 _ENV prd\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let provenance = data.provenance();
+///
+/// assert_eq!(provenance.product, Some("Ancestry.com Family Trees"));
+/// assert_eq!(provenance.corporation, Some("Ancestry.com"));
+/// assert_eq!(provenance.tree_name, Some("Smith Family Tree"));
+/// assert_eq!(provenance.tree_id, Some("123456789"));
+/// assert_eq!(provenance.product_build, None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProvenanceInfo<'a> {
+    /// tag: SOUR, the identifier of the product, e.g. "FTM"
+    pub product: Option<&'a str>,
+    /// tag: SOUR.NAME
+    pub product_name: Option<&'a str>,
+    /// tag: SOUR.VERS
+    pub product_version: Option<&'a str>,
+    /// The build number of the product, see `Header::product_build`
+    pub product_build: Option<&'a str>,
+    /// tag: SOUR.CORP
+    pub corporation: Option<&'a str>,
+    /// tag: DATE, when the document was exported
+    pub exported: Option<&'a str>,
+    /// tag: `_TREE`, the name of the online tree (Ancestry)
+    pub tree_name: Option<&'a str>,
+    /// tag: `_TREE.RIN`, the identifier of the online tree (Ancestry)
+    pub tree_id: Option<&'a str>,
+    /// tag: `_PROJECT_GUID`, the identifier of the project (Family Tree Builder)
+    pub project_guid: Option<&'a str>,
+    /// tag: `_EXPORTED_FROM_SITE_ID`, the identifier of the site (Family Tree Builder)
+    pub site_id: Option<&'a str>,
+}
+
 impl Parser for Header {
     /// Parses HEAD top-level tag. See
     /// https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#HEADER
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip over HEAD tag name
-        tokenizer.next_token();
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "GEDC" => self.gedcom = Some(GedcomMeta::new(tokenizer, level + 1)),
-            "SOUR" => self.source = Some(HeadSour::new(tokenizer, level + 1)),
-            "DEST" => self.destination = Some(tokenizer.take_line_value()),
-            "DATE" => self.date = Some(Date::new(tokenizer, level + 1)),
-            "SUBM" => self.submitter_tag = Some(tokenizer.take_line_value()),
-            "SUBN" => self.submission_tag = Some(tokenizer.take_line_value()),
-            "FILE" => self.filename = Some(tokenizer.take_line_value()),
-            "COPR" => self.copyright = Some(tokenizer.take_continued_text(level + 1)),
-            "CHAR" => self.encoding = Some(Encoding::new(tokenizer, level + 1)),
-            "LANG" => self.language = Some(tokenizer.take_line_value()),
-            "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-            "PLAC" => self.place = Some(HeadPlac::new(tokenizer, level + 1)),
-            _ => panic!("{} Unhandled Header Tag: {}", tokenizer.debug(), tag),
+        tokenizer.next_token()?;
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "GEDC" => self.gedcom = Some(GedcomMeta::new(tokenizer, level + 1)?),
+                "SOUR" => self.source = Some(HeadSour::new(tokenizer, level + 1)?),
+                "DEST" => self.destination = Some(tokenizer.take_line_value()?),
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "SUBM" => self.submitter_tag = Some(tokenizer.take_line_value()?),
+                "SUBN" => self.submission_tag = Some(tokenizer.take_line_value()?),
+                "FILE" => self.filename = Some(tokenizer.take_line_value()?),
+                "COPR" => self.copyright = Some(tokenizer.take_continued_text(level + 1)?),
+                "CHAR" => self.encoding = Some(Encoding::new(tokenizer, level + 1)?),
+                "LANG" => self.language = Some(tokenizer.take_line_value()?),
+                "NOTE" => self.add_note(Note::new(tokenizer, level + 1)?),
+                "PLAC" => self.place = Some(HeadPlac::new(tokenizer, level + 1)?),
+                "SCHMA" => self.schema = Some(HeadSchema::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Header", tag)),
+            }
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -132,52 +322,57 @@ impl Parser for Header {
 ///     0 TRLR";
 ///
 /// let mut ged = GedcomDocument::new(sample.chars());
-/// let data = ged.parse_document();
+/// let data = ged.parse_document().unwrap();
 ///
 /// let head_gedc = data.header.unwrap().gedcom.unwrap();
 /// assert_eq!(head_gedc.version.unwrap(), "5.5");
 /// assert_eq!(head_gedc.form.unwrap(), "LINEAGE-LINKED");
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct GedcomMeta {
     /// tag: VERS
     pub version: Option<String>,
     /// tag: FORM; see Gedcom 5.5.1 specification, p. 50
     pub form: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl GedcomMeta {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> GedcomMeta {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<GedcomMeta, GedcomError> {
         let mut gedc = GedcomMeta::default();
-        gedc.parse(tokenizer, level);
-        gedc
+        gedc.parse(tokenizer, level)?;
+        Ok(gedc)
     }
 }
 
 impl Parser for GedcomMeta {
     /// parse handles parsing GEDC tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip GEDC tag
-        tokenizer.next_token();
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "VERS" => self.version = Some(tokenizer.take_line_value()),
-            // this is the only value that makes sense. warn them otherwise.
-            "FORM" => {
-                let form = tokenizer.take_line_value();
-                if &form.to_uppercase() != "LINEAGE-LINKED" {
-                    println!(
-                        "WARNING: Unrecognized GEDCOM form. Expected LINEAGE-LINKED, found {}",
-                        form
-                    );
+        tokenizer.next_token()?;
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "VERS" => self.version = Some(tokenizer.take_line_value()?),
+                // this is the only value that makes sense. warn them otherwise.
+                "FORM" => {
+                    let (form, span) = tokenizer.take_line_value_with_span()?;
+                    if &form.to_uppercase() != "LINEAGE-LINKED" {
+                        tokenizer.warn(GedcomError::InvalidValue {
+                            span,
+                            tag: "FORM",
+                            value: form.clone(),
+                        });
+                    }
+                    self.form = Some(form);
                 }
-                self.form = Some(form);
+                _ => return Err(tokenizer.unhandled_tag("GEDC", tag)),
             }
-            _ => panic!("{} Unhandled GEDC Tag: {}", tokenizer.debug(), tag),
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -197,7 +392,7 @@ impl Parser for GedcomMeta {
 ///     0 TRLR";
 
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 
 /// let h_char = data.header.unwrap().encoding.unwrap();
 /// assert_eq!(h_char.value.unwrap(), "ASCII");
@@ -206,33 +401,37 @@ impl Parser for GedcomMeta {
 ///     "Version number of ASCII (whatever it means)"
 /// );
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Encoding {
     pub value: Option<String>,
     /// tag: VERS
     pub version: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Encoding {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Encoding {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Encoding, GedcomError> {
         let mut chars = Encoding::default();
-        chars.parse(tokenizer, level);
-        chars
+        chars.parse(tokenizer, level)?;
+        Ok(chars)
     }
 }
 
 impl Parser for Encoding {
     /// parse handles the parsing of the CHARS tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "VERS" => self.version = Some(tokenizer.take_line_value()),
-            _ => panic!("{} Unhandled CHAR Tag: {}", tokenizer.debug(), tag),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "VERS" => self.version = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("CHAR", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -252,21 +451,20 @@ impl Parser for Encoding {
 ///     1 SOUR SOURCE_NAME\n\
 ///     2 VERS Version number of source-program\n\
 ///     2 NAME Name of source-program\n\
+///     2 CORP Corporation name\n\
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let sour = data.header.unwrap().source.unwrap();
-/// assert_eq!(sour.value.unwrap(), "SOURCE_NAME");
-///
-/// let vers = sour.version.unwrap();
-/// assert_eq!(vers, "Version number of source-program");
-///
-/// let name = sour.name.unwrap();
-/// assert_eq!(name, "Name of source-program");
+/// assert_eq!(sour.product(), Some("SOURCE_NAME"));
+/// assert_eq!(sour.version(), Some("Version number of source-program"));
+/// assert_eq!(sour.name(), Some("Name of source-program"));
+/// assert_eq!(sour.corporation_name(), Some("Corporation name"));
+/// assert!(sour.data().is_none());
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct HeadSour {
     pub value: Option<String>,
@@ -278,30 +476,70 @@ pub struct HeadSour {
     pub corporation: Option<Corporation>,
     /// tag: DATA
     pub data: Option<HeadSourData>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl HeadSour {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> HeadSour {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<HeadSour, GedcomError> {
         let mut head_sour = HeadSour::default();
-        head_sour.parse(tokenizer, level);
-        head_sour
+        head_sour.parse(tokenizer, level)?;
+        Ok(head_sour)
+    }
+
+    /// The identifier of the product which produced the document, e.g. "GRAMPS" (tag: SOUR)
+    #[must_use]
+    pub fn product(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// The version of the product (tag: VERS)
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The name of the product (tag: NAME)
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The corporation which owns the product, with its contact details (tag: CORP)
+    #[must_use]
+    pub fn corporation(&self) -> Option<&Corporation> {
+        self.corporation.as_ref()
+    }
+
+    /// The name of the corporation which owns the product (tag: CORP)
+    #[must_use]
+    pub fn corporation_name(&self) -> Option<&str> {
+        self.corporation.as_ref()?.value.as_deref()
+    }
+
+    /// The data source the document was exported from, see `HeadSourData` (tag: DATA)
+    #[must_use]
+    pub fn data(&self) -> Option<&HeadSourData> {
+        self.data.as_ref()
     }
 }
 
 impl Parser for HeadSour {
     /// parse handles the SOUR tag in a header
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "VERS" => self.version = Some(tokenizer.take_line_value()),
-            "NAME" => self.name = Some(tokenizer.take_line_value()),
-            "CORP" => self.corporation = Some(Corporation::new(tokenizer, level + 1)),
-            "DATA" => self.data = Some(HeadSourData::new(tokenizer, level + 1)),
-            _ => panic!("{} Unhandled CHAR Tag: {}", tokenizer.debug(), tag),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "VERS" => self.version = Some(tokenizer.take_line_value()?),
+                "NAME" => self.name = Some(tokenizer.take_line_value()?),
+                "CORP" => self.corporation = Some(Corporation::new(tokenizer, level + 1)?),
+                "DATA" => self.data = Some(HeadSourData::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("CHAR", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -324,20 +562,17 @@ impl Parser for HeadSour {
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let sour = data.header.unwrap().source.unwrap();
-/// assert_eq!(sour.value.unwrap(), "SOURCE_NAME");
+/// assert_eq!(sour.product(), Some("SOURCE_NAME"));
 ///
-/// let sour_data = sour.data.unwrap();
-/// assert_eq!(sour_data.value.unwrap(), "Name of source data");
-/// assert_eq!(sour_data.date.unwrap().value.unwrap(), "1 JAN 1998");
-/// assert_eq!(
-///     sour_data.copyright.unwrap(),
-///     "Copyright of source data"
-/// );
+/// let sour_data = sour.data().unwrap();
+/// assert_eq!(sour_data.name(), Some("Name of source data"));
+/// assert_eq!(sour_data.date().unwrap().value.as_deref(), Some("1 JAN 1998"));
+/// assert_eq!(sour_data.copyright(), Some("Copyright of source data"));
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct HeadSourData {
     pub value: Option<String>,
@@ -345,32 +580,50 @@ pub struct HeadSourData {
     pub date: Option<Date>,
     /// tag: COPR
     pub copyright: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl HeadSourData {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> HeadSourData {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<HeadSourData, GedcomError> {
         let mut head_sour_data = HeadSourData::default();
-        head_sour_data.parse(tokenizer, level);
-        head_sour_data
+        head_sour_data.parse(tokenizer, level)?;
+        Ok(head_sour_data)
+    }
+
+    /// The name of the data source (tag: DATA)
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// When the data source was published or created (tag: DATE)
+    #[must_use]
+    pub fn date(&self) -> Option<&Date> {
+        self.date.as_ref()
+    }
+
+    /// The copyright statement of the data source (tag: COPR)
+    #[must_use]
+    pub fn copyright(&self) -> Option<&str> {
+        self.copyright.as_deref()
     }
 }
 
 impl Parser for HeadSourData {
     /// parse parses the DATA tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "DATE" => self.date = Some(Date::new(tokenizer, level + 1)),
-            "COPR" => self.copyright = Some(tokenizer.take_continued_text(level + 1)),
-            _ => panic!(
-                "{} unhandled DATA tag in header: {}",
-                tokenizer.debug(),
-                tag
-            ),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "COPR" => self.copyright = Some(tokenizer.take_continued_text(level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("DATA", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -390,7 +643,7 @@ impl Parser for HeadSourData {
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let h_plac = data.header.unwrap().place.unwrap();
 /// assert_eq!(h_plac.form[0], "City");
@@ -398,7 +651,7 @@ impl Parser for HeadSourData {
 /// assert_eq!(h_plac.form[2], "State");
 /// assert_eq!(h_plac.form[3], "Country");
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct HeadPlac {
     /// form (tag: FORM) is a comma-separated list of jurisdictional titles (e.g. City, County,
@@ -406,9 +659,16 @@ pub struct HeadPlac {
     /// structure. As with PLAC, this shall be ordered from lowest to highest jurisdiction.
     /// See https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#PLAC-FORM
     pub form: Vec<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl HeadPlac {
+    /// The jurisdictional titles, from lowest to highest jurisdiction
+    #[must_use]
+    pub fn jurisdictional_titles(&self) -> &[String] {
+        &self.form
+    }
+
     pub fn push_jurisdictional_title(&mut self, title: String) {
         self.form.push(title);
     }
@@ -425,37 +685,121 @@ impl HeadPlac {
 }
 
 impl HeadPlac {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> HeadPlac {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<HeadPlac, GedcomError> {
         let mut head_plac = HeadPlac::default();
-        head_plac.parse(tokenizer, level);
-        head_plac
+        head_plac.parse(tokenizer, level)?;
+        Ok(head_plac)
     }
 }
 
 impl Parser for HeadPlac {
     /// parse handles the PLAC tag when present in header
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // In the header, PLAC should have no payload. See
         // https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#HEAD-PLAC
-        tokenizer.next_token();
+        tokenizer.next_token()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "FORM" => {
-                let form = tokenizer.take_line_value();
-                let jurisdictional_titles = form.split(",");
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "FORM" => {
+                    let form = tokenizer.take_line_value()?;
+                    let jurisdictional_titles = form.split(",");
 
-                for t in jurisdictional_titles {
-                    let v = t.trim();
-                    self.push_jurisdictional_title(v.to_string());
+                    for t in jurisdictional_titles {
+                        let v = t.trim();
+                        self.push_jurisdictional_title(v.to_string());
+                    }
                 }
+                _ => return Err(tokenizer.unhandled_tag("PLAC", tag)),
             }
-            _ => panic!(
-                "{} Unhandled PLAC tag in header: {}",
-                tokenizer.debug(),
-                tag
-            ),
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
+
+/// `HeadSchema` (tag: SCHMA) is a container for the extension tags used in the document, mapping
+/// each one to the URI which defines it.
+/// See <https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#SCHMA>
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 7.0\n\
+///     1 SCHMA\n\
+///     2 TAG _SKYPEID http://xmlns.com/foaf/0.1/skypeID\n\
+///     2 TAG _MEMBER http://xmlns.com/foaf/0.1/member\n\
+///     0 TRLR";
+///
+/// let mut doc = GedcomDocument::new(sample.chars());
+/// let data = doc.parse_document().unwrap();
+///
+/// let schema = data.header.unwrap().schema.unwrap();
+/// assert_eq!(schema.tags.len(), 2);
+/// assert_eq!(schema.tags[1].tag, "_MEMBER");
+/// assert_eq!(schema.uri("_SKYPEID"), Some("http://xmlns.com/foaf/0.1/skypeID"));
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct HeadSchema {
+    /// tag: TAG
+    pub tags: Vec<ExtensionTag>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl HeadSchema {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<HeadSchema, GedcomError> {
+        let mut schema = HeadSchema::default();
+        schema.parse(tokenizer, level)?;
+        Ok(schema)
+    }
+
+    /// The URI defining an extension tag, if the schema lists it
+    #[must_use]
+    pub fn uri(&self, tag: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|ext| ext.tag == tag)
+            .map(|ext| ext.uri.as_str())
+    }
+}
+
+impl Parser for HeadSchema {
+    /// parse handles the SCHMA tag in a header
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        // SCHMA has no payload
+        tokenizer.next_token()?;
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "TAG" => {
+                    let value = tokenizer.take_line_value()?;
+                    let mut parts = value.splitn(2, ' ');
+                    let tag = parts.next().unwrap_or_default().to_string();
+                    let uri = parts.next().unwrap_or_default().trim().to_string();
+                    self.tags.push(ExtensionTag { tag, uri });
+                }
+                _ => return Err(tokenizer.unhandled_tag("SCHMA", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
+
+/// `ExtensionTag` is an extension tag documented in the header schema, along with the URI which
+/// defines it.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct ExtensionTag {
+    /// The extension tag, e.g. `_SKYPEID`
+    pub tag: String,
+    /// The URI defining the tag
+    pub uri: String,
+}
@@ -1,13 +1,19 @@
 use crate::{
+    duplicates::event_date,
+    intern::SharedStr,
     parse_subset,
     tokenizer::{Token, Tokenizer},
     types::{
-        event::HasEvents, ChangeDate, Date, EventDetail, MultimediaRecord, Note, SourceCitation,
-        UserDefinedDataset, Xref,
+        event::HasEvents, Age, ChangeDate, Date, Event, EventDetail, ExternalId, ExternalIdKind,
+        MultimediaRecord, Note, Pointer, SourceCitation, UserDefinedDataset, Xref,
     },
-    Parser,
+    util::unescape_at_signs,
+    GedcomError, Parser,
 };
 
+#[cfg(feature = "lds")]
+use crate::types::{LdsOrdinance, OrdinanceType};
+
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
 
@@ -30,7 +36,7 @@ use serde::{Deserialize, Serialize};
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let indi = &data.individuals[0];
 /// assert_eq!(indi.xref.as_ref().unwrap(), "@PERSON1@");
@@ -38,11 +44,13 @@ use serde::{Deserialize, Serialize};
 /// assert_eq!(indi.sex.as_ref().unwrap().value.to_string(), "Male");
 /// ```
 ///
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Individual {
     pub xref: Option<Xref>,
     pub name: Option<Name>,
+    /// tag: NAME, the names after the first in file order, e.g. a married name or an alias
+    pub alternate_names: Vec<Name>,
     pub sex: Option<Gender>,
     pub families: Vec<FamilyLink>,
     pub attributes: Vec<AttributeDetail>,
@@ -50,18 +58,46 @@ pub struct Individual {
     pub events: Vec<EventDetail>,
     pub multimedia: Vec<MultimediaRecord>,
     pub last_updated: Option<String>,
-    pub note: Option<Note>,
+    pub notes: Vec<Note>,
     pub change_date: Option<ChangeDate>,
+    /// tag: CREA, when the record was created, from GEDCOM 7
+    pub created: Option<ChangeDate>,
+    /// tags: BAPL, CONL, ENDL, INIL and SLGC
+    #[cfg(feature = "lds")]
+    pub lds_ordinances: Vec<LdsOrdinance>,
+    /// tag: UID, ids of the record which stay the same across files, from GEDCOM 7
+    pub uids: Vec<String>,
+    /// tags: EXID, `_FSFTID`, `_APID`, `_UID` and `_GUID`, the ids of the individual at other
+    /// services
+    pub external_ids: Vec<ExternalId>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Individual {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<Xref>) -> Individual {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<Xref>,
+    ) -> Result<Individual, GedcomError> {
         let mut indi = Individual::default();
         indi.xref = xref;
-        indi.parse(tokenizer, level);
-        indi
+        indi.parse(tokenizer, level)?;
+        Ok(indi)
+    }
+
+    /// Adds a NAME to the individual: the first one becomes `name`, later ones are kept in
+    /// `alternate_names`.
+    pub fn add_name(&mut self, name: Name) {
+        if self.name.is_none() {
+            self.name = Some(name);
+        } else {
+            self.alternate_names.push(name);
+        }
+    }
+
+    /// Every name of the individual, `name` followed by `alternate_names`
+    pub fn names(&self) -> impl Iterator<Item = &Name> {
+        self.name.iter().chain(&self.alternate_names)
     }
 
     pub fn add_family(&mut self, link: FamilyLink) {
@@ -88,6 +124,68 @@ impl Individual {
     pub fn add_attribute(&mut self, attribute: AttributeDetail) {
         self.attributes.push(attribute);
     }
+
+    /// The first id of the kind given to the individual, see `ExternalId`
+    #[must_use]
+    pub fn external_id(&self, kind: ExternalIdKind) -> Option<&str> {
+        self.external_ids
+            .iter()
+            .find(|id| id.kind == kind)
+            .map(|id| id.value.as_str())
+    }
+
+    /// The age of the individual at the first dated event of the kind, see `Age::between`. When
+    /// the birth isn't dated, the christening or baptism stands in for it and the age is
+    /// approximate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{types::Event, GedcomDocument};
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME George /Washington/\n\
+    ///     1 BIRT\n\
+    ///     2 DATE 22 FEB 1732\n\
+    ///     1 MARR\n\
+    ///     2 DATE 6 JAN 1759\n\
+    ///     1 DEAT\n\
+    ///     2 DATE 14 DEC 1799\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let george = &data.individuals[0];
+    /// assert_eq!(george.age_at(&Event::Marriage).unwrap().to_string(), "26y 10m 15d");
+    /// assert_eq!(george.lifespan().unwrap().years, Some(67));
+    /// assert_eq!(george.age_at(&Event::Burial), None);
+    /// ```
+    #[must_use]
+    pub fn age_at(&self, event: &Event) -> Option<Age> {
+        let (birth, stand_in) = match event_date(self, &Event::Birth) {
+            Some(birth) => (birth, false),
+            None => (
+                event_date(self, &Event::Christening)
+                    .or_else(|| event_date(self, &Event::Baptism))?,
+                true,
+            ),
+        };
+        let mut age = Age::between(&birth, &event_date(self, event)?)?;
+        age.approximate |= stand_in;
+        Some(age)
+    }
+
+    /// The age of the individual at their death, or else their burial, see `age_at`
+    #[must_use]
+    pub fn lifespan(&self) -> Option<Age> {
+        self.age_at(&Event::Death).or_else(|| {
+            let mut age = self.age_at(&Event::Burial)?;
+            age.approximate = true;
+            Some(age)
+        })
+    }
 }
 
 impl HasEvents for Individual {
@@ -101,43 +199,79 @@ impl HasEvents for Individual {
 
 impl Parser for Individual {
     /// parse handles the INDI top-level tag
-    fn parse(&mut self, tokenizer: &mut crate::tokenizer::Tokenizer, level: u8) {
+    fn parse(
+        &mut self,
+        tokenizer: &mut crate::tokenizer::Tokenizer,
+        level: u8,
+    ) -> Result<(), GedcomError> {
         // skip over INDI tag name
-        tokenizer.next_token();
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            // TODO handle xref
-            "NAME" => self.name = Some(Name::new(tokenizer, level + 1)),
-            "SEX" => self.sex = Some(Gender::new(tokenizer, level + 1)),
-            "ADOP" | "BIRT" | "BAPM" | "BARM" | "BASM" | "BLES" | "BURI" | "CENS" | "CHR"
-            | "CHRA" | "CONF" | "CREM" | "DEAT" | "EMIG" | "FCOM" | "GRAD" | "IMMI" | "NATU"
-            | "ORDN" | "RETI" | "RESI" | "PROB" | "WILL" | "EVEN" | "MARR" => {
-                self.add_event(EventDetail::new(tokenizer, level + 1, tag));
-            }
-            "CAST" | "DSCR" | "EDUC" | "IDNO" | "NATI" | "NCHI" | "NMR" | "OCCU" | "PROP"
-            | "RELI" | "SSN" | "TITL" | "FACT" => {
-                // RESI should be an attribute or an event?
-                self.add_attribute(AttributeDetail::new(tokenizer, level + 1, tag));
-            }
-            "FAMC" | "FAMS" => {
-                self.add_family(FamilyLink::new(tokenizer, level + 1, tag));
-            }
-            "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)),
-            "SOUR" => {
-                self.add_source_citation(SourceCitation::new(tokenizer, level + 1));
+        tokenizer.next_token()?;
+
+        // LDS ordinances, kept as custom data without the lds feature
+        #[cfg(not(feature = "lds"))]
+        let mut ordinances = Vec::new();
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                // TODO handle xref
+                "NAME" => self.add_name(Name::new(tokenizer, level + 1)?),
+                "SEX" => self.sex = Some(Gender::new(tokenizer, level + 1)?),
+                "ADOP" | "BIRT" | "BAPM" | "BARM" | "BASM" | "BLES" | "BURI" | "CENS" | "CHR"
+                | "CHRA" | "CONF" | "CREM" | "DEAT" | "EMIG" | "FCOM" | "GRAD" | "IMMI"
+                | "NATU" | "ORDN" | "RETI" | "RESI" | "PROB" | "WILL" | "EVEN" | "MARR" => {
+                    self.add_event(EventDetail::new(tokenizer, level + 1, tag)?);
+                }
+                "CAST" | "DSCR" | "EDUC" | "IDNO" | "NATI" | "NCHI" | "NMR" | "OCCU" | "PROP"
+                | "RELI" | "SSN" | "TITL" | "FACT" => {
+                    // RESI should be an attribute or an event?
+                    self.add_attribute(AttributeDetail::new(tokenizer, level + 1, tag)?);
+                }
+                "FAMC" | "FAMS" => {
+                    self.add_family(FamilyLink::new(tokenizer, level + 1, tag)?);
+                }
+                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "CREA" => self.created = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "UID" => self.uids.push(tokenizer.take_line_value()?),
+                "EXID" => self
+                    .external_ids
+                    .push(ExternalId::new(tokenizer, level + 1)?),
+                "SOUR" => SourceCitation::parse_into(&mut self.source, tokenizer, level + 1)?,
+                "OBJE" => self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, None)?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                #[cfg(feature = "lds")]
+                "BAPL" | "CONL" | "ENDL" | "INIL" | "SLGC" => {
+                    let ordinance = OrdinanceType::from_tag(tag).unwrap();
+                    self.lds_ordinances
+                        .push(LdsOrdinance::new(tokenizer, level + 1, ordinance)?);
+                }
+                #[cfg(not(feature = "lds"))]
+                "BAPL" | "CONL" | "ENDL" | "INIL" | "SLGC" => {
+                    ordinances.push(Box::new(UserDefinedDataset::new(
+                        tokenizer,
+                        level + 1,
+                        tag,
+                    )?));
+                }
+                _ if tokenizer.event_type(tag).is_some() => {
+                    self.add_event(EventDetail::new(tokenizer, level + 1, tag)?);
+                }
+                _ => return Err(tokenizer.unhandled_tag("Individual", tag)),
             }
-            "OBJE" => self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, None)),
-            "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-            _ => panic!("{} Unhandled Individual Tag: {}", tokenizer.debug(), tag),
+            Ok(())
         };
 
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        let mut vendor_ids = ExternalId::take_from(&mut self.custom_data);
+        self.external_ids.append(&mut vendor_ids);
+        #[cfg(not(feature = "lds"))]
+        self.custom_data.append(&mut ordinances);
+
+        Ok(())
     }
 }
 
 /// GenderType is a set of enumerated values that indicate the sex of an individual at birth. See
 /// 5.5 specification, p. 61; https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#SEX
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum GenderType {
     /// Tag 'M'
@@ -150,6 +284,32 @@ pub enum GenderType {
     Unknown,
 }
 
+impl GenderType {
+    /// The SEX payload of the gender, e.g. "M"
+    #[must_use]
+    pub fn tag(&self) -> &'static str {
+        match self {
+            GenderType::Male => "M",
+            GenderType::Female => "F",
+            GenderType::Nonbinary => "X",
+            GenderType::Unknown => "U",
+        }
+    }
+
+    /// The gender of a SEX payload, ignoring case. An empty payload, which some applications
+    /// export, is `Unknown`.
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<GenderType> {
+        match tag.trim().to_ascii_uppercase().as_str() {
+            "M" => Some(GenderType::Male),
+            "F" => Some(GenderType::Female),
+            "X" => Some(GenderType::Nonbinary),
+            "U" | "" => Some(GenderType::Unknown),
+            _ => None,
+        }
+    }
+}
+
 impl ToString for GenderType {
     fn to_string(&self) -> String {
         format!("{:?}", self)
@@ -177,36 +337,52 @@ impl ToString for GenderType {
 ///     3 PAGE Page
 ///     4 CONC : 132
 ///     3 _MYOWNTAG This is a non-standard tag. Not recommended but allowed
+///     2 NOTE Recorded as male in the baptismal register
+///     0 @PERSON2@ INDI\n\
+///     1 SEX X\n\
+///     0 @PERSON3@ INDI\n\
+///     1 SEX\n\
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let sex = data.individuals[0].sex.as_ref().unwrap();
 /// assert_eq!(sex.value.to_string(), "Male");
 /// assert_eq!(sex.fact.as_ref().unwrap(), "A fact about an individual's gender");
 /// assert_eq!(sex.sources[0].xref, "@CITATION1@");
 /// assert_eq!(sex.sources[0].page.as_ref().unwrap(), "Page: 132");
+/// assert_eq!(
+///     sex.notes[0].value.as_ref().unwrap(),
+///     "Recorded as male in the baptismal register"
+/// );
+///
+/// let nonbinary = data.individuals[1].sex.as_ref().unwrap();
+/// assert_eq!(nonbinary.value.to_string(), "Nonbinary");
+/// let missing = data.individuals[2].sex.as_ref().unwrap();
+/// assert_eq!(missing.value.to_string(), "Unknown");
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Gender {
     pub value: GenderType,
     pub fact: Option<String>,
     pub sources: Vec<SourceCitation>,
+    pub notes: Vec<Note>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Gender {
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Gender {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Gender, GedcomError> {
         let mut sex = Gender {
             value: GenderType::Unknown,
             fact: None,
             sources: Vec::new(),
+            notes: Vec::new(),
             custom_data: Vec::new(),
         };
-        sex.parse(tokenizer, level);
-        sex
+        sex.parse(tokenizer, level)?;
+        Ok(sex)
     }
 
     pub fn add_source_citation(&mut self, sour: SourceCitation) {
@@ -215,31 +391,28 @@ impl Gender {
 }
 
 impl Parser for Gender {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
-
-        if let Token::LineValue(gender_string) = &tokenizer.current_token {
-            self.value = match gender_string.as_str() {
-                "M" => GenderType::Male,
-                "F" => GenderType::Female,
-                "X" => GenderType::Nonbinary,
-                "U" => GenderType::Unknown,
-                _ => panic!(
-                    "{} Unknown gender value {} ({})",
-                    tokenizer.debug(),
-                    gender_string,
-                    level
-                ),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
+
+        if let Token::LineValue(gender_string) = tokenizer.current_token {
+            self.value = match GenderType::from_tag(gender_string) {
+                Some(value) => value,
+                None => return Err(tokenizer.invalid_value("SEX", gender_string)),
             };
-            tokenizer.next_token();
+            tokenizer.next_token()?;
         }
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "FACT" => self.fact = Some(tokenizer.take_continued_text(level + 1)),
-            "SOUR" => self.add_source_citation(SourceCitation::new(tokenizer, level + 1)),
-            _ => panic!("{}, Unhandled Gender tag: {}", tokenizer.debug(), tag),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "FACT" => self.fact = Some(tokenizer.take_continued_text(level + 1)?),
+                "SOUR" => SourceCitation::parse_into(&mut self.sources, tokenizer, level + 1)?,
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Gender", tag)),
+            }
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -253,6 +426,17 @@ pub enum FamilyLinkType {
     Child,
 }
 
+impl FamilyLinkType {
+    /// The GEDCOM tag of the link, FAMS or FAMC
+    #[must_use]
+    pub fn tag(&self) -> &'static str {
+        match self {
+            FamilyLinkType::Spouse => "FAMS",
+            FamilyLinkType::Child => "FAMC",
+        }
+    }
+}
+
 impl ToString for FamilyLinkType {
     fn to_string(&self) -> String {
         format!("{:?}", self)
@@ -274,6 +458,31 @@ pub enum Pedigree {
     Sealing,
 }
 
+impl Pedigree {
+    /// Reads a PEDI payload, ignoring case
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Pedigree> {
+        match code.to_lowercase().as_str() {
+            "adopted" => Some(Pedigree::Adopted),
+            "birth" => Some(Pedigree::Birth),
+            "foster" => Some(Pedigree::Foster),
+            "sealing" => Some(Pedigree::Sealing),
+            _ => None,
+        }
+    }
+
+    /// The PEDI payload of the pedigree, e.g. "birth"
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Pedigree::Adopted => "adopted",
+            Pedigree::Birth => "birth",
+            Pedigree::Foster => "foster",
+            Pedigree::Sealing => "sealing",
+        }
+    }
+}
+
 impl ToString for Pedigree {
     fn to_string(&self) -> String {
         format!("{:?}", self)
@@ -296,6 +505,29 @@ pub enum ChildLinkStatus {
     Proven,
 }
 
+impl ChildLinkStatus {
+    /// Reads a STAT payload, ignoring case
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<ChildLinkStatus> {
+        match code.to_lowercase().as_str() {
+            "challenged" => Some(ChildLinkStatus::Challenged),
+            "disproven" => Some(ChildLinkStatus::Disproven),
+            "proven" => Some(ChildLinkStatus::Proven),
+            _ => None,
+        }
+    }
+
+    /// The STAT payload of the status, e.g. "proven"
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            ChildLinkStatus::Challenged => "challenged",
+            ChildLinkStatus::Disproven => "disproven",
+            ChildLinkStatus::Proven => "proven",
+        }
+    }
+}
+
 impl ToString for ChildLinkStatus {
     fn to_string(&self) -> String {
         format!("{:?}", self)
@@ -315,17 +547,101 @@ pub enum AdoptedByWhichParent {
     Both,
 }
 
+impl AdoptedByWhichParent {
+    /// Reads an ADOP payload, ignoring case
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<AdoptedByWhichParent> {
+        match code.to_lowercase().as_str() {
+            "husb" => Some(AdoptedByWhichParent::Husband),
+            "wife" => Some(AdoptedByWhichParent::Wife),
+            "both" => Some(AdoptedByWhichParent::Both),
+            _ => None,
+        }
+    }
+
+    /// The ADOP payload of the parent, e.g. "HUSB"
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            AdoptedByWhichParent::Husband => "HUSB",
+            AdoptedByWhichParent::Wife => "WIFE",
+            AdoptedByWhichParent::Both => "BOTH",
+        }
+    }
+}
+
 impl ToString for AdoptedByWhichParent {
     fn to_string(&self) -> String {
         format!("{:?}", self)
     }
 }
 
+/// `ParentRelationship` is how a child is related to one parent of a family. Family Tree Maker
+/// records it separately for the father and the mother, in the `_FREL` and `_MREL` tags under a
+/// FAMC link.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum ParentRelationship {
+    Natural,
+    Adopted,
+    Step,
+    Foster,
+    Guardian,
+    Related,
+    Sealed,
+    Private,
+    Unknown,
+}
+
+impl ParentRelationship {
+    /// Reads a `_FREL` or `_MREL` payload, ignoring case
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<ParentRelationship> {
+        match code.to_lowercase().as_str() {
+            "natural" | "birth" => Some(ParentRelationship::Natural),
+            "adopted" => Some(ParentRelationship::Adopted),
+            "step" => Some(ParentRelationship::Step),
+            "foster" => Some(ParentRelationship::Foster),
+            "guardian" => Some(ParentRelationship::Guardian),
+            "related" => Some(ParentRelationship::Related),
+            "sealed" | "sealing" => Some(ParentRelationship::Sealed),
+            "private" => Some(ParentRelationship::Private),
+            "unknown" => Some(ParentRelationship::Unknown),
+            _ => None,
+        }
+    }
+
+    /// The `_FREL` or `_MREL` payload of the relationship, e.g. "Natural"
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParentRelationship::Natural => "Natural",
+            ParentRelationship::Adopted => "Adopted",
+            ParentRelationship::Step => "Step",
+            ParentRelationship::Foster => "Foster",
+            ParentRelationship::Guardian => "Guardian",
+            ParentRelationship::Related => "Related",
+            ParentRelationship::Sealed => "Sealed",
+            ParentRelationship::Private => "Private",
+            ParentRelationship::Unknown => "Unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for ParentRelationship {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
 /// FamilyLink indicates the normal lineage links through the use of pointers from the individual
 /// to a family through either the FAMC tag or the FAMS tag. The FAMC tag provides a pointer to a
 /// family where this person is a child. The FAMS tag provides a pointer to a family where this
 /// person is a spouse or parent. See GEDCOM 5.5 spec, page 26.
 ///
+/// A FAMC link also carries the status of the link (STAT), which parent adopted the child (ADOP),
+/// and, as written by Family Tree Maker, the relationship to each parent (`_FREL` and `_MREL`).
+///
 /// # Example
 ///
 /// ```
@@ -346,7 +662,7 @@ impl ToString for AdoptedByWhichParent {
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let famc = data.individuals[0].events[0].family_link.as_ref().unwrap();
 /// assert_eq!(famc.xref, "@ADOPTIVE_PARENTS@");
@@ -363,48 +679,54 @@ pub struct FamilyLink {
     pub pedigree_linkage_type: Option<Pedigree>,
     pub child_linkage_status: Option<ChildLinkStatus>,
     pub adopted_by: Option<AdoptedByWhichParent>,
-    pub note: Option<Note>,
+    /// tag: `_FREL`, the relationship to the husband of the family
+    pub father_relationship: Option<ParentRelationship>,
+    /// tag: `_MREL`, the relationship to the wife of the family
+    pub mother_relationship: Option<ParentRelationship>,
+    pub notes: Vec<Note>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl FamilyLink {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, tag: &str) -> FamilyLink {
-        let xref = tokenizer.take_line_value();
+    pub fn new(tokenizer: &mut Tokenizer, level: u8, tag: &str) -> Result<FamilyLink, GedcomError> {
         let link_type = match tag {
             "FAMC" => FamilyLinkType::Child,
             "FAMS" => FamilyLinkType::Spouse,
-            _ => panic!("Unrecognized family type tag: {}", tag),
+            _ => return Err(tokenizer.unhandled_tag("FamilyLink", tag)),
         };
+        let xref = tokenizer.take_line_value()?;
         let mut family_link = FamilyLink {
             xref,
             family_link_type: link_type,
             pedigree_linkage_type: None,
             child_linkage_status: None,
             adopted_by: None,
-            note: None,
+            father_relationship: None,
+            mother_relationship: None,
+            notes: Vec::new(),
             custom_data: Vec::new(),
         };
-        family_link.parse(tokenizer, level);
-        family_link
+        family_link.parse(tokenizer, level)?;
+        Ok(family_link)
+    }
+
+    /// The family linked to, or `Pointer::Void` for `@VOID@`
+    #[must_use]
+    pub fn pointer(&self) -> Pointer {
+        Pointer::new(&self.xref)
     }
 
     pub fn set_pedigree(&mut self, pedigree_text: &str) {
-        self.pedigree_linkage_type = match pedigree_text.to_lowercase().as_str() {
-            "adopted" => Some(Pedigree::Adopted),
-            "birth" => Some(Pedigree::Birth),
-            "foster" => Some(Pedigree::Foster),
-            "sealing" => Some(Pedigree::Sealing),
-            _ => panic!("Unrecognized FamilyLink.pedigree code: {}", pedigree_text),
-        };
+        match Pedigree::from_code(pedigree_text) {
+            Some(pedigree) => self.pedigree_linkage_type = Some(pedigree),
+            None => panic!("Unrecognized FamilyLink.pedigree code: {}", pedigree_text),
+        }
     }
 
     pub fn set_child_linkage_status(&mut self, status_text: &str) {
-        self.child_linkage_status = match status_text.to_lowercase().as_str() {
-            "challenged" => Some(ChildLinkStatus::Challenged),
-            "disproven" => Some(ChildLinkStatus::Disproven),
-            "proven" => Some(ChildLinkStatus::Proven),
-            _ => panic!(
+        match ChildLinkStatus::from_code(status_text) {
+            Some(status) => self.child_linkage_status = Some(status),
+            None => panic!(
                 "Unrecognized FamilyLink.child_linkage_status code: {}",
                 status_text
             ),
@@ -412,11 +734,9 @@ impl FamilyLink {
     }
 
     pub fn set_adopted_by_which_parent(&mut self, adopted_by_text: &str) {
-        self.adopted_by = match adopted_by_text.to_lowercase().as_str() {
-            "husb" => Some(AdoptedByWhichParent::Husband),
-            "wife" => Some(AdoptedByWhichParent::Wife),
-            "both" => Some(AdoptedByWhichParent::Both),
-            _ => panic!(
+        match AdoptedByWhichParent::from_code(adopted_by_text) {
+            Some(adopted_by) => self.adopted_by = Some(adopted_by),
+            None => panic!(
                 "Unrecognized FamilyLink.adopted_by code: {}",
                 adopted_by_text
             ),
@@ -425,15 +745,51 @@ impl FamilyLink {
 }
 
 impl Parser for FamilyLink {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "PEDI" => self.set_pedigree(tokenizer.take_line_value().as_str()),
-            "STAT" => self.set_child_linkage_status(&tokenizer.take_line_value().as_str()),
-            "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-            "ADOP" => self.set_adopted_by_which_parent(&tokenizer.take_line_value().as_str()),
-            _ => panic!("{} Unhandled FamilyLink Tag: {}", tokenizer.debug(), tag),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "PEDI" => {
+                    let pedigree = tokenizer.take_enumerated("PEDI", Pedigree::from_code)?;
+                    self.pedigree_linkage_type = Some(pedigree);
+                }
+                "STAT" => {
+                    let status = tokenizer.take_enumerated("STAT", ChildLinkStatus::from_code)?;
+                    self.child_linkage_status = Some(status);
+                }
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                "ADOP" => {
+                    let adopted_by =
+                        tokenizer.take_enumerated("ADOP", AdoptedByWhichParent::from_code)?;
+                    self.adopted_by = Some(adopted_by);
+                }
+                _ => return Err(tokenizer.unhandled_tag("FamilyLink", tag)),
+            }
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        let custom_data = parse_subset(tokenizer, level, handle_subset)?;
+
+        // relationships to each parent are kept as custom data when their value is unrecognized
+        for custom in custom_data {
+            let parent = match custom.tag.as_str() {
+                "_FREL" => &mut self.father_relationship,
+                "_MREL" => &mut self.mother_relationship,
+                _ => {
+                    self.custom_data.push(custom);
+                    continue;
+                }
+            };
+            let relationship = custom
+                .value
+                .as_deref()
+                .and_then(ParentRelationship::from_code);
+            match relationship {
+                Some(relationship) if parent.is_none() && custom.children.is_empty() => {
+                    *parent = Some(relationship);
+                }
+                _ => self.custom_data.push(custom),
+            }
+        }
+        Ok(())
     }
 }
 
@@ -462,62 +818,197 @@ impl Parser for FamilyLink {
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// let indi = &data.individuals[0];
 /// assert_eq!(indi.xref.as_ref().unwrap(), "@PERSON1@");
 /// assert_eq!(indi.name.as_ref().unwrap().value.as_ref().unwrap(), "John Doe");
 /// ```
 ///
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Name {
     pub value: Option<String>,
     pub given: Option<String>,
-    pub surname: Option<String>,
+    pub surname: Option<SharedStr>,
     pub prefix: Option<String>,
     pub surname_prefix: Option<String>,
-    pub note: Option<Note>,
+    pub notes: Vec<Note>,
     pub suffix: Option<String>,
     pub source: Vec<SourceCitation>,
+    /// tag: TYPE, the kind of name, e.g. "birth", "married" or "aka"
+    pub name_type: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Name {
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Name {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Name, GedcomError> {
         let mut name = Name {
             value: None,
             given: None,
             surname: None,
             prefix: None,
             surname_prefix: None,
-            note: None,
+            notes: Vec::new(),
             suffix: None,
             source: Vec::new(),
+            name_type: tokenizer.take_alias_type(),
+            custom_data: Vec::new(),
         };
-        name.parse(tokenizer, level);
-        name
+        name.parse(tokenizer, level)?;
+        Ok(name)
     }
 
     pub fn add_source_citation(&mut self, sour: SourceCitation) {
         self.source.push(sour);
     }
+
+    /// Splits the payload around the slashes delimiting the surname, see `NameParts`
+    #[must_use]
+    pub fn value_parts(&self) -> NameParts<'_> {
+        let value = self.value.as_deref().unwrap_or_default();
+        let mut pieces = value.splitn(3, '/').map(str::trim);
+        let mut next = || pieces.next().filter(|part| !part.is_empty());
+        NameParts {
+            given: next(),
+            surname: next(),
+            rest: next(),
+        }
+    }
+
+    /// The given name, from GIVN or else the part of the payload before the surname
+    #[must_use]
+    pub fn given_name(&self) -> Option<&str> {
+        match &self.given {
+            Some(given) => Some(given.trim()).filter(|given| !given.is_empty()),
+            None => self.value_parts().given,
+        }
+    }
+
+    /// The surname, from SURN or else the part of the payload between slashes
+    #[must_use]
+    pub fn family_name(&self) -> Option<&str> {
+        match &self.surname {
+            Some(surname) => Some(surname.trim()).filter(|surname| !surname.is_empty()),
+            None => self.value_parts().surname,
+        }
+    }
+
+    /// The payload without the slashes delimiting the surname, e.g. "John Doe" for "John /Doe/"
+    #[must_use]
+    pub fn full_name(&self) -> Option<String> {
+        let parts = self.value_parts();
+        let words: Vec<&str> = [parts.given, parts.surname, parts.rest]
+            .iter()
+            .flatten()
+            .flat_map(|part| part.split_whitespace())
+            .collect();
+        if words.is_empty() {
+            None
+        } else {
+            Some(words.join(" "))
+        }
+    }
+
+    /// Formats the given name and surname, see `NameFormat`. Missing pieces are left out, along
+    /// with the punctuation around them, and a name with neither gives an empty string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::{types::NameFormat, GedcomDocument};
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @I1@ INDI\n\
+    ///     1 NAME John Parke /Custis/ Jr.\n\
+    ///     0 @I2@ INDI\n\
+    ///     1 NAME /Washington/\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// let john = data.individuals[0].name.as_ref().unwrap();
+    /// assert_eq!(john.format(NameFormat::SurnameGiven), "Custis, John Parke");
+    /// assert_eq!(john.format(NameFormat::GivenSurname), "John Parke Custis");
+    /// assert_eq!(john.format(NameFormat::Initials), "J. P. C.");
+    /// assert_eq!(john.full_name().as_deref(), Some("John Parke Custis Jr."));
+    ///
+    /// let washington = data.individuals[1].name.as_ref().unwrap();
+    /// assert_eq!(washington.given_name(), None);
+    /// assert_eq!(washington.format(NameFormat::SurnameGiven), "Washington");
+    /// ```
+    #[must_use]
+    pub fn format(&self, format: NameFormat) -> String {
+        let given = self.given_name();
+        let surname = self.family_name();
+        match format {
+            NameFormat::SurnameGiven => match (surname, given) {
+                (Some(surname), Some(given)) => format!("{surname}, {given}"),
+                (surname, given) => surname.or(given).unwrap_or_default().to_string(),
+            },
+            NameFormat::GivenSurname => [given, surname]
+                .iter()
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" "),
+            NameFormat::Initials => [given, surname]
+                .iter()
+                .flatten()
+                .flat_map(|part| part.split_whitespace())
+                .filter_map(|word| word.chars().next())
+                .map(|initial| format!("{initial}."))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// `NameParts` are the pieces of the payload of a `Name`, split around the slashes delimiting the
+/// surname, e.g. "John", "Doe" and "Jr." for "John /Doe/ Jr.". A payload without slashes is all
+/// given name. Pieces are trimmed, and `None` when empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NameParts<'a> {
+    /// The text before the surname
+    pub given: Option<&'a str>,
+    /// The text between the slashes
+    pub surname: Option<&'a str>,
+    /// The text after the surname, e.g. a suffix
+    pub rest: Option<&'a str>,
+}
+
+/// `NameFormat` is the layout of a name written by `Name::format`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameFormat {
+    /// "Doe, John", for sorted lists
+    SurnameGiven,
+    /// "John Doe"
+    GivenSurname,
+    /// "J. D.", the initials of the given names and surname
+    Initials,
 }
 
 impl Parser for Name {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "GIVN" => self.given = Some(tokenizer.take_line_value()),
-            "NPFX" => self.prefix = Some(tokenizer.take_line_value()),
-            "NSFX" => self.suffix = Some(tokenizer.take_line_value()),
-            "SPFX" => self.surname_prefix = Some(tokenizer.take_line_value()),
-            "SURN" => self.surname = Some(tokenizer.take_line_value()),
-            "SOUR" => self.add_source_citation(SourceCitation::new(tokenizer, level + 1)),
-            "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-            _ => panic!("{} Unhandled Name Tag: {}", tokenizer.debug(), tag),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "GIVN" => self.given = Some(tokenizer.take_line_value()?),
+                "NPFX" => self.prefix = Some(tokenizer.take_line_value()?),
+                "NSFX" => self.suffix = Some(tokenizer.take_line_value()?),
+                "SPFX" => self.surname_prefix = Some(tokenizer.take_line_value()?),
+                "SURN" => self.surname = Some(tokenizer.take_shared_value()?),
+                "TYPE" => self.name_type = Some(tokenizer.take_line_value()?),
+                "SOUR" => SourceCitation::parse_into(&mut self.source, tokenizer, level + 1)?,
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Name", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -545,6 +1036,29 @@ pub enum IndividualAttribute {
     Fact,
 }
 
+impl IndividualAttribute {
+    /// The GEDCOM tag of the attribute, e.g. OCCU
+    #[must_use]
+    pub fn tag(&self) -> &'static str {
+        match self {
+            IndividualAttribute::CastName => "CAST",
+            IndividualAttribute::PhysicalDescription => "DSCR",
+            IndividualAttribute::ScholasticAchievement => "EDUC",
+            IndividualAttribute::NationalIDNumber => "IDNO",
+            IndividualAttribute::NationalOrTribalOrigin => "NATI",
+            IndividualAttribute::CountOfChildren => "NCHI",
+            IndividualAttribute::CountOfMarriages => "NMR",
+            IndividualAttribute::Occupation => "OCCU",
+            IndividualAttribute::Possessions => "PROP",
+            IndividualAttribute::ReligiousAffiliation => "RELI",
+            IndividualAttribute::ResidesAt => "RESI",
+            IndividualAttribute::SocialSecurityNumber => "SSN",
+            IndividualAttribute::NobilityTypeTitle => "TITL",
+            IndividualAttribute::Fact => "FACT",
+        }
+    }
+}
+
 impl ToString for IndividualAttribute {
     fn to_string(&self) -> String {
         format!("{:?}", self)
@@ -588,7 +1102,7 @@ impl ToString for IndividualAttribute {
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// assert_eq!(data.individuals.len(), 1);
 ///
@@ -603,37 +1117,42 @@ impl ToString for IndividualAttribute {
 /// assert_eq!(a_sour.data.as_ref().unwrap().date.as_ref().unwrap().value.as_ref().unwrap(), "31 DEC 1900");
 /// assert_eq!(a_sour.data.as_ref().unwrap().text.as_ref().unwrap().value.as_ref().unwrap(), "a sample text\nSample text continued here. The word TEST should not be broken!");
 /// assert_eq!(a_sour.certainty_assessment.as_ref().unwrap().to_string(), "Direct");
-/// assert_eq!(a_sour.note.as_ref().unwrap().value.as_ref().unwrap(), "A note\nNote continued here. The word TEST should not be broken!");
+/// assert_eq!(a_sour.notes[0].value.as_ref().unwrap(), "A note\nNote continued here. The word TEST should not be broken!");
 /// ```
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct AttributeDetail {
     pub attribute: IndividualAttribute,
     pub value: Option<String>,
-    pub place: Option<String>,
+    pub place: Option<SharedStr>,
     pub date: Option<Date>,
     pub sources: Vec<SourceCitation>,
-    pub note: Option<Note>,
+    pub notes: Vec<Note>,
     /// attribute_type handles the TYPE tag, a descriptive word or phrase used to further classify the
     /// parent event or attribute tag. This should be used to define what kind of identification
     /// number or fact classification is being defined.
     pub attribute_type: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl AttributeDetail {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, tag: &str) -> AttributeDetail {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        tag: &str,
+    ) -> Result<AttributeDetail, GedcomError> {
         let mut attribute = AttributeDetail {
             attribute: Self::from_tag(tag),
             place: None,
             value: None,
             date: None,
             sources: Vec::new(),
-            note: None,
-            attribute_type: None,
+            notes: Vec::new(),
+            attribute_type: tokenizer.take_alias_type(),
+            custom_data: Vec::new(),
         };
-        attribute.parse(tokenizer, level);
-        attribute
+        attribute.parse(tokenizer, level)?;
+        Ok(attribute)
     }
 
     pub fn from_tag(tag: &str) -> IndividualAttribute {
@@ -659,35 +1178,45 @@ impl AttributeDetail {
     pub fn add_source_citation(&mut self, sour: SourceCitation) {
         self.sources.push(sour);
     }
+
+    /// What the attribute is: the TYPE of a FACT, e.g. "Eye colour", or else the name of the
+    /// attribute, e.g. "Occupation"
+    #[must_use]
+    pub fn kind(&self) -> String {
+        match (&self.attribute, &self.attribute_type) {
+            (IndividualAttribute::Fact, Some(attribute_type)) => attribute_type.clone(),
+            _ => self.attribute.to_string(),
+        }
+    }
 }
 
 impl Parser for AttributeDetail {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
 
         let mut value = String::new();
 
-        if let Token::LineValue(val) = &tokenizer.current_token {
-            value.push_str(&val);
-            tokenizer.next_token();
+        if let Token::LineValue(val) = tokenizer.current_token {
+            value.push_str(&unescape_at_signs(val));
+            tokenizer.next_token()?;
         }
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "DATE" => self.date = Some(Date::new(tokenizer, level + 1)),
-            "SOUR" => self.add_source_citation(SourceCitation::new(tokenizer, level + 1)),
-            "PLAC" => self.place = Some(tokenizer.take_line_value()),
-            "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-            "TYPE" => self.attribute_type = Some(tokenizer.take_continued_text(level + 1)),
-            _ => panic!(
-                "{}, Unhandled AttributeDetail tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "SOUR" => SourceCitation::parse_into(&mut self.sources, tokenizer, level + 1)?,
+                "PLAC" => self.place = Some(tokenizer.take_shared_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                "TYPE" => self.attribute_type = Some(tokenizer.take_continued_text(level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("AttributeDetail", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
 
         if &value != "" {
             self.value = Some(value);
         }
+        Ok(())
     }
 }
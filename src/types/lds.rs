@@ -0,0 +1,198 @@
+//! Latter-day Saint temple ordinances, parsed with the `lds` feature. Without it the ordinance
+//! structures, and the temple fields of submission records, are kept as `UserDefinedDataset`s.
+
+use crate::{
+    intern::SharedStr,
+    parse_subset,
+    tokenizer::Tokenizer,
+    types::{Date, Note, SourceCitation, UserDefinedDataset, Xref},
+    GedcomError, Parser,
+};
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// `OrdinanceType` is the kind of an `LdsOrdinance`, see
+/// <https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#LDS_INDIVIDUAL_ORDINANCE>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum OrdinanceType {
+    /// tag: BAPL
+    Baptism,
+    /// tag: CONL
+    Confirmation,
+    /// tag: ENDL
+    Endowment,
+    /// tag: INIL, from GEDCOM 7
+    Initiatory,
+    /// tag: SLGC, sealing of a child to their parents
+    ChildSealing,
+    /// tag: SLGS, sealing of spouses, held by families
+    SpouseSealing,
+}
+
+impl OrdinanceType {
+    /// The GEDCOM tag of the ordinance, e.g. BAPL
+    #[must_use]
+    pub fn tag(&self) -> &'static str {
+        match self {
+            OrdinanceType::Baptism => "BAPL",
+            OrdinanceType::Confirmation => "CONL",
+            OrdinanceType::Endowment => "ENDL",
+            OrdinanceType::Initiatory => "INIL",
+            OrdinanceType::ChildSealing => "SLGC",
+            OrdinanceType::SpouseSealing => "SLGS",
+        }
+    }
+
+    /// The ordinance of a GEDCOM tag
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<OrdinanceType> {
+        match tag {
+            "BAPL" => Some(OrdinanceType::Baptism),
+            "CONL" => Some(OrdinanceType::Confirmation),
+            "ENDL" => Some(OrdinanceType::Endowment),
+            "INIL" => Some(OrdinanceType::Initiatory),
+            "SLGC" => Some(OrdinanceType::ChildSealing),
+            "SLGS" => Some(OrdinanceType::SpouseSealing),
+            _ => None,
+        }
+    }
+}
+
+/// `LdsOrdinance` is a temple ordinance of an individual, or the sealing of spouses held by a
+/// family. See GEDCOM 5.5.1 specification, p. 35.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{types::OrdinanceType, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5.1\n\
+///     0 @I1@ INDI\n\
+///     1 BAPL\n\
+///     2 DATE 12 MAR 1890\n\
+///     2 TEMP SLAKE\n\
+///     2 STAT COMPLETED\n\
+///     3 DATE 1 APR 1890\n\
+///     1 SLGC\n\
+///     2 FAMC @F1@\n\
+///     0 @F1@ FAM\n\
+///     1 SLGS\n\
+///     2 TEMP LOGAN\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let baptism = &data.individuals[0].lds_ordinances[0];
+/// assert_eq!(baptism.ordinance, OrdinanceType::Baptism);
+/// assert_eq!(baptism.temple_code.as_deref(), Some("SLAKE"));
+/// let status = baptism.status.as_ref().unwrap();
+/// assert_eq!(status.value, "COMPLETED");
+/// assert_eq!(status.date.as_ref().unwrap().value.as_deref(), Some("1 APR 1890"));
+///
+/// let sealing = &data.individuals[0].lds_ordinances[1];
+/// assert_eq!(sealing.family.as_deref(), Some("@F1@"));
+/// assert_eq!(
+///     data.families[0].lds_ordinances[0].ordinance,
+///     OrdinanceType::SpouseSealing
+/// );
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct LdsOrdinance {
+    pub ordinance: OrdinanceType,
+    pub date: Option<Date>,
+    /// tag: TEMP, the abbreviation of the temple
+    pub temple_code: Option<String>,
+    pub place: Option<SharedStr>,
+    /// tag: STAT
+    pub status: Option<OrdinanceStatus>,
+    /// tag: FAMC, the family a child is sealed to
+    pub family: Option<Xref>,
+    pub sources: Vec<SourceCitation>,
+    pub notes: Vec<Note>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl LdsOrdinance {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        ordinance: OrdinanceType,
+    ) -> Result<LdsOrdinance, GedcomError> {
+        let mut lds = LdsOrdinance {
+            ordinance,
+            date: None,
+            temple_code: None,
+            place: None,
+            status: None,
+            family: None,
+            sources: Vec::new(),
+            notes: Vec::new(),
+            custom_data: Vec::new(),
+        };
+        lds.parse(tokenizer, level)?;
+        Ok(lds)
+    }
+}
+
+impl Parser for LdsOrdinance {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        // the tag has no payload
+        tokenizer.take_line_value()?;
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "TEMP" => self.temple_code = Some(tokenizer.take_line_value()?),
+                "PLAC" => self.place = Some(tokenizer.take_shared_value()?),
+                "STAT" => self.status = Some(OrdinanceStatus::new(tokenizer, level + 1)?),
+                "FAMC" if self.ordinance == OrdinanceType::ChildSealing => {
+                    self.family = Some(tokenizer.take_line_value()?);
+                }
+                "SOUR" => SourceCitation::parse_into(&mut self.sources, tokenizer, level + 1)?,
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("LdsOrdinance", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
+
+/// `OrdinanceStatus` (tag: STAT) is the state of an ordinance, e.g. COMPLETED or SUBMITTED, along
+/// with the date it was last changed.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct OrdinanceStatus {
+    pub value: String,
+    pub date: Option<Date>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl OrdinanceStatus {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<OrdinanceStatus, GedcomError> {
+        let mut status = OrdinanceStatus::default();
+        status.parse(tokenizer, level)?;
+        Ok(status)
+    }
+}
+
+impl Parser for OrdinanceStatus {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = tokenizer.take_line_value()?;
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("OrdinanceStatus", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
@@ -1,17 +1,22 @@
 //! Structs and datatypes used to represent facts and data in a Gedcom file
+//!
+//! A field standing for a tag is `Some` whenever the tag is present in the file, holding an empty
+//! string if the tag has no payload, e.g. `1 NOTE`. `None` always means the tag is absent.
 
 // holy wow, this data format is heteronormative af...
 
 #![allow(missing_docs)]
-
-#[cfg(feature = "json")]
-use serde::{Deserialize, Serialize};
+// constructors all fail with the `GedcomError` of the structure being parsed
+#![allow(clippy::missing_errors_doc)]
 
 pub mod event;
-pub use event::{EventDetail, Event};
+pub use event::{CustomEventType, Event, EventCategory, EventDetail};
 
 pub mod date;
-pub use date::{ChangeDate, Date};
+pub use date::{Calendar, ChangeDate, Date, DatePart, DateSortKey, DateValue, Time};
+
+mod age;
+pub use age::*;
 
 mod place;
 pub use place::*;
@@ -57,3 +62,14 @@ pub use multimedia::*;
 
 mod custom;
 pub use custom::*;
+
+mod association;
+pub use association::*;
+
+mod pointer;
+pub use pointer::*;
+
+#[cfg(feature = "lds")]
+mod lds;
+#[cfg(feature = "lds")]
+pub use lds::*;
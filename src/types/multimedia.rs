@@ -1,10 +1,14 @@
 use crate::{
     parse_subset,
-    tokenizer::Tokenizer,
-    types::{ChangeDate, Note, SourceCitation, Xref},
-    Parser,
+    tokenizer::{Token, Tokenizer},
+    types::{ChangeDate, ExternalId, Note, SourceCitation, UserDefinedDataset, Xref},
+    util::is_pointer,
+    GedcomError, Parser,
 };
 
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
 /// MultimediaRecord refers to 1 or more external digital files, and may provide some
 /// additional information about the files and the media they encode.
 ///
@@ -31,7 +35,7 @@ use crate::{
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 ///
 /// assert_eq!(data.multimedia.len(), 1);
 /// let obje = &data.multimedia[0];
@@ -61,38 +65,65 @@ pub struct MultimediaRecord {
     pub automated_record_id: Option<String>,
     pub source_citation: Option<SourceCitation>,
     pub change_date: Option<ChangeDate>,
-    pub note_structure: Option<Note>,
+    /// tag: CREA, when the record was created, from GEDCOM 7
+    pub created: Option<ChangeDate>,
+    pub notes: Vec<Note>,
+    /// tag: UID, ids of the record which stay the same across files, from GEDCOM 7
+    pub uids: Vec<String>,
+    /// tag: EXID, the ids of the record at other services
+    pub external_ids: Vec<ExternalId>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl MultimediaRecord {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<Xref>) -> MultimediaRecord {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<Xref>,
+    ) -> Result<MultimediaRecord, GedcomError> {
         let mut obje = MultimediaRecord::default();
         obje.xref = xref;
-        obje.parse(tokenizer, level);
-        obje
+        obje.parse(tokenizer, level)?;
+        Ok(obje)
     }
 }
 
 impl Parser for MultimediaRecord {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip current line
-        tokenizer.next_token();
+        tokenizer.next_token()?;
+        // a link to a multimedia record, e.g. `1 OBJE @M1@`
+        if let Token::LineValue(value) = tokenizer.current_token {
+            if is_pointer(value) {
+                self.xref = Some(value.to_string());
+                tokenizer.next_token()?;
+            }
+        }
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "FILE" => self.file = Some(MultimediaFileRefn::new(tokenizer, level + 1)),
-            "FORM" => self.form = Some(MultimediaFormat::new(tokenizer, level + 1)),
-            "TITL" => self.title = Some(tokenizer.take_line_value()),
-            "REFN" => {
-                self.user_reference_number = Some(UserReferenceNumber::new(tokenizer, level + 1))
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "FILE" => self.file = Some(MultimediaFileRefn::new(tokenizer, level + 1)?),
+                "FORM" => self.form = Some(MultimediaFormat::new(tokenizer, level + 1)?),
+                "TITL" => self.title = Some(tokenizer.take_line_value()?),
+                "REFN" => {
+                    self.user_reference_number =
+                        Some(UserReferenceNumber::new(tokenizer, level + 1)?)
+                }
+                "RIN" => self.automated_record_id = Some(tokenizer.take_line_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                "SOUR" => self.source_citation = Some(SourceCitation::new(tokenizer, level + 1)?),
+                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "CREA" => self.created = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "UID" => self.uids.push(tokenizer.take_line_value()?),
+                "EXID" => self
+                    .external_ids
+                    .push(ExternalId::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Multimedia", tag)),
             }
-            "RIN" => self.automated_record_id = Some(tokenizer.take_line_value()),
-            "NOTE" => self.note_structure = Some(Note::new(tokenizer, level + 1)),
-            "SOUR" => self.source_citation = Some(SourceCitation::new(tokenizer, level + 1)),
-            "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)),
-            _ => panic!("{} Unhandled Multimedia Tag: {}", tokenizer.debug(), tag),
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -118,7 +149,7 @@ impl Parser for MultimediaRecord {
 ///     0 TRLR";
 ///
 /// let mut record = GedcomDocument::new(sample.chars());
-/// let data = record.parse_document();
+/// let data = record.parse_document().unwrap();
 /// assert_eq!(data.multimedia.len(), 1);
 ///
 /// let obje = &data.multimedia[0];
@@ -133,7 +164,7 @@ impl Parser for MultimediaRecord {
 ///     "http://trees.ancestry.com/rd?f=image&guid=Xxxxxxxx-Xxxx-Xxxx-Xxxx-Xxxxxxxxxxxx&tid=Xxxxxxxx&pid=1"
 /// );
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct MultimediaLink {
     /// Optional reference to link to this submitter
@@ -145,34 +176,50 @@ pub struct MultimediaLink {
     /// The 5.5 spec, page 26, shows TITL as a sub-structure of FILE, but the struct appears as a
     /// sibling in an Ancestry.com export.
     pub title: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl MultimediaLink {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<Xref>) -> MultimediaLink {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<Xref>,
+    ) -> Result<MultimediaLink, GedcomError> {
         let mut obje = MultimediaLink {
             xref,
             file: None,
             form: None,
             title: None,
+            custom_data: Vec::new(),
         };
-        obje.parse(tokenizer, level);
-        obje
+        obje.parse(tokenizer, level)?;
+        Ok(obje)
     }
 }
 
 impl Parser for MultimediaLink {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip current line
-        tokenizer.next_token();
+        tokenizer.next_token()?;
+        // a link to a multimedia record, e.g. `1 OBJE @M1@`
+        if let Token::LineValue(value) = tokenizer.current_token {
+            if is_pointer(value) {
+                self.xref = Some(value.to_string());
+                tokenizer.next_token()?;
+            }
+        }
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "FILE" => self.file = Some(MultimediaFileRefn::new(tokenizer, level + 1)),
-            "FORM" => self.form = Some(MultimediaFormat::new(tokenizer, level + 1)),
-            "TITL" => self.title = Some(tokenizer.take_line_value()),
-            _ => panic!("{} Unhandled Multimedia Tag: {}", tokenizer.debug(), tag),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "FILE" => self.file = Some(MultimediaFileRefn::new(tokenizer, level + 1)?),
+                "FORM" => self.form = Some(MultimediaFormat::new(tokenizer, level + 1)?),
+                "TITL" => self.title = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("Multimedia", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -196,7 +243,7 @@ impl Parser for MultimediaLink {
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 /// assert_eq!(data.multimedia.len(), 1);
 ///
 /// let file = data.multimedia[0].file.as_ref().unwrap();
@@ -217,30 +264,30 @@ pub struct MultimediaFileRefn {
     pub value: Option<String>,
     pub title: Option<String>,
     pub form: Option<MultimediaFormat>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl MultimediaFileRefn {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> MultimediaFileRefn {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<MultimediaFileRefn, GedcomError> {
         let mut file = MultimediaFileRefn::default();
-        file.parse(tokenizer, level);
-        file
+        file.parse(tokenizer, level)?;
+        Ok(file)
     }
 }
 
 impl Parser for MultimediaFileRefn {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "TITL" => self.title = Some(tokenizer.take_line_value()),
-            "FORM" => self.form = Some(MultimediaFormat::new(tokenizer, level + 1)),
-            _ => panic!(
-                "{} Unhandled MultimediaFileRefn Tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "TITL" => self.title = Some(tokenizer.take_line_value()?),
+                "FORM" => self.form = Some(MultimediaFormat::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("MultimediaFileRefn", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -267,7 +314,7 @@ impl Parser for MultimediaFileRefn {
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 /// assert_eq!(data.multimedia.len(), 1);
 ///
 /// let file = data.multimedia[0].file.as_ref().unwrap();
@@ -281,30 +328,30 @@ impl Parser for MultimediaFileRefn {
 pub struct MultimediaFormat {
     pub value: Option<String>,
     pub source_media_type: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl MultimediaFormat {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> MultimediaFormat {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<MultimediaFormat, GedcomError> {
         let mut form = MultimediaFormat::default();
-        form.parse(tokenizer, level);
-        form
+        form.parse(tokenizer, level)?;
+        Ok(form)
     }
 }
 
 impl Parser for MultimediaFormat {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "TYPE" => self.source_media_type = Some(tokenizer.take_line_value()),
-            _ => panic!(
-                "{} Unhandled MultimediaFormat Tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "TYPE" => self.source_media_type = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("MultimediaFormat", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -331,7 +378,7 @@ impl Parser for MultimediaFormat {
 ///     0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 /// assert_eq!(data.multimedia.len(), 1);
 ///
 /// let user_ref = data.multimedia[0].user_reference_number.as_ref().unwrap();
@@ -347,29 +394,29 @@ pub struct UserReferenceNumber {
     pub value: Option<String>,
     /// A user-defined definition of the USER_REFERENCE_NUMBER.
     pub user_reference_type: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl UserReferenceNumber {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> UserReferenceNumber {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<UserReferenceNumber, GedcomError> {
         let mut refn = UserReferenceNumber::default();
-        refn.parse(tokenizer, level);
-        refn
+        refn.parse(tokenizer, level)?;
+        Ok(refn)
     }
 }
 
 impl Parser for UserReferenceNumber {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_line_value()?);
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "TYPE" => self.user_reference_type = Some(tokenizer.take_line_value()),
-            _ => panic!(
-                "{} Unhandled UserReferenceNumber Tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "TYPE" => self.user_reference_type = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("UserReferenceNumber", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
@@ -1,8 +1,12 @@
 use crate::{
     parse_subset,
     tokenizer::Tokenizer,
-    types::{Source, Translation},
-    Parser,
+    types::{
+        ChangeDate, ExternalId, Source, SourceCitation, Translation, UserDefinedDataset,
+        UserReferenceNumber, Xref,
+    },
+    util::is_record_pointer,
+    GedcomError, Parser,
 };
 
 #[cfg(feature = "json")]
@@ -45,10 +49,10 @@ use serde::{Deserialize, Serialize};
 ///     0 TRLR";
 
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 
-/// let note = data.header.unwrap().note.unwrap();
-/// assert_eq!(note.value.unwrap().chars().count(), 1441);
+/// let note = &data.header.unwrap().notes[0];
+/// assert_eq!(note.value.as_ref().unwrap().chars().count(), 1438);
 /// ```
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
@@ -69,27 +73,159 @@ pub struct Note {
     /// the Text-typed payloads of the superstructure and its substructures appear. See
     /// https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#LANG
     pub language: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Note {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Note {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Note, GedcomError> {
         let mut note = Note::default();
-        note.parse(tokenizer, level);
-        note
+        note.parse(tokenizer, level)?;
+        Ok(note)
+    }
+
+    /// The xref of the NOTE record the note points to, e.g. `@N1@` for `1 NOTE @N1@`, or `None`
+    /// for a note written inline
+    #[must_use]
+    pub fn xref(&self) -> Option<&str> {
+        self.value
+            .as_deref()
+            .filter(|value| is_record_pointer(value))
     }
 }
 
 impl Parser for Note {
     /// parse handles the NOTE tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_continued_text(level));
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "MIME" => self.mime = Some(tokenizer.take_line_value()),
-            "TRANS" => self.translation = Some(Translation::new(tokenizer, level + 1)),
-            "LANG" => self.language = Some(tokenizer.take_line_value()),
-            _ => panic!("{} unhandled NOTE tag: {}", tokenizer.debug(), tag),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        let mut text = tokenizer.take_line_value()?;
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "CONT" => {
+                    text.push('\n');
+                    text.push_str(&tokenizer.take_line_value()?);
+                }
+                "CONC" => text.push_str(&tokenizer.take_line_value()?),
+                "MIME" => self.mime = Some(tokenizer.take_line_value()?),
+                "TRAN" | "TRANS" => {
+                    self.translation = Some(Translation::new(tokenizer, level + 1)?);
+                }
+                "LANG" => self.language = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("NOTE", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        self.value = Some(text);
+        Ok(())
+    }
+}
+
+/// A shared note (tag: NOTE), a record holding text which other structures point to, e.g. with
+/// `1 NOTE @N1@`, rather than repeating it. GEDCOM 7 calls it SNOTE, see
+/// <https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#SHARED_NOTE_RECORD>.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5.1\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     1 NOTE @N1@\n\
+///     0 @N1@ NOTE Emigrated with his\n\
+///     1 CONC  brother\n\
+///     1 CONT in 1850\n\
+///     1 SOUR @S1@\n\
+///     0 @S1@ SOUR\n\
+///     0 TRLR\n";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let note = &data.notes[0];
+/// assert_eq!(note.xref.as_deref(), Some("@N1@"));
+/// assert_eq!(note.value.as_deref(), Some("Emigrated with his brother\nin 1850"));
+/// assert_eq!(note.sources[0].xref, "@S1@");
+/// assert_eq!(data.individuals[0].notes[0].xref(), Some("@N1@"));
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct NoteRecord {
+    pub xref: Option<Xref>,
+    /// The text of the note, its CONT and CONC lines joined
+    pub value: Option<String>,
+    /// tag: MIME, the media type of the text, text/plain or text/html
+    pub mime: Option<String>,
+    /// tag: TRAN, the text in another language
+    pub translation: Option<Translation>,
+    /// tag: LANG, the language of the text
+    pub language: Option<String>,
+    /// tag: SOUR, citations of the sources the note draws on
+    pub sources: Vec<SourceCitation>,
+    /// tag: REFN, identifiers given to the record by the user
+    pub user_reference_numbers: Vec<UserReferenceNumber>,
+    /// tag: RIN, the identifier of the record in the system which produced the file
+    pub automated_record_id: Option<String>,
+    pub change_date: Option<ChangeDate>,
+    /// tag: CREA, when the record was created, from GEDCOM 7
+    pub created: Option<ChangeDate>,
+    /// tag: UID, ids of the record which stay the same across files, from GEDCOM 7
+    pub uids: Vec<String>,
+    /// tags: EXID, `_FSFTID`, `_APID`, `_UID` and `_GUID`, the ids of the record at other services
+    pub external_ids: Vec<ExternalId>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl NoteRecord {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<Xref>,
+    ) -> Result<NoteRecord, GedcomError> {
+        let mut note = NoteRecord {
+            xref,
+            ..NoteRecord::default()
+        };
+        note.parse(tokenizer, level)?;
+        Ok(note)
+    }
+}
+
+impl Parser for NoteRecord {
+    /// Parses the NOTE top-level tag
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        let mut text = tokenizer.take_line_value()?;
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "CONT" => {
+                    text.push('\n');
+                    text.push_str(&tokenizer.take_line_value()?);
+                }
+                "CONC" => text.push_str(&tokenizer.take_line_value()?),
+                "MIME" => self.mime = Some(tokenizer.take_line_value()?),
+                "TRAN" | "TRANS" => {
+                    self.translation = Some(Translation::new(tokenizer, level + 1)?);
+                }
+                "LANG" => self.language = Some(tokenizer.take_line_value()?),
+                "SOUR" => SourceCitation::parse_into(&mut self.sources, tokenizer, level + 1)?,
+                "REFN" => self
+                    .user_reference_numbers
+                    .push(UserReferenceNumber::new(tokenizer, level + 1)?),
+                "RIN" => self.automated_record_id = Some(tokenizer.take_line_value()?),
+                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "CREA" => self.created = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "UID" => self.uids.push(tokenizer.take_line_value()?),
+                "EXID" => self
+                    .external_ids
+                    .push(ExternalId::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("NoteRecord", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        let mut vendor_ids = ExternalId::take_from(&mut self.custom_data);
+        self.external_ids.append(&mut vendor_ids);
+        self.value = Some(text);
+        Ok(())
     }
 }
@@ -1,3 +1,11 @@
+use crate::{
+    intern::SharedStr,
+    parse_subset,
+    tokenizer::{Token, Tokenizer},
+    types::UserDefinedDataset,
+    GedcomError, Parser,
+};
+
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
 
@@ -9,9 +17,132 @@ use serde::{Deserialize, Serialize};
 /// The type of each jurisdiction is given in the PLAC.FORM substructure, if present, or in the
 /// HEAD.PLAC.FORM structure. If neither is present, the jurisdictional types are unspecified
 /// beyond the lowest-to-highest order noted above.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Place {
-    pub value: Option<String>,
+    pub value: Option<SharedStr>,
     pub form: Option<String>,
+    /// tag: MAP, the coordinates of the place
+    pub map: Option<MapCoordinates>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl Place {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Place, GedcomError> {
+        let mut place = Place::default();
+        place.parse(tokenizer, level)?;
+        Ok(place)
+    }
+}
+
+impl Parser for Place {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        self.value = Some(tokenizer.take_shared_value()?);
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "FORM" => self.form = Some(tokenizer.take_line_value()?),
+                "MAP" => self.map = Some(MapCoordinates::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Place", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
+
+/// `MapCoordinates` (tag: MAP) locates a place on the globe. The values are kept as written, e.g.
+/// "N18.150944" and "E168.150944", see GEDCOM 5.5.1 specification, p. 58.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::GedcomDocument;
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5.1\n\
+///     0 @I1@ INDI\n\
+///     1 BIRT\n\
+///     2 PLAC Boston, Suffolk, Massachusetts, USA\n\
+///     3 MAP\n\
+///     4 LATI N42.3601\n\
+///     4 LONG W71.0589\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let birth = &data.individuals[0].events[0];
+/// let map = birth.coordinates.as_ref().unwrap();
+///
+/// assert_eq!(map.latitude.as_deref(), Some("N42.3601"));
+/// assert_eq!(map.degrees(), Some((42.3601, -71.0589)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct MapCoordinates {
+    /// tag: LATI
+    pub latitude: Option<String>,
+    /// tag: LONG
+    pub longitude: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl MapCoordinates {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<MapCoordinates, GedcomError> {
+        let mut map = MapCoordinates::default();
+        map.parse(tokenizer, level)?;
+        Ok(map)
+    }
+
+    /// The latitude and longitude in decimal degrees, north and east being positive. Besides the
+    /// GEDCOM form, plain signed decimals are accepted, as some applications write them.
+    #[must_use]
+    pub fn degrees(&self) -> Option<(f64, f64)> {
+        let latitude = parse_degrees(self.latitude.as_deref()?, 'N', 'S')?;
+        let longitude = parse_degrees(self.longitude.as_deref()?, 'E', 'W')?;
+        if latitude.abs() > 90.0 || longitude.abs() > 180.0 {
+            return None;
+        }
+        Some((latitude, longitude))
+    }
+}
+
+impl Parser for MapCoordinates {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        // skip MAP tag
+        tokenizer.next_token()?;
+        if let Token::LineValue(_) = tokenizer.current_token {
+            tokenizer.next_token()?;
+        }
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "LATI" => self.latitude = Some(tokenizer.take_line_value()?),
+                "LONG" => self.longitude = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("MapCoordinates", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
+
+fn parse_degrees(value: &str, positive: char, negative: char) -> Option<f64> {
+    let value = value.trim();
+    let first = value.chars().next()?.to_ascii_uppercase();
+    let (sign, number) = if first == positive {
+        (1.0, &value[1..])
+    } else if first == negative {
+        (-1.0, &value[1..])
+    } else {
+        (1.0, value)
+    };
+    let degrees: f64 = number.trim().parse().ok()?;
+    if degrees.is_finite() {
+        Some(sign * degrees)
+    } else {
+        None
+    }
 }
@@ -0,0 +1,89 @@
+use std::fmt;
+
+use super::Xref;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// `Pointer` is the payload of a line pointing to a record, e.g. the `@F1@` of `1 FAMC @F1@`.
+/// GEDCOM 7 allows `@VOID@` in place of a pointer for a record which isn't known, e.g. to keep
+/// the place of a child in the order of a family, see
+/// <https://gedcom.io/specifications/FamilySearchGEDCOMv7.html#pointer>.
+///
+/// Pointers are kept as their text in the parsed tree, so that `@VOID@` is written back as it was
+/// read, and `Pointer::new` tells the two apart. `@VOID@` isn't reported by the `validate`
+/// module, nor counted as a pointer to a missing record by `GedcomData::stats`.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{types::Pointer, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 7.0\n\
+///     0 @F1@ FAM\n\
+///     1 HUSB @VOID@\n\
+///     1 CHIL @I1@\n\
+///     1 CHIL @VOID@\n\
+///     0 @I1@ INDI\n\
+///     1 FAMC @F1@\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let family = &data.families[0];
+/// assert_eq!(Pointer::new(family.individual1.as_ref().unwrap()), Pointer::Void);
+/// let children: Vec<Pointer> = family.children.iter().map(|child| Pointer::new(child)).collect();
+/// assert_eq!(children, vec![Pointer::Xref("@I1@".to_string()), Pointer::Void]);
+/// assert_eq!(data.individuals[0].families[0].pointer().xref(), Some("@F1@"));
+///
+/// assert!(data.validate().is_empty());
+/// assert!(data.to_gedcom_string().contains("1 CHIL @VOID@\n"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum Pointer {
+    /// `@VOID@`, a pointer to no record
+    Void,
+    /// A pointer to the record with the xref
+    Xref(Xref),
+}
+
+impl Pointer {
+    /// The payload of a void pointer
+    pub const VOID: &'static str = "@VOID@";
+
+    /// Reads the payload of a pointer line
+    #[must_use]
+    pub fn new(value: &str) -> Pointer {
+        if value == Pointer::VOID {
+            Pointer::Void
+        } else {
+            Pointer::Xref(value.to_string())
+        }
+    }
+
+    /// The xref of the record pointed to, or `None` for `@VOID@`
+    #[must_use]
+    pub fn xref(&self) -> Option<&str> {
+        match self {
+            Pointer::Void => None,
+            Pointer::Xref(xref) => Some(xref),
+        }
+    }
+
+    /// Whether this is `@VOID@`
+    #[must_use]
+    pub fn is_void(&self) -> bool {
+        *self == Pointer::Void
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pointer::Void => f.write_str(Pointer::VOID),
+            Pointer::Xref(xref) => f.write_str(xref),
+        }
+    }
+}
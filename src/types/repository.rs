@@ -1,45 +1,88 @@
-use crate::{
-    parse_subset,
-    tokenizer::Tokenizer,
-    Parser,
-};
+use crate::{parse_subset, tokenizer::Tokenizer, GedcomError, Parser};
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
 
-use super::{Address, Xref};
+use super::{
+    ChangeDate, ContactInfo, ExternalId, Note, UserDefinedDataset, UserReferenceNumber, Xref,
+};
 
 /// Data repository, the `REPO` tag
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Repository {
     /// Optional reference to link to this repo
     pub xref: Option<Xref>,
     /// Name of the repository
     pub name: Option<String>,
-    /// Physical address of the data repository
-    pub address: Option<Address>,
+    /// Physical address of the data repository, with its phone numbers, emails, faxes and
+    /// websites
+    pub contact: ContactInfo,
+    /// Notes about the repository, e.g. its opening hours
+    pub notes: Vec<Note>,
+    /// tag: REFN, identifiers given to the record by the user
+    pub user_reference_numbers: Vec<UserReferenceNumber>,
+    /// tag: RIN, the identifier of the record in the system which produced the file
+    pub automated_record_id: Option<String>,
+    /// Date of the last change to the record
+    pub change_date: Option<ChangeDate>,
+    /// tag: CREA, when the record was created, from GEDCOM 7
+    pub created: Option<ChangeDate>,
+    /// tag: UID, ids of the record which stay the same across files, from GEDCOM 7
+    pub uids: Vec<String>,
+    /// tags: EXID, `_FSFTID`, `_APID`, `_UID` and `_GUID`, the ids of the record at other services
+    pub external_ids: Vec<ExternalId>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Repository {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<String>) -> Repository {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<String>,
+    ) -> Result<Repository, GedcomError> {
         let mut repo = Repository::default();
         repo.xref = xref;
-        repo.parse(tokenizer, level);
-        repo
+        repo.parse(tokenizer, level)?;
+        Ok(repo)
     }
 }
 
 impl Parser for Repository {
     /// Parses REPO top-level tag.
-    fn parse(&mut self, tokenizer: &mut crate::tokenizer::Tokenizer, level: u8) {
+    fn parse(
+        &mut self,
+        tokenizer: &mut crate::tokenizer::Tokenizer,
+        level: u8,
+    ) -> Result<(), GedcomError> {
         // skip REPO tag
-        tokenizer.next_token();
+        tokenizer.next_token()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "NAME" => self.name = Some(tokenizer.take_line_value()),
-            "ADDR" => self.address = Some(Address::new(tokenizer, level + 1)),
-            _ => panic!("{} Unhandled Repository Tag: {}", tokenizer.debug(), tag),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "NAME" => self.name = Some(tokenizer.take_line_value()?),
+                "ADDR" | "PHON" | "EMAIL" | "FAX" | "WWW" => {
+                    self.contact.parse_tag(tokenizer, level + 1, tag)?;
+                }
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                "REFN" => self
+                    .user_reference_numbers
+                    .push(UserReferenceNumber::new(tokenizer, level + 1)?),
+                "RIN" => self.automated_record_id = Some(tokenizer.take_line_value()?),
+                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "CREA" => self.created = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "UID" => self.uids.push(tokenizer.take_line_value()?),
+                "EXID" => self
+                    .external_ids
+                    .push(ExternalId::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Repository", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        let mut vendor_ids = ExternalId::take_from(&mut self.custom_data);
+        self.external_ids.append(&mut vendor_ids);
+        Ok(())
     }
 }
 
@@ -51,23 +94,31 @@ pub struct RepoCitation {
     pub xref: Xref,
     /// Call number to find the source at this repository
     pub call_number: Option<String>,
+    /// tag: NOTE
+    pub notes: Vec<Note>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl RepoCitation {
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> RepoCitation {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<RepoCitation, GedcomError> {
         let mut rc = RepoCitation::default();
-        rc.xref = tokenizer.take_line_value();
-        rc.parse(tokenizer, level);
-        rc
+        rc.xref = tokenizer.take_line_value()?;
+        rc.parse(tokenizer, level)?;
+        Ok(rc)
     }
 }
 
 impl Parser for RepoCitation {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "CALN" => self.call_number = Some(tokenizer.take_line_value()),
-            _ => panic!("{} Unhandled RepoCitation Tag: {}", tokenizer.debug(), tag),
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "CALN" => self.call_number = Some(tokenizer.take_line_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("RepoCitation", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
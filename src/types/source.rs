@@ -1,11 +1,14 @@
 use crate::{
+    intern::SharedStr,
     parse_subset,
     tokenizer::{Token, Tokenizer},
     types::{
-        ChangeDate, Date, EventDetail, MultimediaRecord, Note, RepoCitation, UserDefinedDataset,
-        Xref,
+        event::HasEvents, ChangeDate, Date, Event, EventDetail, ExternalId, MultimediaRecord, Note,
+        RepoCitation, UserDefinedDataset, Xref,
     },
-    Parser,
+    util::is_record_pointer,
+    writer::citation_text,
+    GedcomError, Parser,
 };
 
 #[cfg(feature = "json")]
@@ -19,25 +22,41 @@ pub struct Source {
     pub data: SourceData,
     pub abbreviation: Option<String>,
     pub title: Option<String>,
+    /// handles "AUTH" tag, the people or institutions who created the source
     pub author: Option<String>,
+    /// handles "PUBL" tag, e.g. the publisher, place and date of a book
     pub publication_facts: Option<String>,
+    /// handles "TEXT" tag, a transcription of the relevant part of the source
     pub citation_from_source: Option<String>,
     pub change_date: Option<Box<ChangeDate>>,
+    /// tag: CREA, when the record was created, from GEDCOM 7
+    pub created: Option<Box<ChangeDate>>,
+    /// handles "OBJE" tags, links to images of the source
     pub multimedia: Vec<MultimediaRecord>,
     pub notes: Vec<Note>,
     pub repo_citations: Vec<RepoCitation>,
+    /// handles "SOUR" tags, the sources this one was derived from, e.g. the original register
+    /// a microfilm copy was made of
+    pub citations: Vec<SourceCitation>,
     /// handles "RFN" tag; found in Ancestry.com export 
     pub submitter_registered_rfn: Option<String>,
+    /// tag: UID, ids of the record which stay the same across files, from GEDCOM 7
+    pub uids: Vec<String>,
+    /// tags: EXID, `_FSFTID`, `_APID`, `_UID` and `_GUID`, the ids of the record at other services
+    pub external_ids: Vec<ExternalId>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Source {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<String>) -> Source {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<String>,
+    ) -> Result<Source, GedcomError> {
         let mut sour = Source::default();
         sour.xref = xref;
-        sour.parse(tokenizer, level);
-        sour
+        sour.parse(tokenizer, level)?;
+        Ok(sour)
     }
 
     pub fn add_multimedia(&mut self, media: MultimediaRecord) {
@@ -51,59 +70,248 @@ impl Source {
     pub fn add_repo_citation(&mut self, citation: RepoCitation) {
         self.repo_citations.push(citation);
     }
+
+    /// The xrefs of the sources this one was derived from, through its SOUR citations. Citations
+    /// of a source described in place rather than pointed to are left out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gedcom::GedcomDocument;
+    /// let sample = "\
+    ///     0 HEAD\n\
+    ///     1 GEDC\n\
+    ///     2 VERS 5.5\n\
+    ///     0 @S1@ SOUR\n\
+    ///     1 TITL Parish registers of Lavenham, 1558-1850\n\
+    ///     0 @S2@ SOUR\n\
+    ///     1 TITL Microfilm of the parish registers of Lavenham\n\
+    ///     1 SOUR @S1@\n\
+    ///     2 PAGE Reels 1-3\n\
+    ///     0 TRLR";
+    ///
+    /// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+    /// assert!(data.sources[0].derived_from().is_empty());
+    /// assert_eq!(data.sources[1].derived_from(), vec!["@S1@"]);
+    /// ```
+    #[must_use]
+    pub fn derived_from(&self) -> Vec<&str> {
+        self.citations
+            .iter()
+            .map(|citation| citation.xref.as_str())
+            .filter(|xref| is_record_pointer(xref))
+            .collect()
+    }
 }
 
 impl Parser for Source {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip SOUR tag
-        tokenizer.next_token();
+        tokenizer.next_token()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| {
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
             let mut pointer: Option<String> = None;
-            if let Token::Pointer(xref) = &tokenizer.current_token {
+            if let Token::Pointer(xref) = tokenizer.current_token {
                 pointer = Some(xref.to_string());
-                tokenizer.next_token();
+                tokenizer.next_token()?;
             }
             match tag {
-                "DATA" => tokenizer.next_token(),
-                "EVEN" => {
-                    let events_recorded = tokenizer.take_line_value();
-                    let mut event = EventDetail::new(tokenizer, level + 2, "OTHER");
-                    event.with_source_data(events_recorded);
-                    self.data.add_event(event);
-                }
-                "AGNC" => self.data.agency = Some(tokenizer.take_line_value()),
-                "ABBR" => self.abbreviation = Some(tokenizer.take_continued_text(level + 1)),
-                "CHAN" => self.change_date = Some(Box::new(ChangeDate::new(tokenizer, level + 1))),
-                "TITL" => self.title = Some(tokenizer.take_continued_text(level + 1)),
-                "AUTH" => self.author = Some(tokenizer.take_continued_text(level + 1)),
-                "PUBL" => self.publication_facts = Some(tokenizer.take_continued_text(level + 1)),
+                "DATA" => self.data.parse(tokenizer, level + 1)?,
+                "ABBR" => self.abbreviation = Some(tokenizer.take_continued_text(level + 1)?),
+                "CHAN" => self.change_date = Some(Box::new(ChangeDate::new(tokenizer, level + 1)?)),
+                "CREA" => self.created = Some(Box::new(ChangeDate::new(tokenizer, level + 1)?)),
+                "TITL" => self.title = Some(tokenizer.take_continued_text(level + 1)?),
+                "AUTH" => self.author = Some(tokenizer.take_continued_text(level + 1)?),
+                "PUBL" => self.publication_facts = Some(tokenizer.take_continued_text(level + 1)?),
                 "TEXT" => {
-                    self.citation_from_source = Some(tokenizer.take_continued_text(level + 1))
+                    self.citation_from_source = Some(tokenizer.take_continued_text(level + 1)?)
                 }
-                "OBJE" => self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, pointer)),
-                "NOTE" => self.add_note(Note::new(tokenizer, level + 1)),
-                "REPO" => self.add_repo_citation(RepoCitation::new(tokenizer, level + 1)),
-                "RFN" => self.submitter_registered_rfn = Some(tokenizer.take_line_value()),
-                _ => panic!("{} Unhandled Source Tag: {}", tokenizer.debug(), tag),
+                "OBJE" => {
+                    self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, pointer)?);
+                }
+                "NOTE" => self.add_note(Note::new(tokenizer, level + 1)?),
+                "REPO" => self.add_repo_citation(RepoCitation::new(tokenizer, level + 1)?),
+                "SOUR" => SourceCitation::parse_into(&mut self.citations, tokenizer, level + 1)?,
+                "RFN" => self.submitter_registered_rfn = Some(tokenizer.take_line_value()?),
+                "UID" => self.uids.push(tokenizer.take_line_value()?),
+                "EXID" => self
+                    .external_ids
+                    .push(ExternalId::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Source", tag)),
             }
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        let mut vendor_ids = ExternalId::take_from(&mut self.custom_data);
+        self.external_ids.append(&mut vendor_ids);
+        Ok(())
     }
 }
 
+/// `SourceData` is the DATA of a source record, describing what the source holds: the kinds of
+/// events it records, over which periods and places, and who keeps it.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{types::Event, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @S1@ SOUR\n\
+///     1 DATA\n\
+///     2 EVEN BIRT, DEAT\n\
+///     3 DATE FROM 1820 TO 1825\n\
+///     3 PLAC Madison, Connecticut\n\
+///     2 EVEN MARR\n\
+///     2 AGNC Madison County Court\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let source_data = &data.sources[0].data;
+/// let births = &source_data.events[0];
+/// assert_eq!(births.events, vec![Event::Birth, Event::Death]);
+/// assert_eq!(
+///     births.date.as_ref().unwrap().value.as_deref(),
+///     Some("FROM 1820 TO 1825")
+/// );
+/// assert_eq!(births.place.as_deref(), Some("Madison, Connecticut"));
+/// assert!(source_data.events[1].records(&Event::Marriage));
+/// assert_eq!(source_data.agency.as_deref(), Some("Madison County Court"));
+/// ```
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct SourceData {
-    events: Vec<EventDetail>,
+    /// tag: EVEN, the events recorded in the source
+    pub events: Vec<RecordedEvent>,
+    /// tag: AGNC, the institution or individual responsible for the source
     pub agency: Option<String>,
+    pub notes: Vec<Note>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl SourceData {
-    pub fn add_event(&mut self, event: EventDetail) {
+    pub fn add_event(&mut self, event: RecordedEvent) {
         self.events.push(event);
     }
+
+    /// Whether nothing was given about the data of the source
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+            && self.agency.is_none()
+            && self.notes.is_empty()
+            && self.custom_data.is_empty()
+    }
+}
+
+impl HasEvents for SourceData {
+    /// Records the event, with its date and place, as an event covered by the source
+    fn add_event(&mut self, event: EventDetail) {
+        self.events.push(RecordedEvent {
+            events: vec![event.event],
+            date: event.date,
+            place: event.place,
+            custom_data: Vec::new(),
+        });
+    }
+
+    /// The events covered by the source, one for each kind of a recorded event, e.g. a birth and
+    /// a death for "BIRT, DEAT", with the date and place of the recorded event
+    fn events(&self) -> Vec<EventDetail> {
+        self.events
+            .iter()
+            .flat_map(|recorded| {
+                recorded.events.iter().map(move |event| EventDetail {
+                    date: recorded.date.clone(),
+                    place: recorded.place.clone(),
+                    ..EventDetail::from_event(event.clone())
+                })
+            })
+            .collect()
+    }
+}
+
+impl Parser for SourceData {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        // skip DATA tag
+        tokenizer.next_token()?;
+
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "EVEN" => self.add_event(RecordedEvent::new(tokenizer, level + 1)?),
+                "AGNC" => self.agency = Some(tokenizer.take_line_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("SourceData", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
+}
+
+/// `RecordedEvent` is an EVEN entry of the DATA of a source, giving kinds of events the source
+/// records along with the period and jurisdiction it covers for them.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct RecordedEvent {
+    /// The kinds of events recorded, from the comma separated tags of the EVEN value, e.g.
+    /// "BIRT, DEAT, MARR"
+    pub events: Vec<Event>,
+    /// tag: DATE, the period covered, e.g. "FROM 1820 TO 1825"
+    pub date: Option<Date>,
+    /// tag: PLAC, the jurisdiction covered, e.g. "Madison, Connecticut"
+    pub place: Option<SharedStr>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
+}
+
+impl RecordedEvent {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<RecordedEvent, GedcomError> {
+        let events = tokenizer
+            .take_line_value()?
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(Event::from_tag)
+            .collect();
+        let mut recorded = RecordedEvent {
+            events,
+            ..RecordedEvent::default()
+        };
+        recorded.parse(tokenizer, level)?;
+        Ok(recorded)
+    }
+
+    /// Whether the kind of event is among those recorded
+    #[must_use]
+    pub fn records(&self, event: &Event) -> bool {
+        self.events.contains(event)
+    }
+
+    /// The EVEN value listing the events recorded, e.g. "BIRT, DEAT"
+    #[must_use]
+    pub fn tags(&self) -> String {
+        let tags: Vec<&str> = self.events.iter().map(Event::tag).collect();
+        tags.join(", ")
+    }
+}
+
+impl Parser for RecordedEvent {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "PLAC" => self.place = Some(tokenizer.take_shared_value()?),
+                _ => return Err(tokenizer.unhandled_tag("RecordedEvent", tag)),
+            }
+            Ok(())
+        };
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
+    }
 }
 
 /// The data provided in the `SourceCitation` structure is source-related information specific to
@@ -124,7 +332,7 @@ impl SourceData {
 ///     0 TRLR";
 ///
 /// let mut ged = GedcomDocument::new(sample.chars());
-/// let data = ged.parse_document();
+/// let data = ged.parse_document().unwrap();
 ///
 /// assert_eq!(data.individuals[0].source[0].xref, "@SOURCE1@");
 /// assert_eq!(data.individuals[0].source[0].page.as_ref().unwrap(), "42");
@@ -137,29 +345,55 @@ pub struct SourceCitation {
     /// Page number of source
     pub page: Option<String>,
     pub data: Option<SourceCitationData>,
-    pub note: Option<Note>,
+    pub notes: Vec<Note>,
     pub certainty_assessment: Option<CertaintyAssessment>,
     /// handles "RFN" tag; found in Ancestry.com export 
     pub submitter_registered_rfn: Option<String>,
     pub multimedia: Vec<MultimediaRecord>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
+    /// The number of identical citations dropped in favour of this one, when parsing with
+    /// `ParseOptions::deduplicate_citations`
+    pub duplicates: usize,
 }
 
 impl SourceCitation {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> SourceCitation {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<SourceCitation, GedcomError> {
         let mut citation = SourceCitation {
-            xref: tokenizer.take_line_value(),
+            xref: tokenizer.take_line_value()?,
             page: None,
             data: None,
-            note: None,
+            notes: Vec::new(),
             certainty_assessment: None,
             multimedia: Vec::new(),
             custom_data: Vec::new(),
             submitter_registered_rfn: None,
+            duplicates: 0,
         };
-        citation.parse(tokenizer, level);
-        citation
+        citation.parse(tokenizer, level)?;
+        Ok(citation)
+    }
+
+    /// Parses a citation and adds it to `citations`. With `ParseOptions::deduplicate_citations`,
+    /// a citation written out the same as one already in `citations` is counted in its
+    /// `duplicates` instead.
+    pub(crate) fn parse_into(
+        citations: &mut Vec<SourceCitation>,
+        tokenizer: &mut Tokenizer,
+        level: u8,
+    ) -> Result<(), GedcomError> {
+        let citation = SourceCitation::new(tokenizer, level)?;
+        if tokenizer.options.deduplicate_citations {
+            let text = citation_text(&citation);
+            if let Some(original) = citations
+                .iter_mut()
+                .find(|original| citation_text(original) == text)
+            {
+                original.duplicates += 1 + citation.duplicates;
+                return Ok(());
+            }
+        }
+        citations.push(citation);
+        Ok(())
     }
 
     pub fn add_multimedia(&mut self, m: MultimediaRecord) {
@@ -168,32 +402,32 @@ impl SourceCitation {
 }
 
 impl Parser for SourceCitation {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        // the pointer was taken by `new`, leaving the tokenizer at the level of the next line
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
             let mut pointer: Option<String> = None;
-            if let Token::Pointer(xref) = &tokenizer.current_token {
+            if let Token::Pointer(xref) = tokenizer.current_token {
                 pointer = Some(xref.to_string());
-                tokenizer.next_token();
+                tokenizer.next_token()?;
             }
             match tag {
-                "PAGE" => self.page = Some(tokenizer.take_continued_text(level + 1)),
-                "DATA" => self.data = Some(SourceCitationData::new(tokenizer, level + 1)),
-                "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
+                "PAGE" => self.page = Some(tokenizer.take_continued_text(level + 1)?),
+                "DATA" => self.data = Some(SourceCitationData::new(tokenizer, level + 1)?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
                 "QUAY" => {
-                    self.certainty_assessment = Some(CertaintyAssessment::new(tokenizer, level + 1))
+                    self.certainty_assessment =
+                        Some(CertaintyAssessment::new(tokenizer, level + 1)?)
+                }
+                "RFN" => self.submitter_registered_rfn = Some(tokenizer.take_line_value()?),
+                "OBJE" => {
+                    self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, pointer)?);
                 }
-                "RFN" => self.submitter_registered_rfn = Some(tokenizer.take_line_value()),
-                "OBJE" => self.add_multimedia(MultimediaRecord::new(tokenizer, level + 1, pointer)),
-                _ => panic!(
-                    "{} Unhandled SourceCitation Tag: {}",
-                    tokenizer.debug(),
-                    tag
-                ),
+                _ => return Err(tokenizer.unhandled_tag("SourceCitation", tag)),
             }
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -219,7 +453,7 @@ impl Parser for SourceCitation {
 ///     0 TRLR";
 ///
 /// let mut ged = GedcomDocument::new(sample.chars());
-/// let data = ged.parse_document();
+/// let data = ged.parse_document().unwrap();
 /// let citation_data = data.individuals[0].source[0].data.as_ref().unwrap();
 ///
 /// assert_eq!(
@@ -232,33 +466,35 @@ impl Parser for SourceCitation {
 pub struct SourceCitationData {
     pub date: Option<Date>,
     pub text: Option<TextFromSource>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl SourceCitationData {
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> SourceCitationData {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<SourceCitationData, GedcomError> {
         let mut data = SourceCitationData {
             date: None,
             text: None,
+            custom_data: Vec::new(),
         };
-        data.parse(tokenizer, level);
-        data
+        data.parse(tokenizer, level)?;
+        Ok(data)
     }
 }
 
 impl Parser for SourceCitationData {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip because this DATA tag should have now line value
-        tokenizer.next_token();
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "DATE" => self.date = Some(Date::new(tokenizer, level + 1)),
-            "TEXT" => self.text = Some(TextFromSource::new(tokenizer, level + 1)),
-            _ => panic!(
-                "{} unhandled SourceCitationData tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+        tokenizer.next_token()?;
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "DATE" => self.date = Some(Date::new(tokenizer, level + 1)?),
+                "TEXT" => self.text = Some(TextFromSource::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("SourceCitationData", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        Ok(())
     }
 }
 
@@ -288,7 +524,7 @@ impl Parser for SourceCitationData {
 ///     0 TRLR";
 ///
 /// let mut ged = GedcomDocument::new(sample.chars());
-/// let data = ged.parse_document();
+/// let data = ged.parse_document().unwrap();
 /// let citation_data = data.individuals[0].source[0].data.as_ref().unwrap();
 ///
 /// assert_eq!(
@@ -300,38 +536,34 @@ impl Parser for SourceCitationData {
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct TextFromSource {
     pub value: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl TextFromSource {
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> TextFromSource {
-        let mut text = TextFromSource { value: None };
-        text.parse(tokenizer, level);
-        text
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<TextFromSource, GedcomError> {
+        let mut text = TextFromSource::default();
+        text.parse(tokenizer, level)?;
+        Ok(text)
     }
 }
 
 impl Parser for TextFromSource {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        let mut value = String::new();
-        value.push_str(&tokenizer.take_line_value());
-
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "CONC" => value.push_str(&tokenizer.take_line_value()),
-            "CONT" => {
-                value.push('\n');
-                value.push_str(&tokenizer.take_line_value());
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        let mut text = tokenizer.take_line_value()?;
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "CONT" => {
+                    text.push('\n');
+                    text.push_str(&tokenizer.take_line_value()?);
+                }
+                "CONC" => text.push_str(&tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("TextFromSource", tag)),
             }
-            _ => panic!(
-                "{} unhandled TextFromSource tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
-
-        if value != "" {
-            self.value = Some(value);
-        }
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        self.value = Some(text);
+        Ok(())
     }
 }
 
@@ -345,6 +577,8 @@ impl Parser for TextFromSource {
 /// 2 = Secondary evidence, data officially recorded sometime after event
 /// 3 = Direct and primary evidence used, or by dominance of the evidence
 ///
+/// A QUAY tag without a payload is read as `CertaintyAssessment::None`.
+///
 /// # Example
 ///
 /// ```
@@ -361,7 +595,7 @@ impl Parser for TextFromSource {
 ///     0 TRLR";
 ///
 /// let mut ged = GedcomDocument::new(sample.chars());
-/// let data = ged.parse_document();
+/// let data = ged.parse_document().unwrap();
 /// let quay = data.individuals[0].source[0].certainty_assessment.as_ref().unwrap();
 ///
 /// assert_eq!(
@@ -370,6 +604,7 @@ impl Parser for TextFromSource {
 /// );
 /// ```
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum CertaintyAssessment {
     Unreliable,
     Questionable,
@@ -379,11 +614,10 @@ pub enum CertaintyAssessment {
 }
 
 impl CertaintyAssessment {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> CertaintyAssessment {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<CertaintyAssessment, GedcomError> {
         let mut quay = CertaintyAssessment::None;
-        quay.parse(tokenizer, level);
-        quay
+        quay.parse(tokenizer, level)?;
+        Ok(quay)
     }
 
     pub fn get_int(&self) -> Option<u8> {
@@ -404,27 +638,21 @@ impl ToString for CertaintyAssessment {
 }
 
 impl Parser for CertaintyAssessment {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
-        if let Token::LineValue(val) = &tokenizer.current_token {
-            *self = match val.as_str() {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, _level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
+        if let Token::LineValue(val) = tokenizer.current_token {
+            *self = match val {
                 "0" => CertaintyAssessment::Unreliable,
                 "1" => CertaintyAssessment::Questionable,
                 "2" => CertaintyAssessment::Secondary,
                 "3" => CertaintyAssessment::Direct,
-                _ => panic!(
-                    "{} Unknown CertaintyAssessment value {} ({})",
-                    tokenizer.debug(),
-                    val,
-                    level
-                ),
+                _ => return Err(tokenizer.invalid_value("QUAY", val)),
             };
         } else {
-            panic!(
-                "Expected CertaintyAssessment LineValue, found {:?}",
-                tokenizer.current_token
-            );
+            // a QUAY without a payload
+            *self = CertaintyAssessment::None;
+            return Ok(());
         }
-        tokenizer.next_token();
+        tokenizer.next_token()
     }
 }
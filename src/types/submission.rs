@@ -2,7 +2,7 @@ use crate::{
     parse_subset,
     tokenizer::Tokenizer,
     types::{ChangeDate, Note, UserDefinedDataset, Xref},
-    Parser,
+    GedcomError, Parser,
 };
 
 #[cfg(feature = "json")]
@@ -34,54 +34,76 @@ use serde::{Deserialize, Serialize};
 ///    0 TRLR";
 ///
 /// let mut doc = GedcomDocument::new(sample.chars());
-/// let data = doc.parse_document();
+/// let data = doc.parse_document().unwrap();
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Submission {
     pub xref: Option<Xref>,
     pub name_of_family_file: Option<String>,
+    /// tag: TEMP
+    #[cfg(feature = "lds")]
     pub temple_code: Option<String>,
     pub submitter_link: Option<String>,
     pub generations_of_ancestors: Option<String>,
     pub generations_of_descendants: Option<String>,
+    /// tag: ORDI
+    #[cfg(feature = "lds")]
     pub ordinance_process_flag: Option<String>,
     pub automated_record_id: Option<String>,
-    pub note: Option<Note>,
+    pub notes: Vec<Note>,
     pub change_date: Option<ChangeDate>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Submission {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<Xref>) -> Submission {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<Xref>,
+    ) -> Result<Submission, GedcomError> {
         let mut subn = Submission::default();
         subn.xref = xref;
-        subn.parse(tokenizer, level);
-        subn
+        subn.parse(tokenizer, level)?;
+        Ok(subn)
     }
 }
 
 impl Parser for Submission {
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        tokenizer.next_token();
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        tokenizer.next_token()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "ANCE" => self.generations_of_ancestors = Some(tokenizer.take_line_value()),
-            "DATE" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)),
-            "DESC" => self.generations_of_descendants = Some(tokenizer.take_line_value()),
-            "FAMF" => self.name_of_family_file = Some(tokenizer.take_line_value()),
-            "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-            "ORDI" => self.ordinance_process_flag = Some(tokenizer.take_line_value()),
-            "RIN" => self.automated_record_id = Some(tokenizer.take_line_value()),
-            "SUBM" => self.submitter_link = Some(tokenizer.take_line_value()),
-            "TEMP" => self.temple_code = Some(tokenizer.take_line_value()),
-            _ => panic!(
-                "{}, Unhandled SubmissionRecord tag: {}",
-                tokenizer.debug(),
-                tag
-            ),
+        // temple fields, kept as custom data without the lds feature
+        #[cfg(not(feature = "lds"))]
+        let mut temple_fields = Vec::new();
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "ANCE" => self.generations_of_ancestors = Some(tokenizer.take_line_value()?),
+                "CHAN" | "DATE" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "DESC" => self.generations_of_descendants = Some(tokenizer.take_line_value()?),
+                "FAMF" => self.name_of_family_file = Some(tokenizer.take_line_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                #[cfg(feature = "lds")]
+                "ORDI" => self.ordinance_process_flag = Some(tokenizer.take_line_value()?),
+                "RIN" => self.automated_record_id = Some(tokenizer.take_line_value()?),
+                "SUBM" => self.submitter_link = Some(tokenizer.take_line_value()?),
+                #[cfg(feature = "lds")]
+                "TEMP" => self.temple_code = Some(tokenizer.take_line_value()?),
+                #[cfg(not(feature = "lds"))]
+                "ORDI" | "TEMP" => {
+                    temple_fields.push(Box::new(UserDefinedDataset::new(
+                        tokenizer,
+                        level + 1,
+                        tag,
+                    )?));
+                }
+                _ => return Err(tokenizer.unhandled_tag("SubmissionRecord", tag)),
+            }
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        #[cfg(not(feature = "lds"))]
+        self.custom_data.append(&mut temple_fields);
+        Ok(())
     }
 }
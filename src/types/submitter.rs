@@ -1,7 +1,7 @@
 use crate::{
     tokenizer::{Token, Tokenizer},
-    types::{Address, ChangeDate, MultimediaLink, Note, UserDefinedDataset, Xref},
-    Parser, parse_subset,
+    types::{ChangeDate, ContactInfo, ExternalId, MultimediaLink, Note, UserDefinedDataset, Xref},
+    parse_subset, GedcomError, Parser,
 };
 
 #[cfg(feature = "json")]
@@ -11,19 +11,19 @@ use serde::{Deserialize, Serialize};
 /// contained in the GEDCOM transmission. All records in the transmission are assumed to be
 /// submitted by the SUBMITTER referenced in the HEADer, unless a SUBMitter reference inside a
 /// specific record points at a different SUBMITTER record.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Submitter {
     /// Optional reference to link to this submitter
     pub xref: Option<Xref>,
     /// Name of the submitter
     pub name: Option<String>,
-    /// Physical address of the submitter
-    pub address: Option<Address>,
+    /// Physical address of the submitter, with their phone numbers, emails, faxes and websites
+    pub contact: ContactInfo,
     /// A multimedia asset linked to a fact
     pub multimedia: Vec<MultimediaLink>,
-    /// Language preference
-    pub language: Option<String>,
+    /// Language preferences, most preferred first
+    pub languages: Vec<String>,
     /// A registered number of a submitter of Ancestral File data. This number is used in
     /// subsequent submissions or inquiries by the submitter for identification purposes.
     pub registered_refn: Option<String>,
@@ -33,21 +33,28 @@ pub struct Submitter {
     pub automated_record_id: Option<String>,
     /// Date of the last change to the record
     pub change_date: Option<ChangeDate>,
-    /// Note provided by submitter about the enclosing data
-    pub note: Option<Note>,
-    /// Phone number of the submitter
-    pub phone: Option<String>,
+    /// tag: CREA, when the record was created, from GEDCOM 7
+    pub created: Option<ChangeDate>,
+    /// Notes provided by submitter about the enclosing data
+    pub notes: Vec<Note>,
+    /// tag: UID, ids of the record which stay the same across files, from GEDCOM 7
+    pub uids: Vec<String>,
+    /// tags: EXID, `_FSFTID`, `_APID`, `_UID` and `_GUID`, the ids of the record at other services
+    pub external_ids: Vec<ExternalId>,
     pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Submitter {
     /// Shorthand for creating a `Submitter` from its `xref`
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8, xref: Option<Xref>) -> Submitter {
+    pub fn new(
+        tokenizer: &mut Tokenizer,
+        level: u8,
+        xref: Option<Xref>,
+    ) -> Result<Submitter, GedcomError> {
         let mut subm = Submitter::default();
         subm.xref = xref;
-        subm.parse(tokenizer, level);
-        subm
+        subm.parse(tokenizer, level)?;
+        Ok(subm)
     }
 
     /// Adds a `Multimedia` to the tree
@@ -58,27 +65,39 @@ impl Submitter {
 
 impl Parser for Submitter {
     /// Parse handles SUBM top-level tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
         // skip over SUBM tag name
-        tokenizer.next_token();
+        tokenizer.next_token()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| {
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
             let mut pointer: Option<String> = None;
-            if let Token::Pointer(xref) = &tokenizer.current_token {
+            if let Token::Pointer(xref) = tokenizer.current_token {
                 pointer = Some(xref.to_string());
-                tokenizer.next_token();
+                tokenizer.next_token()?;
             }
             match tag {
-                "NAME" => self.name = Some(tokenizer.take_line_value()),
-                "ADDR" => self.address = Some(Address::new(tokenizer, level + 1)),
-                "OBJE" => self.add_multimedia(MultimediaLink::new(tokenizer, level + 1, pointer)),
-                "LANG" => self.language = Some(tokenizer.take_line_value()),
-                "NOTE" => self.note = Some(Note::new(tokenizer, level + 1)),
-                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)),
-                "PHON" => self.phone = Some(tokenizer.take_line_value()),
-                _ => panic!("{} Unhandled Submitter Tag: {}", tokenizer.debug(), tag),
+                "NAME" => self.name = Some(tokenizer.take_line_value()?),
+                "ADDR" | "PHON" | "EMAIL" | "FAX" | "WWW" => {
+                    self.contact.parse_tag(tokenizer, level + 1, tag)?;
+                }
+                "OBJE" => self.add_multimedia(MultimediaLink::new(tokenizer, level + 1, pointer)?),
+                "LANG" => self.languages.push(tokenizer.take_line_value()?),
+                "NOTE" => self.notes.push(Note::new(tokenizer, level + 1)?),
+                "CHAN" => self.change_date = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "CREA" => self.created = Some(ChangeDate::new(tokenizer, level + 1)?),
+                "RFN" => self.registered_refn = Some(tokenizer.take_line_value()?),
+                "RIN" => self.automated_record_id = Some(tokenizer.take_line_value()?),
+                "UID" => self.uids.push(tokenizer.take_line_value()?),
+                "EXID" => self
+                    .external_ids
+                    .push(ExternalId::new(tokenizer, level + 1)?),
+                _ => return Err(tokenizer.unhandled_tag("Submitter", tag)),
             }
+            Ok(())
         };
-        self.custom_data = parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        let mut vendor_ids = ExternalId::take_from(&mut self.custom_data);
+        self.external_ids.append(&mut vendor_ids);
+        Ok(())
     }
 }
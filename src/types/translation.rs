@@ -1,10 +1,7 @@
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
 
-use crate::{
-    tokenizer::Tokenizer,
-    Parser, parse_subset,
-};
+use crate::{parse_subset, tokenizer::Tokenizer, types::UserDefinedDataset, GedcomError, Parser};
 
 /// Translation (tag:TRAN) is a type of TRAN for unstructured human-readable text, such as
 /// is found in NOTE and SNOTE payloads. Each NOTE-TRAN must have either a LANG substructure or a
@@ -18,27 +15,37 @@ pub struct Translation {
     pub mime: Option<String>,
     /// tag:LANG
     pub language: Option<String>,
+    pub custom_data: Vec<Box<UserDefinedDataset>>,
 }
 
 impl Translation {
-    #[must_use]
-    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Translation {
+    pub fn new(tokenizer: &mut Tokenizer, level: u8) -> Result<Translation, GedcomError> {
         let mut tran = Translation::default();
-        tran.parse(tokenizer, level);
-        tran
+        tran.parse(tokenizer, level)?;
+        Ok(tran)
     }
 }
 
 impl Parser for Translation {
     ///parse handles the TRAN tag
-    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) {
-        self.value = Some(tokenizer.take_line_value());
+    fn parse(&mut self, tokenizer: &mut Tokenizer, level: u8) -> Result<(), GedcomError> {
+        let mut text = tokenizer.take_line_value()?;
 
-        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| match tag {
-            "MIME" => self.mime = Some(tokenizer.take_line_value()),
-            "LANG" => self.language = Some(tokenizer.take_line_value()),
-            _ => panic!("{} unhandled NOTE tag: {}", tokenizer.debug(), tag),
+        let handle_subset = |tag: &str, tokenizer: &mut Tokenizer| -> Result<(), GedcomError> {
+            match tag {
+                "CONT" => {
+                    text.push('\n');
+                    text.push_str(&tokenizer.take_line_value()?);
+                }
+                "CONC" => text.push_str(&tokenizer.take_line_value()?),
+                "MIME" => self.mime = Some(tokenizer.take_line_value()?),
+                "LANG" => self.language = Some(tokenizer.take_line_value()?),
+                _ => return Err(tokenizer.unhandled_tag("NOTE", tag)),
+            }
+            Ok(())
         };
-        parse_subset(tokenizer, level, handle_subset);
+        self.custom_data = parse_subset(tokenizer, level, handle_subset)?;
+        self.value = Some(text);
+        Ok(())
     }
 }
@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+
+use crate::types::Pointer;
+
 /// Macro for displaying `Option`s in debug mode without the text wrapping.
 #[macro_export]
 macro_rules! fmt_optional_value {
@@ -9,3 +13,175 @@ macro_rules! fmt_optional_value {
         }
     };
 }
+
+/// Whether a line value is a pointer to a record, e.g. `@I1@`
+pub(crate) fn is_pointer(value: &str) -> bool {
+    value.len() > 2 && value.starts_with('@') && value.ends_with('@') && !value.contains(' ')
+}
+
+/// Whether a line value is a pointer leading to a record, i.e. any pointer but `@VOID@`
+pub(crate) fn is_record_pointer(value: &str) -> bool {
+    is_pointer(value) && value != Pointer::VOID
+}
+
+/// Whether a word is a well-formed xref, e.g. `@I1@`: text between at-signs holding no at-sign
+/// or space of its own, and not starting with `#`, which starts an escape such as `@#DJULIAN@`
+pub(crate) fn is_xref(word: &str) -> bool {
+    is_pointer(word) && !word[1..word.len() - 1].contains('@') && !word[1..].starts_with('#')
+}
+
+/// The literal text of a line value, in which an at-sign is written `@@`. Escapes such as the
+/// `@#DJULIAN@` calendar escape of dates are kept as they are, and so are pointers.
+pub(crate) fn unescape_at_signs(value: &str) -> Cow<'_, str> {
+    if is_pointer(value) || !value.contains("@@") {
+        return Cow::Borrowed(value);
+    }
+    let mut text = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(i) = rest.find('@') {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let len = if let Some(len) = escape_len(rest) {
+            text.push_str(&rest[..len]);
+            len
+        } else {
+            text.push('@');
+            if rest.starts_with("@@") {
+                2
+            } else {
+                1
+            }
+        };
+        rest = &rest[len..];
+    }
+    text.push_str(rest);
+    Cow::Owned(text)
+}
+
+/// Writes the at-signs of a line value as `@@`, the reverse of `unescape_at_signs`
+pub(crate) fn escape_at_signs(value: &str) -> Cow<'_, str> {
+    if is_pointer(value) || !value.contains('@') {
+        return Cow::Borrowed(value);
+    }
+    let mut text = String::with_capacity(value.len() + 2);
+    let mut rest = value;
+    while let Some(i) = rest.find('@') {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let len = if let Some(len) = escape_len(rest) {
+            text.push_str(&rest[..len]);
+            len
+        } else {
+            text.push_str("@@");
+            1
+        };
+        rest = &rest[len..];
+    }
+    text.push_str(rest);
+    Cow::Owned(text)
+}
+
+/// The length of the escape `text` starts with, e.g. `@#DFRENCH R@`, if any
+fn escape_len(text: &str) -> Option<usize> {
+    let escape = text.strip_prefix("@#")?;
+    Some(escape.find('@')? + 3)
+}
+
+/// Splits text into lines ending with CRLF, LF or a lone CR alike, as written by older Mac
+/// applications, each line keeping its terminator. The last line may have none.
+pub(crate) fn split_lines_inclusive(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = match rest.find(['\r', '\n']) {
+            Some(end) if rest[end..].starts_with("\r\n") => end + 2,
+            Some(end) => end + 1,
+            None => rest.len(),
+        };
+        let (line, next) = rest.split_at(end);
+        rest = next;
+        Some(line)
+    })
+}
+
+/// Splits text into lines like `str::lines`, but ending with a lone CR too, see
+/// `split_lines_inclusive`. The terminators are left out.
+pub(crate) fn split_lines(text: &str) -> impl Iterator<Item = &str> {
+    split_lines_inclusive(text).map(|line| line.trim_end_matches(['\r', '\n']))
+}
+
+/// The fields of a line as written by `GedcomWriter`
+pub(crate) struct LineFields<'a> {
+    pub level: &'a str,
+    pub xref: Option<&'a str>,
+    pub tag: Option<&'a str>,
+    pub value: Option<&'a str>,
+}
+
+impl<'a> LineFields<'a> {
+    pub fn split(line: &'a str) -> LineFields<'a> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (level, rest) = split_field(line);
+        let (xref, rest) = match rest {
+            Some(rest) if rest.starts_with('@') => {
+                let (xref, rest) = split_field(rest);
+                (Some(xref), rest)
+            }
+            _ => (None, rest),
+        };
+        let (tag, value) = match rest {
+            Some(rest) => {
+                let (tag, value) = split_field(rest);
+                (Some(tag), value)
+            }
+            None => (None, None),
+        };
+        LineFields {
+            level,
+            xref,
+            tag,
+            value,
+        }
+    }
+}
+
+/// Rewrites the xrefs of GEDCOM text as written by `GedcomWriter`, both where records are defined
+/// and where they are pointed to. `remap` gives the new xref for each xref met, in order of first
+/// appearance, or `None` to keep it. Continued text is left as is, even when it looks like a
+/// pointer.
+pub(crate) fn remap_xrefs(text: &str, mut remap: impl FnMut(&str) -> Option<String>) -> String {
+    let mut remapped = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let fields = LineFields::split(line);
+        let tag = fields.tag.unwrap_or_default();
+        let mut rewrite = |xref: &str| remap(xref).unwrap_or_else(|| xref.to_string());
+        remapped.push_str(fields.level);
+        if let Some(xref) = fields.xref {
+            remapped.push(' ');
+            remapped.push_str(&rewrite(xref));
+        }
+        if let Some(tag) = fields.tag {
+            remapped.push(' ');
+            remapped.push_str(tag);
+        }
+        if let Some(value) = fields.value {
+            remapped.push(' ');
+            if is_record_pointer(value) && !matches!(tag, "CONT" | "CONC") {
+                remapped.push_str(&rewrite(value));
+            } else {
+                remapped.push_str(value);
+            }
+        }
+        remapped.push_str(&line[line.trim_end_matches(['\r', '\n']).len()..]);
+    }
+    remapped
+}
+
+fn split_field(text: &str) -> (&str, Option<&str>) {
+    match text.split_once(' ') {
+        Some((field, rest)) => (field, Some(rest)),
+        None => (text, None),
+    }
+}
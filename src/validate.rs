@@ -0,0 +1,229 @@
+//! Integrity checks of the xrefs of a tree: records sharing an xref, pointers to records which
+//! don't exist, and pointers to a record of the wrong kind, e.g. a FAMC pointing to an individual.
+//! `@VOID@` pointers, which stand for an unknown record in GEDCOM 7, are left alone.
+//!
+//! `GedcomData::validate` checks a parsed tree. `validate_stream` runs the same checks over the
+//! text of a file in two passes, without building the tree, so that files too large to hold in
+//! memory can be checked: the first pass collects the xref of each top-level record, and the
+//! second parses the records one at a time with `GedcomDocument::records`, checking the pointers
+//! of each against the xrefs collected. Only the xrefs are kept in memory.
+//!
+//! # Example
+//!
+//! ```
+//! use gedcom::{validate::{self, Issue}, GedcomDocument, ParseOptions};
+//! let sample = "\
+//!     0 HEAD\n\
+//!     1 GEDC\n\
+//!     2 VERS 5.5\n\
+//!     0 @I1@ INDI\n\
+//!     1 NAME John /Smith/\n\
+//!     1 FAMC @I2@\n\
+//!     1 FAMS @F9@\n\
+//!     0 @I2@ INDI\n\
+//!     1 NAME Mary /Brown/\n\
+//!     0 TRLR";
+//!
+//! let issues = validate::validate_stream(sample, ParseOptions::default()).unwrap();
+//! assert_eq!(
+//!     issues,
+//!     vec![
+//!         Issue::WrongRecordType {
+//!             record: "@I1@".to_string(),
+//!             tag: "FAMC".to_string(),
+//!             pointer: "@I2@".to_string(),
+//!             expected: "FAM",
+//!             found: "INDI".to_string(),
+//!         },
+//!         Issue::UnresolvedPointer {
+//!             record: "@I1@".to_string(),
+//!             tag: "FAMS".to_string(),
+//!             pointer: "@F9@".to_string(),
+//!         },
+//!     ]
+//! );
+//!
+//! let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+//! assert_eq!(data.validate(), issues);
+//! ```
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    util::{is_record_pointer, split_lines, LineFields},
+    GedcomData, GedcomDocument, GedcomError, ParseOptions,
+};
+
+/// `Issue` is an integrity problem found by `GedcomData::validate` or `validate_stream`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    /// More than one top-level record has the same xref
+    DuplicateXref {
+        /// The xref defined more than once
+        xref: String,
+        /// The tag of a later record defining it, e.g. INDI
+        tag: String,
+    },
+    /// A pointer to a record which doesn't exist
+    UnresolvedPointer {
+        /// The xref of the record holding the pointer, or its tag when it has none, e.g. HEAD
+        record: String,
+        /// The tag of the line holding the pointer, e.g. FAMC
+        tag: String,
+        /// The pointer
+        pointer: String,
+    },
+    /// A pointer to a record of another kind than its tag calls for
+    WrongRecordType {
+        /// The xref of the record holding the pointer, or its tag when it has none, e.g. HEAD
+        record: String,
+        /// The tag of the line holding the pointer, e.g. FAMC
+        tag: String,
+        /// The pointer
+        pointer: String,
+        /// The tag of the records the pointer should lead to, e.g. FAM
+        expected: &'static str,
+        /// The tag of the record it leads to
+        found: String,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::DuplicateXref { xref, tag } => {
+                write!(
+                    f,
+                    "{xref} is defined more than once, again by a record tagged {tag}"
+                )
+            }
+            Issue::UnresolvedPointer {
+                record,
+                tag,
+                pointer,
+            } => write!(f, "{tag} {pointer} of {record} points to no record"),
+            Issue::WrongRecordType {
+                record,
+                tag,
+                pointer,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{tag} {pointer} of {record} points to a record tagged {found}, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl GedcomData {
+    /// Checks the xrefs and pointers of the tree, see the `validate` module
+    #[must_use]
+    pub fn validate(&self) -> Vec<Issue> {
+        let text = self.to_gedcom_string();
+        let mut validator = Validator::new(&text);
+        validator.check(&text);
+        validator.issues
+    }
+}
+
+/// Checks the xrefs and pointers of the file `contents` in two passes, holding a single record in
+/// memory at a time, see the `validate` module. Records the parser skips, e.g. SNOTE records, can
+/// be pointed to but their own pointers aren't checked.
+///
+/// # Errors
+///
+/// Returns the `GedcomError` failing the parse of a record. With `ParseOptions::lenient`, only
+/// problems the parser can't skip past are returned.
+pub fn validate_stream(contents: &str, options: ParseOptions) -> Result<Vec<Issue>, GedcomError> {
+    let mut validator = Validator::new(contents);
+    let mut doc = GedcomDocument::with_options(contents.chars(), options);
+    for record in doc.records() {
+        let mut single = GedcomData::default();
+        single.add_record(record?);
+        validator.check(&single.to_gedcom_string());
+    }
+    Ok(validator.issues)
+}
+
+struct Validator<'a> {
+    /// The tag of the record defining each xref
+    definitions: HashMap<&'a str, &'a str>,
+    issues: Vec<Issue>,
+}
+
+impl<'a> Validator<'a> {
+    /// Collects the xrefs defined by the level 0 lines of `text`
+    fn new(text: &'a str) -> Validator<'a> {
+        let mut definitions = HashMap::new();
+        let mut issues = Vec::new();
+        for line in split_lines(text) {
+            let line = LineFields::split(line.trim_start_matches('\u{feff}').trim_start());
+            let (Some(xref), Some(tag)) = (line.xref, line.tag) else {
+                continue;
+            };
+            if line.level == "0" && definitions.insert(xref, tag).is_some() {
+                issues.push(Issue::DuplicateXref {
+                    xref: xref.to_string(),
+                    tag: tag.to_string(),
+                });
+            }
+        }
+        Validator {
+            definitions,
+            issues,
+        }
+    }
+
+    /// Checks the pointers of records as written out by `GedcomWriter`
+    fn check(&mut self, text: &str) {
+        let mut record = "";
+        for line in text.lines().map(LineFields::split) {
+            let tag = line.tag.unwrap_or_default();
+            if line.level == "0" {
+                record = line.xref.unwrap_or(tag);
+            }
+            // continued text can look like a pointer
+            if matches!(tag, "CONT" | "CONC") {
+                continue;
+            }
+            let Some(pointer) = line.value.filter(|value| is_record_pointer(value)) else {
+                continue;
+            };
+            let expected = expected_record(tag);
+            match self.definitions.get(pointer) {
+                None => self.issues.push(Issue::UnresolvedPointer {
+                    record: record.to_string(),
+                    tag: tag.to_string(),
+                    pointer: pointer.to_string(),
+                }),
+                Some(found) => {
+                    if let Some(expected) = expected.filter(|expected| expected != found) {
+                        self.issues.push(Issue::WrongRecordType {
+                            record: record.to_string(),
+                            tag: tag.to_string(),
+                            pointer: pointer.to_string(),
+                            expected,
+                            found: (*found).to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The tag of the records a pointer under `tag` should lead to, when the tag calls for one
+pub(crate) fn expected_record(tag: &str) -> Option<&'static str> {
+    match tag {
+        "FAMC" | "FAMS" => Some("FAM"),
+        "HUSB" | "WIFE" | "CHIL" | "ASSO" | "ALIA" => Some("INDI"),
+        "SUBM" | "ANCI" | "DESI" => Some("SUBM"),
+        "SUBN" => Some("SUBN"),
+        "SOUR" => Some("SOUR"),
+        "REPO" => Some("REPO"),
+        "OBJE" => Some("OBJE"),
+        "NOTE" => Some("NOTE"),
+        _ => None,
+    }
+}
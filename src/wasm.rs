@@ -0,0 +1,143 @@
+//! JavaScript bindings, built with the `wasm` feature, so that browser-based genealogy viewers can
+//! parse and browse trees through [`wasm-bindgen`](https://rustwasm.github.io/wasm-bindgen/).
+//!
+//! Files are parsed in lenient mode, as real-world files often bend the specification, with the
+//! problems skipped kept as warnings. `parse` hands the whole tree over as a JavaScript object,
+//! while the records of a `Tree` are handed over one at a time as JSON text, for `JSON.parse` on
+//! the JavaScript side. Errors are thrown as strings. Nothing in this path prints or panics on bad
+//! input, which would abort the whole module.
+//!
+//! The crate is built as a library for Rust users, so the WebAssembly module is built as a
+//! `cdylib` on the command line, then bound with the `wasm-bindgen` tool:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/gedcom.wasm
+//! ```
+//!
+//! ```js
+//! import init, { parse, Tree } from "./pkg/gedcom.js";
+//!
+//! await init();
+//! console.log(parse(text).individuals.length);
+//! const tree = new Tree(text);
+//! for (const { xref, score } of JSON.parse(tree.search("Smith"))) {
+//!     const person = JSON.parse(tree.individual(xref));
+//!     console.log(person.name.value, tree.parents(xref));
+//! }
+//! ```
+
+use serde::Serialize;
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::{search::SearchMode, GedcomData, GedcomDocument, ParseOptions};
+
+/// Parses GEDCOM text into its `GedcomData`, as a JavaScript object of the same shape as its JSON
+///
+/// # Errors
+///
+/// Returns the message of the problem when the text can't be parsed at all.
+#[wasm_bindgen]
+pub fn parse(text: &str) -> Result<JsValue, String> {
+    Tree::new(text)?
+        .data
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|err| err.to_string())
+}
+
+/// `Tree` is a parsed tree held on the WebAssembly side, to search and browse without handing
+/// the whole tree over to JavaScript.
+#[wasm_bindgen]
+pub struct Tree {
+    data: GedcomData,
+    warnings: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl Tree {
+    /// Parses GEDCOM text
+    ///
+    /// # Errors
+    ///
+    /// Returns the message of the problem when the text can't be parsed at all.
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &str) -> Result<Tree, String> {
+        let mut doc = GedcomDocument::with_options(text.chars(), ParseOptions::lenient());
+        let data = doc.parse_document().map_err(|err| err.to_string())?;
+        let warnings = doc.warnings().iter().map(ToString::to_string).collect();
+        Ok(Tree { data, warnings })
+    }
+
+    /// The problems skipped while parsing
+    #[must_use]
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+
+    /// The whole tree as JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns the message of the problem when the tree can't be serialized.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(&self.data).map_err(|err| err.to_string())
+    }
+
+    /// The individual with the xref as JSON, if there is one
+    #[must_use]
+    pub fn individual(&self, xref: &str) -> Option<String> {
+        let indi = self
+            .data
+            .individuals
+            .iter()
+            .find(|indi| indi.xref.as_deref() == Some(xref))?;
+        serde_json::to_string(indi).ok()
+    }
+
+    /// The family with the xref as JSON, if there is one
+    #[must_use]
+    pub fn family(&self, xref: &str) -> Option<String> {
+        let fam = self
+            .data
+            .families
+            .iter()
+            .find(|fam| fam.xref.as_deref() == Some(xref))?;
+        serde_json::to_string(fam).ok()
+    }
+
+    /// The individuals with a name sounding like `name`, as a JSON array of `{ xref, score }`
+    /// objects, best matches first
+    #[must_use]
+    pub fn search(&self, name: &str) -> String {
+        let results: Vec<_> = self
+            .data
+            .search_individuals(name, SearchMode::Soundex)
+            .into_iter()
+            .map(|result| serde_json::json!({ "xref": result.xref, "score": result.score }))
+            .collect();
+        serde_json::Value::from(results).to_string()
+    }
+
+    /// The xrefs of the parents of the individual, from all of the families they are a child of
+    #[must_use]
+    pub fn parents(&self, xref: &str) -> Vec<String> {
+        xrefs(self.data.query().individuals().parent_of(xref).iter())
+    }
+
+    /// The xrefs of the children of the individual
+    #[must_use]
+    pub fn children(&self, xref: &str) -> Vec<String> {
+        xrefs(self.data.query().individuals().child_of(xref).iter())
+    }
+
+    /// The xrefs of the spouses of the individual
+    #[must_use]
+    pub fn spouses(&self, xref: &str) -> Vec<String> {
+        xrefs(self.data.query().individuals().spouse_of(xref).iter())
+    }
+}
+
+fn xrefs<'a>(individuals: impl Iterator<Item = &'a crate::types::Individual>) -> Vec<String> {
+    individuals.filter_map(|indi| indi.xref.clone()).collect()
+}
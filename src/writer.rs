@@ -0,0 +1,1207 @@
+//! Serializes a `GedcomData` tree back into GEDCOM text.
+//!
+//! Tags are written in the order of the GEDCOM 5.5.1 specification rather than the order they
+//! appeared in the parsed file. A field which is `Some` is always written, even when its value is
+//! empty: `Some(String::new())` becomes a tag line without a payload, e.g. `1 NOTE`, while `None`
+//! writes nothing. The output is always UTF-8.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::{
+    convert,
+    store::{Record, RecordStore, Records},
+    types::{
+        Address, Association, AttributeDetail, ChangeDate, ContactInfo, Corporation, Date,
+        EventDetail, ExternalId, Family, FamilyLink, Gender, Header, Individual,
+        MultimediaFileRefn, MultimediaFormat, MultimediaLink, MultimediaRecord, Name, Note,
+        NoteRecord, RepoCitation, Repository, Source, SourceCitation, Submission, Submitter,
+        Translation, UserDefinedDataset,
+    },
+    util::{escape_at_signs, remap_xrefs, LineFields},
+    GedcomData,
+};
+
+#[cfg(feature = "lds")]
+use crate::types::LdsOrdinance;
+
+/// `LineEnding` is the terminator written after each line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// A line feed, `\n`
+    Lf,
+    /// A carriage return and line feed, `\r\n`
+    CrLf,
+}
+
+/// `TargetVersion` is a version of GEDCOM the output of `GedcomWriter` can be converted to, see the
+/// `convert` module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetVersion {
+    /// GEDCOM 5.5.1
+    V551,
+    /// GEDCOM 7.0
+    V7,
+}
+
+/// `WriterOptions` controls the formatting of the output of `GedcomWriter`.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{writer::{GedcomWriter, LineEnding, WriterOptions}, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NOTE Born in a small town\n\
+///     2 CONT near the coast\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let options = WriterOptions {
+///     line_ending: LineEnding::CrLf,
+///     max_payload_length: Some(10),
+///     ..WriterOptions::default()
+/// };
+///
+/// let mut writer = GedcomWriter::with_options(Vec::new(), options);
+/// writer.write(&data).unwrap();
+/// let output = String::from_utf8(writer.into_inner()).unwrap();
+///
+/// assert!(output.split_terminator('\n').all(|line| line.ends_with('\r')));
+/// assert!(output.contains("1 NOTE Born i\r\n2 CONC n a smal\r\n2 CONC l town\r\n"));
+///
+/// // Reading the output back joins the CONC lines again
+/// let reparsed = GedcomDocument::new(output.chars()).parse_document().unwrap();
+/// let note = &reparsed.individuals[0].notes[0];
+/// assert_eq!(note.value.as_deref(), Some("Born in a small town\nnear the coast"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct WriterOptions {
+    /// The terminator written after each line, `LineEnding::Lf` by default
+    pub line_ending: LineEnding,
+    /// The longest payload written on a single line, in characters. Longer payloads are split
+    /// over CONC lines. GEDCOM 5.5.1 limits lines to 255 characters, but by default payloads
+    /// aren't split.
+    pub max_payload_length: Option<usize>,
+    /// Renumbers the records as they are written, see `XrefFormat`. By default records keep the
+    /// xrefs they were read with.
+    pub xref_format: Option<XrefFormat>,
+    /// Writes a copy of the tree normalized by `GedcomData::normalize`, leaving the tree itself
+    /// untouched. Records are then regenerated even when the tree has a source map.
+    pub normalize: bool,
+    /// Converts the output to another version of GEDCOM, see the `convert` module. By default the
+    /// tree is written as it is.
+    pub target_version: Option<TargetVersion>,
+    /// The number of spaces written before the level number of a line for each level, e.g. 2 to
+    /// write `1 NAME` as `  1 NAME`, which is easier to read and diff. Leading whitespace is
+    /// allowed when reading, but not by the specification, so lines aren't indented by default.
+    pub indent: usize,
+}
+
+impl Default for WriterOptions {
+    fn default() -> WriterOptions {
+        WriterOptions {
+            line_ending: LineEnding::Lf,
+            max_payload_length: None,
+            xref_format: None,
+            normalize: false,
+            target_version: None,
+            indent: 0,
+        }
+    }
+}
+
+/// `XrefStyle` is how the xrefs of a kind of record are formatted: the prefix followed by the
+/// number of the record, counted from 1 and zero-padded to `padding` digits, e.g. `@P0001@`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XrefStyle {
+    /// Written before the number, e.g. `I` for individuals
+    pub prefix: &'static str,
+    /// The least number of digits of the number, 0 for no padding
+    pub padding: usize,
+}
+
+impl XrefStyle {
+    /// A style with the given prefix and no padding
+    #[must_use]
+    pub fn new(prefix: &'static str) -> XrefStyle {
+        XrefStyle { prefix, padding: 0 }
+    }
+
+    pub(crate) fn xref(self, number: usize) -> String {
+        format!("@{}{number:0width$}@", self.prefix, width = self.padding)
+    }
+}
+
+/// `XrefFormat` renumbers the records written by `GedcomWriter`, with an `XrefStyle` for each kind
+/// of record. Records are numbered in the order they are written, counting separately for each
+/// prefix, and every pointer to a record is rewritten along with it. Pointers to records missing
+/// from the tree are left as is.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{writer::{GedcomWriter, WriterOptions, XrefFormat, XrefStyle}, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @FATHER@ INDI\n\
+///     1 FAMS @FAMILY@\n\
+///     0 @CHILD@ INDI\n\
+///     1 FAMC @FAMILY@\n\
+///     0 @FAMILY@ FAM\n\
+///     1 HUSB @FATHER@\n\
+///     1 CHIL @CHILD@\n\
+///     0 TRLR";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+/// let options = WriterOptions {
+///     xref_format: Some(XrefFormat {
+///         individual: XrefStyle { prefix: "P", padding: 4 },
+///         ..XrefFormat::default()
+///     }),
+///     ..WriterOptions::default()
+/// };
+///
+/// let mut writer = GedcomWriter::with_options(Vec::new(), options);
+/// writer.write(&data).unwrap();
+/// let output = String::from_utf8(writer.into_inner()).unwrap();
+///
+/// assert!(output.contains("0 @P0001@ INDI\n1 FAMS @F1@\n"));
+/// assert!(output.contains("0 @F1@ FAM\n1 HUSB @P0001@\n1 CHIL @P0002@\n"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XrefFormat {
+    /// INDI records, `I` by default
+    pub individual: XrefStyle,
+    /// FAM records, `F` by default
+    pub family: XrefStyle,
+    /// SOUR records, `S` by default
+    pub source: XrefStyle,
+    /// REPO records, `R` by default
+    pub repository: XrefStyle,
+    /// OBJE records, `M` by default
+    pub multimedia: XrefStyle,
+    /// NOTE records, `N` by default
+    pub note: XrefStyle,
+    /// SUBM records, `U` by default
+    pub submitter: XrefStyle,
+    /// SUBN records, `B` by default
+    pub submission: XrefStyle,
+    /// Any other record, e.g. user-defined ones, `X` by default
+    pub other: XrefStyle,
+}
+
+impl Default for XrefFormat {
+    fn default() -> XrefFormat {
+        XrefFormat {
+            individual: XrefStyle::new("I"),
+            family: XrefStyle::new("F"),
+            source: XrefStyle::new("S"),
+            repository: XrefStyle::new("R"),
+            multimedia: XrefStyle::new("M"),
+            note: XrefStyle::new("N"),
+            submitter: XrefStyle::new("U"),
+            submission: XrefStyle::new("B"),
+            other: XrefStyle::new("X"),
+        }
+    }
+}
+
+impl XrefFormat {
+    /// The default prefixes, with every number zero-padded to `padding` digits
+    #[must_use]
+    pub fn padded(padding: usize) -> XrefFormat {
+        let mut format = XrefFormat::default();
+        for style in format.styles_mut() {
+            style.padding = padding;
+        }
+        format
+    }
+
+    /// The style of records with the given tag, e.g. INDI
+    #[must_use]
+    pub fn style(&self, tag: &str) -> XrefStyle {
+        match tag {
+            "INDI" => self.individual,
+            "FAM" => self.family,
+            "SOUR" => self.source,
+            "REPO" => self.repository,
+            "OBJE" => self.multimedia,
+            "NOTE" => self.note,
+            "SUBM" => self.submitter,
+            "SUBN" => self.submission,
+            _ => self.other,
+        }
+    }
+
+    fn styles_mut(&mut self) -> [&mut XrefStyle; 9] {
+        [
+            &mut self.individual,
+            &mut self.family,
+            &mut self.source,
+            &mut self.repository,
+            &mut self.multimedia,
+            &mut self.note,
+            &mut self.submitter,
+            &mut self.submission,
+            &mut self.other,
+        ]
+    }
+
+    /// Renumbers the records of GEDCOM text as written by `GedcomWriter`
+    fn renumber(&self, text: &str) -> String {
+        let mut xrefs = HashMap::new();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for line in text.lines().map(LineFields::split) {
+            let (Some(xref), Some(tag), "0") = (line.xref, line.tag, line.level) else {
+                continue;
+            };
+            let style = self.style(tag);
+            let count = counts.entry(style.prefix).or_insert(0);
+            *count += 1;
+            xrefs.entry(xref).or_insert_with(|| style.xref(*count));
+        }
+        remap_xrefs(text, |xref| xrefs.get(xref).cloned())
+    }
+}
+
+/// `GedcomWriter` writes a `GedcomData` tree as GEDCOM lines.
+///
+/// # Example
+///
+/// ```
+/// use gedcom::{writer::GedcomWriter, GedcomDocument};
+/// let sample = "\
+///     0 HEAD\n\
+///     1 GEDC\n\
+///     2 VERS 5.5\n\
+///     0 @I1@ INDI\n\
+///     1 NAME John /Doe/\n\
+///     1 NOTE\n\
+///     0 TRLR\n";
+///
+/// let data = GedcomDocument::new(sample.chars()).parse_document().unwrap();
+///
+/// let mut output = Vec::new();
+/// GedcomWriter::new(&mut output).write(&data).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), sample);
+/// ```
+pub struct GedcomWriter<W: Write> {
+    out: W,
+    options: WriterOptions,
+}
+
+impl<W: Write> GedcomWriter<W> {
+    /// Creates a writer with the default `WriterOptions`
+    pub fn new(out: W) -> GedcomWriter<W> {
+        GedcomWriter::with_options(out, WriterOptions::default())
+    }
+
+    /// Creates a writer formatting its output according to `options`
+    pub fn with_options(out: W, options: WriterOptions) -> GedcomWriter<W> {
+        GedcomWriter { out, options }
+    }
+
+    /// Unwraps the underlying output
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    /// Writes every record of `data`, followed by the TRLR record. `data` is usually a
+    /// `GedcomData`, but can be any tree implementing `Records`. When `data` has a source map,
+    /// records left untouched since the parse are written as they were read, see the `roundtrip`
+    /// module. With `WriterOptions::xref_format`, the records are then renumbered, and with
+    /// `WriterOptions::target_version` the output is converted to another version of GEDCOM.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by the underlying output.
+    pub fn write<R: Records + ?Sized>(&mut self, data: &R) -> io::Result<()> {
+        if self.options.normalize {
+            let mut normalized = GedcomData {
+                header: data.header().cloned(),
+                submitters: owned(data.submitters()),
+                submissions: owned(data.submissions()),
+                individuals: owned(data.individuals()),
+                families: owned(data.families()),
+                repositories: owned(data.repositories()),
+                sources: owned(data.sources()),
+                multimedia: owned(data.multimedia()),
+                notes: owned(data.notes()),
+                custom_data: data.custom_data().to_vec(),
+                ..GedcomData::default()
+            };
+            normalized.normalize();
+            self.options.normalize = false;
+            let written = self.write(&normalized);
+            self.options.normalize = true;
+            return written;
+        }
+        if let Some(version) = self.options.target_version {
+            let options = WriterOptions {
+                line_ending: LineEnding::Lf,
+                // GEDCOM 7 has no CONC lines
+                max_payload_length: self
+                    .options
+                    .max_payload_length
+                    .filter(|_| version == TargetVersion::V551),
+                target_version: None,
+                indent: 0,
+                ..self.options
+            };
+            let mut writer = GedcomWriter::with_options(Vec::new(), options);
+            writer.write(data)?;
+            let text = String::from_utf8_lossy(&writer.out);
+            let text = match version {
+                TargetVersion::V551 => convert::downgrade(&text).text,
+                TargetVersion::V7 => convert::upgrade(&text),
+            };
+            return match self.options.line_ending {
+                LineEnding::Lf => self.write_indented(&text),
+                LineEnding::CrLf => self.write_indented(&text.replace('\n', "\r\n")),
+            };
+        }
+        if let Some(format) = self.options.xref_format {
+            let options = WriterOptions {
+                xref_format: None,
+                indent: 0,
+                ..self.options
+            };
+            let mut writer = GedcomWriter::with_options(Vec::new(), options);
+            writer.write(data)?;
+            let text = String::from_utf8_lossy(&writer.out);
+            return self.write_indented(&format.renumber(&text));
+        }
+        let Some(source_map) = data.source_map() else {
+            return self.write_records(data);
+        };
+        let render = |options| -> io::Result<String> {
+            let mut writer = GedcomWriter::with_options(Vec::new(), options);
+            writer.write_records(data)?;
+            Ok(String::from_utf8_lossy(&writer.out).into_owned())
+        };
+        let compared = render(WriterOptions::default())?;
+        let regenerated = render(self.options)?;
+        source_map.write(&mut self.out, &compared, &regenerated)
+    }
+
+    /// Writes every record of `data` afresh, followed by the TRLR record
+    fn write_records<R: Records + ?Sized>(&mut self, data: &R) -> io::Result<()> {
+        if let Some(header) = data.header() {
+            self.header(header)?;
+        }
+        for subm in data.submitters().iter_records() {
+            self.submitter(&subm)?;
+        }
+        for subn in data.submissions().iter_records() {
+            self.submission(&subn)?;
+        }
+        for indi in data.individuals().iter_records() {
+            self.individual(&indi)?;
+        }
+        for fam in data.families().iter_records() {
+            self.family(&fam)?;
+        }
+        for sour in data.sources().iter_records() {
+            self.source(&sour)?;
+        }
+        for repo in data.repositories().iter_records() {
+            self.repository(&repo)?;
+        }
+        for obje in data.multimedia().iter_records() {
+            self.multimedia_record(0, &obje)?;
+        }
+        for note in data.notes().iter_records() {
+            self.note_record(&note)?;
+        }
+        for custom in data.custom_data() {
+            self.custom(0, custom)?;
+        }
+        self.line(0, None, "TRLR", None)
+    }
+
+    /// Writes a single line
+    fn line(
+        &mut self,
+        level: u8,
+        xref: Option<&str>,
+        tag: &str,
+        value: Option<&str>,
+    ) -> io::Result<()> {
+        let indent = usize::from(level) * self.options.indent;
+        write!(self.out, "{:indent$}{level}", "")?;
+        if let Some(xref) = xref {
+            write!(self.out, " {xref}")?;
+        }
+        write!(self.out, " {tag}")?;
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            write!(self.out, " {}", escape_at_signs(value))?;
+        }
+        match self.options.line_ending {
+            LineEnding::Lf => writeln!(self.out),
+            LineEnding::CrLf => write!(self.out, "\r\n"),
+        }
+    }
+
+    /// Writes GEDCOM text written without indentation, indenting its lines according to
+    /// `WriterOptions::indent`
+    fn write_indented(&mut self, text: &str) -> io::Result<()> {
+        if self.options.indent == 0 {
+            return self.out.write_all(text.as_bytes());
+        }
+        for line in text.split_inclusive('\n') {
+            let level = LineFields::split(line).level.parse::<usize>().unwrap_or(0);
+            let indent = level * self.options.indent;
+            write!(self.out, "{:indent$}{line}", "")?;
+        }
+        Ok(())
+    }
+
+    /// Writes a line if the field is present, even when its value is empty
+    fn field(&mut self, level: u8, tag: &str, value: Option<&str>) -> io::Result<()> {
+        match value {
+            Some(value) => self.line(level, None, tag, Some(value)),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes a payload which may span multiple lines, continuing it with CONT and CONC lines
+    fn text(&mut self, level: u8, tag: &str, value: &str) -> io::Result<()> {
+        self.record_text(level, None, tag, value)
+    }
+
+    /// Like `text`, for a payload on the line of a record with an xref, e.g. `0 @N1@ NOTE`
+    fn record_text(
+        &mut self,
+        level: u8,
+        xref: Option<&str>,
+        tag: &str,
+        value: &str,
+    ) -> io::Result<()> {
+        for (i, text) in value.split('\n').enumerate() {
+            let mut parts = split_payload(text, self.options.max_payload_length);
+            if i == 0 {
+                self.line(level, xref, tag, parts.next())?;
+            } else {
+                self.line(level + 1, None, "CONT", parts.next())?;
+            }
+            for part in parts {
+                self.line(level + 1, None, "CONC", Some(part))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a multi-line payload if the field is present, even when its value is empty
+    fn text_field(&mut self, level: u8, tag: &str, value: Option<&str>) -> io::Result<()> {
+        match value {
+            Some(value) => self.text(level, tag, value),
+            None => Ok(()),
+        }
+    }
+
+    fn header(&mut self, header: &Header) -> io::Result<()> {
+        self.line(0, None, "HEAD", None)?;
+        if let Some(sour) = &header.source {
+            self.line(1, None, "SOUR", sour.value.as_deref())?;
+            self.field(2, "VERS", sour.version.as_deref())?;
+            self.field(2, "NAME", sour.name.as_deref())?;
+            if let Some(corp) = &sour.corporation {
+                self.corporation(2, corp)?;
+            }
+            if let Some(data) = &sour.data {
+                self.line(2, None, "DATA", data.value.as_deref())?;
+                if let Some(date) = &data.date {
+                    self.date(3, date)?;
+                }
+                self.text_field(3, "COPR", data.copyright.as_deref())?;
+                self.custom_data(3, &data.custom_data)?;
+            }
+            self.custom_data(2, &sour.custom_data)?;
+        }
+        self.field(1, "DEST", header.destination.as_deref())?;
+        if let Some(date) = &header.date {
+            self.date(1, date)?;
+        }
+        self.field(1, "SUBM", header.submitter_tag.as_deref())?;
+        self.field(1, "SUBN", header.submission_tag.as_deref())?;
+        self.field(1, "FILE", header.filename.as_deref())?;
+        self.text_field(1, "COPR", header.copyright.as_deref())?;
+        if let Some(gedc) = &header.gedcom {
+            self.line(1, None, "GEDC", None)?;
+            self.field(2, "VERS", gedc.version.as_deref())?;
+            self.field(2, "FORM", gedc.form.as_deref())?;
+            self.custom_data(2, &gedc.custom_data)?;
+        }
+        if let Some(encoding) = &header.encoding {
+            self.line(1, None, "CHAR", encoding.value.as_deref())?;
+            self.field(2, "VERS", encoding.version.as_deref())?;
+            self.custom_data(2, &encoding.custom_data)?;
+        }
+        self.field(1, "LANG", header.language.as_deref())?;
+        if let Some(plac) = &header.place {
+            self.line(1, None, "PLAC", None)?;
+            self.line(2, None, "FORM", Some(&plac.form.join(", ")))?;
+            self.custom_data(2, &plac.custom_data)?;
+        }
+        if let Some(schema) = &header.schema {
+            self.line(1, None, "SCHMA", None)?;
+            for tag in &schema.tags {
+                self.line(2, None, "TAG", Some(&format!("{} {}", tag.tag, tag.uri)))?;
+            }
+            self.custom_data(2, &schema.custom_data)?;
+        }
+        for note in &header.notes {
+            self.note(1, note)?;
+        }
+        self.custom_data(1, &header.custom_data)
+    }
+
+    fn submitter(&mut self, subm: &Submitter) -> io::Result<()> {
+        self.line(0, subm.xref.as_deref(), "SUBM", None)?;
+        self.field(1, "NAME", subm.name.as_deref())?;
+        self.contact(1, &subm.contact)?;
+        for link in &subm.multimedia {
+            self.multimedia_link(1, link)?;
+        }
+        for language in &subm.languages {
+            self.line(1, None, "LANG", Some(language))?;
+        }
+        self.field(1, "RFN", subm.registered_refn.as_deref())?;
+        self.field(1, "RIN", subm.automated_record_id.as_deref())?;
+        for note in &subm.notes {
+            self.note(1, note)?;
+        }
+        if let Some(chan) = &subm.change_date {
+            self.change_date(1, "CHAN", chan)?;
+        }
+        if let Some(crea) = &subm.created {
+            self.change_date(1, "CREA", crea)?;
+        }
+        self.identifiers(1, &subm.uids, &subm.external_ids)?;
+        self.custom_data(1, &subm.custom_data)
+    }
+
+    fn submission(&mut self, subn: &Submission) -> io::Result<()> {
+        self.line(0, subn.xref.as_deref(), "SUBN", None)?;
+        self.field(1, "SUBM", subn.submitter_link.as_deref())?;
+        self.field(1, "FAMF", subn.name_of_family_file.as_deref())?;
+        #[cfg(feature = "lds")]
+        self.field(1, "TEMP", subn.temple_code.as_deref())?;
+        self.field(1, "ANCE", subn.generations_of_ancestors.as_deref())?;
+        self.field(1, "DESC", subn.generations_of_descendants.as_deref())?;
+        #[cfg(feature = "lds")]
+        self.field(1, "ORDI", subn.ordinance_process_flag.as_deref())?;
+        self.field(1, "RIN", subn.automated_record_id.as_deref())?;
+        for note in &subn.notes {
+            self.note(1, note)?;
+        }
+        if let Some(chan) = &subn.change_date {
+            self.change_date(1, "CHAN", chan)?;
+        }
+        self.custom_data(1, &subn.custom_data)
+    }
+
+    fn individual(&mut self, indi: &Individual) -> io::Result<()> {
+        self.line(0, indi.xref.as_deref(), "INDI", None)?;
+        for name in indi.names() {
+            self.name(1, name)?;
+        }
+        if let Some(sex) = &indi.sex {
+            self.gender(1, sex)?;
+        }
+        for event in &indi.events {
+            self.event(1, event)?;
+        }
+        for attr in &indi.attributes {
+            self.attribute(1, attr)?;
+        }
+        #[cfg(feature = "lds")]
+        for ordinance in &indi.lds_ordinances {
+            self.lds_ordinance(1, ordinance)?;
+        }
+        for link in &indi.families {
+            self.family_link(1, link)?;
+        }
+        if let Some(chan) = &indi.change_date {
+            self.change_date(1, "CHAN", chan)?;
+        }
+        if let Some(crea) = &indi.created {
+            self.change_date(1, "CREA", crea)?;
+        }
+        for note in &indi.notes {
+            self.note(1, note)?;
+        }
+        for citation in &indi.source {
+            self.source_citation(1, citation)?;
+        }
+        for obje in &indi.multimedia {
+            self.multimedia_record(1, obje)?;
+        }
+        self.identifiers(1, &indi.uids, &indi.external_ids)?;
+        self.custom_data(1, &indi.custom_data)
+    }
+
+    fn name(&mut self, level: u8, name: &Name) -> io::Result<()> {
+        self.line(level, None, "NAME", name.value.as_deref())?;
+        self.field(level + 1, "TYPE", name.name_type.as_deref())?;
+        self.field(level + 1, "NPFX", name.prefix.as_deref())?;
+        self.field(level + 1, "GIVN", name.given.as_deref())?;
+        self.field(level + 1, "SPFX", name.surname_prefix.as_deref())?;
+        self.field(level + 1, "SURN", name.surname.as_deref())?;
+        self.field(level + 1, "NSFX", name.suffix.as_deref())?;
+        for note in &name.notes {
+            self.note(level + 1, note)?;
+        }
+        for citation in &name.source {
+            self.source_citation(level + 1, citation)?;
+        }
+        self.custom_data(level + 1, &name.custom_data)
+    }
+
+    fn gender(&mut self, level: u8, sex: &Gender) -> io::Result<()> {
+        self.line(level, None, "SEX", Some(sex.value.tag()))?;
+        self.text_field(level + 1, "FACT", sex.fact.as_deref())?;
+        for citation in &sex.sources {
+            self.source_citation(level + 1, citation)?;
+        }
+        for note in &sex.notes {
+            self.note(level + 1, note)?;
+        }
+        self.custom_data(level + 1, &sex.custom_data)
+    }
+
+    #[cfg(feature = "lds")]
+    fn lds_ordinance(&mut self, level: u8, lds: &LdsOrdinance) -> io::Result<()> {
+        self.line(level, None, lds.ordinance.tag(), None)?;
+        if let Some(date) = &lds.date {
+            self.date(level + 1, date)?;
+        }
+        self.field(level + 1, "TEMP", lds.temple_code.as_deref())?;
+        self.field(level + 1, "PLAC", lds.place.as_deref())?;
+        if let Some(status) = &lds.status {
+            self.line(level + 1, None, "STAT", Some(&status.value))?;
+            if let Some(date) = &status.date {
+                self.date(level + 2, date)?;
+            }
+            self.custom_data(level + 2, &status.custom_data)?;
+        }
+        self.field(level + 1, "FAMC", lds.family.as_deref())?;
+        for citation in &lds.sources {
+            self.source_citation(level + 1, citation)?;
+        }
+        for note in &lds.notes {
+            self.note(level + 1, note)?;
+        }
+        self.custom_data(level + 1, &lds.custom_data)
+    }
+
+    fn family_link(&mut self, level: u8, link: &FamilyLink) -> io::Result<()> {
+        self.line(level, None, link.family_link_type.tag(), Some(&link.xref))?;
+        if let Some(pedigree) = &link.pedigree_linkage_type {
+            self.line(level + 1, None, "PEDI", Some(pedigree.code()))?;
+        }
+        if let Some(status) = &link.child_linkage_status {
+            self.line(level + 1, None, "STAT", Some(status.code()))?;
+        }
+        if let Some(adopted_by) = &link.adopted_by {
+            self.line(level + 1, None, "ADOP", Some(adopted_by.code()))?;
+        }
+        if let Some(relationship) = &link.father_relationship {
+            self.line(level + 1, None, "_FREL", Some(relationship.code()))?;
+        }
+        if let Some(relationship) = &link.mother_relationship {
+            self.line(level + 1, None, "_MREL", Some(relationship.code()))?;
+        }
+        for note in &link.notes {
+            self.note(level + 1, note)?;
+        }
+        self.custom_data(level + 1, &link.custom_data)
+    }
+
+    fn event(&mut self, level: u8, event: &EventDetail) -> io::Result<()> {
+        self.line(level, None, event.event.tag(), event.value.as_deref())?;
+        self.field(level + 1, "TYPE", event.event_type.as_deref())?;
+        self.event_details(level + 1, event)
+    }
+
+    /// Writes the substructures of an event, shared with the events recorded by a source
+    fn event_details(&mut self, level: u8, event: &EventDetail) -> io::Result<()> {
+        if let Some(date) = &event.date {
+            self.date(level, date)?;
+        }
+        match &event.coordinates {
+            Some(map) => {
+                self.line(level, None, "PLAC", event.place.as_deref())?;
+                self.line(level + 1, None, "MAP", None)?;
+                self.field(level + 2, "LATI", map.latitude.as_deref())?;
+                self.field(level + 2, "LONG", map.longitude.as_deref())?;
+                self.custom_data(level + 2, &map.custom_data)?;
+            }
+            None => self.field(level, "PLAC", event.place.as_deref())?,
+        }
+        self.contact(level, &event.contact)?;
+        self.field(level, "AGNC", event.agency.as_deref())?;
+        self.field(level, "RELI", event.religion.as_deref())?;
+        self.field(level, "CAUS", event.cause.as_deref())?;
+        self.field(level, "RESN", event.restriction.as_deref())?;
+        self.field(level, "AGE", event.age.as_deref())?;
+        for detail in &event.family_event_details {
+            self.line(level, None, detail.member.tag(), detail.value.as_deref())?;
+            self.field(level + 1, "AGE", detail.age.as_deref())?;
+            self.custom_data(level + 1, &detail.custom_data)?;
+        }
+        if let Some(link) = &event.family_link {
+            self.family_link(level, link)?;
+        }
+        for asso in &event.associations {
+            self.association(level, asso)?;
+        }
+        for note in &event.notes {
+            self.note(level, note)?;
+        }
+        for citation in &event.citations {
+            self.source_citation(level, citation)?;
+        }
+        for obje in &event.multimedia {
+            self.multimedia_record(level, obje)?;
+        }
+        self.custom_data(level, &event.custom_data)
+    }
+
+    fn association(&mut self, level: u8, asso: &Association) -> io::Result<()> {
+        self.line(level, None, &asso.tag, asso.value.as_deref())?;
+        self.field(level + 1, "NAME", asso.name.as_deref())?;
+        let role_tag = if asso.tag == "ASSO" { "RELA" } else { "ROLE" };
+        self.field(level + 1, role_tag, asso.role.as_deref())?;
+        for note in &asso.notes {
+            self.note(level + 1, note)?;
+        }
+        for citation in &asso.citations {
+            self.source_citation(level + 1, citation)?;
+        }
+        self.custom_data(level + 1, &asso.custom_data)
+    }
+
+    fn attribute(&mut self, level: u8, attr: &AttributeDetail) -> io::Result<()> {
+        self.line(level, None, attr.attribute.tag(), attr.value.as_deref())?;
+        self.text_field(level + 1, "TYPE", attr.attribute_type.as_deref())?;
+        if let Some(date) = &attr.date {
+            self.date(level + 1, date)?;
+        }
+        self.field(level + 1, "PLAC", attr.place.as_deref())?;
+        for note in &attr.notes {
+            self.note(level + 1, note)?;
+        }
+        for citation in &attr.sources {
+            self.source_citation(level + 1, citation)?;
+        }
+        self.custom_data(level + 1, &attr.custom_data)
+    }
+
+    fn family(&mut self, fam: &Family) -> io::Result<()> {
+        self.line(0, fam.xref.as_deref(), "FAM", None)?;
+        self.field(1, "HUSB", fam.individual1.as_deref())?;
+        self.field(1, "WIFE", fam.individual2.as_deref())?;
+        for child in &fam.children {
+            self.line(1, None, "CHIL", Some(child))?;
+        }
+        self.field(1, "NCHI", fam.num_children.as_deref())?;
+        for event in fam.events.iter().chain(&fam.family_event) {
+            self.event(1, event)?;
+        }
+        for attr in &fam.attributes {
+            self.attribute(1, attr)?;
+        }
+        #[cfg(feature = "lds")]
+        for ordinance in &fam.lds_ordinances {
+            self.lds_ordinance(1, ordinance)?;
+        }
+        if let Some(chan) = &fam.change_date {
+            self.change_date(1, "CHAN", chan)?;
+        }
+        if let Some(crea) = &fam.created {
+            self.change_date(1, "CREA", crea)?;
+        }
+        for note in &fam.notes {
+            self.note(1, note)?;
+        }
+        for citation in &fam.sources {
+            self.source_citation(1, citation)?;
+        }
+        for obje in &fam.multimedia {
+            self.multimedia_record(1, obje)?;
+        }
+        self.identifiers(1, &fam.uids, &fam.external_ids)?;
+        self.custom_data(1, &fam.custom_data)
+    }
+
+    fn source(&mut self, sour: &Source) -> io::Result<()> {
+        self.line(0, sour.xref.as_deref(), "SOUR", None)?;
+        if !sour.data.is_empty() {
+            self.line(1, None, "DATA", None)?;
+            for recorded in &sour.data.events {
+                self.line(2, None, "EVEN", Some(&recorded.tags()))?;
+                if let Some(date) = &recorded.date {
+                    self.date(3, date)?;
+                }
+                self.field(3, "PLAC", recorded.place.as_deref())?;
+                self.custom_data(3, &recorded.custom_data)?;
+            }
+            self.field(2, "AGNC", sour.data.agency.as_deref())?;
+            for note in &sour.data.notes {
+                self.note(2, note)?;
+            }
+            self.custom_data(2, &sour.data.custom_data)?;
+        }
+        self.text_field(1, "AUTH", sour.author.as_deref())?;
+        self.text_field(1, "TITL", sour.title.as_deref())?;
+        self.text_field(1, "ABBR", sour.abbreviation.as_deref())?;
+        self.text_field(1, "PUBL", sour.publication_facts.as_deref())?;
+        self.text_field(1, "TEXT", sour.citation_from_source.as_deref())?;
+        for repo in &sour.repo_citations {
+            self.repo_citation(1, repo)?;
+        }
+        for obje in &sour.multimedia {
+            self.multimedia_record(1, obje)?;
+        }
+        for note in &sour.notes {
+            self.note(1, note)?;
+        }
+        for citation in &sour.citations {
+            self.source_citation(1, citation)?;
+        }
+        self.field(1, "RFN", sour.submitter_registered_rfn.as_deref())?;
+        if let Some(chan) = &sour.change_date {
+            self.change_date(1, "CHAN", chan)?;
+        }
+        if let Some(crea) = &sour.created {
+            self.change_date(1, "CREA", crea)?;
+        }
+        self.identifiers(1, &sour.uids, &sour.external_ids)?;
+        self.custom_data(1, &sour.custom_data)
+    }
+
+    fn source_citation(&mut self, level: u8, citation: &SourceCitation) -> io::Result<()> {
+        self.text(level, "SOUR", &citation.xref)?;
+        self.text_field(level + 1, "PAGE", citation.page.as_deref())?;
+        if let Some(data) = &citation.data {
+            self.line(level + 1, None, "DATA", None)?;
+            if let Some(date) = &data.date {
+                self.date(level + 2, date)?;
+            }
+            if let Some(text) = &data.text {
+                self.text_field(level + 2, "TEXT", text.value.as_deref())?;
+                self.custom_data(level + 3, &text.custom_data)?;
+            }
+            self.custom_data(level + 2, &data.custom_data)?;
+        }
+        if let Some(quay) = &citation.certainty_assessment {
+            let value = quay.get_int().map(|quay| quay.to_string());
+            self.line(level + 1, None, "QUAY", value.as_deref())?;
+        }
+        self.field(
+            level + 1,
+            "RFN",
+            citation.submitter_registered_rfn.as_deref(),
+        )?;
+        for note in &citation.notes {
+            self.note(level + 1, note)?;
+        }
+        for obje in &citation.multimedia {
+            self.multimedia_record(level + 1, obje)?;
+        }
+        self.custom_data(level + 1, &citation.custom_data)
+    }
+
+    fn repository(&mut self, repo: &Repository) -> io::Result<()> {
+        self.line(0, repo.xref.as_deref(), "REPO", None)?;
+        self.field(1, "NAME", repo.name.as_deref())?;
+        self.contact(1, &repo.contact)?;
+        for note in &repo.notes {
+            self.note(1, note)?;
+        }
+        for refn in &repo.user_reference_numbers {
+            self.line(1, None, "REFN", refn.value.as_deref())?;
+            self.field(2, "TYPE", refn.user_reference_type.as_deref())?;
+            self.custom_data(2, &refn.custom_data)?;
+        }
+        self.field(1, "RIN", repo.automated_record_id.as_deref())?;
+        if let Some(chan) = &repo.change_date {
+            self.change_date(1, "CHAN", chan)?;
+        }
+        if let Some(crea) = &repo.created {
+            self.change_date(1, "CREA", crea)?;
+        }
+        self.identifiers(1, &repo.uids, &repo.external_ids)?;
+        self.custom_data(1, &repo.custom_data)
+    }
+
+    fn repo_citation(&mut self, level: u8, repo: &RepoCitation) -> io::Result<()> {
+        self.line(level, None, "REPO", Some(&repo.xref))?;
+        self.field(level + 1, "CALN", repo.call_number.as_deref())?;
+        for note in &repo.notes {
+            self.note(level + 1, note)?;
+        }
+        self.custom_data(level + 1, &repo.custom_data)
+    }
+
+    fn multimedia_record(&mut self, level: u8, obje: &MultimediaRecord) -> io::Result<()> {
+        let xref = obje.xref.as_deref();
+        if level == 0 {
+            self.line(level, xref, "OBJE", None)?;
+        } else {
+            // a pointer to a multimedia record rather than a record of its own
+            self.line(level, None, "OBJE", xref)?;
+        }
+        if let Some(file) = &obje.file {
+            self.multimedia_file(level + 1, file)?;
+        }
+        if let Some(form) = &obje.form {
+            self.multimedia_format(level + 1, form)?;
+        }
+        self.field(level + 1, "TITL", obje.title.as_deref())?;
+        if let Some(refn) = &obje.user_reference_number {
+            self.line(level + 1, None, "REFN", refn.value.as_deref())?;
+            self.field(level + 2, "TYPE", refn.user_reference_type.as_deref())?;
+            self.custom_data(level + 2, &refn.custom_data)?;
+        }
+        self.field(level + 1, "RIN", obje.automated_record_id.as_deref())?;
+        for note in &obje.notes {
+            self.note(level + 1, note)?;
+        }
+        if let Some(citation) = &obje.source_citation {
+            self.source_citation(level + 1, citation)?;
+        }
+        if let Some(chan) = &obje.change_date {
+            self.change_date(level + 1, "CHAN", chan)?;
+        }
+        if let Some(crea) = &obje.created {
+            self.change_date(level + 1, "CREA", crea)?;
+        }
+        self.identifiers(level + 1, &obje.uids, &obje.external_ids)?;
+        self.custom_data(level + 1, &obje.custom_data)
+    }
+
+    fn multimedia_link(&mut self, level: u8, link: &MultimediaLink) -> io::Result<()> {
+        self.line(level, None, "OBJE", link.xref.as_deref())?;
+        if let Some(file) = &link.file {
+            self.multimedia_file(level + 1, file)?;
+        }
+        if let Some(form) = &link.form {
+            self.multimedia_format(level + 1, form)?;
+        }
+        self.field(level + 1, "TITL", link.title.as_deref())?;
+        self.custom_data(level + 1, &link.custom_data)
+    }
+
+    fn multimedia_file(&mut self, level: u8, file: &MultimediaFileRefn) -> io::Result<()> {
+        self.line(level, None, "FILE", file.value.as_deref())?;
+        if let Some(form) = &file.form {
+            self.multimedia_format(level + 1, form)?;
+        }
+        self.field(level + 1, "TITL", file.title.as_deref())?;
+        self.custom_data(level + 1, &file.custom_data)
+    }
+
+    fn multimedia_format(&mut self, level: u8, form: &MultimediaFormat) -> io::Result<()> {
+        self.line(level, None, "FORM", form.value.as_deref())?;
+        self.field(level + 1, "TYPE", form.source_media_type.as_deref())?;
+        self.custom_data(level + 1, &form.custom_data)
+    }
+
+    fn address(&mut self, level: u8, addr: &Address) -> io::Result<()> {
+        self.text(level, "ADDR", addr.value.as_deref().unwrap_or_default())?;
+        self.field(level + 1, "ADR1", addr.adr1.as_deref())?;
+        self.field(level + 1, "ADR2", addr.adr2.as_deref())?;
+        self.field(level + 1, "ADR3", addr.adr3.as_deref())?;
+        self.field(level + 1, "CITY", addr.city.as_deref())?;
+        self.field(level + 1, "STAE", addr.state.as_deref())?;
+        self.field(level + 1, "POST", addr.post.as_deref())?;
+        self.field(level + 1, "CTRY", addr.country.as_deref())?;
+        for note in &addr.notes {
+            self.note(level + 1, note)?;
+        }
+        self.custom_data(level + 1, &addr.custom_data)
+    }
+
+    fn corporation(&mut self, level: u8, corp: &Corporation) -> io::Result<()> {
+        self.line(level, None, "CORP", corp.value.as_deref())?;
+        self.contact(level + 1, &corp.contact)?;
+        self.custom_data(level + 1, &corp.custom_data)
+    }
+
+    fn contact(&mut self, level: u8, contact: &ContactInfo) -> io::Result<()> {
+        if let Some(addr) = &contact.address {
+            self.address(level, addr)?;
+        }
+        let tags = [
+            ("PHON", &contact.phones),
+            ("EMAIL", &contact.emails),
+            ("FAX", &contact.faxes),
+            ("WWW", &contact.websites),
+        ];
+        for (tag, values) in tags {
+            for value in values {
+                self.line(level, None, tag, Some(value))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn date(&mut self, level: u8, date: &Date) -> io::Result<()> {
+        self.line(level, None, "DATE", date.value.as_deref())?;
+        self.field(level + 1, "TIME", date.time.as_deref())?;
+        self.custom_data(level + 1, &date.custom_data)
+    }
+
+    /// Writes a CHAN or CREA structure
+    fn change_date(&mut self, level: u8, tag: &str, chan: &ChangeDate) -> io::Result<()> {
+        self.line(level, None, tag, None)?;
+        if let Some(date) = &chan.date {
+            self.date(level + 1, date)?;
+        }
+        for note in &chan.notes {
+            self.note(level + 1, note)?;
+        }
+        self.custom_data(level + 1, &chan.custom_data)
+    }
+
+    fn note(&mut self, level: u8, note: &Note) -> io::Result<()> {
+        self.text(level, "NOTE", note.value.as_deref().unwrap_or_default())?;
+        self.field(level + 1, "MIME", note.mime.as_deref())?;
+        if let Some(tran) = &note.translation {
+            self.translation(level + 1, tran)?;
+        }
+        self.field(level + 1, "LANG", note.language.as_deref())?;
+        self.custom_data(level + 1, &note.custom_data)
+    }
+
+    fn note_record(&mut self, note: &NoteRecord) -> io::Result<()> {
+        let text = note.value.as_deref().unwrap_or_default();
+        self.record_text(0, note.xref.as_deref(), "NOTE", text)?;
+        self.field(1, "MIME", note.mime.as_deref())?;
+        if let Some(tran) = &note.translation {
+            self.translation(1, tran)?;
+        }
+        self.field(1, "LANG", note.language.as_deref())?;
+        for citation in &note.sources {
+            self.source_citation(1, citation)?;
+        }
+        for refn in &note.user_reference_numbers {
+            self.line(1, None, "REFN", refn.value.as_deref())?;
+            self.field(2, "TYPE", refn.user_reference_type.as_deref())?;
+            self.custom_data(2, &refn.custom_data)?;
+        }
+        self.field(1, "RIN", note.automated_record_id.as_deref())?;
+        if let Some(chan) = &note.change_date {
+            self.change_date(1, "CHAN", chan)?;
+        }
+        if let Some(crea) = &note.created {
+            self.change_date(1, "CREA", crea)?;
+        }
+        self.identifiers(1, &note.uids, &note.external_ids)?;
+        self.custom_data(1, &note.custom_data)
+    }
+
+    fn translation(&mut self, level: u8, tran: &Translation) -> io::Result<()> {
+        self.text(level, "TRAN", tran.value.as_deref().unwrap_or_default())?;
+        self.field(level + 1, "MIME", tran.mime.as_deref())?;
+        self.field(level + 1, "LANG", tran.language.as_deref())?;
+        self.custom_data(level + 1, &tran.custom_data)
+    }
+
+    fn identifiers(&mut self, level: u8, uids: &[String], ids: &[ExternalId]) -> io::Result<()> {
+        for uid in uids {
+            self.line(level, None, "UID", Some(uid))?;
+        }
+        for id in ids {
+            self.line(level, None, id.kind.tag(), Some(&id.value))?;
+            self.field(level + 1, "TYPE", id.id_type.as_deref())?;
+            self.custom_data(level + 1, &id.custom_data)?;
+        }
+        Ok(())
+    }
+
+    fn custom_data(&mut self, level: u8, data: &[Box<UserDefinedDataset>]) -> io::Result<()> {
+        for custom in data {
+            self.custom(level, custom)?;
+        }
+        Ok(())
+    }
+
+    fn custom(&mut self, level: u8, custom: &UserDefinedDataset) -> io::Result<()> {
+        self.line(
+            level,
+            custom.xref.as_deref(),
+            &custom.tag,
+            custom.value.as_deref(),
+        )?;
+        self.custom_data(level + 1, &custom.children)
+    }
+}
+
+impl GedcomData {
+    /// Writes the tree as GEDCOM text with the default `WriterOptions`, see `GedcomWriter`.
+    #[must_use]
+    pub fn to_gedcom_string(&self) -> String {
+        let mut output = Vec::new();
+        // writing to a Vec can't fail
+        GedcomWriter::new(&mut output).write(self).ok();
+        String::from_utf8_lossy(&output).into_owned()
+    }
+}
+
+/// The GEDCOM text of a citation, for comparing citations
+pub(crate) fn citation_text(citation: &SourceCitation) -> String {
+    let mut writer = GedcomWriter::new(Vec::new());
+    // writing to a Vec can't fail
+    writer.source_citation(0, citation).ok();
+    String::from_utf8_lossy(&writer.out).into_owned()
+}
+
+/// Clones the records of a store
+fn owned<T: Record>(store: &dyn RecordStore<T>) -> Vec<T> {
+    store.iter_records().map(Cow::into_owned).collect()
+}
+
+/// Splits a payload into pieces of at most `max_length` characters, avoiding splits next to a
+/// space since some readers trim the values of CONC lines.
+pub(crate) fn split_payload(text: &str, max_length: Option<usize>) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    let mut first = true;
+    std::iter::from_fn(move || {
+        if rest.is_empty() && !first {
+            return None;
+        }
+        first = false;
+
+        let max_length = match max_length {
+            Some(max_length) if max_length > 0 => max_length,
+            _ => return Some(std::mem::take(&mut rest)),
+        };
+        let Some((mut end, _)) = rest.char_indices().nth(max_length) else {
+            return Some(std::mem::take(&mut rest));
+        };
+
+        let full = end;
+        while end > 0 && (rest[..end].ends_with(' ') || rest[end..].starts_with(' ')) {
+            end = rest[..end].char_indices().next_back().map_or(0, |(i, _)| i);
+        }
+        if end == 0 {
+            end = full;
+        }
+
+        let (part, remainder) = rest.split_at(end);
+        rest = remainder;
+        Some(part)
+    })
+}
@@ -11,7 +11,8 @@ pub mod util {
 #[cfg(test)]
 mod tests {
     use super::util::read_relative;
-    use gedcom::GedcomDocument;
+    use gedcom::search::{NameIndex, SearchMode};
+    use gedcom::{GedcomDocument, GedcomError, ParseOptions};
     use gedcom::types::event::HasEvents;
 
     #[test]
@@ -21,7 +22,7 @@ mod tests {
         assert!(simple_ged.len() > 0);
 
         let mut doc = GedcomDocument::new(simple_ged.chars());
-        let data = doc.parse_document();
+        let data = doc.parse_document().unwrap();
         assert_eq!(data.individuals.len(), 3);
         assert_eq!(data.families.len(), 1);
         assert_eq!(data.submitters.len(), 1);
@@ -51,6 +52,7 @@ mod tests {
         // addresses
         assert_eq!(
             data.submitters[0]
+                .contact
                 .address
                 .as_ref()
                 .unwrap()
@@ -73,7 +75,7 @@ mod tests {
         assert!(simple_ged.len() > 0);
 
         let mut doc = GedcomDocument::new(simple_ged.chars());
-        let data = doc.parse_document();
+        let data = doc.parse_document().unwrap();
         assert_eq!(data.individuals.len(), 538);
         assert_eq!(data.families.len(), 278);
         // assert_eq!(data.submitters.len(), 0);
@@ -106,4 +108,4143 @@ mod tests {
         assert_eq!(events[0].event.to_string(), "Marriage");
         assert_eq!(events[0].date.as_ref().unwrap().value.as_ref().unwrap(), "6 MAR 1730");
     }
+
+    #[test]
+    fn parses_utf16_gedcom() {
+        let bytes = std::fs::read("./tests/fixtures/simple-utf16le.ged").unwrap();
+        let data = gedcom::parse_bytes(&bytes).unwrap();
+        assert_eq!(data.individuals.len(), 3);
+        assert_eq!(data.families.len(), 1);
+
+        let header = data.header.unwrap();
+        assert_eq!(header.encoding.unwrap().value.unwrap(), "UNICODE");
+        assert_eq!(
+            data.individuals[0].name.as_ref().unwrap().value.as_ref().unwrap(),
+            "/Father/"
+        );
+    }
+
+    #[test]
+    fn parses_family_link_details() {
+        use gedcom::types::{
+            AdoptedByWhichParent, ChildLinkStatus, FamilyLinkType, ParentRelationship, Pedigree,
+        };
+
+        let contents = read_relative("./tests/fixtures/family-links.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let links = &data.individuals[0].families;
+        assert_eq!(links.len(), 3);
+
+        let adoptive = &links[0];
+        assert!(matches!(
+            adoptive.pedigree_linkage_type,
+            Some(Pedigree::Adopted)
+        ));
+        assert!(matches!(
+            adoptive.child_linkage_status,
+            Some(ChildLinkStatus::Challenged)
+        ));
+        assert!(matches!(
+            adoptive.adopted_by,
+            Some(AdoptedByWhichParent::Wife)
+        ));
+        assert_eq!(
+            adoptive.notes[0].value.as_deref(),
+            Some("Adopted by his stepmother after his father's remarriage.\nCourt record not yet found.")
+        );
+        assert_eq!(
+            adoptive.father_relationship,
+            Some(ParentRelationship::Natural)
+        );
+        assert_eq!(
+            adoptive.mother_relationship,
+            Some(ParentRelationship::Adopted)
+        );
+        assert!(adoptive.custom_data.is_empty());
+
+        // unrecognized relationships are kept as custom data
+        let guardians = &links[1];
+        assert_eq!(
+            guardians.father_relationship,
+            Some(ParentRelationship::Unknown)
+        );
+        assert_eq!(guardians.mother_relationship, None);
+        assert_eq!(guardians.custom_data.len(), 1);
+        assert_eq!(guardians.custom_data[0].tag, "_MREL");
+        assert_eq!(
+            guardians.custom_data[0].value.as_deref(),
+            Some("Legal Guardian")
+        );
+
+        assert!(matches!(links[2].family_link_type, FamilyLinkType::Spouse));
+
+        let written = data.to_gedcom_string();
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), written);
+        let link = &reparsed.individuals[0].families[0];
+        assert_eq!(link.mother_relationship, Some(ParentRelationship::Adopted));
+    }
+
+    #[test]
+    fn parses_source_records() {
+        let sources_ged: String = read_relative("./tests/fixtures/sources.ged");
+        let data = GedcomDocument::new(sources_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let source = &data.sources[0];
+        assert_eq!(
+            source.author.as_deref(),
+            Some("Church of England. Parish of Lavenham\nSuffolk Record Office")
+        );
+        assert_eq!(
+            source.publication_facts.as_deref(),
+            Some("Ipswich: Suffolk Record Office, 1972. Microfilm of the original registers.")
+        );
+        assert_eq!(
+            source.citation_from_source.as_deref(),
+            Some("12 March 1850, John son of Thomas Smith\nand Sarah his wife, weaver, baptised.")
+        );
+        assert_eq!(
+            source.notes[0].value.as_deref(),
+            Some("Baptisms from 1558 to 1850 only.")
+        );
+
+        // a pointer to a multimedia record, then a link of its own
+        assert_eq!(source.multimedia.len(), 2);
+        assert_eq!(source.multimedia[0].xref.as_deref(), Some("@M1@"));
+        let file = source.multimedia[1].file.as_ref().unwrap();
+        assert_eq!(file.value.as_deref(), Some("registers/lavenham-1850.jpg"));
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 OBJE @M1@\n"));
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), written);
+    }
+
+    #[test]
+    fn parses_derived_sources() {
+        let sources_ged: String = read_relative("./tests/fixtures/sources.ged");
+        let data = GedcomDocument::new(sources_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let original = &data.sources[0];
+        assert!(original.derived_from().is_empty());
+
+        let images = &data.sources[1];
+        assert_eq!(images.citations.len(), 2);
+        assert_eq!(images.citations[0].page.as_deref(), Some("Reel 2, item 4"));
+        // the second citation describes its source in place, without a record to point to
+        assert_eq!(images.derived_from(), vec!["@S1@"]);
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 SOUR @S1@\n2 PAGE Reel 2, item 4\n"));
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.sources[1].derived_from(), vec!["@S1@"]);
+    }
+
+    #[test]
+    fn parses_submitter_records() {
+        let submitter_ged: String = read_relative("./tests/fixtures/submitter.ged");
+        let data = GedcomDocument::new(submitter_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let subm = &data.submitters[0];
+        assert_eq!(subm.name.as_deref(), Some("Anna Kowalska"));
+        assert_eq!(subm.languages, vec!["Polish", "English"]);
+        assert_eq!(subm.multimedia.len(), 2);
+        assert_eq!(subm.multimedia[0].xref.as_deref(), Some("@M1@"));
+        assert_eq!(
+            subm.multimedia[1].file.as_ref().unwrap().value.as_deref(),
+            Some("portraits/anna.jpg")
+        );
+        assert_eq!(subm.registered_refn.as_deref(), Some("2291-AK"));
+        assert_eq!(subm.automated_record_id.as_deref(), Some("17"));
+        assert_eq!(subm.notes.len(), 2);
+        let chan = subm.change_date.as_ref().unwrap().date.as_ref().unwrap();
+        assert_eq!(chan.value.as_deref(), Some("14 JUN 2022"));
+        assert_eq!(chan.time.as_deref(), Some("10:42:00"));
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 LANG Polish\n1 LANG English\n"));
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), written);
+    }
+
+    #[test]
+    fn parses_repository_contact_details() {
+        let sources_ged: String = read_relative("./tests/fixtures/sources.ged");
+        let data = GedcomDocument::new(sources_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let repo = &data.repositories[0];
+        assert_eq!(repo.name.as_deref(), Some("Suffolk Archives"));
+        assert_eq!(
+            repo.contact.address.as_ref().unwrap().city.as_deref(),
+            Some("Ipswich")
+        );
+        assert_eq!(
+            repo.contact.phones,
+            vec!["+44 1473 263910", "+44 1473 263912"]
+        );
+        assert_eq!(repo.contact.emails, vec!["ipswich.archives@suffolk.gov.uk"]);
+        assert_eq!(repo.contact.faxes, vec!["+44 1473 263911"]);
+        assert_eq!(
+            repo.contact.websites,
+            vec!["https://www.suffolkarchives.co.uk"]
+        );
+        assert_eq!(
+            repo.notes[0].value.as_deref(),
+            Some("Open Tuesday to Saturday.\nBooking required for microfilm readers.")
+        );
+        let refn = &repo.user_reference_numbers[0];
+        assert_eq!(refn.value.as_deref(), Some("SA-01"));
+        assert_eq!(refn.user_reference_type.as_deref(), Some("archive code"));
+        assert_eq!(repo.automated_record_id.as_deref(), Some("7731"));
+        assert!(repo.change_date.is_some());
+        assert_eq!(repo.external_ids[0].value, "8A1C0E54D3AB4A39");
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 WWW https://www.suffolkarchives.co.uk\n"));
+        assert!(written.contains("1 PHON +44 1473 263910\n1 PHON +44 1473 263912\n"));
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), written);
+    }
+
+    #[test]
+    fn parses_registered_event_types() {
+        use gedcom::types::{Event, EventCategory};
+
+        let events_ged: String = read_relative("./tests/fixtures/custom-events.ged");
+        let mut doc = GedcomDocument::new(events_ged.chars());
+        doc.register_event_type("_MILT", "Military Service", EventCategory::Military)
+            .register_event_type("_SEPR", "Separation", EventCategory::Vital);
+        let data = doc.parse_document().unwrap();
+
+        let john = &data.individuals[0];
+        assert_eq!(john.events.len(), 2);
+        let service = &john.events[1];
+        assert_eq!(service.event.to_string(), "Military Service");
+        assert_eq!(service.value.as_deref(), Some("Private, 107th Infantry"));
+        assert_eq!(
+            service.date.as_ref().unwrap().value.as_deref(),
+            Some("FROM 1917 TO 1919")
+        );
+        assert_eq!(
+            service.citations[0].page.as_deref(),
+            Some("Service card 4417")
+        );
+        assert!(john.custom_data.is_empty());
+
+        let family = &data.families[0];
+        let separation = &family.events[1];
+        assert!(matches!(
+            &separation.event,
+            Event::Registered(event_type) if event_type.category == EventCategory::Vital
+        ));
+        assert!(separation.event.is_vital());
+        assert!(!service.event.is_vital());
+        assert!(!separation.event.is_family_event());
+        assert_eq!(data.stats().events["_SEPR"], 1);
+
+        // only individuals and families hold events
+        assert_eq!(data.sources[0].custom_data[0].tag, "_MILT");
+
+        // the events are written back under their own tag
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 _MILT Private, 107th Infantry\n2 DATE FROM 1917 TO 1919\n"));
+        let unregistered = GedcomDocument::new(events_ged.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(unregistered.individuals[0].events.len(), 1);
+        assert_eq!(unregistered.individuals[0].custom_data[0].tag, "_MILT");
+    }
+
+    #[test]
+    fn parses_event_witnesses() {
+        let witnesses_ged: String = read_relative("./tests/fixtures/witnesses.ged");
+        let data = GedcomDocument::new(witnesses_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let baptism = &data.individuals[0].events[0];
+        let roles: Vec<_> = baptism
+            .associations
+            .iter()
+            .map(|asso| (asso.tag.as_str(), asso.xref(), asso.role.as_deref()))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                ("ASSO", Some("@I2@"), Some("Godfather")),
+                ("WITN", None, None),
+                ("_SHAR", Some("@I3@"), Some("Witness")),
+                ("_WITN", Some("@I4@"), None),
+            ]
+        );
+
+        let godfather = &baptism.associations[0];
+        assert_eq!(
+            godfather.notes[0].value.as_deref(),
+            Some("Stood in for his brother")
+        );
+        assert_eq!(godfather.citations[0].page.as_deref(), Some("Folio 12"));
+        assert_eq!(baptism.associations[1].name.as_deref(), Some("Thomas Brown"));
+        let shared = &baptism.associations[2];
+        assert_eq!(
+            shared.notes[0].value.as_deref(),
+            Some("Signed the register\nwith a cross")
+        );
+        let witnesses = baptism.associations.iter().filter(|a| a.is_witness());
+        assert_eq!(witnesses.count(), 3);
+
+        let marriage = &data.families[0].events[0];
+        assert_eq!(marriage.associations.len(), 2);
+        assert_eq!(marriage.associations[1].role.as_deref(), Some("Bridesmaid"));
+
+        let written = data.to_gedcom_string();
+        let reparsed = GedcomDocument::new(written.chars()).parse_document().unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), written);
+        assert_eq!(reparsed.individuals[0].events[0].associations.len(), 4);
+    }
+
+    #[test]
+    fn reads_spouse_ages_of_family_events() {
+        use gedcom::types::event::Spouse;
+
+        let contents = read_relative("./tests/fixtures/spouse-ages.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let marriage = &data.families[0].events[0];
+        let husband = marriage.spouse_age(Spouse::Spouse1).unwrap();
+        assert_eq!(husband.years, Some(25));
+        let wife = marriage.spouse_age(Spouse::Spouse2).unwrap();
+        assert_eq!((wife.years, wife.months), (Some(22), Some(4)));
+        // the xref some applications write on the HUSB line is kept
+        assert_eq!(
+            marriage.family_event_details[0].value.as_deref(),
+            Some("@I1@")
+        );
+
+        // ages which can't be read are kept as they were
+        let divorce = &data.families[0].events[1];
+        assert_eq!(divorce.spouse_age(Spouse::Spouse1), None);
+        assert_eq!(divorce.spouse_age(Spouse::Spouse2), None);
+        assert_eq!(
+            divorce.family_event_details[0].age.as_deref(),
+            Some("about forty")
+        );
+
+        assert_eq!(data.to_gedcom_string(), contents);
+    }
+
+    #[test]
+    fn parses_family_events_like_individual_events() {
+        let event_ged: String = read_relative("./tests/fixtures/event-details.ged");
+        let data = GedcomDocument::new(event_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let birth = &data.individuals[0].events[0];
+        let marriage = &data.families[0].events[0];
+        for event in [birth, marriage] {
+            assert!(event.event_type.is_some());
+            assert!(event.date.is_some());
+            assert_eq!(event.place.as_deref(), Some("Bristol, England"));
+            let address = event.contact.address.as_ref().unwrap();
+            assert_eq!(address.city.as_deref(), Some("Bristol"));
+            assert_eq!(address.country.as_deref(), Some("England"));
+            assert_eq!(event.contact.phones, vec!["+44 117 925 0001"]);
+            assert_eq!(event.contact.websites, vec!["https://stmarys.example.org"]);
+            assert_eq!(event.agency.as_deref(), Some("Parish of St Mary"));
+            assert_eq!(event.religion.as_deref(), Some("Anglican"));
+            assert_eq!(event.cause.as_deref(), Some("Natural"));
+            assert_eq!(event.restriction.as_deref(), Some("confidential"));
+            assert!(!event.notes.is_empty());
+            assert_eq!(event.citations.len(), 1);
+            assert_eq!(event.multimedia.len(), 1);
+        }
+        assert_eq!(birth.age.as_deref(), Some("0y"));
+        assert_eq!(marriage.family_event_details.len(), 2);
+        assert_eq!(marriage.family_event_details[1].age.as_deref(), Some("22y"));
+
+        let written = data.to_gedcom_string();
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), written);
+        assert!(written.contains("1 MARR\n2 TYPE Religious\n2 DATE 12 JUN 1875\n"));
+    }
+
+    #[test]
+    fn parses_washington_doc_borrowed() {
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+
+        let owned = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+        let borrowed = gedcom::GedcomRef::parse(&washington_ged);
+
+        assert_eq!(borrowed.header.unwrap().version, Some("5.5.1"));
+        assert_eq!(borrowed.individuals.len(), owned.individuals.len());
+        assert_eq!(borrowed.families.len(), owned.families.len());
+        assert_eq!(borrowed.sources.len(), owned.sources.len());
+        for (a, b) in borrowed.individuals.iter().zip(owned.individuals.iter()) {
+            assert_eq!(a.xref, b.xref.as_deref());
+            assert_eq!(a.events.len(), b.events.len());
+        }
+        assert_eq!(
+            borrowed.individuals[0].name.as_ref().unwrap().value,
+            Some("George /Washington/")
+        );
+        assert_eq!(borrowed.families[0].events[0].date, Some("6 MAR 1730"));
+    }
+
+    #[test]
+    fn streams_records_of_washington_doc() {
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+
+        let owned = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+        let mut doc = GedcomDocument::new(washington_ged.chars());
+        let xrefs: Vec<String> = doc
+            .records()
+            .filter_map(|record| match record.unwrap() {
+                gedcom::Record::Individual(indi) => indi.xref,
+                _ => None,
+            })
+            .collect();
+        let owned_xrefs: Vec<String> = owned
+            .individuals
+            .iter()
+            .filter_map(|indi| indi.xref.clone())
+            .collect();
+        assert_eq!(xrefs, owned_xrefs);
+
+        // the records rebuild the same tree
+        let mut streamed = gedcom::GedcomData::default();
+        for record in GedcomDocument::new(washington_ged.chars()).records() {
+            streamed.add_record(record.unwrap());
+        }
+        assert_eq!(streamed.to_gedcom_string(), owned.to_gedcom_string());
+
+        // iteration stops after an error
+        let truncated = &washington_ged[..washington_ged.find("0 @F1@ FAM").unwrap()];
+        let mut doc = GedcomDocument::new(truncated.chars());
+        let results: Vec<bool> = doc.records().map(|record| record.is_ok()).collect();
+        assert_eq!(results.last(), Some(&false));
+        assert_eq!(results.iter().filter(|ok| !**ok).count(), 1);
+    }
+
+    #[test]
+    fn validates_links_while_streaming() {
+        use gedcom::validate::{validate_stream, Issue};
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        assert!(validate_stream(&washington_ged, ParseOptions::default())
+            .unwrap()
+            .is_empty());
+
+        let broken_ged: String = read_relative("./tests/fixtures/broken-links.ged");
+        let issues = validate_stream(&broken_ged, ParseOptions::default()).unwrap();
+        let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "@I2@ is defined more than once, again by a record tagged INDI",
+                "FAMC @I2@ of @I1@ points to a record tagged INDI, expected FAM",
+                "SOUR @S2@ of @I2@ points to no record",
+                "CHIL @I3@ of @F1@ points to no record",
+            ]
+        );
+        assert_eq!(
+            issues[3],
+            Issue::UnresolvedPointer {
+                record: "@F1@".to_string(),
+                tag: "CHIL".to_string(),
+                pointer: "@I3@".to_string(),
+            }
+        );
+
+        // the in-memory tree has the same issues
+        let data = GedcomDocument::new(broken_ged.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(data.validate(), issues);
+    }
+
+    #[test]
+    fn upgrades_to_gedcom_7() {
+        use gedcom::lint::{lint_with, LintOptions};
+        use gedcom::writer::{GedcomWriter, LineEnding, TargetVersion, WriterOptions};
+
+        let contents = read_relative("./tests/fixtures/upgrade.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let upgraded = data.upgrade_to_v7();
+        // the shared notes skip @N1@, taken by an individual
+        assert_eq!(
+            upgraded,
+            "\
+0 HEAD
+1 GEDC
+2 VERS 7.0
+1 SOUR PAF
+2 VERS 5.2
+1 SUBM @U1@
+0 @U1@ SUBM
+1 NAME Jane Archivist
+1 EXID 1234
+2 TYPE https://gedcom.io/terms/v7/RFN
+0 @N1@ INDI
+1 NAME John /Smith/
+1 SNOTE @N2@
+1 UID 3F2504E04F8911D39A0C0305E82C3301
+0 @I2@ INDI
+1 NAME Thomas /Smith/
+1 BIRT
+2 DATE 1850
+2 SNOTE @N3@
+1 SNOTE @N2@
+0 @I3@ INDI
+1 NAME Mary /Smith/
+1 SNOTE @N3@
+0 @N2@ SNOTE Sailed from Liverpool on the Etruria
+1 CONT with his brother
+0 @N3@ SNOTE Born at sea
+0 TRLR
+"
+        );
+        let options = LintOptions {
+            max_line_length: usize::MAX,
+            ..LintOptions::default()
+        };
+        assert_eq!(lint_with(&upgraded, &options), vec![]);
+
+        // writer options still apply, except for CONC splits
+        let options = WriterOptions {
+            line_ending: LineEnding::CrLf,
+            max_payload_length: Some(10),
+            target_version: Some(TargetVersion::V7),
+            ..WriterOptions::default()
+        };
+        let mut writer = GedcomWriter::with_options(Vec::new(), options);
+        writer.write(&data).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(output, upgraded.replace('\n', "\r\n"));
+    }
+
+    #[test]
+    fn downgrades_to_gedcom_551() {
+        let contents = read_relative("./tests/fixtures/downgrade.ged");
+        let options = ParseOptions {
+            lenient: true,
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let data = GedcomDocument::with_options(contents.chars(), options)
+            .parse_document()
+            .unwrap();
+        let downgrade = data.downgrade_to_v551();
+        let text = &downgrade.text;
+        assert!(text.starts_with(
+            "0 HEAD\n1 GEDC\n2 VERS 5.5.1\n2 FORM LINEAGE-LINKED\n1 CHAR UTF-8\n0 @I1@"
+        ));
+        assert!(text.contains("2 NOTE Born at sea\n3 CONT on the Etruria\n"));
+        assert!(text.contains(
+            "1 NOTE Born at sea\n2 CONT on the Etruria\n0 @F1@ FAM\n1 HUSB @I1@\n0 TRLR"
+        ));
+        assert!(text.contains("1 ASSO @I2@\n2 RELA GODP\n"));
+        assert!(!text.contains("SNOTE"));
+        assert!(text.lines().all(|line| line.chars().count() <= 255));
+        assert!(text.contains("Etruri\n2 CONC a after"));
+
+        let lossy: Vec<String> = downgrade.lossy.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lossy,
+            vec![
+                "SCHMA of HEAD was dropped",
+                "LANG of @N1@ was dropped along with the SNOTE record",
+                "UID of @I1@ was written as _UID",
+                "WIFE of @F1@ was dropped, GEDCOM 5.5.1 has no @VOID@ pointers",
+            ]
+        );
+
+        // the output reads back without loss of text. ASSO isn't read by the parser.
+        let reparsed = GedcomDocument::with_options(text.chars(), ParseOptions::lenient())
+            .parse_document()
+            .unwrap();
+        let note = |data: &gedcom::GedcomData| data.individuals[0].notes[0].value.clone();
+        assert_eq!(note(&reparsed), note(&data));
+        assert_eq!(reparsed.header.unwrap().gedcom_version(), Some("5.5.1"));
+    }
+
+    #[test]
+    fn checks_media_links() {
+        let contents = read_relative("./tests/fixtures/media.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let file = data.individuals[0].multimedia[0].file.as_ref().unwrap();
+        assert_eq!(
+            file.local_path().as_deref(),
+            Some("gedzip/media/photos/John Smith.jpg")
+        );
+        assert!(file.exists("./tests/fixtures"));
+        assert!(!file.exists("./tests"));
+
+        let broken: Vec<(String, String)> = data
+            .check_media("./tests/fixtures")
+            .into_iter()
+            .map(|media| (media.record, media.file))
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                ("@I1@".to_string(), "photos/missing.jpg".to_string()),
+                (
+                    "@M3@".to_string(),
+                    "C:\\Users\\John\\Pictures\\wedding.jpg".to_string()
+                ),
+            ]
+        );
+        let wedding = data.multimedia[2].file.as_ref().unwrap();
+        assert_eq!(
+            wedding.resolve("./tests/fixtures"),
+            Some("C:/Users/John/Pictures/wedding.jpg".into())
+        );
+    }
+
+    #[test]
+    fn reads_custom_tags_through_aliases() {
+        use gedcom::tokenizer::TagAlias;
+
+        let contents = read_relative("./tests/fixtures/tag-aliases.ged");
+        let aliases = [
+            "_MARNM -> NAME(TYPE=married)",
+            "_GIVN -> GIVN",
+            "_DATE2 -> DATE(TYPE=ignored)",
+            "_MILT -> EVEN(TYPE=Military service)",
+            "_OCC -> OCCU(TYPE=Occupation)",
+        ];
+        let mut doc = GedcomDocument::new(contents.chars());
+        for alias in aliases {
+            doc.register_tag_alias(alias.parse().unwrap());
+        }
+        let data = doc.parse_document().unwrap();
+
+        let mary = &data.individuals[0];
+        // the married name is kept alongside the birth name rather than replacing it
+        let names: Vec<_> = mary.names().map(|name| name.value.as_deref()).collect();
+        assert_eq!(names, [Some("Mary /Smith/"), Some("Mary /Jones/")]);
+        let married = &mary.alternate_names[0];
+        assert_eq!(married.given.as_deref(), Some("Mary"));
+        assert_eq!(married.name_type.as_deref(), Some("married"));
+        let birth = &mary.events[0];
+        assert_eq!(
+            birth.date.as_ref().unwrap().value.as_deref(),
+            Some("ABT 1850")
+        );
+        assert_eq!(birth.event_type, None);
+        let service = &mary.events[1];
+        assert_eq!(service.event_type.as_deref(), Some("Military service"));
+        assert_eq!(service.place.as_deref(), Some("Aldershot"));
+        // the TYPE written in the file wins over that of the alias
+        assert_eq!(mary.attributes[0].attribute_type.as_deref(), Some("Trade"));
+        assert_eq!(mary.custom_data.len(), 1);
+        assert_eq!(mary.custom_data[0].tag, "_UNMAPPED");
+
+        // the TYPE of an alias doesn't carry over to the following structures
+        let john = data.individuals[1].name.as_ref().unwrap();
+        assert_eq!(john.name_type, None);
+
+        let output = data.to_gedcom_string();
+        assert!(output
+            .contains("1 NAME Mary /Smith/\n1 NAME Mary /Jones/\n2 TYPE married\n2 GIVN Mary\n"));
+        assert!(output.contains("1 EVEN\n2 TYPE Military service\n"));
+
+        let alias: TagAlias = "_MARNM->name ( TYPE = married )".parse().unwrap();
+        assert_eq!(alias.to_string(), "_MARNM -> NAME(TYPE=married)");
+        assert_eq!(alias.tag(), "NAME");
+        assert_eq!(
+            "MARNM -> NAME".parse::<TagAlias>().unwrap_err(),
+            "'MARNM' is not a user-defined tag starting with an underscore"
+        );
+        assert_eq!(
+            "_MARNM -> NAMES".parse::<TagAlias>().unwrap_err(),
+            "'NAMES' is not a standard tag"
+        );
+        assert!("_MARNM -> NAME(SORT=1)".parse::<TagAlias>().is_err());
+        assert!("_MARNM NAME".parse::<TagAlias>().is_err());
+    }
+
+    #[test]
+    fn parses_notes_on_every_structure() {
+        let contents = read_relative("./tests/fixtures/notes.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let values = |notes: &[gedcom::types::Note]| -> Vec<String> {
+            notes.iter().filter_map(|note| note.value.clone()).collect()
+        };
+
+        let john = &data.individuals[0];
+        assert_eq!(
+            values(&john.notes),
+            vec!["First note on John", "Second note on John\nwhich goes on"]
+        );
+        assert_eq!(
+            values(&john.name.as_ref().unwrap().notes),
+            vec!["Spelled Smyth in the parish register"]
+        );
+        let birth = &john.events[0];
+        assert_eq!(values(&birth.notes), vec!["Born at home", "@N1@"]);
+        assert_eq!(birth.notes[0].xref(), None);
+        assert_eq!(birth.notes[1].xref(), Some("@N1@"));
+        assert_eq!(
+            values(&birth.citations[0].notes),
+            vec!["The entry is faded"]
+        );
+        assert_eq!(values(&john.attributes[0].notes), vec!["Hand-loom weaver"]);
+        let residence = &john.events[1];
+        assert_eq!(
+            values(&residence.contact.address.as_ref().unwrap().notes),
+            vec!["The house no longer stands"]
+        );
+        assert_eq!(values(&john.families[0].notes), vec!["Second marriage"]);
+
+        let family = &data.families[0];
+        assert_eq!(values(&family.notes), vec!["@N1@"]);
+        assert_eq!(values(&family.events[0].notes), vec!["Married by banns"]);
+        assert_eq!(
+            values(&data.sources[0].repo_citations[0].notes),
+            vec!["Kept in the strong room"]
+        );
+        assert_eq!(data.notes[0].xref.as_deref(), Some("@N1@"));
+        assert_eq!(data.notes[0].value.as_deref(), Some("A shared note"));
+
+        // every note is written back where it was read, shared notes included
+        let output = data.to_gedcom_string();
+        let reparsed = GedcomDocument::new(output.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), output);
+        for line in contents.lines().filter(|line| line.contains("NOTE")) {
+            assert!(output.contains(line), "{} is missing", line);
+        }
+        assert!(data.validate().is_empty());
+    }
+
+    #[test]
+    fn converts_shared_notes_between_versions() {
+        let contents = read_relative("./tests/fixtures/notes.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        // the pointers to the NOTE record point to an SNOTE record in GEDCOM 7
+        let upgraded = data.upgrade_to_v7();
+        assert!(upgraded.contains("2 SNOTE @N1@\n"));
+        assert!(upgraded.contains("1 SNOTE @N1@\n"));
+        assert!(upgraded.contains("0 @N1@ SNOTE A shared note\n0 TRLR"));
+        assert!(!upgraded.contains(" NOTE @N1@"));
+
+        // and back, the text of the note embedded where it was pointed to
+        let options = ParseOptions {
+            lenient: true,
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let reparsed = GedcomDocument::with_options(upgraded.chars(), options)
+            .parse_document()
+            .unwrap();
+        let downgrade = reparsed.downgrade_to_v551();
+        assert!(downgrade
+            .text
+            .contains("2 NOTE Born at home\n2 NOTE A shared note\n"));
+        assert!(downgrade.text.contains("1 NOTE A shared note\n0 @S1@ SOUR"));
+        assert!(!downgrade.text.contains("@N1@"));
+    }
+
+    #[test]
+    fn reports_parse_progress() {
+        use std::sync::Mutex;
+
+        static CALLS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let options = ParseOptions {
+            progress: Some(|bytes_read, records_parsed| {
+                CALLS.lock().unwrap().push((bytes_read, records_parsed));
+            }),
+            ..ParseOptions::default()
+        };
+        let mut doc = GedcomDocument::with_options(washington_ged.chars(), options);
+        doc.parse_document().unwrap();
+
+        let calls = CALLS.lock().unwrap();
+        assert_eq!(calls.len(), 4);
+        assert!(calls.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(calls[..3]
+            .iter()
+            .all(|(bytes_read, _)| *bytes_read < doc.input_len()));
+        // every level 0 line but the TRLR, including the records the parser skips
+        let records = washington_ged
+            .lines()
+            .filter(|line| line.trim_start_matches('\u{feff}').starts_with("0 "))
+            .count()
+            - 1;
+        assert_eq!(calls[3], (doc.input_len(), records));
+    }
+
+    #[test]
+    fn cancels_parsing() {
+        use gedcom::{GedcomError, Record};
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut doc = GedcomDocument::with_options(washington_ged.chars(), ParseOptions::lenient());
+        doc.set_cancellation(Arc::clone(&flag));
+        assert!(doc.parse_document().is_ok());
+        assert!(doc.take_partial_data().is_none());
+
+        flag.store(true, Ordering::Relaxed);
+        let mut doc = GedcomDocument::with_options(washington_ged.chars(), ParseOptions::lenient());
+        doc.set_cancellation(Arc::clone(&flag));
+        let err = doc.parse_document().unwrap_err();
+        assert_eq!(err, GedcomError::Cancelled { span: err.span() });
+        assert_eq!(err.line(), 1);
+        let partial = doc.take_partial_data().unwrap();
+        assert!(partial.header.is_none());
+        assert!(doc.take_partial_data().is_none());
+
+        // a streamed parse ends with the error
+        let mut doc = GedcomDocument::new(washington_ged.chars());
+        let token = gedcom::CancellationToken::new();
+        doc.set_cancellation(token.clone());
+        let mut records = doc.records();
+        assert!(matches!(records.next(), Some(Ok(Record::Header(_)))));
+        token.cancel();
+        assert!(matches!(
+            records.next(),
+            Some(Err(GedcomError::Cancelled { .. }))
+        ));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn selects_structures_by_tag_path() {
+        use gedcom::path::TagPath;
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let george = &data.individuals[0];
+        assert_eq!(george.get_path("BIRT/DATE").as_deref(), Some("22 FEB 1732"));
+        assert_eq!(george.get_path("_FSFTID").as_deref(), Some("KNDX-MKG"));
+        assert_eq!(george.select("FAMS").len(), 4);
+        assert_eq!(george.get_path("FAMS[2]").as_deref(), Some("@F44@"));
+        assert_eq!(george.get_path("FAMS[9]"), None);
+
+        let family = data.families[0].to_node();
+        assert_eq!(family.xref.as_deref(), Some("@F1@"));
+        assert_eq!(family.values("CHIL").len(), 6);
+        assert_eq!(family.get_path("MARR/*"), Some("6 MAR 1730"));
+
+        assert_eq!(
+            data.select_values("FAM[@F1@]/MARR/PLAC"),
+            vec!["Lancaster, Virginia, British Colonial America"]
+        );
+        assert_eq!(
+            data.select_values("INDI[@I1@]/NAME/SURN"),
+            vec!["Washington"]
+        );
+        assert_eq!(data.select("INDI/FAMC[@F1@]").len(), 6);
+        assert_eq!(
+            data.select("HEAD/SOUR/CORP/ADDR")[0].value.as_deref(),
+            Some("PO Box 495\nSpringville, UT 84663\nUSA")
+        );
+        assert!(data.select("INDI//NAME").is_empty());
+    }
+
+    #[test]
+    fn reads_any_line_terminators() {
+        use gedcom::lint::{lint, Rule};
+
+        let contents = read_relative("./tests/fixtures/sample.ged").replace("\r\n", "\n");
+        let expected = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap()
+            .to_gedcom_string();
+        let mixed: String = contents
+            .lines()
+            .enumerate()
+            .map(|(i, line)| format!("{line}{}", ["\r\n", "\r", "\n"][i % 3]))
+            .collect();
+        for terminated in [
+            contents.replace('\n', "\r\n"),
+            contents.replace('\n', "\r"),
+            mixed.clone(),
+        ] {
+            let data = GedcomDocument::new(terminated.chars())
+                .parse_document()
+                .unwrap();
+            assert_eq!(data.to_gedcom_string(), expected);
+        }
+
+        // the line counter stays right whatever the terminators
+        let broken = mixed.replacen("1 SEX M", "SEX M", 1);
+        let line = broken
+            .split(['\r', '\n'])
+            .filter(|line| !line.is_empty())
+            .position(|line| line == "SEX M")
+            .unwrap()
+            + 1;
+        let mut doc = GedcomDocument::with_options(broken.chars(), ParseOptions::lenient());
+        doc.parse_document().unwrap();
+        let warning = doc
+            .warnings()
+            .iter()
+            .find(|warning| warning.to_string().contains("Expected a level number"))
+            .unwrap();
+        assert_eq!(warning.span().line as usize, line);
+
+        let lints = lint(&mixed);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].rule, Rule::MixedLineEndings);
+        assert_eq!(
+            lints[0].to_string(),
+            "line 2: line ends with CR, while line 1 ends with CRLF [mixed-line-endings]"
+        );
+        assert!(lint(&contents.replace('\n', "\r")).is_empty());
+    }
+
+    #[test]
+    fn keeps_custom_data_of_every_structure() {
+        let contents = read_relative("./tests/fixtures/custom-everywhere.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let header = data.header.as_ref().unwrap();
+        assert_eq!(
+            header.gedcom.as_ref().unwrap().custom_data[0].tag,
+            "_PROFILE"
+        );
+        let john = &data.individuals[0];
+        assert_eq!(john.name.as_ref().unwrap().custom_data[0].tag, "_RUFNAME");
+        let birth = &john.events[0];
+        let sort = &birth.date.as_ref().unwrap().custom_data[0];
+        assert_eq!(sort.value.as_deref(), Some("18221002"));
+        assert_eq!(birth.notes[0].custom_data[0].tag, "_PRIVATE");
+        let photo = &birth.custom_data[0];
+        assert_eq!(
+            photo.child("_FILE").unwrap().value.as_deref(),
+            Some("birth.jpg")
+        );
+        let citation_data = birth.citations[0].data.as_ref().unwrap();
+        assert_eq!(citation_data.custom_data[0].tag, "_TRANSCRIBER");
+        assert_eq!(john.attributes[0].custom_data[0].tag, "_EMPLOYER");
+        let marriage = &data.families[0].events[0];
+        assert_eq!(
+            marriage.family_event_details[0].custom_data[0].tag,
+            "_SIGNED"
+        );
+        let source = &data.sources[0];
+        assert_eq!(source.data.events[0].custom_data[0].tag, "_PAGES");
+        assert_eq!(source.data.custom_data[0].tag, "_INDEXED");
+        assert_eq!(source.repo_citations[0].custom_data[0].tag, "_SHELF");
+
+        assert_eq!(data.to_gedcom_string(), contents);
+    }
+
+    #[test]
+    fn keeps_whole_custom_subtrees() {
+        let contents = read_relative("./tests/fixtures/custom-subtrees.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let john = &data.individuals[0];
+        assert_eq!(john.custom_data.len(), 2);
+        let army = &john.custom_data[0];
+        assert_eq!(army.value.as_deref(), Some("Army"));
+        let tags: Vec<&str> = army
+            .children
+            .iter()
+            .map(|child| child.tag.as_str())
+            .collect();
+        assert_eq!(tags, vec!["DATE", "PLAC", "NOTE", "_UNIT"]);
+        let map = army.child("PLAC").unwrap().child("_MAP").unwrap();
+        assert_eq!(map.child("_LONG").unwrap().value.as_deref(), Some("W77.61"));
+        let note = army.child("NOTE").unwrap();
+        assert_eq!(
+            note.child("CONC").unwrap().value.as_deref(),
+            Some(" National Guard")
+        );
+        let citation = army.child("_UNIT").unwrap().child("SOUR").unwrap();
+        assert_eq!(citation.value.as_deref(), Some("@S1@"));
+        assert_eq!(
+            citation.child("PAGE").unwrap().value.as_deref(),
+            Some("Card 12")
+        );
+        assert_eq!(john.custom_data[1].value.as_deref(), Some("Navy"));
+
+        let place = &data.custom_data[0];
+        assert_eq!(place.xref.as_deref(), Some("@P1@"));
+        assert_eq!(place.tag, "_PLAC");
+        let alias = place.child("_ALIAS").unwrap();
+        assert_eq!(alias.child("_LANG").unwrap().value.as_deref(), Some("en"));
+
+        assert_eq!(data.to_gedcom_string(), contents);
+    }
+
+    #[test]
+    fn reads_escaped_at_signs_and_calendars() {
+        use gedcom::types::{Calendar, DatePart, DateValue};
+
+        let contents = read_relative("./tests/fixtures/escapes.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let jean = &data.individuals[0];
+        assert_eq!(
+            jean.notes[0].value.as_deref(),
+            Some("Write to jean@example.com, or to\n@dupont on the forum")
+        );
+        assert_eq!(jean.custom_data[0].value.as_deref(), Some("@jdupont"));
+
+        let date = |indi: &gedcom::types::Individual, i: usize| {
+            indi.events[i]
+                .date
+                .as_ref()
+                .unwrap()
+                .value_structured()
+                .unwrap()
+        };
+        assert_eq!(
+            date(jean, 0),
+            DateValue::Exact(DatePart {
+                day: Some(1),
+                month: Some(1),
+                year: 2,
+                calendar: Calendar::French,
+            })
+        );
+        assert_eq!(
+            date(jean, 1).to_string(),
+            "BET @#DJULIAN@ 1 JAN 1700 AND @#DJULIAN@ 1701"
+        );
+        let DateValue::Exact(part) = date(&data.individuals[1], 0) else {
+            panic!("Expected an exact date");
+        };
+        assert_eq!(part.calendar, Calendar::Gregorian);
+        assert_eq!(part.to_string(), "2 FEB 1795");
+        // a month outside the calendar is written as its number rather than panicking
+        let month = |month| DatePart {
+            day: None,
+            month: Some(month),
+            year: 1795,
+            calendar: Calendar::Gregorian,
+        };
+        assert_eq!(month(0).to_string(), "0 1795");
+        assert_eq!(month(13).to_string(), "13 1795");
+        assert!(DateValue::parse("@#DHEBREW@ 1 JAN 5600").is_none());
+
+        // the writer escapes the at-signs again, leaving the calendar escapes as they are
+        let written = data.to_gedcom_string();
+        assert_eq!(written, contents);
+
+        // GEDCOM 7 only doubles an at-sign starting a value, and names calendars with keywords
+        let upgraded = data.upgrade_to_v7();
+        assert!(upgraded.contains("2 DATE FRENCH_R 1 VEND 2\n"));
+        assert!(upgraded.contains("2 DATE BET JULIAN 1 JAN 1700 AND JULIAN 1701\n"));
+        assert!(upgraded.contains("1 NOTE Write to jean@example.com, or to\n2 CONT @@dupont"));
+        let downgraded = GedcomDocument::with_options(upgraded.chars(), ParseOptions::lenient())
+            .parse_document()
+            .unwrap()
+            .downgrade_to_v551();
+        assert!(downgraded.text.contains("2 DATE @#DFRENCH R@ 1 VEND 2\n"));
+        assert!(downgraded
+            .text
+            .contains("1 NOTE Write to jean@@example.com, or to\n"));
+
+        // xrefs are read strictly, and an at-sign doesn't make a value a pointer
+        let malformed = contents.replace("0 @I2@ INDI", "0 @I2 INDI");
+        let err = GedcomDocument::new(malformed.chars())
+            .parse_document()
+            .unwrap_err();
+        assert_eq!(err.line(), 15);
+        assert!(err.to_string().contains("Malformed xref @I2"));
+
+        // leniently, the record is skipped with a warning, and so are its lines
+        let mut doc = GedcomDocument::with_options(malformed.chars(), ParseOptions::lenient());
+        let data = doc.parse_document().unwrap();
+        assert_eq!(doc.warnings().len(), 1);
+        assert_eq!(doc.warnings()[0].line(), 15);
+        assert!(doc.warnings()[0].to_string().contains("Malformed xref @I2"));
+        assert_eq!(data.individuals.len(), 1);
+        assert_eq!(data.individuals[0].xref.as_deref(), Some("@I1@"));
+        assert!(data.individuals[0].alternate_names.is_empty());
+    }
+
+    #[test]
+    fn lints_style_and_conformance_rules() {
+        use gedcom::lint::{lint, lint_with, LintOptions, Rule};
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        assert_eq!(lint(&washington_ged), vec![]);
+
+        let contents = read_relative("./tests/fixtures/lint.ged");
+        let lints: Vec<(Rule, u32)> = lint(&contents)
+            .iter()
+            .map(|lint| (lint.rule, lint.line))
+            .collect();
+        assert_eq!(
+            lints,
+            vec![
+                (Rule::LevelJump, 9),
+                (Rule::LineTooLong, 10),
+                (Rule::XrefCharacters, 11),
+                (Rule::EmptyConc, 14),
+                (Rule::DuplicateXref, 16),
+                (Rule::MissingTrlr, 17),
+            ]
+        );
+        assert_eq!(
+            lint(&contents)[4].to_string(),
+            "line 16: xref @I1@ is already defined on line 6 [duplicate-xref]"
+        );
+
+        // GEDCOM 7 has no line length limit, and rules can be turned off by id
+        let options = LintOptions {
+            allow: vec!["empty-conc".parse().unwrap(), Rule::MissingTrlr],
+            max_line_length: usize::MAX,
+        };
+        let rules: Vec<Rule> = lint_with(&contents, &options)
+            .iter()
+            .map(|lint| lint.rule)
+            .collect();
+        assert_eq!(
+            rules,
+            vec![Rule::LevelJump, Rule::XrefCharacters, Rule::DuplicateXref]
+        );
+        assert!("no-such-rule".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn calls_custom_tag_handlers() {
+        use std::collections::HashMap;
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+
+        let mut familysearch_ids = HashMap::new();
+        let mut event_definitions = Vec::new();
+        let mut doc = GedcomDocument::new(washington_ged.chars());
+        doc.on_custom_tag("_FSFTID", |tag| {
+            let xref = tag.record.unwrap().to_string();
+            familysearch_ids.insert(xref, tag.dataset.value.clone().unwrap());
+            Ok(())
+        })
+        .on_custom_tag("_EVDEF", |tag| {
+            assert_eq!((tag.record, tag.level), (None, 0));
+            event_definitions.push(tag.span.line);
+            Ok(())
+        });
+        let data = doc.parse_document().unwrap();
+        drop(doc);
+
+        assert_eq!(familysearch_ids.len(), 538);
+        assert_eq!(familysearch_ids["@I1@"], "KNDX-MKG");
+        assert_eq!(event_definitions.len(), 64);
+        // the ids are read into the tree as well
+        assert_eq!(
+            data.individuals[0].external_id(gedcom::types::ExternalIdKind::FamilySearch),
+            Some("KNDX-MKG")
+        );
+
+        // errors raised by handlers fail a strict parse, and are warnings in lenient mode
+        let reject = |tag: &gedcom::types::CustomTagContext| {
+            Err(GedcomError::ParseError {
+                span: tag.span,
+                message: format!("Unexpected {}", tag.dataset.tag),
+            })
+        };
+        let mut doc = GedcomDocument::new(washington_ged.chars());
+        doc.on_custom_tag("_UID", reject);
+        assert_eq!(doc.parse_document().unwrap_err().line(), 24);
+
+        let mut doc = GedcomDocument::with_options(washington_ged.chars(), ParseOptions::lenient());
+        doc.on_custom_tag("_UID", reject);
+        let data = doc.parse_document().unwrap();
+        assert_eq!(doc.warnings().len(), 538);
+        assert_eq!(data.individuals.len(), 538);
+    }
+
+    #[test]
+    fn deduplicates_citations() {
+        let contents = read_relative("./tests/fixtures/duplicate-citations.ged");
+
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(data.individuals[0].events[0].citations.len(), 4);
+
+        let options = ParseOptions {
+            deduplicate_citations: true,
+            ..ParseOptions::default()
+        };
+        let data = GedcomDocument::with_options(contents.chars(), options)
+            .parse_document()
+            .unwrap();
+        let indi = &data.individuals[0];
+
+        let birth: Vec<(&str, usize)> = indi.events[0]
+            .citations
+            .iter()
+            .map(|citation| (citation.page.as_deref().unwrap(), citation.duplicates))
+            .collect();
+        assert_eq!(birth, vec![("Entry 12", 2), ("Entry 13", 0)]);
+        assert_eq!(indi.name.as_ref().unwrap().source.len(), 1);
+        assert_eq!(indi.name.as_ref().unwrap().source[0].duplicates, 1);
+        assert_eq!(indi.source.len(), 1);
+        assert_eq!(indi.source[0].duplicates, 1);
+
+        // each kept citation is written once
+        assert_eq!(indi.source[0].page, None);
+        assert_eq!(data.to_gedcom_string().matches("SOUR @S1@").count(), 4);
+    }
+
+    #[test]
+    fn interns_places_and_surnames() {
+        let contents = read_relative("./tests/fixtures/washington.ged");
+        let places = |data: &gedcom::GedcomData| -> Vec<gedcom::intern::SharedStr> {
+            data.individuals
+                .iter()
+                .flat_map(|indi| &indi.events)
+                .filter_map(|event| event.place.clone())
+                .collect()
+        };
+
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        assert!(data.strings.is_empty());
+        let plain = places(&data);
+
+        let options = ParseOptions {
+            intern_strings: true,
+            ..ParseOptions::default()
+        };
+        let mut data = GedcomDocument::with_options(contents.chars(), options)
+            .parse_document()
+            .unwrap();
+        let interned = places(&data);
+        assert_eq!(interned, plain);
+
+        // every occurrence of a place is the same allocation
+        let mount_vernon: Vec<_> = interned
+            .iter()
+            .filter(|place| **place == interned[0])
+            .collect();
+        assert!(mount_vernon.len() > 1);
+        assert!(mount_vernon
+            .iter()
+            .all(|place| place.ptr_eq(mount_vernon[0])));
+        let distinct: std::collections::HashSet<&str> =
+            interned.iter().map(|place| place.as_str()).collect();
+        assert!(data.strings.len() >= distinct.len());
+        assert!(data.strings.len() < interned.len());
+
+        // the tree reads and writes the same either way
+        let text = data.to_gedcom_string();
+        assert_eq!(
+            text,
+            GedcomDocument::new(contents.chars())
+                .parse_document()
+                .unwrap()
+                .to_gedcom_string()
+        );
+
+        data.individuals.clear();
+        data.families.clear();
+        drop(interned);
+        data.strings.shrink();
+        assert!(data.strings.is_empty());
+    }
+
+    #[test]
+    fn parses_generic_events_and_facts() {
+        use gedcom::types::{Event, IndividualAttribute};
+
+        let contents = read_relative("./tests/fixtures/generic-facts.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let ingrid = &data.individuals[0];
+        let events: Vec<(String, Option<&str>)> = ingrid
+            .events
+            .iter()
+            .map(|event| (event.kind(), event.value.as_deref()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("DNA Match".to_string(), Some("27 cM across 2 segments")),
+                ("Confirmation of vows".to_string(), None),
+            ]
+        );
+        assert!(ingrid
+            .events
+            .iter()
+            .all(|event| event.event == Event::Event));
+        let facts: Vec<(String, Option<&str>)> = ingrid
+            .attributes
+            .iter()
+            .map(|fact| (fact.kind(), fact.value.as_deref()))
+            .collect();
+        assert_eq!(
+            facts,
+            vec![
+                ("Eye colour".to_string(), Some("Blue")),
+                ("Farm name".to_string(), Some("Dahl farm")),
+            ]
+        );
+
+        let family = &data.families[0];
+        assert_eq!(family.events[0].kind(), "Separation");
+        let tenure = &family.attributes[0];
+        assert!(matches!(tenure.attribute, IndividualAttribute::Fact));
+        assert_eq!(tenure.kind(), "Tenure");
+        assert_eq!(tenure.value.as_deref(), Some("Tenant farmers"));
+        assert_eq!(tenure.place.as_deref(), Some("Voss, Norway"));
+
+        assert_eq!(data.to_gedcom_string(), contents);
+    }
+
+    #[test]
+    fn captures_external_ids() {
+        use gedcom::types::{ExternalId, ExternalIdKind};
+
+        let contents = read_relative("./tests/fixtures/external-ids.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let george = &data.individuals[0];
+        assert_eq!(
+            george.external_ids,
+            vec![
+                ExternalId {
+                    kind: ExternalIdKind::FamilySearch,
+                    value: "KNDX-MKG".to_string(),
+                    id_type: None,
+                    custom_data: Vec::new(),
+                },
+                ExternalId {
+                    kind: ExternalIdKind::Ancestry,
+                    value: "1,7602::2771226".to_string(),
+                    id_type: None,
+                    custom_data: Vec::new(),
+                },
+                ExternalId {
+                    kind: ExternalIdKind::Uid,
+                    value: "2B7D51F7A6C14BFE9F6C2B0B3A6C4E7D1A2B".to_string(),
+                    id_type: None,
+                    custom_data: Vec::new(),
+                },
+            ]
+        );
+        assert!(george.custom_data.is_empty());
+
+        // an id with substructures of its own is left as custom data
+        let martha = &data.individuals[1];
+        assert_eq!(
+            martha.external_id(ExternalIdKind::FamilySearch),
+            Some("LCMD-3V9")
+        );
+        assert_eq!(martha.external_id(ExternalIdKind::Ancestry), None);
+        assert_eq!(martha.custom_data[0].tag, "_APID");
+
+        let family = &data.families[0];
+        assert_eq!(
+            family.external_id(ExternalIdKind::Guid),
+            Some("7C2E0F5A-9D3B-4E61-8F0B-2A9C5D1E3B47")
+        );
+
+        assert_eq!(data.to_gedcom_string(), contents);
+    }
+
+    #[test]
+    fn parses_gedcom7_identifiers() {
+        use gedcom::search::{RecordKind, UidIndex};
+        use gedcom::types::ExternalIdKind;
+
+        let contents = read_relative("./tests/fixtures/gedcom7-ids.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let ole = &data.individuals[0];
+        assert_eq!(ole.uids.len(), 2);
+        let kinds: Vec<ExternalIdKind> = ole.external_ids.iter().map(|id| id.kind).collect();
+        assert_eq!(kinds, [ExternalIdKind::Exid, ExternalIdKind::FamilySearch]);
+        assert_eq!(
+            ole.external_ids[0].id_type.as_deref(),
+            Some("https://www.familysearch.org/tree/person/")
+        );
+        assert_eq!(
+            data.families[0].external_id(ExternalIdKind::Exid),
+            Some("1122")
+        );
+        assert_eq!(data.families[0].external_ids[0].id_type, None);
+        assert_eq!(data.repositories[0].external_ids[0].value, "8001");
+        assert_eq!(data.submitters[0].uids.len(), 1);
+
+        let index = UidIndex::new(&data);
+        assert_eq!(index.len(), 6);
+        let location = index.get("6A1D4F7E-0C2B-4B8E-9F3A-5E7D2C1B0A98").unwrap();
+        assert_eq!((location.kind, location.index), (RecordKind::Individual, 0));
+        let location = index.get("71b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d").unwrap();
+        assert_eq!(location.kind, RecordKind::Multimedia);
+        assert!(index.get("@I1@").is_none());
+
+        assert_eq!(data.to_gedcom_string(), contents);
+    }
+
+    #[test]
+    fn records_structure_spans() {
+        let options = ParseOptions {
+            record_spans: true,
+            ..ParseOptions::default()
+        };
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::with_options(washington_ged.chars(), options.clone())
+            .parse_document()
+            .unwrap();
+        let spans = data.spans.as_ref().unwrap();
+
+        for indi in &data.individuals {
+            let xref = indi.xref.as_deref().unwrap();
+            let record = spans.span(xref, "").unwrap();
+            let text = &washington_ged[record.bytes.clone()];
+            assert!(text.starts_with(&format!("0 {xref} INDI")));
+        }
+        let date = spans.span("@I1@", "BIRT.DATE").unwrap();
+        assert_eq!(date.lines, 29..30);
+        assert!(washington_ged[date.bytes.clone()].starts_with("2 DATE 22 FEB 1732"));
+        let birth = spans.span("@I1@", "BIRT").unwrap();
+        let (record, structure) = spans.at_offset(date.bytes.start + 4).unwrap();
+        assert_eq!(record.xref.as_deref(), Some("@I1@"));
+        assert_eq!(structure, date);
+        assert!(birth.bytes.start < date.bytes.start && date.bytes.end <= birth.bytes.end);
+
+        // continuation lines belong to the text they continue
+        let contents = read_relative("./tests/fixtures/continuation.ged");
+        let data = GedcomDocument::with_options(contents.chars(), options.clone())
+            .parse_document()
+            .unwrap();
+        let spans = data.spans.unwrap();
+        assert_eq!(spans.span("@I1@", "NOTE").unwrap().lines, 11..16);
+        assert_eq!(spans.span("@I1@", "SOUR.DATA.TEXT").unwrap().lines, 22..24);
+        assert_eq!(spans.records()[0].structure("COPR").unwrap().lines, 6..9);
+
+        // each document of a stream is located within the whole stream
+        let stream = format!("{contents}{contents}");
+        let documents = GedcomDocument::with_options(stream.chars(), options)
+            .parse_all_documents()
+            .unwrap();
+        let second = documents[1].spans.as_ref().unwrap();
+        let note = second.span("@I1@", "NOTE").unwrap();
+        assert_eq!(note.lines, 37..42);
+        assert!(stream[note.bytes.clone()].starts_with("1 NOTE Lived at the mill,"));
+    }
+
+    #[test]
+    fn finds_records_by_xref() {
+        use gedcom::{store::IndexedGedcomData, writer::GedcomWriter};
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+        let written = data.to_gedcom_string();
+        let tree = IndexedGedcomData::from(data);
+
+        for fam in tree.families.records() {
+            let found = tree.find_family(fam.xref.as_deref().unwrap()).unwrap();
+            assert!(std::ptr::eq(found, fam));
+            for xref in fam.individual1.iter().chain(&fam.children) {
+                let indi = tree.find_individual(xref).unwrap();
+                assert_eq!(indi.xref.as_ref(), Some(xref));
+            }
+        }
+        assert!(tree.find_individual("@I0@").is_none());
+
+        let mut output = Vec::new();
+        GedcomWriter::new(&mut output).write(&tree).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), written);
+        assert_eq!(tree.into_gedcom_data().to_gedcom_string(), written);
+    }
+
+    #[test]
+    fn shares_tree_between_threads() {
+        use gedcom::{search::Indexes, store::ArcGedcomData, GedcomData};
+        use std::{sync::Arc, thread};
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<GedcomData>();
+        assert_send_sync::<ArcGedcomData>();
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+        let written = data.to_gedcom_string();
+        let tree = Arc::new(ArcGedcomData::from(data));
+
+        let handlers: Vec<_> = ["@I1@", "@I2@", "@F1@"]
+            .iter()
+            .map(|xref| {
+                let tree = Arc::clone(&tree);
+                thread::spawn(move || {
+                    let indexes = Indexes::new(&*tree);
+                    (indexes.xrefs.get(xref), tree.individuals.get(xref))
+                })
+            })
+            .collect();
+        let found: Vec<_> = handlers
+            .into_iter()
+            .map(|handler| handler.join().unwrap())
+            .collect();
+        assert_eq!(found[0].0.unwrap().index, 0);
+        assert!(Arc::ptr_eq(
+            found[1].1.as_ref().unwrap(),
+            &tree.individuals.records()[1]
+        ));
+        assert!(found[2].0.is_some() && found[2].1.is_none());
+
+        assert_eq!(tree.to_gedcom_data().to_gedcom_string(), written);
+    }
+
+    #[test]
+    fn converts_between_borrowed_and_owned() {
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+
+        let borrowed = gedcom::GedcomRef::parse(&washington_ged);
+        let owned = borrowed.to_owned();
+        assert_eq!(owned.individuals.len(), borrowed.individuals.len());
+        assert_eq!(owned.families.len(), borrowed.families.len());
+
+        let round_trip = owned.as_ref_model();
+        for (a, b) in round_trip.individuals.iter().zip(borrowed.individuals.iter()) {
+            assert_eq!(a.xref, b.xref);
+            assert_eq!(a.sex, b.sex);
+            assert_eq!(a.events.len(), b.events.len());
+            assert_eq!(a.families.len(), b.families.len());
+        }
+        for (a, b) in round_trip.families.iter().zip(borrowed.families.iter()) {
+            assert_eq!(a.individual1, b.individual1);
+            assert_eq!(a.children, b.children);
+        }
+    }
+
+    #[test]
+    fn recovers_from_broken_files() {
+        // every file in the corpus is broken in some way. Whichever mode it's parsed in, the
+        // parser must either recover or return an error, never panic.
+        for entry in std::fs::read_dir("./tests/fixtures/broken").unwrap() {
+            let path = entry.unwrap().path();
+            let contents = gedcom::input::decode(&std::fs::read(&path).unwrap());
+
+            let strict = GedcomDocument::new(contents.chars()).parse_document();
+            let options = ParseOptions::lenient();
+            let mut doc = GedcomDocument::with_options(contents.chars(), options);
+            let lenient = doc.parse_document();
+
+            // invalid bytes are replaced while decoding, so they don't reach the parser
+            if path.ends_with("mixed-encoding.ged") {
+                let data = lenient.unwrap();
+                let name = data.individuals[0].name.as_ref().unwrap();
+                assert_eq!(name.value.as_deref(), Some("René /M\u{FFFD}ller/"));
+                continue;
+            }
+
+            assert!(strict.is_err(), "{} parsed without error", path.display());
+            if lenient.is_ok() {
+                assert!(
+                    !doc.warnings().is_empty(),
+                    "{} had no warnings",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lenient_parsing_keeps_valid_records() {
+        let contents = read_relative("./tests/fixtures/broken/invalid-values.ged");
+
+        let mut doc = GedcomDocument::with_options(contents.chars(), ParseOptions::lenient());
+        let data = doc.parse_document().unwrap();
+
+        let lines: Vec<u32> = doc.warnings().iter().map(GedcomError::line).collect();
+        assert_eq!(lines, vec![4, 8, 10, 12, 15]);
+        assert_eq!(data.individuals.len(), 1);
+        assert!(data.individuals[0].name.is_some());
+        assert!(data.individuals[0].sex.is_none());
+        assert_eq!(data.families.len(), 1);
+        assert_eq!(data.families[0].individual1.as_deref(), Some("@I1@"));
+    }
+
+    #[test]
+    fn skips_lines_without_a_tag() {
+        let contents = read_relative("./tests/fixtures/broken/missing-tags.ged");
+
+        let err = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap_err();
+        assert_eq!(err.line(), 4);
+
+        let mut doc = GedcomDocument::with_options(contents.chars(), ParseOptions::lenient());
+        let data = doc.parse_document().unwrap();
+
+        let lines: Vec<u32> = doc.warnings().iter().map(GedcomError::line).collect();
+        assert_eq!(lines, vec![4, 7, 9]);
+        let xrefs: Vec<_> = data
+            .individuals
+            .iter()
+            .map(|indi| indi.xref.as_deref().unwrap())
+            .collect();
+        assert_eq!(xrefs, vec!["@I1@", "@I3@"]);
+        assert!(data.individuals[0].sex.is_some());
+    }
+
+    #[test]
+    fn skips_broken_records() {
+        let contents = read_relative("./tests/fixtures/broken/broken-records.ged");
+
+        let err = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap_err();
+        assert_eq!(err.line(), 9);
+
+        let options = ParseOptions {
+            skip_broken_records: true,
+            ..ParseOptions::default()
+        };
+        let mut doc = GedcomDocument::with_options(contents.chars(), options.clone());
+        let data = doc.parse_document().unwrap();
+
+        let xrefs: Vec<_> = data
+            .individuals
+            .iter()
+            .map(|indi| indi.xref.as_deref().unwrap())
+            .collect();
+        assert_eq!(xrefs, vec!["@I1@", "@I4@"]);
+        assert_eq!(data.families.len(), 1);
+        let skipped: Vec<_> = doc
+            .record_errors()
+            .iter()
+            .map(|skipped| {
+                (
+                    skipped.xref.as_deref().unwrap(),
+                    skipped.line,
+                    skipped.cause.line(),
+                )
+            })
+            .collect();
+        assert_eq!(skipped, vec![("@I2@", 7, 9), ("@I3@", 12, 14)]);
+        assert_eq!(
+            doc.record_errors()[0].to_string(),
+            "skipped record @I2@ at line 7: line 9, column 7: Invalid SEX value: Z"
+        );
+
+        // in lenient mode the problems are skipped within the records instead
+        let options = ParseOptions {
+            lenient: true,
+            ..options
+        };
+        let mut doc = GedcomDocument::with_options(contents.chars(), options);
+        let data = doc.parse_document().unwrap();
+        assert_eq!(data.individuals.len(), 4);
+        assert!(doc.record_errors().is_empty());
+        let lines: Vec<u32> = doc.warnings().iter().map(GedcomError::line).collect();
+        assert_eq!(lines, vec![9, 14, 15]);
+    }
+
+    #[test]
+    fn reports_the_span_of_problems() {
+        let contents = read_relative("./tests/fixtures/broken/invalid-values.ged");
+
+        let mut doc = GedcomDocument::with_options(contents.chars(), ParseOptions::lenient());
+        doc.parse_document().unwrap();
+
+        let spans: Vec<(u32, u32, &str)> = doc
+            .warnings()
+            .iter()
+            .map(|warning| {
+                let span = warning.span();
+                let text = &contents[span.offset..span.offset + span.len];
+                (span.line, span.column, text)
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (4, 8, "LINEAGE-CLIPPED"),
+                (8, 7, "Z"),
+                (10, 8, "step"),
+                (12, 8, "7"),
+                (15, 3, "HUSB"),
+            ]
+        );
+
+        let contents = read_relative("./tests/fixtures/broken/bad-levels.ged");
+        let err = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap_err();
+        let span = err.span();
+        assert_eq!((span.line, span.column), (8, 1));
+        assert_eq!(&contents[span.offset..span.offset + span.len], "X BIRT");
+    }
+
+    #[test]
+    fn warns_about_dropped_substructures() {
+        let contents = read_relative("./tests/fixtures/dropped-substructures.ged");
+
+        // dropping a substructure doesn't fail a strict parse
+        let mut doc = GedcomDocument::new(contents.chars());
+        let data = doc.parse_document().unwrap();
+
+        let dropped: Vec<(u32, String)> = doc
+            .warnings()
+            .iter()
+            .map(|warning| (warning.line(), warning.to_string()))
+            .collect();
+        assert_eq!(
+            dropped,
+            vec![
+                (
+                    5,
+                    "line 5, column 3: Dropped VERS substructure of FORM".to_string()
+                ),
+                (
+                    9,
+                    "line 9, column 3: Dropped SOUR substructure of GIVN".to_string()
+                ),
+                (
+                    11,
+                    "line 11, column 3: Dropped _PRIVATE substructure of GIVN".to_string()
+                ),
+            ]
+        );
+        // rather than being mistaken for substructures of the name
+        let name = data.individuals[0].name.as_ref().unwrap();
+        assert_eq!(name.given.as_deref(), Some("John"));
+        assert!(name.source.is_empty());
+        assert_eq!(data.individuals[0].families.len(), 1);
+
+        let data_of_source = &data.sources[0].data;
+        assert_eq!(data_of_source.events.len(), 1);
+        assert_eq!(data_of_source.agency.as_deref(), Some("Parish of Lavenham"));
+        assert_eq!(
+            data_of_source.notes[0].value.as_deref(),
+            Some("Registers kept by the vicar")
+        );
+        assert!(data.to_gedcom_string().contains(
+            "1 DATA\n2 EVEN BIRT\n3 DATE 1850\n2 AGNC Parish of Lavenham\n2 NOTE Registers"
+        ));
+    }
+    #[test]
+    fn parses_events_recorded_by_sources() {
+        use gedcom::types::{Calendar, DatePart, DateValue, Event};
+
+        let sample_ged: String = read_relative("./tests/fixtures/sample.ged");
+        let data = GedcomDocument::new(sample_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let source_data = &data.sources[0].data;
+        assert_eq!(source_data.events.len(), 1);
+        let recorded = &source_data.events[0];
+        assert_eq!(
+            recorded.events,
+            vec![Event::Birth, Event::Death, Event::Marriage]
+        );
+        assert!(!recorded.records(&Event::Burial));
+        assert_eq!(
+            recorded.date.as_ref().unwrap().value_structured(),
+            Some(DateValue::FromTo(
+                DatePart {
+                    day: None,
+                    month: Some(1),
+                    year: 1820,
+                    calendar: Calendar::Gregorian,
+                },
+                DatePart {
+                    day: None,
+                    month: Some(12),
+                    year: 1825,
+                    calendar: Calendar::Gregorian,
+                },
+            ))
+        );
+        assert_eq!(
+            recorded.place.as_deref(),
+            Some("Madison, Connecticut, United States of America")
+        );
+        assert_eq!(source_data.agency.as_deref(), Some("Madison County Court"));
+
+        assert!(data.to_gedcom_string().contains(
+            "1 DATA\n2 EVEN BIRT, DEAT, MARR\n3 DATE FROM Jan 1820 TO DEC 1825\n\
+             3 PLAC Madison, Connecticut, United States of America\n2 AGNC Madison County Court\n"
+        ));
+    }
+
+    #[test]
+    fn queries_events_of_individuals_families_and_sources() {
+        use gedcom::types::Event;
+
+        let sample_ged: String = read_relative("./tests/fixtures/sample.ged");
+        let data = GedcomDocument::new(sample_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let robert = &data.individuals[0];
+        let death = robert.death().unwrap();
+        assert_eq!(death.date.unwrap().value.as_deref(), Some("14 Apr 1905"));
+        let sorted: Vec<Event> = robert
+            .dated_events_sorted()
+            .into_iter()
+            .map(|event| event.event)
+            .collect();
+        assert_eq!(sorted, vec![Event::Birth, Event::Residence, Event::Death]);
+
+        let family = &data.families[0];
+        assert!(family.birth().is_none());
+        assert_eq!(family.events_of_kind(&Event::Marriage).len(), 1);
+
+        let recorded = data.sources[0].data.events();
+        assert_eq!(recorded.len(), 3);
+        assert_eq!(recorded[1].event, Event::Death);
+        assert_eq!(
+            recorded[1].place.as_deref(),
+            Some("Madison, Connecticut, United States of America")
+        );
+        assert_eq!(data.sources[0].data.dated_events_sorted().len(), 3);
+    }
+
+    #[test]
+    fn sorts_children_by_birth() {
+        let contents = read_relative("./tests/fixtures/sibling-order.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        // born after 1861, Walter comes after Clara and Daisy, born in March 1861. Undated
+        // children, whose dates are phrases or who are missing, keep their order last.
+        assert_eq!(
+            data.families[0].children_sorted_by_birth(&data),
+            vec!["@I7@", "@I2@", "@I5@", "@I4@", "@I8@", "@I1@", "@I3@", "@I6@", "@I9@"]
+        );
+    }
+
+    #[test]
+    fn lists_dated_events_in_chronological_order() {
+        let sample_ged: String = read_relative("./tests/fixtures/sample.ged");
+        let data = GedcomDocument::new(sample_ged.chars())
+            .parse_document()
+            .unwrap();
+        let events = |timeline: gedcom::timeline::Timeline| -> Vec<(String, Option<String>)> {
+            timeline
+                .entries()
+                .into_iter()
+                .map(|entry| (entry.event_kind, entry.xref.map(str::to_string)))
+                .collect()
+        };
+        let event = |kind: &str, xref: &str| (kind.to_string(), Some(xref.to_string()));
+
+        assert_eq!(
+            events(data.timeline()),
+            vec![
+                event("Birth", "@I1@"),
+                event("Birth", "@I2@"),
+                event("Marriage", "@F1@"),
+                event("Birth", "@I3@"),
+                event("Adoption", "@I3@"),
+                event("Residence", "@I1@"),
+                event("Death", "@I1@"),
+            ]
+        );
+        assert_eq!(
+            events(data.timeline().descendants_of("@I2@")),
+            vec![
+                event("Birth", "@I2@"),
+                event("Marriage", "@F1@"),
+                event("Birth", "@I3@"),
+                event("Adoption", "@I3@"),
+            ]
+        );
+        assert_eq!(
+            events(data.timeline().ancestors_of("@I3@").between(1850, 1870)),
+            vec![
+                event("Marriage", "@F1@"),
+                event("Birth", "@I3@"),
+                event("Adoption", "@I3@"),
+            ]
+        );
+
+        let death = &data.timeline().on_day(4, 14).entries()[0];
+        assert_eq!(
+            death.description,
+            "Death of Robert Eugene Williams in Stamford, Fairfield, Connecticut, United States of America"
+        );
+    }
+
+    #[test]
+    fn counts_name_frequencies() {
+        use gedcom::analysis::{self, NameCount, NameOptions};
+
+        let names_ged: String = read_relative("./tests/fixtures/names.ged");
+        let data = GedcomDocument::new(names_ged.chars())
+            .parse_document()
+            .unwrap();
+        let row = |name: &str, count: usize, variants: &[&str]| NameCount {
+            name: name.to_string(),
+            count,
+            variants: variants.iter().map(|variant| variant.to_string()).collect(),
+        };
+
+        let exact = analysis::name_frequencies(&data);
+        assert_eq!(exact.surnames.len(), 8);
+        assert_eq!(exact.given_names[0], row("Hans", 3, &[]));
+        assert_eq!(exact.given_names[1], row("Kari", 2, &[]));
+
+        let folded = analysis::name_frequencies_with(
+            &data,
+            NameOptions {
+                fold_case: true,
+                strip_diacritics: true,
+                ..NameOptions::default()
+            },
+        );
+        assert_eq!(folded.surnames[0], row("OLSEN", 2, &["Olsen"]));
+        assert_eq!(folded.surnames[1], row("Sorensen", 2, &["Sørensen"]));
+        assert_eq!(folded.given_names[3], row("Soren", 2, &["Søren"]));
+
+        let grouped = analysis::name_frequencies_with(
+            &data,
+            NameOptions {
+                group_patronymics: true,
+                ..NameOptions::default()
+            },
+        );
+        assert_eq!(
+            grouped.surnames,
+            vec![
+                row("OLSEN", 4, &["Olsdatter", "Olsen", "Olsson"]),
+                row("Sorensen", 2, &["Sørensen"]),
+                row("Hansdatter", 1, &[]),
+                row("Son", 1, &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_sourcing_of_facts() {
+        use gedcom::analysis::{self, Confidence, ConfidenceCounts, Fact};
+
+        let allged = read_relative("./tests/fixtures/allged.ged");
+        let data = GedcomDocument::new(allged.chars())
+            .parse_document()
+            .unwrap();
+        let report = analysis::sourcing(&data);
+        assert_eq!(report.individuals.len(), 8);
+
+        let person = report.person("@PERSON1@").unwrap();
+        let facts: Vec<_> = person
+            .facts
+            .iter()
+            .map(|fact| (fact.fact, fact.citations, fact.best_quality, fact.confidence))
+            .collect();
+        assert_eq!(
+            facts,
+            vec![
+                (Fact::Birth, 1, Some(2), Confidence::Secondary),
+                (Fact::Death, 1, Some(3), Confidence::Primary),
+                (Fact::Marriage, 1, Some(3), Confidence::Primary),
+            ]
+        );
+        assert_eq!(person.weakest(), Some(Confidence::Secondary));
+        // the spouse shares the marriage, which the tree counts once
+        let spouse = report.person("@PERSON2@").unwrap();
+        assert_eq!(spouse.facts, person.facts[2..]);
+        assert_eq!(report.person("@PERSON3@").unwrap().weakest(), None);
+
+        let count = |unsourced, secondary, primary| ConfidenceCounts {
+            unsourced,
+            secondary,
+            primary,
+        };
+        assert_eq!(report.births, count(0, 1, 0));
+        assert_eq!(*report.counts(Fact::Death), count(0, 0, 1));
+        assert_eq!(report.marriages, count(0, 0, 1));
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn parses_compressed_files() {
+        let plain = gedcom::parse_file("./tests/fixtures/sample.ged").unwrap();
+        for path in [
+            "./tests/fixtures/compressed/sample.ged.gz",
+            "./tests/fixtures/compressed/sample.zip",
+        ] {
+            let data = gedcom::parse_file(path).unwrap();
+            assert_eq!(data.individuals.len(), plain.individuals.len(), "{path}");
+            assert_eq!(data.families.len(), plain.families.len(), "{path}");
+            assert_eq!(data.to_gedcom_string(), plain.to_gedcom_string(), "{path}");
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "compression"))]
+    fn rejects_compressed_files_without_the_feature() {
+        let err = gedcom::parse_file("./tests/fixtures/compressed/sample.ged.gz").unwrap_err();
+        match err {
+            gedcom::ParseFileError::Io(err) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            }
+            gedcom::ParseFileError::Parse(err) => panic!("unexpected parse error: {}", err),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn compares_trees_ignoring_xref_numbering() {
+        use gedcom::testing;
+
+        let data = testing::load_fixture("./tests/fixtures/simple.ged");
+        let renumbered = read_relative("./tests/fixtures/simple.ged")
+            .replace("@SUBMITTER@", "@U1@")
+            .replace("@FATHER@", "@I10@")
+            .replace("@MOTHER@", "@I20@")
+            .replace("@CHILD@", "@I30@")
+            .replace("@FAMILY@", "@F7@");
+        let mut renumbered = GedcomDocument::new(renumbered.chars())
+            .parse_document()
+            .unwrap();
+        testing::assert_trees_eq(&data, &renumbered);
+        assert_eq!(testing::normalize(&data), testing::normalize(&renumbered));
+
+        assert_eq!(
+            testing::snapshot_record(&renumbered, "@F7@").unwrap(),
+            testing::snapshot_record(&data, "@FAMILY@").unwrap()
+        );
+        assert!(testing::snapshot_record(&data, "@MISSING@").is_none());
+
+        renumbered.individuals[2].name = None;
+        let result = std::panic::catch_unwind(|| testing::assert_trees_eq(&data, &renumbered));
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("1 NAME /Child/"), "{}", message);
+    }
+
+    #[test]
+    fn summarizes_simple_doc() {
+        let simple_ged: String = read_relative("./tests/fixtures/simple.ged");
+        let data = GedcomDocument::new(simple_ged.chars())
+            .parse_document()
+            .unwrap();
+        let stats = data.stats();
+
+        assert_eq!(stats.individuals, 3);
+        assert_eq!(stats.families, 1);
+        assert_eq!(stats.submitters, 1);
+        let events: Vec<(&str, usize)> = stats.events.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(events, vec![("BIRT", 3), ("DEAT", 3), ("MARR", 1)]);
+        let (earliest, latest) = stats.date_range.as_ref().unwrap();
+        assert_eq!(earliest.to_string(), "1 JAN 1899");
+        assert_eq!(latest.to_string(), "29 FEB 2000");
+        assert_eq!(stats.surnames.len(), 3);
+        assert_eq!(stats.surnames[0], ("Child".to_string(), 1));
+        assert_eq!(stats.unresolved_xrefs, 0);
+        assert!(stats.to_string().contains("  individuals: 3\n"));
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+        let stats = data.stats();
+        assert_eq!(stats.individuals, data.individuals.len());
+        assert_eq!(stats.surnames[0].0, "Washington");
+    }
+
+    #[test]
+    fn counts_shared_record_usage() {
+        let contents = read_relative("./tests/fixtures/sources.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let usage = data.usage_counts();
+
+        assert_eq!(usage.sources["@S1@"], 1);
+        assert_eq!(usage.sources["@S2@"], 0);
+        assert_eq!(usage.multimedia["@M1@"], 1);
+        assert!(usage.notes.is_empty());
+        assert!(usage.submitters.is_empty());
+        assert_eq!(usage.unused(), vec!["@S2@"]);
+
+        let simple_ged = read_relative("./tests/fixtures/simple.ged");
+        let data = GedcomDocument::new(simple_ged.chars())
+            .parse_document()
+            .unwrap();
+        assert!(data.usage_counts().unused().is_empty());
+    }
+
+    #[test]
+    fn formats_names() {
+        use gedcom::types::{NameFormat, NameParts};
+
+        let sample = "\
+            0 HEAD\n\
+            1 GEDC\n\
+            2 VERS 5.5\n\
+            0 @I1@ INDI\n\
+            1 NAME Martha Dandridge /Custis/\n\
+            2 GIVN Martha\n\
+            0 @I2@ INDI\n\
+            1 NAME Pocahontas\n\
+            0 @I3@ INDI\n\
+            1 NAME\n\
+            0 TRLR";
+        let data = GedcomDocument::new(sample.chars())
+            .parse_document()
+            .unwrap();
+        let names: Vec<_> = data
+            .individuals
+            .iter()
+            .map(|indi| indi.name.as_ref().unwrap())
+            .collect();
+
+        assert_eq!(
+            names[0].value_parts(),
+            NameParts {
+                given: Some("Martha Dandridge"),
+                surname: Some("Custis"),
+                rest: None,
+            }
+        );
+        assert_eq!(names[0].format(NameFormat::SurnameGiven), "Custis, Martha");
+        assert_eq!(names[0].full_name().unwrap(), "Martha Dandridge Custis");
+
+        assert_eq!(names[1].family_name(), None);
+        assert_eq!(names[1].format(NameFormat::SurnameGiven), "Pocahontas");
+        assert_eq!(names[1].format(NameFormat::Initials), "P.");
+
+        assert_eq!(names[2].value_parts(), NameParts::default());
+        assert_eq!(names[2].full_name(), None);
+        assert_eq!(names[2].format(NameFormat::GivenSurname), "");
+    }
+
+    #[test]
+    fn profiles_tags_of_vendor_files() {
+        use gedcom::inspect;
+
+        let file = std::fs::File::open("./tests/fixtures/vendor/ancestry.ged").unwrap();
+        let profile = inspect::tag_profile(file).unwrap();
+        assert_eq!(profile.paths["HEAD"], 1);
+        assert_eq!(profile.paths["INDI"], 2);
+        assert!(profile
+            .custom()
+            .all(|(path, _)| path.contains("._") || path.starts_with('_')));
+        assert!(profile.custom().count() > 0);
+
+        let utf16 = std::fs::read("./tests/fixtures/simple-utf16le.ged").unwrap();
+        let profile = inspect::tag_profile(utf16.as_slice()).unwrap();
+        assert_eq!(profile.paths["INDI.BIRT.DATE"], 3);
+        assert_eq!(profile.custom().count(), 0);
+    }
+
+    #[test]
+    fn joins_continuation_lines() {
+        let contents = read_relative("./tests/fixtures/continuation.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let header = data.header.as_ref().unwrap();
+        assert_eq!(
+            header.copyright.as_deref(),
+            Some("Copyright 2021 by the Smith family\nAll rights reserved.")
+        );
+        let indi = &data.individuals[0];
+        assert_eq!(
+            indi.notes[0].value.as_deref(),
+            Some("Lived at the mill, then moved to town\n    Indented line\n\nAfter a blank line")
+        );
+        let address = indi.events[0].contact.address.as_ref().unwrap();
+        assert_eq!(address.value.as_deref(), Some("12 Quay Street\nBristol"));
+        let text = indi.source[0].data.as_ref().unwrap().text.as_ref().unwrap();
+        assert_eq!(text.value.as_deref(), Some("Baptised in the parish church"));
+
+        let written = data.to_gedcom_string();
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.individuals[0].notes[0].value, indi.notes[0].value);
+    }
+
+    #[test]
+    fn keeps_repeated_phone_numbers() {
+        let contents = read_relative("./tests/fixtures/allged.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let header = data.header.as_ref().unwrap();
+        let corp = header
+            .source
+            .as_ref()
+            .and_then(|sour| sour.corporation.as_ref())
+            .unwrap();
+        assert_eq!(corp.contact.phones.len(), 3);
+        assert_eq!(
+            corp.contact.phones[2],
+            "Corporation phone number 3 (last one!)"
+        );
+        let submitter = data
+            .submitters
+            .iter()
+            .find(|subm| subm.xref.as_deref() == Some("@SUBMITTER@"))
+            .unwrap();
+        assert_eq!(
+            submitter.contact.phones,
+            vec![
+                "Submitter phone number 1",
+                "Submitter phone number 2",
+                "Submitter phone number 3 (last one!)"
+            ]
+        );
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains(
+            "1 PHON Submitter phone number 2\n1 PHON Submitter phone number 3 (last one!)\n"
+        ));
+    }
+
+    #[test]
+    fn computes_ages() {
+        use gedcom::types::{Age, Event};
+
+        let contents = read_relative("./tests/fixtures/allged.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let ages: Vec<&str> = data
+            .families
+            .iter()
+            .flat_map(|fam| &fam.events)
+            .chain(data.individuals.iter().flat_map(|indi| &indi.events))
+            .flat_map(|event| {
+                event
+                    .family_event_details
+                    .iter()
+                    .filter_map(|detail| detail.age.as_deref())
+                    .chain(event.age.as_deref())
+            })
+            .collect();
+        assert!(ages.contains(&"42y 6m 9d"));
+        for age in ages {
+            let parsed = Age::parse(age).unwrap();
+            // keywords such as CHILD are written as the bound they stand for
+            if !age.starts_with(char::is_alphabetic) {
+                assert_eq!(parsed.to_string(), age);
+            }
+        }
+
+        let contents = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let george = &data.individuals[0];
+        let lifespan = george.lifespan().unwrap();
+        assert_eq!(lifespan.to_string(), "67y 9m 22d");
+        assert!(!lifespan.approximate);
+        assert_eq!(george.age_at(&Event::Burial).unwrap().days, Some(26));
+        // nobody in the tree dies before they are born
+        assert!(data
+            .individuals
+            .iter()
+            .filter(|indi| indi.lifespan().is_some())
+            .all(|indi| indi.lifespan().unwrap().years < Some(120)));
+    }
+
+    #[test]
+    fn queries_records() {
+        use gedcom::types::{Event, GenderType};
+
+        let contents = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let xrefs = |indis: Vec<&gedcom::types::Individual>| -> Vec<String> {
+            indis.iter().filter_map(|indi| indi.xref.clone()).collect()
+        };
+
+        let parents = data.query().individuals().parent_of("@I1@");
+        assert_eq!(xrefs(parents.iter().collect()), vec!["@I3@", "@I6@"]);
+        let children = data.query().individuals().child_of("@I3@");
+        assert_eq!(
+            xrefs(children.iter().collect()),
+            vec!["@I1@", "@I11@", "@I12@", "@I13@", "@I14@", "@I15@"]
+        );
+        let spouses = data.query().individuals().spouse_of("@I3@");
+        assert_eq!(xrefs(spouses.iter().collect()), vec!["@I6@"]);
+
+        // the same selection as a hand-written loop
+        let custises = data
+            .query()
+            .individuals()
+            .surname("custis")
+            .born_between(1750, 1799)
+            .sex(&GenderType::Female);
+        let expected: Vec<&gedcom::types::Individual> = data
+            .individuals
+            .iter()
+            .filter(|indi| {
+                indi.name.as_ref().unwrap().family_name() == Some("Custis")
+                    && indi.sex.as_ref().map(|sex| sex.value.tag()) == Some("F")
+                    && indi.events.iter().any(|event| {
+                        event.event == Event::Birth
+                            && event
+                                .date
+                                .as_ref()
+                                .and_then(|date| date.value.as_deref())
+                                .is_some_and(|date| {
+                                    (1750..1800).any(|year| date.ends_with(&year.to_string()))
+                                })
+                    })
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(xrefs(custises.iter().collect()), xrefs(expected));
+
+        let families = data.query().families().spouse("@I1@").min_children(2);
+        let families: Vec<&str> = families
+            .iter()
+            .filter_map(|fam| fam.xref.as_deref())
+            .collect();
+        assert_eq!(families, vec!["@F2@"]);
+        assert_eq!(
+            data.query()
+                .families()
+                .has_event(Event::Marriage)
+                .iter()
+                .count(),
+            data.families
+                .iter()
+                .filter(|fam| fam
+                    .events
+                    .iter()
+                    .any(|event| event.event == Event::Marriage))
+                .count()
+        );
+    }
+
+    #[test]
+    fn finds_events_by_distance() {
+        use gedcom::geo::{self, LatLon};
+        use gedcom::search::RecordKind;
+        use gedcom::types::Event;
+
+        let contents = read_relative("./tests/fixtures/coordinates.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let located = geo::located_events(&data);
+        assert_eq!(located.len(), 6);
+        assert_eq!(located[5].record.kind, RecordKind::Family);
+        // plain signed decimals are read as well
+        assert_eq!(located[4].position, LatLon::new(43.6532, -79.3832));
+
+        let lavenham = LatLon::new(52.1086, 0.7963);
+        let born_nearby: Vec<&str> = geo::events_within(&data, lavenham, 10.0)
+            .iter()
+            .filter(|located| located.event.event == Event::Birth)
+            .filter_map(|located| located.xref)
+            .collect();
+        assert_eq!(born_nearby, vec!["@I1@", "@I2@"]);
+
+        let clusters = geo::cluster(&located, 25.0);
+        let sizes: Vec<usize> = clusters.iter().map(|c| c.events.len()).collect();
+        assert_eq!(sizes, vec![4, 2]);
+        assert!(
+            clusters[1]
+                .center
+                .distance_km(&LatLon::new(43.6532, -79.3832))
+                < 0.001
+        );
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("2 PLAC Toronto, Ontario, Canada\n3 MAP\n4 LATI 43.6532\n"));
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(geo::located_events(&reparsed).len(), 6);
+    }
+
+    #[test]
+    fn searches_washington_doc_names() {
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars()).parse_document().unwrap();
+        let index = NameIndex::new(&data);
+        assert_eq!(index.len(), data.individuals.len());
+
+        let exact = index.search("George Washington", SearchMode::Exact);
+        let soundex = index.search("George Washingtin", SearchMode::Soundex);
+        let typo = index.search("Gorge Washingtin", SearchMode::Levenshtein(1));
+        assert!(exact.iter().any(|r| r.xref == "@I1@"));
+        assert!(soundex.iter().any(|r| r.xref == "@I1@"));
+        assert!(typo.iter().any(|r| r.xref == "@I1@"));
+        // George Washington Parke /Custis/ matches too
+        assert_eq!(exact.len(), soundex.len());
+        assert!(soundex.iter().all(|r| r.score < 1.0));
+
+        let custis = data.search_individuals("custis", SearchMode::CaseInsensitive);
+        assert!(custis.len() >= 4);
+        assert!(custis.iter().all(|r| (r.score - 1.0).abs() < f64::EPSILON));
+    }
+
+    #[test]
+    fn writes_parsed_files_back() {
+        for path in [
+            "./tests/fixtures/washington.ged",
+            "./tests/fixtures/allged.ged",
+        ] {
+            let contents: String = read_relative(path);
+            let data = GedcomDocument::new(contents.chars())
+                .parse_document()
+                .unwrap();
+
+            let written = data.to_gedcom_string();
+            let reparsed = GedcomDocument::new(written.chars())
+                .parse_document()
+                .unwrap();
+            assert_eq!(reparsed.individuals.len(), data.individuals.len(), "{path}");
+            assert_eq!(reparsed.families.len(), data.families.len(), "{path}");
+            assert_eq!(reparsed.sources.len(), data.sources.len(), "{path}");
+            assert_eq!(reparsed.custom_data.len(), data.custom_data.len(), "{path}");
+            assert_eq!(reparsed.to_gedcom_string(), written, "{path}");
+        }
+    }
+
+    #[test]
+    fn formats_xrefs_on_write() {
+        use gedcom::writer::{GedcomWriter, WriterOptions, XrefFormat};
+
+        let write = |data: &gedcom::GedcomData| {
+            let options = WriterOptions {
+                xref_format: Some(XrefFormat::padded(3)),
+                ..WriterOptions::default()
+            };
+            let mut writer = GedcomWriter::with_options(Vec::new(), options);
+            writer.write(data).unwrap();
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+
+        let contents = read_relative("./tests/fixtures/simple.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let written = write(&data);
+        assert!(written.contains("1 SUBM @U001@\n"));
+        assert!(written.contains("0 @I002@ INDI\n1 NAME /Mother/\n"));
+        assert!(written.contains("1 HUSB @I001@\n1 WIFE @I002@\n1 CHIL @I003@\n"));
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.stats().unresolved_xrefs, 0);
+
+        // records written as they were read are renumbered too
+        let options = ParseOptions {
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let data = GedcomDocument::with_options(contents.chars(), options)
+            .parse_document()
+            .unwrap();
+        let mut renumbered = contents.clone();
+        for (xref, new) in [
+            ("@SUBMITTER@", "@U001@"),
+            ("@FATHER@", "@I001@"),
+            ("@MOTHER@", "@I002@"),
+            ("@CHILD@", "@I003@"),
+            ("@FAMILY@", "@F001@"),
+        ] {
+            renumbered = renumbered.replace(xref, new);
+        }
+        assert_eq!(write(&data), renumbered);
+    }
+
+    #[test]
+    fn reads_and_writes_indented_files() {
+        use gedcom::writer::{GedcomWriter, WriterOptions, XrefFormat};
+
+        let write = |data: &gedcom::GedcomData, options| {
+            let mut writer = GedcomWriter::with_options(Vec::new(), options);
+            writer.write(data).unwrap();
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+
+        let contents = read_relative("./tests/fixtures/simple.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let options = WriterOptions {
+            indent: 2,
+            ..WriterOptions::default()
+        };
+        let indented = write(&data, options);
+        assert!(indented.starts_with("0 HEAD\n  1 "));
+        assert!(indented.contains("\n  1 GEDC\n    2 VERS 5.5\n"));
+        assert!(indented.contains("\n0 @FATHER@ INDI\n  1 NAME /Father/\n"));
+        assert!(indented.ends_with("\n0 TRLR\n"));
+
+        // indented files, with spaces or tabs, read the same as flat ones
+        let flat = data.to_gedcom_string();
+        let borrowed_flat = gedcom::GedcomRef::parse(&flat)
+            .to_owned()
+            .to_gedcom_string();
+        let tabbed = indented.replace("  ", "\t");
+        for text in [&indented, &tabbed] {
+            let reparsed = GedcomDocument::new(text.chars()).parse_document().unwrap();
+            assert_eq!(reparsed.to_gedcom_string(), flat);
+            let borrowed = gedcom::GedcomRef::parse(text);
+            assert_eq!(borrowed.to_owned().to_gedcom_string(), borrowed_flat);
+        }
+
+        // lines are indented after being renumbered
+        let options = WriterOptions {
+            indent: 1,
+            xref_format: Some(XrefFormat::padded(3)),
+            ..WriterOptions::default()
+        };
+        let renumbered = write(&data, options);
+        assert!(renumbered.contains("\n0 @I002@ INDI\n 1 NAME /Mother/\n"));
+    }
+
+    #[test]
+    fn parses_creation_dates() {
+        let contents = read_relative("./tests/fixtures/timestamps.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let indi = &data.individuals[0];
+        let changed = indi.change_date.as_ref().unwrap().date.as_ref().unwrap();
+        assert_eq!(changed.time.as_deref(), Some("16:02:34Z"));
+        let created = indi.created.as_ref().unwrap().date.as_ref().unwrap();
+        assert_eq!(created.value.as_deref(), Some("1 JAN 2020"));
+        assert_eq!(created.time.as_deref(), Some("09:30:00Z"));
+
+        let created_on = |crea: Option<&gedcom::types::ChangeDate>| {
+            crea.and_then(|crea| crea.date.as_ref()?.value.clone())
+        };
+        assert_eq!(
+            created_on(data.families[0].created.as_ref()).unwrap(),
+            "2 JAN 2020"
+        );
+        assert_eq!(
+            created_on(data.sources[0].created.as_deref()).unwrap(),
+            "3 JAN 2020"
+        );
+        assert_eq!(
+            created_on(data.repositories[0].created.as_ref()).unwrap(),
+            "4 JAN 2020"
+        );
+        assert_eq!(
+            created_on(data.multimedia[0].created.as_ref()).unwrap(),
+            "5 JAN 2020"
+        );
+        assert_eq!(
+            created_on(data.submitters[0].created.as_ref()).unwrap(),
+            "6 JAN 2020"
+        );
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 CREA\n2 DATE 1 JAN 2020\n3 TIME 09:30:00Z\n"));
+        let reparsed = GedcomDocument::new(written.chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.to_gedcom_string(), written);
+    }
+
+    #[test]
+    fn reads_times_of_dates() {
+        use gedcom::{types::Time, GedcomError};
+
+        let contents = read_relative("./tests/fixtures/timestamps.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let indi = &data.individuals[0];
+        let changed = indi.change_date.as_ref().unwrap().date.as_ref().unwrap();
+        let time = changed.time_structured().unwrap();
+        assert_eq!(
+            time,
+            Time {
+                hour: 16,
+                minute: 2,
+                second: Some(34),
+                nanosecond: 0,
+                utc: true,
+            }
+        );
+        let created = indi.created.as_ref().unwrap().date.as_ref().unwrap();
+        assert!(created.time_structured().unwrap() < time);
+
+        // times out of range are kept as they are written, with a warning
+        let sample = "\
+            0 HEAD\n\
+            1 GEDC\n\
+            2 VERS 5.5.1\n\
+            1 DATE 2 OCT 2019\n\
+            2 TIME 7:05:09.125\n\
+            0 @I1@ INDI\n\
+            1 CHAN\n\
+            2 DATE 3 OCT 2019\n\
+            3 TIME 25:00:00\n\
+            0 TRLR";
+        let mut doc = GedcomDocument::new(sample.chars());
+        let data = doc.parse_document().unwrap();
+        let date = data.header.as_ref().unwrap().date.as_ref().unwrap();
+        let time = date.time_structured().unwrap();
+        assert_eq!(
+            (time.hour, time.nanosecond, time.utc),
+            (7, 125_000_000, false)
+        );
+        assert_eq!(time.to_string(), "07:05:09.125");
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            date.to_naive_datetime().unwrap().to_string(),
+            "2019-10-02 07:05:09.125"
+        );
+
+        let changed = data.individuals[0].change_date.as_ref().unwrap();
+        let changed = changed.date.as_ref().unwrap();
+        assert_eq!(changed.time.as_deref(), Some("25:00:00"));
+        assert_eq!(changed.time_structured(), None);
+        #[cfg(feature = "chrono")]
+        assert_eq!(changed.to_naive_datetime(), None);
+        assert!(matches!(
+            &doc.warnings()[..],
+            [GedcomError::InvalidValue { tag: "TIME", value, .. }] if value == "25:00:00"
+        ));
+    }
+
+    #[test]
+    fn keeps_void_pointers() {
+        use gedcom::{kinship::Kinship, types::Pointer, validate};
+
+        let contents = read_relative("./tests/fixtures/void-pointers.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let family = &data.families[0];
+        assert_eq!(
+            Pointer::new(family.individual1.as_ref().unwrap()),
+            Pointer::Void
+        );
+        assert!(Pointer::new(&family.children[0]).is_void());
+        assert_eq!(data.individuals[1].families[1].pointer(), Pointer::Void);
+        assert_eq!(Kinship::new(&data).parents("@I2@"), (None, Some("@I1@")));
+
+        assert!(data.validate().is_empty());
+        assert!(
+            validate::validate_stream(&contents, ParseOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(data.stats().unresolved_xrefs, 0);
+        assert!(data.usage_counts().notes.is_empty());
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 FAMC @VOID@\n2 PEDI adopted\n"));
+        assert!(written.contains("1 HUSB @VOID@\n1 WIFE @I1@\n1 CHIL @VOID@\n1 CHIL @I2@\n"));
+    }
+
+    #[test]
+    fn normalizes_trees_for_comparison() {
+        use gedcom::writer::{GedcomWriter, WriterOptions};
+
+        let write_normalized = |data: &gedcom::GedcomData| {
+            let options = WriterOptions {
+                normalize: true,
+                ..WriterOptions::default()
+            };
+            let mut writer = GedcomWriter::with_options(Vec::new(), options);
+            writer.write(data).unwrap();
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+
+        let contents = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let mut shuffled = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        shuffled.individuals.reverse();
+        shuffled.families.reverse();
+        let name = shuffled.individuals[0].name.as_mut().unwrap();
+        name.value = name
+            .value
+            .as_ref()
+            .map(|value| format!("  {}  ", value.replace(' ', "   ")));
+        assert_ne!(shuffled.to_gedcom_string(), data.to_gedcom_string());
+
+        let normalized = write_normalized(&data);
+        assert_eq!(write_normalized(&shuffled), normalized);
+        // the option leaves the tree itself untouched
+        assert_ne!(shuffled.to_gedcom_string(), normalized);
+
+        shuffled.normalize();
+        assert_eq!(shuffled.to_gedcom_string(), normalized);
+    }
+
+    #[test]
+    fn keeps_empty_payloads_distinct_from_missing_tags() {
+        let sample = "\
+            0 HEAD\n\
+            1 GEDC\n\
+            2 VERS 5.5\n\
+            0 @I1@ INDI\n\
+            1 SEX\n\
+            1 NOTE\n\
+            1 SOUR @S1@\n\
+            2 DATA\n\
+            3 TEXT\n\
+            2 QUAY\n\
+            0 @I2@ INDI\n\
+            1 NAME Jane /Doe/\n\
+            0 TRLR\n";
+
+        let data = GedcomDocument::new(sample.chars())
+            .parse_document()
+            .unwrap();
+        let present = &data.individuals[0];
+        assert_eq!(present.notes[0].value.as_deref(), Some(""));
+        let citation = &present.source[0];
+        let text = citation.data.as_ref().unwrap().text.as_ref().unwrap();
+        assert_eq!(text.value.as_deref(), Some(""));
+        assert!(matches!(
+            citation.certainty_assessment,
+            Some(gedcom::types::CertaintyAssessment::None)
+        ));
+        let missing = &data.individuals[1];
+        assert!(missing.notes.is_empty());
+        assert!(missing.sex.is_none());
+
+        let borrowed = gedcom::GedcomRef::parse(sample);
+        assert_eq!(borrowed.individuals[0].sex, Some(""));
+        assert_eq!(borrowed.individuals[1].sex, None);
+
+        let written = data.to_gedcom_string();
+        assert!(written.contains("1 NOTE\n"));
+        assert!(written.contains("3 TEXT\n"));
+        assert!(!written.contains("@I2@ INDI\n1 NAME Jane /Doe/\n1 NOTE"));
+    }
+
+    #[test]
+    fn reads_records_through_a_custom_store() {
+        use gedcom::store::{MemoryStore, Record, RecordStore, Records};
+        use gedcom::types::*;
+        use std::borrow::Cow;
+
+        /// hands out copies of its records, as a store decoding them on demand would
+        struct CopyingStore<T>(Vec<T>);
+
+        impl<T: Record> RecordStore<T> for CopyingStore<T> {
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+            fn lookup(&self, xref: &str) -> Option<Cow<'_, T>> {
+                let record = self.0.iter().find(|r| r.xref() == Some(xref))?;
+                Some(Cow::Owned(record.clone()))
+            }
+            fn iter_records(&self) -> Box<dyn Iterator<Item = Cow<'_, T>> + '_> {
+                Box::new(self.0.iter().map(|r| Cow::Owned(r.clone())))
+            }
+            fn add(&mut self, record: T) {
+                self.0.push(record);
+            }
+        }
+
+        struct Tree {
+            data: gedcom::GedcomData,
+            individuals: CopyingStore<Individual>,
+            families: MemoryStore<Family>,
+        }
+
+        impl Records for Tree {
+            fn header(&self) -> Option<&Header> {
+                self.data.header()
+            }
+            fn submitters(&self) -> &dyn RecordStore<Submitter> {
+                self.data.submitters()
+            }
+            fn submissions(&self) -> &dyn RecordStore<Submission> {
+                self.data.submissions()
+            }
+            fn individuals(&self) -> &dyn RecordStore<Individual> {
+                &self.individuals
+            }
+            fn families(&self) -> &dyn RecordStore<Family> {
+                &self.families
+            }
+            fn repositories(&self) -> &dyn RecordStore<Repository> {
+                self.data.repositories()
+            }
+            fn sources(&self) -> &dyn RecordStore<Source> {
+                self.data.sources()
+            }
+            fn multimedia(&self) -> &dyn RecordStore<MultimediaRecord> {
+                self.data.multimedia()
+            }
+            fn custom_data(&self) -> &[Box<UserDefinedDataset>] {
+                self.data.custom_data()
+            }
+        }
+
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+        let expected_output = data.to_gedcom_string();
+        let expected_indexes = gedcom::search::Indexes::new(&data);
+
+        let mut data = data;
+        let tree = Tree {
+            individuals: CopyingStore(std::mem::take(&mut data.individuals)),
+            families: MemoryStore::from(std::mem::take(&mut data.families)),
+            data,
+        };
+
+        let family = tree.families().lookup("@F1@").unwrap();
+        assert_eq!(family.xref(), Some("@F1@"));
+        let husband = family.individual1.as_deref().unwrap();
+        assert!(tree.individuals().lookup(husband).is_some());
+
+        let mut output = Vec::new();
+        gedcom::writer::GedcomWriter::new(&mut output)
+            .write(&tree)
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected_output);
+
+        let indexes = gedcom::search::Indexes::new(&tree);
+        assert_eq!(indexes.xrefs.len(), expected_indexes.xrefs.len());
+        assert_eq!(indexes.names.len(), expected_indexes.names.len());
+        assert_eq!(
+            indexes.places.places().count(),
+            expected_indexes.places.places().count()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "import")]
+    fn imports_spreadsheets() {
+        use gedcom::import::{self, ImportError};
+
+        let csv = read_relative("./tests/fixtures/people.csv");
+        let data = import::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(data.individuals.len(), 7);
+        assert!(data.validate().is_empty());
+
+        let george = &data.individuals[5];
+        assert_eq!(george.xref.as_deref(), Some("@I6@"));
+        assert_eq!(
+            george.name.as_ref().unwrap().given.as_deref(),
+            Some("George")
+        );
+        assert_eq!(
+            george.events[0].place.as_deref(),
+            Some("Wakefield, Yorkshire")
+        );
+        let william = &data.individuals[0];
+        assert_eq!(william.events.len(), 2);
+        assert_eq!(data.individuals[2].sex.as_ref().unwrap().value.tag(), "M");
+        assert!(data.individuals[6].sex.is_none());
+
+        // Thomas fathers George with Jane, and the unnamed child with an unknown mother
+        let families: Vec<(Option<&str>, Option<&str>, Vec<&str>)> = data
+            .families
+            .iter()
+            .map(|fam| {
+                (
+                    fam.individual1.as_deref(),
+                    fam.individual2.as_deref(),
+                    fam.children.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            families,
+            vec![
+                (Some("@I1@"), Some("@I2@"), vec!["@I3@", "@I4@"]),
+                (Some("@I3@"), Some("@I5@"), vec!["@I6@"]),
+                (Some("@I3@"), None, vec!["@I7@"]),
+            ]
+        );
+        assert_eq!(data.individuals[2].families.len(), 3);
+
+        // the tree is written out with a header, and reads back the same
+        let text = data.to_gedcom_string();
+        assert!(text.starts_with("0 HEAD\n1 GEDC\n2 VERS 5.5.1\n2 FORM LINEAGE-LINKED\n"));
+        let reread = GedcomDocument::new(text.chars()).parse_document().unwrap();
+        assert_eq!(reread.to_gedcom_string(), text);
+
+        let err = import::from_csv("id,father\na,\nb,c\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, ImportError::UnknownParent { row: 2, .. }));
+        assert_eq!(err.to_string(), "row 2: no row has the id c of its parent");
+        let err = import::from_json(r#"[{"id": "a"}, {"id": "a"}]"#.as_bytes()).unwrap_err();
+        assert!(matches!(err, ImportError::DuplicateId { row: 2, .. }));
+        assert!(matches!(
+            import::from_json("{}".as_bytes()),
+            Err(ImportError::Json(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "gedcomx")]
+    fn exports_washington_doc_as_gedcomx() {
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+
+        let gedcomx = data.to_gedcomx();
+        assert_eq!(gedcomx.persons.len(), data.individuals.len());
+        assert_eq!(gedcomx.source_descriptions.len(), data.sources.len());
+        let george = &gedcomx.persons[0];
+        assert_eq!(george.id, "I1");
+        assert_eq!(george.names[0].name_forms[0].full_text, "George Washington");
+        assert_eq!(
+            george.gender.as_ref().unwrap().gender_type,
+            "http://gedcomx.org/Male"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&gedcomx.to_json().unwrap()).unwrap();
+        let relationships = json["relationships"].as_array().unwrap();
+        let couple = relationships
+            .iter()
+            .find(|r| r["type"] == "http://gedcomx.org/Couple")
+            .unwrap();
+        assert!(couple["person1"]["resource"]
+            .as_str()
+            .unwrap()
+            .starts_with("#I"));
+        for person in json["persons"].as_array().unwrap() {
+            assert!(!person["id"].as_str().unwrap().contains('@'));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "gedcomx")]
+    fn exports_generic_facts_as_gedcomx() {
+        let contents = read_relative("./tests/fixtures/generic-facts.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let gedcomx = data.to_gedcomx();
+        let types: Vec<&str> = gedcomx.persons[0]
+            .facts
+            .iter()
+            .map(|fact| fact.fact_type.as_str())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "data:,DNA%20Match",
+                "data:,Confirmation%20of%20vows",
+                "data:,Eye%20colour",
+                "data:,Farm%20name",
+            ]
+        );
+        let couple = &gedcomx.relationships[0];
+        assert_eq!(couple.facts.len(), 2);
+        assert_eq!(couple.facts[1].fact_type, "data:,Tenure");
+    }
+
+    #[test]
+    fn iterates_records_of_every_kind() {
+        use gedcom::{GedcomData, RecordRef};
+
+        let contents = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let records: Vec<RecordRef> = data.records().collect();
+        let count = data.submitters.len()
+            + data.submissions.len()
+            + data.individuals.len()
+            + data.families.len()
+            + data.sources.len()
+            + data.repositories.len()
+            + data.multimedia.len()
+            + data.notes.len()
+            + data.custom_data.len();
+        assert_eq!(records.len(), count);
+        assert_eq!(records[0].xref(), data.individuals[0].xref.as_deref());
+        assert_eq!(records.last().unwrap().tag(), "_EVDEF");
+
+        let family = data.families[0].xref.as_deref().unwrap();
+        let record = data.get_record(family).unwrap();
+        assert_eq!(record.tag(), "FAM");
+        assert!(matches!(record, RecordRef::Family(fam) if fam.xref.as_deref() == Some(family)));
+        assert!(data.get_record("@MISSING@").is_none());
+
+        // copying every record into a new tree, without the header, gives the same records
+        let mut copy = GedcomData::default();
+        for record in data.records() {
+            copy.add_record(record.to_owned());
+        }
+        copy.header = data.header.clone();
+        assert_eq!(copy.to_gedcom_string(), data.to_gedcom_string());
+    }
+
+    #[test]
+    fn prints_debug_trees() {
+        let contents = read_relative("./tests/fixtures/kinship.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let mut output = Vec::new();
+        data.debug_tree(&mut output, "@I9@", 1).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "George Hale @I9@\n  \
+               ancestors:\n    \
+                 father: Thomas Hale @I3@\n    \
+                 mother: Jane Reed @I7@\n  \
+               descendants:\n    \
+                 spouse: Lucy Reed @I10@ in @F5@\n      \
+                   child: Edward Hale @I11@\n      \
+                   child: Alice Hale @I12@\n"
+        );
+
+        // the collapsed pedigree of Edward reaches his great-grandparents twice
+        let mut output = Vec::new();
+        data.debug_tree(&mut output, "@I11@", 3).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("father: William Hale @I1@").count(), 2);
+        assert!(!output.contains("descendants:"));
+
+        let mut output = Vec::new();
+        data.debug_tree(&mut output, "@MISSING@", 3).unwrap();
+        assert_eq!(output, b"@MISSING@ (missing)\n");
+    }
+
+    #[test]
+    fn repairs_structural_problems() {
+        use gedcom::repair::{RepairKind, RepairOptions};
+
+        let contents = read_relative("./tests/fixtures/repair.ged");
+        let parse = || {
+            GedcomDocument::new(contents.chars())
+                .parse_document()
+                .unwrap()
+        };
+        let mut data = parse();
+        let repairs = data.repair(RepairOptions::default());
+        let changes: Vec<String> = repairs.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "INDI record given the xref @I5@",
+                "SOUR record given the xref @S1@",
+                "empty DEAT removed from @I1@",
+                "empty _FLAG removed from @I1@",
+                "empty NOTE removed from @I2@",
+                "empty _UNIT removed from @I3@",
+                "empty _MILT removed from @I3@",
+                "empty MARR removed from @F1@",
+                "empty _PLAC removed from _PLAC",
+                "duplicate CHIL @I3@ removed from @F1@",
+                "FAMS @F1@ added to @I1@",
+                "WIFE @I2@ added to @F1@",
+                "FAMS @F1@ of @I4@ left unmatched, the family has two spouses",
+                "CHIL @I5@ added to @F1@",
+            ]
+        );
+        assert_eq!(repairs[10].kind, RepairKind::AddedLink);
+        assert_eq!(repairs[10].value.as_deref(), Some("@F1@"));
+
+        let family = &data.families[0];
+        assert_eq!(family.individual2.as_deref(), Some("@I2@"));
+        // Ruth is left out, as the family already has two spouses, and the missing child is left
+        // for validation
+        assert_eq!(family.individual1.as_deref(), Some("@I1@"));
+        assert_eq!(repairs[12].kind, RepairKind::UnrepairedLink);
+        assert_eq!(family.children, vec!["@I3@", "@I9@", "@I5@"]);
+        assert!(data.individuals[0].events[0].date.is_some());
+        assert!(data.custom_data.is_empty());
+        // only the link which couldn't be repaired is left
+        let repairs = data.repair(RepairOptions::default());
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].kind, RepairKind::UnrepairedLink);
+
+        // repairs can be picked one by one
+        let mut data = parse();
+        let options = RepairOptions {
+            assign_xrefs: false,
+            strip_empty: false,
+            link_families: false,
+            ..RepairOptions::default()
+        };
+        let repairs = data.repair(options);
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].kind, RepairKind::RemovedDuplicate);
+        assert_eq!(data.individuals[4].xref, None);
+    }
+
+    #[test]
+    fn writes_reports() {
+        use gedcom::report::{Report, ReportFormat};
+
+        let contents = read_relative("./tests/fixtures/kinship.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let report = Report::new(&data, ReportFormat::Text);
+        assert_eq!(
+            report.family("@F5@").unwrap(),
+            "Family of George Hale and Lucy Reed @F5@\n\n\
+             Husband:\n  \
+               George Hale @I9@\n\n\
+             Wife:\n  \
+               Lucy Reed @I10@\n\n\
+             Children:\n  \
+               Edward Hale @I11@\n  \
+               Alice Hale @I12@\n"
+        );
+
+        // the collapsed pedigree of Edward lists William under both of his lines
+        let outline = report.ancestors("@I11@", 3).unwrap();
+        assert!(outline.starts_with("Ancestors of Edward Hale\n\n1. Edward Hale @I11@\n"));
+        assert!(outline.contains("\n      8. William Hale @I1@\n"));
+        assert!(outline.contains("\n      14. William Hale @I1@\n"));
+        assert_eq!(outline.lines().count(), 17);
+        assert_eq!(report.ancestors("@I11@", 0).unwrap().lines().count(), 3);
+
+        let report = Report::new(&data, ReportFormat::Markdown);
+        assert_eq!(
+            report.individual("@I10@").unwrap(),
+            "# Lucy Reed @I10@\n\n\
+             ## Facts\n\n\
+             - Sex: Female\n\n\
+             ## Parents\n\n\
+             - Father: Henry Reed @I8@\n\
+             - Mother: Ann Hale @I4@\n\n\
+             ## Families\n\n\
+             - Spouse: George Hale @I9@ in @F5@\n  \
+               - Child: Edward Hale @I11@\n  \
+               - Child: Alice Hale @I12@\n"
+        );
+        assert!(report.individual("@I99@").is_none());
+    }
+
+    #[test]
+    fn edits_records_keeping_links() {
+        use gedcom::types::{FamilyLink, FamilyLinkType};
+
+        let contents = read_relative("./tests/fixtures/kinship.ged");
+        let mut data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        assert!(data.add_child_to_family("@F3@", "@I12@"));
+        assert!(data.add_child_to_family("@F3@", "@I12@"));
+        assert_eq!(data.families[2].children, vec!["@I9@", "@I12@"]);
+
+        // Lucy is unlinked from her marriage, and made a child of a family which doesn't exist
+        let mut lucy = data.individuals[9].clone();
+        lucy.families.retain(|link| link.xref != "@F5@");
+        let mut missing = lucy.families[0].clone();
+        missing.xref = "@F9@".to_string();
+        lucy.families.push(missing);
+        assert!(data.replace_individual(lucy).is_some());
+        assert_eq!(data.families[4].individual2, None);
+        assert_eq!(data.individuals[9].families.len(), 1);
+
+        let removed = data.remove_family("@F2@").unwrap();
+        assert_eq!(removed.children, vec!["@I7@", "@I8@"]);
+        assert!(data.individuals[4].families.is_empty());
+        let edward = data.remove_individual("@I11@").unwrap();
+        assert_eq!(edward.name.unwrap().value.as_deref(), Some("Edward /Hale/"));
+        assert_eq!(data.families[3].children, vec!["@I12@"]);
+        assert!(data.remove_individual("@I11@").is_none());
+
+        // every link is recorded on both sides
+        assert!(data.validate().is_empty());
+        let is_member = |link: &FamilyLink, xref: &str| {
+            let fam = data
+                .families
+                .iter()
+                .find(|fam| fam.xref.as_deref() == Some(link.xref.as_str()))
+                .unwrap();
+            match link.family_link_type {
+                FamilyLinkType::Spouse => {
+                    fam.individual1.as_deref() == Some(xref)
+                        || fam.individual2.as_deref() == Some(xref)
+                }
+                FamilyLinkType::Child => fam.children.iter().any(|child| child == xref),
+            }
+        };
+        for indi in &data.individuals {
+            let xref = indi.xref.as_deref().unwrap();
+            assert!(indi.families.iter().all(|link| is_member(link, xref)));
+        }
+    }
+
+    #[test]
+    fn renders_charts() {
+        use gedcom::render::{Chart, Labels};
+
+        let contents = read_relative("./tests/fixtures/kinship.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        // the great-grandparents Edward reaches twice are drawn once, with an edge per line
+        let chart = Chart::ancestors(&data, "@I11@", 3);
+        assert_eq!(chart.individuals().len(), 11);
+        assert_eq!(chart.edges().len(), 14);
+        let dot = chart.to_dot(Labels::default());
+        assert!(dot.starts_with("digraph ancestors {\n"));
+        assert_eq!(dot.matches("\"@I1@\" [label=\"William Hale").count(), 1);
+        assert_eq!(dot.matches("\"@I1@\" -> ").count(), 2);
+
+        let chart = Chart::ancestors(&data, "@I11@", 1);
+        assert_eq!(chart.individuals(), &["@I11@", "@I9@", "@I10@"]);
+
+        let labels = Labels {
+            name: false,
+            years: false,
+            xref: false,
+        };
+        let mermaid = Chart::descendants(&data, "@I9@", 5).to_mermaid(labels);
+        assert_eq!(
+            mermaid,
+            "flowchart TD\n  \
+               I9[\"@I9@\"]\n  \
+               I11[\"@I11@\"]\n  \
+               I12[\"@I12@\"]\n  \
+               I9 --> I11\n  \
+               I9 --> I12\n"
+        );
+    }
+
+    #[test]
+    fn finds_pedigree_collapse() {
+        use gedcom::kinship::Kinship;
+
+        let contents = read_relative("./tests/fixtures/kinship.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let kinship = Kinship::new(&data);
+
+        // George and Lucy are double first cousins
+        assert_eq!(kinship.relationship("@I9@", "@I10@").coefficient, 0.25);
+        assert_eq!(kinship.relationship("@I3@", "@I4@").coefficient, 0.5);
+        assert_eq!(kinship.relationship("@I1@", "@I5@").coefficient, 0.0);
+
+        let collapse = kinship.pedigree_collapse("@I11@");
+        let shared: Vec<&str> = collapse.iter().map(|ancestor| ancestor.xref).collect();
+        assert_eq!(shared, vec!["@I1@", "@I2@", "@I5@", "@I6@"]);
+        assert!(collapse.iter().all(|ancestor| ancestor.path_lengths == vec![3, 3]));
+        assert_eq!(kinship.inbreeding_coefficient("@I11@"), 0.125);
+
+        // Thomas is both grandfather and great-uncle of Edward
+        let relationship = kinship.relationship("@I11@", "@I3@");
+        assert_eq!(relationship.coefficient, 0.375);
+        assert_eq!(relationship.common_ancestors[0].xref, "@I3@");
+        assert_eq!(relationship.common_ancestors[0].path_lengths, vec![(2, 0)]);
+
+        // adoption doesn't count towards consanguinity
+        assert_eq!(kinship.parents("@I12@"), (None, None));
+        assert_eq!(kinship.inbreeding_coefficient("@I12@"), 0.0);
+        assert!(kinship.pedigree_collapse("@I12@").is_empty());
+    }
+
+    #[test]
+    fn privatizes_living_individuals() {
+        use gedcom::edit::{PrivacyAction, PrivacyOptions};
+
+        let contents = read_relative("./tests/fixtures/living.ged");
+        let parse = || {
+            GedcomDocument::new(contents.chars())
+                .parse_document()
+                .unwrap()
+        };
+        let names = |data: &gedcom::GedcomData| -> Vec<String> {
+            data.individuals
+                .iter()
+                .filter_map(|indi| indi.name.as_ref()?.value.clone())
+                .collect()
+        };
+
+        // Joan is 100, Peter was buried and Anne has no dates
+        let mut living = parse();
+        assert_eq!(
+            living.privatize(&PrivacyOptions::new(2026)),
+            vec!["@I3@", "@I4@"]
+        );
+        assert_eq!(
+            names(&living),
+            vec![
+                "Harold /Pearce/",
+                "Joan /Mills/",
+                "Living",
+                "Living",
+                "Peter /Pearce/",
+                "Anne /Pearce/"
+            ]
+        );
+        let david = &living.individuals[2];
+        assert!(david.events.is_empty() && david.attributes.is_empty() && david.notes.is_empty());
+        assert_eq!(david.families.len(), 2);
+        assert!(david.sex.is_some());
+        assert_eq!(living.families[0].events.len(), 1);
+        assert!(living.families[1].events.is_empty());
+        assert_eq!(living.families[1].children, vec!["@I5@"]);
+
+        let mut name_only = parse();
+        let options = PrivacyOptions {
+            action: PrivacyAction::NameOnly,
+            undated_are_living: true,
+            ..PrivacyOptions::new(2026)
+        };
+        assert_eq!(name_only.privatize(&options), vec!["@I3@", "@I4@", "@I6@"]);
+        assert_eq!(names(&name_only), names(&parse()));
+        assert!(name_only.individuals[3].events.is_empty());
+
+        let mut removed = parse();
+        let options = PrivacyOptions {
+            action: PrivacyAction::Remove,
+            years: 110,
+            ..PrivacyOptions::new(2026)
+        };
+        assert_eq!(removed.privatize(&options), vec!["@I2@", "@I3@", "@I4@"]);
+        assert_eq!(
+            names(&removed),
+            vec!["Harold /Pearce/", "Peter /Pearce/", "Anne /Pearce/"]
+        );
+        assert_eq!(removed.families[0].individual2, None);
+        assert_eq!(removed.families[0].children, vec!["@I6@"]);
+        // Peter's parents are both gone, but the family keeps him as a child
+        assert_eq!(removed.families[1].children, vec!["@I5@"]);
+        assert!(removed.individuals[0]
+            .families
+            .iter()
+            .all(|link| link.xref == "@F1@"));
+    }
+
+    #[test]
+    fn privatizes_preserved_lines() {
+        use gedcom::edit::{PrivacyAction, PrivacyOptions};
+
+        let sample = "\
+            0 HEAD\n\
+            1 GEDC\n\
+            2 VERS 5.5\n\
+            0 @I1@ INDI\n\
+            1 NAME John /Smith/\n\
+            1 DEAT\n\
+            2 DATE 1961\n\
+            1 FOOB Kept for the dead\n\
+            1 FAMC @F1@\n\
+            0 @I2@ INDI\n\
+            1 NAME Mary /Smith/\n\
+            1 FOOB Secret of the living\n\
+            1 FAMS @F1@\n\
+            0 INDI\n\
+            1 NAME Anne /Smith/\n\
+            1 FOOB Secret of the living without an xref\n\
+            0 @F1@ FAM\n\
+            1 WIFE @I2@\n\
+            1 CHIL @I1@\n\
+            1 FOOB Secret of the family\n\
+            1 _MARR Secret wedding\n\
+            0 TRLR\n";
+        let options = ParseOptions {
+            lenient: true,
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let mut data = GedcomDocument::with_options(sample.chars(), options)
+            .parse_document()
+            .unwrap();
+        assert_eq!(data.to_gedcom_string(), sample);
+
+        // Mary keeps her name, so her record is written as it was but for the unknown tag
+        let options = PrivacyOptions {
+            action: PrivacyAction::NameOnly,
+            undated_are_living: true,
+            ..PrivacyOptions::new(2026)
+        };
+        assert_eq!(data.privatize(&options), vec!["@I2@"]);
+        let output = data.to_gedcom_string();
+        assert!(!output.contains("Secret"), "{}", output);
+        assert!(output.contains("1 FOOB Kept for the dead\n"));
+        assert!(output.contains("0 @I2@ INDI\n1 NAME Mary /Smith/\n1 FAMS @F1@\n0 INDI\n"));
+        assert!(output.contains("0 INDI\n1 NAME Anne /Smith/\n0 @F1@ FAM\n"));
+        assert!(data.families[0].custom_data.is_empty());
+
+        // Anne is removed along with Mary, although she has no xref
+        let options = PrivacyOptions {
+            action: PrivacyAction::Remove,
+            undated_are_living: true,
+            ..PrivacyOptions::new(2026)
+        };
+        assert_eq!(data.privatize(&options), vec!["@I2@"]);
+        assert_eq!(data.individuals.len(), 1);
+    }
+
+    #[test]
+    fn numbers_ancestors_and_descendants() {
+        use gedcom::numbering;
+
+        let contents = read_relative("./tests/fixtures/kinship.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let ancestors = numbering::ahnentafel(&data, "@I11@");
+        assert_eq!(ancestors.len(), 11);
+        assert_eq!(ancestors["@I11@"], 1);
+        assert_eq!(ancestors["@I10@"], 3);
+        assert_eq!(ancestors["@I8@"], 6);
+        // William is both 8 and 14, and keeps the lowest number
+        assert_eq!(ancestors["@I1@"], 8);
+        assert_eq!(ancestors["@I6@"], 11);
+        assert!(numbering::ahnentafel(&data, "@I99@").is_empty());
+
+        let descendants = numbering::daboville(&data, "@I1@");
+        assert_eq!(descendants.len(), 7);
+        assert_eq!(descendants["@I4@"], "1.2");
+        assert_eq!(descendants["@I10@"], "1.2.1");
+        // Edward descends from William through both Thomas and Ann
+        assert_eq!(descendants["@I11@"], "1.1.1.1");
+        assert_eq!(descendants["@I12@"], "1.1.1.2");
+
+        let descendants = numbering::henry(&data, "@I1@");
+        assert_eq!(descendants["@I10@"], "121");
+        assert_eq!(descendants["@I12@"], "1112");
+    }
+
+    #[test]
+    fn finds_duplicate_individuals() {
+        use gedcom::duplicates::{DuplicateOptions, Matcher};
+
+        let contents = read_relative("./tests/fixtures/duplicates.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let duplicates = data.find_duplicates(&DuplicateOptions::default());
+        let pairs: Vec<(&str, &str)> = duplicates
+            .iter()
+            .map(|pair| (pair.first.as_str(), pair.second.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("@I6@", "@I7@"), ("@I1@", "@I2@")]);
+        // only names can be compared for Ellen
+        assert_eq!(duplicates[0].matches.len(), 1);
+        assert_eq!(duplicates[1].matches.len(), 4);
+
+        // John and Joan are never paired as their sexes differ
+        let options = DuplicateOptions {
+            matchers: vec![Matcher::Name { weight: 1.0 }],
+            min_score: 0.5,
+        };
+        assert!(data
+            .find_duplicates(&options)
+            .iter()
+            .all(|pair| (pair.first.as_str(), pair.second.as_str()) != ("@I1@", "@I4@")));
+
+        let options = DuplicateOptions {
+            matchers: vec![Matcher::SameParents { weight: 1.0 }],
+            min_score: 1.0,
+        };
+        let duplicates = data.find_duplicates(&options);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].first, "@I1@");
+    }
+
+    #[test]
+    fn extracts_one_place_and_one_name_studies() {
+        use gedcom::{extract, search::SearchMode};
+
+        let contents = read_relative("./tests/fixtures/one-place.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let xrefs = |data: &gedcom::GedcomData| -> Vec<String> {
+            data.individuals
+                .iter()
+                .filter_map(|indi| indi.xref.clone())
+                .collect()
+        };
+
+        // born, married with a witness, and working in Lavenham
+        let study = extract::one_place(&data, "Lavenham, Suffolk");
+        assert_eq!(xrefs(&study), vec!["@I1@", "@I2@", "@I4@", "@I5@"]);
+        assert_eq!(study.families.len(), 2);
+        assert!(study.families[0].children.is_empty());
+        assert_eq!(study.families[1].individual2, None);
+        assert_eq!(study.sources.len(), 1);
+        assert_eq!(study.repositories.len(), 1);
+        // Jane is left out, so her witnessing keeps only her name
+        let witness = &study.individuals[0].events[1].associations[0];
+        assert_eq!(witness.value, None);
+        assert_eq!(witness.name.as_deref(), Some("Jane /Smith/"));
+
+        let reparsed = GedcomDocument::new(study.to_gedcom_string().chars())
+            .parse_document()
+            .unwrap();
+        assert_eq!(reparsed.stats().unresolved_xrefs, 0);
+        assert_eq!(reparsed.individuals.len(), 4);
+
+        let study = extract::one_name(&data, "Smith", SearchMode::Soundex);
+        assert_eq!(xrefs(&study), vec!["@I1@", "@I3@", "@I5@", "@I6@"]);
+        assert_eq!(study.stats().unresolved_xrefs, 0);
+
+        let study = extract::one_name(&data, "smith", SearchMode::CaseInsensitive);
+        assert_eq!(xrefs(&study), vec!["@I1@", "@I6@"]);
+        assert!(study.families[0].children.is_empty());
+        assert_eq!(study.families[1].individual2.as_deref(), Some("@I6@"));
+
+        assert!(extract::one_place(&data, "Lav").individuals.is_empty());
+    }
+
+    #[test]
+    fn extracts_branches_of_a_tree() {
+        use gedcom::extract::ExtractOptions;
+
+        let contents = read_relative("./tests/fixtures/kinship.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let xrefs = |data: &gedcom::GedcomData| -> Vec<String> {
+            data.individuals
+                .iter()
+                .filter_map(|indi| indi.xref.clone())
+                .collect()
+        };
+
+        // George with his parents, wife and children
+        let options = ExtractOptions {
+            ancestors: 1,
+            descendants: 1,
+            ..ExtractOptions::default()
+        };
+        let branch = data.extract("@I9@", options);
+        assert_eq!(
+            xrefs(&branch),
+            vec!["@I3@", "@I7@", "@I9@", "@I10@", "@I11@", "@I12@"]
+        );
+        assert_eq!(branch.stats().unresolved_xrefs, 0);
+        // the families Thomas, Jane and Lucy were born in keep them as children only
+        assert_eq!(branch.families.len(), 5);
+        assert_eq!(branch.families[0].individual1, None);
+        assert_eq!(branch.families[0].children, vec!["@I3@"]);
+
+        // William's descendants, through Ann's marriage too, without their spouses
+        let options = ExtractOptions {
+            descendants: 2,
+            include_spouses: false,
+            ..ExtractOptions::default()
+        };
+        let branch = data.extract("@I1@", options);
+        assert_eq!(
+            xrefs(&branch),
+            vec!["@I1@", "@I3@", "@I4@", "@I9@", "@I10@"]
+        );
+        assert_eq!(branch.families[0].individual2, None);
+        assert_eq!(branch.stats().unresolved_xrefs, 0);
+
+        let branch = data.extract("@I99@", ExtractOptions::default());
+        assert!(branch.individuals.is_empty() && branch.families.is_empty());
+    }
+
+    #[test]
+    fn reads_vendor_header_metadata() {
+        let contents = read_relative("./tests/fixtures/vendor-header.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+
+        let provenance = data.provenance();
+        assert_eq!(provenance.product, Some("MYHERITAGE"));
+        assert_eq!(
+            provenance.product_name,
+            Some("MyHeritage Family Tree Builder")
+        );
+        assert_eq!(provenance.product_version, Some("8.0.0.8414"));
+        assert_eq!(provenance.product_build, Some("8414"));
+        assert_eq!(provenance.corporation, Some("MyHeritage Ltd."));
+        assert_eq!(provenance.exported, Some("14 MAR 2021"));
+        assert_eq!(
+            provenance.project_guid,
+            Some("6A4F0E5C-2B3D-4E1F-9A7B-8C6D5E4F3A2B")
+        );
+        assert_eq!(provenance.site_id, Some("123456789"));
+        assert_eq!(provenance.tree_name, None);
+
+        // vendor tags under SOUR are kept and written back
+        let sour = data.header.as_ref().unwrap().source.as_ref().unwrap();
+        assert_eq!(sour.custom_data[0].tag, "_RTLSAVE");
+        let output = data.to_gedcom_string();
+        assert!(output.contains("1 SOUR MYHERITAGE\n2 VERS 8.0.0.8414\n"));
+        assert!(output.contains("2 _RTLSAVE RTL\n"));
+
+        assert_eq!(
+            gedcom::GedcomData::default().provenance(),
+            gedcom::types::ProvenanceInfo::default()
+        );
+    }
+
+    #[test]
+    fn preserves_untouched_records() {
+        use gedcom::{types::Individual, ParseOptions};
+
+        let contents = read_relative("./tests/fixtures/roundtrip.ged");
+        let options = ParseOptions {
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let mut data = GedcomDocument::with_options(contents.chars(), options)
+            .parse_document()
+            .unwrap();
+        assert_eq!(data.to_gedcom_string(), contents);
+
+        let source_map = data.source_map.as_ref().unwrap();
+        let tags: Vec<&str> = source_map
+            .records()
+            .iter()
+            .map(|r| r.tag.as_str())
+            .collect();
+        assert_eq!(
+            tags,
+            vec!["HEAD", "INDI", "FAM", "INDI", "NOTE", "INDI", "TRLR"]
+        );
+        let mary = source_map.record("@I2@").unwrap();
+        assert_eq!(mary.lines, 19..25);
+        assert!(mary
+            .raw
+            .starts_with("0 @I2@ INDI\r\n1 NAME Mary /Brown/\r\n"));
+
+        // edit one record, delete another and add a new one
+        data.individuals[0].sex = None;
+        data.individuals
+            .retain(|indi| indi.xref.as_deref() != Some("@I3@"));
+        data.families[0].children.clear();
+        data.add_individual(Individual {
+            xref: Some("@I4@".to_string()),
+            ..Individual::default()
+        });
+        let output = data.to_gedcom_string();
+
+        let original: Vec<&str> = contents.split_inclusive('\n').collect();
+        let written: Vec<&str> = output.split_inclusive('\n').collect();
+        // the header is untouched
+        assert_eq!(written[..7], original[..7]);
+        assert_eq!(
+            written[7..14].concat(),
+            "0 @I1@ INDI\n1 NAME John /Smith/\n1 BIRT\n2 DATE 12 MAR 1850\n\
+             2 PLAC Lavenham, Suffolk, England\n1 FAMS @F1@\n0 @F1@ FAM\n"
+        );
+        // Mary keeps her tag order and CONC split, and the unread NOTE record is kept
+        assert_eq!(written[16..23], original[18..25]);
+        assert_eq!(written[23..], ["0 @I4@ INDI\n", "0 TRLR\r\n"]);
+
+        data.source_map = None;
+        assert!(!data.to_gedcom_string().contains('\r'));
+    }
+
+    #[test]
+    fn passes_unrecognized_structures_through() {
+        use gedcom::ParseOptions;
+
+        for file in &["ancestry.ged", "rootsmagic.ged"] {
+            let contents = read_relative(&format!("./tests/fixtures/vendor/{file}"));
+            let options = ParseOptions {
+                lenient: true,
+                preserve_lines: true,
+                ..ParseOptions::default()
+            };
+            let mut doc = GedcomDocument::with_options(contents.chars(), options);
+            let mut data = doc.parse_document().unwrap();
+            assert!(!doc.warnings().is_empty());
+            assert_eq!(data.to_gedcom_string(), contents);
+
+            // regenerated records keep the structures the parser skipped, in place
+            for indi in &mut data.individuals {
+                let name = indi.name.as_mut().unwrap();
+                name.value = name.value.as_ref().map(|value| value.to_uppercase());
+            }
+            let expected: String = contents
+                .split_inclusive('\n')
+                .map(|line| match line.strip_prefix("1 NAME ") {
+                    Some(name) => format!("1 NAME {}", name.to_uppercase()),
+                    None => line.to_string(),
+                })
+                .collect();
+            assert_eq!(data.to_gedcom_string(), expected);
+        }
+
+        let contents = read_relative("./tests/fixtures/vendor/ancestry.ged");
+        let options = ParseOptions {
+            lenient: true,
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let mut data = GedcomDocument::with_options(contents.chars(), options)
+            .parse_document()
+            .unwrap();
+        let john = data.source_map.as_ref().unwrap().record("@I1@").unwrap();
+        let unrecognized: Vec<&str> = john.unrecognized().collect();
+        assert_eq!(unrecognized.len(), 12);
+        assert_eq!(unrecognized[0], "2 FONE Jon Smit\n3 TYPE phonetic\n");
+        assert_eq!(unrecognized[10], "3 EVEN BIRT\n4 ROLE CHIL\n");
+
+        // structures go along with their parent
+        data.individuals[0].name = None;
+        let output = data.to_gedcom_string();
+        assert!(!output.contains("FONE"));
+        assert!(!output.contains("ROMN"));
+        assert!(output.contains("1 SEX M\n1 ALIA @I2@\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn inspects_files_from_the_command_line() {
+        use std::process::Command;
+
+        let run = |args: &[&str]| {
+            let output = Command::new(env!("CARGO_BIN_EXE_gedcom"))
+                .args(args)
+                .output()
+                .unwrap();
+            (
+                output.status.code(),
+                String::from_utf8(output.stdout).unwrap(),
+            )
+        };
+
+        let (status, stdout) = run(&["stats", "./tests/fixtures/simple.ged"]);
+        assert_eq!(status, Some(0));
+        assert!(stdout.contains("  individuals: 3\n"));
+
+        let (status, stdout) = run(&["query", "./tests/fixtures/simple.ged", "@MOTHER@"]);
+        assert_eq!(status, Some(0));
+        assert!(stdout.starts_with("0 @MOTHER@ INDI\n1 NAME /Mother/\n"));
+        assert!(!stdout.contains("@FATHER@ INDI"));
+
+        let (_, stdout) = run(&["grep", "./tests/fixtures/simple.ged", "Fathr"]);
+        assert_eq!(stdout, "@FATHER@\t0.83\t/Father/\n");
+
+        let (status, stdout) = run(&["validate", "./tests/fixtures/broken-links.ged"]);
+        assert_eq!(status, Some(1));
+        assert_eq!(stdout.lines().count(), 4);
+        let (status, _) = run(&["validate", "./tests/fixtures/simple.ged"]);
+        assert_eq!(status, Some(0));
+
+        let (status, stdout) = run(&["lint", "./tests/fixtures/lint.ged"]);
+        assert_eq!(status, Some(1));
+        assert!(stdout.starts_with("line 9: level 3 follows level 1 [level-jump]\n"));
+
+        let (_, stdout) = run(&["tags", "./tests/fixtures/simple.ged"]);
+        assert!(stdout.contains("INDI.BIRT.DATE: 3\n"));
+
+        let (_, stdout) = run(&["unused", "./tests/fixtures/sources.ged"]);
+        assert_eq!(stdout, "@S2@\n");
+
+        let (status, _) = run(&["query", "./tests/fixtures/simple.ged"]);
+        assert_eq!(status, Some(2));
+
+        // to-json is only built along with the json feature
+        let (status, _) = run(&["to-json", "./tests/fixtures/simple.ged"]);
+        if cfg!(feature = "json") {
+            assert_eq!(status, Some(0));
+        } else {
+            assert_eq!(status, Some(2));
+        }
+    }
+
+    #[test]
+    fn reads_lowercase_tags() {
+        let contents = read_relative("./tests/fixtures/lowercase-tags.ged");
+        assert!(GedcomDocument::new(contents.chars())
+            .parse_document()
+            .is_err());
+
+        let options = ParseOptions {
+            case_insensitive_tags: true,
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let mut doc = GedcomDocument::with_options(contents.chars(), options);
+        let mut data = doc.parse_document().unwrap();
+        assert_eq!(data.individuals.len(), 2);
+        assert_eq!(data.families[0].children, vec!["@I2@"]);
+        let john = &data.individuals[0];
+        assert_eq!(john.events[0].place.as_deref(), Some("Boston"));
+        assert_eq!(john.custom_data[0].tag, "_custom");
+
+        // a warning per tag
+        let warnings = doc.warnings();
+        assert_eq!(warnings.len(), 14);
+        assert_eq!(
+            warnings[0].to_string(),
+            "line 1, column 3: Tag head read as HEAD"
+        );
+        assert!(matches!(
+            &warnings[3],
+            GedcomError::MiscasedTag { tag, normalized: "CHAR", .. } if tag == "char"
+        ));
+
+        // untouched records are written back as they were
+        assert_eq!(data.to_gedcom_string(), contents);
+        data.individuals[0].sex = None;
+        let output = data.to_gedcom_string();
+        assert!(output.contains(
+            "0 @I1@ INDI\n1 NAME John /Doe/\n1 BIRT\n2 DATE 1 JAN 1900\n2 PLAC Boston\n\
+             1 _custom kept as written\n0 @I2@ INDI\n"
+        ));
+    }
+
+    #[test]
+    fn parses_concatenated_documents() {
+        let contents = read_relative("./tests/fixtures/concatenated.ged");
+        let documents = GedcomDocument::new(contents.chars())
+            .parse_all_documents()
+            .unwrap();
+        let systems: Vec<Option<&str>> = documents
+            .iter()
+            .map(|data| data.provenance().product)
+            .collect();
+        assert_eq!(
+            systems,
+            vec![Some("PAF"), Some("Legacy"), Some("RootsMagic")]
+        );
+        // the second document has no TRLR
+        assert_eq!(documents[1].individuals.len(), 2);
+        assert_eq!(documents[2].families.len(), 1);
+
+        // each document keeps its own lines
+        let options = ParseOptions {
+            preserve_lines: true,
+            ..ParseOptions::default()
+        };
+        let documents = GedcomDocument::with_options(contents.chars(), options)
+            .parse_all_documents()
+            .unwrap();
+        let written: String = documents.iter().map(|d| d.to_gedcom_string()).collect();
+        // with a TRLR written where it was missing
+        let tom_roe = "1 NAME Tom /Roe/\n";
+        let expected = contents.replacen(tom_roe, &format!("{tom_roe}0 TRLR\n"), 1);
+        assert_eq!(written, expected);
+        let tom = documents[1].source_map.as_ref().unwrap().record("@I2@");
+        assert_eq!(tom.unwrap().lines, 14..16);
+
+        assert_eq!(
+            GedcomDocument::new(contents.chars())
+                .parse_document()
+                .unwrap()
+                .individuals
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "log")]
+    fn logs_warnings() {
+        use log::{Level, Log, Metadata, Record};
+        use std::sync::Mutex;
+
+        struct Collect(Mutex<Vec<(Level, String)>>);
+        impl Log for Collect {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &Record) {
+                let message = (record.level(), record.args().to_string());
+                self.0.lock().unwrap().push(message);
+            }
+            fn flush(&self) {}
+        }
+        static LOGGER: Collect = Collect(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let sample = "0 HEAD\n1 GEDC\n2 VERS 5.5\n0 @I1@ INDI\n1 SEX Z\n0 TRLR";
+        let mut doc = GedcomDocument::with_options(sample.chars(), ParseOptions::lenient());
+        doc.parse_document().unwrap();
+
+        // other tests may log at the same time
+        let logged = LOGGER.0.lock().unwrap();
+        assert!(logged.contains(&(Level::Debug, "line 4: INDI record @I1@".to_string())));
+        assert!(logged.contains(&(
+            Level::Warn,
+            "line 5, column 7: Invalid SEX value: Z (in record @I1@)".to_string()
+        )));
+    }
+
+    #[test]
+    fn parses_lds_ordinances() {
+        let contents = read_relative("./tests/fixtures/lds.ged");
+        let data = GedcomDocument::new(contents.chars())
+            .parse_document()
+            .unwrap();
+        let indi = &data.individuals[0];
+
+        #[cfg(feature = "lds")]
+        {
+            use gedcom::types::OrdinanceType;
+
+            let ordinances: Vec<OrdinanceType> = indi
+                .lds_ordinances
+                .iter()
+                .map(|lds| lds.ordinance)
+                .collect();
+            assert_eq!(
+                ordinances,
+                vec![
+                    OrdinanceType::Baptism,
+                    OrdinanceType::Endowment,
+                    OrdinanceType::ChildSealing
+                ]
+            );
+            assert_eq!(indi.lds_ordinances[1].notes.len(), 1);
+            assert_eq!(indi.lds_ordinances[2].family.as_deref(), Some("@F1@"));
+            let sealing = &data.families[0].lds_ordinances[0];
+            assert_eq!(sealing.temple_code.as_deref(), Some("SGEOR"));
+            assert_eq!(data.submissions[0].temple_code.as_deref(), Some("SLAKE"));
+            assert!(indi.custom_data.is_empty());
+        }
+
+        // without the lds feature, ordinances are kept as custom data
+        #[cfg(not(feature = "lds"))]
+        {
+            let tags: Vec<&str> = indi.custom_data.iter().map(|c| c.tag.as_str()).collect();
+            assert_eq!(tags, vec!["BAPL", "ENDL", "SLGC"]);
+            assert_eq!(
+                indi.custom_data[0].child("TEMP").unwrap().value.as_deref(),
+                Some("SLAKE")
+            );
+            assert_eq!(data.families[0].custom_data[0].tag, "SLGS");
+            assert_eq!(data.submissions[0].custom_data.len(), 2);
+        }
+
+        // either way the ordinances are written back
+        let output = data.to_gedcom_string();
+        assert!(output.contains(
+            "1 BAPL\n2 DATE 3 MAY 1880\n2 TEMP SLAKE\n2 STAT COMPLETED\n3 DATE 1 JUN 1880\n"
+        ));
+        assert!(output.contains("1 SLGS\n2 DATE 12 DEC 1870\n2 TEMP SGEOR\n"));
+        assert!(output.contains("1 ORDI yes\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "gedzip")]
+    fn reads_and_writes_gedzip_packages() {
+        use gedcom::gedzip::{self, Gedzip};
+        use std::io::{Cursor, ErrorKind};
+
+        let mut package = Gedzip::open("./tests/fixtures/gedzip/sample.gdz").unwrap();
+        let data = package.parse().unwrap();
+        assert_eq!(data.individuals.len(), 1);
+        assert_eq!(package.media_files().count(), 1);
+
+        // URLs and absolute paths aren't part of the package
+        assert_eq!(
+            gedzip::media_references(&data),
+            vec![
+                "photos/John%20Smith.jpg",
+                "https://example.com/photos/john.jpg",
+                "C:\\Photos\\family.jpg",
+            ]
+        );
+        assert_eq!(
+            package.referenced_media(&data),
+            vec!["photos/John Smith.jpg"]
+        );
+        let broken: Vec<String> = package
+            .check_media(&data)
+            .into_iter()
+            .map(|media| media.file)
+            .collect();
+        assert_eq!(broken, vec!["C:\\Photos\\family.jpg"]);
+
+        let dir = std::env::temp_dir().join(format!("gedzip-test-{}", std::process::id()));
+        let extracted = package.extract_media(&data, &dir).unwrap();
+        assert_eq!(extracted, vec![dir.join("photos/John Smith.jpg")]);
+        let photo = std::fs::read(&extracted[0]).unwrap();
+        assert_eq!(photo, package.read_media("photos/John Smith.jpg").unwrap());
+
+        // round trip through a new package, taking the media from the extracted directory
+        let mut output = Cursor::new(Vec::new());
+        gedzip::write(&mut output, &data, &dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let mut package = Gedzip::new(output).unwrap();
+        assert_eq!(
+            package.parse().unwrap().to_gedcom_string(),
+            data.to_gedcom_string()
+        );
+        assert_eq!(package.read_media("photos/John Smith.jpg").unwrap(), photo);
+
+        let missing = package.read_media("photos/missing.jpg").unwrap_err();
+        assert_eq!(missing.kind(), ErrorKind::NotFound);
+        let missing =
+            gedzip::write(Cursor::new(Vec::new()), &data, "./tests/fixtures").unwrap_err();
+        assert_eq!(missing.kind(), ErrorKind::NotFound);
+        let invalid = Gedzip::new(Cursor::new(b"0 HEAD\n0 TRLR\n".to_vec()))
+            .err()
+            .unwrap();
+        assert_eq!(invalid.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reads_raw_lines() {
+        use gedcom::tokenizer::{GedcomLine, Tokenizer};
+
+        let simple_ged: String = read_relative("./tests/fixtures/simple.ged");
+        let mut tokenizer = Tokenizer::new(simple_ged.chars());
+        let lines: Vec<GedcomLine> = tokenizer.lines().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(
+            lines.len(),
+            simple_ged.lines().filter(|line| !line.is_empty()).count()
+        );
+        assert_eq!(lines.iter().filter(|line| line.level == 0).count(), 7);
+        assert_eq!(
+            lines[7],
+            GedcomLine {
+                level: 0,
+                xref: Some("@SUBMITTER@"),
+                tag: "SUBM",
+                value: None,
+                line: 8
+            }
+        );
+        assert_eq!(
+            (lines[10].tag, lines[10].value),
+            ("CONT", Some("address continued here"))
+        );
+        assert_eq!(lines.last().unwrap().tag, "TRLR");
+
+        let mut tokenizer = Tokenizer::new("0 HEAD\n1 @X@\n0 TRLR".chars());
+        let mut lines = tokenizer.lines();
+        assert_eq!(lines.next().unwrap().unwrap().tag, "HEAD");
+        assert!(lines.next().unwrap().is_err());
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "wasm")]
+    fn browses_tree_through_wasm_exports() {
+        use gedcom::wasm::{self, Tree};
+
+        let simple_ged: String = read_relative("./tests/fixtures/simple.ged");
+        let json: serde_json::Value =
+            serde_json::from_str(&wasm::parse(&simple_ged).unwrap()).unwrap();
+        assert_eq!(json["individuals"].as_array().unwrap().len(), 3);
+
+        let tree = Tree::new(&simple_ged).unwrap();
+        assert!(tree.warnings().is_empty());
+        let child: serde_json::Value =
+            serde_json::from_str(&tree.individual("@CHILD@").unwrap()).unwrap();
+        assert_eq!(child["xref"], "@CHILD@");
+        assert!(tree.individual("@NOBODY@").is_none());
+        assert!(tree.family("@FAMILY@").is_some());
+
+        let results: serde_json::Value = serde_json::from_str(&tree.search("Mother")).unwrap();
+        assert_eq!(results[0]["xref"], "@MOTHER@");
+        assert_eq!(tree.parents("@CHILD@"), vec!["@FATHER@", "@MOTHER@"]);
+        assert_eq!(tree.children("@MOTHER@"), vec!["@CHILD@"]);
+        assert_eq!(tree.spouses("@FATHER@"), vec!["@MOTHER@"]);
+
+        let broken = "0 HEAD\n1 GEDC\n2 VERS 5.5\n0 @I1@ INDI\n1\n0 TRLR";
+        assert_eq!(Tree::new(broken).unwrap().warnings().len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parses_washington_doc_in_parallel() {
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+
+        let sequential = GedcomDocument::new(washington_ged.chars())
+            .parse_document()
+            .unwrap();
+        let parallel = gedcom::parallel::parse_parallel(&washington_ged).unwrap();
+
+        assert!(parallel.header.is_some());
+        assert_eq!(parallel.individuals.len(), sequential.individuals.len());
+        assert_eq!(parallel.families.len(), sequential.families.len());
+        for (a, b) in parallel.individuals.iter().zip(sequential.individuals.iter()) {
+            assert_eq!(a.xref, b.xref);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn builds_washington_doc_indexes_in_background() {
+        let washington_ged: String = read_relative("./tests/fixtures/washington.ged");
+        let data = GedcomDocument::new(washington_ged.chars()).parse_document().unwrap();
+        let data = std::sync::Arc::new(data);
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+
+        let indexes = data.build_indexes_async(&pool).wait();
+        let expected = gedcom::search::Indexes::new(&*data);
+
+        assert_eq!(indexes.xrefs.len(), expected.xrefs.len());
+        assert_eq!(indexes.names.len(), data.individuals.len());
+        for place in expected.places.places() {
+            assert_eq!(indexes.places.get(place), expected.places.get(place));
+        }
+        let family = indexes.xrefs.get("@F1@").unwrap();
+        assert_eq!(data.families[family.index].xref.as_deref(), Some("@F1@"));
+    }
 }